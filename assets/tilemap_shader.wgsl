@@ -2,20 +2,86 @@
     mesh2d_bindings::mesh,
     mesh2d_functions::{get_world_from_local, mesh2d_position_local_to_clip, mesh2d_position_local_to_world},
 }
-#import mesh_view_bindings::globals;
+#import mesh_view_bindings::{globals, view};
 
+/// Input to the user provided `sample_tile` function.
+///
+/// This is part of the custom shader API: Fields are only ever added at the end,
+/// never removed or changed in meaning without a note in CHANGELOG.md.
+/// Fields marked with a shader def are only populated if that def is enabled
+/// (see `Customization::shader_defs`), otherwise they are zero.
 struct ExtractIn {
     /// tile_index: Index of the tile in the atlas 0-based, x-axis first
     tile_index: u32,
     /// 2d logical map position
     tile_position: vec2<i32>,
-    /// offset from the tile anchor point in pixel/world coordinates to render
+    /// Deprecated, same as `tile_offset_texels`
     tile_offset: vec2<f32>,
+    /// Animation state as passed in via the mesh (usually time in seconds),
+    /// scaled by `Map::set_animation_speed`
     animation_state: f32,
+    /// [EXTRACT_MAP_POSITION] Fractional map position of the fragment being rendered.
+    /// Note that for overhangs this is the position of the fragment, not of the sampled tile.
+    map_position: vec2<f32>,
+    /// [EXTRACT_MAP_POSITION_DERIVATIVES] Screen space derivatives of `map_position`,
+    /// eg. for selecting a level of detail.
+    map_position_dpdx: vec2<f32>,
+    map_position_dpdy: vec2<f32>,
+    /// [EXTRACT_MIX_COLOR] Mix color of the fragment (from `MapAttributes`).
+    /// The final color is still multiplied with this after `sample_tile`.
+    mix_color: vec4<f32>,
+    /// Offset from the tile anchor point in atlas pixels (texels), as taken by
+    /// `sample_tile_at`. Independent of the world tile size, the entity transform and the
+    /// projection, for pattern tiles it is relative to the logical tile (`map.tile_size`).
+    tile_offset_texels: vec2<f32>,
+    /// Position within the tile, (0, 0) is the top left and (1, 1) the bottom right corner
+    /// (in atlas orientation). Outside of 0..1 for overhangs.
+    tile_uv: vec2<f32>,
+    /// Flip and rotation flags of the tile (`tile_value & TILE_FLAGS_MASK`).
+    /// The transform is already applied to `tile_offset_texels` and `tile_uv`, and
+    /// `tile_index` does not hold any flag bits.
+    tile_flags: u32,
+    /// [TILE_COLORS] Color of the tile (see `MapBuilder::with_tile_color_channel`), eg. for
+    /// effects depending on fog of war. The result of `sample_tile` is still multiplied with it.
+    tile_color: vec4<f32>,
+    /// [ADDITIONAL_ATLASES] Atlas of the tile (0 is `atlas_texture`), see
+    /// `MapBuilder::with_additional_atlas`. `tile_index` is the index in that atlas,
+    /// `sample_tile_at` samples it, `tile_offset_texels` and `tile_uv` use its tile size.
+    atlas_index: u32,
 };
 
+/// Flags in the high bits of tile values, as used by Tiled.
+/// These are stable: they keep their bits and meaning in future versions.
+/// Mirror the consts of the same name in the `tile_flags` Rust module.
+const TILE_FLIP_X: u32 = 0x80000000u;
+const TILE_FLIP_Y: u32 = 0x40000000u;
+/// Transpose the tile (flip along its top left to bottom right diagonal), applied before the
+/// flips. Together with `TILE_FLIP_X` this is a clockwise rotation by 90 degrees.
+const TILE_ROTATE: u32 = 0x20000000u;
+const TILE_FLAGS_MASK: u32 = 0xe0000000u;
+const TILE_INDEX_MASK: u32 = 0x1fffffffu;
+/// Bits of tile values selecting the atlas of maps with additional atlases
+/// (`TILE_ATLAS_SHIFT` and `TILE_ATLAS_MASK` of the `additional_atlases` Rust module)
+const TILE_ATLAS_SHIFT: u32 = 27u;
+const TILE_ATLAS_MASK: u32 = 0x18000000u;
+/// Tile value of upper layers that draws nothing (`EMPTY_TILE` of the `tile_layers` Rust module)
+const EMPTY_TILE: u32 = 0xffffffffu;
+
 #[user_code]
 
+/// Tile layout of an additional atlas, see `MapBuilder::with_additional_atlas`
+struct AtlasLayout {
+    /// Size of the atlas, in pixels
+    atlas_size: vec2<f32>,
+    /// Size of each tile in the atlas, in pixels
+    tile_size: vec2<f32>,
+    inner_padding: vec2<f32>,
+    outer_padding_topleft: vec2<f32>,
+    outer_padding_bottomright: vec2<f32>,
+    /// [derived] Number of tiles in the atlas
+    n_tiles: vec2<u32>,
+};
+
 struct Map {
     /// Size of the map, in tiles.
     /// Will be derived from underlying map texture.
@@ -44,10 +110,9 @@ struct Map {
     /// fractional 2d map index -> relative local world pos
     projection: mat3x3<f32>,
 
-    /// Global transform of the entity holding the map as transformation matrix & offset.
-    /// This is currently redundant with mesh.model,
-    /// which should represent the same info as a 4x4 affine matrix, but we consider it a bit
-    /// more consistent in conjunction with the inverse below. May be removed in the future.
+    /// Transform last given to `Map::apply_transform`, as transformation matrix & offset.
+    /// This is shared by all entities using the map, use mesh.model for the transform of the
+    /// entity being rendered.
     global_transform_matrix: mat3x3<f32>,
     global_transform_translation: vec3<f32>,
 
@@ -68,6 +133,94 @@ struct Map {
     /// [derived] Inverse of global transform of the entity holding the map as transformation matrix & offset.
     global_inverse_transform_matrix: mat3x3<f32>,
     global_inverse_transform_translation: vec3<f32>,
+
+    /// Only map positions in [clip_min, clip_max] are rendered
+    clip_min: vec2<f32>,
+    clip_max: vec2<f32>,
+
+    /// Width (in map coordinates) of the fade-out at the clip rectangle edges
+    clip_feather: f32,
+
+    /// If non-zero, every tile of the map is `uniform_tile` and map_texture is not used
+    has_uniform_tile: u32,
+    uniform_tile: u32,
+
+    /// Number of tiles a tile graphic may extend upwards beyond its own cell (perspective overhangs)
+    overhang_levels: u32,
+
+    /// [derived] Map offset of the neighbor tile directly below
+    overhang_step: vec2<i32>,
+
+    /// Free parameters for custom shader code, see `Map::set_param`
+    custom_params: array<vec4<f32>, 4>,
+
+    /// Number of used entries in palette_keys and palette_colors, see `Map::set_palette`
+    n_palette_keys: u32,
+    n_palette_colors: u32,
+    /// Maximum (linear RGB) distance of a texel to a key color for it to be replaced
+    palette_tolerance: f32,
+    /// In owner mode, tile values hold the owner id in palette_owner_mask << palette_owner_shift
+    palette_owner_shift: u32,
+    palette_owner_mask: u32,
+    palette_keys: array<vec4<f32>, 8>,
+    palette_colors: array<vec4<f32>, 8>,
+
+    /// Color of fragments outside of the map (but inside of its bounding box)
+    outside_color: vec4<f32>,
+
+    /// Size of each tile in world units (local coordinates of the map entity).
+    /// All offsets in the shader are in atlas pixels (`tile_size`) regardless of this.
+    world_tile_size: vec2<f32>,
+
+    /// If non-zero, the map is shifted so its tile grid starts on a whole screen pixel
+    pixel_snap: u32,
+
+    /// Tile preview (`tile_preview`) position and size in tiles, disabled if the size is zero
+    preview_origin: vec2<i32>,
+    preview_size: vec2<u32>,
+    /// Opacity of the tile preview
+    preview_opacity: f32,
+
+    /// Width (in tiles) of the fade towards `edge_fade_color` at the map edges, per axis
+    edge_fade: vec2<f32>,
+    edge_fade_color: vec4<f32>,
+
+    /// Factor for the color of tiles in `emissive_tiles`
+    emissive_strength: f32,
+
+    /// 0: rectangular grid, 1: odd rows shifted by half a tile (pointy-top hexagons),
+    /// 2: odd columns shifted by half a tile (flat-top hexagons),
+    /// 3 and 4: the same with even rows or columns shifted
+    stagger: u32,
+
+    /// Tiles written before the last upload (max exclusive), tinted for debugging
+    dirty_region_min: vec2<u32>,
+    dirty_region_max: vec2<u32>,
+    dirty_region_highlight: f32,
+    dirty_region_color: vec4<f32>,
+    /// Non-zero if the map content repeats infinitely in both axes
+    repeat_content: u32,
+    /// Blend factor towards `secondary_atlas_texture` (with `SECONDARY_ATLAS`)
+    atlas_blend: f32,
+    /// Maximum relative brightness change and hue rotation (radians) of tiles in
+    /// `color_jitter_tiles` (with `COLOR_JITTER`)
+    color_jitter: f32,
+    color_jitter_hue: f32,
+    /// Factor for the final color, see `Map::set_color_filter`
+    color_filter: vec4<f32>,
+    /// Scale and offset of the animation state of the mesh, see `Map::set_animation_speed`
+    animation_speed: f32,
+    animation_offset: f32,
+    /// Number of tile layers (at least 1) and bitset of the hidden ones, see `MapBuilder::with_layers`
+    n_layers: u32,
+    hidden_layers: u32,
+    /// Number of atlases (at least 1) and the layouts of the additional ones (atlas 1 to 3),
+    /// see `MapBuilder::with_additional_atlas`
+    n_atlases: u32,
+    atlases: array<AtlasLayout, 3>,
+    /// If non-zero, the mesh covers exactly the map and fragments just outside of it (due to
+    /// rounding at the map edges) render the closest edge tile, see `MapBuilder::with_exact_bounds`
+    exact_bounds: u32,
 };
 
 @group(2) @binding(0)
@@ -85,13 +238,158 @@ var atlas_texture: texture_2d<f32>;
 @group(2) @binding(102)
 var atlas_sampler: sampler;
 
+struct Decal {
+    /// Center of the decal in map coordinates
+    map_position: vec2<f32>,
+    /// cos and sin of the rotation angle
+    rotation: vec2<f32>,
+    scale: f32,
+    atlas_index: u32,
+};
+
+@group(2) @binding(103)
+var<storage> decals: array<Decal>;
+
+/// Coarse grid of decals, see `Map::update_decals`:
+/// cell size (in tiles, 0 if there are no decals), grid width, grid height,
+/// then for each cell the start of its list in the decal indices (plus one end entry),
+/// then the decal indices.
+@group(2) @binding(104)
+var<storage> decal_grid: array<u32>;
+
+/// Bitset of tile indices excluded from dominance overhangs, see `MapBuilder::with_overhang_exclusions`
+@group(2) @binding(105)
+var<storage> overhang_exclusions: array<u32>;
+
+/// Tiles of the preview, see `Map::set_preview`
+@group(2) @binding(106)
+var<storage> tile_preview: array<u32>;
+
+/// Bitset of emissive tile indices, see `MapBuilder::with_emissive_tiles`
+@group(2) @binding(107)
+var<storage> emissive_tiles: array<u32>;
+
+#ifdef SECONDARY_ATLAS
+/// Atlas with the same layout as `atlas_texture`, see `MapBuilder::with_secondary_atlas`
+@group(2) @binding(108)
+var secondary_atlas_texture: texture_2d<f32>;
+
+@group(2) @binding(109)
+var secondary_atlas_sampler: sampler;
+#endif // SECONDARY_ATLAS
+
+/// Overlay patches, see `Map::add_patch`: number of patches, then for each patch its origin,
+/// size, start of its tiles in this buffer and opacity (f32 bits), then the tiles.
+@group(2) @binding(110)
+var<storage> patches: array<u32>;
+
+/// Bitset of tile indices with color jitter, see `MapBuilder::with_color_jitter`
+@group(2) @binding(111)
+var<storage> color_jitter_tiles: array<u32>;
+
+#ifdef OVERLAY_CANVAS
+/// Paintable layer covering the map, see `MapBuilder::with_overlay_canvas`
+@group(2) @binding(112)
+var overlay_canvas_texture: texture_2d<f32>;
+
+@group(2) @binding(113)
+var overlay_canvas_sampler: sampler;
+#endif // OVERLAY_CANVAS
+
+#ifdef TILE_COLORS
+/// Color per tile (linear RGBA8 as packed by `pack4x8unorm`) the tile color is multiplied with,
+/// see `MapBuilder::with_tile_color_channel`
+@group(2) @binding(114)
+var<storage> tile_colors: array<u32>;
+#endif // TILE_COLORS
+
+#ifdef TILE_ANIMATIONS
+/// Tile animations, see `MapBuilder::with_tile_animations`: number of animations, then for
+/// each animation (ordered by base index) its base index, start of its frames in this buffer,
+/// number of frames, fps (f32 bits) and mode (1 for ping-pong), then the frames.
+@group(2) @binding(115)
+var<storage> tile_animations: array<u32>;
+#endif // TILE_ANIMATIONS
+
+#ifdef TILE_LAYERS
+/// Tiles of the layers above layer 0 (which is `map_texture`), one layer after the other,
+/// `EMPTY_TILE` where nothing is drawn
+@group(2) @binding(116)
+var<storage> layer_tiles: array<u32>;
+#endif // TILE_LAYERS
+
+#ifdef ADDITIONAL_ATLASES
+/// Atlases 1 to 3, selected by the `TILE_ATLAS_MASK` bits of the tile values,
+/// see `MapBuilder::with_additional_atlas`
+@group(2) @binding(117)
+var additional_atlas_1_texture: texture_2d<f32>;
+
+@group(2) @binding(118)
+var additional_atlas_1_sampler: sampler;
+
+@group(2) @binding(119)
+var additional_atlas_2_texture: texture_2d<f32>;
+
+@group(2) @binding(120)
+var additional_atlas_2_sampler: sampler;
+
+@group(2) @binding(121)
+var additional_atlas_3_texture: texture_2d<f32>;
+
+@group(2) @binding(122)
+var additional_atlas_3_sampler: sampler;
+#endif // ADDITIONAL_ATLASES
+
+#ifdef TILE_OVERHANG_FLAGS
+/// Overhang directions per tile index (`OVERHANG_FLAGS_SET` plus one bit per direction,
+/// 0 for tiles following the overhang rules of the map), see `MapBuilder::with_tile_overhang_flags`
+@group(2) @binding(123)
+var<storage> tile_overhang_flags: array<u32>;
+
+const OVERHANG_FLAGS_SET: u32 = 256u;
+#endif // TILE_OVERHANG_FLAGS
+
+#ifdef MINIMAP
+/// Packed linear color per tile index, see `Map::minimap_builder`
+@group(2) @binding(124)
+var<storage> minimap_colors: array<u32>;
+#endif // MINIMAP
+
+#ifdef AUTOTILE_RULES
+/// Blob tile per neighbor mask (`AUTOTILE_TABLE_SIZE` entries), followed by the rule per terrain:
+/// The first tile with the layout in the bits above `AUTOTILE_LAYOUT_SHIFT` (0 for terrains
+/// without a rule), see `MapBuilder::with_autotile`
+@group(2) @binding(125)
+var<storage> autotile_rules: array<u32>;
+
+const AUTOTILE_TABLE_SIZE: u32 = 256u;
+const AUTOTILE_LAYOUT_SHIFT: u32 = 29u;
+const AUTOTILE_WANG_16: u32 = 1u;
+#endif // AUTOTILE_RULES
+
+/// Whether `get_tile_index` returns the preview tiles
+var<private> use_preview: bool = false;
+
+/// Fragment specific parts of `ExtractIn`, set once per fragment.
+var<private> fragment_extract: ExtractIn;
+
+/// Atlas `sample_tile_at` samples, the one of the tile passed to `sample_tile`
+var<private> current_atlas: u32 = 0u;
+
 
 struct Vertex {
     @builtin(instance_index) instance_index: u32,
     @location(0) position: vec3<f32>,
+    /// [MESH_MAP_POSITION] Only for meshes whose vertex positions are not map local,
+    /// otherwise the map position is derived from the vertex position
+    #ifdef MESH_MAP_POSITION
     @location(1) map_position: vec2<f32>,
+    #endif
     @location(2) mix_color: vec4<f32>,
     @location(3) animation_state: f32,
+    #ifdef MOTION_VECTORS
+    @location(4) previous_clip_position: vec4<f32>,
+    #endif
 };
 
 struct VertexOutput {
@@ -102,6 +400,20 @@ struct VertexOutput {
     @location(1) map_position: vec2<f32>,
     @location(2) mix_color: vec4<f32>,
     @location(3) animation_state: f32,
+    #ifdef MOTION_VECTORS
+    @location(4) clip_position: vec4<f32>,
+    @location(5) previous_clip_position: vec4<f32>,
+    #endif
+}
+
+/// Shift clip space `position` such that the map position (0, 0) lands on a pixel corner.
+/// All vertices are shifted by the same amount, so the map is only moved, not distorted.
+fn snap_to_pixel_grid(model: mat4x4<f32>, position: vec4<f32>) -> vec4<f32> {
+    var origin = mesh2d_position_local_to_clip(model, vec4<f32>(map.world_offset, 0.0, 1.0));
+    var half_viewport = view.viewport.zw * 0.5;
+    var pixel = origin.xy / origin.w * half_viewport;
+    var offset = (round(pixel) - pixel) / half_viewport;
+    return position + vec4<f32>(offset * position.w, 0.0, 0.0);
 }
 
 /// Custom vertex shader for passing along the UV coordinate
@@ -112,16 +424,33 @@ fn vertex(v: Vertex) -> VertexOutput {
     var model: mat4x4<f32> = get_world_from_local(v.instance_index);
 
     out.position = mesh2d_position_local_to_clip(model, vec4<f32>(v.position, 1.0));
+    if map.pixel_snap != 0u {
+        out.position = snap_to_pixel_grid(model, out.position);
+    }
     out.world_position = mesh2d_position_local_to_world(model, vec4<f32>(v.position, 1.0));
     out.mix_color = v.mix_color;
+    #ifdef MESH_MAP_POSITION
     out.map_position = v.map_position;
-    out.animation_state = v.animation_state;
+    #else
+    out.map_position = map.inverse_projection
+        * ((v.position.xy - map.world_offset) / map.world_tile_size);
+    #endif
+    out.animation_state = v.animation_state * map.animation_speed + map.animation_offset;
+    #ifdef MOTION_VECTORS
+    out.clip_position = out.position;
+    out.previous_clip_position = v.previous_clip_position;
+    #endif
     return out;
 }
 
 /// Position (world/pixel units) in tilemap atlas of the top left corner
 /// of the tile with the given index
 fn atlas_index_to_position(index: u32, tile_position: vec2<i32>) -> vec2<f32> {
+    // Fast path for single-tile atlases
+    if all(map.n_tiles == vec2<u32>(1u, 1u)) && map.atlas_tile_size_factor <= 1 {
+        return map.outer_padding_topleft;
+    }
+
     var index_f = f32(index);
     var index_y = floor(index_f / f32(map.n_tiles.x));
     var index_x = index_f - index_y * f32(map.n_tiles.x);
@@ -179,29 +508,207 @@ fn world_to_tile_offset(world_position: vec2<f32>, world_tile_base: vec2<f32>) -
     return vec2<f32>(1.0, -1.0) * (world_position - world_tile_base);
 }
 
+#ifdef PALETTE_SWAP
+/// Replace a color close to a key color by the corresponding palette color
+/// (in owner mode: by the color of `owner`), keeping its luminance.
+fn apply_palette(color: vec4<f32>, owner: u32) -> vec4<f32> {
+    var luma = vec3<f32>(0.2126, 0.7152, 0.0722);
+    for (var i = 0u; i < map.n_palette_keys; i++) {
+        var key = map.palette_keys[i];
+        if distance(color.rgb, key.rgb) > map.palette_tolerance {
+            continue;
+        }
+
+        var replacement_index = i;
+        #ifdef PALETTE_OWNER
+        replacement_index = owner;
+        #endif
+        if replacement_index >= map.n_palette_colors {
+            return color;
+        }
+        var replacement = map.palette_colors[replacement_index].rgb;
+        var scale = dot(color.rgb, luma) / max(dot(key.rgb, luma), 0.0001);
+        return vec4<f32>(min(replacement * scale, vec3<f32>(1.0)), color.a);
+    }
+    return color;
+}
+#endif // PALETTE_SWAP
+
+/// Position in the atlas tile that is shown at `uv` of a tile with the given flags
+fn flip_tile_uv(uv: vec2<f32>, flags: u32) -> vec2<f32> {
+    var r = uv;
+    if (flags & TILE_FLIP_Y) != 0u {
+        r.y = 1.0 - r.y;
+    }
+    if (flags & TILE_FLIP_X) != 0u {
+        r.x = 1.0 - r.x;
+    }
+    if (flags & TILE_ROTATE) != 0u {
+        r = r.yx;
+    }
+    return r;
+}
+
 /// Sample tile from the tile atlas
-/// tile_index: Index of the tile in the atlas
-/// tile_offset: Offset from tile anchor point in pixel/world coordinates
+/// tile_index: Tile value from the map (index of the tile in the atlas, plus owner in palette
+///   owner mode and flags)
+/// tile_offset: Offset from tile anchor point in atlas pixels
 fn _sample_tile(
     tile_index: u32,
     pos: MapPosition,
     animation_state: f32,
 ) -> vec4<f32> {
+    // Empty tiles (eg. of imported Tiled layers) draw nothing in any layer
+    if tile_index == EMPTY_TILE {
+        return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    }
 
-    var e: ExtractIn;
-    e.tile_index = tile_index;
+    var e: ExtractIn = fragment_extract;
+    var value = tile_index & TILE_INDEX_MASK;
+    e.tile_index = value;
+    #ifdef PALETTE_OWNER
+    e.tile_index = value & ((1u << map.palette_owner_shift) - 1u);
+    #endif
+    var tile_size = map.tile_size;
+    #ifdef ADDITIONAL_ATLASES
+    e.tile_index = e.tile_index & ~TILE_ATLAS_MASK;
+    e.atlas_index = (value & TILE_ATLAS_MASK) >> TILE_ATLAS_SHIFT;
+    if e.atlas_index > 0u && e.atlas_index < map.n_atlases {
+        tile_size = map.atlases[e.atlas_index - 1u].tile_size;
+    }
+    current_atlas = e.atlas_index;
+    #endif
+    #ifdef TILE_ANIMATIONS
+    e.tile_index = animate_tile(e.tile_index, animation_state);
+    #endif
+    e.tile_flags = tile_index & TILE_FLAGS_MASK;
     e.tile_position = pos.tile;
-    e.tile_offset = pos.offset;
+    e.tile_uv = flip_tile_uv(pos.offset / tile_size + map.tile_anchor_point, e.tile_flags);
+    e.tile_offset_texels = (e.tile_uv - map.tile_anchor_point) * tile_size;
+    e.tile_offset = e.tile_offset_texels;
     e.animation_state = animation_state;
+    #ifdef TILE_COLORS
+    e.tile_color = get_tile_color(pos.tile);
+    #endif
+
+    var color = sample_tile(e);
+    current_atlas = 0u;
+    #ifdef PALETTE_SWAP
+    color = apply_palette(color, (value >> map.palette_owner_shift) & map.palette_owner_mask);
+    #endif
+    #ifdef COLOR_JITTER
+    if has_color_jitter(e.tile_index) {
+        color = apply_color_jitter(color, pos.tile);
+    }
+    #endif
+    #ifdef EMISSIVE_TILES
+    if is_emissive(e.tile_index) {
+        color = vec4<f32>(color.rgb * map.emissive_strength, color.a);
+    }
+    #endif
+    #ifdef TILE_COLORS
+    color = color * e.tile_color;
+    #endif
+    return color;
+}
+
+#ifdef TILE_COLORS
+/// Color of the tile at `tile_position` (wrapped for repeating content), white outside the map
+fn get_tile_color(tile_position: vec2<i32>) -> vec4<f32> {
+    var p = tile_position;
+    if map.repeat_content != 0u {
+        p = wrap_tile(p);
+    }
+    if any(p < vec2<i32>(0)) || any(p >= vec2<i32>(map.map_size)) {
+        return vec4<f32>(1.0);
+    }
+    return unpack4x8unorm(tile_colors[p.y * i32(map.map_size.x) + p.x]);
+}
+#endif // TILE_COLORS
+
+#ifdef TILE_ANIMATIONS
+/// Atlas index of the current frame for tiles with atlas index `index` (without owner bits),
+/// `index` itself for tiles without an animation (same as `Map::animated_tile_index`)
+fn animate_tile(index: u32, animation_state: f32) -> u32 {
+    // Binary search for the animation with base `index`
+    var lo = 0u;
+    var hi = tile_animations[0];
+    while lo < hi {
+        let mid = (lo + hi) / 2u;
+        let header = 1u + mid * 5u;
+        let base = tile_animations[header];
+        if base == index {
+            let start = tile_animations[header + 1u];
+            let n = i32(max(tile_animations[header + 2u], 1u));
+            let fps = bitcast<f32>(tile_animations[header + 3u]);
+            let ping_pong = tile_animations[header + 4u] == 1u && n > 1;
+            let period = select(n, 2 * n - 2, ping_pong);
+            var k = i32(floor(animation_state * fps)) % period;
+            k = select(k, k + period, k < 0);
+            k = select(k, period - k, k >= n);
+            return tile_animations[start + u32(k)];
+        }
+        if base < index {
+            lo = mid + 1u;
+        } else {
+            hi = mid;
+        }
+    }
+    return index;
+}
+#endif // TILE_ANIMATIONS
+
+#ifdef EMISSIVE_TILES
+/// index: Atlas index (without owner bits)
+fn is_emissive(index: u32) -> bool {
+    var word = index / 32u;
+    return word < arrayLength(&emissive_tiles)
+        && (emissive_tiles[word] & (1u << (index % 32u))) != 0u;
+}
+#endif // EMISSIVE_TILES
+
+#ifdef COLOR_JITTER
+/// index: Atlas index (without owner bits)
+fn has_color_jitter(index: u32) -> bool {
+    var word = index / 32u;
+    return word < arrayLength(&color_jitter_tiles)
+        && (color_jitter_tiles[word] & (1u << (index % 32u))) != 0u;
+}
+
+/// Deterministic value in [-1, 1) for a tile, the same as on the CPU (see `Map::color_jitter_at`)
+fn color_jitter_hash(tile: vec2<i32>, seed: u32) -> f32 {
+    var h = (bitcast<u32>(tile.x) * 0x27d4eb2du) ^ (bitcast<u32>(tile.y) * 0x165667b1u) ^ seed;
+    h = (h ^ (h >> 15u)) * 0x2c1b3c6du;
+    h = (h ^ (h >> 12u)) * 0x297a2d39u;
+    h = h ^ (h >> 15u);
+    // Multiples of 2^-23, exact in f32
+    return f32(i32(h >> 8u) - 8388608) * (1.0 / 8388608.0);
+}
 
-    return sample_tile(e);
+fn apply_color_jitter(color: vec4<f32>, tile: vec2<i32>) -> vec4<f32> {
+    var rgb = color.rgb * (1.0 + map.color_jitter * color_jitter_hash(tile, 0x85ebca6bu));
+    if map.color_jitter_hue != 0.0 {
+        // Rotation around the gray axis
+        let angle = map.color_jitter_hue * color_jitter_hash(tile, 0x9e3779b9u);
+        let k = vec3<f32>(0.57735, 0.57735, 0.57735);
+        let c = cos(angle);
+        rgb = rgb * c + cross(k, rgb) * sin(angle) + k * dot(k, rgb) * (1.0 - c);
+    }
+    return vec4<f32>(max(rgb, vec3<f32>(0.0)), color.a);
 }
+#endif // COLOR_JITTER
 
 fn sample_tile_at(
     tile_index: u32,
     tile_position: vec2<i32>,
     tile_offset: vec2<f32>,
 ) -> vec4<f32> {
+    #ifdef ADDITIONAL_ATLASES
+    if current_atlas > 0u {
+        return sample_additional_atlas(current_atlas, tile_index, tile_offset);
+    }
+    #endif
+
     // Tile start position in the atlas
     var tile_start = atlas_index_to_position(tile_index, tile_position);
 
@@ -224,9 +731,53 @@ fn sample_tile_at(
         return vec4<f32>(0.0, 0.0, 0.0, 0.0);
     }
 
-    return textureSample(
-        atlas_texture, atlas_sampler, total_offset / map.atlas_size
+    return sample_atlas(total_offset / map.atlas_size);
+}
+
+#ifdef ADDITIONAL_ATLASES
+/// `sample_tile_at` for a tile of additional atlas `atlas` (1 to 3), which is drawn texel for
+/// texel around the tile anchor point. Atlases the map doesn't have render nothing.
+fn sample_additional_atlas(atlas: u32, tile_index: u32, tile_offset: vec2<f32>) -> vec4<f32> {
+    if atlas >= map.n_atlases {
+        return vec4<f32>(0.0);
+    }
+    let layout = map.atlases[atlas - 1u];
+    let n_tiles = max(layout.n_tiles, vec2<u32>(1u));
+    let index2d = vec2<f32>(f32(tile_index % n_tiles.x), f32(tile_index / n_tiles.x));
+    let tile_start = index2d * (layout.tile_size + layout.inner_padding)
+        + layout.outer_padding_topleft;
+
+    // As in `sample_tile_at`, at most half of the inner padding is rendered
+    let rect_offset = tile_offset + map.tile_anchor_point * layout.tile_size;
+    let max_overhang = layout.inner_padding / 2.0;
+    if any(rect_offset < -max_overhang) || any(rect_offset >= layout.tile_size + max_overhang) {
+        return vec4<f32>(0.0);
+    }
+
+    let uv = (tile_start + rect_offset) / layout.atlas_size;
+    switch atlas {
+        case 1u: {
+            return textureSample(additional_atlas_1_texture, additional_atlas_1_sampler, uv);
+        }
+        case 2u: {
+            return textureSample(additional_atlas_2_texture, additional_atlas_2_sampler, uv);
+        }
+        default: {
+            return textureSample(additional_atlas_3_texture, additional_atlas_3_sampler, uv);
+        }
+    }
+}
+#endif // ADDITIONAL_ATLASES
+
+/// Sample the atlas at `uv` (blended with the secondary atlas if there is one)
+fn sample_atlas(uv: vec2<f32>) -> vec4<f32> {
+    var color = textureSample(atlas_texture, atlas_sampler, uv);
+    #ifdef SECONDARY_ATLAS
+    color = mix(
+        color, textureSample(secondary_atlas_texture, secondary_atlas_sampler, uv), map.atlas_blend
     );
+    #endif
+    return color;
 }
 
 /// Same as sample_tile_at, but allow control of all the parameters
@@ -235,7 +786,7 @@ fn sample_tile_direct(
     tile_index: u32,
     /// Will be used for the correct offset for pattern tiles
     tile_position: vec2<i32>,
-    /// Offset from the tile anchor point in pixel/world coordinates to render
+    /// Offset from the tile anchor point in atlas pixels to render
     tile_offset: vec2<f32>,
     /// Tile anchor point. When in doubt, use map.tile_anchor_point.
     map_tile_anchor_point: vec2<f32>,
@@ -301,11 +852,71 @@ struct MapPosition {
 };
 
 
-///
+/// Tile shown at `map_position`, or of the tile preview there while rendering the preview
 fn get_tile_index(map_position: vec2<i32>) -> u32 {
+    #ifdef AUTOTILE_RULES
+    return autotile(map_position, get_stored_tile(map_position));
+    #else
+    return get_stored_tile(map_position);
+    #endif
+}
+
+/// Tile value at `map_position` as stored in the map (or the preview), before autotiling
+fn get_stored_tile(map_position_: vec2<i32>) -> u32 {
+    var map_position = map_position_;
+    if map.repeat_content != 0u {
+        map_position = wrap_tile(map_position);
+    }
+    if use_preview {
+        var p = map_position - map.preview_origin;
+        if all(p >= vec2<i32>(0)) && all(p < vec2<i32>(map.preview_size)) {
+            return tile_preview[p.y * i32(map.preview_size.x) + p.x];
+        }
+    }
+    if map.has_uniform_tile != 0u {
+        return map.uniform_tile;
+    }
     return map_texture[map_position.y * i32(map.map_size.x) + map_position.x];
 }
 
+#ifdef AUTOTILE_RULES
+/// Tile shown for the terrain `value` at `map_position`: The transition tile of the rule of
+/// the terrain matching its neighbors, see `MapBuilder::with_autotile`
+fn autotile(map_position: vec2<i32>, value: u32) -> u32 {
+    var terrain = value & TILE_INDEX_MASK;
+    if terrain >= arrayLength(&autotile_rules) - AUTOTILE_TABLE_SIZE {
+        return value;
+    }
+    var rule = autotile_rules[AUTOTILE_TABLE_SIZE + terrain];
+    var layout = rule >> AUTOTILE_LAYOUT_SHIFT;
+    if layout == 0u {
+        return value;
+    }
+
+    // One bit per neighbor of the same terrain, clockwise starting at y - 1.
+    // Neighbors outside of the map count as the same terrain.
+    var neighbors = array<vec2<i32>, 8>(
+        vec2<i32>(0, -1), vec2<i32>(1, -1), vec2<i32>(1, 0), vec2<i32>(1, 1),
+        vec2<i32>(0, 1), vec2<i32>(-1, 1), vec2<i32>(-1, 0), vec2<i32>(-1, -1),
+    );
+    var mask = 0u;
+    for (var i = 0u; i < 8u; i++) {
+        var neighbor = map_position + neighbors[i];
+        if !is_valid_tile(neighbor) || (get_stored_tile(neighbor) & TILE_INDEX_MASK) == terrain {
+            mask = mask | (1u << i);
+        }
+    }
+
+    var offset = autotile_rules[mask];
+    if layout == AUTOTILE_WANG_16 {
+        // Cardinal neighbors only
+        offset = (mask & 1u) | ((mask >> 1u) & 2u) | ((mask >> 2u) & 4u) | ((mask >> 3u) & 8u);
+    }
+    var first = rule & ((1u << AUTOTILE_LAYOUT_SHIFT) - 1u);
+    return (first + offset) | (value & TILE_FLAGS_MASK);
+}
+#endif // AUTOTILE_RULES
+
 fn get_tile_index_checked(map_position: vec2<i32>) -> u32 {
     if !is_valid_tile(map_position) {
         return 0u;
@@ -329,7 +940,16 @@ fn blend(c0: vec4<f32>, c1: vec4<f32>) -> vec4<f32> {
     return r;
 }
 
+/// Position of `tile` in the map content for repeated content, see `MapBuilder::with_repeat_content`
+fn wrap_tile(tile: vec2<i32>) -> vec2<i32> {
+    var size = vec2<i32>(map.map_size);
+    return ((tile % size) + size) % size;
+}
+
 fn is_valid_tile(tile: vec2<i32>) -> bool {
+    if map.repeat_content != 0u {
+        return true;
+    }
     if tile.x < 0 || tile.y < 0 {
         return false;
     }
@@ -369,12 +989,110 @@ fn sample_neighbor(pos: MapPosition, tile_offset: vec2<i32>, animation_state: f3
 
     // kind of tile being displayed at that position
     var tile_index = get_tile_index(tile);
+    #ifdef TILE_OVERHANG_FLAGS
+    if !tile_overhangs(tile_index, -tile_offset, true) {
+        return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    }
+    #endif
     return sample_neighbor_tile_index(tile_index, pos, tile_offset, animation_state);
 }
 
+#ifdef TILE_OVERHANG_FLAGS
+/// Whether the tile with value `tile_value` overhangs into the tile at `offset` from it,
+/// `fallback` (the overhang rules of the map) for tiles without overhang flags
+fn tile_overhangs(tile_value: u32, offset: vec2<i32>, fallback: bool) -> bool {
+    var index = tile_value & TILE_INDEX_MASK;
+    #ifdef PALETTE_OWNER
+    index = index & ((1u << map.palette_owner_shift) - 1u);
+    #endif
+    #ifdef ADDITIONAL_ATLASES
+    index = index & ~TILE_ATLAS_MASK;
+    #endif
+    if index >= arrayLength(&tile_overhang_flags) {
+        return fallback;
+    }
+    var entry = tile_overhang_flags[index];
+    if (entry & OVERHANG_FLAGS_SET) == 0u {
+        return fallback;
+    }
+    // Bit of the direction, numbered row by row without the center
+    var d = clamp(offset, vec2<i32>(-1), vec2<i32>(1)) + vec2<i32>(1);
+    var bit = u32(d.y * 3 + d.x);
+    if bit == 4u {
+        return true;
+    }
+    bit = select(bit, bit - 1u, bit > 4u);
+    return (entry & (1u << bit)) != 0u;
+}
+#endif // TILE_OVERHANG_FLAGS
+
+#ifdef OVERHANG_EXCLUSIONS
+/// tile_value: Tile value from the map (in palette owner mode including the owner)
+fn is_overhang_excluded(tile_value: u32) -> bool {
+    var index = tile_value & TILE_INDEX_MASK;
+    #ifdef PALETTE_OWNER
+    index = index & ((1u << map.palette_owner_shift) - 1u);
+    #endif
+    #ifdef ADDITIONAL_ATLASES
+    index = index & ~TILE_ATLAS_MASK;
+    #endif
+    var word = index / 32u;
+    return word < arrayLength(&overhang_exclusions)
+        && (overhang_exclusions[word] & (1u << (index % 32u))) != 0u;
+}
+#endif // OVERHANG_EXCLUSIONS
+
+#ifdef DOMINANCE_SAME_DEPTH
+/// Whether the neighbor at `offset` is at a different depth, ie. one of the underhang
+/// directions or their opposites (the overhang directions)
+fn is_perspective_neighbor(offset: vec2<i32>) -> bool {
+    var d = offset;
+    // Underhang directions are the ones with a negative x, or zero x and a negative y
+    if d.x > 0 || (d.x == 0 && d.y > 0) {
+        d = -d;
+    }
+    var r = false;
+    #ifdef PERSPECTIVE_UNDER_NN
+    r = r || all(d == vec2<i32>(-1, -1));
+    #endif
+    #ifdef PERSPECTIVE_UNDER_PP
+    r = r || all(d == vec2<i32>(-1, -1));
+    #endif
+    #ifdef PERSPECTIVE_UNDER_NP
+    r = r || all(d == vec2<i32>(-1, 1));
+    #endif
+    #ifdef PERSPECTIVE_UNDER_PN
+    r = r || all(d == vec2<i32>(-1, 1));
+    #endif
+    #ifdef PERSPECTIVE_UNDER_NZ
+    r = r || all(d == vec2<i32>(-1, 0));
+    #endif
+    #ifdef PERSPECTIVE_UNDER_PZ
+    r = r || all(d == vec2<i32>(-1, 0));
+    #endif
+    #ifdef PERSPECTIVE_UNDER_ZN
+    r = r || all(d == vec2<i32>(0, -1));
+    #endif
+    #ifdef PERSPECTIVE_UNDER_ZP
+    r = r || all(d == vec2<i32>(0, -1));
+    #endif
+    return r;
+}
+#endif // DOMINANCE_SAME_DEPTH
+
+/// Overhangs from neighbors with higher tile indices.
+/// With `DOMINANCE_SAME_DEPTH` (dominance combined with perspective overhangs), only neighbors
+/// at the same depth are considered, the others are left to the perspective overhangs.
 fn render_dominance_overhangs(color: vec4<f32>, index: u32, pos: MapPosition, animation_state: f32) -> vec4<f32> {
     var c = color;
 
+    #ifdef OVERHANG_EXCLUSIONS
+    // Excluded tiles are not overhung by their neighbors
+    if is_overhang_excluded(index) {
+        return c;
+    }
+    #endif
+
     // We want to render overhangs from all the neighbors where the tile index is greater than the
     // current tile index. More so we want to render them in order of tile index (from lowest to
     // highest) to ensure that the overhangs are rendered in the correct order.
@@ -404,7 +1122,7 @@ fn render_dominance_overhangs(color: vec4<f32>, index: u32, pos: MapPosition, an
     // Then, sort the neighbors by index
     for (var i = 0u; i < 8u; i = i + 1u) {
         for (var j = i + 1u; j < 8u; j = j + 1u) {
-            if neighbors[i] > neighbors[j] {
+            if (neighbors[i] & TILE_INDEX_MASK) > (neighbors[j] & TILE_INDEX_MASK) {
                 var tmp = neighbors[i];
                 neighbors[i] = neighbors[j];
                 neighbors[j] = tmp;
@@ -418,7 +1136,22 @@ fn render_dominance_overhangs(color: vec4<f32>, index: u32, pos: MapPosition, an
 
     // Finally, render the overhangs in order of index
     for (var i = 0u; i < 8u; i = i + 1u) {
-        if neighbors[i] > index {
+        // Flags don't change the order of tiles
+        var overhangs = (neighbors[i] & TILE_INDEX_MASK) > (index & TILE_INDEX_MASK);
+        #ifdef OVERHANG_EXCLUSIONS
+        // Excluded tiles don't overhang, so their padding in the atlas is never sampled
+        overhangs = overhangs && !is_overhang_excluded(neighbors[i]);
+        #endif
+        #ifdef TILE_OVERHANG_FLAGS
+        // Tiles with overhang flags overhang in their directions regardless of their index
+        // (tiles outside of the map are 0 here, they must not overhang)
+        overhangs = tile_overhangs(neighbors[i], -neighbor_offsets[i], overhangs)
+            && is_valid_tile(pos.tile + neighbor_offsets[i]);
+        #endif
+        #ifdef DOMINANCE_SAME_DEPTH
+        overhangs = overhangs && !is_perspective_neighbor(neighbor_offsets[i]);
+        #endif
+        if overhangs {
             c = blend(c, sample_neighbor_tile_index(neighbors[i], pos, neighbor_offsets[i], animation_state));
         }
     }
@@ -508,6 +1241,11 @@ fn render_perspective_overhangs(color: vec4<f32>, pos: MapPosition, animation_st
         c = blend(c, sample_neighbor(pos, vec2<i32>( -1, -1), animation_state));
     #endif
 
+    // Tall tiles further below, that reach up to this position
+    for (var k = 2u; k <= map.overhang_levels; k = k + 1u) {
+        c = blend(c, sample_neighbor(pos, map.overhang_step * i32(k), animation_state));
+    }
+
     return c;
 }
 
@@ -520,17 +1258,318 @@ fn desaturate(color: vec4<f32>, amount: f32) -> vec4<f32> {
 }
 
 
+/// Fade `color` towards `map.edge_fade_color` close to the map edges.
+/// Distances are measured in map coordinates, so under projections the fade follows the
+/// projected map edges.
+fn render_edge_fade(color: vec4<f32>, map_position: vec2<f32>) -> vec4<f32> {
+    if all(map.edge_fade <= vec2<f32>(0.0, 0.0)) || map.repeat_content != 0u {
+        return color;
+    }
+    var distance = min(map_position, vec2<f32>(map.map_size) - map_position);
+    if any(distance < vec2<f32>(0.0, 0.0)) {
+        // Outside of the map
+        return color;
+    }
+    // Axes without fade are fully opaque
+    var fade = select(
+        vec2<f32>(1.0, 1.0),
+        clamp(distance / map.edge_fade, vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 1.0)),
+        map.edge_fade > vec2<f32>(0.0, 0.0)
+    );
+    return mix(map.edge_fade_color, color, min(fade.x, fade.y));
+}
+
+/// Blend all decals covering `map_position` on top of `color`
+fn render_decals(color: vec4<f32>, map_position: vec2<f32>) -> vec4<f32> {
+    var cell_size = decal_grid[0];
+    if cell_size == 0u {
+        return color;
+    }
+
+    var grid_size = vec2<i32>(i32(decal_grid[1]), i32(decal_grid[2]));
+    var cell = vec2<i32>(floor(map_position / f32(cell_size)));
+    if any(cell < vec2<i32>(0)) || any(cell >= grid_size) {
+        return color;
+    }
+
+    var cell_index = u32(cell.y * grid_size.x + cell.x);
+    var indices_start = 4u + u32(grid_size.x * grid_size.y);
+    var result = color;
+    var grid = map_to_grid(map_position);
+
+    for (var i = decal_grid[3u + cell_index]; i < decal_grid[4u + cell_index]; i++) {
+        var decal = decals[decal_grid[indices_start + i]];
+
+        // Offset from the decal center in local world coordinates
+        var offset = (
+            map.projection * vec3<f32>(grid - map_to_grid(decal.map_position), 0.0)
+        ).xy * map.tile_size;
+
+        // Undo rotation and scale
+        var c = decal.rotation.x;
+        var s = decal.rotation.y;
+        var unrotated = vec2<f32>(c * offset.x + s * offset.y, -s * offset.x + c * offset.y)
+            / decal.scale;
+
+        // Atlas y points downwards
+        var texel = vec2<f32>(unrotated.x, -unrotated.y) + map.tile_size / 2.0;
+        if any(texel < vec2<f32>(0.0)) || any(texel >= map.tile_size) {
+            continue;
+        }
+
+        var tile_start = atlas_index_to_position(decal.atlas_index, vec2<i32>(0, 0));
+        var uv = (tile_start + texel) / map.atlas_size;
+        var decal_color = textureSampleLevel(atlas_texture, atlas_sampler, uv, 0.0);
+        #ifdef SECONDARY_ATLAS
+        decal_color = mix(
+            decal_color,
+            textureSampleLevel(secondary_atlas_texture, secondary_atlas_sampler, uv, 0.0),
+            map.atlas_blend
+        );
+        #endif
+        result = blend(result, decal_color);
+    }
+
+    return result;
+}
+
+#ifdef OVERLAY_CANVAS
+/// Blend the overlay canvas at `map_position` on top of `color`, keeping its alpha
+fn render_overlay_canvas(color: vec4<f32>, map_position: vec2<f32>) -> vec4<f32> {
+    var map_size = vec2<f32>(map.map_size);
+    if any(map_position < vec2<f32>(0.0)) || any(map_position >= map_size) {
+        return color;
+    }
+    var overlay = textureSampleLevel(
+        overlay_canvas_texture, overlay_canvas_sampler, map_position / map_size, 0.0
+    );
+    return vec4<f32>(mix(color.rgb, overlay.rgb, overlay.a), color.a);
+}
+#endif // OVERLAY_CANVAS
+
+/// Blend the tiles of all patches covering `pos` on top of `color`, in insertion order
+fn render_patches(color: vec4<f32>, pos: MapPosition, animation_state: f32) -> vec4<f32> {
+    var result = color;
+    var n = patches[0];
+    for (var i = 0u; i < n; i++) {
+        var header = 1u + i * 6u;
+        var origin = vec2<i32>(i32(patches[header]), i32(patches[header + 1u]));
+        var size = vec2<i32>(i32(patches[header + 2u]), i32(patches[header + 3u]));
+        var p = pos.tile - origin;
+        if any(p < vec2<i32>(0)) || any(p >= size) {
+            continue;
+        }
+        var tile = patches[patches[header + 4u] + u32(p.y * size.x + p.x)];
+        var c = _sample_tile(tile, pos, animation_state);
+        c.a = c.a * bitcast<f32>(patches[header + 5u]);
+        result = blend(result, c);
+    }
+    return result;
+}
+
+/// Tiles (including overhangs) at `pos`, without decals and clipping
+fn render_tiles(pos: MapPosition, animation_state: f32) -> vec4<f32> {
+    var color = vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    var is_valid = is_valid_tile(pos.tile);
+    // for invalid tile, assume low index so (almost) everything overlaps in dominance rendering
+    var index = 0u;
+    var sample_color = map.outside_color;
+
+    if is_valid {
+        index = get_tile_index(pos.tile);
+        sample_color = _sample_tile(index, pos, animation_state);
+    }
+
+    #ifdef PERSPECTIVE_UNDERHANGS
+    if sample_color.a < 1.0 {
+        color = render_perspective_underhangs(color, pos, animation_state);
+    }
+    #endif // PERSPECTIVE_UNDERHANGS
+
+    color = blend(color, sample_color);
+
+    #ifdef DOMINANCE_OVERHANGS
+        color = render_dominance_overhangs(color, index, pos, animation_state);
+    #endif
+
+    #ifdef PERSPECTIVE_OVERHANGS
+        color = render_perspective_overhangs(color, pos, animation_state);
+    #endif
+
+    return color;
+}
+
+#ifdef TILE_LAYERS
+fn is_layer_visible(layer: u32) -> bool {
+    return (map.hidden_layers & (1u << layer)) == 0u;
+}
+
+/// Blend the tiles of the visible layers above layer 0 at `pos` on top of `color` (the tiles of
+/// layer 0), in order. Upper layers have no overhangs.
+fn render_layers(color: vec4<f32>, pos: MapPosition, animation_state: f32) -> vec4<f32> {
+    var result = color;
+    if !is_layer_visible(0u) {
+        result = vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    }
+    if !is_valid_tile(pos.tile) {
+        return result;
+    }
+    var p = pos.tile;
+    if map.repeat_content != 0u {
+        p = wrap_tile(p);
+    }
+    var n = i32(map.map_size.x * map.map_size.y);
+    var i = p.y * i32(map.map_size.x) + p.x;
+    for (var layer = 1u; layer < map.n_layers; layer++) {
+        var tile = layer_tiles[i32(layer - 1u) * n + i];
+        if !is_layer_visible(layer) || tile == EMPTY_TILE {
+            continue;
+        }
+        result = blend(result, _sample_tile(tile, pos, animation_state));
+    }
+    return result;
+}
+#endif // TILE_LAYERS
+
+/// Whether tiles of the preview may be visible at `tile` (directly or by overhangs)
+fn is_near_preview(tile: vec2<i32>) -> bool {
+    if map.preview_size.x == 0u {
+        return false;
+    }
+    var margin = vec2<i32>(i32(max(map.overhang_levels, 1u)));
+    var p = tile - map.preview_origin;
+    return all(p >= -margin) && all(p < vec2<i32>(map.preview_size) + margin);
+}
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    #ifdef MOTION_VECTORS
+    /// Like bevy's motion vector prepass: Screen space (uv) motion since the last frame
+    @location(1) motion_vector: vec2<f32>,
+    #endif
+}
+
+/// Tile of the hexagon containing `grid` (a position in the unstaggered grid of tile rectangles),
+/// ie. the one with the closest center, and the offset in it.
+fn staggered_map_position(grid: vec2<f32>) -> MapPosition {
+    // Staggered columns are computed as staggered rows with swapped axes
+    var columns = map.stagger == 2u || map.stagger == 4u;
+    // Odd (1, 2) or even (3, 4) rows or columns are shifted
+    var parity = select(0, 1, map.stagger >= 3u);
+    var g = grid;
+    if columns {
+        g = grid.yx;
+    }
+
+    var closest = 1e30;
+    var tile = vec2<f32>(0.0, 0.0);
+    var center = vec2<f32>(0.0, 0.0);
+    for (var d = -1; d <= 1; d++) {
+        var row = floor(g.y) + f32(d);
+        var shift = 0.5 * f32(abs(i32(row) + parity) % 2);
+        var t = vec2<f32>(floor(g.x - shift), row);
+        var c = t + vec2<f32>(0.5 + shift, 0.5);
+        // Weighted such that all neighbors are equally far
+        var delta = (g - c) * vec2<f32>(1.0, 0.8660254);
+        var distance = dot(delta, delta);
+        if distance < closest {
+            closest = distance;
+            tile = t;
+            center = c;
+        }
+    }
+    if columns {
+        tile = tile.yx;
+        center = center.yx;
+    }
+
+    var offset = (map.projection * vec3<f32>(grid - center, 0.0)).xy;
+    var pos: MapPosition;
+    pos.tile = vec2<i32>(tile);
+    pos.offset = (vec2<f32>(0.5, 0.5) + vec2<f32>(1.0, -1.0) * offset - map.tile_anchor_point)
+        * map.tile_size;
+    return pos;
+}
+
+/// Position in the unstaggered grid of tile rectangles of `map_position`,
+/// the inverse of `staggered_map_position()` (see `MapUniform::map_to_grid`).
+fn map_to_grid(map_position: vec2<f32>) -> vec2<f32> {
+    if map.stagger == 0u {
+        return map_position;
+    }
+    var columns = map.stagger == 2u || map.stagger == 4u;
+    var parity = select(0, 1, map.stagger >= 3u);
+    var tile = floor(map_position);
+    var offset = map_position - tile - vec2<f32>(0.5, 0.5);
+    var center = tile + vec2<f32>(0.5, 0.5);
+    if columns {
+        center.y += 0.5 * f32(abs(i32(tile.x) + parity) % 2);
+    } else {
+        center.x += 0.5 * f32(abs(i32(tile.y) + parity) % 2);
+    }
+    return center + map.inverse_projection * vec2<f32>(offset.x, -offset.y);
+}
+
+#ifdef MOTION_VECTORS
+fn motion_vector(in: VertexOutput) -> vec2<f32> {
+    // Unknown previous position
+    if in.previous_clip_position.w == 0.0 {
+        return vec2<f32>(0.0, 0.0);
+    }
+    var current = in.clip_position.xy / in.clip_position.w;
+    var previous = in.previous_clip_position.xy / in.previous_clip_position.w;
+    return (current - previous) * vec2<f32>(0.5, -0.5);
+}
+#endif
+
+#ifdef MINIMAP
+/// Color of the tile at `tile` of a minimap, one color per tile index
+fn render_minimap(tile: vec2<i32>) -> vec4<f32> {
+    if !is_valid_tile(tile) {
+        return map.outside_color;
+    }
+    // Tiles of additional atlases use the colors of the same index
+    var index = get_tile_index(tile) & TILE_INDEX_MASK & ~TILE_ATLAS_MASK;
+    if map.palette_owner_mask != 0u {
+        index = index & ((1u << map.palette_owner_shift) - 1u);
+    }
+    if index >= arrayLength(&minimap_colors) {
+        return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    }
+    return unpack4x8unorm(minimap_colors[index]);
+}
+#endif // MINIMAP
+
 @fragment
 fn fragment(
     in: VertexOutput
-) -> @location(0) vec4<f32> {
+) -> FragmentOutput {
     var world_position = in.world_position.xy;
     var color = vec4<f32>(0.0, 0.0, 0.0, 0.0);
 
+    #ifdef EXTRACT_MAP_POSITION
+    fragment_extract.map_position = in.map_position;
+    #endif
+    #ifdef EXTRACT_MAP_POSITION_DERIVATIVES
+    fragment_extract.map_position_dpdx = dpdx(in.map_position);
+    fragment_extract.map_position_dpdy = dpdy(in.map_position);
+    #endif
+    #ifdef EXTRACT_MIX_COLOR
+    fragment_extract.mix_color = in.mix_color;
+    #endif
+
     var tile = floor(in.map_position);
     var map_space_offset = in.map_position - tile;
+    if map.exact_bounds != 0u && map.stagger == 0u {
+        // Rounding at the map edges must not show the outside color between adjacent maps
+        var clamped = clamp(tile, vec2<f32>(0.0), vec2<f32>(map.map_size) - 1.0);
+        map_space_offset = clamp(in.map_position - clamped, vec2<f32>(0.0), vec2<f32>(1.0));
+        tile = clamped;
+    }
 
-    var world_space_offset = map.global_transform_matrix * (
+    // Offset in the (unscaled, unrotated) map, independent of the entity transform,
+    // so entities sharing a map render the same tiles.
+    var world_space_offset = (
         map.projection * vec3<f32>(map_space_offset, 0.0)
     ) * vec3<f32>(map.tile_size, 1.0);
 
@@ -538,37 +1577,64 @@ fn fragment(
     pos.tile = vec2<i32>(tile);
     pos.offset = vec2<f32>(1.0, -1.0) * world_space_offset.xy;
 
-    var index = get_tile_index(pos.tile);
-    var is_valid = is_valid_tile(pos.tile);
-    var sample_color = color;
-
-    if is_valid {
-        sample_color = _sample_tile(index, pos, in.animation_state);
-    }
-    else {
-        // for invalid tile, assume low index so (almost) everything overlaps in dominance rendering
-        index = 0u;
+    // The mesh interpolates positions in the unstaggered grid
+    var map_position = in.map_position;
+    if map.stagger != 0u {
+        pos = staggered_map_position(in.map_position);
+        map_position = vec2<f32>(pos.tile) + pos.offset / map.tile_size + map.tile_anchor_point;
+        #ifdef EXTRACT_MAP_POSITION
+        fragment_extract.map_position = map_position;
+        #endif
     }
 
-    #ifdef PERSPECTIVE_UNDERHANGS
-    if sample_color.a < 1.0 {
-        color = render_perspective_underhangs(color, pos, in.animation_state);
-    }
-    #endif // PERSPECTIVE_UNDERHANGS
+    #ifdef MINIMAP
+    color = render_minimap(pos.tile);
+    #else
+    color = render_tiles(pos, in.animation_state);
 
-    if is_valid {
-        color = blend(color, sample_color);
+    // Render again with the preview tiles in place (so overhangs match) and fade between both
+    if is_near_preview(pos.tile) {
+        use_preview = true;
+        color = mix(color, render_tiles(pos, in.animation_state), map.preview_opacity);
+        use_preview = false;
     }
+    #endif // MINIMAP
 
-    #ifdef DOMINANCE_OVERHANGS
-        color = render_dominance_overhangs(color, index, pos, in.animation_state);
+    #ifdef TILE_LAYERS
+    color = render_layers(color, pos, in.animation_state);
     #endif
 
-    #ifdef PERSPECTIVE_OVERHANGS
-        color = render_perspective_overhangs(color, pos, in.animation_state);
+    color = render_patches(color, pos, in.animation_state);
+    #ifdef OVERLAY_CANVAS
+    color = render_overlay_canvas(color, map_position);
     #endif
+    color = render_decals(color, map_position);
+    color = render_edge_fade(color, map_position);
+
+    // Debug tint of the tiles written for the last upload (see `FastTileMapDebug`)
+    if map.dirty_region_highlight > 0.0
+        && all(pos.tile >= vec2<i32>(map.dirty_region_min))
+        && all(pos.tile < vec2<i32>(map.dirty_region_max)) {
+        var tint = vec4<f32>(map.dirty_region_color.rgb, 1.0);
+        color = mix(color, tint, map.dirty_region_highlight * map.dirty_region_color.a);
+    }
 
-    color = color * in.mix_color;
+    // Clip rectangle, distance to the closest edge (negative outside)
+    var clip_distance = min(map_position - map.clip_min, map.clip_max - map_position);
+    var d = min(clip_distance.x, clip_distance.y);
+    if d < 0.0 {
+        color = vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    }
+    else if map.clip_feather > 0.0 {
+        color.a = color.a * clamp(d / map.clip_feather, 0.0, 1.0);
+    }
 
-    return color;
+    color = color * in.mix_color * map.color_filter;
+
+    var out: FragmentOutput;
+    out.color = color;
+    #ifdef MOTION_VECTORS
+    out.motion_vector = motion_vector(in);
+    #endif
+    return out;
 }
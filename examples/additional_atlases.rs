@@ -0,0 +1,50 @@
+//! A map drawing its tiles from two atlases: Terrain from `pixel_tiles_16.png` and a few
+//! markers from the generated debug atlas, selected per tile with `tile_in_atlas`.
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let (markers, marker_size) = debug_atlas(&mut images);
+    let map = Map::builder(
+        uvec2(64, 64),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .with_additional_atlas(markers, marker_size, Vec2::ZERO)
+    .build_and_set(|p| {
+        if p.x % 8 == 0 && p.y % 8 == 0 {
+            // Atlas 1 is the first additional atlas
+            tile_in_atlas(1, (p.x + p.y) / 8)
+        } else {
+            (p.x + p.y) % 4 + 1
+        }
+    });
+
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+}
@@ -3,8 +3,10 @@
 //! This has the following implications:
 //! - The map is not re-uploaded to the GPU (faster for very big maps)
 //! - The bevy-side map does not reflect the current animation state (which may or may not be
-//!     desired, depending on your application). This could be worked around by computing
-//!     the animation state again on CPU when needed.
+//!   desired, depending on your application). This could be worked around by computing
+//!   the animation state again on CPU when needed.
+//! For simply cycling through atlas tiles, the built-in tile animations need no shader code,
+//! see tile_animations.rs.
 
 use bevy::{
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
@@ -23,6 +25,7 @@ struct AnimationCustomization;
 impl Customization for AnimationCustomization {
     const SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x1d1e1e1e1e1e1e1e);
     type UserData = DefaultUserData;
+    type ExtraBindings = NoExtraBindings;
     fn custom_shader_code() -> String {
         r#"
             // wgpu doesnt like this being empty, so the default is to have a dummy u32
@@ -42,7 +45,7 @@ impl Customization for AnimationCustomization {
                     tile_index = 6u + offs;
                 }
 
-                return sample_tile_at(tile_index, in.tile_position, in.tile_offset);
+                return sample_tile_at(tile_index, in.tile_position, in.tile_offset_texels);
             }
         "#.to_string()
     }
@@ -62,8 +65,8 @@ fn main() {
                 ..default()
             }),
             LogDiagnosticsPlugin::default(),
-            FrameTimeDiagnosticsPlugin::default(),
-            MouseControlsCameraPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
             CustomFastTileMapPlugin::<AnimationCustomization>::default(),
         ))
         .add_systems(Startup, startup)
@@ -84,7 +87,7 @@ fn startup(
         asset_server.load("pixel_tiles_16.png"),
         vec2(16., 16.),
     )
-    .build_and_set(|p| ((p.x + p.y) % 4 + 1) as u32);
+    .build_and_set(|p| (p.x + p.y) % 4 + 1);
 
     commands.spawn(MapBundleManaged::<AnimationCustomization> {
         material: materials.add(map),
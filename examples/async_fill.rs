@@ -0,0 +1,55 @@
+//! A 4096x4096 map whose tiles are computed on the async compute task pool, so the window
+//! (and the camera) is usable right away. The map shows up once its tiles are ready.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, report_loaded)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let (map, fill) = Map::builder(
+        uvec2(4096, 4096),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .build_and_set_async(|p| {
+        // Something slow enough to notice
+        let (x, y) = (p.x as f32 / 64.0, p.y as f32 / 64.0);
+        let v = (x.sin() * y.cos() + (x * 0.3 + y * 0.7).sin()) * 2.0;
+        (v.abs() as u32).min(3) + 1
+    });
+
+    commands.spawn((MapBundleManaged::new(map, materials.as_mut()), fill));
+}
+
+fn report_loaded(mut removed: RemovedComponents<MapAsyncFill>) {
+    for entity in removed.read() {
+        info!("Tiles of map {:?} are ready", entity);
+    }
+}
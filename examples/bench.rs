@@ -32,6 +32,8 @@ fn startup(
         // Tile size (pixels)
         vec2(16., 16.),
     )
+    // Slightly vary the brightness of the (single, repeated) tile
+    .with_color_jitter(0.03, vec![0..1])
     .build();
 
     commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
@@ -50,8 +52,8 @@ fn main() {
                 ..default()
             }),
             LogDiagnosticsPlugin::default(),
-            FrameTimeDiagnosticsPlugin::default(),
-            MouseControlsCameraPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
             FastTileMapPlugin::default(),
         ))
         .add_systems(Startup, startup)
@@ -0,0 +1,117 @@
+//! Example for stacking map layers with different blend modes:
+//! A base layer, a multiplied shadow layer and an additive glow layer.
+//! Press space to cycle through the blend modes of the shadow layer.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2},
+    prelude::*,
+    window::PresentMode,
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::Rng;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1820., 920.).into(),
+                    // disable vsync so we can see the raw FPS speed
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, cycle_shadow_blend_mode)
+        .run();
+}
+
+#[derive(Component)]
+struct ShadowLayer;
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let tiles_texture = asset_server.load("pixel_tiles_16.png");
+    let map_size = uvec2(64, 64);
+    let mut rng = rand::thread_rng();
+
+    // Base layer: random ground tiles
+    let base = Map::builder(map_size, tiles_texture.clone(), vec2(16., 16.))
+        .build_and_set(|_| rng.gen_range(1..5));
+    commands.spawn(MapBundleManaged::new(base, materials.as_mut()));
+
+    // Shadow layer: A bright tile darkened by a color gradient, multiplied with the base layer.
+    let shadow = Map::builder(map_size, tiles_texture.clone(), vec2(16., 16.))
+        .with_blend_mode(MapBlendMode::Multiply)
+        .build_and_set(|_| 5);
+    commands.spawn((
+        MapBundleManaged {
+            material: materials.add(shadow),
+            attributes: MapAttributes {
+                mix_color: vec![
+                    Vec4::new(0.2, 0.2, 0.4, 1.0),
+                    Vec4::new(1.0, 1.0, 1.0, 1.0),
+                    Vec4::new(1.0, 1.0, 1.0, 1.0),
+                    Vec4::new(0.2, 0.2, 0.4, 1.0),
+                ],
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            ..default()
+        },
+        ShadowLayer,
+    ));
+
+    // Glow layer: Few tinted tiles added on top, all others are transparent (tile 0).
+    let glow = Map::builder(map_size, tiles_texture, vec2(16., 16.))
+        .with_blend_mode(MapBlendMode::Additive)
+        .build_and_set(|_| if rng.gen_bool(0.05) { 5 } else { 0 });
+    commands.spawn(MapBundleManaged {
+        material: materials.add(glow),
+        attributes: MapAttributes {
+            mix_color: vec![Vec4::new(0.8, 0.4, 0.1, 1.0); 4],
+        },
+        transform: Transform::from_xyz(0.0, 0.0, 2.0),
+        ..default()
+    });
+} // startup
+
+fn cycle_shadow_blend_mode(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    maps: Query<&Handle<Map>, With<ShadowLayer>>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    if !keyboard.just_pressed(KeyCode::Space) {
+        return;
+    }
+
+    for map_handle in maps.iter() {
+        let Some(map) = materials.get_mut(map_handle) else {
+            continue;
+        };
+        let blend_mode = match map.blend_mode() {
+            MapBlendMode::Multiply => MapBlendMode::Alpha,
+            MapBlendMode::Alpha => MapBlendMode::PremultipliedAlpha,
+            MapBlendMode::PremultipliedAlpha => MapBlendMode::Additive,
+            MapBlendMode::Additive => MapBlendMode::Multiply,
+        };
+        println!("Shadow layer blend mode: {:?}", blend_mode);
+        map.set_blend_mode(blend_mode);
+    }
+}
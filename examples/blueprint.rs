@@ -0,0 +1,141 @@
+//! Overlay patches: A 3x2 construction blueprint follows the cursor without touching the map
+//! data or spawning entities. Click to toggle a second, fixed patch at the clicked tile.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::Rng;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1820., 920.).into(),
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, (move_blueprint, toggle_marker))
+        .run();
+}
+
+#[derive(Resource)]
+struct Blueprint {
+    id: PatchId,
+    marker: Option<PatchId>,
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let mut rng = rand::thread_rng();
+    let mut map = Map::builder(
+        uvec2(64, 64),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .build_and_set(|_| rng.gen_range(1..4));
+
+    let id = map
+        .add_patch(Patch {
+            origin: UVec2::ZERO,
+            size: uvec2(3, 2),
+            tiles: vec![6, 7, 6, 7, 8, 7],
+            opacity: 0.6,
+        })
+        .unwrap();
+    commands.insert_resource(Blueprint { id, marker: None });
+
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+}
+
+/// Tile under the cursor
+fn cursor_tile(
+    windows: &Query<&Window>,
+    cameras: &Query<(&GlobalTransform, &Camera)>,
+    map: &Map,
+) -> Option<UVec2> {
+    let cursor = windows.single().cursor_position()?;
+    let (camera_transform, camera) = cameras.single();
+    let world = camera.viewport_to_world_2d(camera_transform, cursor)?;
+    map.world_to_tile_clipped(world)
+}
+
+fn move_blueprint(
+    windows: Query<&Window>,
+    cameras: Query<(&GlobalTransform, &Camera)>,
+    maps: Query<&Handle<Map>>,
+    blueprint: Res<Blueprint>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    for handle in maps.iter() {
+        let Some(tile) = materials
+            .get(handle)
+            .and_then(|map| cursor_tile(&windows, &cameras, map))
+        else {
+            continue;
+        };
+        // Only access the map mutably (and re-upload it) if the blueprint actually moves
+        if materials
+            .get(handle)
+            .unwrap()
+            .patch(blueprint.id)
+            .unwrap()
+            .origin
+            != tile
+        {
+            materials
+                .get_mut(handle)
+                .unwrap()
+                .move_patch(blueprint.id, tile);
+        }
+    }
+}
+
+fn toggle_marker(
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    cameras: Query<(&GlobalTransform, &Camera)>,
+    maps: Query<&Handle<Map>>,
+    mut blueprint: ResMut<Blueprint>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    for handle in maps.iter() {
+        let Some(map) = materials.get_mut(handle) else {
+            continue;
+        };
+        match blueprint.marker.take() {
+            Some(marker) => {
+                map.remove_patch(marker);
+            }
+            None => {
+                if let Some(tile) = cursor_tile(&windows, &cameras, map) {
+                    let marker = Patch::filled(tile, uvec2(2, 2), 9, 1.0);
+                    blueprint.marker = map.add_patch(marker).ok();
+                }
+            }
+        }
+    }
+}
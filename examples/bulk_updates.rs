@@ -0,0 +1,72 @@
+//! Benchmark of writing large regions of a map tile by tile (`MapIndexerMut::set`) against the
+//! bulk operations `fill_rect`, `copy_from_slice` and `rows_mut`, which copy a row at a time.
+//! Runs without a window and prints the timings, build with --release for meaningful numbers.
+
+use std::time::{Duration, Instant};
+
+use bevy::{
+    math::{uvec2, vec2, URect},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+const MAP_SIZE: u32 = 1024;
+const REGION_SIZE: u32 = 512;
+const ROUNDS: u32 = 20;
+
+/// Average time of `f` over `ROUNDS` runs (after a warmup run)
+fn measure(map: &mut Map, mut f: impl FnMut(&mut MapIndexerMut, u32)) -> Duration {
+    f(&mut map.indexer_mut(), 0);
+    let start = Instant::now();
+    for round in 1..=ROUNDS {
+        f(&mut map.indexer_mut(), round);
+    }
+    start.elapsed() / ROUNDS
+}
+
+fn main() {
+    let mut map: Map = Map::builder(
+        uvec2(MAP_SIZE, MAP_SIZE),
+        Handle::default(),
+        vec2(16.0, 16.0),
+    )
+    .build_and_set(|_| 1);
+    let region = URect::new(100, 100, 100 + REGION_SIZE, 100 + REGION_SIZE);
+    // Generated terrain to blit into the map
+    let chunk: Vec<u32> = (0..REGION_SIZE * REGION_SIZE).map(|i| i % 7 + 1).collect();
+
+    let set_fill = measure(&mut map, |m, round| {
+        for y in region.min.y..region.max.y {
+            for x in region.min.x..region.max.x {
+                m.set(x, y, round % 4 + 1);
+            }
+        }
+    });
+    let fill_rect = measure(&mut map, |m, round| {
+        m.fill_rect(region.min, region.max, round % 4 + 1);
+    });
+    let set_copy = measure(&mut map, |m, _| {
+        for y in 0..REGION_SIZE {
+            for x in 0..REGION_SIZE {
+                let v = chunk[(y * REGION_SIZE + x) as usize];
+                m.set(region.min.x + x, region.min.y + y, v);
+            }
+        }
+    });
+    let copy_from_slice = measure(&mut map, |m, _| {
+        m.copy_from_slice(region, &chunk);
+    });
+    let rows_mut = measure(&mut map, |m, _| {
+        let rows = m.rows_mut().skip(region.min.y as usize);
+        for (row, chunk_row) in rows.zip(chunk.chunks_exact(REGION_SIZE as usize)) {
+            row[region.min.x as usize..region.max.x as usize].copy_from_slice(chunk_row);
+        }
+    });
+
+    println!("Writing {REGION_SIZE}x{REGION_SIZE} tiles of a {MAP_SIZE}x{MAP_SIZE} map:");
+    println!("  fill with set():   {set_fill:?}");
+    println!("  fill_rect():       {fill_rect:?}");
+    println!("  copy with set():   {set_copy:?}");
+    println!("  copy_from_slice(): {copy_from_slice:?}");
+    println!("  rows_mut():        {rows_mut:?}");
+}
@@ -0,0 +1,95 @@
+//! Cave generation with a simple cellular automaton.
+//! Starting from random noise, 4 iterations of the "4-5 rule" are applied, ie. a tile becomes a
+//! wall iff at least 5 of its neighbors are walls (or 4 if it is a wall itself).
+//! Press space to generate a new cave.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2},
+    prelude::*,
+    window::PresentMode,
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::Rng;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+const FLOOR: u32 = 2;
+const WALL: u32 = 4;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1820., 920.).into(),
+                    // disable vsync so we can see the raw FPS speed
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, regenerate)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let map = Map::builder(
+        uvec2(160, 90),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .build_and_initialize(generate_cave);
+
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+}
+
+fn generate_cave(m: &mut MapIndexerMut) {
+    let mut rng = rand::thread_rng();
+    for p in m.positions() {
+        m.set_uvec(p, if rng.gen_bool(0.45) { WALL } else { FLOOR });
+    }
+
+    let all = URect::from_corners(UVec2::ZERO, m.size());
+    for _ in 0..4 {
+        m.step_region(all, |_, tile, neighborhood| {
+            // Tiles outside of the map count as walls
+            let walls = neighborhood.count(|v| v != FLOOR);
+            if walls >= 5 || (tile == WALL && walls >= 4) {
+                WALL
+            } else {
+                FLOOR
+            }
+        });
+    }
+}
+
+fn regenerate(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    maps: Query<&Handle<Map>>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    if !keyboard.just_pressed(KeyCode::Space) {
+        return;
+    }
+    for map_handle in maps.iter() {
+        if let Some(map) = materials.get_mut(map_handle) {
+            generate_cave(&mut map.indexer_mut());
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! A 3x3 grid of 64x64 maps with exact bounds placed edge to edge, while the camera zooms
+//! through fractional scales (like 0.75). There should be no seams between the maps.
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+const CHUNK_SIZE: UVec2 = uvec2(64, 64);
+const TILE_SIZE: Vec2 = vec2(16., 16.);
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, FastTileMapPlugin::default()))
+        .add_systems(Startup, startup)
+        .add_systems(Update, zoom)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let chunk_world_size = CHUNK_SIZE.as_vec2() * TILE_SIZE;
+    for y in -1..=1 {
+        for x in -1..=1 {
+            let map = Map::builder(
+                CHUNK_SIZE,
+                asset_server.load("pixel_tiles_16.png"),
+                TILE_SIZE,
+            )
+            .with_dominance_overhang()
+            .with_exact_bounds(true)
+            // Edge tiles differ from the inner tiles, so seams and overlaps would stand out
+            .build_and_set(|p| {
+                let edge =
+                    p.x == 0 || p.y == 0 || p.x + 1 == CHUNK_SIZE.x || p.y + 1 == CHUNK_SIZE.y;
+                if edge {
+                    2
+                } else {
+                    1
+                }
+            });
+
+            let translation = (vec2(x as f32, y as f32) * chunk_world_size).extend(0.0);
+            commands.spawn(MapBundleManaged {
+                transform: Transform::from_translation(translation),
+                ..MapBundleManaged::new(map, materials.as_mut())
+            });
+        }
+    }
+}
+
+/// Oscillate the camera scale between 0.5 and 1.5
+fn zoom(time: Res<Time>, mut projections: Query<&mut OrthographicProjection>) {
+    let scale = 1.0 + 0.5 * (time.elapsed_seconds() * 0.5).sin();
+    for mut projection in projections.iter_mut() {
+        projection.scale = scale;
+    }
+}
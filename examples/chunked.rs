@@ -0,0 +1,69 @@
+//! An infinite, procedurally generated world streamed in chunks with `ChunkedMap`.
+//! Pan around with the mouse, middle click to place a tile.
+//! Logs the number of loaded chunk maps and how many of them are uploaded per frame.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::vec2,
+    prelude::*,
+    window::PrimaryWindow,
+};
+use bevy_fast_tilemap::prelude::*;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MapDiagnosticsPlugin::<NoCustomization>::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, place_tile)
+        .run();
+}
+
+/// Islands of sand and grass in water, from a few overlapping sine waves
+fn terrain(chunk: IVec2, m: &mut MapIndexerMut) {
+    for local in m.positions() {
+        let p = (chunk * m.size().as_ivec2()).as_vec2() + local.as_vec2();
+        let h = (p.x * 0.05).sin() + (p.y * 0.07).cos() + (p.x * 0.013 + p.y * 0.021).sin();
+        let tile = match h {
+            h if h > 0.8 => 3,
+            h if h > 0.4 => 2,
+            _ => 1,
+        };
+        m.set_uvec(local, tile);
+    }
+}
+
+fn startup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((Camera2dBundle::default(), ChunkedMapCamera));
+    let atlas = asset_server.load("pixel_tiles_16.png");
+    let chunked = ChunkedMap::new(UVec2::splat(32), vec2(16., 16.), atlas, terrain);
+    commands.spawn(chunked.with_radius(2, 3));
+}
+
+fn place_tile(
+    buttons: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    chunked: Query<&ChunkedMap>,
+    mut maps: ResMut<Assets<Map>>,
+) {
+    let (camera, camera_transform) = cameras.single();
+    let cursor = windows.single().cursor_position();
+    let Some(world) = cursor.and_then(|p| camera.viewport_to_world_2d(camera_transform, p)) else {
+        return;
+    };
+    if buttons.just_pressed(MouseButton::Middle) {
+        let chunked = chunked.single();
+        chunked.set_tile(chunked.world_tile_at(world), 4, &mut maps);
+    }
+}
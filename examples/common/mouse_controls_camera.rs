@@ -4,16 +4,13 @@ use bevy::{
     input::mouse::{MouseMotion, MouseWheel},
     math::vec3,
     prelude::*,
+    render::camera::NormalizedRenderTarget,
+    window::PrimaryWindow,
 };
 
+#[derive(Default)]
 pub struct MouseControlsCameraPlugin;
 
-impl Default for MouseControlsCameraPlugin {
-    fn default() -> MouseControlsCameraPlugin {
-        MouseControlsCameraPlugin {}
-    }
-}
-
 impl Plugin for MouseControlsCameraPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Update, mouse_controls_camera);
@@ -22,10 +19,13 @@ impl Plugin for MouseControlsCameraPlugin {
 
 /// Use RMB for panning
 /// Use scroll wheel for zooming
+/// With multiple windows, only the cameras of the window under the cursor are controlled.
 fn mouse_controls_camera(
     mouse_button: Res<ButtonInput<MouseButton>>,
     mut mouse_motion_events: EventReader<MouseMotion>,
     mut mouse_wheel_events: EventReader<MouseWheel>,
+    windows: Query<(Entity, &Window)>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
     mut camera_query: Query<(
         &GlobalTransform,
         &mut Transform,
@@ -33,9 +33,23 @@ fn mouse_controls_camera(
         &mut OrthographicProjection,
     )>,
 ) {
+    // If the cursor is in none of the windows (eg. while dragging outside), control all cameras
+    let hovered = windows
+        .iter()
+        .find(|(_, window)| window.cursor_position().is_some())
+        .map(|(entity, _)| entity);
+    let primary_window = primary_window.iter().next();
+    let controlled = |camera: &Camera| match (hovered, camera.target.normalize(primary_window)) {
+        (Some(hovered), Some(NormalizedRenderTarget::Window(window))) => window.entity() == hovered,
+        _ => true,
+    };
+
     for event in mouse_motion_events.read() {
         if mouse_button.pressed(MouseButton::Left) || mouse_button.pressed(MouseButton::Right) {
-            for (_, mut transform, _, _) in camera_query.iter_mut() {
+            for (_, mut transform, camera, _) in camera_query.iter_mut() {
+                if !controlled(camera) {
+                    continue;
+                }
                 transform.translation.x -= event.delta.x * transform.scale.x;
                 transform.translation.y += event.delta.y * transform.scale.y;
             }
@@ -48,7 +62,10 @@ fn mouse_controls_camera(
     }
 
     if wheel_y != 0. {
-        for (_, mut transform, _, mut _ortho) in camera_query.iter_mut() {
+        for (_, mut transform, camera, mut _ortho) in camera_query.iter_mut() {
+            if !controlled(camera) {
+                continue;
+            }
             let factor = f32::powf(2., -wheel_y / 2.);
             transform.scale *= vec3(factor, factor, 1.0);
             transform.scale = transform
@@ -30,8 +30,8 @@ fn main() {
                 ..default()
             }),
             LogDiagnosticsPlugin::default(),
-            FrameTimeDiagnosticsPlugin::default(),
-            MouseControlsCameraPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
             FastTileMapPlugin::default(),
         ))
         .add_systems(Startup, startup)
@@ -57,10 +57,8 @@ fn startup(
     .build_and_initialize(|m| {
         // Initialize using a closure
         // Set all tiles in layer 0 to index 4
-        for y in 0..m.size().y {
-            for x in 0..m.size().y {
-                m.set(x, y, ((x + y) % 4 + 1) as u32);
-            }
+        for p in m.positions() {
+            m.set_uvec(p, (p.x + p.y) % 4 + 1);
         }
     });
 
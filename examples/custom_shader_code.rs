@@ -4,6 +4,9 @@ modify the appearance of the tiles. In this case, we add a "special" bit to the
 and use it to make some tiles bounce up and down and tint them red.
 */
 
+// `#[derive(ShaderType)]` emits never-called `check` functions.
+#![allow(dead_code)]
+
 use bevy::{
     core_pipeline::{
         bloom::{BloomCompositeMode, BloomSettings},
@@ -34,6 +37,7 @@ struct MyCustomization;
 impl Customization for MyCustomization {
     const SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x1d1e1e1e1e1e1e1e);
     type UserData = UserData;
+    type ExtraBindings = NoExtraBindings;
 
     // This is how you can insert custom code snippeds into tilemap_shader.wgsl.
     // Note that the code is inserted verbatim, so it requires some understanding of
@@ -62,15 +66,17 @@ impl Customization for MyCustomization {
 
             // extract the actual tile index
             var tile_index = in.tile_index & 0x00FF;
-            var tile_offset = in.tile_offset;
+            var tile_offset = in.tile_offset_texels;
 
+            // Offsets are in atlas pixels, `tile_uv` is relative to the tile size,
+            // so this works for any tile size, world tile size or transform.
             if special {
-                tile_offset.y += abs(sin(in.animation_state * 5.0 + tile_offset.x * 0.002)) * 20.0;
+                tile_offset.y += abs(sin(in.animation_state * 5.0 + in.tile_uv.x * 0.5)) * 0.15 * map.tile_size.y;
             }
 
             // Sometimes mirror tile on the x-Axis for some reason :)
             if user_data.cursor_position.x % 2 == 0 {
-                tile_offset.x = 256.0 - tile_offset.x;
+                tile_offset.x = (1.0 - in.tile_uv.x - map.tile_anchor_point.x) * map.tile_size.x;
             }
 
             var color = sample_tile_at(tile_index, in.tile_position, tile_offset);
@@ -82,7 +88,7 @@ impl Customization for MyCustomization {
 
             // Add a white glow to the hovered tile
             if u32(in.tile_position.x) == user_data.cursor_position.x && u32(in.tile_position.y) == user_data.cursor_position.y {
-                var v = (sin(in.animation_state * 3.0) + 1.5) * (tile_offset.y + 64.0) / 40.0;
+                var v = (sin(in.animation_state * 3.0) + 1.5) * in.tile_uv.y * 3.2;
                 color = color * vec4(v * 10.0, v * 10.0, v * 10.0, 1.0);
             }
 
@@ -106,8 +112,8 @@ fn main() {
                 ..default()
             }),
             LogDiagnosticsPlugin::default(),
-            FrameTimeDiagnosticsPlugin::default(),
-            MouseControlsCameraPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
             CustomFastTileMapPlugin::<MyCustomization>::default(),
         ))
         .add_systems(Startup, startup)
@@ -165,19 +171,17 @@ fn startup(
 /// Fill the map with a random pattern
 fn init_map(m: &mut MapIndexerMut<MyCustomization>) {
     let mut rng = rand::thread_rng();
-    for y in 0..m.size().y {
-        for x in 0..m.size().x {
-            // Actual tile index
-            let mut v = rng.gen_range(1..4);
-
-            // With a 10% chance, set the "special" bit, which
-            // we will interpret in our custom shader code above
-            if rng.gen_bool(0.1) {
-                v |= 0x0100;
-            }
-
-            m.set(x, y, v);
+    for p in m.positions() {
+        // Actual tile index
+        let mut v = rng.gen_range(1..4);
+
+        // With a 10% chance, set the "special" bit, which
+        // we will interpret in our custom shader code above
+        if rng.gen_bool(0.1) {
+            v |= 0x0100;
         }
+
+        m.set_uvec(p, v);
     }
 }
 
@@ -0,0 +1,50 @@
+//! A map using the generated debug atlas (feature `debug-atlas`), no asset files needed.
+//! Each tile shows its index as colored quadrants.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::uvec2,
+    prelude::*,
+    window::PresentMode,
+};
+use bevy_fast_tilemap::prelude::*;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1820., 920.).into(),
+                    // disable vsync so we can see the raw FPS speed
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    // All 64 tiles of the debug atlas, row by row
+    let (atlas, tile_size) = debug_atlas(&mut images);
+    let map = Map::builder(uvec2(8, 8), atlas, tile_size).build_and_set(|p| p.y * 8 + p.x);
+
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+}
@@ -0,0 +1,120 @@
+//! Decals: Small images stamped onto the map at arbitrary positions, without any entities.
+//! 2000 decals with random rotation, scale and lifetime are spread over the map,
+//! hold space to splatter more at the cursor.
+//! The map slowly rotates to show that decals move along with it.
+
+use bevy::{
+    diagnostic::{EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2, Vec3Swizzles},
+    prelude::*,
+    window::PresentMode,
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::Rng;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1820., 920.).into(),
+                    // disable vsync so we can see the raw FPS speed
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            EntityCountDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, (rotate, splatter))
+        .run();
+}
+
+fn random_decal(rng: &mut impl Rng, position: Vec2) -> Decal {
+    Decal::new(position, rng.gen_range(6..12))
+        .with_rotation(rng.gen_range(0.0..std::f32::consts::TAU))
+        .with_scale(rng.gen_range(0.3..1.5))
+        .with_ttl(rng.gen_range(5.0..30.0))
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let mut rng = rand::thread_rng();
+    let mut map = Map::builder(
+        uvec2(128, 128),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .build_and_set(|_| rng.gen_range(1..4));
+
+    let half_size = map.world_size() / 2.0;
+    for _ in 0..2000 {
+        let position = vec2(
+            rng.gen_range(-half_size.x..half_size.x),
+            rng.gen_range(-half_size.y..half_size.y),
+        );
+        map.add_decal(random_decal(&mut rng, position));
+    }
+
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+}
+
+fn rotate(time: Res<Time>, mut maps: Query<&mut Transform, With<Handle<Map>>>) {
+    for mut transform in maps.iter_mut() {
+        transform.rotate_z(0.05 * time.delta_seconds());
+    }
+}
+
+fn splatter(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    windows: Query<&Window>,
+    cameras: Query<(&GlobalTransform, &Camera)>,
+    maps: Query<(&Handle<Map>, &GlobalTransform)>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    if !keyboard.pressed(KeyCode::Space) {
+        return;
+    }
+    let Some(cursor) = windows.single().cursor_position() else {
+        return;
+    };
+    let (camera_transform, camera) = cameras.single();
+    let Some(world) = camera
+        .viewport_to_world(camera_transform, cursor)
+        .map(|ray| ray.origin.truncate())
+    else {
+        return;
+    };
+
+    let mut rng = rand::thread_rng();
+    for (map_handle, transform) in maps.iter() {
+        let Some(map) = materials.get_mut(map_handle) else {
+            continue;
+        };
+        // Decal positions are relative to the map entity
+        let local = transform
+            .affine()
+            .inverse()
+            .transform_point3(world.extend(0.0))
+            .xy();
+        for _ in 0..10 {
+            let offset = vec2(rng.gen_range(-40.0..40.0), rng.gen_range(-40.0..40.0));
+            map.add_decal(random_decal(&mut rng, local + offset));
+        }
+    }
+}
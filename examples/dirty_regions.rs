@@ -0,0 +1,132 @@
+//! Random edits with `FastTileMapDebug::dirty_regions`: the tiles written before each upload
+//! flash up. The text shows the upload counters of `MapDiagnosticsPlugin`.
+//! Press D to toggle the visualization.
+
+use bevy::{
+    diagnostic::DiagnosticsStore,
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+const MAP_SIZE: UVec2 = uvec2(64, 64);
+
+type Counters = MapDiagnosticsPlugin<NoCustomization>;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+            Counters::default(),
+        ))
+        .insert_resource(FastTileMapDebug::show_dirty_regions(true))
+        .insert_resource(Edits {
+            timer: Timer::from_seconds(0.4, TimerMode::Repeating),
+            rng: 0x2545_f491,
+        })
+        .add_systems(Startup, startup)
+        .add_systems(Update, (random_edits, show_counters, toggle_debug))
+        .run();
+}
+
+#[derive(Resource)]
+struct Edits {
+    timer: Timer,
+    /// xorshift state
+    rng: u32,
+}
+
+impl Edits {
+    fn next(&mut self, max: u32) -> u32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 17;
+        self.rng ^= self.rng << 5;
+        self.rng % max
+    }
+}
+
+#[derive(Component)]
+struct CounterText;
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let map = Map::builder(
+        MAP_SIZE,
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .build_and_set(|p| (p.x / 8 + p.y / 8) % 4);
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+
+    commands.spawn((TextBundle::from_section("", default()), CounterText));
+}
+
+/// Every now and then write a single tile, a rectangle or a row
+fn random_edits(
+    time: Res<Time>,
+    mut edits: ResMut<Edits>,
+    maps: Query<&Handle<Map>>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    if !edits.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let tile = edits.next(4);
+    let min = uvec2(edits.next(MAP_SIZE.x), edits.next(MAP_SIZE.y));
+    let kind = edits.next(3);
+    let size = uvec2(1 + edits.next(12), 1 + edits.next(12));
+
+    for handle in maps.iter() {
+        let Some(map) = materials.get_mut(handle) else {
+            continue;
+        };
+        let mut m = map.indexer_mut();
+        match kind {
+            0 => m.set_uvec(min, tile),
+            1 => m
+                .region_mut(URect::from_corners(min, (min + size).min(MAP_SIZE)))
+                .fill(tile),
+            _ => {
+                for x in 0..MAP_SIZE.x {
+                    m.set(x, min.y, tile);
+                }
+            }
+        }
+    }
+}
+
+fn show_counters(
+    diagnostics: Res<DiagnosticsStore>,
+    mut texts: Query<&mut Text, With<CounterText>>,
+) {
+    let latest = |path| {
+        diagnostics
+            .get(&path)
+            .and_then(|d| d.value())
+            .unwrap_or_default()
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = format!(
+            "Map uploads: {}\nUploaded bytes: {}\nD: toggle dirty regions",
+            latest(Counters::map_uploads_path()),
+            latest(Counters::map_upload_bytes_path()),
+        );
+    }
+}
+
+fn toggle_debug(keys: Res<ButtonInput<KeyCode>>, mut debug: ResMut<FastTileMapDebug>) {
+    if keys.just_pressed(KeyCode::KeyD) {
+        debug.dirty_regions = !debug.dirty_regions;
+    }
+}
@@ -1,12 +1,15 @@
 //! Simple example for illustrating axonometrically projected tilemaps.
 //! To keep the math simple instead of strictly isometric, we stick to a projection
 //! where each tile ends up a diamond shape that is twice as wide as high.
+//! A 2x2 brush is previewed under the cursor, middle click places it.
+
+use std::ops::Range;
 
 use bevy::{
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
     math::{uvec2, vec2},
     prelude::*,
-    window::PresentMode,
+    window::{PresentMode, PrimaryWindow},
 };
 use bevy_fast_tilemap::prelude::*;
 use rand::Rng;
@@ -29,15 +32,18 @@ fn main() {
                 ..default()
             }),
             LogDiagnosticsPlugin::default(),
-            FrameTimeDiagnosticsPlugin::default(),
-            MouseControlsCameraPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
             FastTileMapPlugin::default(),
         ))
         .add_systems(Startup, startup)
-        .add_systems(Update, show_coordinate)
+        .add_systems(Update, (show_coordinate, tile_brush))
         .run();
 }
 
+/// Tiles that stay within their own cell, see below
+const EXCLUDED_TILES: Range<u32> = 2..3;
+
 fn startup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -65,6 +71,9 @@ fn startup(
     // Tiles with higher index will be drawn on top of tiles with lower index.
     // For this we draw in the "padding" area of the tile atlas.
     .with_dominance_overhang()
+    // Tile 2 has the highest index but neither overhangs its neighbors nor is overhung by them,
+    // so it always stays within its own cell.
+    .with_overhang_exclusions(vec![EXCLUDED_TILES])
     .build_and_initialize(init_map);
 
     commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
@@ -73,35 +82,80 @@ fn startup(
 /// Fill the map with a random pattern
 fn init_map(m: &mut MapIndexerMut) {
     let mut rng = rand::thread_rng();
-    for y in 0..m.size().y {
-        for x in 0..m.size().x {
-            m.set(x, y, rng.gen_range(0..3));
-        }
+    for p in m.positions() {
+        m.set_uvec(p, rng.gen_range(0..3));
     }
 } // reset_map
 
-/// Highlight the currently hovered tile red, reset all other tiles
+/// Print the map coordinate under the cursor
 fn show_coordinate(
     mut cursor_moved_events: EventReader<CursorMoved>,
-    mut camera_query: Query<(&GlobalTransform, &Camera), With<OrthographicProjection>>,
-    maps: Query<&Handle<Map>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    maps: Query<(&Handle<Map>, &GlobalTransform)>,
     materials: Res<Assets<Map>>,
 ) {
     for event in cursor_moved_events.read() {
-        for map_handle in maps.iter() {
-            let map = materials.get(map_handle).unwrap();
+        // Translate window coordinates to world coordinates,
+        // using the camera of the window the cursor moved in
+        let Some(world) = cursor_to_world(
+            cameras.iter(),
+            event.window,
+            primary_window.iter().next(),
+            event.position,
+        ) else {
+            continue;
+        };
 
-            for (global, camera) in camera_query.iter_mut() {
-                // Translate viewport coordinates to world coordinates
-                if let Some(world) = camera
-                    .viewport_to_world(global, event.position)
-                    .map(|ray| ray.origin.truncate())
-                {
-                    // The map can convert between world coordinates and map coordinates
-                    let coord = map.world_to_map(world);
-                    println!("Map coordinate: {:?}", coord);
-                } // if Some(world)
-            } // for (global, camera)
+        for (map_handle, transform) in maps.iter() {
+            let map = materials.get(map_handle).unwrap();
+            // The map can convert between world coordinates and map coordinates
+            let coord = map.world_to_map_with(transform, world);
+            println!("Map coordinate: {:?}", coord);
         } // for map
     } // for event
-} // highlight_hovered
+} // show_coordinate
+
+/// Preview a 2x2 stamp under the cursor and place it on middle click
+fn tile_brush(
+    buttons: Res<ButtonInput<MouseButton>>,
+    windows: Query<(Entity, &Window)>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    maps: Query<(&Handle<Map>, &GlobalTransform)>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    let world = windows.iter().find_map(|(entity, window)| {
+        cursor_to_world(
+            cameras.iter(),
+            entity,
+            primary_window.iter().next(),
+            window.cursor_position()?,
+        )
+    });
+    let stamp = TileStamp::new(uvec2(2, 2), vec![1, 1, 0, 1]);
+
+    for (map_handle, transform) in maps.iter() {
+        let map = materials.get(map_handle).unwrap();
+        let preview = world
+            .map(|world| map.world_to_map_with(transform, world).floor())
+            .filter(|p| p.cmpge(Vec2::ZERO).all() && p.cmplt(map.map_size().as_vec2()).all())
+            .map(|p| TilePreview {
+                origin: p.as_uvec2(),
+                tiles: stamp.clone(),
+                opacity: 0.6,
+            });
+
+        let place = buttons.just_pressed(MouseButton::Middle);
+        // Only touch the map if something changed, so it is not re-uploaded every frame
+        if map.preview() == preview.as_ref() && !place {
+            continue;
+        }
+        let map = materials.get_mut(map_handle).unwrap();
+        if let (true, Some(preview)) = (place, &preview) {
+            map.indexer_mut()
+                .place_stamp(preview.origin, &preview.tiles);
+        }
+        map.set_preview(preview);
+    }
+}
@@ -0,0 +1,38 @@
+//! Compare the time of the first large edit of a fresh map with and without
+//! `MapBuilder::with_expected_edit_size`, ie. with and without allocating the buffers used
+//! by the edit on the first call.
+//! Run with `--release`.
+
+use std::time::{Duration, Instant};
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+const SIZE: UVec2 = uvec2(500, 400);
+
+fn first_edit(hint: Option<usize>) -> (Duration, Duration) {
+    let mut builder = Map::<NoCustomization>::builder(SIZE, Handle::default(), vec2(16., 16.));
+    if let Some(tiles) = hint {
+        builder = builder.with_expected_edit_size(tiles);
+    }
+    let mut map = builder.build();
+
+    let start = Instant::now();
+    map.indexer_mut().flood_fill(UVec2::ZERO, 1);
+    let first = start.elapsed();
+
+    let start = Instant::now();
+    map.indexer_mut().flood_fill(UVec2::ZERO, 2);
+    (first, start.elapsed())
+}
+
+fn main() {
+    let tiles = (SIZE.x * SIZE.y) as usize;
+    for (name, hint) in [("without hint", None), ("with hint", Some(tiles))] {
+        let (first, second) = first_edit(hint);
+        println!("Flood fill of {tiles} tiles {name}: first {first:?}, second {second:?}");
+    }
+}
@@ -0,0 +1,74 @@
+//! In-game tile editor with the egui panel of the `editor-ui` feature.
+//! Select a tile in the palette and paint with the left mouse button.
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    window::PrimaryWindow,
+};
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+use bevy_fast_tilemap::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, EguiPlugin, FastTileMapPlugin::default()))
+        .add_systems(Startup, startup)
+        .add_systems(Update, editor)
+        .run();
+}
+
+#[derive(Resource)]
+struct Editor(EditorUiState);
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+    mut contexts: EguiContexts,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let atlas = asset_server.load("pixel_tiles_16.png");
+    let map = Map::builder(uvec2(48, 32), atlas.clone(), vec2(16., 16.)).build_and_set(|_| 2);
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+
+    commands.insert_resource(Editor(EditorUiState::new(contexts.add_image(atlas))));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn editor(
+    mut contexts: EguiContexts,
+    mut editor: ResMut<Editor>,
+    windows: Query<(Entity, &Window), With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    maps: Query<(&Handle<Map>, &GlobalTransform)>,
+    mut materials: ResMut<Assets<Map>>,
+    images: Res<Assets<Image>>,
+) {
+    let (handle, transform) = maps.single();
+    let (window_entity, window) = windows.single();
+    let ctx = contexts.ctx_mut();
+
+    if !ctx.is_pointer_over_area() {
+        let world = window
+            .cursor_position()
+            .and_then(|cursor| cursor_to_world(&cameras, window_entity, None, cursor));
+        let Some(map) = materials.get(handle) else {
+            return;
+        };
+        editor.0.hover(map, transform, world);
+        if buttons.pressed(MouseButton::Left) {
+            editor.0.apply_tool(&mut materials, handle);
+        }
+    }
+
+    egui::SidePanel::left("tile editor").show(ctx, |ui| {
+        fast_tilemap_editor_ui(
+            ui,
+            materials.get_mut(handle).unwrap(),
+            &images,
+            &mut editor.0,
+        );
+    });
+}
@@ -0,0 +1,96 @@
+//! Emissive tiles glowing with bloom in an HDR pipeline.
+//! The sand and grass colored tiles stand in for lava flowing through stone, their emissive
+//! strength pulses over time. Press space to toggle bloom.
+
+use bevy::{
+    core_pipeline::bloom::BloomSettings,
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+const STONE: u32 = 3;
+const LAVA: u32 = 1;
+/// Cooler lava at the edges of the flows
+const LAVA_CRUST: u32 = 2;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, (pulse, toggle_bloom))
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                // Bloom needs color values above 1
+                hdr: true,
+                ..default()
+            },
+            ..default()
+        },
+        BloomSettings::NATURAL,
+    ));
+
+    let map = Map::builder(
+        uvec2(64, 64),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .with_emissive_tiles(vec![LAVA..LAVA + 1, LAVA_CRUST..LAVA_CRUST + 1], 3.0)
+    .build_and_initialize(|m| {
+        for p in m.positions() {
+            let p = p.as_vec2();
+            let river = (p.y - 32.0 - 8.0 * (p.x * 0.15).sin()).abs();
+            let pool = (p.distance(vec2(16.0, 16.0)) - 5.0).min(p.distance(vec2(48.0, 50.0)) - 4.0);
+            let tile = match river.min(pool + 2.0) {
+                d if d < 2.0 => LAVA,
+                d if d < 3.0 => LAVA_CRUST,
+                _ => STONE,
+            };
+            m.set(p.x as u32, p.y as u32, tile);
+        }
+    });
+
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+}
+
+fn pulse(time: Res<Time>, maps: Query<&Handle<Map>>, mut materials: ResMut<Assets<Map>>) {
+    let strength = 2.5 + 1.5 * (time.elapsed_seconds() * 2.0).sin();
+    for handle in maps.iter() {
+        if let Some(map) = materials.get_mut(handle) {
+            map.set_emissive_strength(strength);
+        }
+    }
+}
+
+fn toggle_bloom(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    cameras: Query<(Entity, Option<&BloomSettings>), With<Camera>>,
+) {
+    if !keys.just_pressed(KeyCode::Space) {
+        return;
+    }
+    for (entity, bloom) in cameras.iter() {
+        match bloom {
+            Some(_) => commands.entity(entity).remove::<BloomSettings>(),
+            None => commands.entity(entity).insert(BloomSettings::NATURAL),
+        };
+    }
+}
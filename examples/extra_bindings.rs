@@ -0,0 +1,145 @@
+//! Custom shader code with extra bindings: A noise texture (bindings 200 and 201) distorts
+//! the tile offsets, a lookup buffer (binding 202) holds how strongly each tile index wobbles.
+//!
+//! Bindings below 200 are used by the map itself, so custom bindings start at 200.
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{AsBindGroup, Extent3d, TextureDimension, TextureFormat},
+        texture::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor},
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::Rng;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+const NOISE_SIZE: u32 = 64;
+
+#[derive(Debug, Clone, Default, AsBindGroup)]
+struct NoiseBindings {
+    #[texture(200)]
+    #[sampler(201)]
+    noise: Handle<Image>,
+
+    /// Distortion strength (in pixels) per tile index
+    #[storage(202, read_only)]
+    wobble: Vec<f32>,
+}
+
+#[derive(Clone, TypePath, Default)]
+struct NoiseCustomization;
+
+impl Customization for NoiseCustomization {
+    const SHADER_HANDLE: Handle<Shader> =
+        Handle::weak_from_u128(0x5e2b7c01d94a4f3e8a61c2d0b7f4e913);
+    type UserData = DefaultUserData;
+    type ExtraBindings = NoiseBindings;
+
+    fn custom_shader_code() -> String {
+        r#"
+        struct UserData {
+            dummy: u32,
+        };
+
+        @group(2) @binding(200)
+        var noise_texture: texture_2d<f32>;
+        @group(2) @binding(201)
+        var noise_sampler: sampler;
+        @group(2) @binding(202)
+        var<storage> wobble: array<f32>;
+
+        fn sample_tile(in: ExtractIn) -> vec4<f32> {
+            // Scroll the noise slowly over the map
+            var uv = (vec2<f32>(in.tile_position) + in.tile_uv) / 8.0
+                + vec2<f32>(in.animation_state * 0.05, 0.0);
+            var distortion = textureSampleLevel(noise_texture, noise_sampler, uv, 0.0).rg - 0.5;
+            var amplitude = wobble[min(in.tile_index, arrayLength(&wobble) - 1u)];
+            return sample_tile_at(in.tile_index, in.tile_position, in.tile_offset_texels + distortion * amplitude);
+        }
+        "#
+        .to_string()
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            MouseControlsCameraPlugin,
+            CustomFastTileMapPlugin::<NoiseCustomization>::default(),
+        ))
+        .add_systems(Startup, startup)
+        .run();
+}
+
+/// Tiling texture of smoothed random values
+fn noise_texture() -> Image {
+    let mut rng = rand::thread_rng();
+    let random: Vec<[u8; 2]> = (0..NOISE_SIZE * NOISE_SIZE).map(|_| rng.gen()).collect();
+    let at = |x: u32, y: u32| random[((y % NOISE_SIZE) * NOISE_SIZE + x % NOISE_SIZE) as usize];
+
+    let mut data = Vec::with_capacity((NOISE_SIZE * NOISE_SIZE * 4) as usize);
+    for y in 0..NOISE_SIZE {
+        for x in 0..NOISE_SIZE {
+            // Average over a 3x3 neighborhood (wrapping around) so the noise is less harsh
+            let mut sum = [0u32; 2];
+            for (dx, dy) in (0..3).flat_map(|dy| (0..3).map(move |dx| (dx, dy))) {
+                let v = at(x + NOISE_SIZE - 1 + dx, y + NOISE_SIZE - 1 + dy);
+                sum[0] += v[0] as u32;
+                sum[1] += v[1] as u32;
+            }
+            data.extend([(sum[0] / 9) as u8, (sum[1] / 9) as u8, 0, 255]);
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: NOISE_SIZE,
+            height: NOISE_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::Repeat,
+        address_mode_v: ImageAddressMode::Repeat,
+        ..ImageSamplerDescriptor::linear()
+    });
+    image
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<Map<NoiseCustomization>>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let mut map = Map::builder(
+        uvec2(64, 64),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .build_and_set(|p| (p.x / 8 + p.y / 8) % 4 + 1);
+
+    // Tile 1 wobbles the most, tile 4 not at all
+    *map.extra_bindings_mut() = NoiseBindings {
+        noise: images.add(noise_texture()),
+        wobble: vec![0.0, 12.0, 6.0, 3.0, 0.0],
+    };
+
+    commands.spawn(MapBundleManaged::<NoiseCustomization> {
+        material: materials.add(map),
+        ..default()
+    });
+}
@@ -0,0 +1,123 @@
+/*!
+Custom shader code using every field of `ExtractIn`.
+- `map_position` draws a thin grid along the tile borders,
+- `map_position_dpdx` / `map_position_dpdy` fade the grid out when zooming out (so it doesn't
+  turn into noise) and
+- `mix_color` is used to only tint the grid (instead of the whole map).
+
+This serves as a reference for what is available in custom shaders.
+*/
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2},
+    prelude::*,
+    window::PresentMode,
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::Rng;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+#[derive(Clone, TypePath, Default)]
+struct GridCustomization;
+
+impl Customization for GridCustomization {
+    const SHADER_HANDLE: Handle<Shader> =
+        Handle::weak_from_u128(0x3b7d6e4a1c2f45d8a9e0b1c2d3e4f5a6);
+    type UserData = DefaultUserData;
+    type ExtraBindings = NoExtraBindings;
+
+    fn custom_shader_code() -> String {
+        r#"
+        struct UserData {
+            dummy: u32,
+        };
+
+        // Instead of a custom `UserData` type a custom parameter is used here.
+        // map.custom_params[0].x: Width of the grid lines, in tiles
+
+        fn sample_tile(in: ExtractIn) -> vec4<f32> {
+            var color = sample_tile_at(in.tile_index, in.tile_position, in.tile_offset_texels);
+
+            // Distance to the closest tile border, in tiles
+            var f = fract(in.map_position);
+            var border = min(min(f.x, 1.0 - f.x), min(f.y, 1.0 - f.y));
+
+            // Size of a screen pixel in tiles
+            var pixel = max(length(in.map_position_dpdx), length(in.map_position_dpdy));
+
+            // Fade out the grid once a pixel covers more than a quarter of a tile
+            var fade = clamp(2.0 - pixel * 8.0, 0.0, 1.0);
+
+            if border < map.custom_params[0].x && fade > 0.0 {
+                // mix_color is applied to the whole map after this, so undo it for the tile
+                // and apply it (twice) for the grid only.
+                var grid = mix(color, in.mix_color, 0.8 * fade);
+                color = grid / max(in.mix_color, vec4<f32>(0.01));
+            }
+
+            return color;
+        }
+    "#
+        .to_string()
+    }
+
+    fn shader_defs() -> Vec<String> {
+        vec![
+            "EXTRACT_MAP_POSITION".to_string(),
+            "EXTRACT_MAP_POSITION_DERIVATIVES".to_string(),
+            "EXTRACT_MIX_COLOR".to_string(),
+        ]
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1820., 920.).into(),
+                    // disable vsync so we can see the raw FPS speed
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            CustomFastTileMapPlugin::<GridCustomization>::default(),
+        ))
+        .add_systems(Startup, startup)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map<GridCustomization>>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let mut rng = rand::thread_rng();
+
+    let map = Map::<GridCustomization>::builder(
+        uvec2(64, 64),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .with_param(0, Vec4::new(0.03, 0.0, 0.0, 0.0))
+    .build_and_set(|_| rng.gen_range(1..5));
+
+    commands.spawn(MapBundleManaged {
+        material: materials.add(map),
+        attributes: MapAttributes {
+            mix_color: vec![Vec4::new(1.0, 0.9, 0.2, 1.0); 4],
+        },
+        ..default()
+    });
+}
@@ -0,0 +1,47 @@
+//! A 20x12 board that always exactly fills the window, try resizing it.
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+const BOARD: UVec2 = uvec2(20, 12);
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1000., 600.).into(),
+                    ..default()
+                }),
+                ..default()
+            }),
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    windows: Query<&Window>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let map = Map::builder(
+        BOARD,
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .fit_to_viewport(windows.single().size(), BOARD)
+    .build_and_set(|p| 1 + (p.x + p.y) % 2);
+
+    commands
+        .spawn(MapBundleManaged::new(map, materials.as_mut()))
+        .insert(FitMapToWindow { tiles: BOARD });
+}
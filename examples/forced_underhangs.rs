@@ -0,0 +1,123 @@
+//! Forced underhangs with the IDENTITY projection: Towers whose spires reach 1.5 tiles above
+//! their cell.
+//! The map mesh (white outline, the map itself is yellow) extends two tiles upwards for the
+//! spires of the top row and one tile downwards for underhangs of the bottom row, but stays tight
+//! on the left and right.
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+const TILE: u32 = 16;
+/// Height of the spires above the cell of a tower, in pixels
+const SPIRE: u32 = 24;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, outline_meshes)
+        .run();
+}
+
+/// Atlas with a grass tile and a tower tile, with room for the spire above each tile
+fn tower_atlas() -> Image {
+    let (width, height) = (2 * TILE, SPIRE + TILE + SPIRE);
+    let mut data = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let (tile, x) = (x / TILE, x % TILE);
+            let center = x.abs_diff(TILE / 2);
+            let pixel = match (tile, y) {
+                // Grass
+                (0, y) if (SPIRE..SPIRE + TILE).contains(&y) => [60, 140, 60, 255],
+                // Tower body
+                (1, y) if (SPIRE..SPIRE + TILE).contains(&y) && center < 6 => [150, 150, 160, 255],
+                (1, y) if (SPIRE..SPIRE + TILE).contains(&y) => [60, 140, 60, 255],
+                // Spire, narrowing towards the top
+                (1, y) if y < SPIRE && center * SPIRE < 6 * y => [200, 60, 60, 255],
+                _ => [0, 0, 0, 0],
+            };
+            data.extend(pixel);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    )
+}
+
+fn startup(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let spire = vec2(0.0, SPIRE as f32);
+    let map = Map::builder(
+        uvec2(12, 8),
+        images.add(tower_atlas()),
+        vec2(TILE as f32, TILE as f32),
+    )
+    .with_padding(2.0 * spire, spire, spire)
+    .with_projection(IDENTITY)
+    // Tiles overhang onto the tile above them (map y points down)
+    .with_forced_underhangs(vec![vec2(0.0, -1.0)])
+    // and beyond, as the spires are taller than a tile
+    .with_overhang_extent_tiles(2)
+    .build_and_set(|p| if (p.x + p.y) % 3 == 0 { 1 } else { 0 });
+
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+}
+
+fn outline_meshes(
+    mut gizmos: Gizmos,
+    maps: Query<(&Handle<Map>, &GlobalTransform)>,
+    materials: Res<Assets<Map>>,
+) {
+    for (handle, transform) in maps.iter() {
+        let Some(map) = materials.get(handle) else {
+            continue;
+        };
+        gizmos.rect_2d(
+            transform.translation().truncate(),
+            0.0,
+            map.world_size(),
+            Color::WHITE,
+        );
+        let map_rect = Rect::from_corners(
+            map.map_to_local(Vec2::ZERO),
+            map.map_to_local(map.map_size().as_vec2()),
+        );
+        gizmos.rect_2d(
+            transform
+                .transform_point(map_rect.center().extend(0.0))
+                .truncate(),
+            0.0,
+            map_rect.size(),
+            Color::srgb(1.0, 1.0, 0.0),
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! Hexagonal maps with all four layouts: pointy-top tiles with odd (top left) or even (top
+//! right) rows shifted and flat-top tiles with odd (bottom left) or even (bottom right) columns
+//! shifted. The hovered tile is highlighted.
+//! The atlases are generated at startup, each tile is a hexagon inscribed in its rectangle.
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+/// Tile size for (roughly) regular pointy-top hexagons, see `HEX_POINTY_TOP`.
+/// Flat-top tiles have the axes swapped.
+const TILE: UVec2 = uvec2(28, 32);
+const COLORS: [[u8; 4]; 3] = [[70, 130, 60, 255], [90, 150, 70, 255], [200, 60, 60, 255]];
+const HIGHLIGHT: u32 = 2;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, highlight_hovered)
+        .run();
+}
+
+/// Atlas with one hexagon of size `tile` per color, with a darker border
+fn hex_atlas(tile: UVec2, flat_top: bool) -> Image {
+    let (width, height) = (tile.x * COLORS.len() as u32, tile.y);
+    let mut data = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let [r, g, b, a] = COLORS[(x / tile.x) as usize];
+            // Distance from the tile center, relative to the half tile size
+            let p = (vec2((x % tile.x) as f32, y as f32) + 0.5) / tile.as_vec2() * 2.0 - 1.0;
+            let p = if flat_top { p.yx().abs() } else { p.abs() };
+            // Pointy top: vertical sides, vertices at the top and bottom center
+            let d = p.x.max(p.x * 0.5 + p.y);
+            let pixel = match d {
+                d if d < 0.85 => [r, g, b, a],
+                d if d <= 1.0 => [r / 2, g / 2, b / 2, a],
+                _ => [0, 0, 0, 0],
+            };
+            data.extend(pixel);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    )
+}
+
+fn startup(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let pointy_top = images.add(hex_atlas(TILE, false));
+    let flat_top = images.add(hex_atlas(TILE.yx(), true));
+    let layouts = [
+        (HEX_POINTY_TOP, pointy_top.clone(), vec2(-200.0, 150.0)),
+        (HEX_POINTY_TOP_EVEN, pointy_top, vec2(200.0, 150.0)),
+        (HEX_FLAT_TOP, flat_top.clone(), vec2(-200.0, -150.0)),
+        (HEX_FLAT_TOP_EVEN, flat_top, vec2(200.0, -150.0)),
+    ];
+
+    for (projection, atlas, position) in layouts {
+        let tile_size = match projection.stagger.staggers_columns() {
+            true => TILE.yx(),
+            false => TILE,
+        };
+        let map = Map::builder(uvec2(12, 9), atlas, tile_size.as_vec2())
+            .with_projection(projection)
+            .build_and_initialize(reset_map);
+
+        commands.spawn(MapBundleManaged {
+            material: materials.add(map),
+            transform: Transform::from_translation(position.extend(0.0)),
+            ..default()
+        });
+    }
+}
+
+fn reset_map(m: &mut MapIndexerMut) {
+    for p in m.positions() {
+        m.set_uvec(p, (p.x + p.y) % 2);
+    }
+}
+
+/// Highlight the hovered tile, reset all other tiles
+fn highlight_hovered(
+    mut cursor_moved_events: EventReader<CursorMoved>,
+    cameras: Query<(&GlobalTransform, &Camera)>,
+    maps: Query<(&Handle<Map>, &GlobalTransform)>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    let Some(event) = cursor_moved_events.read().last() else {
+        return;
+    };
+    let Some(world) = cameras
+        .iter()
+        .find_map(|(global, camera)| camera.viewport_to_world_2d(global, event.position))
+    else {
+        return;
+    };
+
+    for (handle, transform) in maps.iter() {
+        let Some(map) = materials.get_mut(handle) else {
+            continue;
+        };
+        // `floor()` of a map position is the hexagon containing it
+        let tile = map.world_to_map_with(transform, world).floor();
+        let hovered = (tile.cmpge(Vec2::ZERO).all() && tile.cmplt(map.map_size().as_vec2()).all())
+            .then_some(tile.as_uvec2());
+
+        let mut m = map.indexer_mut();
+        reset_map(&mut m);
+        if let Some(tile) = hovered {
+            m.set_uvec(tile, HIGHLIGHT);
+        }
+    }
+}
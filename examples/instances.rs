@@ -0,0 +1,126 @@
+//! Rendering the same map many times with `MapInstances`.
+//! A 16x16 "room template" is rendered at 200 positions, using a single entity and a single
+//! draw call. Hovering an instance highlights the tile under the cursor in all instances.
+
+use bevy::{
+    diagnostic::{EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2, vec3},
+    prelude::*,
+    window::PresentMode,
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::Rng;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+const ROOM_SIZE: u32 = 16;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1820., 920.).into(),
+                    // disable vsync so we can see the raw FPS speed
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            EntityCountDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, highlight_hovered)
+        .run();
+}
+
+fn room(x: u32, y: u32) -> u32 {
+    let wall = x == 0 || y == 0 || x == ROOM_SIZE - 1 || y == ROOM_SIZE - 1;
+    let door = (x == ROOM_SIZE / 2 || y == ROOM_SIZE / 2) && wall;
+    match (wall, door) {
+        (_, true) => 2,
+        (true, false) => 4,
+        (false, false) => 1,
+    }
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle {
+        transform: Transform::from_scale(vec3(4.0, 4.0, 1.0)),
+        ..default()
+    });
+
+    let map = Map::builder(
+        uvec2(ROOM_SIZE, ROOM_SIZE),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .build_and_set(|p| room(p.x, p.y));
+
+    // 20x10 rooms on a grid, slightly jittered and rotated
+    let mut rng = rand::thread_rng();
+    let spacing = ROOM_SIZE as f32 * 16.0 * 1.2;
+    let mut transforms = Vec::new();
+    for y in 0..10 {
+        for x in 0..20 {
+            let position = vec2(x as f32 - 9.5, y as f32 - 4.5) * spacing
+                + vec2(rng.gen_range(-20.0..20.0), rng.gen_range(-20.0..20.0));
+            transforms.push(
+                Transform::from_translation(position.extend(0.0))
+                    .with_rotation(Quat::from_rotation_z(rng.gen_range(-0.2..0.2))),
+            );
+        }
+    }
+
+    commands.spawn((
+        MapBundleManaged::new(map, materials.as_mut()),
+        MapInstances::new(transforms),
+    ));
+}
+
+fn highlight_hovered(
+    mut cursor_moved_events: EventReader<CursorMoved>,
+    cameras: Query<(&GlobalTransform, &Camera)>,
+    maps: Query<(&Handle<Map>, &MapInstances, &GlobalTransform)>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    let Some(event) = cursor_moved_events.read().last() else {
+        return;
+    };
+    let (camera_transform, camera) = cameras.single();
+    let Some(world) = camera
+        .viewport_to_world(camera_transform, event.position)
+        .map(|ray| ray.origin.truncate())
+    else {
+        return;
+    };
+
+    for (map_handle, instances, transform) in maps.iter() {
+        let Some(map) = materials.get_mut(map_handle) else {
+            continue;
+        };
+        let hovered = instances.instance_at(map, world, transform);
+
+        let mut m = map.indexer_mut();
+        for y in 0..ROOM_SIZE {
+            for x in 0..ROOM_SIZE {
+                m.set(x, y, room(x, y));
+            }
+        }
+        if let Some((instance, p)) = hovered {
+            println!("Instance {}, tile {:?}", instance, p.as_uvec2());
+            m.set_uvec(p.as_uvec2(), 3);
+        }
+    }
+}
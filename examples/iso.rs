@@ -28,8 +28,8 @@ fn main() {
                 ..default()
             }),
             LogDiagnosticsPlugin::default(),
-            FrameTimeDiagnosticsPlugin::default(),
-            MouseControlsCameraPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
             FastTileMapPlugin::default(),
         ))
         .add_systems(Startup, startup)
@@ -44,11 +44,8 @@ fn startup(
 ) {
     commands.spawn(Camera2dBundle::default());
 
-    // Note that tile index 0 is used to draw tiles that are outside
-    // the logical map (but inside the rectangular map bounding box).
-    // In iso.png we chose a dotted outline to make this visible,
-    // in practice you might prefer a transparent tile here or one
-    // that can serve as some sort of background to your map.
+    // The parts of the rectangular map bounding box outside of the logical map
+    // are transparent by default, here we give them a dark background color.
     let map = Map::builder(
         // Map size
         uvec2(23, 57),
@@ -58,6 +55,9 @@ fn startup(
         vec2(40., 20.),
     )
     .with_projection(AXONOMETRIC)
+    .with_outside_color(Color::srgb(0.1, 0.1, 0.15))
+    // A color per tile, for highlighting the hovered tile
+    .with_tile_color_channel()
     // Build the map is to provide an initializer callback here.
     .build_and_initialize(reset_map);
 
@@ -66,20 +66,19 @@ fn startup(
 
 /// Fill the map with a chessboard pattern.
 fn reset_map(m: &mut MapIndexerMut) {
-    for y in 0..m.size().y {
-        for x in 0..m.size().x {
-            m.set(x, y, (((x + y) % 2) + 1) as u32);
-        }
+    for p in m.positions() {
+        m.set_uvec(p, ((p.x + p.y) % 2) + 1);
     }
 } // reset_map
 
-/// Highlight the currently hovered tile red, reset all other tiles
+/// Tint the currently hovered tile red, reset the previously hovered one
 fn highlight_hovered(
     mut cursor_moved_events: EventReader<CursorMoved>,
     mut camera_query: Query<(&GlobalTransform, &Camera), With<OrthographicProjection>>,
     maps: Query<&Handle<Map>>,
+    mut hovered: Local<Option<UVec2>>,
 
-    // We'll actually change the map contents for highlighting
+    // We'll change the tile colors of the map for highlighting
     mut materials: ResMut<Assets<Map>>,
 ) {
     for event in cursor_moved_events.read() {
@@ -101,17 +100,18 @@ fn highlight_hovered(
                         .clamp(uvec2(0, 0), map.map_size() - uvec2(1, 1));
 
                     // Modifying the map requires that the underlying data be synchronized to
-                    // the GPU again so you want to avoid to do this every frame if your map is
-                    // very large. The transfer cost does not depend on how much you change, so
-                    // you may as well generate the whole thing (of course consider the actual
-                    // generation time).
+                    // the GPU again, only the rows of the changed tiles are uploaded.
+                    // The tile values stay as they are, the tint is multiplied with the tile
+                    // color in the shader.
                     //
                     // Note that this technically does *not* modify the `Map` component, but
                     // the underlying data which is stored in the material.
                     let mut m = map.indexer_mut();
 
-                    reset_map(&mut m);
-                    m.set_uvec(coord, 3u32);
+                    if let Some(previous) = hovered.replace(coord) {
+                        m.set_color_uvec(previous, Color::WHITE);
+                    }
+                    m.set_color_uvec(coord, Color::srgb(1.0, 0.3, 0.3));
                 } // if Some(world)
             } // for (global, camera)
         } // for map
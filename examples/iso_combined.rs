@@ -0,0 +1,98 @@
+//! Axonometric map combining perspective and dominance overhangs.
+//! Perspective decides which neighbors are drawn in front of or behind a tile,
+//! dominance blends the tiles of the remaining neighbors at the same depth by their index.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2},
+    prelude::*,
+    window::PresentMode,
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::Rng;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1820., 920.).into(),
+                    // disable vsync so we can see the raw FPS speed
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, show_coordinate)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let map = Map::builder(
+        // Map size
+        uvec2(100, 100),
+        // Tile atlas
+        asset_server.load("iso_256x128_dominance.png"),
+        // Tile size
+        vec2(256.0, 128.0),
+    )
+    .with_padding(vec2(256.0, 128.0), vec2(256.0, 128.0), vec2(256.0, 128.0))
+    // Neighbors above and below in the projection are ordered by perspective, the ones to the
+    // left and right (at the same depth) by dominance, ie. their index in the tile atlas.
+    // This samples both sets of neighbors, so it costs about as much as both modes together.
+    .with_projection(AXONOMETRIC)
+    .with_overhangs(true, true, true)
+    .build_and_initialize(init_map);
+
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+} // startup
+
+/// Fill the map with a random pattern
+fn init_map(m: &mut MapIndexerMut) {
+    let mut rng = rand::thread_rng();
+    for p in m.positions() {
+        m.set_uvec(p, rng.gen_range(1..4));
+    }
+} // reset_map
+
+/// Highlight the currently hovered tile red, reset all other tiles
+fn show_coordinate(
+    mut cursor_moved_events: EventReader<CursorMoved>,
+    mut camera_query: Query<(&GlobalTransform, &Camera), With<OrthographicProjection>>,
+    mut materials: ResMut<Assets<Map>>,
+    maps: Query<&Handle<Map>>,
+) {
+    for event in cursor_moved_events.read() {
+        for map_handle in maps.iter() {
+            let map = materials.get_mut(map_handle).unwrap();
+            for (global, camera) in camera_query.iter_mut() {
+                // Translate viewport coordinates to world coordinates
+                if let Some(world) = camera
+                    .viewport_to_world(global, event.position)
+                    .map(|ray| ray.origin.truncate())
+                {
+                    // The map can convert between world coordinates and map coordinates
+                    let coord = map.world_to_map(world);
+                    println!("Map coordinate: {:?}", coord);
+                } // if Some(world)
+            } // for (global, camera)
+        } // for map
+    } // for event
+} // highlight_hovered
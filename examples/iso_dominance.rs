@@ -29,8 +29,8 @@ fn main() {
                 ..default()
             }),
             LogDiagnosticsPlugin::default(),
-            FrameTimeDiagnosticsPlugin::default(),
-            MouseControlsCameraPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
             FastTileMapPlugin::default(),
         ))
         .add_systems(Startup, startup)
@@ -67,10 +67,8 @@ fn startup(
 /// Fill the map with a random pattern
 fn init_map(m: &mut MapIndexerMut) {
     let mut rng = rand::thread_rng();
-    for y in 0..m.size().y {
-        for x in 0..m.size().x {
-            m.set(x, y, rng.gen_range(1..4));
-        }
+    for p in m.positions() {
+        m.set_uvec(p, rng.gen_range(1..4));
     }
 } // reset_map
 
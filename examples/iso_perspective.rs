@@ -29,8 +29,8 @@ fn main() {
                 ..default()
             }),
             LogDiagnosticsPlugin::default(),
-            FrameTimeDiagnosticsPlugin::default(),
-            MouseControlsCameraPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
             FastTileMapPlugin::default(),
         ))
         .add_systems(Startup, startup)
@@ -75,7 +75,6 @@ fn startup(
                 Vec4::new(1.0, 1.0, 1.0, 1.0), // color gets multiplied, so this means no change
                 Vec4::new(0.0, 0.0, 1.0, 1.0),
             ],
-            ..default()
         },
         ..default()
     });
@@ -87,6 +86,7 @@ fn show_coordinate(
     mut camera_query: Query<(&GlobalTransform, &Camera), With<OrthographicProjection>>,
     maps: Query<&Handle<Map>>,
     mut materials: ResMut<Assets<Map>>,
+    images: Res<Assets<Image>>,
 ) {
     for event in cursor_moved_events.read() {
         for map_handle in maps.iter() {
@@ -104,6 +104,10 @@ fn show_coordinate(
                     // the tile
                     let world2 = map.map_to_world_3d(coord.extend(0.0));
                     println!("Map coordinate: {:?} World-Z: {:?}", coord, world2.z);
+
+                    // The tile actually seen under the cursor, which can be a tall neighbor
+                    // overhanging the tile at `coord`
+                    println!("Picked tile: {:?}", map.pick(world, &images));
                 } // if Some(world)
             } // for (global, camera)
         } // for map
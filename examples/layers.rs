@@ -0,0 +1,127 @@
+//! A single map with four stacked tile layers (ground, props, roofs and fog) that are rendered
+//! in one draw call. Press 1 to 4 to show or hide the layers.
+//! The atlas is generated at startup, the props and the fog are partially transparent.
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::Rng;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+const TILE: u32 = 16;
+const GRASS: u32 = 0;
+const DIRT: u32 = 1;
+const TREE: u32 = 2;
+const ROOF: u32 = 3;
+const FOG: u32 = 4;
+
+const KEYS: [KeyCode; 4] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+];
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, toggle_layers)
+        .run();
+}
+
+/// Color of pixel `p` of tile `index`
+fn tile_pixel(index: u32, p: Vec2) -> [u8; 4] {
+    let center = p + 0.5 - TILE as f32 / 2.0;
+    match index {
+        GRASS => [70, 140, 60, 255],
+        DIRT => [130, 100, 60, 255],
+        TREE if center.length() < 6.0 => [30, 90, 40, 255],
+        ROOF if center.abs().max_element() < 7.0 => [160, 60, 50, 255],
+        FOG => [200, 200, 210, 140],
+        _ => [0, 0, 0, 0],
+    }
+}
+
+fn atlas() -> Image {
+    let (width, height) = (TILE * 5, TILE);
+    let mut data = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let p = vec2((x % TILE) as f32, y as f32);
+            data.extend(tile_pixel(x / TILE, p));
+        }
+    }
+    Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    )
+}
+
+fn startup(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let mut rng = rand::thread_rng();
+    let map = Map::builder(uvec2(64, 40), images.add(atlas()), vec2(16., 16.))
+        .with_layers(4)
+        .build_and_initialize(|m| {
+            for p in m.positions() {
+                m.set_uvec(p, if rng.gen_bool(0.2) { DIRT } else { GRASS });
+                if rng.gen_bool(0.1) {
+                    m.set_layer_uvec(1, p, TREE);
+                }
+                // A village of roofs in the middle, fog around the edges
+                if p.x.abs_diff(32) < 8 && p.y.abs_diff(20) < 5 && (p.x + p.y) % 3 != 0 {
+                    m.set_layer_uvec(2, p, ROOF);
+                }
+                if p.x < 6 || p.x >= 58 || p.y < 6 || p.y >= 34 {
+                    m.set_layer_uvec(3, p, FOG);
+                }
+            }
+        });
+
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+}
+
+/// Show or hide layer `i` when pressing key `i + 1`
+fn toggle_layers(
+    keys: Res<ButtonInput<KeyCode>>,
+    maps: Query<&Handle<Map>>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    for (layer, key) in KEYS.iter().enumerate() {
+        if !keys.just_pressed(*key) {
+            continue;
+        }
+        for handle in maps.iter() {
+            if let Some(map) = materials.get_mut(handle) {
+                let visible = map.is_layer_visible(layer as u32);
+                map.set_layer_visible(layer as u32, !visible);
+            }
+        }
+    }
+}
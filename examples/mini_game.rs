@@ -0,0 +1,192 @@
+//! Minimal game loop on a map:
+//! Arrow keys move a character from tile to tile, walls block it, stepping on the switch opens
+//! the door. The tile under the cursor is highlighted with the map preview (without changing
+//! the map data) and the camera follows the character, clamped to the map.
+//!
+//! Tile indices refer to `pixel_tiles_16.png`.
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+const FLOOR: u32 = 2;
+const WALL: u32 = 3;
+const DOOR_CLOSED: u32 = 4;
+const DOOR_OPEN: u32 = 5;
+const SWITCH: u32 = 6;
+const HIGHLIGHT: u32 = 7;
+
+const MAP_SIZE: UVec2 = uvec2(40, 24);
+const DOOR: UVec2 = uvec2(20, 12);
+const SWITCH_POSITION: UVec2 = uvec2(8, 18);
+
+#[derive(Component)]
+struct Player {
+    tile: UVec2,
+}
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, FastTileMapPlugin::default()))
+        .add_systems(Startup, startup)
+        .add_systems(
+            Update,
+            (
+                move_player,
+                open_door,
+                (follow_player, highlight_hovered).after(move_player),
+            ),
+        )
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    let mut camera = Camera2dBundle::default();
+    camera.projection.scale = 0.5;
+    commands.spawn(camera);
+
+    // Outer walls and a dividing wall with a closed door
+    let map = Map::builder(
+        MAP_SIZE,
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .build_and_set(|p| {
+        let border = p.x == 0 || p.y == 0 || p.x == MAP_SIZE.x - 1 || p.y == MAP_SIZE.y - 1;
+        if p == DOOR {
+            DOOR_CLOSED
+        } else if p == SWITCH_POSITION {
+            SWITCH
+        } else if border || p.x == DOOR.x {
+            WALL
+        } else {
+            FLOOR
+        }
+    });
+
+    let start = uvec2(4, 4);
+    let position = map.tile_center_world(start);
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+    commands.spawn((
+        Player { tile: start },
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgb(1.0, 0.8, 0.2),
+                custom_size: Some(vec2(12.0, 12.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(1.0)),
+            ..default()
+        },
+    ));
+}
+
+/// Move one tile per key press, unless the target tile blocks
+fn move_player(
+    keys: Res<ButtonInput<KeyCode>>,
+    maps: Query<&Handle<Map>>,
+    materials: Res<Assets<Map>>,
+    mut players: Query<(&mut Player, &mut Transform)>,
+) {
+    let direction = [
+        (KeyCode::ArrowLeft, IVec2::NEG_X),
+        (KeyCode::ArrowRight, IVec2::X),
+        (KeyCode::ArrowUp, IVec2::NEG_Y),
+        (KeyCode::ArrowDown, IVec2::Y),
+    ]
+    .into_iter()
+    .find(|(key, _)| keys.just_pressed(*key))
+    .map(|(_, direction)| direction);
+    let Some(direction) = direction else {
+        return;
+    };
+    let Some(map) = maps.iter().next().and_then(|handle| materials.get(handle)) else {
+        return;
+    };
+
+    for (mut player, mut transform) in players.iter_mut() {
+        // Reading goes through the (immutable) indexer, so it does not re-upload the map
+        let target = player.tile.as_ivec2() + direction;
+        let Some(target) = map.content_tile(target.as_vec2()) else {
+            continue;
+        };
+        if matches!(map.indexer().at_uvec(target), WALL | DOOR_CLOSED) {
+            continue;
+        }
+        player.tile = target;
+        transform.translation = map.tile_center_world(player.tile).extend(1.0);
+    }
+}
+
+/// Open the door when the player steps onto the switch.
+/// Only the changed tile is written, and only once, so the map is only uploaded on that frame.
+fn open_door(
+    players: Query<&Player, Changed<Player>>,
+    maps: Query<&Handle<Map>>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    if !players.iter().any(|player| player.tile == SWITCH_POSITION) {
+        return;
+    }
+    for handle in maps.iter() {
+        materials.set_tile_if_neq(handle, DOOR, DOOR_OPEN);
+    }
+}
+
+/// Keep the player in view, without showing anything beyond the map
+fn follow_player(
+    players: Query<&Transform, (With<Player>, Changed<Transform>)>,
+    mut cameras: Query<(&mut Transform, &OrthographicProjection), Without<Player>>,
+    maps: Query<&Handle<Map>>,
+    materials: Res<Assets<Map>>,
+) {
+    let Ok(player) = players.get_single() else {
+        return;
+    };
+    let Some(map) = maps.iter().next().and_then(|handle| materials.get(handle)) else {
+        return;
+    };
+    for (mut transform, projection) in cameras.iter_mut() {
+        let center = map.clamp_view_center(player.translation.truncate(), projection.area.size());
+        transform.translation = center.extend(transform.translation.z);
+    }
+}
+
+/// Show a highlight tile under the cursor with the preview, which leaves the map data as is
+fn highlight_hovered(
+    windows: Query<&Window>,
+    cameras: Query<(&GlobalTransform, &Camera)>,
+    maps: Query<&Handle<Map>>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    let Some(cursor) = windows.single().cursor_position() else {
+        return;
+    };
+    let (camera_transform, camera) = cameras.single();
+    let Some(world) = camera
+        .viewport_to_world(camera_transform, cursor)
+        .map(|ray| ray.origin.truncate())
+    else {
+        return;
+    };
+    for handle in maps.iter() {
+        // Only borrow mutably when the hovered tile changed, see `Map::set_preview`
+        let Some(map) = materials.get(handle) else {
+            continue;
+        };
+        let preview = map.world_to_tile(world).map(|tile| TilePreview {
+            origin: tile,
+            tiles: TileStamp::single(HIGHLIGHT),
+            opacity: 0.5,
+        });
+        if map.preview() != preview.as_ref() {
+            materials.get_mut(handle).unwrap().set_preview(preview);
+        }
+    }
+}
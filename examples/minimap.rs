@@ -0,0 +1,89 @@
+//! A large map with a minimap in the corner of the screen: The minimap renders the tiles of the
+//! map with their average atlas colors and shows the random edits of the map as they happen.
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::Rng;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, change_map)
+        .run();
+}
+
+#[derive(Component)]
+struct MainMap;
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    let camera = commands.spawn(Camera2dBundle::default()).id();
+
+    let map = Map::builder(
+        uvec2(512, 512),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .with_atlas_metadata()
+    .build_and_set(|p| (p.x / 32 + p.y / 32) % 4 + 1);
+    let handle = materials.add(map);
+
+    // 0.5 world units per tile, ie. 256x256 at the default camera scale
+    let minimap = materials
+        .get(&handle)
+        .unwrap()
+        .minimap_builder(&handle)
+        .with_world_tile_size(vec2(0.5, 0.5))
+        .build();
+
+    commands.spawn((
+        MapBundleManaged {
+            material: handle,
+            ..default()
+        },
+        MainMap,
+    ));
+
+    // Child of the camera so it stays in the corner while the camera moves
+    let minimap = commands
+        .spawn(MapBundleManaged {
+            material: materials.add(minimap),
+            transform: Transform::from_xyz(-400.0, 200.0, 1.0),
+            ..default()
+        })
+        .id();
+    commands.entity(camera).add_child(minimap);
+}
+
+/// Paint small random squares into the map, the minimap shows them with the next frame.
+fn change_map(mut materials: ResMut<Assets<Map>>, maps: Query<&Handle<Map>, With<MainMap>>) {
+    let mut rng = rand::thread_rng();
+
+    for handle in maps.iter() {
+        let Some(map) = materials.get_mut(handle) else {
+            continue;
+        };
+        let mut m = map.indexer_mut();
+        let x = rng.gen_range(0..m.size().x - 8);
+        let y = rng.gen_range(0..m.size().y - 8);
+        let tile = rng.gen_range(1..5);
+        for p in 0..64 {
+            m.set(x + p % 8, y + p / 8, tile);
+        }
+    }
+}
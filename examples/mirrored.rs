@@ -0,0 +1,110 @@
+//! A map and its mirror image, made by a negative x scale on the second map entity.
+//! Clicking a tile of either map highlights it in both of them, picking works on the mirrored
+//! map just like on the original.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2, vec3},
+    prelude::*,
+    window::PresentMode,
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::Rng;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+const HIGHLIGHT: u32 = 3;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1820., 920.).into(),
+                    // disable vsync so we can see the raw FPS speed
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, pick)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle {
+        transform: Transform::from_scale(vec3(0.5, 0.5, 1.0)),
+        ..default()
+    });
+
+    let mut rng = rand::thread_rng();
+    let atlas: Handle<Image> = asset_server.load("pixel_tiles_16.png");
+
+    // Same content for both maps, so the mirroring is easy to see
+    let tiles: Vec<u32> = (0..32 * 32).map(|_| rng.gen_range(0..3)).collect();
+    for scale_x in [1.0, -1.0] {
+        let map = Map::builder(uvec2(32, 32), atlas.clone(), vec2(16., 16.))
+            .build_and_set(|p| tiles[(p.y * 32 + p.x) as usize]);
+
+        commands.spawn(MapBundleManaged {
+            material: materials.add(map),
+            transform: Transform::from_translation(vec3(scale_x * 270.0, 0.0, 0.0))
+                .with_scale(vec3(scale_x, 1.0, 1.0)),
+            ..default()
+        });
+    }
+}
+
+/// Highlight the clicked tile in both maps
+fn pick(
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    cameras: Query<(&GlobalTransform, &Camera)>,
+    maps: Query<&Handle<Map>>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(cursor) = windows.single().cursor_position() else {
+        return;
+    };
+    let (camera_transform, camera) = cameras.single();
+    let Some(world) = camera
+        .viewport_to_world(camera_transform, cursor)
+        .map(|ray| ray.origin.truncate())
+    else {
+        return;
+    };
+
+    // The transform of each entity (including the mirroring) is applied to its map,
+    // so `world_to_map` takes it into account
+    let picked = maps.iter().find_map(|handle| {
+        let map = materials.get(handle)?;
+        let tile = map.world_to_map(world).floor();
+        let inside = tile.cmpge(Vec2::ZERO).all() && tile.cmplt(map.map_size().as_vec2()).all();
+        inside.then(|| tile.as_uvec2())
+    });
+    let Some(tile) = picked else {
+        return;
+    };
+    println!("Picked tile {:?}", tile);
+    for handle in maps.iter() {
+        if let Some(map) = materials.get_mut(handle) {
+            map.indexer_mut().set_uvec(tile, HIGHLIGHT);
+        }
+    }
+}
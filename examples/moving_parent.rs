@@ -0,0 +1,123 @@
+//! A map on a "ship" that moves in a circle and pulses in size.
+//! The tile under the cursor is highlighted and a marker follows its center, both are converted
+//! after the transforms of the current frame are known, so they stay exactly on the moving map.
+
+use bevy::{
+    math::{uvec2, vec2, vec3, Vec3Swizzles},
+    prelude::*,
+    window::PrimaryWindow,
+};
+use bevy_fast_tilemap::prelude::*;
+
+const HIGHLIGHT: u32 = 4;
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, FastTileMapPlugin::default()))
+        .init_resource::<Highlighted>()
+        .add_systems(Startup, startup)
+        .add_systems(Update, move_ship)
+        // After the map transforms of this frame were applied
+        .add_systems(PostUpdate, highlight_hovered.after(MapSystems::Prepare))
+        .run();
+}
+
+#[derive(Component)]
+struct Ship;
+
+#[derive(Component)]
+struct Marker;
+
+/// The tile being highlighted and its original value
+#[derive(Resource, Default)]
+struct Highlighted(Option<(UVec2, u32)>);
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let map = Map::builder(
+        uvec2(16, 12),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .build_and_set(|_| 1);
+
+    commands
+        .spawn((Ship, SpatialBundle::default()))
+        .with_children(|ship| {
+            ship.spawn(MapBundleManaged::new(map, materials.as_mut()));
+        });
+
+    commands.spawn((
+        Marker,
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgb(1.0, 0.2, 0.2),
+                custom_size: Some(vec2(4.0, 4.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 10.0),
+            ..default()
+        },
+    ));
+}
+
+fn move_ship(time: Res<Time>, mut ships: Query<&mut Transform, With<Ship>>) {
+    let t = time.elapsed_seconds();
+    for mut transform in ships.iter_mut() {
+        transform.translation = vec3(t.cos(), t.sin(), 0.0) * 150.0;
+        transform.rotation = Quat::from_rotation_z(t * 0.5);
+        transform.scale = Vec3::splat(1.0 + 0.3 * (t * 0.7).sin());
+    }
+}
+
+fn highlight_hovered(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    maps: Query<&Handle<Map>>,
+    mut markers: Query<(&mut Transform, &mut Visibility), With<Marker>>,
+    mut materials: ResMut<Assets<Map>>,
+    mut highlighted: ResMut<Highlighted>,
+) {
+    let (camera, camera_transform) = cameras.single();
+    let cursor = windows.single().cursor_position();
+    let world = cursor.and_then(|p| camera.viewport_to_world_2d(camera_transform, p));
+    let (mut marker, mut marker_visibility) = markers.single_mut();
+    *marker_visibility = Visibility::Hidden;
+
+    for map_handle in maps.iter() {
+        let Some(map) = materials.get(map_handle) else {
+            continue;
+        };
+        // The map has a single entity, so its transform is applied to it automatically
+        let tile = world
+            .map(|world| map.world_to_map(world).floor())
+            .filter(|tile| {
+                tile.cmpge(Vec2::ZERO).all() && tile.cmplt(map.map_size().as_vec2()).all()
+            })
+            .map(|tile| tile.as_uvec2());
+
+        if let Some(tile) = tile {
+            let center = map.map_to_world_3d((tile.as_vec2() + 0.5).extend(0.0));
+            marker.translation = center.xy().extend(marker.translation.z);
+            *marker_visibility = Visibility::Visible;
+        }
+
+        // Only touch the map when the hovered tile changes
+        if highlighted.0.map(|(tile, _)| tile) == tile {
+            continue;
+        }
+        let mut m = materials.get_mut(map_handle).unwrap().indexer_mut();
+        if let Some((tile, value)) = highlighted.0.take() {
+            m.set_uvec(tile, value);
+        }
+        if let Some(tile) = tile {
+            highlighted.0 = Some((tile, m.at_uvec(tile)));
+            m.set_uvec(tile, HIGHLIGHT);
+        }
+    }
+}
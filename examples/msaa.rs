@@ -0,0 +1,93 @@
+//! Changing `Msaa` at runtime, eg. from a graphics settings menu.
+//! Cycles through all sample counts supported by the adapter every two seconds,
+//! the map should keep rendering all the time.
+//!
+//! Nothing special is needed for this: The sample count is part of the pipeline key,
+//! so maps will just use a different pipeline after the change.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{render_resource::TextureFormat, renderer::RenderAdapter, texture::BevyDefault},
+    window::PresentMode,
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::Rng;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1820., 920.).into(),
+                    // disable vsync so we can see the raw FPS speed
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, cycle_msaa)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let mut rng = rand::thread_rng();
+    let map = Map::builder(
+        uvec2(100, 100),
+        asset_server.load("iso_256x128.png"),
+        vec2(256.0, 128.0),
+    )
+    .with_padding(vec2(256.0, 128.0), vec2(256.0, 128.0), vec2(256.0, 128.0))
+    .with_projection(AXONOMETRIC)
+    .with_perspective_overhang()
+    .build_and_set(|_| rng.gen_range(1..4));
+
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+}
+
+fn cycle_msaa(
+    time: Res<Time>,
+    mut timer: Local<Option<Timer>>,
+    adapter: Res<RenderAdapter>,
+    mut msaa: ResMut<Msaa>,
+) {
+    let timer = timer.get_or_insert_with(|| Timer::from_seconds(2.0, TimerMode::Repeating));
+    if !timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    // Not all adapters support all sample counts, using an unsupported one would panic
+    let flags = adapter
+        .get_texture_format_features(TextureFormat::bevy_default())
+        .flags;
+    let modes = [Msaa::Off, Msaa::Sample2, Msaa::Sample4, Msaa::Sample8];
+    let supported: Vec<_> = modes
+        .into_iter()
+        .filter(|mode| flags.sample_count_supported(mode.samples()))
+        .collect();
+
+    let current = supported
+        .iter()
+        .position(|mode| *mode == *msaa)
+        .unwrap_or(0);
+    *msaa = supported[(current + 1) % supported.len()];
+    println!("Msaa: {:?}", *msaa);
+}
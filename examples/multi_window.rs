@@ -0,0 +1,128 @@
+//! One map shown in two windows: An overview in the primary window and a zoomed in detail view
+//! with its own camera in a second window.
+//! Pan and zoom with the mouse in either window, hovering a tile in either window highlights it.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2, vec3},
+    prelude::*,
+    render::camera::RenderTarget,
+    window::{PresentMode, PrimaryWindow, WindowRef},
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::Rng;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+const HIGHLIGHT: u32 = 4;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1220., 920.).into(),
+                    // disable vsync so we can see the raw FPS speed
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .init_resource::<Highlighted>()
+        .add_systems(Startup, startup)
+        .add_systems(Update, highlight_hovered)
+        .run();
+}
+
+/// The tile being highlighted and its original value
+#[derive(Resource, Default)]
+struct Highlighted(Option<(UVec2, u32)>);
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    // Overview in the primary window
+    commands.spawn(Camera2dBundle {
+        transform: Transform::from_scale(vec3(2.0, 2.0, 1.0)),
+        ..default()
+    });
+
+    // Detail view in a second window, with HDR to make sure maps render into views
+    // with different target formats
+    let detail_window = commands
+        .spawn(Window {
+            title: String::from("Fast Tilemap example (detail)"),
+            resolution: (600., 600.).into(),
+            ..default()
+        })
+        .id();
+    commands.spawn(Camera2dBundle {
+        camera: Camera {
+            target: RenderTarget::Window(WindowRef::Entity(detail_window)),
+            hdr: true,
+            ..default()
+        },
+        transform: Transform::from_scale(vec3(0.25, 0.25, 1.0)),
+        ..default()
+    });
+
+    let mut rng = rand::thread_rng();
+    let map = Map::builder(
+        uvec2(64, 64),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .build_and_set(|_| rng.gen_range(1..4));
+
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+}
+
+fn highlight_hovered(
+    mut cursor_moved_events: EventReader<CursorMoved>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    maps: Query<(&Handle<Map>, &GlobalTransform)>,
+    mut materials: ResMut<Assets<Map>>,
+    mut highlighted: ResMut<Highlighted>,
+) {
+    let Some(event) = cursor_moved_events.read().last() else {
+        return;
+    };
+    // Each window has its own camera, so the window of the event matters
+    let Some(world) = cursor_to_world(
+        cameras.iter(),
+        event.window,
+        primary_window.iter().next(),
+        event.position,
+    ) else {
+        return;
+    };
+
+    for (map_handle, transform) in maps.iter() {
+        let Some(map) = materials.get_mut(map_handle) else {
+            continue;
+        };
+        if let Some((tile, value)) = highlighted.0.take() {
+            map.indexer_mut().set_uvec(tile, value);
+        }
+
+        let tile = map.world_to_map_with(transform, world).floor();
+        if tile.cmplt(Vec2::ZERO).any() || tile.cmpge(map.map_size().as_vec2()).any() {
+            continue;
+        }
+        let tile = tile.as_uvec2();
+        let mut m = map.indexer_mut();
+        highlighted.0 = Some((tile, m.at_uvec(tile)));
+        m.set_uvec(tile, HIGHLIGHT);
+    }
+}
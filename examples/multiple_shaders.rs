@@ -1,5 +1,8 @@
 //! This example demonstrates how you can use a different custom shader for each layer in your map.
 
+// `#[derive(ShaderType)]` emits never-called `check` functions.
+#![allow(dead_code)]
+
 use bevy::{
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
     math::{uvec2, vec2, vec4},
@@ -37,12 +40,13 @@ impl Customization for CustomizationA {
                     return vec4(0.0, 0.0, 0.0, 0.0);
                 }
 
-                return user_data.color * sample_tile_at(in.tile_index, in.tile_position, in.tile_offset);
+                return user_data.color * sample_tile_at(in.tile_index, in.tile_position, in.tile_offset_texels);
             }
         "#.to_string()
     }
     const SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x1d1e1e1e1e1e1e1e);
     type UserData = UserDataA;
+    type ExtraBindings = NoExtraBindings;
 }
 
 #[derive(Debug, Clone, Default, Reflect, AsBindGroup, ShaderType)]
@@ -64,16 +68,17 @@ impl Customization for CustomizationB {
                     return vec4(0.0, 0.0, 0.0, 0.0);
                 }
 
-                var offs = in.tile_offset;
+                var offs = in.tile_offset_texels;
                 let r = 1.5;
-                offs.x += sin(f32(in.tile_offset.y) + user_data.frequency * in.animation_state) * r;
-                offs.y += cos(f32(in.tile_offset.x) + user_data.frequency * in.animation_state) * r;
+                offs.x += sin(f32(in.tile_offset_texels.y) + user_data.frequency * in.animation_state) * r;
+                offs.y += cos(f32(in.tile_offset_texels.x) + user_data.frequency * in.animation_state) * r;
                 return sample_tile_at(in.tile_index, in.tile_position, offs);
             }
         "#.to_string()
     }
     const SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x1d1e1e1e1e1e1e1f);
     type UserData = UserDataB;
+    type ExtraBindings = NoExtraBindings;
 }
 
 fn main() {
@@ -90,8 +95,8 @@ fn main() {
                 ..default()
             }),
             LogDiagnosticsPlugin::default(),
-            FrameTimeDiagnosticsPlugin::default(),
-            MouseControlsCameraPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
             CustomFastTileMapPlugin::<CustomizationA>::default(),
             CustomFastTileMapPlugin::<CustomizationB>::default(),
         ))
@@ -150,10 +155,8 @@ fn startup(
 /// Fill the map with a random pattern
 fn init_map<C: Customization>(m: &mut MapIndexerMut<C>) {
     let mut rng = rand::thread_rng();
-    for y in 0..m.size().y {
-        for x in 0..m.size().x {
-            let v = rng.gen_range(0..2);
-            m.set(x, y, v);
-        }
+    for p in m.positions() {
+        let v = rng.gen_range(0..2);
+        m.set_uvec(p, v);
     }
 }
@@ -0,0 +1,45 @@
+//! Time `find_path` across a 1024x1024 map with scattered walls.
+//! Run with `--release --features pathfinding`.
+
+use std::time::Instant;
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const SIZE: UVec2 = uvec2(1024, 1024);
+const WALL: u32 = 1;
+
+fn main() {
+    let (start, goal) = (UVec2::ZERO, SIZE - 1);
+    let mut rng = StdRng::seed_from_u64(0);
+    let map = Map::<NoCustomization>::builder(SIZE, Handle::default(), vec2(16., 16.))
+        .build_and_set(|p| {
+            if p != start && p != goal && rng.gen_bool(0.25) {
+                WALL
+            } else {
+                0
+            }
+        });
+    let cost = |tile| (tile != WALL).then_some(1);
+
+    for connectivity in [Connectivity::Four, Connectivity::Eight] {
+        let options = PathOptions {
+            connectivity,
+            ..default()
+        };
+        let before = Instant::now();
+        let path = find_path_with(&map, start, goal, options, cost);
+        let elapsed = before.elapsed();
+        match path {
+            Some(path) => println!(
+                "{connectivity:?}: path of {} tiles in {elapsed:?}",
+                path.len()
+            ),
+            None => println!("{connectivity:?}: no path, gave up after {elapsed:?}"),
+        }
+    }
+}
@@ -1,3 +1,6 @@
+// `#[derive(ShaderType)]` emits never-called `check` functions.
+#![allow(dead_code)]
+
 use bevy::{
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
     math::{uvec2, vec2},
@@ -22,6 +25,7 @@ struct PatternCustomization;
 impl Customization for PatternCustomization {
     const SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x1d1e1e1e1e1e1e1e);
     type UserData = UserData;
+    type ExtraBindings = NoExtraBindings;
     fn custom_shader_code() -> String {
         r#"
             struct UserData {
@@ -50,7 +54,7 @@ impl Customization for PatternCustomization {
 
             // DEBUG: Render tiles flat
             fn dbg_sample_tile(in: ExtractIn) -> vec4<f32> {
-                return sample_tile_at(in.tile_index, in.tile_position, in.tile_offset);
+                return sample_tile_at(in.tile_index, in.tile_position, in.tile_offset_texels);
             }
 
             fn sample_tile(in: ExtractIn) -> vec4<f32> {
@@ -76,7 +80,7 @@ impl Customization for PatternCustomization {
                 var animation_speed = 0.0;
 
                 // DEBUG: Render rectangular tile borders
-                if in.tile_offset.x <= 2.0 * bw_dbg || in.tile_offset.y <= 2.0 * bw_dbg {
+                if in.tile_offset_texels.x <= 2.0 * bw_dbg || in.tile_offset_texels.y <= 2.0 * bw_dbg {
                     return vec4<f32>(1.0, 0.0, 0.0, 1.0);
                 }
 
@@ -88,23 +92,23 @@ impl Customization for PatternCustomization {
                 var shift = 100.0;
 
                 // Positions of the (inner) irregular boundaries inside this tile
-                var r = map.tile_size.x + offs(f32(in.tile_position.y) * map.tile_size.y + in.tile_offset.y + a) - doffs;
-                var l = offs(f32(in.tile_position.y) * map.tile_size.y + in.tile_offset.y - a + shift) + doffs;
-                var t = map.tile_size.y + offs(f32(in.tile_position.x) * map.tile_size.x + in.tile_offset.x - a) - doffs;
-                var b = offs(f32(in.tile_position.x) * map.tile_size.x + in.tile_offset.x + a + shift) + doffs;
+                var r = map.tile_size.x + offs(f32(in.tile_position.y) * map.tile_size.y + in.tile_offset_texels.y + a) - doffs;
+                var l = offs(f32(in.tile_position.y) * map.tile_size.y + in.tile_offset_texels.y - a + shift) + doffs;
+                var t = map.tile_size.y + offs(f32(in.tile_position.x) * map.tile_size.x + in.tile_offset_texels.x - a) - doffs;
+                var b = offs(f32(in.tile_position.x) * map.tile_size.x + in.tile_offset_texels.x + a + shift) + doffs;
 
                 // determine actual tile index to render at this position
                 var tile_offset = vec2<i32>(0, 0);
-                if in.tile_offset.x > r - bw {
+                if in.tile_offset_texels.x > r - bw {
                     tile_offset = tile_offset + vec2<i32>(1, 0);
                 }
-                else if in.tile_offset.x <= l + bw {
+                else if in.tile_offset_texels.x <= l + bw {
                     tile_offset = tile_offset + vec2<i32>(-1, 0);
                 }
-                if in.tile_offset.y > t - bw {
+                if in.tile_offset_texels.y > t - bw {
                     tile_offset = tile_offset + vec2<i32>(0, 1);
                 }
-                else if in.tile_offset.y <= b + bw {
+                else if in.tile_offset_texels.y <= b + bw {
                     tile_offset = tile_offset + vec2<i32>(0, -1);
                 }
                 var tile_position = in.tile_position + tile_offset;
@@ -126,14 +130,14 @@ impl Customization for PatternCustomization {
 
                 var is_border = false;
 
-                var is_xborder = abs(in.tile_offset.x - r) <= bw || abs(in.tile_offset.x - l) <= bw;
-                var is_yborder = abs(in.tile_offset.y - t) <= bw || abs(in.tile_offset.y - b) <= bw;
+                var is_xborder = abs(in.tile_offset_texels.x - r) <= bw || abs(in.tile_offset_texels.x - l) <= bw;
+                var is_yborder = abs(in.tile_offset_texels.y - t) <= bw || abs(in.tile_offset_texels.y - b) <= bw;
 
                 // DEBUG: Render offs() instances
-                if abs(in.tile_offset.x - r) <= bw_dbg || abs(in.tile_offset.x - l) <= bw_dbg {
+                if abs(in.tile_offset_texels.x - r) <= bw_dbg || abs(in.tile_offset_texels.x - l) <= bw_dbg {
                     return vec4<f32>(0.0, 1.0, 0.0, 1.0);
                 }
-                if abs(in.tile_offset.y - t) <= bw_dbg || abs(in.tile_offset.y - b) <= bw_dbg {
+                if abs(in.tile_offset_texels.y - t) <= bw_dbg || abs(in.tile_offset_texels.y - b) <= bw_dbg {
                     return vec4<f32>(0.0, 1.0, 1.0, 1.0);
                 }
 
@@ -184,7 +188,7 @@ impl Customization for PatternCustomization {
                     return border_color;
                 }
 
-                var color = sample_tile_at(max_index, in.tile_position, in.tile_offset);
+                var color = sample_tile_at(max_index, in.tile_position, in.tile_offset_texels);
                 return color;
             }
         "#.to_string()
@@ -206,8 +210,8 @@ fn main() {
                 ..default()
             }),
             LogDiagnosticsPlugin::default(),
-            FrameTimeDiagnosticsPlugin::default(),
-            MouseControlsCameraPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
             CustomFastTileMapPlugin::<PatternCustomization>::default(),
         ))
         .add_systems(Startup, startup)
@@ -234,10 +238,8 @@ fn startup(
     .build_and_initialize(|m| {
         let mut rng = rand::thread_rng();
 
-        for y in 0..m.size().y {
-            for x in 0..m.size().y {
-                m.set(x, y, rng.gen_range(0..4));
-            }
+        for p in m.positions() {
+            m.set_uvec(p, rng.gen_range(0..4));
         }
     });
 
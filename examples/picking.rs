@@ -0,0 +1,129 @@
+//! Picking across three stacked map layers.
+//! Clicking removes the topmost visible tile under the cursor: A sprite of the top layer
+//! (only where the sprite itself is opaque, not its transparent surroundings), otherwise
+//! a green tile of the middle layer, otherwise a tile of the ground layer.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2, vec3},
+    prelude::*,
+    window::PresentMode,
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::Rng;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1820., 920.).into(),
+                    // disable vsync so we can see the raw FPS speed
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, pick)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle {
+        transform: Transform::from_scale(vec3(0.5, 0.5, 1.0)),
+        ..default()
+    });
+
+    let mut rng = rand::thread_rng();
+    let atlas: Handle<Image> = asset_server.load("pixel_tiles_16.png");
+
+    // Spawned deliberately in a different order than they are stacked,
+    // the pick system sorts them by z.
+    let layers = [
+        ("middle", 1.0, 0.3, 3..4),
+        ("ground", 0.0, 1.0, 2..3),
+        ("top", 2.0, 0.1, 6..12),
+    ];
+    for (name, z, density, tiles) in layers {
+        let map = Map::builder(uvec2(64, 64), atlas.clone(), vec2(16., 16.)).build_and_set(|_| {
+            if rng.gen_bool(density) {
+                rng.gen_range(tiles.clone())
+            } else {
+                0
+            }
+        });
+
+        commands.spawn((
+            Name::new(name),
+            MapBundleManaged {
+                material: materials.add(map),
+                transform: Transform::from_translation(vec3(0.0, 0.0, z)),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn pick(
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    cameras: Query<(&GlobalTransform, &Camera)>,
+    layers: Query<(Entity, &Handle<Map>, &GlobalTransform, &Name)>,
+    mut materials: ResMut<Assets<Map>>,
+    images: Res<Assets<Image>>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(cursor) = windows.single().cursor_position() else {
+        return;
+    };
+    let (camera_transform, camera) = cameras.single();
+    let Some(world) = camera
+        .viewport_to_world(camera_transform, cursor)
+        .map(|ray| ray.origin.truncate())
+    else {
+        return;
+    };
+
+    // Top to bottom
+    let mut stack: Vec<_> = layers.iter().collect();
+    stack.sort_by(|a, b| b.2.translation().z.total_cmp(&a.2.translation().z));
+
+    let picked = pick_tile_in_layers(
+        stack
+            .iter()
+            .map(|(entity, handle, transform, _)| (*entity, *handle, *transform)),
+        world,
+        &materials,
+        &images,
+        PickOptions {
+            alpha_threshold: Some(0.5),
+            ..default()
+        },
+    );
+
+    let Some((entity, tile)) = picked else {
+        println!("Nothing picked");
+        return;
+    };
+    let (_, handle, _, name) = layers.get(entity).unwrap();
+    println!("Picked tile {:?} of layer {}", tile, name);
+    // Clicking an already cleared tile does not re-upload the map
+    materials.set_tile_if_neq(handle, tile, 0);
+}
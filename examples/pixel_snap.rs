@@ -0,0 +1,66 @@
+//! Global pixel snapping with `FastTileMapGlobals`, toggled with the space key.
+//! The camera starts zoomed in to 6x and drifts by fractional pixels: Snapped, the map moves in
+//! whole pixel steps and stays crisp, otherwise the texel edges fall between screen pixels.
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Pixel snap example (space toggles snapping)"),
+                    resolution: (1280., 720.).into(),
+                    ..default()
+                }),
+                ..default()
+            }),
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, (toggle_pixel_snap, drift_camera))
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    let mut camera = Camera2dBundle::default();
+    camera.transform.scale = Vec3::new(1.0 / 6.0, 1.0 / 6.0, 1.0);
+    commands.spawn(camera);
+
+    let map = Map::builder(
+        uvec2(64, 64),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .build_and_set(|p| (p.x + p.y) % 4 + 1);
+
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+}
+
+fn toggle_pixel_snap(keys: Res<ButtonInput<KeyCode>>, mut globals: ResMut<FastTileMapGlobals>) {
+    if keys.just_pressed(KeyCode::Space) {
+        globals.pixel_snap = !globals.pixel_snap;
+        info!("pixel snap: {}", globals.pixel_snap);
+    }
+}
+
+/// Move the camera slowly, so it is at fractional pixel positions most of the time
+fn drift_camera(time: Res<Time>, mut cameras: Query<&mut Transform, With<Camera>>) {
+    for mut transform in cameras.iter_mut() {
+        transform.translation.x += 0.7 * time.delta_seconds();
+        transform.translation.y += 0.3 * time.delta_seconds();
+    }
+}
@@ -0,0 +1,118 @@
+//! Example for blending between two projections of the same map.
+//! Press space to toggle between the axonometric view and a "tactical" top-down view.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{mat3, uvec2, vec2, vec3},
+    prelude::*,
+    window::PresentMode,
+};
+use bevy_fast_tilemap::prelude::*;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+/// Same as `AXONOMETRIC`, but tiles are stretched vertically so the map appears as seen from
+/// straight above.
+/// Note that this has the same orientation as `AXONOMETRIC`, so the blend between the two
+/// never degenerates.
+const TACTICAL: TileProjection = TileProjection {
+    projection: mat3(
+        vec3(0.5, -1.0, 0.5),
+        vec3(0.5, 1.0, -0.5),
+        vec3(0.0, -1.0, 0.0),
+    ),
+    tile_anchor_point: vec2(0.0, 0.5),
+    stagger: TileStagger::None,
+};
+
+/// Duration of the transition between the two views in seconds.
+const TRANSITION_TIME: f32 = 0.5;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1820., 920.).into(),
+                    // disable vsync so we can see the raw FPS speed
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .init_resource::<TacticalView>()
+        .add_systems(Startup, startup)
+        .add_systems(Update, (toggle_view, blend_projection).chain())
+        .run();
+}
+
+/// Whether we are (transitioning) to the tactical view.
+#[derive(Resource, Default)]
+struct TacticalView(bool);
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let map = Map::builder(
+        // Map size
+        uvec2(23, 57),
+        // Tile atlas texture
+        asset_server.load("iso.png"),
+        // Tile size
+        vec2(40., 20.),
+    )
+    .with_projection(AXONOMETRIC)
+    .with_secondary_projection(TACTICAL)
+    .build_and_set(|pos| ((pos.x + pos.y) % 2) + 1);
+
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+} // startup
+
+fn toggle_view(keyboard: Res<ButtonInput<KeyCode>>, mut tactical: ResMut<TacticalView>) {
+    if keyboard.just_pressed(KeyCode::Space) {
+        tactical.0 = !tactical.0;
+    }
+}
+
+/// Move the projection blend of all maps towards the currently selected view.
+fn blend_projection(
+    tactical: Res<TacticalView>,
+    time: Res<Time>,
+    maps: Query<&Handle<Map>>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    let target = if tactical.0 { 1.0 } else { 0.0 };
+    let step = time.delta_seconds() / TRANSITION_TIME;
+
+    for map_handle in maps.iter() {
+        // Only access the map mutably if it actually changes,
+        // as that triggers an upload to the GPU.
+        let Some(blend) = materials.get(map_handle).map(|map| map.projection_blend()) else {
+            continue;
+        };
+        if blend == target {
+            continue;
+        }
+
+        let blend = if target > blend {
+            (blend + step).min(target)
+        } else {
+            (blend - step).max(target)
+        };
+        if let Some(map) = materials.get_mut(map_handle) {
+            map.set_projection_blend(blend);
+        }
+    }
+}
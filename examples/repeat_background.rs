@@ -0,0 +1,56 @@
+//! A small 32x32 pattern map repeats infinitely as a background behind a regular map.
+//! Pan and zoom around: The background fills any view with a single entity, its mesh follows
+//! the view (`MeshManagedByMap::ViewportFit`).
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    // Diagonal stripes, which make the seams easy to spot if there were any
+    let background = Map::builder(
+        uvec2(32, 32),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .with_repeat_content()
+    .build_and_set(|p| ((p.x + p.y) / 4) % 2);
+    commands.spawn(MapBundleManaged {
+        mesh_managed_by_map: MeshManagedByMap::ViewportFit,
+        ..MapBundleManaged::new(background, materials.as_mut())
+    });
+
+    let map = Map::builder(
+        uvec2(16, 16),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .build_and_set(|p| 2 + (p.x / 4 + p.y / 4) % 2);
+    commands.spawn(MapBundleManaged {
+        transform: Transform::from_xyz(0.0, 0.0, 1.0),
+        ..MapBundleManaged::new(map, materials.as_mut())
+    });
+}
@@ -0,0 +1,185 @@
+//! Mirroring a simulation that keeps its own grid into a map.
+//! A toy falling sand simulation on a 512x512 `Vec<u8>` is stepped at 60 Hz and tracks which
+//! rows changed. Each frame only those rows are converted into tiles with
+//! `MapIndexerMut::sync_changed_rows_from`, and the map is not touched at all if nothing changed.
+//! Press space to reset the simulation.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2, vec3},
+    prelude::*,
+    window::PresentMode,
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::Rng;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+const SIZE: usize = 512;
+
+const EMPTY: u8 = 0;
+const SAND: u8 = 1;
+const WALL: u8 = 2;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1820., 920.).into(),
+                    // disable vsync so we can see the raw FPS speed
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .insert_resource(Time::<Fixed>::from_hz(60.0))
+        .insert_resource(Simulation::new())
+        .add_systems(Startup, startup)
+        .add_systems(FixedUpdate, step_simulation)
+        .add_systems(Update, (reset, sync_map).chain())
+        .run();
+}
+
+/// Simulation state, entirely independent of the map.
+#[derive(Resource)]
+struct Simulation {
+    cells: Vec<u8>,
+    /// Bit `y % 64` of `changed_rows[y / 64]` is set iff row `y` changed since the last sync.
+    changed_rows: Vec<u64>,
+}
+
+impl Simulation {
+    fn new() -> Self {
+        let mut rng = rand::thread_rng();
+        let mut cells = vec![EMPTY; SIZE * SIZE];
+
+        // Some random ledges for the sand to pile up on
+        for _ in 0..40 {
+            let y = rng.gen_range(64..SIZE);
+            let x0 = rng.gen_range(0..SIZE - 64);
+            let len = rng.gen_range(16..64);
+            cells[y * SIZE + x0..y * SIZE + x0 + len].fill(WALL);
+        }
+
+        Self {
+            cells,
+            changed_rows: vec![!0; SIZE.div_ceil(64)],
+        }
+    }
+
+    fn mark_changed(&mut self, y: usize) {
+        self.changed_rows[y / 64] |= 1 << (y % 64);
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.cells.swap(a, b);
+        self.mark_changed(a / SIZE);
+        self.mark_changed(b / SIZE);
+    }
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle {
+        transform: Transform::from_scale(vec3(4.0, 4.0, 1.0)),
+        ..default()
+    });
+
+    let map = Map::builder(
+        uvec2(SIZE as u32, SIZE as u32),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .build();
+
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+}
+
+fn step_simulation(mut sim: ResMut<Simulation>) {
+    let mut rng = rand::thread_rng();
+
+    // Emit some sand at the top
+    for x in [SIZE / 4, SIZE / 2, 3 * SIZE / 4] {
+        let x = x + rng.gen_range(0..8) - 4;
+        if sim.cells[x] == EMPTY {
+            sim.cells[x] = SAND;
+            sim.mark_changed(0);
+        }
+    }
+
+    // Map y grows downwards, so iterate bottom up to move each grain at most once per step
+    for y in (0..SIZE - 1).rev() {
+        // Alternate the preferred diagonal to avoid drifting to one side
+        let left_first = rng.gen_bool(0.5);
+        for x in 0..SIZE {
+            let i = y * SIZE + x;
+            if sim.cells[i] != SAND {
+                continue;
+            }
+            let below = i + SIZE;
+            if sim.cells[below] == EMPTY {
+                sim.swap(i, below);
+                continue;
+            }
+            let left = (x > 0).then(|| below - 1);
+            let right = (x < SIZE - 1).then(|| below + 1);
+            let (first, second) = match left_first {
+                true => (left, right),
+                false => (right, left),
+            };
+            if let Some(j) = [first, second]
+                .into_iter()
+                .flatten()
+                .find(|j| sim.cells[*j] == EMPTY)
+            {
+                sim.swap(i, j);
+            }
+        }
+    }
+}
+
+fn reset(keyboard: Res<ButtonInput<KeyCode>>, mut sim: ResMut<Simulation>) {
+    if keyboard.just_pressed(KeyCode::Space) {
+        *sim = Simulation::new();
+    }
+}
+
+fn sync_map(
+    mut sim: ResMut<Simulation>,
+    maps: Query<&Handle<Map>>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    if sim.changed_rows.iter().all(|bits| *bits == 0) {
+        // Don't even access the map mutably, which would cause it to be uploaded again
+        return;
+    }
+
+    for map_handle in maps.iter() {
+        let Some(map) = materials.get_mut(map_handle) else {
+            continue;
+        };
+        map.indexer_mut().sync_changed_rows_from(
+            &sim.cells,
+            &sim.changed_rows,
+            |cell| match *cell {
+                SAND => 2,
+                WALL => 4,
+                _ => 1,
+            },
+        );
+    }
+
+    sim.changed_rows.fill(0);
+}
@@ -0,0 +1,56 @@
+//! Crossfade a map between two atlases with the same tile layout, eg. for a change of seasons.
+//! The map samples both atlases and blends them by `Map::set_atlas_blend`.
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+/// Duration of a full cycle through both atlases in seconds.
+const CYCLE_TIME: f32 = 6.0;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, blend_atlases)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    // Both atlases have 32x32 tiles of 16x16 pixels
+    let map = Map::builder(
+        uvec2(64, 64),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .with_secondary_atlas(asset_server.load("debug01.png"))
+    .build_and_set(|p| (p.x / 4 + p.y / 4 * 7) % 32);
+
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+}
+
+fn blend_atlases(time: Res<Time>, maps: Query<&Handle<Map>>, mut materials: ResMut<Assets<Map>>) {
+    let phase = time.elapsed_seconds() / CYCLE_TIME * std::f32::consts::TAU;
+    let blend = 0.5 - 0.5 * phase.cos();
+    for handle in maps.iter() {
+        if let Some(map) = materials.get_mut(handle) {
+            map.set_atlas_blend(blend);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Team colors with color key palettes.
+//! The green in the unit tiles of the atlas is a key color that gets replaced per map
+//! (top row: one map layer per team) or per tile owner (bottom: a single map, the owner is
+//! stored in the upper bits of each tile value).
+//! Press space to shuffle the team colors.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2, vec3},
+    prelude::*,
+    window::PresentMode,
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+const MAP_SIZE: u32 = 32;
+const OWNER_SHIFT: u32 = 16;
+
+/// The green of the unit tiles 6..9 in the atlas
+const KEY_COLOR: Color = Color::srgb(88.0 / 255.0, 210.0 / 255.0, 70.0 / 255.0);
+
+const TEAM_COLORS: [Color; 4] = [
+    Color::srgb(0.9, 0.2, 0.2),
+    Color::srgb(0.95, 0.85, 0.2),
+    Color::srgb(0.6, 0.3, 0.9),
+    Color::srgb(0.3, 0.9, 0.9),
+];
+
+#[derive(Component)]
+struct Team(usize);
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1820., 920.).into(),
+                    // disable vsync so we can see the raw FPS speed
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, shuffle_colors)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle {
+        transform: Transform::from_scale(vec3(2.0, 2.0, 1.0)),
+        ..default()
+    });
+
+    let atlas: Handle<Image> = asset_server.load("pixel_tiles_16.png");
+    let tile_size = vec2(16., 16.);
+    let map_width = MAP_SIZE as f32 * tile_size.x;
+    let mut rng = rand::thread_rng();
+
+    // One layer per team, each with its own palette
+    for (team, color) in TEAM_COLORS.iter().enumerate() {
+        let x = (team as f32 - 1.5) * (map_width / 2.0 + 16.0);
+        let y = map_width / 4.0 + 16.0;
+
+        let ground = Map::builder(uvec2(MAP_SIZE / 2, MAP_SIZE / 2), atlas.clone(), tile_size)
+            .build_and_set(|_| 3);
+        commands.spawn(MapBundleManaged {
+            transform: Transform::from_translation(vec3(x, y, 0.0)),
+            ..MapBundleManaged::new(ground, materials.as_mut())
+        });
+
+        let mut units = Map::builder(uvec2(MAP_SIZE / 2, MAP_SIZE / 2), atlas.clone(), tile_size)
+            .with_color_key_palette(vec![KEY_COLOR], 0.05)
+            .build_and_set(|_| {
+                if rng.gen_bool(0.3) {
+                    rng.gen_range(6..9)
+                } else {
+                    0
+                }
+            });
+        units.set_palette(vec![*color]);
+        commands.spawn((
+            MapBundleManaged {
+                transform: Transform::from_translation(vec3(x, y, 1.0)),
+                ..MapBundleManaged::new(units, materials.as_mut())
+            },
+            Team(team),
+        ));
+    }
+
+    // A single layer with units of all teams, indexed by the owner bits of the tile values
+    let y = -(map_width / 2.0 + 16.0) / 2.0;
+    let ground =
+        Map::builder(uvec2(MAP_SIZE * 2, MAP_SIZE), atlas.clone(), tile_size).build_and_set(|_| 2);
+    commands.spawn(MapBundleManaged {
+        transform: Transform::from_translation(vec3(0.0, y, 0.0)),
+        ..MapBundleManaged::new(ground, materials.as_mut())
+    });
+
+    let mut units = Map::builder(uvec2(MAP_SIZE * 2, MAP_SIZE), atlas, tile_size)
+        .with_color_key_palette(vec![KEY_COLOR], 0.05)
+        .with_palette_owner_bits(OWNER_SHIFT, 8)
+        .build_and_set(|p| {
+            if !rng.gen_bool(0.3) {
+                return 0;
+            }
+            // Teams fight in four bands across the map
+            let owner = p.x * TEAM_COLORS.len() as u32 / (MAP_SIZE * 2);
+            (owner << OWNER_SHIFT) | rng.gen_range(6..9)
+        });
+    units.set_palette(TEAM_COLORS.to_vec());
+    commands.spawn(MapBundleManaged {
+        transform: Transform::from_translation(vec3(0.0, y, 1.0)),
+        ..MapBundleManaged::new(units, materials.as_mut())
+    });
+}
+
+fn shuffle_colors(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    layers: Query<(&Handle<Map>, Option<&Team>)>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    if !keyboard.just_pressed(KeyCode::Space) {
+        return;
+    }
+
+    let mut colors = TEAM_COLORS.to_vec();
+    colors.shuffle(&mut rand::thread_rng());
+
+    for (handle, team) in layers.iter() {
+        let Some(map) = materials.get_mut(handle) else {
+            continue;
+        };
+        if map.color_keys().is_empty() {
+            continue;
+        }
+        match team {
+            Some(Team(team)) => map.set_palette(vec![colors[*team]]),
+            None => map.set_palette(colors.clone()),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! A 4096x1 strip map scrolls by like a ticker, from one end to the other and back.
+//! The strip is exactly one tile high, so it lines up with the bar sprite behind it,
+//! and tiles don't step or jitter at the far end of the strip.
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+const LENGTH: u32 = 4096;
+const TILE_SIZE: Vec2 = vec2(16., 16.);
+
+/// Scroll speed in tiles per second
+const SPEED: f32 = 200.0;
+
+#[derive(Component)]
+struct Ticker;
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, FastTileMapPlugin::default()))
+        .add_systems(Startup, startup)
+        .add_systems(Update, scroll)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    // Bar of exactly the size of one row of tiles, the strip must cover it completely
+    commands.spawn(SpriteBundle {
+        sprite: Sprite {
+            color: Color::srgb(0.8, 0.1, 0.1),
+            custom_size: Some(vec2(2000.0, TILE_SIZE.y)),
+            ..default()
+        },
+        ..default()
+    });
+
+    // Numbered runs of tiles, so the position along the strip is easy to follow
+    let map = Map::builder(
+        uvec2(LENGTH, 1),
+        asset_server.load("pixel_tiles_16.png"),
+        TILE_SIZE,
+    )
+    .build_and_set(|p| 2 + (p.x / 8) % 6);
+    commands.spawn((
+        Ticker,
+        MapBundleManaged {
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            ..MapBundleManaged::new(map, materials.as_mut())
+        },
+    ));
+}
+
+fn scroll(time: Res<Time>, mut tickers: Query<&mut Transform, With<Ticker>>) {
+    // Back and forth across the whole strip
+    let length = LENGTH as f32 * TILE_SIZE.x;
+    let distance = (time.elapsed_seconds() * SPEED * TILE_SIZE.x) % (2.0 * length);
+    let offset = length / 2.0 - (distance - length).abs();
+    for mut transform in tickers.iter_mut() {
+        transform.translation.x = offset;
+    }
+}
@@ -0,0 +1,62 @@
+//! Like animation_custom_shader.rs, but with the built-in tile animations instead of custom
+//! shader code: tiles with index 6 cycle through the tiles 6, 7 and 8, five frames per second.
+//! The map is never changed (and re-uploaded) after startup.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2},
+    prelude::*,
+    window::PresentMode,
+};
+use bevy_fast_tilemap::prelude::*;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1820., 920.).into(),
+                    // disable vsync so we can see the raw FPS speed
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let (l, h) = (32 - 10, 32 + 10);
+    let map = Map::builder(
+        uvec2(64, 64),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .with_tile_animation(6, &[6, 7, 8], 5.0)
+    .build_and_set(|p| {
+        if p.x >= l && p.x <= h && p.y >= l && p.y <= h {
+            6
+        } else {
+            (p.x + p.y) % 4 + 1
+        }
+    });
+
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+}
@@ -0,0 +1,142 @@
+//! Checks the definition of `ExtractIn::tile_uv` and `ExtractIn::tile_offset_texels`:
+//! Every tile gets a red crosshair at its center (`tile_uv` (0.5, 0.5)) and a green outline
+//! along its border, one atlas pixel wide.
+//! Shown for a plain map, an axonometric map, a map with a different world tile size and a
+//! scaled and rotated transform, and a pattern map.
+
+use bevy::{
+    math::{uvec2, vec2, vec3},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+#[derive(Clone, TypePath, Default)]
+struct TileUvCustomization;
+
+impl Customization for TileUvCustomization {
+    const SHADER_HANDLE: Handle<Shader> =
+        Handle::weak_from_u128(0x51c0f7d2a8e34b6c9d1e2f3a4b5c6d7e);
+    type UserData = DefaultUserData;
+    type ExtraBindings = NoExtraBindings;
+
+    fn custom_shader_code() -> String {
+        r#"
+        struct UserData {
+            dummy: u32,
+        };
+
+        fn sample_tile(in: ExtractIn) -> vec4<f32> {
+            var color = sample_tile_at(in.tile_index, in.tile_position, in.tile_offset_texels);
+            var uv = in.tile_uv;
+            if any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) {
+                // Overhang
+                return color;
+            }
+
+            // One atlas pixel from the border
+            var texels = min(uv, 1.0 - uv) * map.tile_size;
+            if min(texels.x, texels.y) < 1.0 {
+                return vec4<f32>(0.0, 1.0, 0.0, 1.0);
+            }
+
+            var d = abs(uv - 0.5);
+            if min(d.x, d.y) < 0.02 && max(d.x, d.y) < 0.25 {
+                return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+            }
+            return color;
+        }
+        "#
+        .to_string()
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            MouseControlsCameraPlugin,
+            CustomFastTileMapPlugin::<TileUvCustomization>::default(),
+        ))
+        .add_systems(Startup, startup)
+        .run();
+}
+
+fn spawn(
+    commands: &mut Commands,
+    materials: &mut Assets<Map<TileUvCustomization>>,
+    map: Map<TileUvCustomization>,
+    transform: Transform,
+) {
+    commands.spawn(MapBundleManaged {
+        material: materials.add(map),
+        transform,
+        ..default()
+    });
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map<TileUvCustomization>>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let map = Map::builder(
+        uvec2(8, 8),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .build_and_set(|_| 2);
+    spawn(
+        &mut commands,
+        &mut materials,
+        map,
+        Transform::from_xyz(-300.0, 150.0, 0.0),
+    );
+
+    let map = Map::builder(uvec2(6, 6), asset_server.load("iso.png"), vec2(40., 20.))
+        .with_projection(AXONOMETRIC)
+        .build_and_set(|_| 1);
+    spawn(
+        &mut commands,
+        &mut materials,
+        map,
+        Transform::from_xyz(150.0, 150.0, 0.0),
+    );
+
+    // Atlas pixels are no longer world pixels here, tile_uv still spans each tile
+    let map = Map::builder(
+        uvec2(5, 5),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .with_world_tile_size(vec2(24., 24.))
+    .build_and_set(|_| 3);
+    spawn(
+        &mut commands,
+        &mut materials,
+        map,
+        Transform::from_xyz(-300.0, -150.0, 0.0)
+            .with_rotation(Quat::from_rotation_z(0.3))
+            .with_scale(vec3(1.5, 0.8, 1.0)),
+    );
+
+    // Pattern tiles: tile_uv is relative to the map tile, not to the 4x4 tiles pattern
+    let map = Map::builder(
+        uvec2(8, 8),
+        asset_server.load("patterns.png"),
+        vec2(64., 64.),
+    )
+    .with_atlas_tile_size_factor(4)
+    .build_and_set(|p| p.x % 4);
+    spawn(
+        &mut commands,
+        &mut materials,
+        map,
+        Transform::from_xyz(200.0, -150.0, 0.0).with_scale(vec3(0.4, 0.4, 1.0)),
+    );
+}
@@ -0,0 +1,50 @@
+//! A map made with Tiled (feature `tiled`), spawned with one map per tile layer.
+//!
+//! Loads `assets/tiled/sample.tmx` by default. Other maps can be given as asset path, eg. the
+//! `desert.tmx` example map of Tiled after copying it (and `tmw_desert_spacing.png`) to
+//! `assets/tiled/`:
+//!
+//! ```sh
+//! cargo run --example tiled -- tiled/desert.tmx
+//! ```
+
+use bevy::prelude::*;
+use bevy_fast_tilemap::prelude::*;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+            TiledMapPlugin,
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, log_objects)
+        .run();
+}
+
+fn startup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn(Camera2dBundle::default());
+
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "tiled/sample.tmx".to_string());
+    commands.spawn(TiledMapBundle {
+        tiled_map: asset_server.load(path),
+        ..default()
+    });
+}
+
+/// Object layers are available once the layers are spawned.
+fn log_objects(objects: Query<&TiledObjects, Added<TiledObjects>>) {
+    for layer in objects.iter().flat_map(|objects| &objects.0) {
+        for object in &layer.objects {
+            info!("{}: {} at {:?}", layer.name, object.name, object.rect);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! A tank drives in circles over an iso map and leaves tracks in the overlay canvas of the map,
+//! with an occasional scorch mark. Rain slowly washes the tracks away again.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+/// Radius of the circle the tank drives, in world units
+const ROUTE_RADIUS: f32 = 300.0;
+
+#[derive(Component)]
+struct Tank;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            FastTileMapPlugin::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, (drive, paint_tracks).chain())
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let map = Map::builder(uvec2(32, 32), asset_server.load("iso.png"), vec2(40., 20.))
+        .with_projection(AXONOMETRIC)
+        .with_overlay_canvas(8)
+        .build_and_set(|p| ((p.x + p.y) % 2) + 1);
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+
+    commands.spawn((
+        Tank,
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgb(0.2, 0.4, 0.1),
+                custom_size: Some(vec2(16.0, 10.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(ROUTE_RADIUS, 0.0, 1.0),
+            ..default()
+        },
+    ));
+}
+
+fn drive(time: Res<Time>, mut tanks: Query<&mut Transform, With<Tank>>) {
+    let angle = time.elapsed_seconds() * 0.5;
+    for mut transform in tanks.iter_mut() {
+        let position = vec2(angle.cos(), angle.sin() * 0.5) * ROUTE_RADIUS;
+        transform.translation = position.extend(1.0);
+        transform.rotation = Quat::from_rotation_z(angle + std::f32::consts::FRAC_PI_2);
+    }
+}
+
+/// Paint tracks under the tank, all painting happens on the CPU side copy of the canvas,
+/// only the touched rows are uploaded.
+fn paint_tracks(
+    time: Res<Time>,
+    tanks: Query<&Transform, With<Tank>>,
+    maps: Query<&Handle<Map>>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    let Some(map) = maps.get_single().ok().and_then(|h| materials.get_mut(h)) else {
+        return;
+    };
+
+    // Rain
+    let rain = Color::srgba(0.0, 0.0, 0.0, 0.2 * time.delta_seconds());
+    let t = time.elapsed_seconds();
+    let drop = vec2((t * 731.0).sin(), (t * 537.0).cos()) * ROUTE_RADIUS * 1.2;
+    map.paint_circle(drop, 60.0, rain, CanvasBlend::Erase);
+
+    for transform in tanks.iter() {
+        let center = transform.translation.truncate();
+        let side = transform.rotation * Vec3::X * 5.0;
+        let track = Color::srgba(0.25, 0.18, 0.1, 0.3);
+        for offset in [side, -side] {
+            map.paint_circle(center + offset.truncate(), 2.5, track, CanvasBlend::Over);
+        }
+
+        // Now and then something explodes next to the tank
+        if (t % 3.0) < time.delta_seconds() {
+            let scorch = Color::srgba(0.05, 0.05, 0.05, 0.8);
+            let impact = center + side.truncate() * 6.0;
+            map.paint_circle(impact, 18.0, scorch, CanvasBlend::Over);
+        }
+    }
+}
@@ -1,8 +1,7 @@
 //! Example illustrating alternative map initialization and live updates.
-//! We're maintaining a 1024x1024 map here that is changed every frame, so
-//! thats megabytes of updates every frame we need to refresh on the CPU.
-//! For this reason, this example will run with much lower FPS on most machines than the other
-//! ones.
+//! We're maintaining a 1024x1024 map here that is changed every frame.
+//! Only the rows written in a frame are uploaded to the GPU (see `Map::pending_tile_rows`),
+//! not the whole 4MB of tiles.
 //!
 //! Compiling this with --release can make a huge difference (for me 30 vs 200 FPS)
 
@@ -34,9 +33,9 @@ fn main() {
                 ..default()
             }),
             LogDiagnosticsPlugin::default(),
-            FrameTimeDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
             //WorldInspectorPlugin::new(),
-            MouseControlsCameraPlugin::default(),
+            MouseControlsCameraPlugin,
             FastTileMapPlugin::default(),
         ))
         .add_systems(Startup, startup)
@@ -61,6 +60,8 @@ fn startup(
         // Tile Size
         vec2(16., 16.),
     )
+    // Tile size and padding are read from pixel_tiles_16.tiles.ron
+    .with_atlas_metadata()
     .build_and_set(|_| 2);
 
     commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
@@ -79,10 +80,7 @@ fn change_map(mut materials: ResMut<Assets<Map>>, maps: Query<&Handle<Map>>) {
         let y_min = rng.gen_range(0..m.size().y - k);
         let i = rng.gen_range(1..12);
 
-        for y in y_min..y_min + k {
-            for x in x_min..x_min + k {
-                m.set(x, y, i);
-            }
-        }
+        // A row at a time, see the bulk_updates example for how this compares to `set()`
+        m.fill_rect(uvec2(x_min, y_min), uvec2(x_min + k, y_min + k), i);
     }
 } // fn change_map
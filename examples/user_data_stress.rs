@@ -0,0 +1,154 @@
+/*!
+Stress test for tiles and user data changed in the same frame: Every frame, a marker tile moves
+across a 512x512 map and the user data follows it. The shader paints tiles magenta wherever the
+two disagree, so any frame rendering the user data of one frame with the tiles of another
+shows up as a magenta flash.
+
+Runs without vsync, the window title shows the number of frames rendered so far.
+*/
+
+// `#[derive(ShaderType)]` emits never-called `check` functions.
+#![allow(dead_code)]
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2},
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderType},
+    window::PresentMode,
+};
+use bevy_fast_tilemap::prelude::*;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+const MAP_SIZE: u32 = 512;
+/// Bit of the marker tile, the other bits are the atlas index
+const MARKER: u32 = 0x1000;
+
+#[derive(Debug, Clone, Default, Reflect, AsBindGroup, ShaderType)]
+struct UserData {
+    marker_position: UVec2,
+}
+
+#[derive(Clone, TypePath, Default)]
+struct StressCustomization;
+
+impl Customization for StressCustomization {
+    const SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x6b2e_90d4_1f3a_7c58);
+    type UserData = UserData;
+    type ExtraBindings = NoExtraBindings;
+
+    fn custom_shader_code() -> String {
+        r#"
+        struct UserData {
+            marker_position: vec2<u32>,
+        };
+
+        fn sample_tile(in: ExtractIn) -> vec4<f32> {
+            var at_marker = all(in.tile_position == vec2<i32>(user_data.marker_position));
+            var is_marker = (in.tile_index & 0x1000u) != 0u;
+            if at_marker != is_marker {
+                // Tiles and user data are from different frames
+                return vec4<f32>(1.0, 0.0, 1.0, 1.0);
+            }
+            if is_marker {
+                return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+            }
+            return sample_tile_at(in.tile_index, in.tile_position, in.tile_offset_texels);
+        }
+        "#
+        .to_string()
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            CustomFastTileMapPlugin::<StressCustomization>::default(),
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, (move_marker, move_user_data).chain())
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map<StressCustomization>>>,
+) {
+    commands.spawn(Camera2dBundle {
+        // Show the whole map
+        projection: OrthographicProjection {
+            scale: 8.0,
+            ..default()
+        },
+        ..default()
+    });
+
+    let map = Map::<StressCustomization>::builder(
+        uvec2(MAP_SIZE, MAP_SIZE),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .build_and_set(|p| (p.x / 16 + p.y / 16) % 4 + 1);
+
+    commands.spawn(MapBundleManaged {
+        material: materials.add(map),
+        ..default()
+    });
+}
+
+/// Position of the marker in frame `frame`, moving diagonally so each frame writes other rows
+fn marker_position(frame: u32) -> UVec2 {
+    uvec2(frame % MAP_SIZE, (frame * 7) % MAP_SIZE)
+}
+
+/// Move the marker tile in the map data.
+fn move_marker(
+    mut materials: ResMut<Assets<Map<StressCustomization>>>,
+    maps: Query<&Handle<Map<StressCustomization>>>,
+    mut frame: Local<u32>,
+    mut windows: Query<&mut Window>,
+) {
+    let (old, new) = (marker_position(*frame), marker_position(*frame + 1));
+    *frame += 1;
+    for handle in maps.iter() {
+        let Some(map) = materials.get_mut(handle) else {
+            continue;
+        };
+        let mut m = map.indexer_mut();
+        let tile = m.at(old.x, old.y);
+        m.set(old.x, old.y, tile & !MARKER);
+        let tile = m.at(new.x, new.y);
+        m.set(new.x, new.y, tile | MARKER);
+    }
+    for mut window in windows.iter_mut() {
+        window.title = format!("user_data_stress: frame {}", *frame);
+    }
+}
+
+/// Move the user data along, in another system of the same frame.
+fn move_user_data(
+    mut materials: ResMut<Assets<Map<StressCustomization>>>,
+    maps: Query<&Handle<Map<StressCustomization>>>,
+    mut frame: Local<u32>,
+) {
+    *frame += 1;
+    for handle in maps.iter() {
+        if let Some(map) = materials.get_mut(handle) {
+            map.user_data.marker_position = marker_position(*frame);
+        }
+    }
+}
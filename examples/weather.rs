@@ -0,0 +1,87 @@
+//! Weather overlay: Animated rain or snow over the whole view, without any particles or atlas.
+//! Press R for rain, S for snow, up/down to change intensity and left/right to change the wind.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    math::{uvec2, vec2},
+    prelude::*,
+    window::PresentMode,
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::Rng;
+
+#[path = "common/mouse_controls_camera.rs"]
+mod mouse_controls_camera;
+use mouse_controls_camera::MouseControlsCameraPlugin;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: String::from("Fast Tilemap example"),
+                    resolution: (1820., 920.).into(),
+                    // disable vsync so we can see the raw FPS speed
+                    present_mode: PresentMode::Immediate,
+                    ..default()
+                }),
+                ..default()
+            }),
+            LogDiagnosticsPlugin::default(),
+            FrameTimeDiagnosticsPlugin,
+            MouseControlsCameraPlugin,
+            FastTileMapPlugin::default(),
+            WeatherOverlayPlugin,
+        ))
+        .add_systems(Startup, startup)
+        .add_systems(Update, control_weather)
+        .run();
+}
+
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<Map>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    let mut rng = rand::thread_rng();
+    let map = Map::builder(
+        uvec2(64, 64),
+        asset_server.load("pixel_tiles_16.png"),
+        vec2(16., 16.),
+    )
+    .build_and_set(|_| rng.gen_range(1..5));
+    commands.spawn(MapBundleManaged::new(map, materials.as_mut()));
+
+    // This is all that is needed for the weather
+    commands.spawn(WeatherOverlay::rain(0.5));
+}
+
+fn control_weather(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut overlays: Query<&mut WeatherOverlay>,
+) {
+    let dt = time.delta_seconds();
+    for mut overlay in overlays.iter_mut() {
+        if keyboard.just_pressed(KeyCode::KeyR) {
+            overlay.kind = WeatherKind::Rain;
+        }
+        if keyboard.just_pressed(KeyCode::KeyS) {
+            overlay.kind = WeatherKind::Snow;
+        }
+        if keyboard.pressed(KeyCode::ArrowUp) {
+            overlay.intensity = (overlay.intensity + 0.5 * dt).min(1.0);
+        }
+        if keyboard.pressed(KeyCode::ArrowDown) {
+            overlay.intensity = (overlay.intensity - 0.5 * dt).max(0.0);
+        }
+        if keyboard.pressed(KeyCode::ArrowRight) {
+            overlay.wind += 0.5 * dt;
+        }
+        if keyboard.pressed(KeyCode::ArrowLeft) {
+            overlay.wind -= 0.5 * dt;
+        }
+    }
+}
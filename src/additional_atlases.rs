@@ -0,0 +1,195 @@
+//! Additional atlases of a map, for tiles that can not be merged into one atlas (eg. because
+//! they come from different asset packs).
+//!
+//! Bits 27 and 28 of tile values select the atlas of the tile (0 is the atlas given to the
+//! builder), see [`MapBuilder::with_additional_atlas`]:
+//!
+//! ```
+//! # use bevy::{math::{uvec2, vec2}, prelude::*};
+//! # use bevy_fast_tilemap::prelude::*;
+//! let mut map: Map = Map::builder(uvec2(4, 4), default(), vec2(16., 16.))
+//!     .with_additional_atlas(default(), vec2(32., 32.), Vec2::ZERO)
+//!     .build_and_set(|_| 0);
+//! let mut m = map.indexer_mut();
+//! m.set(1, 2, tile_in_atlas(1, 7) | TILE_FLIP_X);
+//! assert_eq!(m.index_at(1, 2), tile_in_atlas(1, 7));
+//! assert_eq!(map.atlas_of(tile_in_atlas(1, 7)), 1);
+//! ```
+
+use bevy::{prelude::*, render::render_resource::ShaderType};
+
+use super::{
+    map::Map,
+    map_builder::{MapBuilder, MapError},
+    map_uniform::MapUniform,
+    plugin::Customization,
+    tile_flags::TILE_FLAGS_MASK,
+};
+
+/// Maximum number of atlases of a map, including the atlas given to the builder.
+pub const MAX_ATLASES: u32 = 4;
+
+/// Lowest bit of the atlas selector in tile values.
+pub const TILE_ATLAS_SHIFT: u32 = 27;
+
+/// Bits of tile values that select the atlas of maps with additional atlases.
+/// Maps without additional atlases don't interpret them.
+pub const TILE_ATLAS_MASK: u32 = (MAX_ATLASES - 1) << TILE_ATLAS_SHIFT;
+
+/// Tile value of tile `index` of atlas `atlas` (0 for the atlas given to the builder).
+pub const fn tile_in_atlas(atlas: u32, index: u32) -> u32 {
+    (index & !(TILE_ATLAS_MASK | TILE_FLAGS_MASK)) | ((atlas << TILE_ATLAS_SHIFT) & TILE_ATLAS_MASK)
+}
+
+/// Tile layout of an additional atlas, as in the `Map` uniform.
+#[derive(ShaderType, Clone, Copy, Debug, Default, Reflect)]
+pub(crate) struct AtlasLayout {
+    /// Size of the atlas in pixels, zero until it is loaded
+    pub(crate) atlas_size: Vec2,
+    pub(crate) tile_size: Vec2,
+    pub(crate) inner_padding: Vec2,
+    pub(crate) outer_padding_topleft: Vec2,
+    pub(crate) outer_padding_bottomright: Vec2,
+    /// Number of tiles in the atlas, derived from its size
+    pub(crate) n_tiles: UVec2,
+}
+
+impl MapUniform {
+    /// Atlas of the given tile value, 0 for maps without additional atlases.
+    pub(crate) fn atlas_selector(&self, tile: u32) -> u32 {
+        match self.n_atlases {
+            0 | 1 => 0,
+            _ => (tile & TILE_ATLAS_MASK) >> TILE_ATLAS_SHIFT,
+        }
+    }
+
+    /// This uniform with the layout of additional atlas `atlas` (1 or above) instead of the
+    /// layout of the atlas, for mirroring the shader on the CPU.
+    pub(crate) fn for_atlas(&self, atlas: u32) -> MapUniform {
+        let layout = self.atlases[atlas as usize - 1];
+        MapUniform {
+            atlas_size: layout.atlas_size,
+            tile_size: layout.tile_size,
+            atlas_tile_size_factor: 1,
+            inner_padding: layout.inner_padding,
+            outer_padding_topleft: layout.outer_padding_topleft,
+            outer_padding_bottomright: layout.outer_padding_bottomright,
+            n_tiles: layout.n_tiles,
+            ..self.clone()
+        }
+    }
+
+    /// Measure additional atlas `atlas` (1 or above), see [`Self::update_atlas_size`].
+    fn update_additional_atlas_size(&mut self, atlas: u32, atlas_size: Vec2) -> bool {
+        let mut uniform = self.for_atlas(atlas);
+        if !uniform.update_atlas_size(atlas_size) {
+            return false;
+        }
+        let layout = &mut self.atlases[atlas as usize - 1];
+        layout.atlas_size = uniform.atlas_size;
+        layout.n_tiles = uniform.n_tiles;
+        true
+    }
+}
+
+impl<C: Customization> MapBuilder<C> {
+    /// Add an atlas with tiles of size `tile_size` (in pixels) and `inner_padding` between
+    /// them (the atlas has no outer padding), up to [`MAX_ATLASES`] including the atlas given
+    /// to the builder.
+    ///
+    /// Tiles select their atlas with bits 27 and 28 of their value, see [`tile_in_atlas`].
+    /// They are drawn texel for texel around the tile anchor point, so tiles larger than the
+    /// tile size of the map extend into the neighboring tiles (like inner padding, they are
+    /// only drawn there with overhangs).
+    /// Palette owner bits are limited to bits below 27, tile animations, emissive tiles etc.
+    /// apply to the tile index in all atlases.
+    /// The map waits for all atlases to load.
+    pub fn with_additional_atlas(
+        mut self,
+        atlas: Handle<Image>,
+        tile_size: Vec2,
+        inner_padding: Vec2,
+    ) -> Self {
+        let map = &mut self.map;
+        if map.additional_atlases.len() as u32 + 1 >= MAX_ATLASES {
+            warn!("A map can have at most {MAX_ATLASES} atlases, ignoring additional atlas");
+            return self;
+        }
+        let uniform = &mut map.map_uniform;
+        uniform.atlases[map.additional_atlases.len()] = AtlasLayout {
+            tile_size,
+            inner_padding,
+            ..default()
+        };
+        map.additional_atlases.push(atlas);
+        uniform.n_atlases = map.additional_atlases.len() as u32 + 1;
+
+        // The owner must not overlap the atlas selector
+        if (uniform.palette_owner_mask << uniform.palette_owner_shift) & TILE_ATLAS_MASK != 0 {
+            let (shift, bits) = (
+                uniform.palette_owner_shift,
+                uniform.palette_owner_mask.count_ones(),
+            );
+            map.set_palette_owner_bits(shift, bits);
+        }
+        self
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Additional atlases, see [`MapBuilder::with_additional_atlas`].
+    pub fn additional_atlases(&self) -> &[Handle<Image>] {
+        &self.additional_atlases
+    }
+
+    /// Atlas of the tile value `tile`, 0 for the atlas given to the builder (and for all tiles of
+    /// maps without additional atlases).
+    pub fn atlas_of(&self, tile: u32) -> u32 {
+        self.map_uniform.atlas_selector(tile)
+    }
+
+    /// Number of tiles in each direction of atlas `atlas` (0 for the atlas given to the builder),
+    /// zero until it is loaded.
+    pub fn atlas_n_tiles(&self, atlas: u32) -> UVec2 {
+        match atlas {
+            0 => self.map_uniform.n_tiles,
+            _ if atlas < self.map_uniform.n_atlases => {
+                self.map_uniform.atlases[atlas as usize - 1].n_tiles
+            }
+            _ => UVec2::ZERO,
+        }
+    }
+
+    /// Measure the additional atlases that are loaded, see [`Map::update`].
+    pub(crate) fn update_additional_atlases(&mut self, images: &Assets<Image>) -> bool {
+        let mut changed = false;
+        for (i, atlas) in self.additional_atlases.iter().enumerate() {
+            if let Some(image) = images.get(atlas) {
+                let size = image.size().as_vec2();
+                changed |= self
+                    .map_uniform
+                    .update_additional_atlas_size(i as u32 + 1, size);
+            }
+        }
+        changed
+    }
+
+    /// The checks of [`Map::validate`] for the additional atlases that are loaded.
+    pub(crate) fn validate_additional_atlases(
+        &self,
+        images: &Assets<Image>,
+    ) -> Result<(), MapError> {
+        for (i, atlas) in self.additional_atlases.iter().enumerate() {
+            let uniform = self.map_uniform.for_atlas(i as u32 + 1);
+            if uniform.tile_size.cmple(Vec2::ZERO).any() {
+                return Err(MapError::ZeroTileSize {
+                    tile_size: uniform.tile_size,
+                });
+            }
+            if let Some(image) = images.get(atlas) {
+                uniform.atlas_tile_count(image.size().as_vec2())?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use bevy::{ecs::entity::Entities, math::Vec3Swizzles, prelude::*, utils::HashSet};
+
+use super::{map::Map, plugin::Customization};
+
+/// What happens to entities anchored to a map that is despawned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum AnchorOrphanPolicy {
+    /// Despawn the anchored entity (recursively).
+    #[default]
+    Despawn,
+    /// Keep the anchored entity where it is, but remove its [`TileAnchor`].
+    Orphan,
+}
+
+/// Keeps the [`Transform`] of this entity at the center of `tile` of the given map,
+/// updated whenever the map entity moves or the map changes (eg. its projection).
+///
+/// The z-coordinate of the entity is left untouched.
+/// The anchored entity should not have a parent, as its transform is interpreted as global.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct TileAnchor {
+    /// Entity holding the map.
+    pub map: Entity,
+    pub tile: UVec2,
+    /// Offset from the tile center, in (local) world units of the map.
+    pub offset: Vec2,
+    pub on_map_despawn: AnchorOrphanPolicy,
+}
+
+impl Default for TileAnchor {
+    fn default() -> Self {
+        Self {
+            map: Entity::PLACEHOLDER,
+            tile: UVec2::ZERO,
+            offset: Vec2::ZERO,
+            on_map_despawn: default(),
+        }
+    }
+}
+
+impl TileAnchor {
+    pub fn new(map: Entity, tile: UVec2) -> Self {
+        Self {
+            map,
+            tile,
+            ..default()
+        }
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_orphan_policy(mut self, policy: AnchorOrphanPolicy) -> Self {
+        self.on_map_despawn = policy;
+        self
+    }
+}
+
+/// Update the transforms of [`TileAnchor`] entities.
+/// Only anchors whose anchor, map transform or map asset changed are touched.
+///
+/// Runs after transform propagation, so the global transform of the anchored entity
+/// is updated here as well.
+#[allow(clippy::type_complexity)]
+pub fn update_tile_anchors<C: Customization>(
+    mut ev_asset: EventReader<AssetEvent<Map<C>>>,
+    map_materials: Res<Assets<Map<C>>>,
+    maps: Query<(&Handle<Map<C>>, Ref<GlobalTransform>), Without<TileAnchor>>,
+    mut anchors: Query<(
+        Entity,
+        Ref<TileAnchor>,
+        &mut Transform,
+        &mut GlobalTransform,
+    )>,
+    entities: &Entities,
+    mut commands: Commands,
+) {
+    let modified: HashSet<_> = ev_asset
+        .read()
+        .filter_map(|ev| match ev {
+            AssetEvent::Added { id }
+            | AssetEvent::Modified { id }
+            | AssetEvent::LoadedWithDependencies { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (entity, anchor, mut transform, mut global) in anchors.iter_mut() {
+        let Ok((map_handle, map_transform)) = maps.get(anchor.map) else {
+            if !entities.contains(anchor.map) {
+                match anchor.on_map_despawn {
+                    AnchorOrphanPolicy::Despawn => commands.entity(entity).despawn_recursive(),
+                    AnchorOrphanPolicy::Orphan => {
+                        commands.entity(entity).remove::<TileAnchor>();
+                    }
+                }
+            }
+            // Otherwise the map has a different customization (or is not a map at all)
+            continue;
+        };
+
+        if !anchor.is_changed()
+            && !map_transform.is_changed()
+            && !modified.contains(&map_handle.id())
+        {
+            continue;
+        }
+
+        let Some(map) = map_materials.get(map_handle) else {
+            continue;
+        };
+
+        let local = map.tile_center_local(anchor.tile) + anchor.offset;
+        let world = map_transform.transform_point(local.extend(0.0));
+        transform.translation = world.xy().extend(transform.translation.z);
+        *global = GlobalTransform::from(*transform);
+    }
+}
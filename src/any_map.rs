@@ -0,0 +1,181 @@
+//! Operations on maps of any [`Customization`], for global changes such as dimming all maps
+//! while a menu is open, written once instead of once per customization.
+//!
+//! Every map entity gets an [`AnyMapHandle`] (by [`insert_any_map_handles`] of each
+//! [`crate::plugin::CustomFastTileMapPlugin`]), which gives access to its map as `dyn AnyMap`:
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_fast_tilemap::prelude::*;
+//! fn dim_all_maps(mut commands: Commands, maps: Query<&AnyMapHandle>) {
+//!     for map in maps.iter() {
+//!         map.modify(&mut commands, |map| map.set_color_filter(Color::srgb(0.4, 0.4, 0.4)));
+//!     }
+//! }
+//!
+//! fn log_map_memory(world: &mut World) {
+//!     let mut maps = world.query::<&AnyMapHandle>();
+//!     let images = world.resource::<Assets<Image>>();
+//!     let total: MapMemoryInfo = maps
+//!         .iter(world)
+//!         .filter_map(|handle| handle.get(world))
+//!         .map(|map| map.memory_info(images))
+//!         .sum();
+//!     info!("Maps use {} bytes on the GPU", total.gpu_bytes());
+//! }
+//! ```
+//!
+//! Entities sharing a map each have a handle, so operations are applied once per entity.
+
+use bevy::prelude::*;
+
+use super::{map::Map, memory::MapMemoryInfo, plugin::Customization};
+
+/// The parts of the [`Map`] API that don't depend on the [`Customization`],
+/// see [`AnyMapHandle`].
+pub trait AnyMap: Send + Sync + 'static {
+    fn map_size(&self) -> UVec2;
+
+    /// See [`Map::memory_info`].
+    fn memory_info(&self, images: &Assets<Image>) -> MapMemoryInfo;
+
+    /// See [`Map::is_loaded`].
+    fn is_loaded(&self, images: &Assets<Image>) -> bool;
+
+    /// See [`Map::set_color_filter`].
+    fn set_color_filter(&mut self, color: Color);
+    fn color_filter(&self) -> Color;
+
+    /// See [`Map::set_emissive_strength`].
+    fn set_emissive_strength(&mut self, strength: f32);
+    fn emissive_strength(&self) -> f32;
+
+    /// See [`Map::set_pixel_snap`].
+    fn set_pixel_snap(&mut self, pixel_snap: bool);
+
+    /// See [`Map::set_animation_speed`].
+    fn set_animation_speed(&mut self, speed: f32);
+    fn animation_speed(&self) -> f32;
+
+    /// See [`Map::set_param`].
+    fn set_param(&mut self, index: usize, value: Vec4);
+}
+
+impl<C: Customization> AnyMap for Map<C> {
+    fn map_size(&self) -> UVec2 {
+        Map::map_size(self)
+    }
+
+    fn memory_info(&self, images: &Assets<Image>) -> MapMemoryInfo {
+        Map::memory_info(self, images)
+    }
+
+    fn is_loaded(&self, images: &Assets<Image>) -> bool {
+        Map::is_loaded(self, images)
+    }
+
+    fn set_color_filter(&mut self, color: Color) {
+        Map::set_color_filter(self, color)
+    }
+
+    fn color_filter(&self) -> Color {
+        Map::color_filter(self)
+    }
+
+    fn set_emissive_strength(&mut self, strength: f32) {
+        Map::set_emissive_strength(self, strength)
+    }
+
+    fn emissive_strength(&self) -> f32 {
+        Map::emissive_strength(self)
+    }
+
+    fn set_pixel_snap(&mut self, pixel_snap: bool) {
+        Map::set_pixel_snap(self, pixel_snap)
+    }
+
+    fn set_animation_speed(&mut self, speed: f32) {
+        Map::set_animation_speed(self, speed)
+    }
+
+    fn animation_speed(&self) -> f32 {
+        Map::animation_speed(self)
+    }
+
+    fn set_param(&mut self, index: usize, value: Vec4) {
+        Map::set_param(self, index, value)
+    }
+}
+
+/// Handle of the map of an entity without its [`Customization`], see [`crate::any_map`].
+#[derive(Component, Clone, Copy)]
+pub struct AnyMapHandle {
+    id: UntypedAssetId,
+    get: for<'w> fn(&'w World, UntypedAssetId) -> Option<&'w dyn AnyMap>,
+    get_mut: for<'w> fn(&'w mut World, UntypedAssetId) -> Option<&'w mut dyn AnyMap>,
+}
+
+impl AnyMapHandle {
+    pub fn new<C: Customization>(id: AssetId<Map<C>>) -> Self {
+        Self {
+            id: id.untyped(),
+            get: get_map::<C>,
+            get_mut: get_map_mut::<C>,
+        }
+    }
+
+    /// Asset id of the map.
+    pub fn id(&self) -> UntypedAssetId {
+        self.id
+    }
+
+    /// The map, if it (still) exists.
+    pub fn get<'w>(&self, world: &'w World) -> Option<&'w dyn AnyMap> {
+        (self.get)(world, self.id)
+    }
+
+    /// The map, if it (still) exists. Like [`Assets::get_mut`], this marks it as modified.
+    pub fn get_mut<'w>(&self, world: &'w mut World) -> Option<&'w mut dyn AnyMap> {
+        (self.get_mut)(world, self.id)
+    }
+
+    /// Modify the map with the next command flush (if it still exists then),
+    /// for modifying maps from regular systems.
+    pub fn modify(self, commands: &mut Commands, f: impl FnOnce(&mut dyn AnyMap) + Send + 'static) {
+        commands.add(move |world: &mut World| {
+            if let Some(map) = self.get_mut(world) {
+                f(map);
+            }
+        });
+    }
+}
+
+fn get_map<C: Customization>(world: &World, id: UntypedAssetId) -> Option<&dyn AnyMap> {
+    let map = world
+        .get_resource::<Assets<Map<C>>>()?
+        .get(id.typed::<Map<C>>())?;
+    Some(map)
+}
+
+fn get_map_mut<C: Customization>(world: &mut World, id: UntypedAssetId) -> Option<&mut dyn AnyMap> {
+    let maps = world.get_resource_mut::<Assets<Map<C>>>()?.into_inner();
+    Some(maps.get_mut(id.typed::<Map<C>>())?)
+}
+
+impl std::fmt::Debug for AnyMapHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("AnyMapHandle").field(&self.id).finish()
+    }
+}
+
+/// Insert (or update) the [`AnyMapHandle`] of entities with a `Handle<Map<C>>`.
+pub fn insert_any_map_handles<C: Customization>(
+    mut commands: Commands,
+    maps: Query<(Entity, &Handle<Map<C>>), Changed<Handle<Map<C>>>>,
+) {
+    for (entity, handle) in maps.iter() {
+        commands
+            .entity(entity)
+            .insert(AnyMapHandle::new::<C>(handle.id()));
+    }
+}
@@ -0,0 +1,113 @@
+use bevy::{
+    prelude::*,
+    tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task, TaskPool},
+};
+
+use super::{
+    map::{positions, Map, MapLoading},
+    map_builder::{MapBuilder, MapError},
+    plugin::{Customization, NoCustomization},
+};
+
+/// Tiles of a map being computed on the [`AsyncComputeTaskPool`], see
+/// [`MapBuilder::build_and_set_async`].
+///
+/// While a map entity has this component, it stays [`MapLoading`] (so it gets no mesh and is
+/// not rendered). Once the task finished, the tiles are put into the map
+/// (see [`Map::replace_texture`]) and this component is removed.
+#[derive(Component)]
+pub struct MapAsyncFill<C: Customization = NoCustomization> {
+    task: Task<Vec<u32>>,
+    _customization: std::marker::PhantomData<C>,
+}
+
+impl<C: Customization> MapAsyncFill<C> {
+    /// Fill the map of the entity with the result of `task`, which must be the tiles of the
+    /// whole map in row-major order (`y * size.x + x`).
+    pub fn new(task: Task<Vec<u32>>) -> Self {
+        Self {
+            task,
+            _customization: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<C: Customization> MapBuilder<C> {
+    /// Build the map right away and compute its tiles with `initializer` (which receives the
+    /// position of each tile, like [`Self::build_and_set`]) on the [`AsyncComputeTaskPool`],
+    /// so huge maps don't block a frame.
+    /// Spawn the returned [`MapAsyncFill`] on the map entity, the map is loaded once its tiles
+    /// and its atlas are available.
+    ///
+    /// ```
+    /// # use bevy::{math::{uvec2, vec2}, prelude::*};
+    /// # use bevy_fast_tilemap::prelude::*;
+    /// fn startup(mut commands: Commands, mut materials: ResMut<Assets<Map>>) {
+    ///     let (map, fill) = Map::builder(uvec2(4096, 4096), default(), vec2(16.0, 16.0))
+    ///         .build_and_set_async(|p| (p.x ^ p.y) % 4);
+    ///     commands.spawn((MapBundleManaged::new(map, materials.as_mut()), fill));
+    /// }
+    /// ```
+    pub fn build_and_set_async<F>(self, mut initializer: F) -> (Map<C>, MapAsyncFill<C>)
+    where
+        F: FnMut(UVec2) -> u32 + Send + 'static,
+    {
+        let map = self.build();
+        let size = map.map_size();
+        let pool = AsyncComputeTaskPool::get_or_init(TaskPool::default);
+        let task =
+            pool.spawn(async move { positions(size).map(&mut initializer).collect::<Vec<_>>() });
+        (map, MapAsyncFill::new(task))
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Replace all tiles of the map with `tiles` in row-major order (`y * size.x + x`),
+    /// eg. computed on another thread. Fails if the number of tiles doesn't match the map size.
+    pub fn replace_texture(&mut self, tiles: Vec<u32>) -> Result<(), MapError> {
+        let size = self.map_size();
+        let expected = (size.x * size.y) as usize;
+        if tiles.len() != expected {
+            return Err(MapError::WrongTileCount {
+                expected,
+                actual: tiles.len(),
+            });
+        }
+        // Uniformly filled maps don't need their data on the GPU
+        match tiles.first() {
+            Some(&first) if tiles.iter().all(|&tile| tile == first) => {
+                self.set_uniform_tile(Some(first));
+            }
+            _ => {
+                self.map_uniform.has_uniform_tile = 0;
+                self.map_texture = tiles;
+                if let Some(ticks) = self.change_ticks.as_mut() {
+                    ticks.record_all();
+                }
+                self.mark_written(URect::from_corners(UVec2::ZERO, size));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Put the tiles of finished [`MapAsyncFill`] tasks into their maps.
+pub fn apply_async_map_fills<C: Customization>(
+    mut commands: Commands,
+    mut map_materials: ResMut<Assets<Map<C>>>,
+    mut fills: Query<(Entity, &Handle<Map<C>>, &mut MapAsyncFill<C>)>,
+) {
+    for (entity, map_handle, mut fill) in fills.iter_mut() {
+        let Some(tiles) = block_on(future::poll_once(&mut fill.task)) else {
+            continue;
+        };
+        commands.entity(entity).remove::<MapAsyncFill<C>>();
+        let Some(map) = map_materials.get_mut(map_handle) else {
+            warn!("Map entity {:?} finished filling, but has no map", entity);
+            continue;
+        };
+        if let Err(e) = map.replace_texture(tiles) {
+            error!("Map entity {:?} can not be filled: {}", entity, e);
+        }
+    }
+}
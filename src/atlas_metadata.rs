@@ -0,0 +1,189 @@
+use std::fmt;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AssetPath, AsyncReadExt, LoadContext, LoadState},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use super::{map::Map, plugin::Customization};
+
+/// Tile layout of an atlas, loaded from a `.tiles.ron` sidecar file next to the atlas image,
+/// see [`crate::map_builder::MapBuilder::with_atlas_metadata`].
+///
+/// Example `pixel_tiles_16.tiles.ron` for `pixel_tiles_16.png`:
+/// ```ron
+/// (
+///     tile_size: (16.0, 16.0),
+///     inner_padding: (0.0, 0.0),
+///     n_tiles: Some((32, 32)),
+/// )
+/// ```
+#[derive(Asset, TypePath, Debug, Clone, PartialEq, Deserialize)]
+pub struct AtlasMetadata {
+    /// Size of each tile in the atlas, in pixels.
+    pub tile_size: Vec2,
+    #[serde(default)]
+    pub inner_padding: Vec2,
+    #[serde(default)]
+    pub outer_padding_topleft: Vec2,
+    #[serde(default)]
+    pub outer_padding_bottomright: Vec2,
+    /// Expected number of tiles in the atlas, a warning is logged if the atlas does not match.
+    #[serde(default)]
+    pub n_tiles: Option<UVec2>,
+}
+
+/// Loading state of the [`AtlasMetadata`] of a map.
+#[derive(Debug, Clone, Default)]
+pub(crate) enum AtlasMetadataState {
+    /// Not requested (or already applied or missing), builder values are used.
+    #[default]
+    Unused,
+    /// Requested, but loading has not yet started.
+    Requested,
+    Loading(Handle<AtlasMetadata>),
+    /// Applied, the expected number of tiles is checked once the atlas is loaded.
+    Applied(Option<UVec2>),
+}
+
+impl AtlasMetadataState {
+    /// Whether the map has to wait for its metadata before it can be loaded.
+    pub(crate) fn is_pending(&self) -> bool {
+        matches!(self, Self::Requested | Self::Loading(_))
+    }
+}
+
+#[derive(Debug)]
+pub enum AtlasMetadataLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for AtlasMetadataLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read atlas metadata: {e}"),
+            Self::Ron(e) => write!(f, "could not parse atlas metadata: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for AtlasMetadataLoaderError {}
+
+/// Loader for `.tiles.ron` files.
+#[derive(Default)]
+pub struct AtlasMetadataLoader;
+
+impl AssetLoader for AtlasMetadataLoader {
+    type Asset = AtlasMetadata;
+    type Settings = ();
+    type Error = AtlasMetadataLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<AtlasMetadata, AtlasMetadataLoaderError> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(AtlasMetadataLoaderError::Io)?;
+        ron::de::from_bytes(&bytes).map_err(AtlasMetadataLoaderError::Ron)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tiles.ron"]
+    }
+}
+
+/// Path of the metadata sidecar of the atlas at `atlas`, ie. with the extension replaced by
+/// `tiles.ron`.
+pub fn atlas_metadata_path(atlas: &AssetPath) -> AssetPath<'static> {
+    AssetPath::from(atlas.path().with_extension("tiles.ron"))
+        .with_source(atlas.source().clone_owned())
+}
+
+impl<C: Customization> Map<C> {
+    /// Use the tile size and padding of `metadata` instead of the builder values.
+    pub(crate) fn apply_atlas_metadata(&mut self, metadata: &AtlasMetadata) {
+        let uniform = &mut self.map_uniform;
+        // Keep the world tile size if it was set explicitly
+        if uniform.world_tile_size == uniform.tile_size {
+            uniform.world_tile_size = metadata.tile_size;
+        }
+        uniform.tile_size = metadata.tile_size;
+        uniform.inner_padding = metadata.inner_padding;
+        uniform.outer_padding_topleft = metadata.outer_padding_topleft;
+        uniform.outer_padding_bottomright = metadata.outer_padding_bottomright;
+        self.update_world_size();
+    }
+
+    /// Warn if the loaded atlas does not have the number of tiles given in its metadata.
+    pub(crate) fn validate_atlas_metadata(&mut self) {
+        if let AtlasMetadataState::Applied(Some(expected)) = self.atlas_metadata {
+            let n_tiles = self.map_uniform.n_tiles;
+            if n_tiles != expected {
+                warn!("Atlas has {n_tiles} tiles, but its metadata expects {expected}");
+            }
+        }
+        self.atlas_metadata = AtlasMetadataState::Unused;
+    }
+}
+
+/// Load the [`AtlasMetadata`] of maps built with
+/// [`crate::map_builder::MapBuilder::with_atlas_metadata`] and apply it to them,
+/// before they finish loading in [`crate::map::update_loading_maps`].
+/// If there is no sidecar file, the builder values are kept.
+pub fn load_atlas_metadata<C: Customization>(
+    asset_server: Res<AssetServer>,
+    metadata: Res<Assets<AtlasMetadata>>,
+    mut maps: ResMut<Assets<Map<C>>>,
+) {
+    let pending: Vec<_> = maps
+        .iter()
+        .filter(|(_, map)| map.atlas_metadata.is_pending())
+        .map(|(id, _)| id)
+        .collect();
+
+    for id in pending {
+        let map = maps.get(id).unwrap();
+        let state = match &map.atlas_metadata {
+            AtlasMetadataState::Requested => {
+                let Some(atlas_path) = asset_server.get_path(&map.atlas_texture) else {
+                    warn!("Atlas of map {id} has no path, can not load its atlas metadata");
+                    maps.get_mut(id).unwrap().atlas_metadata = AtlasMetadataState::Unused;
+                    continue;
+                };
+                let path = atlas_metadata_path(&atlas_path);
+                AtlasMetadataState::Loading(asset_server.load(path))
+            }
+            AtlasMetadataState::Loading(handle) => {
+                let handle = handle.clone();
+                if let Some(loaded) = metadata.get(&handle) {
+                    info!(
+                        "Using tile size {} and padding {} / {} / {} from {:?} for map {id} \
+                        (instead of the builder values)",
+                        loaded.tile_size,
+                        loaded.inner_padding,
+                        loaded.outer_padding_topleft,
+                        loaded.outer_padding_bottomright,
+                        handle.path(),
+                    );
+                    maps.get_mut(id).unwrap().apply_atlas_metadata(loaded);
+                    AtlasMetadataState::Applied(loaded.n_tiles)
+                } else if let Some(LoadState::Failed(e)) = asset_server.get_load_state(&handle) {
+                    debug!("No atlas metadata for map {id}, using the builder values: {e}");
+                    AtlasMetadataState::Unused
+                } else {
+                    // Still loading, don't touch the map so it is not re-extracted
+                    continue;
+                }
+            }
+            AtlasMetadataState::Unused | AtlasMetadataState::Applied(_) => continue,
+        };
+        maps.get_mut(id).unwrap().atlas_metadata = state;
+    }
+}
@@ -0,0 +1,159 @@
+use std::{fmt, sync::Arc};
+
+use bevy::{math::ivec2, prelude::*};
+
+use super::{map::Map, neighborhood::Neighborhood, plugin::Customization};
+
+/// Rule picking the tile value of a tile from the terrain of it and its neighbors,
+/// see [`Map::set_autotile`].
+#[derive(Clone)]
+pub struct AutoTiler {
+    rule: Arc<dyn Fn(&Neighborhood) -> Option<u32> + Send + Sync>,
+}
+
+impl AutoTiler {
+    /// Autotiler calling `rule` with the terrain of each tile and its neighbors.
+    /// Returning `None` keeps the tile value.
+    pub fn new(rule: impl Fn(&Neighborhood) -> Option<u32> + Send + Sync + 'static) -> Self {
+        Self {
+            rule: Arc::new(rule),
+        }
+    }
+
+    /// Autotiler for 16 tile variants per terrain:
+    /// The tile value is `first(terrain)` plus a bit for each cardinal neighbor of the same
+    /// terrain, `1` for `y - 1`, `2` for `x + 1`, `4` for `y + 1` and `8` for `x - 1`.
+    pub fn cardinal(first: impl Fn(u32) -> u32 + Send + Sync + 'static) -> Self {
+        Self::new(move |terrain| {
+            let center = terrain.center();
+            let mask = [ivec2(0, -1), ivec2(1, 0), ivec2(0, 1), ivec2(-1, 0)]
+                .iter()
+                .enumerate()
+                .filter(|(_, offset)| terrain.get(**offset) == center)
+                .fold(0, |mask, (i, _)| mask | 1 << i);
+            Some(first(center) + mask)
+        })
+    }
+
+    /// Tile value for the center of `terrain`, `None` to keep the current value.
+    pub fn tile(&self, terrain: &Neighborhood) -> Option<u32> {
+        (self.rule)(terrain)
+    }
+}
+
+impl fmt::Debug for AutoTiler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AutoTiler").finish_non_exhaustive()
+    }
+}
+
+/// Where an [`AutoTiler`] gets the terrain of each tile from.
+#[derive(Clone)]
+pub enum TerrainSource {
+    /// Terrain computed from the tile value. This has to give the same terrain for all values
+    /// the autotiler writes for a terrain, eg. `|v| v / 16` for [`AutoTiler::cardinal`].
+    Tiles(Arc<dyn Fn(u32) -> u32 + Send + Sync>),
+}
+
+impl TerrainSource {
+    pub fn tiles(terrain: impl Fn(u32) -> u32 + Send + Sync + 'static) -> Self {
+        Self::Tiles(Arc::new(terrain))
+    }
+
+    fn terrain(&self, value: u32) -> u32 {
+        match self {
+            Self::Tiles(f) => f(value),
+        }
+    }
+}
+
+impl fmt::Debug for TerrainSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tiles(_) => f.write_str("TerrainSource::Tiles(..)"),
+        }
+    }
+}
+
+/// Autotiling state of a map, see [`Map::set_autotile`].
+#[derive(Debug, Clone)]
+pub(crate) struct MapAutoTile {
+    tiler: AutoTiler,
+    terrain: TerrainSource,
+    /// Tiles written since the last [`Map::apply_autotile`] (`max` exclusive)
+    pending: Option<URect>,
+}
+
+impl MapAutoTile {
+    pub(crate) fn mark(&mut self, rect: URect) {
+        self.pending = Some(self.pending.map_or(rect, |pending| pending.union(rect)));
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Automatically correct tile values with `tiler` whenever tiles are written.
+    /// With the plugin, [`apply_autotiling`] re-runs it over the written tiles expanded by one
+    /// tile before the map is uploaded, so no frame shows the written values before autotiling.
+    /// The whole map is autotiled once after this call.
+    pub fn set_autotile(&mut self, tiler: AutoTiler, terrain: TerrainSource) {
+        let size = self.map_size();
+        self.autotile = Some(MapAutoTile {
+            tiler,
+            terrain,
+            pending: Some(URect::from_corners(UVec2::ZERO, size)),
+        });
+    }
+
+    /// Stop autotiling, see [`Self::set_autotile`].
+    pub fn clear_autotile(&mut self) {
+        self.autotile = None;
+    }
+
+    /// Whether tiles were written since the last autotiling.
+    pub fn needs_autotile(&self) -> bool {
+        self.autotile
+            .as_ref()
+            .is_some_and(|autotile| autotile.pending.is_some())
+    }
+
+    /// Autotile the tiles written since the last call (and their neighbors) now.
+    /// This is done by [`apply_autotiling`] with the plugin, call it directly for maps that
+    /// are not assets or need the corrected values right away.
+    ///
+    /// Writes made by the autotiler itself don't trigger further autotiling.
+    pub fn apply_autotile(&mut self) {
+        // Taken while writing, so the autotiler's writes are not marked as pending
+        let Some(mut autotile) = self.autotile.take() else {
+            return;
+        };
+        if let Some(pending) = autotile.pending.take() {
+            let region = URect::from_corners(
+                pending.min.saturating_sub(UVec2::ONE),
+                (pending.max + UVec2::ONE).min(self.map_size()),
+            );
+            let (tiler, terrain) = (&autotile.tiler, &autotile.terrain);
+            self.indexer_mut()
+                .step_region(region, |_, value, neighborhood| {
+                    tiler
+                        .tile(&neighborhood.map(|v| terrain.terrain(v)))
+                        .unwrap_or(value)
+                });
+        }
+        self.autotile = Some(autotile);
+    }
+}
+
+/// Autotile the written tiles of all maps with [`Map::set_autotile`].
+/// Only maps with written tiles are accessed mutably.
+pub fn apply_autotiling<C: Customization>(mut maps: ResMut<Assets<Map<C>>>) {
+    let pending: Vec<_> = maps
+        .iter()
+        .filter(|(_, map)| map.needs_autotile())
+        .map(|(id, _)| id)
+        .collect();
+    for id in pending {
+        if let Some(map) = maps.get_mut(id) {
+            map.apply_autotile();
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Autotiling in the shader: The map stores terrain IDs and the shader shows the transition tile
+//! of each terrain matching its neighbors, so no CPU work is needed when painting terrain.
+//! For autotiling on the CPU with arbitrary rules see [`Map::set_autotile`].
+//!
+//! ```
+//! # use bevy::{math::{uvec2, vec2}, prelude::*};
+//! # use bevy_fast_tilemap::prelude::*;
+//! const GRASS: u32 = 0;
+//! const WATER: u32 = 1;
+//! let map: Map = Map::builder(uvec2(8, 8), default(), vec2(16., 16.))
+//!     // Water shows the 47 blob tiles starting at atlas index 16
+//!     .with_autotile(AutotileRules::new().with_terrain(WATER, 16, AutotileLayout::Blob47))
+//!     .build_and_set(|p| if p.x < 4 { WATER } else { GRASS });
+//! // Water surrounded by water, and water with grass to the east
+//! assert_eq!(map.shown_tile(uvec2(1, 1)), 16 + autotile_index(0xff));
+//! assert_eq!(map.shown_tile(uvec2(3, 1)), 16 + autotile_index(0b1111_0001));
+//! // Terrains without rules are shown as stored
+//! assert_eq!(map.shown_tile(uvec2(6, 1)), GRASS);
+//! ```
+
+use bevy::prelude::*;
+
+use super::{
+    map::Map,
+    map_builder::MapBuilder,
+    plugin::Customization,
+    tile_flags::{TILE_FLAGS_MASK, TILE_INDEX_MASK},
+};
+
+/// Neighbors in the order of their bits in neighbor masks: Clockwise, starting at `y - 1`.
+pub const AUTOTILE_NEIGHBORS: [IVec2; 8] = [
+    IVec2::new(0, -1),
+    IVec2::new(1, -1),
+    IVec2::new(1, 0),
+    IVec2::new(1, 1),
+    IVec2::new(0, 1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, 0),
+    IVec2::new(-1, -1),
+];
+
+/// Entries of the blob lookup table in front of the rules in the buffer of a map.
+pub(crate) const AUTOTILE_TABLE_SIZE: usize = 256;
+
+/// Bits of the layout in the rule of a terrain, the bits below are the first tile.
+const LAYOUT_SHIFT: u32 = 29;
+
+/// Arrangement of the transition tiles of a terrain in the atlas, see [`AutotileRules`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AutotileLayout {
+    /// 16 tiles, one per combination of the 4 cardinal neighbors: The tile offset has a bit for
+    /// each cardinal neighbor of the same terrain, `1` for `y - 1`, `2` for `x + 1`, `4` for
+    /// `y + 1` and `8` for `x - 1` (as for [`crate::autotile::AutoTiler::cardinal`]).
+    Wang16,
+    /// 47 tiles, also covering the diagonal neighbors: The tile offset is
+    /// [`autotile_index`] of the neighbor mask.
+    Blob47,
+}
+
+impl AutotileLayout {
+    /// Number of transition tiles per terrain.
+    pub const fn n_tiles(self) -> u32 {
+        match self {
+            Self::Wang16 => 16,
+            Self::Blob47 => 47,
+        }
+    }
+
+    /// Offset of the transition tile from the first tile of a terrain for the neighbors of the
+    /// same terrain in `neighbors_mask` (one bit per neighbor of [`AUTOTILE_NEIGHBORS`]).
+    pub fn tile_offset(self, neighbors_mask: u32) -> u32 {
+        match self {
+            Self::Wang16 => {
+                (neighbors_mask & 1)
+                    | (neighbors_mask >> 1 & 2)
+                    | (neighbors_mask >> 2 & 4)
+                    | (neighbors_mask >> 3 & 8)
+            }
+            Self::Blob47 => autotile_index(neighbors_mask),
+        }
+    }
+
+    fn from_bits(bits: u32) -> Option<Self> {
+        match bits {
+            1 => Some(Self::Wang16),
+            2 => Some(Self::Blob47),
+            _ => None,
+        }
+    }
+
+    fn bits(self) -> u32 {
+        match self {
+            Self::Wang16 => 1,
+            Self::Blob47 => 2,
+        }
+    }
+}
+
+/// `neighbors_mask` without the diagonal neighbors whose two adjacent cardinal neighbors are
+/// not both set, as these don't change the look of a blob tile.
+fn reduce_blob_mask(neighbors_mask: u32) -> u32 {
+    let mut mask = neighbors_mask & 0xff;
+    for corner in [1, 3, 5, 7] {
+        let (a, b) = (corner - 1, (corner + 1) % 8);
+        if mask & (1 << a) == 0 || mask & (1 << b) == 0 {
+            mask &= !(1 << corner);
+        }
+    }
+    mask
+}
+
+/// Index (`0..47`) of the tile of the 47 tile blob set for the neighbors of the same terrain in
+/// `neighbors_mask` (one bit per neighbor of [`AUTOTILE_NEIGHBORS`]), as shown by
+/// [`AutotileLayout::Blob47`].
+///
+/// Diagonal neighbors only count if both adjacent cardinal neighbors are set, the tiles are
+/// ordered by the remaining mask, so `0` is the isolated tile and `46` the one surrounded by
+/// its terrain.
+pub fn autotile_index(neighbors_mask: u32) -> u32 {
+    let mask = reduce_blob_mask(neighbors_mask);
+    (0..mask).filter(|m| reduce_blob_mask(*m) == *m).count() as u32
+}
+
+/// Transition tiles per terrain ID for [`MapBuilder::with_autotile`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AutotileRules {
+    /// Packed rule per terrain, 0 for terrains without one
+    rules: Vec<u32>,
+}
+
+impl AutotileRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show tiles with value `terrain` as the transition tile of `layout` matching their
+    /// neighbors, counting from atlas index `first` (see
+    /// [`crate::additional_atlases::tile_in_atlas`] for tiles of additional atlases).
+    /// Flag bits of `first` are ignored.
+    ///
+    /// Rules are stored per terrain up to the highest terrain given, so keep terrain IDs small.
+    pub fn with_terrain(mut self, terrain: u32, first: u32, layout: AutotileLayout) -> Self {
+        if self.rules.len() <= terrain as usize {
+            self.rules.resize(terrain as usize + 1, 0);
+        }
+        self.rules[terrain as usize] = (first & TILE_INDEX_MASK) | layout.bits() << LAYOUT_SHIFT;
+        self
+    }
+
+    /// First tile and layout of `terrain`, `None` for terrains shown as stored.
+    pub fn get(&self, terrain: u32) -> Option<(u32, AutotileLayout)> {
+        unpack_rule(self.rules.get(terrain as usize).copied()?)
+    }
+}
+
+fn unpack_rule(rule: u32) -> Option<(u32, AutotileLayout)> {
+    let layout = AutotileLayout::from_bits(rule >> LAYOUT_SHIFT)?;
+    Some((rule & ((1 << LAYOUT_SHIFT) - 1), layout))
+}
+
+impl<C: Customization> MapBuilder<C> {
+    /// Autotile the map in the shader: The map stores terrain IDs (tile values without flags)
+    /// and each tile of a terrain with a rule in `rules` shows the transition tile matching the
+    /// terrain of its 8 neighbors (neighbors outside of the map count as the same terrain).
+    /// Flags of the stored values apply to the shown tiles.
+    ///
+    /// Changing a tile thus changes the look of its neighbors right away, without writing them.
+    /// Game logic keeps working with terrain IDs, use [`Map::shown_tile`] for the shown tiles.
+    /// This only applies to the tiles of layer 0 (see [`MapBuilder::with_layers`]), and
+    /// each sampled tile reads its 8 neighbors, so overhangs get more expensive.
+    pub fn with_autotile(mut self, rules: AutotileRules) -> Self {
+        let mut buffer: Vec<u32> = (0..AUTOTILE_TABLE_SIZE as u32)
+            .map(autotile_index)
+            .collect();
+        buffer.extend(rules.rules);
+        self.map.autotile_rules = buffer;
+        self
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// First tile and layout shown for tiles of `terrain`, `None` if they are shown as stored,
+    /// see [`MapBuilder::with_autotile`].
+    pub fn autotile_rule(&self, terrain: u32) -> Option<(u32, AutotileLayout)> {
+        let rule = self.terrain_rules().get(terrain as usize).copied()?;
+        unpack_rule(rule)
+    }
+
+    /// Tile value the shader shows at `pos` (which must be inside of the map), ie. the stored
+    /// value with [`MapBuilder::with_autotile`] applied.
+    pub fn shown_tile(&self, pos: UVec2) -> u32 {
+        self.autotiled(pos.as_ivec2(), self.indexer().at_uvec(pos))
+    }
+
+    /// `autotile`: The tile shown for the stored `value` at `pos`
+    pub(crate) fn autotiled(&self, pos: IVec2, value: u32) -> u32 {
+        let terrain = value & TILE_INDEX_MASK;
+        let Some((first, layout)) = self.autotile_rule(terrain) else {
+            return value;
+        };
+        let size = self.map_size().as_ivec2();
+        let m = self.indexer();
+        let mut mask = 0;
+        for (i, offset) in AUTOTILE_NEIGHBORS.iter().enumerate() {
+            let mut neighbor = pos + *offset;
+            if self.repeats_content() {
+                neighbor = neighbor.rem_euclid(size);
+            }
+            let inside = neighbor.cmpge(IVec2::ZERO).all() && neighbor.cmplt(size).all();
+            if !inside || m.at_ivec(neighbor) & TILE_INDEX_MASK == terrain {
+                mask |= 1 << i;
+            }
+        }
+        (first + layout.tile_offset(mask)) | (value & TILE_FLAGS_MASK)
+    }
+
+    /// Packed rules per terrain, without the blob lookup table.
+    pub(crate) fn terrain_rules(&self) -> &[u32] {
+        self.autotile_rules
+            .get(AUTOTILE_TABLE_SIZE..)
+            .unwrap_or_default()
+    }
+
+    /// Whether any terrain has an autotile rule.
+    pub(crate) fn has_autotile_rules(&self) -> bool {
+        self.terrain_rules().iter().any(|rule| *rule != 0)
+    }
+}
@@ -0,0 +1,145 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use bevy::prelude::*;
+
+use super::{map::Map, plugin::Customization};
+
+/// Per-tile record of when each tile was last written, see [`Map::enable_change_ticks`].
+#[derive(Debug, Default)]
+pub(crate) struct ChangeTicks {
+    /// Tick recorded for writes.
+    tick: u32,
+    /// True iff `tick` has been handed out by `Map::current_tick`,
+    /// in which case the next write needs to use a newer tick.
+    observed: AtomicBool,
+    width: u32,
+    tiles: Vec<u32>,
+    /// Maximum tick of each row
+    rows: Vec<u32>,
+}
+
+impl Clone for ChangeTicks {
+    fn clone(&self) -> Self {
+        Self {
+            tick: self.tick,
+            observed: AtomicBool::new(self.observed.load(Ordering::Relaxed)),
+            width: self.width,
+            tiles: self.tiles.clone(),
+            rows: self.rows.clone(),
+        }
+    }
+}
+
+impl ChangeTicks {
+    fn new(size: UVec2) -> Self {
+        Self {
+            // Tiles start out at 0, so writes before the first `current_tick` are reported
+            // as newer.
+            tick: 1,
+            observed: AtomicBool::new(false),
+            width: size.x,
+            tiles: vec![0; (size.x * size.y) as usize],
+            rows: vec![0; size.y as usize],
+        }
+    }
+
+    fn write_tick(&mut self) -> u32 {
+        if self.observed.swap(false, Ordering::Relaxed) {
+            self.tick += 1;
+        }
+        self.tick
+    }
+
+    /// Record a write to the tile at `(x, y)`.
+    pub(crate) fn record(&mut self, x: u32, y: u32) {
+        let tick = self.write_tick();
+        self.tiles[(y * self.width + x) as usize] = tick;
+        self.rows[y as usize] = tick;
+    }
+
+    /// Record a write to all tiles of row `y`.
+    pub(crate) fn record_row(&mut self, y: u32) {
+        let tick = self.write_tick();
+        let start = (y * self.width) as usize;
+        self.tiles[start..start + self.width as usize].fill(tick);
+        self.rows[y as usize] = tick;
+    }
+
+    /// Record a write to all tiles in `rect` (`max` exclusive).
+    pub(crate) fn record_rect(&mut self, rect: URect) {
+        if rect.is_empty() {
+            return;
+        }
+        let tick = self.write_tick();
+        for y in rect.min.y..rect.max.y {
+            let start = (y * self.width) as usize;
+            self.tiles[start + rect.min.x as usize..start + rect.max.x as usize].fill(tick);
+            self.rows[y as usize] = tick;
+        }
+    }
+
+    /// Record a write to all tiles.
+    pub(crate) fn record_all(&mut self) {
+        let tick = self.write_tick();
+        self.tiles.fill(tick);
+        self.rows.fill(tick);
+    }
+
+    /// Reallocate for a map of `size`, keeping the current tick. All tiles count as written.
+    pub(crate) fn resize(&mut self, size: UVec2) {
+        self.width = size.x;
+        self.tiles = vec![0; (size.x * size.y) as usize];
+        self.rows = vec![0; size.y as usize];
+        self.record_all();
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Start recording for every tile when it was last written through an indexer
+    /// (see [`Self::tiles_changed_since`]).
+    ///
+    /// This costs 4 bytes of memory per tile.
+    pub fn enable_change_ticks(&mut self) {
+        if self.change_ticks.is_none() {
+            self.change_ticks = Some(ChangeTicks::new(self.map_size()));
+        }
+    }
+
+    pub fn disable_change_ticks(&mut self) {
+        self.change_ticks = None;
+    }
+
+    /// Current change tick, store this to later query tiles changed since now
+    /// with [`Self::tiles_changed_since`].
+    /// Returns 0 if change ticks are not enabled.
+    pub fn current_tick(&self) -> u32 {
+        let Some(ticks) = self.change_ticks.as_ref() else {
+            return 0;
+        };
+        ticks.observed.store(true, Ordering::Relaxed);
+        ticks.tick
+    }
+
+    /// Tiles in `region` (`max` exclusive) that have been changed after `tick`
+    /// was returned by [`Self::current_tick`].
+    /// Returns nothing if change ticks are not enabled.
+    ///
+    /// Rows without any changes are skipped as a whole.
+    pub fn tiles_changed_since(
+        &self,
+        tick: u32,
+        region: URect,
+    ) -> impl Iterator<Item = UVec2> + '_ {
+        let max = region.max.min(self.map_size());
+        let min = region.min.min(max);
+        self.change_ticks.iter().flat_map(move |ticks| {
+            (min.y..max.y)
+                .filter(move |y| ticks.rows[*y as usize] > tick)
+                .flat_map(move |y| {
+                    (min.x..max.x)
+                        .filter(move |x| ticks.tiles[(y * ticks.width + x) as usize] > tick)
+                        .map(move |x| UVec2::new(x, y))
+                })
+        })
+    }
+}
@@ -0,0 +1,281 @@
+use std::sync::Arc;
+
+use bevy::{
+    math::{ivec2, vec2},
+    prelude::*,
+    utils::HashMap,
+};
+
+use super::{
+    bundle::MapBundleManaged,
+    map::{Map, MapIndexerMut},
+    map_assets::MapAssetsExt,
+    plugin::{Customization, NoCustomization},
+};
+
+/// Source of the tiles of the chunks of a [`ChunkedMap`].
+pub trait ChunkProvider<C: Customization = NoCustomization>: Send + Sync + 'static {
+    /// Set the tiles of the chunk at `chunk_coord`.
+    /// The chunk is all zeros before, so only non-zero tiles need to be set.
+    /// World tile `chunk_coord * chunk_size + p` is at `p` in `indexer`.
+    fn fill(&self, chunk_coord: IVec2, indexer: &mut MapIndexerMut<C>);
+}
+
+impl<C: Customization, F> ChunkProvider<C> for F
+where
+    F: Fn(IVec2, &mut MapIndexerMut<C>) + Send + Sync + 'static,
+{
+    fn fill(&self, chunk_coord: IVec2, indexer: &mut MapIndexerMut<C>) {
+        self(chunk_coord, indexer)
+    }
+}
+
+/// Cameras (or any other entities) around which the chunks of all [`ChunkedMap`]s are loaded.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub struct ChunkedMapCamera;
+
+/// Added to the map entities of the chunks of a [`ChunkedMap`].
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct MapChunk {
+    /// Entity holding the [`ChunkedMap`].
+    pub chunked_map: Entity,
+    pub coord: IVec2,
+}
+
+#[derive(Debug, Clone)]
+struct Chunk<C: Customization> {
+    entity: Entity,
+    map: Handle<Map<C>>,
+}
+
+/// Unbounded map made of equally sized map entities ("chunks"), which are streamed in around
+/// [`ChunkedMapCamera`]s with tiles from a [`ChunkProvider`].
+///
+/// World tile `(x, y)` has its top left corner at `origin + (x, -y) * tile_size`,
+/// ie. like in a single map, y grows downwards.
+/// Chunks are spawned as separate entities (with [`MapChunk`]) and are placed in world
+/// coordinates, the transform of the entity holding the `ChunkedMap` is not used.
+/// They have exact bounds (see [`crate::map_builder::MapBuilder::with_exact_bounds`]), so there
+/// are no seams between them at any zoom level.
+///
+/// Chunks farther than `unload_radius` from all cameras are hidden and reused for new chunks.
+/// Changes to a chunk (eg. by [`Self::set_tile`]) are lost when it is unloaded.
+#[derive(Component)]
+pub struct ChunkedMap<C: Customization = NoCustomization> {
+    /// Size of each chunk, in tiles.
+    pub chunk_size: UVec2,
+    pub tile_size: Vec2,
+    pub atlas: Handle<Image>,
+    /// World position of the top left corner of world tile `(0, 0)`, z is used for all chunks.
+    pub origin: Vec3,
+    /// Chunks within this distance (in chunks, in both x and y) of a camera are loaded.
+    pub load_radius: u32,
+    /// Chunks beyond this distance (in chunks, in both x and y) of all cameras are unloaded,
+    /// should be larger than `load_radius` to avoid repeated loading when moving back and forth.
+    pub unload_radius: u32,
+    /// Maximum number of chunks loaded per frame, closest first.
+    pub chunks_per_frame: usize,
+    provider: Arc<dyn ChunkProvider<C>>,
+    loaded: HashMap<IVec2, Chunk<C>>,
+    pool: Vec<Chunk<C>>,
+}
+
+impl<C: Customization> ChunkedMap<C> {
+    pub fn new(
+        chunk_size: UVec2,
+        tile_size: Vec2,
+        atlas: Handle<Image>,
+        provider: impl ChunkProvider<C>,
+    ) -> Self {
+        Self {
+            chunk_size,
+            tile_size,
+            atlas,
+            origin: Vec3::ZERO,
+            load_radius: 1,
+            unload_radius: 2,
+            chunks_per_frame: 4,
+            provider: Arc::new(provider),
+            loaded: HashMap::new(),
+            pool: Vec::new(),
+        }
+    }
+
+    pub fn with_origin(mut self, origin: Vec3) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    pub fn with_radius(mut self, load_radius: u32, unload_radius: u32) -> Self {
+        self.load_radius = load_radius;
+        self.unload_radius = unload_radius.max(load_radius);
+        self
+    }
+
+    /// World tile at the given world position.
+    pub fn world_tile_at(&self, world: Vec2) -> IVec2 {
+        let p = (world - self.origin.truncate()) / self.tile_size;
+        vec2(p.x, -p.y).floor().as_ivec2()
+    }
+
+    /// Coordinate of the chunk containing the given world tile.
+    pub fn chunk_of_tile(&self, world_tile: IVec2) -> IVec2 {
+        world_tile.div_euclid(self.chunk_size.as_ivec2())
+    }
+
+    /// Coordinate of the chunk at the given world position (whether it is loaded or not).
+    pub fn chunk_at(&self, world: Vec2) -> IVec2 {
+        self.chunk_of_tile(self.world_tile_at(world))
+    }
+
+    /// Entity of the chunk at `chunk_coord`, if it is loaded.
+    pub fn chunk_entity(&self, chunk_coord: IVec2) -> Option<Entity> {
+        self.loaded.get(&chunk_coord).map(|chunk| chunk.entity)
+    }
+
+    /// Coordinates and entities of all loaded chunks.
+    pub fn loaded_chunks(&self) -> impl Iterator<Item = (IVec2, Entity)> + '_ {
+        self.loaded
+            .iter()
+            .map(|(coord, chunk)| (*coord, chunk.entity))
+    }
+
+    /// Value of the given world tile, if its chunk is loaded.
+    pub fn tile(&self, world_tile: IVec2, maps: &Assets<Map<C>>) -> Option<u32> {
+        let chunk = self.loaded.get(&self.chunk_of_tile(world_tile))?;
+        let map = maps.get(&chunk.map)?;
+        let p = world_tile.rem_euclid(self.chunk_size.as_ivec2()).as_uvec2();
+        Some(map.indexer().at_uvec(p))
+    }
+
+    /// Set the given world tile, returns false (and does nothing) if its chunk is not loaded.
+    pub fn set_tile(&self, world_tile: IVec2, v: u32, maps: &mut Assets<Map<C>>) -> bool {
+        let Some(chunk) = self.loaded.get(&self.chunk_of_tile(world_tile)) else {
+            return false;
+        };
+        if maps.get(&chunk.map).is_none() {
+            return false;
+        }
+        let p = world_tile.rem_euclid(self.chunk_size.as_ivec2()).as_uvec2();
+        maps.set_tile_if_neq(&chunk.map, p, v);
+        true
+    }
+
+    /// Transform placing the chunk at `chunk_coord` with its edges exactly at the
+    /// edges of the neighboring chunks.
+    fn chunk_transform(&self, chunk_coord: IVec2, map: &Map<C>) -> Transform {
+        let first_tile = chunk_coord * self.chunk_size.as_ivec2();
+        let corner =
+            self.origin.truncate() + vec2(1.0, -1.0) * first_tile.as_vec2() * self.tile_size;
+        let translation = corner - map.map_to_local(Vec2::ZERO);
+        Transform::from_translation(translation.extend(self.origin.z))
+    }
+}
+
+/// Load chunks of [`ChunkedMap`]s around [`ChunkedMapCamera`]s and unload far away ones.
+pub fn update_chunked_maps<C: Customization>(
+    mut commands: Commands,
+    mut chunked_maps: Query<(Entity, &mut ChunkedMap<C>)>,
+    cameras: Query<&GlobalTransform, With<ChunkedMapCamera>>,
+    mut chunks: Query<(&mut Transform, &mut Visibility, &mut MapChunk)>,
+    mut maps: ResMut<Assets<Map<C>>>,
+) {
+    for (chunked_entity, mut chunked) in chunked_maps.iter_mut() {
+        let centers: Vec<IVec2> = cameras
+            .iter()
+            .map(|camera| chunked.chunk_at(camera.translation().truncate()))
+            .collect();
+        let distance = |coord: IVec2| {
+            centers
+                .iter()
+                .map(|center| (coord - *center).abs().max_element())
+                .min()
+                .unwrap_or(i32::MAX)
+        };
+
+        // Unload far chunks
+        let unload_radius = chunked.unload_radius as i32;
+        let far: Vec<IVec2> = chunked
+            .loaded
+            .keys()
+            .copied()
+            .filter(|coord| distance(*coord) > unload_radius)
+            .collect();
+        for coord in far {
+            let chunk = chunked.loaded.remove(&coord).unwrap();
+            if let Ok((_, mut visibility, _)) = chunks.get_mut(chunk.entity) {
+                *visibility = Visibility::Hidden;
+            }
+            chunked.pool.push(chunk);
+        }
+
+        // Load missing chunks, closest first
+        let r = chunked.load_radius as i32;
+        let mut missing: Vec<IVec2> = centers
+            .iter()
+            .flat_map(|center| {
+                (-r..=r).flat_map(move |y| (-r..=r).map(move |x| *center + ivec2(x, y)))
+            })
+            .filter(|coord| !chunked.loaded.contains_key(coord))
+            .collect();
+        missing.sort_by_key(|coord| (distance(*coord), coord.y, coord.x));
+        missing.dedup();
+
+        for coord in missing.into_iter().take(chunked.chunks_per_frame) {
+            let reused = chunked.pool.pop();
+            let handle = match reused.as_ref() {
+                Some(chunk) => chunk.map.clone(),
+                None => maps.add(
+                    Map::<C>::builder(chunked.chunk_size, chunked.atlas.clone(), chunked.tile_size)
+                        .with_exact_bounds(true)
+                        .build(),
+                ),
+            };
+            let Some(map) = maps.get_mut(&handle) else {
+                continue;
+            };
+            map.set_uniform_tile(Some(0));
+            chunked.provider.fill(coord, &mut map.indexer_mut());
+            let transform = chunked.chunk_transform(coord, map);
+
+            let chunk_info = MapChunk {
+                chunked_map: chunked_entity,
+                coord,
+            };
+            let reused = reused.and_then(|chunk| {
+                chunks
+                    .get_mut(chunk.entity)
+                    .ok()
+                    .map(|components| (chunk.entity, components))
+            });
+            let entity = match reused {
+                Some((entity, (mut t, mut visibility, mut info))) => {
+                    *t = transform;
+                    *visibility = Visibility::Inherited;
+                    *info = chunk_info;
+                    entity
+                }
+                // New chunk, or the pooled entity has been despawned
+                None => commands
+                    .spawn((
+                        MapBundleManaged {
+                            material: handle.clone(),
+                            transform,
+                            ..default()
+                        },
+                        chunk_info,
+                    ))
+                    .id(),
+            };
+            chunked.loaded.insert(
+                coord,
+                Chunk {
+                    entity,
+                    map: handle,
+                },
+            );
+        }
+    }
+}
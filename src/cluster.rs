@@ -0,0 +1,241 @@
+use bevy::{
+    math::{uvec2, vec2, U64Vec2, Vec3Swizzles},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use super::{map::Map, plugin::Customization};
+
+/// Group of orthogonally connected tiles, see [`Map::cluster_tiles`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileCluster {
+    /// Centroid of the tile centers, in world coordinates.
+    pub center_world: Vec2,
+    /// Number of tiles in the cluster.
+    pub tiles: usize,
+    /// Bounding box of the cluster's tiles in world coordinates.
+    pub bounds: Rect,
+}
+
+/// Connected component of matching tiles, in map coordinates.
+#[derive(Debug, Clone)]
+struct Component {
+    tiles: usize,
+    /// `max` exclusive
+    bounds: URect,
+    /// Sum of all tile positions, for the centroid
+    sum: U64Vec2,
+    /// First tile in row-major order
+    first: UVec2,
+}
+
+/// Labeling of the connected components of a map's tiles that match a predicate,
+/// which can be kept up to date incrementally (see [`Self::update`]).
+///
+/// Tiles are connected if they share an edge, tiles touching only diagonally are not.
+#[derive(Debug, Clone)]
+pub struct TileClusters {
+    size: UVec2,
+    min_size: usize,
+    /// Component label per tile, or 0 if the tile doesn't match
+    labels: Vec<u32>,
+    components: HashMap<u32, Component>,
+    next_label: u32,
+    /// Change tick of the map at the last update
+    tick: u32,
+}
+
+impl TileClusters {
+    /// Label all tiles of `map` for which `pred` is true.
+    /// Components with fewer than `min_size` tiles are tracked but not reported as clusters.
+    pub fn new<C: Customization>(
+        map: &Map<C>,
+        pred: impl Fn(u32) -> bool,
+        min_size: usize,
+    ) -> Self {
+        let mut clusters = Self {
+            size: UVec2::ZERO,
+            min_size,
+            labels: Vec::new(),
+            components: HashMap::new(),
+            next_label: 1,
+            tick: 0,
+        };
+        clusters.rebuild(map, &pred);
+        clusters
+    }
+
+    fn rebuild<C: Customization>(&mut self, map: &Map<C>, pred: &impl Fn(u32) -> bool) {
+        self.tick = map.current_tick();
+        self.size = map.map_size();
+        self.labels = vec![0; (self.size.x * self.size.y) as usize];
+        self.components.clear();
+        self.next_label = 1;
+
+        let indexer = map.indexer();
+        for y in 0..self.size.y {
+            for x in 0..self.size.x {
+                self.flood(uvec2(x, y), &|p| pred(indexer.at_uvec(p)));
+            }
+        }
+    }
+
+    /// Label the component containing `start`, if it matches and is not labeled yet.
+    fn flood(&mut self, start: UVec2, matches: &impl Fn(UVec2) -> bool) {
+        if self.labels[self.index(start)] != 0 || !matches(start) {
+            return;
+        }
+
+        let label = self.next_label;
+        self.next_label += 1;
+        let mut component = Component {
+            tiles: 0,
+            bounds: URect::from_corners(start, start + UVec2::ONE),
+            sum: U64Vec2::ZERO,
+            first: start,
+        };
+
+        let mut stack = vec![start];
+        let i = self.index(start);
+        self.labels[i] = label;
+        while let Some(p) = stack.pop() {
+            component.tiles += 1;
+            component.sum += p.as_u64vec2();
+            component.bounds = component.bounds.union_point(p).union_point(p + UVec2::ONE);
+            if (p.y, p.x) < (component.first.y, component.first.x) {
+                component.first = p;
+            }
+
+            let neighbors = [
+                (p.x > 0).then(|| p - UVec2::X),
+                (p.y > 0).then(|| p - UVec2::Y),
+                (p.x + 1 < self.size.x).then(|| p + UVec2::X),
+                (p.y + 1 < self.size.y).then(|| p + UVec2::Y),
+            ];
+            for n in neighbors.into_iter().flatten() {
+                let j = self.index(n);
+                if self.labels[j] == 0 && matches(n) {
+                    self.labels[j] = label;
+                    stack.push(n);
+                }
+            }
+        }
+
+        self.components.insert(label, component);
+    }
+
+    fn index(&self, p: UVec2) -> usize {
+        (p.y * self.size.x + p.x) as usize
+    }
+
+    /// Bring the labeling up to date with the tiles of `map`, which must be the same map
+    /// (and `pred` the same predicate) this was created with.
+    /// Returns true iff any component changed.
+    ///
+    /// With change ticks enabled (see [`Map::enable_change_ticks`]), only components
+    /// containing or touching changed tiles are recomputed, otherwise everything is.
+    pub fn update<C: Customization>(&mut self, map: &Map<C>, pred: impl Fn(u32) -> bool) -> bool {
+        if map.current_tick() == 0 || map.map_size() != self.size {
+            self.rebuild(map, &pred);
+            return true;
+        }
+
+        let full = URect::from_corners(UVec2::ZERO, self.size);
+        let changed: Vec<UVec2> = map.tiles_changed_since(self.tick, full).collect();
+        self.tick = map.current_tick();
+        if changed.is_empty() {
+            return false;
+        }
+        let max_tile = self.size - UVec2::ONE;
+
+        // Components are only affected by a change of one of their tiles or of a neighbor
+        let mut affected = HashSet::new();
+        for p in changed.iter() {
+            let neighborhood = URect::from_corners(p.saturating_sub(UVec2::ONE), *p + UVec2::ONE)
+                .intersect(URect::from_corners(UVec2::ZERO, max_tile));
+            for y in neighborhood.min.y..=neighborhood.max.y {
+                for x in neighborhood.min.x..=neighborhood.max.x {
+                    let is_neighbor = x == p.x || y == p.y;
+                    let label = self.labels[self.index(uvec2(x, y))];
+                    if is_neighbor && label != 0 {
+                        affected.insert(label);
+                    }
+                }
+            }
+        }
+
+        // Unlabel affected components and relabel their tiles and the changed tiles
+        let mut seeds = changed;
+        for label in affected {
+            let Some(component) = self.components.remove(&label) else {
+                continue;
+            };
+            for y in component.bounds.min.y..component.bounds.max.y {
+                for x in component.bounds.min.x..component.bounds.max.x {
+                    let i = self.index(uvec2(x, y));
+                    if self.labels[i] == label {
+                        self.labels[i] = 0;
+                        seeds.push(uvec2(x, y));
+                    }
+                }
+            }
+        }
+
+        let indexer = map.indexer();
+        for p in seeds {
+            self.flood(p, &|p| pred(indexer.at_uvec(p)));
+        }
+        true
+    }
+
+    /// Label of the component the given tile belongs to, `None` if it doesn't match.
+    /// Labels are stable as long as a component is not affected by changes.
+    pub fn label_at(&self, tile: UVec2) -> Option<u32> {
+        if tile.cmpge(self.size).any() {
+            return None;
+        }
+        let label = self.labels[self.index(tile)];
+        (label != 0).then_some(label)
+    }
+
+    /// All components with at least `min_size` tiles, in the order of their first tile
+    /// (row by row).
+    pub fn clusters<C: Customization>(&self, map: &Map<C>) -> Vec<TileCluster> {
+        let mut components: Vec<_> = self
+            .components
+            .values()
+            .filter(|component| component.tiles >= self.min_size)
+            .collect();
+        components.sort_by_key(|component| (component.first.y, component.first.x));
+
+        components
+            .into_iter()
+            .map(|component| {
+                let to_world = |p: Vec2| map.map_to_world_3d(p.extend(0.0)).xy();
+                let center = component.sum.as_dvec2() / component.tiles as f64;
+                let min = component.bounds.min.as_vec2();
+                let max = component.bounds.max.as_vec2();
+                let corners = [min, vec2(max.x, min.y), max, vec2(min.x, max.y)];
+                let bounds = corners.into_iter().fold(Rect::EMPTY, |rect, corner| {
+                    rect.union_point(to_world(corner))
+                });
+                TileCluster {
+                    center_world: to_world(center.as_vec2() + Vec2::splat(0.5)),
+                    tiles: component.tiles,
+                    bounds,
+                }
+            })
+            .collect()
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Groups of orthogonally connected tiles for which `pred` is true
+    /// (eg. to place one ambient sound emitter per lake), ignoring groups with fewer than
+    /// `min_size` tiles.
+    ///
+    /// Use [`TileClusters`] to keep the clusters up to date while the map changes.
+    pub fn cluster_tiles(&self, pred: impl Fn(u32) -> bool, min_size: usize) -> Vec<TileCluster> {
+        TileClusters::new(self, pred, min_size).clusters(self)
+    }
+}
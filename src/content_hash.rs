@@ -0,0 +1,149 @@
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Range,
+    sync::{Mutex, MutexGuard},
+};
+
+use bevy::{prelude::*, render::render_resource::encase::UniformBuffer};
+
+use super::{
+    map::{positions, Map},
+    plugin::Customization,
+};
+
+/// Hashes of the rows of the tile data, kept up to date with the rows written since
+/// (see [`Map::content_hash`]).
+#[derive(Debug, Default)]
+pub(crate) struct RowHashes(Mutex<RowHashState>);
+
+#[derive(Debug, Clone, Default)]
+struct RowHashState {
+    rows: Vec<u64>,
+    /// Rows written since their hash was computed
+    dirty: Option<Range<u32>>,
+}
+
+impl Clone for RowHashes {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(self.state().clone()))
+    }
+}
+
+impl RowHashes {
+    fn state(&self) -> MutexGuard<'_, RowHashState> {
+        // A panic while holding the lock at most leaves hashes to be recomputed
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record a write to the rows `rows`.
+    pub(crate) fn mark(&mut self, rows: Range<u32>) {
+        let state = self.0.get_mut().unwrap_or_else(|e| e.into_inner());
+        state.dirty = Some(match state.dirty.take() {
+            Some(dirty) => dirty.start.min(rows.start)..dirty.end.max(rows.end),
+            None => rows,
+        });
+    }
+}
+
+/// Hash the non-zero words of a tile index bitset, so bitsets that only differ in their length
+/// hash the same.
+fn hash_bitset(bits: &[u32], hasher: &mut impl Hasher) {
+    for (i, word) in bits.iter().enumerate().filter(|(_, word)| **word != 0) {
+        (i, *word).hash(hasher);
+    }
+    u32::MAX.hash(hasher);
+}
+
+fn bitsets_eq(a: &[u32], b: &[u32]) -> bool {
+    let n = a.len().max(b.len());
+    (0..n).all(|i| a.get(i).copied().unwrap_or(0) == b.get(i).copied().unwrap_or(0))
+}
+
+impl<C: Customization> Map<C> {
+    /// Hash of the content of the map, eg. for finding identical chunks to share a single map.
+    ///
+    /// Covers the map size, the tile values and the tile index sets of
+    /// [`crate::map_builder::MapBuilder::with_emissive_tiles`],
+    /// [`crate::map_builder::MapBuilder::with_color_jitter`] and
+    /// [`crate::map_builder::MapBuilder::with_overhang_exclusions`] as well as the flags of
+    /// [`crate::map_builder::MapBuilder::with_tile_overhang_flags`] and the rules of
+    /// [`crate::map_builder::MapBuilder::with_autotile`].
+    /// Not covered are the atlas, transforms and other settings of the map and the preview,
+    /// patches and decals. `user_data` is only covered by [`Self::content_hash_with_user_data`].
+    ///
+    /// Row hashes are cached, only rows written since the last call are hashed again,
+    /// so this is cheap to call repeatedly for mostly unchanged maps.
+    /// Hashes are stable within a build of the program, but don't persist them.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.map_size().hash(&mut hasher);
+        self.hash_rows(&mut hasher);
+        hash_bitset(&self.emissive_tiles, &mut hasher);
+        hash_bitset(&self.color_jitter_tiles, &mut hasher);
+        hash_bitset(&self.overhang_exclusions, &mut hasher);
+        // Zero entries don't count either
+        hash_bitset(&self.tile_overhang_flags, &mut hasher);
+        hash_bitset(self.terrain_rules(), &mut hasher);
+        hasher.finish()
+    }
+
+    /// Like [`Self::content_hash`], but also covering `user_data`.
+    pub fn content_hash_with_user_data(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.content_hash().hash(&mut hasher);
+        let mut buffer = UniformBuffer::new(Vec::new());
+        match buffer.write(&self.user_data) {
+            Ok(()) => buffer.into_inner().hash(&mut hasher),
+            Err(e) => warn!("Failed to hash map user data: {e}"),
+        }
+        hasher.finish()
+    }
+
+    /// Whether `other` has the same content, as covered by [`Self::content_hash`].
+    /// Maps with different hashes are rejected without comparing their tiles.
+    pub fn content_eq(&self, other: &Map<C>) -> bool {
+        if self.map_size() != other.map_size() || self.content_hash() != other.content_hash() {
+            return false;
+        }
+        let (a, b) = (self.indexer(), other.indexer());
+        let tiles_eq = match (self.uniform_tile(), other.uniform_tile()) {
+            (Some(x), Some(y)) => x == y,
+            _ => positions(self.map_size()).all(|p| a.at_uvec(p) == b.at_uvec(p)),
+        };
+        tiles_eq
+            && bitsets_eq(&self.emissive_tiles, &other.emissive_tiles)
+            && bitsets_eq(&self.color_jitter_tiles, &other.color_jitter_tiles)
+            && bitsets_eq(&self.overhang_exclusions, &other.overhang_exclusions)
+            && bitsets_eq(&self.tile_overhang_flags, &other.tile_overhang_flags)
+            && bitsets_eq(self.terrain_rules(), other.terrain_rules())
+    }
+
+    /// Feed the hashes of all rows to `hasher`, updating the ones written since the last call.
+    fn hash_rows(&self, hasher: &mut impl Hasher) {
+        let size = self.map_size();
+        let mut state = self.row_hashes.state();
+        let dirty = match state.rows.len() == size.y as usize {
+            true => state.dirty.take().unwrap_or(0..0),
+            false => {
+                state.rows = vec![0; size.y as usize];
+                state.dirty = None;
+                0..size.y
+            }
+        };
+
+        let uniform_row = self.uniform_tile().map(|tile| vec![tile; size.x as usize]);
+        for y in dirty.start..dirty.end.min(size.y) {
+            let row = match &uniform_row {
+                Some(row) => row.as_slice(),
+                None => {
+                    let start = (y * size.x) as usize;
+                    &self.map_texture[start..start + size.x as usize]
+                }
+            };
+            let mut hasher = DefaultHasher::new();
+            row.hash(&mut hasher);
+            state.rows[y as usize] = hasher.finish();
+        }
+        state.rows.hash(hasher);
+    }
+}
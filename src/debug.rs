@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+use super::{map::Map, plugin::Customization};
+
+/// Debug visualizations for all maps, eg. for checking which parts of a map are written.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_fast_tilemap::prelude::*;
+/// App::new().insert_resource(FastTileMapDebug::show_dirty_regions(true));
+/// ```
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct FastTileMapDebug {
+    /// Tint the bounding rectangle of the tiles written through indexers before each upload
+    /// of a map, fading out over `dirty_region_frames` frames.
+    pub dirty_regions: bool,
+    pub dirty_region_frames: u32,
+    pub dirty_region_color: Color,
+}
+
+impl Default for FastTileMapDebug {
+    fn default() -> Self {
+        Self {
+            dirty_regions: false,
+            dirty_region_frames: 20,
+            dirty_region_color: Color::srgba(1.0, 0.0, 1.0, 0.6),
+        }
+    }
+}
+
+impl FastTileMapDebug {
+    pub fn show_dirty_regions(show: bool) -> Self {
+        Self {
+            dirty_regions: show,
+            ..default()
+        }
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Tiles currently tinted for [`FastTileMapDebug::dirty_regions`] (`max` exclusive) and the
+    /// strength of the tint, if any.
+    pub fn debug_dirty_region(&self) -> Option<(URect, f32)> {
+        let uniform = &self.map_uniform;
+        (uniform.dirty_region_highlight > 0.0).then(|| {
+            (
+                URect::from_corners(uniform.dirty_region_min, uniform.dirty_region_max),
+                uniform.dirty_region_highlight,
+            )
+        })
+    }
+}
+
+/// Pass the written rectangles of the maps to their uniforms (with [`FastTileMapDebug`]) and
+/// fade them out.
+/// Maps are only modified if they were written or are still fading, so this does not cause
+/// uploads by itself, except for the fade.
+pub fn update_map_debug<C: Customization>(
+    debug: Res<FastTileMapDebug>,
+    mut maps: ResMut<Assets<Map<C>>>,
+) {
+    let outdated: Vec<_> = maps
+        .iter()
+        .filter(|(_, map)| map.written.is_some() || map.map_uniform.dirty_region_highlight > 0.0)
+        .map(|(id, _)| id)
+        .collect();
+
+    let fade = 1.0 / debug.dirty_region_frames.max(1) as f32;
+    for id in outdated {
+        let Some(map) = maps.get_mut(id) else {
+            continue;
+        };
+        let uniform = &mut map.map_uniform;
+        match map.written.take() {
+            Some(rect) if debug.dirty_regions => {
+                uniform.dirty_region_min = rect.min;
+                uniform.dirty_region_max = rect.max;
+                uniform.dirty_region_highlight = 1.0;
+                uniform.dirty_region_color = debug.dirty_region_color.to_linear().to_vec4();
+            }
+            _ if debug.dirty_regions => {
+                uniform.dirty_region_highlight = (uniform.dirty_region_highlight - fade).max(0.0);
+            }
+            _ => uniform.dirty_region_highlight = 0.0,
+        }
+    }
+}
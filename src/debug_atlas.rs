@@ -0,0 +1,68 @@
+use bevy::{
+    math::vec2,
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+
+/// Number of tiles per row (and column) in the debug atlas.
+const TILES_PER_ROW: u32 = 8;
+const TILE_SIZE: u32 = 16;
+
+/// Colors of the two index bits encoded in each quadrant.
+const BIT_COLORS: [[u8; 4]; 4] = [
+    [0, 0, 0, 255],
+    [230, 60, 50, 255],
+    [70, 200, 80, 255],
+    [60, 110, 230, 255],
+];
+const BORDER_COLOR: [u8; 4] = [128, 128, 128, 255];
+const MARKER_COLOR: [u8; 4] = [255, 255, 255, 255];
+
+/// Color of the pixel at `p` of debug tile `index`.
+fn debug_tile_pixel(index: u32, p: UVec2) -> [u8; 4] {
+    if p.x == 0 || p.y == 0 || p.x == TILE_SIZE - 1 || p.y == TILE_SIZE - 1 {
+        return BORDER_COLOR;
+    }
+    let half = TILE_SIZE / 2;
+    let quadrant = (p.x >= half) as u32 + 2 * (p.y >= half) as u32;
+    if quadrant == 3 {
+        return MARKER_COLOR;
+    }
+    BIT_COLORS[((index >> (2 * quadrant)) & 3) as usize]
+}
+
+/// Add an atlas of 64 numbered 16x16 tiles (without padding) to `images`, so maps can be tried
+/// out without any asset files.
+/// Returns the atlas handle and the tile size.
+///
+/// Tiles are numbered row by row and show their index as colored quadrants, so wrong tile
+/// indices are easy to spot: The top left, top right and bottom left quadrants encode bits 0-1,
+/// 2-3 and 4-5 of the index (black, red, green or blue for 0 to 3), the bottom right quadrant is
+/// white so the orientation of a tile is visible.
+pub fn debug_atlas(images: &mut Assets<Image>) -> (Handle<Image>, Vec2) {
+    let size = TILES_PER_ROW * TILE_SIZE;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let index = (y / TILE_SIZE) * TILES_PER_ROW + x / TILE_SIZE;
+            let p = UVec2::new(x % TILE_SIZE, y % TILE_SIZE);
+            data.extend(debug_tile_pixel(index, p));
+        }
+    }
+
+    let image = Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    );
+    (images.add(image), vec2(TILE_SIZE as f32, TILE_SIZE as f32))
+}
@@ -0,0 +1,253 @@
+use std::collections::VecDeque;
+
+use bevy::{math::vec2, prelude::*, render::render_resource::ShaderType};
+
+use super::{map::Map, plugin::Customization, tile_projection::TileStagger};
+
+/// Size (in tiles) of the cells of the grid used to find the decals covering a fragment.
+const DECAL_CELL_SIZE: u32 = 8;
+
+/// A small image (a tile of the map's atlas) stamped onto the map at an arbitrary position,
+/// eg. a scorch mark, see [`Map::add_decal`].
+///
+/// Decals are not entities and not part of the tile data: they are rendered on top of the map
+/// (including overhangs) and move with it.
+#[derive(Debug, Clone, Copy)]
+pub struct Decal {
+    /// Center of the decal in local coordinates of the map entity
+    /// (ie. world coordinates if the map entity is not transformed).
+    pub position: Vec2,
+    /// Index of the atlas tile to render.
+    pub atlas_index: u32,
+    /// Counter clockwise rotation, in radians.
+    pub rotation: f32,
+    /// Scale relative to the tile size.
+    pub scale: f32,
+    /// If set, the decal is removed after this many seconds.
+    pub ttl: Option<f32>,
+}
+
+impl Decal {
+    pub fn new(position: Vec2, atlas_index: u32) -> Self {
+        Self {
+            position,
+            atlas_index,
+            rotation: 0.0,
+            scale: 1.0,
+            ttl: None,
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_ttl(mut self, ttl: f32) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+}
+
+/// Decal as stored in the storage buffer, see `Decal` in the shader.
+#[derive(ShaderType, Debug, Clone, Copy, Default)]
+pub(crate) struct GpuDecal {
+    map_position: Vec2,
+    rotation: Vec2,
+    scale: f32,
+    atlas_index: u32,
+}
+
+/// Decals of a map, in the order they were added.
+#[derive(Debug, Clone)]
+pub(crate) struct MapDecals {
+    /// Decals and (once known) the elapsed time at which they expire.
+    decals: VecDeque<(Decal, Option<f32>)>,
+    capacity: usize,
+    /// Earliest expiry time of all decals
+    next_expiry: Option<f32>,
+    /// True iff the GPU data needs to be rebuilt.
+    pub(crate) dirty: bool,
+}
+
+impl Default for MapDecals {
+    fn default() -> Self {
+        Self {
+            decals: VecDeque::new(),
+            capacity: 4096,
+            next_expiry: None,
+            dirty: false,
+        }
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Add a decal.
+    /// If the map already holds [`Self::decal_capacity`] decals, the oldest one is removed.
+    ///
+    /// Decals of a map are uploaded along with the map data, so adding many of them at once
+    /// is cheap, but changing them frequently on very large maps is not.
+    pub fn add_decal(&mut self, decal: Decal) {
+        if self.decals.capacity == 0 {
+            return;
+        }
+        while self.decals.decals.len() >= self.decals.capacity {
+            self.decals.decals.pop_front();
+        }
+        self.decals.decals.push_back((decal, None));
+        self.decals.dirty = true;
+    }
+
+    pub fn clear_decals(&mut self) {
+        self.decals.decals.clear();
+        self.decals.next_expiry = None;
+        self.decals.dirty = true;
+    }
+
+    /// All decals currently on the map, oldest first.
+    pub fn decals(&self) -> impl Iterator<Item = &Decal> {
+        self.decals.decals.iter().map(|(decal, _)| decal)
+    }
+
+    /// Maximum number of decals on this map, defaults to 4096.
+    pub fn decal_capacity(&self) -> usize {
+        self.decals.capacity
+    }
+
+    /// Set the maximum number of decals, removing the oldest decals if there are too many.
+    pub fn set_decal_capacity(&mut self, capacity: usize) {
+        self.decals.capacity = capacity;
+        let excess = self.decals.decals.len().saturating_sub(capacity);
+        if excess > 0 {
+            self.decals.decals.drain(..excess);
+            self.decals.dirty = true;
+        }
+    }
+
+    /// Move all decals by `offset` (in local coordinates).
+    pub(crate) fn move_decals(&mut self, offset: Vec2) {
+        for (decal, _) in self.decals.decals.iter_mut() {
+            decal.position += offset;
+        }
+        self.decals.dirty = true;
+    }
+
+    /// True iff the decals need to be updated at elapsed time `now`.
+    pub(crate) fn decals_need_update(&self, now: f32) -> bool {
+        self.decals.dirty || self.decals.next_expiry.is_some_and(|t| t <= now)
+    }
+
+    /// Remove expired decals and rebuild the GPU data if anything changed.
+    pub(crate) fn update_decals(&mut self, now: f32) {
+        let decals = &mut self.decals;
+        for (decal, expiry) in decals.decals.iter_mut() {
+            if expiry.is_none() {
+                *expiry = decal.ttl.map(|ttl| now + ttl);
+            }
+        }
+        let n = decals.decals.len();
+        decals
+            .decals
+            .retain(|(_, expiry)| expiry.is_none_or(|t| t > now));
+        decals.next_expiry = decals
+            .decals
+            .iter()
+            .filter_map(|(_, expiry)| *expiry)
+            .reduce(f32::min);
+        if !decals.dirty && decals.decals.len() == n {
+            return;
+        }
+        decals.dirty = false;
+
+        if self.decals.decals.is_empty() || self.map_size().cmpeq(UVec2::ZERO).any() {
+            self.decal_buffer = vec![GpuDecal::default()];
+            self.decal_grid = vec![0];
+            return;
+        }
+
+        let grid_size = (self.map_size() + UVec2::splat(DECAL_CELL_SIZE - 1)) / DECAL_CELL_SIZE;
+        let mut cells = vec![Vec::new(); (grid_size.x * grid_size.y) as usize];
+        let mut buffer = Vec::with_capacity(self.decals.decals.len());
+
+        for (i, (decal, _)) in self.decals.decals.iter().enumerate() {
+            let (sin, cos) = decal.rotation.sin_cos();
+            buffer.push(GpuDecal {
+                map_position: self.local_to_map(decal.position),
+                rotation: vec2(cos, sin),
+                scale: decal.scale,
+                atlas_index: decal.atlas_index,
+            });
+
+            // Cells touched by the bounding square of the bounding circle. Tiles of staggered maps
+            // are shifted by up to one tile against the grid.
+            let margin = match self.map_uniform.stagger() {
+                TileStagger::None => 0.0,
+                _ => 1.0,
+            };
+            let radius = decal.scale.abs() * self.world_tile_size().length() / 2.0;
+            let mut low = Vec2::MAX;
+            let mut high = Vec2::MIN;
+            for corner in [
+                vec2(-1.0, -1.0),
+                vec2(1.0, -1.0),
+                vec2(1.0, 1.0),
+                vec2(-1.0, 1.0),
+            ] {
+                let p = self.local_to_map(decal.position + corner * radius);
+                low = low.min(p);
+                high = high.max(p);
+            }
+            let low = low - Vec2::splat(margin);
+            let high = high + Vec2::splat(margin);
+            let max_cell = grid_size.as_vec2() - Vec2::ONE;
+            let low = (low / DECAL_CELL_SIZE as f32).floor();
+            let high = (high / DECAL_CELL_SIZE as f32).floor();
+            if high.cmplt(Vec2::ZERO).any() || low.cmpgt(max_cell).any() {
+                continue;
+            }
+            let low = low.max(Vec2::ZERO).as_uvec2();
+            let high = high.min(max_cell).as_uvec2();
+            for y in low.y..=high.y {
+                for x in low.x..=high.x {
+                    cells[(y * grid_size.x + x) as usize].push(i as u32);
+                }
+            }
+        }
+
+        let n_cells = cells.len();
+        let mut grid = Vec::with_capacity(4 + n_cells + cells.iter().map(Vec::len).sum::<usize>());
+        grid.extend([DECAL_CELL_SIZE, grid_size.x, grid_size.y]);
+        let mut start = 0;
+        for cell in cells.iter() {
+            grid.push(start);
+            start += cell.len() as u32;
+        }
+        grid.push(start);
+        for cell in cells {
+            grid.extend(cell);
+        }
+
+        self.decal_buffer = buffer;
+        self.decal_grid = grid;
+    }
+}
+
+/// Remove expired decals and prepare changed decals for rendering.
+pub fn update_map_decals<C: Customization>(time: Res<Time>, mut maps: ResMut<Assets<Map<C>>>) {
+    let now = time.elapsed_seconds();
+    let changed: Vec<_> = maps
+        .iter()
+        .filter(|(_, map)| map.decals_need_update(now))
+        .map(|(id, _)| id)
+        .collect();
+    for id in changed {
+        if let Some(map) = maps.get_mut(id) {
+            map.update_decals(now);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    prelude::*,
+    utils::HashSet,
+};
+
+use super::{
+    map::Map,
+    memory::{total_map_memory, MapMemoryInfo},
+    plugin::{Customization, NoCustomization},
+    uniform_batch::MapUniformBatch,
+};
+
+/// Adds diagnostics about the maps of customization `C`
+/// (see [`Self::maps_path`], [`Self::map_uploads_path`], [`Self::map_upload_bytes_path`],
+/// [`Self::map_uniform_writes_path`], [`Self::map_cpu_bytes_path`] and
+/// [`Self::map_gpu_bytes_path`]), eg. for output with `LogDiagnosticsPlugin`.
+///
+/// Each changed map is prepared for rendering with a new bind group, but the uniforms of all maps
+/// share a single buffer that is written once per frame, no matter how many maps changed.
+pub struct MapDiagnosticsPlugin<C: Customization = NoCustomization>(PhantomData<C>);
+
+impl<C: Customization> Default for MapDiagnosticsPlugin<C> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<C: Customization> Plugin for MapDiagnosticsPlugin<C> {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(Self::maps_path()))
+            .register_diagnostic(Diagnostic::new(Self::map_uploads_path()))
+            .register_diagnostic(Diagnostic::new(Self::map_upload_bytes_path()))
+            .register_diagnostic(Diagnostic::new(Self::map_uniform_writes_path()))
+            .register_diagnostic(Diagnostic::new(Self::map_cpu_bytes_path()))
+            .register_diagnostic(Diagnostic::new(Self::map_gpu_bytes_path()))
+            .add_systems(Update, Self::diagnostic_system);
+    }
+}
+
+impl<C: Customization> MapDiagnosticsPlugin<C> {
+    /// Number of `Map<C>` assets.
+    pub fn maps_path() -> DiagnosticPath {
+        DiagnosticPath::new(format!("fast_tilemap/{}/maps", C::short_type_path()))
+    }
+
+    /// Number of `Map<C>` assets extracted (and thus uploaded) for rendering in the last frame.
+    pub fn map_uploads_path() -> DiagnosticPath {
+        DiagnosticPath::new(format!("fast_tilemap/{}/map_uploads", C::short_type_path()))
+    }
+
+    /// Number of bytes uploaded for the extracted maps in the last frame,
+    /// see [`Map::upload_size`].
+    pub fn map_upload_bytes_path() -> DiagnosticPath {
+        DiagnosticPath::new(format!(
+            "fast_tilemap/{}/map_upload_bytes",
+            C::short_type_path()
+        ))
+    }
+
+    /// Number of writes to the uniform buffer shared by all `Map<C>` assets in the last frame,
+    /// at most one. Always zero without a render app.
+    pub fn map_uniform_writes_path() -> DiagnosticPath {
+        DiagnosticPath::new(format!(
+            "fast_tilemap/{}/map_uniform_writes",
+            C::short_type_path()
+        ))
+    }
+
+    /// Bytes of all `Map<C>` assets on the CPU, see [`MapMemoryInfo::cpu_bytes`].
+    pub fn map_cpu_bytes_path() -> DiagnosticPath {
+        DiagnosticPath::new(format!(
+            "fast_tilemap/{}/map_cpu_bytes",
+            C::short_type_path()
+        ))
+    }
+
+    /// Estimated bytes of all `Map<C>` assets on the GPU, including their atlases (each counted
+    /// once), see [`total_map_memory`].
+    pub fn map_gpu_bytes_path() -> DiagnosticPath {
+        DiagnosticPath::new(format!(
+            "fast_tilemap/{}/map_gpu_bytes",
+            C::short_type_path()
+        ))
+    }
+
+    pub fn diagnostic_system(
+        mut diagnostics: Diagnostics,
+        maps: Res<Assets<Map<C>>>,
+        images: Option<Res<Assets<Image>>>,
+        mut ev_asset: EventReader<AssetEvent<Map<C>>>,
+        uniform_batch: Option<Res<MapUniformBatch<C>>>,
+        mut uniform_writes: Local<u64>,
+    ) {
+        // Changed maps are extracted once per frame, no matter how often they changed
+        let uploaded: HashSet<AssetId<Map<C>>> = ev_asset
+            .read()
+            .filter_map(|ev| match ev {
+                AssetEvent::Added { id } | AssetEvent::Modified { id } => Some(*id),
+                _ => None,
+            })
+            .collect();
+        diagnostics.add_measurement(&Self::maps_path(), || maps.len() as f64);
+        diagnostics.add_measurement(&Self::map_uploads_path(), || uploaded.len() as f64);
+        diagnostics.add_measurement(&Self::map_upload_bytes_path(), || {
+            uploaded
+                .iter()
+                .filter_map(|id| maps.get(*id))
+                .map(|map| map.upload_size())
+                .sum::<u64>() as f64
+        });
+        let writes = uniform_batch.map_or(0, |batch| batch.writes());
+        let new_writes = writes - *uniform_writes;
+        *uniform_writes = writes;
+        diagnostics.add_measurement(&Self::map_uniform_writes_path(), || new_writes as f64);
+
+        // Without images (eg. headless), atlases count as not loaded
+        let memory = match images {
+            Some(images) => total_map_memory(&maps, &images),
+            None => maps
+                .iter()
+                .map(|(_, map)| map.buffer_memory_info())
+                .sum::<MapMemoryInfo>(),
+        };
+        diagnostics.add_measurement(&Self::map_cpu_bytes_path(), || memory.cpu_bytes() as f64);
+        diagnostics.add_measurement(&Self::map_gpu_bytes_path(), || memory.gpu_bytes() as f64);
+    }
+}
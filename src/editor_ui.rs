@@ -0,0 +1,214 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use super::{map::Map, map_assets::MapAssetsExt, plugin::Customization};
+
+/// Tool applied by [`EditorUiState::apply_tool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EditorTool {
+    /// Square of [`EditorUiState::brush_size`] tiles around the hovered tile.
+    #[default]
+    Brush,
+    /// Flood fill from the hovered tile, see [`crate::map::MapIndexerMut::flood_fill`].
+    Fill,
+    /// Select the hovered tile value.
+    Pick,
+}
+
+/// State of a [`fast_tilemap_editor_ui`] panel.
+#[derive(Debug, Clone)]
+pub struct EditorUiState {
+    /// egui texture of the map atlas,
+    /// eg. from `EguiContexts::add_image(map.atlas_texture().clone())`.
+    pub atlas_texture: egui::TextureId,
+    /// Tile value placed by the tools.
+    pub selected: u32,
+    pub tool: EditorTool,
+    /// Edge length of the brush, in tiles.
+    pub brush_size: u32,
+    /// Tile under the cursor, see [`Self::hover`].
+    pub hovered: Option<UVec2>,
+    /// Width of the tiles in the palette, in egui points.
+    pub palette_tile_width: f32,
+}
+
+impl EditorUiState {
+    pub fn new(atlas_texture: egui::TextureId) -> Self {
+        Self {
+            atlas_texture,
+            selected: 0,
+            tool: EditorTool::default(),
+            brush_size: 1,
+            hovered: None,
+            palette_tile_width: 32.0,
+        }
+    }
+
+    /// Set the hovered tile to the tile of `map` at `world`
+    /// (eg. from [`crate::picking::cursor_to_world`]), if any.
+    ///
+    /// Skip this while the pointer is over egui (`egui::Context::is_pointer_over_area`), so the
+    /// hovered tile stays put and can be edited in the panel.
+    pub fn hover<C: Customization>(
+        &mut self,
+        map: &Map<C>,
+        transform: &GlobalTransform,
+        world: Option<Vec2>,
+    ) {
+        self.hovered = world
+            .map(|world| map.world_to_map_with(transform, world).floor())
+            .filter(|p| p.cmpge(Vec2::ZERO).all() && p.cmplt(map.map_size().as_vec2()).all())
+            .map(|p| p.as_uvec2());
+    }
+
+    /// Apply the current tool at the hovered tile of `map`, eg. while a mouse button is pressed
+    /// over the map. Returns whether the map changed, it is only modified (and re-uploaded) if
+    /// the tool changes any tiles.
+    pub fn apply_tool<C: Customization>(
+        &mut self,
+        maps: &mut Assets<Map<C>>,
+        map: impl Into<AssetId<Map<C>>>,
+    ) -> bool {
+        let id = map.into();
+        let (Some(tile), Some(map)) = (self.hovered, maps.get(id)) else {
+            return false;
+        };
+        if self.tool == EditorTool::Pick {
+            self.selected = map.indexer().at_uvec(tile);
+            return false;
+        }
+        maps.modify_if(
+            id,
+            |map| self.would_change(map, tile),
+            |map| {
+                let mut m = map.indexer_mut();
+                match self.tool {
+                    EditorTool::Brush => m.region_mut(self.brush_rect(tile)).fill(self.selected),
+                    EditorTool::Fill => {
+                        m.flood_fill(tile, self.selected);
+                    }
+                    EditorTool::Pick => {}
+                }
+            },
+        )
+    }
+
+    /// Whether the current tool changes `map` when applied at `tile`.
+    fn would_change<C: Customization>(&self, map: &Map<C>, tile: UVec2) -> bool {
+        let m = map.indexer();
+        match self.tool {
+            EditorTool::Brush => {
+                let rect = self.brush_rect(tile);
+                let rect = URect::from_corners(rect.min, rect.max.min(m.size()));
+                (rect.min.y..rect.max.y)
+                    .flat_map(|y| (rect.min.x..rect.max.x).map(move |x| (x, y)))
+                    .any(|(x, y)| m.at(x, y) != self.selected)
+            }
+            EditorTool::Fill => m.at_uvec(tile) != self.selected,
+            EditorTool::Pick => false,
+        }
+    }
+
+    /// Tiles covered by the brush at `tile`, `max` exclusive.
+    fn brush_rect(&self, tile: UVec2) -> URect {
+        let size = UVec2::splat(self.brush_size.max(1));
+        let min = tile.as_ivec2() - ((size - 1) / 2).as_ivec2();
+        let max = min + size.as_ivec2();
+        URect::from_corners(min.max(IVec2::ZERO).as_uvec2(), max.as_uvec2())
+    }
+}
+
+/// Tile editor panel for `map`: tool buttons, the hovered tile (editable), a button for filling
+/// the map and the atlas as a palette for selecting the tile to place.
+/// Returns whether the map was changed from the panel.
+///
+/// The app decides where to show the panel and how to wire input: Update
+/// `state` with [`EditorUiState::hover`] and call [`EditorUiState::apply_tool`] on clicks.
+/// As `map` is borrowed mutably, a map from [`Assets::get_mut`] is re-uploaded every frame the
+/// panel is shown, so prefer smaller maps (or layers) for editing.
+pub fn fast_tilemap_editor_ui<C: Customization>(
+    ui: &mut egui::Ui,
+    map: &mut Map<C>,
+    images: &Assets<Image>,
+    state: &mut EditorUiState,
+) -> bool {
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        ui.selectable_value(&mut state.tool, EditorTool::Brush, "Brush");
+        ui.selectable_value(&mut state.tool, EditorTool::Fill, "Fill");
+        ui.selectable_value(&mut state.tool, EditorTool::Pick, "Pick");
+    });
+    if state.tool == EditorTool::Brush {
+        ui.add(egui::Slider::new(&mut state.brush_size, 1..=16).text("Brush size"));
+    }
+
+    ui.horizontal(|ui| match state.hovered {
+        Some(tile) => {
+            ui.label(format!("Tile ({}, {}):", tile.x, tile.y));
+            let mut value = map.indexer().at_uvec(tile);
+            if ui.add(egui::DragValue::new(&mut value)).changed() {
+                map.indexer_mut().set_uvec(tile, value);
+                changed = true;
+            }
+        }
+        None => {
+            ui.label("No tile hovered");
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(format!("Selected: {}", state.selected));
+        if ui.button("Fill map").clicked() {
+            let size = map.map_size();
+            map.indexer_mut()
+                .region_mut(URect::from_corners(UVec2::ZERO, size))
+                .fill(state.selected);
+            changed = true;
+        }
+    });
+
+    ui.separator();
+    atlas_palette(ui, map, images, state);
+    changed
+}
+
+/// Atlas tiles as buttons selecting their index.
+fn atlas_palette<C: Customization>(
+    ui: &mut egui::Ui,
+    map: &Map<C>,
+    images: &Assets<Image>,
+    state: &mut EditorUiState,
+) {
+    let Some(atlas_size) = images
+        .get(map.atlas_texture())
+        .map(|image| image.size().as_vec2())
+    else {
+        ui.label("Atlas not loaded");
+        return;
+    };
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        ui.horizontal_wrapped(|ui| {
+            for (index, rect) in (0..).map_while(|i| map.atlas_rect(i).map(|rect| (i, rect))) {
+                let uv = egui::Rect::from_min_max(
+                    to_pos2(rect.min / atlas_size),
+                    to_pos2(rect.max / atlas_size),
+                );
+                let width = state.palette_tile_width;
+                let size = egui::vec2(width, width * rect.height() / rect.width());
+                let image =
+                    egui::Image::new(egui::load::SizedTexture::new(state.atlas_texture, size))
+                        .uv(uv);
+                let button = egui::ImageButton::new(image).selected(state.selected == index);
+                if ui.add(button).on_hover_text(index.to_string()).clicked() {
+                    state.selected = index;
+                }
+            }
+        });
+    });
+}
+
+fn to_pos2(v: Vec2) -> egui::Pos2 {
+    egui::pos2(v.x, v.y)
+}
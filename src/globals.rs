@@ -0,0 +1,32 @@
+use bevy::prelude::*;
+
+use super::{map::Map, plugin::Customization};
+
+/// Settings applied to all maps, can be changed at any time and take effect in the same frame
+/// (in [`crate::plugin::MapSystems::Prepare`]).
+#[derive(Resource, Debug, Clone, Default, Reflect)]
+#[reflect(Resource)]
+pub struct FastTileMapGlobals {
+    /// Snap all maps to the screen pixel grid, in addition to the maps that have pixel
+    /// snapping enabled themselves (see [`Map::set_pixel_snap`]).
+    /// Ie. while this is set every map is snapped, while it is unset only those maps are.
+    pub pixel_snap: bool,
+}
+
+/// Apply the [`FastTileMapGlobals`] to all maps.
+/// Only maps whose effective settings change are modified (and thus re-extracted).
+pub fn apply_map_globals<C: Customization>(
+    globals: Res<FastTileMapGlobals>,
+    mut maps: ResMut<Assets<Map<C>>>,
+) {
+    let outdated: Vec<_> = maps
+        .iter()
+        .filter(|(_, map)| map.is_pixel_snapped() != (globals.pixel_snap || map.pixel_snap()))
+        .map(|(id, _)| id)
+        .collect();
+    for id in outdated {
+        if let Some(map) = maps.get_mut(id) {
+            map.map_uniform.pixel_snap = (globals.pixel_snap || map.pixel_snap) as u32;
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use bevy::{ecs::system::SystemId, prelude::*, utils::HashSet};
+
+use super::{map::Map, plugin::Customization};
+
+/// Lifecycle hooks of a map entity: One-shot systems (see [`World::register_system`]) the plugin
+/// runs with the map entity as input at the points documented below.
+///
+/// Hooks are run with [`Commands::run_system_with_input`] by the system that reached the point,
+/// so they run right after it (at the next command flush) with full [`World`] access.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_fast_tilemap::prelude::*;
+/// fn log_loaded(In(entity): In<Entity>, maps: Query<&Handle<Map>>) {
+///     info!("Map {:?} of {entity} is loaded", maps.get(entity).ok());
+/// }
+///
+/// fn setup(world: &mut World) {
+///     let on_loaded = world.register_system(log_loaded);
+///     world.spawn((
+///         MapBundleManaged::<NoCustomization>::default(),
+///         MapHooks {
+///             on_loaded: Some(on_loaded),
+///             ..default()
+///         },
+///     ));
+/// }
+/// ```
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct MapHooks {
+    /// Run once the map finished loading, by [`crate::map::update_loading_maps`]:
+    /// [`crate::map::MapLoading`] is removed and the managed mesh is inserted by then.
+    pub on_loaded: Option<SystemId<Entity>>,
+    /// Run in each frame in which the map asset changed, by [`run_map_sync_hooks`] in `Last`
+    /// after all changes of the frame and right before the map is extracted for rendering.
+    /// Changes the hook makes to the map are synced (and trigger the hook again) in the next
+    /// frame, so it should only modify the map when needed.
+    pub before_sync: Option<SystemId<Entity>>,
+    /// Run after the entity got a new managed mesh, by
+    /// [`crate::map::update_map_vertex_attributes`] and
+    /// [`crate::viewport_fit::update_viewport_fit_meshes`] (not for the mesh inserted on loading).
+    /// Maps with a mesh of their own (see [`crate::shared_mesh::SharedMapMeshes`]) get a new
+    /// mesh every frame for the animation state.
+    pub after_mesh_rebuild: Option<SystemId<Entity>>,
+}
+
+/// Run `hook` (if any) of `entity` after the current system.
+pub(crate) fn run_hook(commands: &mut Commands, hook: Option<SystemId<Entity>>, entity: Entity) {
+    if let Some(hook) = hook {
+        commands.run_system_with_input(hook, entity);
+    }
+}
+
+/// Run the [`MapHooks::before_sync`] hooks of entities whose map changed this frame.
+/// Has to run after [`bevy::asset::AssetEvents`].
+pub fn run_map_sync_hooks<C: Customization>(
+    mut commands: Commands,
+    mut ev_asset: EventReader<AssetEvent<Map<C>>>,
+    maps: Query<(Entity, &Handle<Map<C>>, &MapHooks)>,
+) {
+    let changed: HashSet<AssetId<Map<C>>> = ev_asset
+        .read()
+        .filter_map(|ev| match ev {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if changed.is_empty() {
+        return;
+    }
+
+    for (entity, handle, hooks) in maps.iter() {
+        if changed.contains(&handle.id()) {
+            run_hook(&mut commands, hooks.before_sync, entity);
+        }
+    }
+}
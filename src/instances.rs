@@ -0,0 +1,158 @@
+use bevy::{
+    math::{vec2, Vec3Swizzles},
+    prelude::*,
+    render::{mesh::Indices, render_asset::RenderAssetUsages, render_resource::PrimitiveTopology},
+};
+
+use super::{
+    map::{
+        Map, MapAttributes, ATTRIBUTE_ANIMATION_STATE, ATTRIBUTE_MAP_POSITION, ATTRIBUTE_MIX_COLOR,
+    },
+    plugin::Customization,
+};
+
+/// Render a map with a managed mesh (see [`MeshManagedByMap`](super::map::MeshManagedByMap))
+/// several times, once for each of `transforms` (relative to the map entity).
+///
+/// All instances are part of a single mesh, so they share the map data, bind group and draw call,
+/// making this much cheaper than spawning one map entity per instance.
+/// `MapAttributes::mix_color` is applied to all instances alike (4 entries, one per corner).
+#[derive(Component, Default, Clone, Debug)]
+pub struct MapInstances {
+    pub transforms: Vec<Transform>,
+}
+
+impl MapInstances {
+    pub fn new(transforms: Vec<Transform>) -> Self {
+        Self { transforms }
+    }
+
+    /// Map position at `world` in instance `instance`, for a map entity with the given
+    /// `transform`.
+    pub fn world_to_map<C: Customization>(
+        &self,
+        map: &Map<C>,
+        instance: usize,
+        world: Vec2,
+        transform: &GlobalTransform,
+    ) -> Option<Vec2> {
+        let instance = transform.affine() * self.transforms.get(instance)?.compute_affine();
+        let local = instance.inverse().transform_point3(world.extend(0.0));
+        Some(map.local_to_map(local.xy()))
+    }
+
+    /// The topmost instance (ie. the last in `transforms`) covering the map at `world`
+    /// and the map position in that instance.
+    pub fn instance_at<C: Customization>(
+        &self,
+        map: &Map<C>,
+        world: Vec2,
+        transform: &GlobalTransform,
+    ) -> Option<(usize, Vec2)> {
+        let size = map.map_size().as_vec2();
+        (0..self.transforms.len()).rev().find_map(|instance| {
+            let p = self.world_to_map(map, instance, world, transform)?;
+            (p.cmpge(Vec2::ZERO).all() && p.cmplt(size).all()).then_some((instance, p))
+        })
+    }
+
+    /// Mesh with one quad of the size of the map (see [`Map::world_size`]) per instance.
+    pub(crate) fn mesh<C: Customization>(
+        &self,
+        map: &Map<C>,
+        attributes: Option<&MapAttributes>,
+        time: &Time,
+    ) -> Mesh {
+        let half_size = map.world_size() / 2.0;
+        let corners = [
+            vec2(half_size.x, half_size.y),
+            vec2(-half_size.x, half_size.y),
+            vec2(-half_size.x, -half_size.y),
+            vec2(half_size.x, -half_size.y),
+        ];
+        let uvs = [[1.0, 0.0], [0.0, 0.0], [0.0, 1.0], [1.0, 1.0]];
+        let colors: [Vec4; 4] = std::array::from_fn(|i| {
+            attributes
+                .and_then(|a| a.mix_color.get(i).copied())
+                .unwrap_or(Vec4::ONE)
+        });
+        let map_positions = corners.map(|c| map.local_to_grid(c));
+
+        let n = self.transforms.len();
+        let mut positions = Vec::with_capacity(4 * n);
+        let mut indices = Vec::with_capacity(6 * n);
+        for (i, transform) in self.transforms.iter().enumerate() {
+            let base = 4 * i as u32;
+            positions.extend(corners.map(|c| transform.transform_point(c.extend(0.0)).to_array()));
+            indices.extend([0, 1, 2, 0, 2, 3].map(|j| base + j));
+        }
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; 4 * n])
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs.repeat(n))
+        .with_inserted_attribute(ATTRIBUTE_MIX_COLOR, colors.repeat(n))
+        .with_inserted_attribute(ATTRIBUTE_MAP_POSITION, map_positions.repeat(n))
+        .with_inserted_attribute(
+            ATTRIBUTE_ANIMATION_STATE,
+            vec![time.elapsed_seconds_wrapped(); 4 * n],
+        )
+        .with_inserted_indices(Indices::U32(indices))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{math::uvec2, render::mesh::VertexAttributeValues};
+
+    use super::*;
+
+    #[test]
+    fn instances_repeat_the_map_quad() {
+        let map: Map = Map::builder(uvec2(8, 4), default(), vec2(16.0, 16.0)).build();
+        let single = MapInstances::new(vec![Transform::default()]).mesh(&map, None, &default());
+        let transforms = vec![
+            Transform::default(),
+            Transform::from_xyz(200.0, 0.0, 0.0),
+            Transform::from_xyz(0.0, 100.0, 1.0).with_scale(Vec3::splat(0.5)),
+        ];
+        let mesh = MapInstances::new(transforms.clone()).mesh(&map, None, &default());
+
+        assert_eq!(single.count_vertices(), 4);
+        assert_eq!(mesh.count_vertices(), 3 * 4);
+        assert_eq!(mesh.indices().map(|i| i.len()), Some(3 * 6));
+        let Some(VertexAttributeValues::Float32x2(single_positions)) =
+            single.attribute(ATTRIBUTE_MAP_POSITION)
+        else {
+            panic!("no map positions");
+        };
+        let Some(VertexAttributeValues::Float32x2(map_positions)) =
+            mesh.attribute(ATTRIBUTE_MAP_POSITION)
+        else {
+            panic!("no map positions");
+        };
+        assert_eq!(map_positions, &single_positions.repeat(3));
+
+        // Only the vertex positions are transformed
+        let Some(VertexAttributeValues::Float32x3(corners)) =
+            single.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("no positions");
+        };
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("no positions");
+        };
+        for (i, transform) in transforms.iter().enumerate() {
+            let expected: Vec<[f32; 3]> = corners
+                .iter()
+                .map(|c| transform.transform_point(Vec3::from(*c)).to_array())
+                .collect();
+            assert_eq!(positions[4 * i..4 * (i + 1)], expected, "{i}");
+        }
+    }
+}
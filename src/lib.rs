@@ -17,20 +17,149 @@
 //! rendered as a single quad and a shader cares for rendering the correct tiles at the correct
 //! position.
 
+// `#[derive(ShaderType)]` emits never-called `check` functions.
+#[allow(dead_code)]
+pub mod additional_atlases;
+pub mod anchor;
+pub mod any_map;
+pub mod async_fill;
+pub mod atlas_metadata;
+pub mod autotile;
+pub mod autotile_rules;
 pub mod bundle;
+pub mod change_ticks;
+pub mod chunked;
+pub mod cluster;
+pub mod content_hash;
+pub mod debug;
+#[cfg(feature = "debug-atlas")]
+pub mod debug_atlas;
+// `#[derive(ShaderType)]` emits never-called `check` functions.
+#[allow(dead_code)]
+pub mod decal;
+pub mod diagnostics;
+#[cfg(feature = "editor-ui")]
+pub mod editor_ui;
+pub mod globals;
+pub mod hooks;
+pub mod instances;
+// `#[derive(ShaderType)]` emits never-called `check` functions.
+#[allow(dead_code)]
 pub mod map;
+pub mod map_assets;
 pub mod map_builder;
+pub mod map_bytes;
+// `#[derive(ShaderType)]` emits never-called `check` functions.
+#[allow(dead_code)]
 pub mod map_uniform;
+pub mod memory;
+pub mod minimap;
+#[cfg(feature = "motion-vectors")]
+pub mod motion_vectors;
+pub mod neighborhood;
+pub mod overhang_flags;
+pub mod overlay_canvas;
+pub mod palette;
+pub mod patch;
+#[cfg(feature = "pathfinding")]
+pub mod pathfinding;
+pub mod picking;
 pub mod plugin;
+pub mod preview;
+pub mod region;
+pub mod region_mut;
+pub mod render_to_image;
+pub mod resize;
+pub mod selection;
 pub mod shader;
+pub mod shader_snippets;
+pub mod shared_mesh;
+pub mod state_snapshot;
+pub mod tile_animation;
+pub mod tile_changed;
+pub mod tile_colors;
+pub mod tile_flags;
+pub mod tile_layers;
 pub mod tile_projection;
+pub mod tile_ref;
+pub mod tile_upload;
+#[cfg(feature = "tiled")]
+pub mod tiled_map;
+pub mod uniform_batch;
+pub mod update_queue;
+#[cfg(feature = "validation")]
+pub mod validation;
+pub mod viewport_fit;
+pub mod visibility;
+pub mod warmup;
+// `#[derive(ShaderType)]` emits never-called `check` functions.
+#[allow(dead_code)]
+pub mod weather;
+pub mod window_fit;
 
 pub mod prelude {
+    pub use super::additional_atlases::*;
+    pub use super::anchor::*;
+    pub use super::any_map::*;
+    pub use super::async_fill::*;
+    pub use super::atlas_metadata::*;
+    pub use super::autotile::*;
+    pub use super::autotile_rules::*;
     pub use super::bundle::*;
+    pub use super::chunked::*;
+    pub use super::cluster::*;
+    pub use super::content_hash::*;
+    pub use super::debug::*;
+    #[cfg(feature = "debug-atlas")]
+    pub use super::debug_atlas::*;
+    pub use super::decal::*;
+    pub use super::diagnostics::*;
+    #[cfg(feature = "editor-ui")]
+    pub use super::editor_ui::*;
+    pub use super::globals::*;
+    pub use super::hooks::*;
+    pub use super::instances::*;
     pub use super::map::*;
+    pub use super::map_assets::*;
     pub use super::map_builder::*;
+    pub use super::map_bytes::*;
     pub use super::map_uniform::*;
+    pub use super::memory::*;
+    pub use super::minimap::*;
+    #[cfg(feature = "motion-vectors")]
+    pub use super::motion_vectors::*;
+    pub use super::neighborhood::*;
+    pub use super::overhang_flags::*;
+    pub use super::overlay_canvas::*;
+    pub use super::palette::*;
+    pub use super::patch::*;
+    #[cfg(feature = "pathfinding")]
+    pub use super::pathfinding::*;
+    pub use super::picking::*;
     pub use super::plugin::*;
+    pub use super::preview::*;
+    pub use super::region::*;
+    pub use super::region_mut::*;
+    pub use super::shader_snippets::*;
+    pub use super::shared_mesh::*;
+    pub use super::state_snapshot::*;
+    pub use super::tile_animation::*;
+    pub use super::tile_changed::*;
+    pub use super::tile_colors::*;
+    pub use super::tile_flags::*;
+    pub use super::tile_layers::*;
     pub use super::tile_projection::*;
+    pub use super::tile_ref::*;
+    pub use super::tile_upload::*;
+    #[cfg(feature = "tiled")]
+    pub use super::tiled_map::*;
+    pub use super::update_queue::*;
+    #[cfg(feature = "validation")]
+    pub use super::validation::*;
+    pub use super::viewport_fit::*;
+    pub use super::visibility::*;
+    pub use super::warmup::*;
+    pub use super::weather::*;
+    pub use super::window_fit::*;
 
 }
@@ -1,59 +1,244 @@
+use std::{ops::Range, time::Duration};
+
 use bevy::{
-    math::{dmat2, vec2, Vec3Swizzles},
+    asset::LoadState,
+    math::{dmat2, uvec2, vec2, vec3, Vec3Swizzles},
     prelude::*,
     render::{
         mesh::MeshVertexAttribute,
-        render_resource::{AsBindGroup, ShaderDefVal, ShaderRef, ShaderType, VertexFormat},
-        texture::{ImageFilterMode, ImageSampler, ImageSamplerDescriptor},
+        primitives::Aabb,
+        render_asset::RenderAssets,
+        render_resource::{
+            AsBindGroup, AsBindGroupError, BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry,
+            BindingResource, BindingType, BlendComponent, BlendFactor, BlendOperation, BlendState,
+            BufferBinding, BufferBindingType, OwnedBindingResource, PreparedBindGroup,
+            ShaderDefVal, ShaderRef, ShaderStages, ShaderType, UnpreparedBindGroup, VertexFormat,
+        },
+        renderer::RenderDevice,
+        texture::{FallbackImage, GpuImage, ImageFilterMode, ImageSampler, ImageSamplerDescriptor},
     },
     sprite::{Material2d, Mesh2dHandle},
+    utils::{HashMap, HashSet},
 };
 
 use super::{
+    async_fill::MapAsyncFill,
+    atlas_metadata::AtlasMetadataState,
+    autotile::MapAutoTile,
+    change_ticks::ChangeTicks,
+    content_hash::RowHashes,
+    decal::{GpuDecal, MapDecals},
+    hooks::{run_hook, MapHooks},
+    instances::MapInstances,
     map_builder::MapBuilder,
     map_uniform::MapUniform,
+    minimap::MinimapLink,
+    overlay_canvas::OverlayCanvas,
+    patch::MapPatches,
     plugin::{Customization, NoCustomization},
+    preview::TilePreview,
+    region::{MapRegion, MapRegions},
+    shared_mesh::{SharedMapMeshes, MESH_SEGMENT_TILES},
+    tile_animation::TileAnimation,
+    tile_changed::MapTileChanged,
+    tile_layers::EMPTY_TILE,
+    tile_projection::{TileProjection, TileStagger},
+    tile_upload::TileUpload,
+    uniform_batch::{MapUniformSlot, UniformBinding},
+    update_queue::MapUpdates,
+    viewport_fit::{quad_mesh, ViewportFitRect},
 };
 
-const ATTRIBUTE_MAP_POSITION: MeshVertexAttribute =
+pub(crate) const ATTRIBUTE_MAP_POSITION: MeshVertexAttribute =
     MeshVertexAttribute::new("MapPosition", 988779054, VertexFormat::Float32x2);
-const ATTRIBUTE_MIX_COLOR: MeshVertexAttribute =
+pub(crate) const ATTRIBUTE_MIX_COLOR: MeshVertexAttribute =
     MeshVertexAttribute::new("MixColor", 988779055, VertexFormat::Float32x4);
-const ATTRIBUTE_ANIMATION_STATE: MeshVertexAttribute =
+pub(crate) const ATTRIBUTE_ANIMATION_STATE: MeshVertexAttribute =
     MeshVertexAttribute::new("AnimationState", 988779056, VertexFormat::Float32);
 
-#[derive(Debug, Clone, Default, Reflect, AsBindGroup, ShaderType)]
+#[derive(Debug, Clone, Default, PartialEq, Reflect, AsBindGroup, ShaderType)]
 pub struct DefaultUserData {
     x: u32,
 }
 
+/// Extra bindings of a [`Customization`] without any.
+#[derive(Debug, Clone, Default, AsBindGroup)]
+pub struct NoExtraBindings {}
+
 /// Map, holding handles to a map texture with the tile data and an atlas texture
 /// with the tile renderings.
-#[derive(Asset, Debug, Clone, Reflect, AsBindGroup)]
-#[bind_group_data(MapKey)]
+#[derive(Asset, Debug, Clone, Reflect)]
 pub struct Map<C: Customization = NoCustomization> {
     /// Stores all the data that goes into the shader uniform,
     /// such as projection data, offsets, sizes, etc..
-    #[uniform(0)]
     pub(crate) map_uniform: MapUniform,
 
-    #[uniform(1)]
     pub user_data: C::UserData,
 
+    /// Additional bindings for custom shader code, see [`Customization::ExtraBindings`].
+    #[reflect(ignore)]
+    pub(crate) extra_bindings: C::ExtraBindings,
+
     /// Texture containing the tile IDs (one per each pixel)
-    #[storage(100, read_only)]
     pub(crate) map_texture: Vec<u32>,
 
     /// Atlas texture with the individual tiles
-    #[texture(101)]
-    #[sampler(102)]
     pub(crate) atlas_texture: Handle<Image>,
 
+    /// Atlas with the same layout blended in, see [`MapBuilder::with_secondary_atlas`]
+    pub(crate) secondary_atlas: Option<Handle<Image>>,
+
+    /// Atlases selected by the tile values, see [`MapBuilder::with_additional_atlas`]
+    pub(crate) additional_atlases: Vec<Handle<Image>>,
+
+    /// Decals prepared for rendering, see [`Map::add_decal`]
+    #[reflect(ignore)]
+    pub(crate) decal_buffer: Vec<GpuDecal>,
+
+    /// Grid for finding the decals covering a given map position
+    #[reflect(ignore)]
+    pub(crate) decal_grid: Vec<u32>,
+
+    /// Bitset of tile indices excluded from dominance overhangs,
+    /// see [`MapBuilder::with_overhang_exclusions`]
+    #[reflect(ignore)]
+    pub(crate) overhang_exclusions: Vec<u32>,
+
+    /// Bitset of emissive tile indices, see [`MapBuilder::with_emissive_tiles`]
+    #[reflect(ignore)]
+    pub(crate) emissive_tiles: Vec<u32>,
+
+    /// Bitset of tile indices with color jitter, see [`MapBuilder::with_color_jitter`]
+    #[reflect(ignore)]
+    pub(crate) color_jitter_tiles: Vec<u32>,
+
+    /// Tiles of the preview, see [`Map::set_preview`]
+    #[reflect(ignore)]
+    pub(crate) preview_buffer: Vec<u32>,
+
+    #[reflect(ignore)]
+    pub(crate) preview: Option<TilePreview>,
+
+    /// Patch headers and tiles, see [`Map::add_patch`]
+    #[reflect(ignore)]
+    pub(crate) patch_buffer: Vec<u32>,
+
+    #[reflect(ignore)]
+    pub(crate) patches: MapPatches,
+
     pub(crate) perspective_defs: Vec<String>,
     pub(crate) perspective_underhangs: bool,
     pub(crate) perspective_overhangs: bool,
     pub(crate) dominance_overhangs: bool,
     pub(crate) force_underhangs: Vec<Vec2>,
+    pub(crate) blend_mode: MapBlendMode,
+
+    /// Primary and secondary projection, if blending between them is enabled.
+    pub(crate) projections: Option<[TileProjection; 2]>,
+    pub(crate) projection_blend: f32,
+
+    /// Per-map pixel snapping, see [`Map::set_pixel_snap`].
+    pub(crate) pixel_snap: bool,
+
+    /// Animation speed to apply with the next [`apply_map_animation_speeds`],
+    /// see [`Map::set_animation_speed`].
+    pub(crate) pending_animation_speed: Option<f32>,
+
+    /// Whether the map is rendered with a motion vector target (feature `motion-vectors`).
+    pub(crate) motion_vectors: bool,
+
+    /// See [`MapBuilder::with_expected_atlas_columns`]
+    pub(crate) expected_atlas_columns: Option<u32>,
+
+    /// See [`MapBuilder::with_atlas_metadata`]
+    #[reflect(ignore)]
+    pub(crate) atlas_metadata: AtlasMetadataState,
+
+    #[reflect(ignore)]
+    pub(crate) regions: MapRegions,
+
+    #[reflect(ignore)]
+    pub(crate) change_ticks: Option<ChangeTicks>,
+
+    /// Bounding rectangle of the tiles written since the last [`update_map_debug`]
+    #[reflect(ignore)]
+    pub(crate) written: Option<URect>,
+
+    #[reflect(ignore)]
+    pub(crate) decals: MapDecals,
+
+    /// See [`Map::set_autotile`]
+    #[reflect(ignore)]
+    pub(crate) autotile: Option<MapAutoTile>,
+
+    /// Writes that look like they have x and y swapped, see [`MapIndexerMut::set`]
+    #[reflect(ignore)]
+    pub(crate) transposed_writes: u32,
+
+    /// See [`Map::update_queue`]
+    #[reflect(ignore)]
+    pub(crate) updates: MapUpdates,
+
+    /// See [`MapBuilder::with_expected_edit_size`]
+    #[reflect(ignore)]
+    pub(crate) edit_scratch: EditScratch,
+
+    /// See [`Map::content_hash`]
+    #[reflect(ignore)]
+    pub(crate) row_hashes: RowHashes,
+
+    /// See [`MapBuilder::with_overlay_canvas`]
+    #[reflect(ignore)]
+    pub(crate) overlay_canvas: Option<OverlayCanvas>,
+
+    /// Packed color per tile if `tile_color_channel` is set (a single placeholder otherwise),
+    /// see [`MapBuilder::with_tile_color_channel`]
+    #[reflect(ignore)]
+    pub(crate) tile_colors: Vec<u32>,
+    pub(crate) tile_color_channel: bool,
+
+    /// Tile animations ordered by base index, see [`MapBuilder::with_tile_animations`]
+    #[reflect(ignore)]
+    pub(crate) tile_animations: Vec<TileAnimation>,
+
+    /// `tile_animations` prepared for rendering
+    #[reflect(ignore)]
+    pub(crate) tile_animation_buffer: Vec<u32>,
+
+    /// Tiles of the layers above layer 0, one after the other (a single placeholder without
+    /// upper layers), see [`MapBuilder::with_layers`]
+    #[reflect(ignore)]
+    pub(crate) layer_tiles: Vec<u32>,
+
+    /// Overhang directions per atlas index (with `OVERHANG_FLAGS_SET`, 0 for tiles without),
+    /// see [`MapBuilder::with_tile_overhang_flags`]
+    #[reflect(ignore)]
+    pub(crate) tile_overhang_flags: Vec<u32>,
+
+    /// Map whose tiles this minimap renders, see [`Map::minimap_builder`]
+    #[reflect(ignore)]
+    pub(crate) minimap: Option<MinimapLink>,
+
+    /// Packed colors of a minimap per atlas index, see [`MapBuilder::with_minimap_colors`]
+    #[reflect(ignore)]
+    pub(crate) minimap_colors: Vec<u32>,
+
+    /// Blob tile per neighbor mask followed by the packed rule per terrain (a single
+    /// placeholder without rules), see [`MapBuilder::with_autotile`]
+    #[reflect(ignore)]
+    pub(crate) autotile_rules: Vec<u32>,
+
+    /// Rows of `map_texture` to write to the GPU, see [`Map::pending_tile_rows`]
+    #[reflect(ignore)]
+    pub(crate) tile_upload: TileUpload,
+
+    /// Slot of `map_uniform` in the uniform buffer shared by all maps of the customization
+    #[reflect(ignore)]
+    pub(crate) uniform_slot: MapUniformSlot,
+
+    /// Bounding rectangle of the tiles written since the last [`MapTileChanged`]
+    #[reflect(ignore)]
+    pub(crate) tiles_changed: Option<URect>,
 
     pub(crate) _customization: std::marker::PhantomData<C>,
 }
@@ -63,24 +248,463 @@ impl<C: Customization> Default for Map<C> {
         Self {
             map_uniform: Default::default(),
             user_data: Default::default(),
+            extra_bindings: Default::default(),
             map_texture: Vec::new(),
             atlas_texture: Default::default(),
+            secondary_atlas: None,
+            additional_atlases: Vec::new(),
+            decal_buffer: vec![GpuDecal::default()],
+            decal_grid: vec![0],
+            overhang_exclusions: vec![0],
+            emissive_tiles: vec![0],
+            color_jitter_tiles: vec![0],
+            preview_buffer: vec![0],
+            preview: None,
+            patch_buffer: vec![0],
+            patches: Default::default(),
             perspective_defs: Vec::new(),
             perspective_underhangs: true,
             perspective_overhangs: true,
             dominance_overhangs: false,
             force_underhangs: Vec::new(),
+            blend_mode: MapBlendMode::Alpha,
+            projections: None,
+            projection_blend: 0.0,
+            pixel_snap: false,
+            pending_animation_speed: None,
+            motion_vectors: false,
+            expected_atlas_columns: None,
+            atlas_metadata: AtlasMetadataState::Unused,
+            regions: Default::default(),
+            change_ticks: None,
+            written: None,
+            decals: Default::default(),
+            autotile: None,
+            transposed_writes: 0,
+            updates: Default::default(),
+            edit_scratch: Default::default(),
+            row_hashes: Default::default(),
+            overlay_canvas: None,
+            tile_colors: vec![0],
+            tile_color_channel: false,
+            tile_animations: Vec::new(),
+            tile_animation_buffer: vec![0],
+            layer_tiles: vec![EMPTY_TILE],
+            tile_overhang_flags: vec![0],
+            minimap: None,
+            minimap_colors: vec![0],
+            autotile_rules: vec![0],
+            tile_upload: Default::default(),
+            uniform_slot: Default::default(),
+            tiles_changed: None,
             _customization: std::marker::PhantomData,
         }
     }
 }
 
+/// Buffers reused by edits, so large edits don't allocate every time,
+/// see [`MapBuilder::with_expected_edit_size`].
+/// Users take them with `std::mem::take` and put them back afterwards.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EditScratch {
+    /// Stack of [`MapIndexerMut::flood_fill`]
+    pub(crate) positions: Vec<UVec2>,
+    /// Old values of [`MapIndexerMut::step_region`]
+    pub(crate) values: Vec<u32>,
+}
+
+impl EditScratch {
+    /// Reserve enough for edits of up to `tiles` tiles.
+    pub(crate) fn reserve(&mut self, tiles: usize) {
+        // Each filled tile pushes at most 4 neighbors
+        self.positions.reserve(4 * tiles + 1);
+        // The region plus its border is largest for regions of a single row or column
+        self.values.reserve(3 * tiles + 6);
+    }
+}
+
+/// Bindings of the map material (group 2) provided by the map itself, except for the map uniform
+/// at binding 0, which `Map::as_bind_group` binds.
+/// [`Customization::ExtraBindings`] are added to these, at binding 200 and above.
+#[derive(AsBindGroup)]
+pub(crate) struct MapBindings<'a, C: Customization> {
+    #[uniform(1)]
+    user_data: &'a C::UserData,
+
+    #[storage(100, read_only)]
+    map_texture: &'a Vec<u32>,
+
+    #[texture(101)]
+    #[sampler(102)]
+    atlas_texture: Handle<Image>,
+
+    #[storage(103, read_only)]
+    decal_buffer: &'a Vec<GpuDecal>,
+
+    #[storage(104, read_only)]
+    decal_grid: &'a Vec<u32>,
+
+    #[storage(105, read_only)]
+    overhang_exclusions: &'a Vec<u32>,
+
+    #[storage(106, read_only)]
+    preview_buffer: &'a Vec<u32>,
+
+    #[storage(107, read_only)]
+    emissive_tiles: &'a Vec<u32>,
+
+    #[texture(108)]
+    #[sampler(109)]
+    secondary_atlas: Option<Handle<Image>>,
+
+    #[storage(110, read_only)]
+    patch_buffer: &'a Vec<u32>,
+
+    #[storage(111, read_only)]
+    color_jitter_tiles: &'a Vec<u32>,
+
+    #[texture(112)]
+    #[sampler(113)]
+    overlay_canvas: Option<Handle<Image>>,
+
+    #[storage(114, read_only)]
+    tile_colors: &'a Vec<u32>,
+
+    #[storage(115, read_only)]
+    tile_animation_buffer: &'a Vec<u32>,
+
+    #[storage(116, read_only)]
+    layer_tiles: &'a Vec<u32>,
+
+    #[texture(117)]
+    #[sampler(118)]
+    additional_atlas_1: Option<Handle<Image>>,
+
+    #[texture(119)]
+    #[sampler(120)]
+    additional_atlas_2: Option<Handle<Image>>,
+
+    #[texture(121)]
+    #[sampler(122)]
+    additional_atlas_3: Option<Handle<Image>>,
+
+    #[storage(123, read_only)]
+    tile_overhang_flags: &'a Vec<u32>,
+
+    #[storage(124, read_only)]
+    minimap_colors: &'a Vec<u32>,
+
+    #[storage(125, read_only)]
+    autotile_rules: &'a Vec<u32>,
+}
+
+impl<'a, C: Customization> From<&'a Map<C>> for MapBindings<'a, C> {
+    fn from(map: &'a Map<C>) -> Self {
+        Self {
+            user_data: &map.user_data,
+            map_texture: &map.map_texture,
+            atlas_texture: map.atlas_texture.clone(),
+            decal_buffer: &map.decal_buffer,
+            decal_grid: &map.decal_grid,
+            overhang_exclusions: &map.overhang_exclusions,
+            preview_buffer: &map.preview_buffer,
+            emissive_tiles: &map.emissive_tiles,
+            secondary_atlas: map.secondary_atlas.clone(),
+            patch_buffer: &map.patch_buffer,
+            color_jitter_tiles: &map.color_jitter_tiles,
+            overlay_canvas: map.overlay_canvas().cloned(),
+            tile_colors: &map.tile_colors,
+            tile_animation_buffer: &map.tile_animation_buffer,
+            layer_tiles: &map.layer_tiles,
+            additional_atlas_1: map.additional_atlases.first().cloned(),
+            additional_atlas_2: map.additional_atlases.get(1).cloned(),
+            additional_atlas_3: map.additional_atlases.get(2).cloned(),
+            tile_overhang_flags: &map.tile_overhang_flags,
+            minimap_colors: &map.minimap_colors,
+            autotile_rules: &map.autotile_rules,
+        }
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Uniform for binding 0.
+    /// Minimaps render the tiles of their source, as uploaded by the source.
+    fn bound_uniform(&self) -> MapUniform {
+        match &self.minimap {
+            Some(link) => link.uniform(&self.map_uniform),
+            None => self.map_uniform.clone(),
+        }
+    }
+
+    /// All bindings except for the uniform at binding 0.
+    fn bindings(
+        &self,
+        layout: &BindGroupLayout,
+        render_device: &RenderDevice,
+        images: &RenderAssets<GpuImage>,
+        fallback_image: &FallbackImage,
+    ) -> Result<Vec<(u32, OwnedBindingResource)>, AsBindGroupError> {
+        // Extra bindings first, they are the ones most likely to wait for an image
+        let mut bindings = self
+            .extra_bindings
+            .unprepared_bind_group(layout, render_device, images, fallback_image)?
+            .bindings;
+        // The tiles go to a buffer kept across uploads that only changed rows are written to
+        let placeholder = vec![0];
+        let mut map_bindings = MapBindings::from(self);
+        map_bindings.map_texture = &placeholder;
+        let map_bindings = map_bindings
+            .unprepared_bind_group(layout, render_device, images, fallback_image)?
+            .bindings;
+        let tiles = match &self.minimap {
+            Some(link) => link
+                .tiles(self.map_size())
+                .ok_or(AsBindGroupError::RetryNextUpdate)?,
+            None => {
+                let width = self.map_size().x;
+                self.tile_upload
+                    .buffer(render_device, &self.map_texture, width)
+                    .ok_or(AsBindGroupError::RetryNextUpdate)?
+            }
+        };
+        for (binding, resource) in map_bindings {
+            match binding {
+                100 => bindings.push((binding, OwnedBindingResource::Buffer(tiles.clone()))),
+                _ => bindings.push((binding, resource)),
+            }
+        }
+        Ok(bindings)
+    }
+}
+
+impl<C: Customization> AsBindGroup for Map<C> {
+    type Data = MapKey;
+
+    fn label() -> Option<&'static str> {
+        Some("Map")
+    }
+
+    /// Binds the uniform at the slot of the map in a buffer shared by all maps of the
+    /// customization, which is written to the GPU once per frame for all of them.
+    fn as_bind_group(
+        &self,
+        layout: &BindGroupLayout,
+        render_device: &RenderDevice,
+        images: &RenderAssets<GpuImage>,
+        fallback_image: &FallbackImage,
+    ) -> Result<PreparedBindGroup<Self::Data>, AsBindGroupError> {
+        let mut bindings = self.bindings(layout, render_device, images, fallback_image)?;
+        let uniform = self.bound_uniform();
+        // Maps without a slot (not added to the assets of an app with the plugin) get their own
+        let uniform = match self.uniform_slot.bind(render_device, &uniform) {
+            Some(binding) => binding,
+            None => UniformBinding::standalone(render_device, &uniform),
+        };
+
+        let mut entries: Vec<BindGroupEntry> = bindings
+            .iter()
+            .map(|(index, binding)| BindGroupEntry {
+                binding: *index,
+                resource: binding.get_binding(),
+            })
+            .collect();
+        entries.push(BindGroupEntry {
+            binding: 0,
+            resource: BindingResource::Buffer(BufferBinding {
+                buffer: &uniform.buffer,
+                offset: uniform.offset,
+                size: Some(uniform.size),
+            }),
+        });
+        let bind_group = render_device.create_bind_group(Self::label(), layout, &entries);
+        drop(entries);
+
+        bindings.push((0, OwnedBindingResource::Buffer(uniform.buffer)));
+        Ok(PreparedBindGroup {
+            bindings,
+            bind_group,
+            data: self.into(),
+        })
+    }
+
+    /// Binding 0 is a buffer of its own here, see [`Self::as_bind_group`].
+    fn unprepared_bind_group(
+        &self,
+        layout: &BindGroupLayout,
+        render_device: &RenderDevice,
+        images: &RenderAssets<GpuImage>,
+        fallback_image: &FallbackImage,
+    ) -> Result<UnpreparedBindGroup<Self::Data>, AsBindGroupError> {
+        let mut bindings = self.bindings(layout, render_device, images, fallback_image)?;
+        let uniform = UniformBinding::standalone(render_device, &self.bound_uniform());
+        bindings.push((0, OwnedBindingResource::Buffer(uniform.buffer)));
+        Ok(UnpreparedBindGroup {
+            bindings,
+            data: self.into(),
+        })
+    }
+
+    fn bind_group_layout_entries(render_device: &RenderDevice) -> Vec<BindGroupLayoutEntry> {
+        let mut entries = vec![BindGroupLayoutEntry {
+            binding: 0,
+            visibility: ShaderStages::all(),
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: Some(MapUniform::min_size()),
+            },
+            count: None,
+        }];
+        entries.extend(MapBindings::<C>::bind_group_layout_entries(render_device));
+        entries.extend(C::ExtraBindings::bind_group_layout_entries(render_device));
+        entries
+    }
+}
+
+/// How the rendered map is blended with whatever has been rendered below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect)]
+pub enum MapBlendMode {
+    /// Regular alpha blending.
+    #[default]
+    Alpha,
+    /// Alpha blending for premultiplied colors.
+    PremultipliedAlpha,
+    /// Add the map color (weighted by its alpha) to the background, eg. for glow effects.
+    Additive,
+    /// Multiply the background with the map color (weighted by its alpha),
+    /// eg. for shadow or lighting layers.
+    Multiply,
+}
+
+impl MapBlendMode {
+    fn blend_state(&self) -> BlendState {
+        // Keep the alpha of what was rendered before for additive/multiply
+        let keep_alpha = BlendComponent {
+            src_factor: BlendFactor::Zero,
+            dst_factor: BlendFactor::One,
+            operation: BlendOperation::Add,
+        };
+
+        match self {
+            MapBlendMode::Alpha => BlendState::ALPHA_BLENDING,
+            MapBlendMode::PremultipliedAlpha => BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            MapBlendMode::Additive => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::SrcAlpha,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+                alpha: keep_alpha,
+            },
+            // src * dst + dst * (1 - src_alpha),
+            // ie. transparent parts of the map leave the background unchanged
+            MapBlendMode::Multiply => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::Dst,
+                    dst_factor: BlendFactor::OneMinusSrcAlpha,
+                    operation: BlendOperation::Add,
+                },
+                alpha: keep_alpha,
+            },
+        }
+    }
+}
+
 #[derive(Eq, PartialEq, Hash, Clone)]
 pub struct MapKey {
     pub(crate) perspective_defs: Vec<String>,
     pub(crate) perspective_underhangs: bool,
     pub(crate) perspective_overhangs: bool,
     pub(crate) dominance_overhangs: bool,
+    pub(crate) blend_mode: MapBlendMode,
+    pub(crate) palette_swap: bool,
+    pub(crate) palette_owner: bool,
+    pub(crate) overhang_exclusions: bool,
+    pub(crate) motion_vectors: bool,
+    pub(crate) emissive: bool,
+    pub(crate) secondary_atlas: bool,
+    pub(crate) color_jitter: bool,
+    pub(crate) overlay_canvas: bool,
+    pub(crate) tile_colors: bool,
+    pub(crate) tile_animations: bool,
+    pub(crate) tile_layers: bool,
+    pub(crate) additional_atlases: bool,
+    pub(crate) tile_overhang_flags: bool,
+    pub(crate) minimap: bool,
+    pub(crate) autotile_rules: bool,
+}
+
+impl MapKey {
+    /// Fragment shader defs for maps with this key
+    /// (in addition to those of [`Customization::shader_defs`]).
+    pub(crate) fn shader_defs(&self) -> Vec<String> {
+        let mut defs = Vec::new();
+        if self.perspective_underhangs {
+            defs.push("PERSPECTIVE_UNDERHANGS".to_string());
+        }
+        if self.perspective_overhangs {
+            defs.push("PERSPECTIVE_OVERHANGS".to_string());
+        }
+        if self.dominance_overhangs {
+            defs.push("DOMINANCE_OVERHANGS".to_string());
+        }
+        if self.palette_swap {
+            defs.push("PALETTE_SWAP".to_string());
+        }
+        if self.palette_owner {
+            defs.push("PALETTE_OWNER".to_string());
+        }
+        if self.dominance_overhangs && self.overhang_exclusions {
+            defs.push("OVERHANG_EXCLUSIONS".to_string());
+        }
+        if self.dominance_overhangs
+            && (self.perspective_underhangs || self.perspective_overhangs)
+            && !self.perspective_defs.is_empty()
+        {
+            defs.push("DOMINANCE_SAME_DEPTH".to_string());
+        }
+        if self.motion_vectors {
+            defs.push("MOTION_VECTORS".to_string());
+        }
+        if self.emissive {
+            defs.push("EMISSIVE_TILES".to_string());
+        }
+        if self.secondary_atlas {
+            defs.push("SECONDARY_ATLAS".to_string());
+        }
+        if self.color_jitter {
+            defs.push("COLOR_JITTER".to_string());
+        }
+        if self.overlay_canvas {
+            defs.push("OVERLAY_CANVAS".to_string());
+        }
+        if self.tile_colors {
+            defs.push("TILE_COLORS".to_string());
+        }
+        if self.tile_animations {
+            defs.push("TILE_ANIMATIONS".to_string());
+        }
+        if self.tile_layers {
+            defs.push("TILE_LAYERS".to_string());
+        }
+        if self.additional_atlases {
+            defs.push("ADDITIONAL_ATLASES".to_string());
+        }
+        // Perspective overhangs only sample neighbors with perspective defs
+        if self.tile_overhang_flags
+            && (self.dominance_overhangs || !self.perspective_defs.is_empty())
+        {
+            defs.push("TILE_OVERHANG_FLAGS".to_string());
+        }
+        if self.minimap {
+            defs.push("MINIMAP".to_string());
+        }
+        if self.autotile_rules {
+            defs.push("AUTOTILE_RULES".to_string());
+        }
+        defs.extend(self.perspective_defs.iter().cloned());
+        defs
+    }
 }
 
 impl<C: Customization> From<&Map<C>> for MapKey {
@@ -90,6 +714,23 @@ impl<C: Customization> From<&Map<C>> for MapKey {
             perspective_underhangs: map.perspective_underhangs,
             perspective_overhangs: map.perspective_overhangs,
             dominance_overhangs: map.dominance_overhangs,
+            blend_mode: map.blend_mode,
+            palette_swap: map.map_uniform.n_palette_keys > 0,
+            palette_owner: map.map_uniform.palette_owner_mask != 0,
+            overhang_exclusions: map.overhang_exclusions.iter().any(|bits| *bits != 0),
+            motion_vectors: map.motion_vectors,
+            emissive: map.emissive_tiles.iter().any(|bits| *bits != 0),
+            secondary_atlas: map.secondary_atlas.is_some(),
+            color_jitter: map.color_jitter_tiles.iter().any(|bits| *bits != 0)
+                && (map.map_uniform.color_jitter != 0.0 || map.map_uniform.color_jitter_hue != 0.0),
+            overlay_canvas: map.overlay_canvas().is_some(),
+            tile_colors: map.tile_color_channel,
+            tile_animations: !map.tile_animations().is_empty(),
+            tile_layers: map.n_layers() > 1,
+            additional_atlases: !map.additional_atlases.is_empty(),
+            tile_overhang_flags: map.has_tile_overhang_flags(),
+            minimap: map.is_minimap(),
+            autotile_rules: map.has_autotile_rules(),
         }
     }
 }
@@ -101,45 +742,52 @@ pub struct MapAttributes {
 }
 
 impl MapAttributes {
-    fn set_mix_color(attributes: Option<&MapAttributes>, mesh: &mut Mesh) {
-        let l = mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap().len();
+    pub(crate) fn set_mix_color(attributes: Option<&MapAttributes>, mesh: &mut Mesh) {
+        let Some(l) = vertex_count(mesh) else {
+            return;
+        };
 
         let mut v = vec![Vec4::ONE; l];
         if let Some(attr) = attributes {
+            // Any mismatch is reported by `validate_mix_colors` with the validation feature
+            #[cfg(not(feature = "validation"))]
             if attr.mix_color.len() > v.len() {
-                v.resize(attr.mix_color.len(), Vec4::ONE);
+                warn_once!(
+                    "MapAttributes::mix_color has {} entries, but the mesh only has {} vertices",
+                    attr.mix_color.len(),
+                    v.len()
+                );
             }
-            for (i, c) in attr.mix_color.iter().enumerate() {
-                v[i] = *c;
+            for (v, c) in v.iter_mut().zip(attr.mix_color.iter()) {
+                *v = *c;
             }
         }
 
         mesh.insert_attribute(ATTRIBUTE_MIX_COLOR, v);
     }
 
-    fn set_map_position<C: Customization>(
+    pub(crate) fn set_animation_state(
         _attributes: Option<&MapAttributes>,
         mesh: &mut Mesh,
-        map: &Map<C>,
+        time: &Time,
     ) {
-        let v: Vec<_> = mesh
-            .attribute(Mesh::ATTRIBUTE_POSITION)
-            .unwrap()
-            .as_float3()
-            .unwrap()
-            .iter()
-            .map(|p| map.world_to_map(Vec2::new(p[0], p[1])))
-            .collect();
-        mesh.insert_attribute(ATTRIBUTE_MAP_POSITION, v);
-    }
-
-    fn set_animation_state(_attributes: Option<&MapAttributes>, mesh: &mut Mesh, time: &Time) {
-        let l = mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap().len();
+        let Some(l) = vertex_count(mesh) else {
+            return;
+        };
         let v = vec![time.elapsed_seconds_wrapped(); l];
         mesh.insert_attribute(ATTRIBUTE_ANIMATION_STATE, v);
     }
 }
 
+/// Number of vertices of `mesh` (as given by its positions).
+fn vertex_count(mesh: &Mesh) -> Option<usize> {
+    let Some(positions) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        warn_once!("Map mesh has no vertex positions");
+        return None;
+    };
+    Some(positions.len())
+}
+
 impl<C: Customization> Material2d for Map<C> {
     fn vertex_shader() -> ShaderRef {
         C::SHADER_HANDLE.into()
@@ -154,44 +802,55 @@ impl<C: Customization> Material2d for Map<C> {
         layout: &bevy::render::mesh::MeshVertexBufferLayoutRef,
         key: bevy::sprite::Material2dKey<Self>,
     ) -> Result<(), bevy::render::render_resource::SpecializedMeshPipelineError> {
-        let vertex_layout = layout.0.get_layout(&[
+        let mut attributes = vec![
             Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
-            ATTRIBUTE_MAP_POSITION.at_shader_location(1),
             ATTRIBUTE_MIX_COLOR.at_shader_location(2),
             ATTRIBUTE_ANIMATION_STATE.at_shader_location(3),
-        ])?;
+        ];
+        // Map positions are derived from the vertex positions, unless the mesh has them
+        // (eg. for `MapInstances`, whose vertices are transformed)
+        if layout.0.contains(ATTRIBUTE_MAP_POSITION) {
+            attributes.push(ATTRIBUTE_MAP_POSITION.at_shader_location(1));
+            descriptor
+                .vertex
+                .shader_defs
+                .push(ShaderDefVal::Bool("MESH_MAP_POSITION".into(), true));
+        }
+        #[cfg(feature = "motion-vectors")]
+        if key.bind_group_data.motion_vectors {
+            attributes.push(
+                crate::motion_vectors::ATTRIBUTE_PREVIOUS_CLIP_POSITION.at_shader_location(4),
+            );
+        }
+        let vertex_layout = layout.0.get_layout(&attributes)?;
         descriptor.vertex.buffers = vec![vertex_layout];
 
-        let fragment = descriptor.fragment.as_mut().unwrap();
+        // Maps may be mirrored by a negative scale, which turns the quad around
+        descriptor.primitive.cull_mode = None;
 
-        if key.bind_group_data.perspective_underhangs {
-            fragment.shader_defs.push(ShaderDefVal::Bool(
-                "PERSPECTIVE_UNDERHANGS".to_string(),
-                true,
-            ));
-        }
+        let fragment = descriptor.fragment.as_mut().unwrap();
 
-        if key.bind_group_data.perspective_overhangs {
-            fragment.shader_defs.push(ShaderDefVal::Bool(
-                "PERSPECTIVE_OVERHANGS".to_string(),
-                true,
-            ));
+        // Target formats (HDR or not) and multisampling are set up per view from `key.mesh_key`,
+        // so maps render into views of all windows, only the blending is ours.
+        for target in fragment.targets.iter_mut().flatten() {
+            target.blend = Some(key.bind_group_data.blend_mode.blend_state());
         }
 
-        if key.bind_group_data.dominance_overhangs {
-            fragment
-                .shader_defs
-                .push(ShaderDefVal::Bool("DOMINANCE_OVERHANGS".to_string(), true));
+        for def in key.bind_group_data.shader_defs() {
+            fragment.shader_defs.push(ShaderDefVal::Bool(def, true));
         }
 
-        for def in key.bind_group_data.perspective_defs.iter() {
-            fragment
-                .shader_defs
-                .push(ShaderDefVal::Bool(def.clone(), true));
+        for def in C::shader_defs() {
+            fragment.shader_defs.push(ShaderDefVal::Bool(def, true));
         }
 
         debug!("{:?}", fragment.shader_defs);
 
+        #[cfg(feature = "motion-vectors")]
+        if key.bind_group_data.motion_vectors {
+            crate::motion_vectors::specialize(descriptor);
+        }
+
         Ok(())
     }
 }
@@ -200,14 +859,25 @@ impl<C: Customization> Material2d for Map<C> {
 /// - This component
 /// - [`Map`]
 /// - [`bevy::sprite::Mesh2dHandle`]
+///
 /// The Mesh will be automatically replaced with a rectangular mesh matching
 /// the bounding box of the `Map` whenever a map change is detected.
 ///
 /// This is convenient if you dont care about the exact dimensions / shape of your mesh
 /// but just want to be sure it holds the full map.
-#[derive(Debug, Component, Clone, Default, Reflect)]
+#[derive(Debug, Component, Clone, Copy, PartialEq, Eq, Default, Reflect)]
 #[reflect(Component)]
-pub struct MeshManagedByMap;
+pub enum MeshManagedByMap {
+    /// The mesh covers the whole map.
+    /// Maps of the same world size share their mesh, see [`SharedMapMeshes`].
+    #[default]
+    Full,
+    /// The mesh only covers the part of the map visible by the active orthographic cameras
+    /// (plus a margin) and is rebuilt when the view leaves it.
+    /// Use this for extremely zoomed in views of large maps: Map positions are then only
+    /// interpolated across a small quad, which avoids precision artifacts.
+    ViewportFit,
+}
 
 /// Component temporarily active during map loading.
 /// Will be added by [`crate::bundle::MapBundle`] and will automatically be removed, once the map is loaded.
@@ -215,6 +885,46 @@ pub struct MeshManagedByMap;
 #[reflect(Component)]
 pub struct MapLoading;
 
+/// Added instead of [`MapLoading`] to map entities that can not finish loading, because their
+/// map asset was removed, failed to load or did not appear within [`MapLoadStallTimeout`],
+/// or because the map does not fit its atlas (see [`crate::map_builder::MapError`]).
+/// To retry, set a valid material handle and insert [`MapLoading`] again.
+#[derive(Debug, Component, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct MapBroken;
+
+/// How long a map entity may be [`MapLoading`] without its `Handle<Map<C>>` referring to an
+/// asset, before [`MapLoadStalled`] is sent and the entity is marked [`MapBroken`].
+/// Defaults to 5 seconds.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct MapLoadStallTimeout(pub Duration);
+
+impl Default for MapLoadStallTimeout {
+    fn default() -> Self {
+        Self(Duration::from_secs(5))
+    }
+}
+
+/// Sent (once per entity) when a map entity did not finish loading because its material handle
+/// does not refer to a map asset and it was marked [`MapBroken`], see [`MapLoadStallTimeout`].
+/// Usually means the `material` of the bundle was not set, the map was added to
+/// a different `Assets<Map<C>>` than the handle is for or the map was removed while loading.
+#[derive(Event, Debug)]
+pub struct MapLoadStalled<C: Customization = NoCustomization> {
+    pub map: Entity,
+    pub handle: AssetId<Map<C>>,
+}
+
+/// Sent when the atlas layout of a map is derived, ie. when its atlas is first loaded and
+/// whenever the atlas (or its size) changes later on.
+/// From then on [`Map::atlas_tile_count`] is known, eg. for [`Map::validate_indices`].
+#[derive(Event, Debug)]
+pub struct MapAtlasReady<C: Customization = NoCustomization> {
+    pub map: AssetId<Map<C>>,
+    /// Size of the atlas in tiles
+    pub n_tiles: UVec2,
+}
+
 impl<C: Customization> Map<C> {
     /// Create a [`MapBuilder`] for configuring your map.
     pub fn builder(
@@ -225,28 +935,136 @@ impl<C: Customization> Map<C> {
         MapBuilder::new(map_size, atlas_texture, tile_size)
     }
 
-    pub fn indexer_mut(&mut self) -> MapIndexerMut<C> {
+    pub fn indexer_mut(&mut self) -> MapIndexerMut<'_, C> {
         MapIndexerMut::<C> { map: self }
     }
 
-    pub fn indexer(&self) -> MapIndexer<C> {
+    pub fn indexer(&self) -> MapIndexer<'_, C> {
         MapIndexer::<C> { map: self }
     }
 
+    /// Additional bindings for custom shader code, see [`Customization::ExtraBindings`].
+    pub fn extra_bindings(&self) -> &C::ExtraBindings {
+        &self.extra_bindings
+    }
+
+    pub fn extra_bindings_mut(&mut self) -> &mut C::ExtraBindings {
+        &mut self.extra_bindings
+    }
+
     /// Dimensions of this map in tiles.
     pub fn map_size(&self) -> UVec2 {
         self.map_uniform.map_size()
     }
 
-    /// Size of the map contents bounding box in world coordinates
+    /// Size of the map contents bounding box (including padding for overhangs) in world units,
+    /// before the transform of the map entity, ie. the size of the managed mesh.
+    /// Scale and rotation of the entity apply on top, see [`Self::world_bounds`] for the
+    /// transformed bounds.
     pub fn world_size(&self) -> Vec2 {
         self.map_uniform.world_size()
     }
 
+    /// Size of each tile in the atlas, in pixels.
     pub fn tile_size(&self) -> Vec2 {
         self.map_uniform.tile_size
     }
 
+    /// Size of each tile in world units, see [`MapBuilder::with_world_tile_size`].
+    pub fn world_tile_size(&self) -> Vec2 {
+        self.map_uniform.world_tile_size
+    }
+
+    /// Grow the written rectangle (see [`FastTileMapDebug::dirty_regions`]) by `rect`.
+    pub(crate) fn mark_written(&mut self, rect: URect) {
+        self.mark_uploaded(rect);
+        self.row_hashes.mark(rect.min.y..rect.max.y);
+        self.tile_upload.mark(rect.min.y..rect.max.y);
+        let changed = self.tiles_changed.map_or(rect, |c| c.union(rect));
+        self.tiles_changed = Some(changed);
+        if let Some(autotile) = self.autotile.as_mut() {
+            autotile.mark(rect);
+        }
+    }
+
+    /// Grow the written rectangle by `rect` for changes to other per-tile data than the tile
+    /// values (eg. tile colors), which are uploaded the same way.
+    pub(crate) fn mark_uploaded(&mut self, rect: URect) {
+        self.written = Some(self.written.map_or(rect, |written| written.union(rect)));
+    }
+
+    /// Number of bytes uploaded whenever the map is changed, ie. its uniforms and buffers
+    /// (but not the atlas or [`Customization::ExtraBindings`]).
+    /// This is an upper bound, of the tiles only the rows written since the last upload are
+    /// uploaded (see [`Map::pending_tile_rows`]).
+    pub fn upload_size(&self) -> u64 {
+        [
+            self.map_uniform.size(),
+            self.user_data.size(),
+            self.map_texture.size(),
+            self.decal_buffer.size(),
+            self.decal_grid.size(),
+            self.overhang_exclusions.size(),
+            self.preview_buffer.size(),
+            self.emissive_tiles.size(),
+            self.patch_buffer.size(),
+            self.color_jitter_tiles.size(),
+            self.tile_colors.size(),
+            self.tile_animation_buffer.size(),
+            self.layer_tiles.size(),
+            self.tile_overhang_flags.size(),
+            self.minimap_colors.size(),
+            self.autotile_rules.size(),
+        ]
+        .iter()
+        .map(|size| size.get())
+        .sum()
+    }
+
+    /// Number of segments of the shared mesh in each direction, see [`SharedMapMeshes`].
+    pub fn mesh_segments(&self) -> UVec2 {
+        if self.world_tile_size().cmple(Vec2::ZERO).any() {
+            return UVec2::ONE;
+        }
+        let tiles = self.world_size() / self.world_tile_size();
+        (tiles / MESH_SEGMENT_TILES as f32)
+            .ceil()
+            .as_uvec2()
+            .max(UVec2::ONE)
+    }
+
+    /// Whether map entities with `attributes` can use a mesh of [`SharedMapMeshes`].
+
+    pub(crate) fn can_share_mesh(&self, attributes: Option<&MapAttributes>) -> bool {
+        !self.motion_vectors
+            && attributes.is_none_or(|a| a.mix_color.iter().all(|c| *c == Vec4::ONE))
+    }
+
+    /// Tile atlas of this map.
+    pub fn atlas_texture(&self) -> &Handle<Image> {
+        &self.atlas_texture
+    }
+
+    /// Atlas blended in with [`Self::set_atlas_blend`], see [`MapBuilder::with_secondary_atlas`].
+    pub fn secondary_atlas(&self) -> Option<&Handle<Image>> {
+        self.secondary_atlas.as_ref()
+    }
+
+    /// Blend between the colors of the primary atlas (`0.0`) and the secondary atlas (`1.0`)
+    /// given by [`MapBuilder::with_secondary_atlas`], eg. for crossfading between seasons.
+    pub fn set_atlas_blend(&mut self, blend: f32) {
+        if self.secondary_atlas.is_none() {
+            warn!("set_atlas_blend() called on map without secondary atlas");
+            return;
+        }
+        self.map_uniform.atlas_blend = blend.clamp(0.0, 1.0);
+    }
+
+    /// Current blending factor between primary and secondary atlas.
+    pub fn atlas_blend(&self) -> f32 {
+        self.map_uniform.atlas_blend
+    }
+
     /// Convert map position in `[(0.0, 0.0) .. self.size)`
     /// to local world position (before this entities transform).
     /// E.g. map position `(0.5, 0.5)` is in the center of the tile
@@ -255,9 +1073,15 @@ impl<C: Customization> Map<C> {
         self.map_uniform.map_to_local(map_position.extend(0.0)).xy()
     }
 
+    /// Center of the given tile in local world coordinates.
+    pub fn tile_center_local(&self, tile: UVec2) -> Vec2 {
+        self.map_to_local(tile.as_vec2() + Vec2::splat(0.5))
+    }
+
     /// Same as [`Self::map_to_local`], but return a 3d coordinate,
     /// z-value is the logical "depth" of the map position (for eg axonometric projection).
-    /// Not generally consistent with actual z-position of the mesh.
+    /// Not generally consistent with actual z-position of the mesh,
+    /// use [`Self::depth_at`] for a depth with a documented scale.
     pub fn map_to_local_3d(&self, map_position: Vec3) -> Vec3 {
         self.map_uniform.map_to_local(map_position)
     }
@@ -266,76 +1090,767 @@ impl<C: Customization> Map<C> {
         self.map_uniform.map_to_world(map_position)
     }
 
+    /// Center of the given tile in world coordinates, see [`Self::apply_transform`].
+    pub fn tile_center_world(&self, tile: UVec2) -> Vec2 {
+        let center = tile.as_vec2() + Vec2::splat(0.5);
+        self.map_to_world_3d(center.extend(0.0)).xy()
+    }
+
+    /// Tile of the map data at the given world position, `None` outside of the map,
+    /// see [`Self::content_tile`].
+    pub fn world_to_tile(&self, world: Vec2) -> Option<UVec2> {
+        self.content_tile(self.world_to_map(world))
+    }
+
+    /// Bounding rectangle of the map tiles (without overhangs) in world coordinates.
+    pub fn world_bounds(&self) -> Rect {
+        let size = self.map_size().as_vec2();
+        let mut low = Vec2::INFINITY;
+        let mut high = Vec2::NEG_INFINITY;
+        for corner in [Vec2::ZERO, vec2(size.x, 0.0), vec2(0.0, size.y), size] {
+            let world = self.map_to_world_3d(corner.extend(0.0)).xy();
+            low = low.min(world);
+            high = high.max(world);
+        }
+        Rect::from_corners(low, high)
+    }
+
+    /// Clamp the center of a view of `view_size` world units (eg. the area visible by a camera)
+    /// such that the view stays within [`Self::world_bounds`].
+    /// Along axes where the view is larger than the map, the view is centered on the map.
+    pub fn clamp_view_center(&self, center: Vec2, view_size: Vec2) -> Vec2 {
+        let bounds = self.world_bounds();
+        let margin = (view_size / 2.0).min(bounds.half_size());
+        center.clamp(bounds.min + margin, bounds.max - margin)
+    }
+
+    /// Convert local world position (before this entities transform) to map position.
+    pub fn local_to_map(&self, local: Vec2) -> Vec2 {
+        self.map_uniform.local_to_map(local.extend(0.0)).xy()
+    }
+
+    /// Like [`Self::local_to_map`], but ignoring [`TileStagger`].
+    pub(crate) fn local_to_grid(&self, local: Vec2) -> Vec2 {
+        self.map_uniform.local_to_grid(local.extend(0.0)).xy()
+    }
+
+    /// Set the transform of the entity holding this map, which is used for converting
+    /// between world and map coordinates (eg. [`Self::world_to_map`]).
+    /// Computations are carried out relative to the map position, so they stay precise
+    /// even for maps far away from the origin.
+    ///
+    /// This transform is stored in the map asset, so it is shared by all entities using the
+    /// same map handle. For maps attached to several entities, use the `*_with` methods
+    /// (eg. [`Self::world_to_map_with`]) instead.
+    /// Rendering does not depend on it.
+    ///
+    /// Maps used by a single entity get the `GlobalTransform` of that entity applied
+    /// automatically in [`crate::plugin::MapSystems::Prepare`], see [`sync_map_transforms`].
+    ///
+    /// Negative scales mirror the map. Perspective overhangs consider tiles higher up in the
+    /// world further away, so for transforms that turn the map upside down (eg. a negative y
+    /// scale) the underhang and overhang directions are swapped, which recompiles the pipeline
+    /// of the map. Horizontal mirroring keeps them.
+    /// Transforms that scale the map to zero in x or y are ignored with a warning,
+    /// as there are no map positions for them.
+    pub fn apply_transform(&mut self, transform: &GlobalTransform) {
+        if is_degenerate_transform(transform) {
+            warn_once!("Map entity is scaled to zero, its transform is not applied to the map");
+            return;
+        }
+        let flipped = self.map_uniform.flips_depth();
+        self.map_uniform.apply_transform(transform);
+        if self.map_uniform.flips_depth() != flipped {
+            self.update_perspective_defs();
+        }
+    }
+
     /// Convert world position to map position.
+    /// Any rotation and scale of the map entity is supported, as long as the map is not seen
+    /// edge-on along z (eg. rotated by 90 degrees around x), which yields NaN and a warning.
     pub fn world_to_map(&self, world: Vec2) -> Vec2 {
-        self.map_uniform.world_to_map(world.extend(0.0)).xy()
+        self.map_uniform.world_to_map_2d(world)
     }
 
     pub fn world_to_map_3d(&self, world: Vec3) -> Vec3 {
         self.map_uniform.world_to_map(world)
     }
 
-    pub fn is_loaded(&self, images: &Assets<Image>) -> bool {
-        images.get(&self.atlas_texture).is_some()
+    /// Convert world position to map position for the map entity with the given `transform`.
+    /// See [`Self::world_to_map`] for the supported transforms.
+    pub fn world_to_map_with(&self, transform: &GlobalTransform, world: Vec2) -> Vec2 {
+        self.map_uniform.world_to_map_2d_with(transform, world)
     }
 
-    /// Update internal state.
-    /// Call this when map size changed or assets may have become available.
-    /// Should not be necessary to call this if only map contents changed.
-    pub fn update(&mut self, images: &Assets<Image>) -> bool {
-        let Some(atlas_texture) = images.get(&self.atlas_texture) else {
-            return false;
-        };
-
-        self.map_uniform
-            .update_atlas_size(atlas_texture.size().as_vec2())
+    pub fn world_to_map_3d_with(&self, transform: &GlobalTransform, world: Vec3) -> Vec3 {
+        self.map_uniform.world_to_map_with(transform, world)
     }
 
-    pub(crate) fn update_inverse_projection(&mut self) {
-        let projection2d = dmat2(
-            self.map_uniform.projection.x_axis.xy().as_dvec2(),
-            self.map_uniform.projection.y_axis.xy().as_dvec2(),
-        );
+    /// Convert map position to world position for the map entity with the given `transform`.
+    pub fn map_to_world_3d_with(&self, transform: &GlobalTransform, map_position: Vec3) -> Vec3 {
+        self.map_uniform.map_to_world_with(transform, map_position)
+    }
 
-        self.map_uniform.inverse_projection = projection2d.inverse().as_mat2();
+    /// Set the named regions of this map.
+    /// Regions may overlap, in which case earlier regions take priority over later ones.
+    pub fn set_regions(&mut self, regions: Vec<MapRegion>) {
+        self.regions = MapRegions::new(regions, self.map_size());
+    }
 
-        // Iterate through the four "straight" neighboring map directions, and figure
-        // out which of these have negative Z-values after projection to the world.
-        // These are exactly the directions we should "overlap" in the shader in perspective
-        // overhang mode.
-        let offsets = [
-            (vec2(0.0, -1.0), "ZN"),
-            (vec2(-1.0, -1.0), "NN"),
-            (vec2(-1.0, 0.0), "NZ"),
-            (vec2(-1.0, 1.0), "NP"),
-            (vec2(0.0, 1.0), "ZP"),
-            (vec2(1.0, 1.0), "PP"),
-            (vec2(1.0, 0.0), "PZ"),
-            (vec2(1.0, -1.0), "PN"),
-        ];
+    /// All regions of this map, in priority order.
+    pub fn regions(&self) -> &[MapRegion] {
+        self.regions.regions()
+    }
 
-        let mut defs = Vec::new();
+    /// Region the given tile belongs to, if any.
+    pub fn region_at_tile(&self, tile: UVec2) -> Option<&MapRegion> {
+        self.regions.at(tile)
+    }
 
-        if self.force_underhangs.is_empty() {
-            // Derive underhangs from perspective (z < 0) values
-            for (offset, def) in offsets.iter() {
-                if self.map_uniform.map_to_local(offset.extend(0.0)).z < 0.0 {
-                    defs.push(format!("PERSPECTIVE_UNDER_{}", def));
-                }
-            }
-        } else {
-            // Use forced underhangs
-            for direction in self.force_underhangs.iter() {
-                for (offset, def) in offsets.iter() {
-                    if direction.angle_between(*offset) == 0.0 {
-                        defs.push(format!("PERSPECTIVE_UNDER_{}", def));
-                    }
-                }
-            }
+    /// Region the given world position belongs to, if any.
+    pub fn region_at_world(&self, world: Vec2) -> Option<&MapRegion> {
+        let map_position = self.world_to_map(world).floor();
+        if map_position.x < 0.0 || map_position.y < 0.0 {
+            return None;
         }
-        self.perspective_defs = defs;
+        self.region_at_tile(map_position.as_uvec2())
     }
-} // impl Map
+
+    /// Change how this map is blended with what is rendered below it.
+    /// This requires a different render pipeline, which will be compiled on first use.
+    pub fn set_blend_mode(&mut self, blend_mode: MapBlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    pub fn blend_mode(&self) -> MapBlendMode {
+        self.blend_mode
+    }
+
+    /// Blend between the primary projection (`0.0`) and the secondary projection (`1.0`)
+    /// given by [`MapBuilder::with_secondary_projection`].
+    /// Intermediate values linearly interpolate the projection matrices, so animating this
+    /// gives a smooth transition, eg. to a top-down "tactical" view.
+    pub fn set_projection_blend(&mut self, blend: f32) {
+        if self.projections.is_none() {
+            warn!("set_projection_blend() called on map without secondary projection");
+            return;
+        }
+        self.projection_blend = blend.clamp(0.0, 1.0);
+        self.update_projection();
+    }
+
+    /// Current blending factor between primary and secondary projection.
+    pub fn projection_blend(&self) -> f32 {
+        self.projection_blend
+    }
+
+    /// Derive the effective projection from `projections` and `projection_blend`
+    /// and update everything that depends on it.
+    pub(crate) fn update_projection(&mut self) {
+        // Decals are stored in map coordinates
+        self.decals.dirty = true;
+        if let Some([a, b]) = self.projections {
+            let t = self.projection_blend;
+            self.map_uniform.projection = a.projection * (1.0 - t) + b.projection * t;
+            self.map_uniform.tile_anchor_point = a.tile_anchor_point.lerp(b.tile_anchor_point, t);
+        }
+
+        self.update_inverse_projection();
+        self.update_world_size();
+
+        // Keep the mesh size constant during the transition
+        // (so it doesn't need to be rebuilt every frame).
+        if let Some([a, b]) = self.projections {
+            self.map_uniform.expand_world_size(a.projection);
+            self.map_uniform.expand_world_size(b.projection);
+        }
+    }
+
+    /// Only render the part of the map inside of `rect` (in map coordinates),
+    /// `None` renders the whole map.
+    /// This does not change the mesh, so clipped parts are simply transparent.
+    pub fn set_clip_rect(&mut self, rect: Option<Rect>) {
+        let rect = rect.unwrap_or(Rect {
+            min: Vec2::splat(f32::MIN),
+            max: Vec2::splat(f32::MAX),
+        });
+        self.map_uniform.clip_min = rect.min;
+        self.map_uniform.clip_max = rect.max;
+    }
+
+    /// Current clip rectangle in map coordinates, if any.
+    pub fn clip_rect(&self) -> Option<Rect> {
+        if self.map_uniform.clip_min == Vec2::splat(f32::MIN)
+            && self.map_uniform.clip_max == Vec2::splat(f32::MAX)
+        {
+            return None;
+        }
+        Some(Rect {
+            min: self.map_uniform.clip_min,
+            max: self.map_uniform.clip_max,
+        })
+    }
+
+    /// Fade out the map over `width` (in map coordinates) towards the edges of the clip rectangle.
+    pub fn set_clip_feather(&mut self, width: f32) {
+        self.map_uniform.clip_feather = width.max(0.0);
+    }
+
+    /// Color of the parts of the map quad that are outside of the map, eg. the corners
+    /// around an axonometric map. Default is transparent.
+    /// Overhangs of tiles at the map border are still rendered on top of it.
+    pub fn set_outside_color(&mut self, color: Color) {
+        self.map_uniform.outside_color = Vec4::from_array(color.to_linear().to_f32_array());
+    }
+
+    pub fn outside_color(&self) -> Color {
+        LinearRgba::from_f32_array(self.map_uniform.outside_color.to_array()).into()
+    }
+
+    /// Whether the mesh covers exactly the map, see [`MapBuilder::with_exact_bounds`].
+    pub fn has_exact_bounds(&self) -> bool {
+        self.map_uniform.exact_bounds != 0
+    }
+
+    /// Fade the map towards `color` within `width` tiles of its edges (per axis, zero disables
+    /// the fade for that axis). Distances are measured in map coordinates, so under
+    /// projections (eg. isometric maps) the fade follows the projected map edges.
+    /// Overhangs and decals are faded along with the tiles, the outside of the map is not
+    /// affected (see [`Self::set_outside_color`]).
+    pub fn set_edge_fade(&mut self, width: Vec2, color: Color) {
+        self.map_uniform.edge_fade = width.max(Vec2::ZERO);
+        self.map_uniform.edge_fade_color = Vec4::from_array(color.to_linear().to_f32_array());
+    }
+
+    /// Edge fade width and color, see [`Self::set_edge_fade`].
+    pub fn edge_fade(&self) -> (Vec2, Color) {
+        let color = LinearRgba::from_f32_array(self.map_uniform.edge_fade_color.to_array());
+        (self.map_uniform.edge_fade, color.into())
+    }
+
+    /// Multiply the final color of the map (including overhangs, decals and the outside color)
+    /// with `color`, eg. to dim all maps while a menu is open, see [`crate::any_map`].
+    /// This is applied together with the mix color of [`MapAttributes`], but for all entities
+    /// using the map and without rebuilding meshes. Default is white.
+    pub fn set_color_filter(&mut self, color: Color) {
+        self.map_uniform.color_filter = Vec4::from_array(color.to_linear().to_f32_array());
+    }
+
+    pub fn color_filter(&self) -> Color {
+        LinearRgba::from_f32_array(self.map_uniform.color_filter.to_array()).into()
+    }
+
+    /// Speed of the animations of this map relative to the animation state of its meshes
+    /// (usually the time, see [`MapAttributes`]), eg. `0.0` pauses them and `0.3` slows them
+    /// down, independently of other maps. Default is `1.0`.
+    ///
+    /// Takes effect in [`crate::plugin::MapSystems::Prepare`] (see
+    /// [`apply_map_animation_speeds`]), animations continue from where they are at that time.
+    pub fn set_animation_speed(&mut self, speed: f32) {
+        self.pending_animation_speed = Some(speed);
+    }
+
+    pub fn animation_speed(&self) -> f32 {
+        self.pending_animation_speed
+            .unwrap_or(self.map_uniform.animation_speed)
+    }
+
+    /// Animation state the shader of this map uses for the animation state `mesh_state` of
+    /// a mesh (eg. [`Time::elapsed_seconds_wrapped`]), see [`Self::set_animation_speed`].
+    pub fn scaled_animation_state(&self, mesh_state: f32) -> f32 {
+        mesh_state * self.map_uniform.animation_speed + self.map_uniform.animation_offset
+    }
+
+    /// Apply the pending animation speed, so the animation state at `mesh_state` stays as is.
+    pub(crate) fn apply_animation_speed(&mut self, mesh_state: f32) {
+        let Some(speed) = self.pending_animation_speed.take() else {
+            return;
+        };
+        let state = self.scaled_animation_state(mesh_state);
+        self.map_uniform.animation_speed = speed;
+        self.map_uniform.animation_offset = state - mesh_state * speed;
+    }
+
+    /// Render this map snapped to the screen pixel grid: The map is shifted (by less than a
+    /// pixel) so its tile grid starts on a whole pixel, which keeps pixel art crisp when the
+    /// camera or map moves by fractional pixels.
+    /// Maps are also snapped while [`FastTileMapGlobals::pixel_snap`] is set, regardless of this.
+    pub fn set_pixel_snap(&mut self, pixel_snap: bool) {
+        self.pixel_snap = pixel_snap;
+        self.map_uniform.pixel_snap = pixel_snap as u32;
+    }
+
+    /// Per-map pixel snapping as set with [`Self::set_pixel_snap`],
+    /// see [`Self::is_pixel_snapped`] for whether the map is actually rendered snapped.
+    pub fn pixel_snap(&self) -> bool {
+        self.pixel_snap
+    }
+
+    /// Whether the map is currently rendered snapped to the pixel grid, either by
+    /// [`Self::set_pixel_snap`] or by [`FastTileMapGlobals::pixel_snap`].
+    pub fn is_pixel_snapped(&self) -> bool {
+        self.map_uniform.pixel_snap != 0
+    }
+
+    /// See [`MapBuilder::with_overhang_exclusions`].
+    pub(crate) fn set_overhang_exclusions(&mut self, ranges: &[Range<u32>]) {
+        self.overhang_exclusions = index_bitset(ranges);
+    }
+
+    /// Whether tiles with the given atlas index are excluded from dominance overhangs.
+    pub fn is_overhang_excluded(&self, index: u32) -> bool {
+        bitset_contains(&self.overhang_exclusions, index)
+    }
+
+    /// See [`MapBuilder::with_emissive_tiles`].
+    pub(crate) fn set_emissive_tiles(&mut self, ranges: &[Range<u32>]) {
+        self.emissive_tiles = index_bitset(ranges);
+    }
+
+    /// Whether tiles with the given atlas index are emissive.
+    pub fn is_emissive(&self, index: u32) -> bool {
+        bitset_contains(&self.emissive_tiles, index)
+    }
+
+    /// Factor the color (not the alpha) of emissive tiles is multiplied with,
+    /// see [`MapBuilder::with_emissive_tiles`].
+    /// In HDR pipelines, values above 1 make these tiles bloom.
+    pub fn set_emissive_strength(&mut self, strength: f32) {
+        self.map_uniform.emissive_strength = strength.max(0.0);
+    }
+
+    pub fn emissive_strength(&self) -> f32 {
+        self.map_uniform.emissive_strength
+    }
+
+    /// See [`MapBuilder::with_color_jitter`].
+    pub(crate) fn set_color_jitter_tiles(&mut self, ranges: &[Range<u32>]) {
+        self.color_jitter_tiles = index_bitset(ranges);
+    }
+
+    /// Whether tiles with the given atlas index get color jitter.
+    pub fn has_color_jitter(&self, index: u32) -> bool {
+        bitset_contains(&self.color_jitter_tiles, index)
+    }
+
+    /// Maximum relative change of the brightness of tiles with color jitter,
+    /// see [`MapBuilder::with_color_jitter`].
+    pub fn set_color_jitter(&mut self, amount: f32) {
+        self.map_uniform.color_jitter = amount.max(0.0);
+    }
+
+    pub fn color_jitter(&self) -> f32 {
+        self.map_uniform.color_jitter
+    }
+
+    /// Maximum hue rotation (in radians) of tiles with color jitter,
+    /// see [`MapBuilder::with_color_jitter_hue`].
+    pub fn set_color_jitter_hue(&mut self, angle: f32) {
+        self.map_uniform.color_jitter_hue = angle.abs();
+    }
+
+    pub fn color_jitter_hue(&self) -> f32 {
+        self.map_uniform.color_jitter_hue
+    }
+
+    /// Brightness jitter of the tile at `pos`: The shader multiplies its color by
+    /// `1.0 + color_jitter_at(pos)`, before the mix color and any tints.
+    /// 0 for tiles without color jitter (or outside of the map).
+    pub fn color_jitter_at(&self, pos: UVec2) -> f32 {
+        self.jitter_of(pos, COLOR_JITTER_VALUE_SEED) * self.map_uniform.color_jitter
+    }
+
+    /// Hue rotation (in radians) of the tile at `pos`, see [`Self::color_jitter_at`].
+    pub fn color_jitter_hue_at(&self, pos: UVec2) -> f32 {
+        self.jitter_of(pos, COLOR_JITTER_HUE_SEED) * self.map_uniform.color_jitter_hue
+    }
+
+    fn jitter_of(&self, pos: UVec2, seed: u32) -> f32 {
+        let on_map = pos.cmplt(self.map_size()).all();
+        let index = self.map_uniform.atlas_index(self.indexer().at_uvec(pos));
+        match on_map && self.has_color_jitter(index) {
+            true => color_jitter_hash(pos.as_ivec2(), seed),
+            false => 0.0,
+        }
+    }
+
+    /// Tile at the given world position, if it is on the map and not clipped away.
+    pub fn world_to_tile_clipped(&self, world: Vec2) -> Option<UVec2> {
+        let map_position = self.world_to_map(world);
+        if let Some(clip) = self.clip_rect() {
+            if !clip.contains(map_position) {
+                return None;
+            }
+        }
+        self.content_tile(map_position)
+    }
+
+    /// Whether the map content repeats infinitely, see [`MapBuilder::with_repeat_content`].
+    pub fn repeats_content(&self) -> bool {
+        self.map_uniform.repeat_content != 0
+    }
+
+    /// Tile of the map data shown at `map_position`: The tile containing it, wrapped into the
+    /// map for [`Self::repeats_content`], `None` if it is outside of the map otherwise.
+    pub fn content_tile(&self, map_position: Vec2) -> Option<UVec2> {
+        let tile = map_position.floor().as_ivec2();
+        let size = self.map_size().as_ivec2();
+        if self.repeats_content() && size.cmpgt(IVec2::ZERO).all() {
+            return Some(tile.rem_euclid(size).as_uvec2());
+        }
+        (tile.cmpge(IVec2::ZERO).all() && tile.cmplt(size).all()).then(|| tile.as_uvec2())
+    }
+
+    /// Set custom shader parameter `index` (in `0..4`),
+    /// available in custom shader code as `map.custom_params[index]`.
+    /// For simple customizations this can replace a custom `UserData` type.
+    pub fn set_param(&mut self, index: usize, value: Vec4) {
+        let Some(param) = self.map_uniform.custom_params.get_mut(index) else {
+            warn!("Custom shader parameter index {} out of range", index);
+            return;
+        };
+        *param = value;
+    }
+
+    /// Custom shader parameter `index` (in `0..4`), see [`Self::set_param`].
+    pub fn param(&self, index: usize) -> Vec4 {
+        self.map_uniform
+            .custom_params
+            .get(index)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Declare that every tile of the map has value `tile` (`Some`), or not (`None`).
+    ///
+    /// While set, the map data is not uploaded to the GPU (only a single value is)
+    /// and the shader skips the map data lookup.
+    /// Writing a different value through an indexer automatically switches back to regular
+    /// per-tile data.
+    /// This is also detected automatically when building the map.
+    pub fn set_uniform_tile(&mut self, tile: Option<u32>) {
+        let n = self.map_size().x as usize * self.map_size().y as usize;
+        match tile {
+            Some(tile) => {
+                if let Some(ticks) = self.change_ticks.as_mut() {
+                    ticks.record_all();
+                }
+                self.mark_written(URect::from_corners(UVec2::ZERO, self.map_size()));
+                self.map_uniform.has_uniform_tile = 1;
+                self.map_uniform.uniform_tile = tile;
+                self.map_texture = vec![tile];
+            }
+            None => {
+                if let Some(tile) = self.uniform_tile() {
+                    self.map_uniform.has_uniform_tile = 0;
+                    self.map_texture = vec![tile; n];
+                }
+            }
+        }
+    }
+
+    /// The value of all tiles, if the map is uniformly filled (see [`Self::set_uniform_tile`]).
+    pub fn uniform_tile(&self) -> Option<u32> {
+        match self.map_uniform.has_uniform_tile {
+            0 => None,
+            _ => Some(self.map_uniform.uniform_tile),
+        }
+    }
+
+    /// Load `entity` (using this map) again like a newly spawned map: Its atlas is re-measured
+    /// and its managed mesh rebuilt once the atlas is available, see [`MapLoading`].
+    /// Maps usually recover from atlas reloads by themselves (see [`update_reloaded_atlases`]),
+    /// this is for recovering explicitly, eg. after replacing the atlas handle.
+    pub fn reload(&mut self, commands: &mut Commands, entity: Entity) {
+        self.map_uniform.atlas_size = Vec2::ZERO;
+        commands.entity(entity).insert(MapLoading);
+    }
+
+    pub fn is_loaded(&self, images: &Assets<Image>) -> bool {
+        images.get(&self.atlas_texture).is_some()
+            && self
+                .secondary_atlas
+                .as_ref()
+                .is_none_or(|atlas| images.contains(atlas))
+            && self
+                .additional_atlases
+                .iter()
+                .all(|atlas| images.contains(atlas))
+    }
+
+    /// Update internal state.
+    /// Call this when map size changed or assets may have become available.
+    /// Should not be necessary to call this if only map contents changed.
+    pub fn update(&mut self, images: &Assets<Image>) -> bool {
+        let Some(atlas_texture) = images.get(&self.atlas_texture) else {
+            return false;
+        };
+
+        let changed = self.update_additional_atlases(images);
+        self.map_uniform
+            .update_atlas_size(atlas_texture.size().as_vec2())
+            || changed
+    }
+
+    /// Warn if the secondary atlas has a different layout than the primary one,
+    /// tiles would be blended with unrelated tiles then.
+    pub(crate) fn validate_secondary_atlas(&self, images: &Assets<Image>) {
+        let Some(secondary) = self.secondary_atlas.as_ref().and_then(|a| images.get(a)) else {
+            return;
+        };
+        let mut uniform = self.map_uniform.clone();
+        uniform.update_atlas_size(secondary.size().as_vec2());
+        if uniform.n_tiles != self.map_uniform.n_tiles {
+            warn!(
+                "Secondary atlas has {} tiles, but the atlas has {}, tile layouts should match",
+                uniform.n_tiles, self.map_uniform.n_tiles
+            );
+        }
+    }
+
+    pub(crate) fn update_inverse_projection(&mut self) {
+        let projection2d = dmat2(
+            self.map_uniform.projection.x_axis.xy().as_dvec2(),
+            self.map_uniform.projection.y_axis.xy().as_dvec2(),
+        );
+
+        self.map_uniform.inverse_projection = projection2d.inverse().as_mat2();
+
+        // Neighbor directly below in world space, ie. whose tile graphic might extend upwards
+        // into this tile, see `MapBuilder::with_overhang_extent_tiles`.
+        let step = self.map_uniform.inverse_projection * Vec2::NEG_Y;
+        if self.map_uniform.overhang_levels > 1 && (step - step.round()).abs().max_element() > 0.01
+        {
+            warn!(
+                "Projection does not map tiles vertically onto each other, overhang extent is approximate"
+            );
+        }
+        self.map_uniform.overhang_step = step.round().as_ivec2();
+
+        self.update_perspective_defs();
+    }
+
+    /// Update the `PERSPECTIVE_UNDER_*` shader defs from the projection and the transform of
+    /// the map entity, see [`Self::apply_transform`].
+    fn update_perspective_defs(&mut self) {
+        self.perspective_defs = perspective_defs(self.depth_projection(), &self.force_underhangs);
+    }
+
+    /// Projection that decides which tiles are further away, for [`Self::depth_at`] and the
+    /// `PERSPECTIVE_UNDER_*` shader defs.
+    fn depth_projection(&self) -> Mat3 {
+        // Overhangs can not be blended, so while blending projections use the ones of whichever
+        // projection is closer.
+        let projection = match self.projections {
+            Some([_, b]) if self.projection_blend > 0.5 => b.projection,
+            Some([a, _]) => a.projection,
+            None => self.map_uniform.projection,
+        };
+        match self.map_uniform.flips_depth() {
+            true => Mat3::from_diagonal(vec3(1.0, 1.0, -1.0)) * projection,
+            false => projection,
+        }
+    }
+
+    /// Depth of `map_pos` before shifting it into `[0, max_depth]`, see [`Self::depth_at`].
+    fn raw_depth(&self, map_pos: Vec2) -> f32 {
+        let grid = self.map_uniform.map_to_grid(map_pos);
+        (self.depth_projection() * grid.extend(0.0)).z
+    }
+
+    /// Range of [`Self::raw_depth`] over the map.
+    fn raw_depth_range(&self) -> (f32, f32) {
+        // Staggered tiles reach half a tile beyond the grid
+        let margin = match self.map_uniform.stagger() {
+            TileStagger::None => 0.0,
+            _ => 0.5,
+        };
+        let low = Vec2::splat(-margin);
+        let high = self.size().as_vec2() + margin;
+        [low, vec2(high.x, low.y), vec2(low.x, high.y), high]
+            .into_iter()
+            .map(|corner| (self.depth_projection() * corner.extend(0.0)).z)
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), z| {
+                (min.min(z), max.max(z))
+            })
+    }
+
+    /// Depth of the map position `map_pos`, larger values are closer to the viewer.
+    ///
+    /// This is the z-row of the projection applied to the (unstaggered) map position, in tiles
+    /// (ie. independent of the tile size), shifted so that the furthest corner of the map has
+    /// depth 0:
+    /// `depth_at(p) = projection.row(2).xy · p - min over the map corners`.
+    /// So depth is linear (and thus monotonic) along the depth axis of the projection and lies
+    /// in `[0, max_depth()]` for all positions on the map.
+    /// Projections without a depth axis (eg. [`crate::tile_projection::IDENTITY`]) have depth 0
+    /// everywhere, forced underhangs do not change the depth.
+    ///
+    /// A tile is drawn under a neighbor (with perspective overhangs) exactly when the neighbor
+    /// has the larger depth, and mirrored map entities (see [`Self::apply_transform`]) as well as
+    /// projection blending (which uses the closer projection) are taken into account the same
+    /// way as for rendering.
+    /// This formula is part of the public API, changing it is a breaking change.
+    pub fn depth_at(&self, map_pos: Vec2) -> f32 {
+        self.raw_depth(map_pos) - self.raw_depth_range().0
+    }
+
+    /// Largest value of [`Self::depth_at`] on this map.
+    pub fn max_depth(&self) -> f32 {
+        let (min, max) = self.raw_depth_range();
+        max - min
+    }
+
+    /// Update the world size (ie. the size of the mesh) and offset of the map.
+    ///
+    /// With forced underhangs or unstaggered projections without depth (such as
+    /// [`crate::tile_projection::IDENTITY`]), only the sides that neighboring tiles can actually
+    /// overhang onto get a margin, just as large as the overhangs can get: one tile for direct
+    /// neighbors, more upwards for [`MapBuilder::with_overhang_extent_tiles`].
+    /// So flat maps without dominance overhangs are exactly as large as their tiles, which keeps
+    /// eg. strip maps aligned with other content. Decals reaching beyond such maps are cut off.
+    /// Otherwise each side gets a margin of one tile.
+    /// Maps with [`MapBuilder::with_exact_bounds`] never get a margin.
+    pub(crate) fn update_world_size(&mut self) {
+        if self.has_exact_bounds() {
+            self.map_uniform
+                .update_world_size_with_margins(Vec2::ZERO, Vec2::ZERO);
+            return;
+        }
+        let directions: Vec<Vec2> =
+            underhang_directions(self.map_uniform.projection, &self.force_underhangs)
+                .map(|(direction, _)| *direction)
+                .collect();
+        // Staggered tiles reach beyond the grid, see `TileStagger`
+        let flat = directions.is_empty() && self.map_uniform.stagger() == TileStagger::None;
+        if self.force_underhangs.is_empty() && !flat {
+            self.map_uniform.update_world_size();
+            return;
+        }
+
+        // Offsets of the neighbors the shader samples, a fragment just outside of the map can
+        // show the tile at such an offset if that is inside of the map.
+        let mut offsets = Vec::new();
+        if self.dominance_overhangs {
+            offsets.extend(UNDERHANG_DIRECTIONS.iter().map(|(direction, _)| *direction));
+        }
+        if self.perspective_underhangs {
+            offsets.extend(directions.iter().copied());
+        }
+        if self.perspective_overhangs {
+            offsets.extend(directions.iter().map(|direction| -*direction));
+            let step = self.map_uniform.overhang_step.as_vec2();
+            offsets.extend((2..=self.map_uniform.overhang_levels).map(|k| step * k as f32));
+        }
+
+        let (low, high) = offsets
+            .iter()
+            .fold((Vec2::ZERO, Vec2::ZERO), |(low, high), offset| {
+                (low.max(*offset), high.max(-*offset))
+            });
+        self.map_uniform.update_world_size_with_margins(low, high);
+    }
+} // impl Map
+
+/// Neighboring map directions and their shader def suffixes.
+const UNDERHANG_DIRECTIONS: [(Vec2, &str); 8] = [
+    (vec2(0.0, -1.0), "ZN"),
+    (vec2(-1.0, -1.0), "NN"),
+    (vec2(-1.0, 0.0), "NZ"),
+    (vec2(-1.0, 1.0), "NP"),
+    (vec2(0.0, 1.0), "ZP"),
+    (vec2(1.0, 1.0), "PP"),
+    (vec2(1.0, 0.0), "PZ"),
+    (vec2(1.0, -1.0), "PN"),
+];
+
+/// `PERSPECTIVE_UNDER_*` shader defs for the neighbor directions to render underhangs from.
+///
+/// Without `force_underhangs`, these are exactly the directions that have negative Z-values after
+/// projection to the world.
+/// Otherwise they are the directions in `force_underhangs` (in any order).
+/// Defs are always in the order of [`UNDERHANG_DIRECTIONS`].
+pub(crate) fn perspective_defs(projection: Mat3, force_underhangs: &[Vec2]) -> Vec<String> {
+    underhang_directions(projection, force_underhangs)
+        .map(|(_, def)| format!("PERSPECTIVE_UNDER_{}", def))
+        .collect()
+}
+
+/// Entries of [`UNDERHANG_DIRECTIONS`] to render underhangs from, see [`perspective_defs`].
+fn underhang_directions<'a>(
+    projection: Mat3,
+    force_underhangs: &'a [Vec2],
+) -> impl Iterator<Item = &'static (Vec2, &'static str)> + 'a {
+    UNDERHANG_DIRECTIONS
+        .iter()
+        .filter(move |(offset, _)| match force_underhangs.is_empty() {
+            true => (projection * offset.extend(0.0)).z < 0.0,
+            false => force_underhangs
+                .iter()
+                .any(|direction| direction.angle_between(*offset) == 0.0),
+        })
+}
+
+/// All positions of a map of the given size, row by row.
+pub(crate) fn positions(size: UVec2) -> impl Iterator<Item = UVec2> {
+    (0..size.y).flat_map(move |y| (0..size.x).map(move |x| uvec2(x, y)))
+}
+
+/// Whether `transform` scales the map plane to zero (or to non-finite values),
+/// in which case there is no mapping between world and map positions.
+fn is_degenerate_transform(transform: &GlobalTransform) -> bool {
+    let matrix = transform.affine().matrix3;
+    let area = matrix.x_axis.cross(matrix.y_axis).length();
+    !area.is_finite() || area <= f32::EPSILON * matrix.x_axis.length() * matrix.y_axis.length()
+}
+
+/// Bitset (for a storage buffer, so never empty) of the tile indices in `ranges`.
+fn index_bitset(ranges: &[Range<u32>]) -> Vec<u32> {
+    let end = ranges.iter().map(|range| range.end).max().unwrap_or(0);
+    let mut bits = vec![0; (end as usize).div_ceil(32).max(1)];
+    for index in ranges.iter().flat_map(|range| range.clone()) {
+        bits[(index / 32) as usize] |= 1 << (index % 32);
+    }
+    bits
+}
+
+/// Seeds of [`color_jitter_hash`] for the brightness and the hue, as in the shader.
+pub(crate) const COLOR_JITTER_VALUE_SEED: u32 = 0x85ebca6b;
+pub(crate) const COLOR_JITTER_HUE_SEED: u32 = 0x9e3779b9;
+
+/// Deterministic value in `[-1, 1)` for `tile`, the same as `color_jitter_hash` in the shader.
+/// Results are multiples of `2^-23`, so they are exact on the GPU as well.
+pub(crate) fn color_jitter_hash(tile: IVec2, seed: u32) -> f32 {
+    let mut h =
+        (tile.x as u32).wrapping_mul(0x27d4eb2d) ^ (tile.y as u32).wrapping_mul(0x165667b1) ^ seed;
+    h = (h ^ (h >> 15)).wrapping_mul(0x2c1b3c6d);
+    h = (h ^ (h >> 12)).wrapping_mul(0x297a2d39);
+    h ^= h >> 15;
+    ((h >> 8) as i32 - (1 << 23)) as f32 * (1.0 / (1 << 23) as f32)
+}
+
+/// `apply_color_jitter` of the shader.
+pub(crate) fn apply_color_jitter(uniform: &MapUniform, color: Vec4, tile: IVec2) -> Vec4 {
+    let mut rgb = color.xyz()
+        * (1.0 + uniform.color_jitter * color_jitter_hash(tile, COLOR_JITTER_VALUE_SEED));
+    if uniform.color_jitter_hue != 0.0 {
+        // Rotation around the gray axis, as `hue_shift` of the shader snippets
+        let angle = uniform.color_jitter_hue * color_jitter_hash(tile, COLOR_JITTER_HUE_SEED);
+        let k = Vec3::splat(0.57735);
+        let c = angle.cos();
+        rgb = rgb * c + k.cross(rgb) * angle.sin() + k * k.dot(rgb) * (1.0 - c);
+    }
+    rgb.max(Vec3::ZERO).extend(color.w)
+}
+
+fn bitset_contains(bits: &[u32], index: u32) -> bool {
+    bits.get((index / 32) as usize)
+        .is_some_and(|bits| bits & (1 << (index % 32)) != 0)
+}
+
+/// Number of writes with swapped looking coordinates (see [`MapIndexerMut::set`])
+/// before a warning is logged.
+const TRANSPOSED_WRITES_WARNING: u32 = 8;
 
 // Indexer into a map.
 // Indexer into a map.
@@ -352,6 +1867,12 @@ impl<'a, C: Customization> MapIndexer<'a, C> {
         self.map.map_size()
     }
 
+    /// All tile positions of the map, row by row.
+    /// Prefer this over nested loops, which make it easy to mix up `size().x` and `size().y`.
+    pub fn positions(&self) -> impl Iterator<Item = UVec2> {
+        positions(self.size())
+    }
+
     /// Get tile at given position.
     pub fn at_ivec(&self, i: IVec2) -> u32 {
         self.at(i.x as u32, i.y as u32)
@@ -368,6 +1889,9 @@ impl<'a, C: Customization> MapIndexer<'a, C> {
         if x >= self.size().x || y >= self.size().y {
             return 0;
         }
+        if let Some(tile) = self.map.uniform_tile() {
+            return tile;
+        }
         let idx = y as usize * self.size().x as usize + x as usize;
         self.map.map_texture[idx]
     }
@@ -405,6 +1929,12 @@ impl<'a, C: Customization> MapIndexerMut<'a, C> {
         self.map.map_size()
     }
 
+    /// All tile positions of the map, row by row.
+    /// Prefer this over nested loops, which make it easy to mix up `size().x` and `size().y`.
+    pub fn positions(&self) -> impl Iterator<Item = UVec2> {
+        positions(self.size())
+    }
+
     /// Get tile at given position.
     pub fn at_ivec(&self, i: IVec2) -> u32 {
         self.at(i.x as u32, i.y as u32)
@@ -421,6 +1951,9 @@ impl<'a, C: Customization> MapIndexerMut<'a, C> {
         if x >= self.size().x || y >= self.size().y {
             return 0;
         }
+        if let Some(tile) = self.map.uniform_tile() {
+            return tile;
+        }
         let idx = y as usize * self.size().x as usize + x as usize;
         self.map.map_texture[idx]
     }
@@ -431,13 +1964,156 @@ impl<'a, C: Customization> MapIndexerMut<'a, C> {
     }
 
     /// Set tile at given position.
+    /// Positions out of bounds are ignored, writing the value a tile already has is not recorded
+    /// as a change (see [`Map::tiles_changed_since`]).
+    /// Obtaining the indexer from [`Assets::get_mut`] still re-uploads the map, use
+    /// [`MapAssetsExt::set_tile_if_neq`] to avoid that for idempotent writes.
+    ///
+    /// In debug builds, repeated writes that are only out of bounds in one dimension
+    /// but would be in bounds with x and y swapped log a warning, as they usually come from
+    /// loops with mixed up dimensions (see [`Self::positions`]).
     pub fn set(&mut self, x: u32, y: u32, v: u32) {
         // ensure x/y do not go out of bounds individually (even if the final index is in-bounds)
         if x >= self.size().x || y >= self.size().y {
+            if cfg!(debug_assertions) {
+                self.check_transposed(x, y);
+            }
             return;
         }
+        if let Some(tile) = self.map.uniform_tile() {
+            if tile == v {
+                return;
+            }
+            self.map.set_uniform_tile(None);
+        }
         let idx = y as usize * self.size().x as usize + x as usize;
+        if self.map.map_texture[idx] == v {
+            return;
+        }
         self.map.map_texture[idx] = v;
+        if let Some(ticks) = self.map.change_ticks.as_mut() {
+            ticks.record(x, y);
+        }
+        self.map.mark_written(URect::new(x, y, x + 1, y + 1));
+    }
+
+    /// Count writes to `x`, `y` (out of bounds) that would be in bounds with x and y swapped,
+    /// warning once there are many.
+    pub(crate) fn check_transposed(&mut self, x: u32, y: u32) {
+        let size = self.size();
+        let swapped = uvec2(y, x);
+        if size.x == size.y || swapped.cmpge(size).any() {
+            return;
+        }
+        self.map.transposed_writes += 1;
+        if self.map.transposed_writes == TRANSPOSED_WRITES_WARNING {
+            warn!(
+                "Ignored {} writes out of bounds of a {}x{} map (eg. at x={}, y={}) \
+                 that would be in bounds with x and y swapped. \
+                 Are the dimensions of a loop mixed up? Consider `MapIndexerMut::positions()`.",
+                TRANSPOSED_WRITES_WARNING, size.x, size.y, x, y
+            );
+        }
+    }
+
+    /// Set all tiles from `u16` tile indices in row-major order, as stored by older versions
+    /// of this crate (which used an `R16Uint` map texture).
+    /// Tile indices keep their meaning, they are just widened to `u32`.
+    /// Returns the number of tiles written, 0 (leaving the map as it is) if `tiles` does not
+    /// hold exactly one index per tile.
+    pub fn copy_from_u16_slice(&mut self, tiles: &[u16]) -> usize {
+        let size = self.size();
+        let n = size.x as usize * size.y as usize;
+        if tiles.len() != n {
+            warn!(
+                "copy_from_u16_slice: got {} tiles, but map has {}",
+                tiles.len(),
+                n
+            );
+            return 0;
+        }
+        self.map.set_uniform_tile(None);
+        for (dst, src) in self.map.map_texture.iter_mut().zip(tiles) {
+            *dst = (*src).into();
+        }
+        if let Some(ticks) = self.map.change_ticks.as_mut() {
+            ticks.record_all();
+        }
+        self.map
+            .mark_written(URect::from_corners(UVec2::ZERO, size));
+        n
+    }
+
+    /// Set all tiles from a simulation grid `src` with the same size and layout
+    /// (row-major, `y * size.x + x`) as the map, converting cells with `to_tile`.
+    ///
+    /// Intended for mirroring a simulation that keeps its own state (eg. `Vec<u8>`)
+    /// into the map every frame, see the `sand` example.
+    pub fn sync_from<T: Copy>(&mut self, src: &[T], to_tile: impl Fn(&T) -> u32) {
+        self.sync_rows(src, |_| true, to_tile);
+    }
+
+    /// Like [`Self::sync_from`], but only converts the rows `y` whose bit `y % 64`
+    /// in `changed_rows[y / 64]` is set.
+    /// Missing entries of `changed_rows` count as unchanged.
+    ///
+    /// Note that any mutable access to the map asset makes bevy upload the whole map,
+    /// so if no rows changed, better not access the map mutably at all.
+    pub fn sync_changed_rows_from<T: Copy>(
+        &mut self,
+        src: &[T],
+        changed_rows: &[u64],
+        to_tile: impl Fn(&T) -> u32,
+    ) {
+        self.sync_rows(
+            src,
+            |y| {
+                changed_rows
+                    .get((y / 64) as usize)
+                    .is_some_and(|bits| bits & (1 << (y % 64)) != 0)
+            },
+            to_tile,
+        );
+    }
+
+    fn sync_rows<T: Copy>(
+        &mut self,
+        src: &[T],
+        changed: impl Fn(u32) -> bool,
+        to_tile: impl Fn(&T) -> u32,
+    ) {
+        self.map.set_uniform_tile(None);
+        if src.len() != self.map.map_texture.len() {
+            warn!(
+                "sync_from: got {} cells, but map has {} tiles",
+                src.len(),
+                self.map.map_texture.len()
+            );
+        }
+
+        let width = self.size().x as usize;
+        if width == 0 {
+            return;
+        }
+        let mut written_rows = None;
+        let rows = self.map.map_texture.chunks_exact_mut(width);
+        for (y, (dst, src)) in rows.zip(src.chunks(width)).enumerate() {
+            if !changed(y as u32) {
+                continue;
+            }
+            for (dst, src) in dst.iter_mut().zip(src) {
+                *dst = to_tile(src);
+            }
+            if let Some(ticks) = self.map.change_ticks.as_mut() {
+                ticks.record_row(y as u32);
+            }
+            let y = y as u32;
+            written_rows = Some(written_rows.map_or((y, y), |(first, _)| (first, y)));
+        }
+        if let Some((first, last)) = written_rows {
+            self.map
+                .mark_written(URect::new(0, first, width as u32, last + 1));
+        }
     }
 
     pub fn world_to_map(&self, world: Vec2) -> Vec2 {
@@ -461,24 +2137,67 @@ impl<'a, C: Customization> MapIndexerMut<'a, C> {
     }
 }
 
-pub fn log_map_events<C: Customization>(
-    mut ev_asset: EventReader<AssetEvent<Map<C>>>,
-    map_handles: Query<&Handle<Map<C>>>,
+pub fn log_map_events<C: Customization>(mut ev_changed: EventReader<MapTileChanged<C>>) {
+    for ev in ev_changed.read() {
+        debug!("Map {} tiles changed: {:?}", ev.map, ev.rect);
+    }
+}
+
+/// Send [`MapAtlasReady`] for maps whose atlas layout was derived since the last run.
+#[allow(clippy::type_complexity)]
+pub fn send_map_atlas_ready<C: Customization>(
+    map_materials: Res<Assets<Map<C>>>,
+    mut ev_ready: EventWriter<MapAtlasReady<C>>,
+    // Atlas and layout of each map the event was last sent for
+    mut sent: Local<HashMap<AssetId<Map<C>>, (AssetId<Image>, UVec2)>>,
 ) {
-    for ev in ev_asset.read() {
-        for map_handle in map_handles.iter() {
-            match ev {
-                AssetEvent::Modified { id } if *id == map_handle.id() => {
-                    debug!("Map modified");
-                }
-                _ => (),
-            }
+    if !map_materials.is_changed() {
+        return;
+    }
+    sent.retain(|id, _| map_materials.contains(*id));
+    for (id, map) in map_materials.iter() {
+        let n_tiles = map.map_uniform.n_tiles;
+        if n_tiles == UVec2::ZERO {
+            continue;
+        }
+        let layout = (map.atlas_texture.id(), n_tiles);
+        if sent.insert(id, layout) != Some(layout) {
+            ev_ready.send(MapAtlasReady { map: id, n_tiles });
         }
     }
 }
 
+/// Set the sampler used for atlases, unless it is set already
+/// (so the atlas is not modified needlessly).
+fn configure_atlas_sampler(images: &mut Assets<Image>, atlas: &Handle<Image>) {
+    let configured = matches!(
+        images.get(atlas).map(|image| &image.sampler),
+        Some(ImageSampler::Descriptor(ImageSamplerDescriptor {
+            min_filter: ImageFilterMode::Nearest,
+            mag_filter: ImageFilterMode::Nearest,
+            mipmap_filter: ImageFilterMode::Linear,
+            ..
+        }))
+    );
+    if configured {
+        return;
+    }
+    if let Some(image) = images.get_mut(atlas) {
+        image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+            // min_filter of linear gives undesired grid lines when zooming out
+            min_filter: ImageFilterMode::Nearest,
+            // mag_filter of linear gives mushy edges on tiles in closeup which is
+            // usually not what we want
+            mag_filter: ImageFilterMode::Nearest,
+            mipmap_filter: ImageFilterMode::Linear,
+            ..default()
+        });
+    }
+}
+
 /// Check to see if any maps' assets became available
-/// if so.
+/// if so. Maps with a [`MapAsyncFill`] wait for their tiles as well.
+#[allow(clippy::type_complexity)]
 pub fn update_loading_maps<C: Customization>(
     mut images: ResMut<Assets<Image>>,
     mut map_materials: ResMut<Assets<Map<C>>>,
@@ -488,52 +2207,281 @@ pub fn update_loading_maps<C: Customization>(
             Option<&MapAttributes>,
             &Handle<Map<C>>,
             Option<&MeshManagedByMap>,
+            Option<&MapInstances>,
+            Option<&MapHooks>,
         ),
-        With<MapLoading>,
+        (With<MapLoading>, Without<MapAsyncFill<C>>),
     >,
     mut meshes: ResMut<Assets<Mesh>>,
+    mut shared_meshes: ResMut<SharedMapMeshes>,
     mut commands: Commands,
     time: Res<Time>,
 ) {
-    for (entity, attributes, map_handle, manage_mesh) in maps.iter_mut() {
-        let Some(map) = map_materials.get_mut(map_handle) else {
+    for (entity, attributes, map_handle, manage_mesh, instances, hooks) in maps.iter_mut() {
+        // Only borrow mutably once the map can be loaded, so waiting maps are not re-extracted
+        let ready = map_materials
+            .get(map_handle)
+            .is_some_and(|map| !map.atlas_metadata.is_pending() && map.is_loaded(&images));
+        if !ready {
             continue;
-        };
-        let Some(atlas) = images.get_mut(&map.atlas_texture) else {
+        }
+        if let Err(e) = map_materials.get(map_handle).unwrap().validate(&images) {
+            error!("Map entity {:?} can not be loaded: {}", entity, e);
+            commands
+                .entity(entity)
+                .remove::<MapLoading>()
+                .insert(MapBroken);
             continue;
-        };
-
-        atlas.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
-            // min_filter of linear gives undesired grid lines when zooming out
-            min_filter: ImageFilterMode::Nearest,
-            // mag_filter of linear gives mushy edges on tiles in closeup which is
-            // usually not what we want
-            mag_filter: ImageFilterMode::Nearest,
-            mipmap_filter: ImageFilterMode::Linear,
-            ..default()
-        });
+        }
+        let map = map_materials.get_mut(map_handle).unwrap();
+        configure_atlas_sampler(&mut images, &map.atlas_texture);
+        if let Some(secondary) = &map.secondary_atlas {
+            configure_atlas_sampler(&mut images, secondary);
+        }
+        for atlas in &map.additional_atlases {
+            configure_atlas_sampler(&mut images, atlas);
+        }
 
         commands.entity(entity).remove::<MapLoading>();
         map.update(images.as_ref());
+        map.validate_atlas_metadata();
+        map.validate_expected_atlas_columns();
+        map.validate_secondary_atlas(&images);
 
         if manage_mesh.is_some() {
-            let mut mesh = Mesh::from(Rectangle {
-                half_size: map.world_size() / 2.0,
-            });
-
-            MapAttributes::set_mix_color(attributes, &mut mesh);
-            MapAttributes::set_map_position(attributes, &mut mesh, &map);
-            MapAttributes::set_animation_state(attributes, &mut mesh, &time);
+            let mesh = match instances {
+                Some(instances) => meshes.add(instances.mesh(map, attributes, &time)),
+                None if map.can_share_mesh(attributes) => shared_meshes.get_or_add(
+                    map.world_size(),
+                    map.mesh_segments(),
+                    &mut meshes,
+                    &time,
+                ),
+                None => {
+                    let mut mesh = Mesh::from(Rectangle {
+                        half_size: map.world_size() / 2.0,
+                    });
+
+                    MapAttributes::set_mix_color(attributes, &mut mesh);
+                    MapAttributes::set_animation_state(attributes, &mut mesh, &time);
+                    meshes.add(mesh)
+                }
+            };
 
-            let mesh = Mesh2dHandle(meshes.add(mesh));
-            commands.entity(entity).insert(mesh);
+            commands.entity(entity).insert(Mesh2dHandle(mesh));
         }
 
+        run_hook(&mut commands, hooks.and_then(|h| h.on_loaded), entity);
         debug!("Map loaded: {:?}", map.map_size());
     }
 }
 
+/// Update maps whose atlas was re-added (eg. removed and inserted again) or modified
+/// (eg. hot reloaded) after they were loaded: The atlas gets the map sampler again, the
+/// atlas size is re-measured and the map is re-extracted, so it renders with the new atlas.
+/// See [`Map::reload`] for reloading a map explicitly.
+pub fn update_reloaded_atlases<C: Customization>(
+    mut ev_image: EventReader<AssetEvent<Image>>,
+    mut images: ResMut<Assets<Image>>,
+    mut map_materials: ResMut<Assets<Map<C>>>,
+) {
+    let reloaded: HashSet<AssetId<Image>> = ev_image
+        .read()
+        .filter_map(|ev| match ev {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if reloaded.is_empty() {
+        return;
+    }
+
+    let affected: Vec<AssetId<Map<C>>> = map_materials
+        .iter()
+        .filter(|(_, map)| {
+            // Maps that were never loaded are left to `update_loading_maps`
+            map.map_uniform.atlas_size != Vec2::ZERO
+                && (reloaded.contains(&map.atlas_texture.id())
+                    || map
+                        .secondary_atlas
+                        .as_ref()
+                        .is_some_and(|atlas| reloaded.contains(&atlas.id()))
+                    || map
+                        .additional_atlases
+                        .iter()
+                        .any(|atlas| reloaded.contains(&atlas.id())))
+        })
+        .map(|(id, _)| id)
+        .collect();
+
+    for id in affected {
+        let map = map_materials.get_mut(id).unwrap();
+        configure_atlas_sampler(&mut images, &map.atlas_texture);
+        if let Some(secondary) = &map.secondary_atlas {
+            configure_atlas_sampler(&mut images, secondary);
+        }
+        for atlas in &map.additional_atlases {
+            configure_atlas_sampler(&mut images, atlas);
+        }
+        if map.update(images.as_ref()) {
+            debug!(
+                "Atlas of map {id} changed its size to {}",
+                map.map_uniform.atlas_size
+            );
+        }
+        map.validate_secondary_atlas(&images);
+    }
+}
+
+/// Warn about and send [`MapLoadStalled`] for maps stuck in [`MapLoading`]
+/// because their handle does not refer to an asset, and mark them [`MapBroken`].
+/// Maps whose asset was removed or failed to load are marked immediately,
+/// others after [`MapLoadStallTimeout`].
+#[allow(clippy::too_many_arguments)]
+pub fn detect_stalled_map_loads<C: Customization>(
+    mut commands: Commands,
+    map_materials: Res<Assets<Map<C>>>,
+    asset_server: Res<AssetServer>,
+    maps: Query<(Entity, &Handle<Map<C>>), With<MapLoading>>,
+    timeout: Res<MapLoadStallTimeout>,
+    time: Res<Time>,
+    // When each entity was first seen without an asset
+    mut missing_since: Local<HashMap<Entity, Duration>>,
+    mut ev_asset: EventReader<AssetEvent<Map<C>>>,
+    mut ev_stalled: EventWriter<MapLoadStalled<C>>,
+) {
+    let removed: HashSet<AssetId<Map<C>>> = ev_asset
+        .read()
+        .filter_map(|ev| match ev {
+            AssetEvent::Removed { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    let now = time.elapsed();
+    let mut missing = HashMap::new();
+    for (entity, map_handle) in maps.iter() {
+        if map_materials.contains(map_handle) {
+            continue;
+        }
+        let since = missing_since.get(&entity).copied().unwrap_or(now);
+        let reason = if removed.contains(&map_handle.id()) {
+            "was removed".to_string()
+        } else if let Some(LoadState::Failed(e)) = asset_server.get_load_state(map_handle) {
+            format!("failed to load: {e}")
+        } else if now - since >= timeout.0 {
+            format!(
+                "did not appear within {:?}. Did you forget to set the `material` of \
+                 the map bundle?",
+                now - since
+            )
+        } else {
+            missing.insert(entity, since);
+            continue;
+        };
+
+        warn!(
+            "Map entity {:?} can not finish loading, its {} asset {:?} {}",
+            entity,
+            std::any::type_name::<Map<C>>(),
+            map_handle.id(),
+            reason,
+        );
+        commands
+            .entity(entity)
+            .remove::<MapLoading>()
+            .insert(MapBroken);
+        ev_stalled.send(MapLoadStalled {
+            map: entity,
+            handle: map_handle.id(),
+        });
+    }
+    *missing_since = missing;
+}
+
+/// Apply the `GlobalTransform` of map entities to their maps (see [`Map::apply_transform`])
+/// after transform propagation, so conversions between world and map coordinates match the
+/// rendered frame, also for maps that are children of moving entities.
+/// Maps used by several entities are left alone, use the `*_with` conversions for those.
+///
+/// Maps added or replaced (eg. with [`Assets::insert`]) after their entity was placed get the
+/// transform of that entity as well.
+///
+/// Conversions in `Update` see the transforms of the previous frame, run them after
+/// [`crate::plugin::MapSystems::Prepare`] to convert with the transforms of the current frame.
+/// Like all map changes, this re-uploads a moving map every frame.
+#[allow(clippy::type_complexity)]
+pub fn sync_map_transforms<C: Customization>(
+    changed: Query<
+        (&Handle<Map<C>>, &GlobalTransform),
+        Or<(Changed<GlobalTransform>, Changed<Handle<Map<C>>>)>,
+    >,
+    placed: Query<(&Handle<Map<C>>, &GlobalTransform)>,
+    all: Query<&Handle<Map<C>>>,
+    mut map_events: EventReader<AssetEvent<Map<C>>>,
+    mut map_materials: ResMut<Assets<Map<C>>>,
+) {
+    // Modified also covers our own changes below, which are skipped as up to date next frame
+    let replaced: HashSet<AssetId<Map<C>>> = map_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if changed.is_empty() && replaced.is_empty() {
+        return;
+    }
+    let mut users: HashMap<AssetId<Map<C>>, usize> = HashMap::new();
+    for handle in all.iter() {
+        *users.entry(handle.id()).or_default() += 1;
+    }
+
+    let reloaded = placed
+        .iter()
+        .filter(|(handle, _)| replaced.contains(&handle.id()));
+    for (handle, transform) in changed.iter().chain(reloaded) {
+        if users.get(&handle.id()) != Some(&1) {
+            continue;
+        }
+        // Skipped here already, so the map is not accessed mutably every frame
+        if is_degenerate_transform(transform) {
+            warn_once!("Map entity is scaled to zero, its transform is not applied to the map");
+            continue;
+        }
+        // Only borrow mutably on actual changes, so the map is not re-extracted needlessly
+        let outdated = map_materials
+            .get(handle)
+            .is_some_and(|map| !map.map_uniform.has_transform(transform));
+        if outdated {
+            map_materials
+                .get_mut(handle)
+                .unwrap()
+                .apply_transform(transform);
+        }
+    }
+}
+
+/// Apply the animation speeds set with [`Map::set_animation_speed`],
+/// continuing from the animation state of the current frame.
+pub fn apply_map_animation_speeds<C: Customization>(
+    time: Res<Time>,
+    mut maps: ResMut<Assets<Map<C>>>,
+) {
+    let pending: Vec<_> = maps
+        .iter()
+        .filter(|(_, map)| map.pending_animation_speed.is_some())
+        .map(|(id, _)| id)
+        .collect();
+    for id in pending {
+        if let Some(map) = maps.get_mut(id) {
+            map.apply_animation_speed(time.elapsed_seconds_wrapped());
+        }
+    }
+}
+
 /// Update mesh if MapAttributes change
+#[allow(clippy::type_complexity)]
 pub fn update_map_vertex_attributes<C: Customization>(
     map_materials: ResMut<Assets<Map<C>>>,
     maps: Query<(
@@ -542,18 +2490,49 @@ pub fn update_map_vertex_attributes<C: Customization>(
         &MapAttributes,
         Option<&Mesh2dHandle>,
         Option<&MeshManagedByMap>,
+        Option<Ref<MapInstances>>,
+        Option<&ViewportFitRect>,
+        Option<&MapHooks>,
     )>,
     mut meshes: ResMut<Assets<Mesh>>,
+    mut shared_meshes: ResMut<SharedMapMeshes>,
     mut commands: Commands,
     time: Res<Time>,
 ) {
-    for (entity, map_handle, attr, mesh_handle, manage_mesh) in maps.iter() {
+    let mut shared_in_use = HashSet::new();
+    for (entity, map_handle, attr, mesh_handle, manage_mesh, instances, fit, hooks) in maps.iter() {
+        let rebuilt = hooks.and_then(|h| h.after_mesh_rebuild);
         let Some(map) = map_materials.get(map_handle) else {
             warn!("No map material");
             continue;
         };
 
-        let mut mesh = if manage_mesh.is_some() {
+        if let (Some(instances), Some(_)) = (instances, manage_mesh) {
+            if instances.is_changed() {
+                // Bounds are only computed for meshes without `Aabb`
+                commands.entity(entity).remove::<Aabb>();
+            }
+            let mesh = Mesh2dHandle(meshes.add(instances.mesh(map, Some(attr), &time)));
+            commands.entity(entity).insert(mesh);
+            run_hook(&mut commands, rebuilt, entity);
+            continue;
+        }
+
+        let fit = fit.filter(|_| manage_mesh == Some(&MeshManagedByMap::ViewportFit));
+        if fit.is_none() && manage_mesh.is_some() && map.can_share_mesh(Some(attr)) {
+            let shared =
+                shared_meshes.get_or_add(map.world_size(), map.mesh_segments(), &mut meshes, &time);
+            shared_in_use.insert(shared.id());
+            if mesh_handle.map(|h| &h.0) != Some(&shared) {
+                commands.entity(entity).insert(Mesh2dHandle(shared));
+                run_hook(&mut commands, rebuilt, entity);
+            }
+            continue;
+        }
+
+        let mut mesh = if let Some(fit) = fit {
+            quad_mesh(fit.0)
+        } else if manage_mesh.is_some() {
             Mesh::from(Rectangle {
                 half_size: map.world_size() / 2.0,
             })
@@ -562,10 +2541,915 @@ pub fn update_map_vertex_attributes<C: Customization>(
         };
 
         MapAttributes::set_mix_color(Some(attr), &mut mesh);
-        MapAttributes::set_map_position(Some(attr), &mut mesh, &map);
         MapAttributes::set_animation_state(Some(attr), &mut mesh, &time);
 
         let mesh = Mesh2dHandle(meshes.add(mesh));
         commands.entity(entity).insert(mesh);
+        run_hook(&mut commands, rebuilt, entity);
+    }
+
+    shared_meshes.update(
+        |handle| shared_in_use.contains(&handle.id()),
+        &mut meshes,
+        &time,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    use bevy::{
+        math::{ivec2, uvec2, vec3},
+        render::{
+            mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+            render_asset::RenderAssetUsages,
+        },
+    };
+
+    use super::*;
+    use crate::autotile_rules::{AutotileLayout, AutotileRules, AUTOTILE_TABLE_SIZE};
+    use crate::overhang_flags::OverhangFlags;
+    use crate::tile_layers::MAX_TILE_LAYERS;
+    use crate::tile_projection::{
+        AXONOMETRIC, HEX_FLAT_TOP, HEX_FLAT_TOP_EVEN, HEX_POINTY_TOP, HEX_POINTY_TOP_EVEN, IDENTITY,
+    };
+
+    fn builder() -> MapBuilder<NoCustomization> {
+        Map::builder(uvec2(8, 8), default(), vec2(16.0, 16.0))
+    }
+
+    fn key(map: &Map) -> MapKey {
+        MapKey::from(map)
+    }
+
+    /// Quad of two triangles sharing two of its four vertices, with positions in `format`
+    fn indexed_quad(format: VertexFormat) -> Mesh {
+        let corners: [[f32; 2]; 4] = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        let mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_indices(Indices::U16(vec![0, 1, 2, 0, 2, 3]));
+        match format {
+            VertexFormat::Float32x2 => mesh.with_inserted_attribute(
+                MeshVertexAttribute::new("Vertex_Position", 0, VertexFormat::Float32x2),
+                corners.to_vec(),
+            ),
+            _ => mesh.with_inserted_attribute(
+                Mesh::ATTRIBUTE_POSITION,
+                corners.map(|[x, y]| [x, y, 0.0]).to_vec(),
+            ),
+        }
+    }
+
+    fn hash(key: &MapKey) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn mirrored(projection: TileProjection) -> TileProjection {
+        TileProjection {
+            projection: Mat3::from_cols(
+                -projection.projection.x_axis,
+                projection.projection.y_axis,
+                projection.projection.z_axis,
+            ),
+            ..projection
+        }
+    }
+
+    #[test]
+    fn identity_defs() {
+        let map = builder().with_projection(IDENTITY).build();
+        assert_eq!(
+            key(&map).shader_defs(),
+            ["PERSPECTIVE_UNDERHANGS", "PERSPECTIVE_OVERHANGS"]
+        );
+    }
+
+    #[test]
+    fn axonometric_defs() {
+        let map = builder().with_projection(AXONOMETRIC).build();
+        assert_eq!(
+            key(&map).shader_defs(),
+            [
+                "PERSPECTIVE_UNDERHANGS",
+                "PERSPECTIVE_OVERHANGS",
+                "PERSPECTIVE_UNDER_NZ",
+                "PERSPECTIVE_UNDER_NP",
+                "PERSPECTIVE_UNDER_ZP",
+            ]
+        );
+    }
+
+    #[test]
+    fn mirrored_axonometric_defs() {
+        let map = builder().with_projection(mirrored(AXONOMETRIC)).build();
+        assert_eq!(
+            map.perspective_defs,
+            [
+                "PERSPECTIVE_UNDER_ZP",
+                "PERSPECTIVE_UNDER_PP",
+                "PERSPECTIVE_UNDER_PZ",
+            ]
+        );
+    }
+
+    #[test]
+    fn upside_down_transform_swaps_perspective_defs() {
+        let mut map = builder().with_projection(AXONOMETRIC).build();
+        let defs = map.perspective_defs.clone();
+
+        // Horizontal mirroring keeps the depth order
+        map.apply_transform(&Transform::from_scale(vec3(-1.0, 1.0, 1.0)).into());
+        assert_eq!(map.perspective_defs, defs);
+
+        map.apply_transform(&Transform::from_scale(vec3(1.0, -1.0, 1.0)).into());
+        assert_eq!(
+            map.perspective_defs,
+            [
+                "PERSPECTIVE_UNDER_ZN",
+                "PERSPECTIVE_UNDER_PZ",
+                "PERSPECTIVE_UNDER_PN",
+            ]
+        );
+
+        map.apply_transform(&GlobalTransform::IDENTITY);
+        assert_eq!(map.perspective_defs, defs);
+    }
+
+    #[test]
+    fn hinted_edits_do_not_allocate() {
+        let mut map = Map::builder(uvec2(32, 16), default(), vec2(16.0, 16.0))
+            .with_expected_edit_size(32 * 16)
+            .build();
+        let positions = map.edit_scratch.positions.as_ptr();
+        let values = map.edit_scratch.values.as_ptr();
+
+        assert_eq!(map.indexer_mut().flood_fill(uvec2(3, 4), 1), 32 * 16);
+        map.indexer_mut()
+            .step_region(URect::new(0, 0, 32, 16), |_, v, _| v + 1);
+        map.indexer_mut()
+            .step_region(URect::new(0, 3, 32 * 16, 4), |_, v, _| v + 1);
+        assert_eq!(map.edit_scratch.positions.as_ptr(), positions);
+        assert_eq!(map.edit_scratch.values.as_ptr(), values);
+        assert_eq!(map.indexer().at(31, 15), 2);
+    }
+
+    #[test]
+    fn positions_of_non_square_maps() {
+        let map: Map = Map::builder(uvec2(3, 2), default(), vec2(16.0, 16.0)).build();
+        let positions: Vec<UVec2> = map.indexer().positions().collect();
+        assert_eq!(
+            positions,
+            [
+                uvec2(0, 0),
+                uvec2(1, 0),
+                uvec2(2, 0),
+                uvec2(0, 1),
+                uvec2(1, 1),
+                uvec2(2, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn transposed_writes_are_counted() {
+        let mut map: Map = Map::builder(uvec2(3, 2), default(), vec2(16.0, 16.0)).build();
+        let mut m = map.indexer_mut();
+        // In bounds with x and y swapped
+        m.set(1, 2, 1);
+        // Out of bounds either way
+        m.set(5, 0, 1);
+        m.set(0, 5, 1);
+        assert_eq!(
+            map.transposed_writes,
+            if cfg!(debug_assertions) { 1 } else { 0 }
+        );
+
+        // Tile colors are checked the same way
+        let mut map: Map = Map::builder(uvec2(3, 2), default(), vec2(16.0, 16.0))
+            .with_tile_color_channel()
+            .build();
+        let mut m = map.indexer_mut();
+        m.set_color(1, 2, Color::BLACK);
+        m.set_color(5, 0, Color::BLACK);
+        assert_eq!(
+            map.transposed_writes,
+            if cfg!(debug_assertions) { 1 } else { 0 }
+        );
+    }
+
+    #[test]
+    fn dominance_defs() {
+        let map = builder().with_dominance_overhang().build();
+        assert_eq!(key(&map).shader_defs(), ["DOMINANCE_OVERHANGS"]);
+    }
+
+    #[test]
+    fn combined_overhang_defs() {
+        let map = builder()
+            .with_projection(AXONOMETRIC)
+            .with_overhangs(true, true, true)
+            .build();
+        assert_eq!(
+            key(&map).shader_defs(),
+            [
+                "PERSPECTIVE_UNDERHANGS",
+                "PERSPECTIVE_OVERHANGS",
+                "DOMINANCE_OVERHANGS",
+                "DOMINANCE_SAME_DEPTH",
+                "PERSPECTIVE_UNDER_NZ",
+                "PERSPECTIVE_UNDER_NP",
+                "PERSPECTIVE_UNDER_ZP",
+            ]
+        );
+
+        // Without any perspective directions, dominance applies to all neighbors
+        let map = builder().with_overhangs(true, true, true).build();
+        assert!(!key(&map)
+            .shader_defs()
+            .contains(&"DOMINANCE_SAME_DEPTH".to_string()));
+    }
+
+    #[test]
+    fn overhang_exclusion_defs() {
+        let map = builder()
+            .with_dominance_overhang()
+            .with_overhang_exclusions(vec![3..4, 40..42])
+            .build();
+        assert_eq!(
+            key(&map).shader_defs(),
+            ["DOMINANCE_OVERHANGS", "OVERHANG_EXCLUSIONS"]
+        );
+        let excluded: Vec<u32> = (0..64).filter(|i| map.is_overhang_excluded(*i)).collect();
+        assert_eq!(excluded, [3, 40, 41]);
+
+        // Only used for dominance overhangs
+        let map = builder()
+            .with_overhang_exclusions(vec![3..4, 40..42])
+            .build();
+        assert!(!key(&map)
+            .shader_defs()
+            .contains(&"OVERHANG_EXCLUSIONS".to_string()));
+    }
+
+    #[test]
+    fn tile_overhang_flag_defs() {
+        let map = builder()
+            .with_dominance_overhang()
+            .with_tile_overhang_flags(2..4, OverhangFlags::SOUTH)
+            .build();
+        assert_eq!(
+            key(&map).shader_defs(),
+            ["DOMINANCE_OVERHANGS", "TILE_OVERHANG_FLAGS"]
+        );
+        assert_eq!(map.tile_overhang_flags.len(), 4);
+
+        // Flat maps without dominance overhangs have no overhangs to refine
+        let map = builder()
+            .with_tile_overhang_flags(2..4, OverhangFlags::SOUTH)
+            .build();
+        assert!(!key(&map)
+            .shader_defs()
+            .contains(&"TILE_OVERHANG_FLAGS".to_string()));
+    }
+
+    #[test]
+    fn minimap_defs() {
+        let map = builder().build();
+        let minimap = map
+            .minimap_builder(&Handle::default())
+            .with_minimap_colors(vec![Color::WHITE])
+            .build();
+        assert!(minimap.is_minimap());
+        assert_eq!(key(&minimap).shader_defs(), ["MINIMAP"]);
+        assert_eq!(minimap.minimap_colors, [u32::MAX]);
+        assert!(!key(&map).shader_defs().contains(&"MINIMAP".to_string()));
+    }
+
+    #[test]
+    fn autotile_defs() {
+        let map = builder().with_autotile(AutotileRules::new()).build();
+        assert!(key(&map).shader_defs().is_empty());
+
+        let rules = AutotileRules::new().with_terrain(3, 16, AutotileLayout::Wang16);
+        let map = builder().with_autotile(rules).build();
+        assert_eq!(key(&map).shader_defs(), ["AUTOTILE_RULES"]);
+        assert_eq!(map.autotile_rules.len(), AUTOTILE_TABLE_SIZE + 4);
+    }
+
+    #[test]
+    fn chunk_maps_sharing_an_atlas_keep_their_tiles() {
+        use bevy::{
+            asset::AssetEvents,
+            render::{
+                render_asset::RenderAssetUsages,
+                render_resource::{Extent3d, TextureDimension, TextureFormat},
+            },
+        };
+
+        use crate::{
+            bundle::MapBundleManaged,
+            tile_upload::{claim_tile_uploads, MapTileUploads},
+        };
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_asset::<Mesh>()
+            .init_asset::<Map>()
+            .init_resource::<SharedMapMeshes>()
+            .init_resource::<MapTileUploads<NoCustomization>>()
+            .add_systems(Update, update_loading_maps::<NoCustomization>)
+            .add_systems(
+                Last,
+                claim_tile_uploads::<NoCustomization>.after(AssetEvents),
+            );
+
+        // 5x5 chunks cloned from one template, all added in the same frame
+        let image = Image::new_fill(
+            Extent3d {
+                width: 64,
+                height: 64,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[255; 4],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::all(),
+        );
+        let atlas = app.world_mut().resource_mut::<Assets<Image>>().add(image);
+        let template: Map = Map::builder(uvec2(4, 4), atlas.clone(), vec2(16.0, 16.0)).build();
+        let handles: Vec<Handle<Map>> = (0..25)
+            .map(|chunk| {
+                let mut map = template.clone();
+                map.indexer_mut()
+                    .fill_rect_with(UVec2::ZERO, UVec2::MAX, |p| chunk * 16 + p.y * 4 + p.x);
+                let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+                app.world_mut().spawn(MapBundleManaged {
+                    material: handle.clone(),
+                    ..default()
+                });
+                handle
+            })
+            .collect();
+        app.update();
+        app.update();
+
+        let maps = app.world().resource::<Assets<Map>>();
+        let maps: Vec<&Map> = handles.iter().map(|h| maps.get(h).unwrap()).collect();
+        for (chunk, map) in maps.iter().enumerate() {
+            assert_eq!(map.indexer().at(3, 2), chunk as u32 * 16 + 11);
+            for other in &maps[chunk + 1..] {
+                assert!(!map.tile_upload.same_slot(&other.tile_upload));
+            }
+        }
+        let images = app.world().resource::<Assets<Image>>();
+        assert!(matches!(
+            images.get(&atlas).unwrap().sampler,
+            ImageSampler::Descriptor(ImageSamplerDescriptor {
+                min_filter: ImageFilterMode::Nearest,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn emissive_defs() {
+        let map = builder()
+            .with_emissive_tiles(vec![2..4, 33..34], 4.0)
+            .build();
+        assert!(key(&map)
+            .shader_defs()
+            .contains(&"EMISSIVE_TILES".to_string()));
+        let emissive: Vec<u32> = (0..64).filter(|i| map.is_emissive(*i)).collect();
+        assert_eq!(emissive, [2, 3, 33]);
+        assert_eq!(map.emissive_strength(), 4.0);
+
+        // The strength is uniform only
+        let mut brighter = map.clone();
+        brighter.set_emissive_strength(8.0);
+        assert!(key(&map) == key(&brighter));
+
+        let plain = builder().build();
+        assert!(!plain.is_emissive(0));
+        assert!(!key(&plain)
+            .shader_defs()
+            .contains(&"EMISSIVE_TILES".to_string()));
+    }
+
+    #[test]
+    fn color_jitter_defs() {
+        let map = builder()
+            .with_color_jitter(0.03, vec![1..2])
+            .build_and_set(|p| (p.x + p.y) % 2);
+        assert!(key(&map)
+            .shader_defs()
+            .contains(&"COLOR_JITTER".to_string()));
+        assert!(map.has_color_jitter(1) && !map.has_color_jitter(0));
+
+        // Pinned, as the shader computes the same values
+        assert_eq!(
+            color_jitter_hash(ivec2(3, 5), COLOR_JITTER_VALUE_SEED),
+            0.6759876
+        );
+        assert_eq!(
+            color_jitter_hash(ivec2(3, 5), COLOR_JITTER_HUE_SEED),
+            -0.47812438
+        );
+        assert_eq!(map.color_jitter_at(uvec2(3, 4)), 0.0);
+        assert_eq!(map.color_jitter_at(uvec2(3, 5)), 0.03 * 0.6759876);
+        assert_eq!(map.color_jitter_hue_at(uvec2(3, 5)), 0.0);
+        assert_eq!(map.color_jitter_at(uvec2(8, 0)), 0.0);
+
+        // Compiled out without jitter
+        let mut off = map.clone();
+        off.set_color_jitter(0.0);
+        assert!(!key(&off)
+            .shader_defs()
+            .contains(&"COLOR_JITTER".to_string()));
+    }
+
+    #[test]
+    fn secondary_atlas_defs() {
+        let mut map = builder().with_secondary_atlas(Handle::default()).build();
+        assert!(key(&map)
+            .shader_defs()
+            .contains(&"SECONDARY_ATLAS".to_string()));
+
+        // The blend is uniform only
+        let before = key(&map);
+        map.set_atlas_blend(2.0);
+        assert_eq!(map.atlas_blend(), 1.0);
+        assert!(key(&map) == before);
+
+        let mut plain = builder().build();
+        plain.set_atlas_blend(0.5);
+        assert_eq!(plain.atlas_blend(), 0.0);
+        assert!(!key(&plain)
+            .shader_defs()
+            .contains(&"SECONDARY_ATLAS".to_string()));
+    }
+
+    #[test]
+    fn additional_atlas_defs() {
+        let map = builder()
+            .with_additional_atlas(Handle::default(), vec2(8.0, 8.0), Vec2::ZERO)
+            .build();
+        assert!(key(&map)
+            .shader_defs()
+            .contains(&"ADDITIONAL_ATLASES".to_string()));
+        assert_eq!(map.map_uniform.n_atlases, 2);
+
+        let plain = builder().build();
+        assert!(!key(&plain)
+            .shader_defs()
+            .contains(&"ADDITIONAL_ATLASES".to_string()));
+    }
+
+    #[test]
+    fn tile_color_defs() {
+        let map = builder().with_tile_color_channel().build();
+        assert!(key(&map).shader_defs().contains(&"TILE_COLORS".to_string()));
+        assert_eq!(
+            map.tile_colors.len(),
+            map.map_size().element_product() as usize
+        );
+
+        // Without the channel, only a placeholder is uploaded
+        let plain = builder().build();
+        assert!(!key(&plain)
+            .shader_defs()
+            .contains(&"TILE_COLORS".to_string()));
+        assert_eq!(plain.tile_colors.len(), 1);
+    }
+
+    #[test]
+    fn tile_layer_defs() {
+        let mut map = builder().with_layers(3).build();
+        assert!(key(&map).shader_defs().contains(&"TILE_LAYERS".to_string()));
+        assert_eq!(
+            map.layer_tiles.len(),
+            2 * map.map_size().element_product() as usize
+        );
+        // Visibility is uniform only
+        let before = key(&map);
+        map.set_layer_visible(2, false);
+        assert!(key(&map) == before);
+        assert_eq!(map.map_uniform.hidden_layers, 0b100);
+
+        // A single layer needs no layer buffer, too many layers are capped
+        let plain = builder().with_layers(1).build();
+        assert!(!key(&plain)
+            .shader_defs()
+            .contains(&"TILE_LAYERS".to_string()));
+        assert_eq!(plain.layer_tiles.len(), 1);
+        assert_eq!(
+            builder().with_layers(40).build().n_layers(),
+            MAX_TILE_LAYERS
+        );
+    }
+
+    #[test]
+    fn tile_animation_defs() {
+        let mut map = builder()
+            .with_tile_animations(vec![TileAnimation::new(2, 3, 4.0)])
+            .build();
+        assert!(key(&map)
+            .shader_defs()
+            .contains(&"TILE_ANIMATIONS".to_string()));
+
+        // Header and frames
+        assert_eq!(map.tile_animation_buffer.len(), 1 + 5 + 3);
+
+        // The count is still uploaded
+        map.set_tile_animations(vec![]);
+        assert!(!key(&map)
+            .shader_defs()
+            .contains(&"TILE_ANIMATIONS".to_string()));
+        assert_eq!(map.tile_animation_buffer, [0]);
+    }
+
+    #[test]
+    fn forced_underhangs_only_expand_overhung_sides() {
+        // Neighbors to the left and right overhang onto each other
+        let map = builder()
+            .with_projection(IDENTITY)
+            .with_forced_underhangs(vec![vec2(1.0, 0.0)])
+            .build();
+        assert_eq!(map.world_size(), vec2(10.0, 8.0) * 16.0);
+        // The quad starts one tile left of the map and exactly at its top
+        let quad_min = -map.world_size() / 2.0;
+        assert_eq!(map.map_to_local(vec2(-1.0, 8.0)), quad_min);
+
+        // Tall tiles additionally reach up to 3 tiles above the map
+        let map = builder()
+            .with_projection(IDENTITY)
+            .with_forced_underhangs(vec![vec2(1.0, 0.0)])
+            .with_overhang_extent_tiles(3)
+            .build();
+        assert_eq!(map.world_size(), vec2(10.0, 11.0) * 16.0);
+        assert_eq!(map.map_to_local(vec2(9.0, -3.0)), map.world_size() / 2.0);
+
+        // Without forced underhangs all sides get one tile
+        let map = builder().with_projection(IDENTITY).build();
+        assert_eq!(map.world_size(), vec2(10.0, 10.0) * 16.0);
+    }
+
+    #[test]
+    fn forced_underhang_defs() {
+        for (direction, def) in UNDERHANG_DIRECTIONS {
+            // Only the direction matters, not the length
+            for direction in [direction, direction * 3.0] {
+                let map = builder()
+                    .with_projection(AXONOMETRIC)
+                    .with_forced_underhangs(vec![direction])
+                    .build();
+                assert_eq!(
+                    key(&map).shader_defs(),
+                    [
+                        "PERSPECTIVE_UNDERHANGS".to_string(),
+                        "PERSPECTIVE_OVERHANGS".to_string(),
+                        format!("PERSPECTIVE_UNDER_{}", def),
+                    ]
+                );
+            }
+        }
+
+        // Directions that are not one of the eight neighbors are ignored
+        let defs = perspective_defs(IDENTITY.projection, &[vec2(1.0, 2.0)]);
+        assert!(defs.is_empty());
+    }
+
+    #[test]
+    fn equal_configs_give_equal_keys() {
+        let a = builder().with_projection(AXONOMETRIC).build();
+        let b = builder().with_projection(AXONOMETRIC).build();
+        assert!(key(&a) == key(&b));
+        assert_eq!(hash(&key(&a)), hash(&key(&b)));
+
+        let c = builder().with_projection(IDENTITY).build();
+        assert!(key(&a) != key(&c));
+
+        let d = builder()
+            .with_projection(AXONOMETRIC)
+            .with_blend_mode(MapBlendMode::Additive)
+            .build();
+        assert!(key(&a) != key(&d));
+    }
+
+    #[test]
+    fn forced_underhang_order_does_not_change_key() {
+        let directions = [vec2(1.0, 0.0), vec2(0.0, 1.0), vec2(-1.0, -1.0)];
+        let a = builder()
+            .with_forced_underhangs(directions.to_vec())
+            .build();
+        let b = builder()
+            .with_forced_underhangs(directions.iter().rev().copied().collect())
+            .build();
+        assert!(key(&a) == key(&b));
+        assert_eq!(hash(&key(&a)), hash(&key(&b)));
+    }
+
+    #[test]
+    fn defs_follow_projection_sign() {
+        // Tilting the map so that higher rows are further away enables underhangs from below
+        let projection = Mat3::from_cols(
+            vec3(1.0, 0.0, 0.0),
+            vec3(0.0, -1.0, 0.5),
+            vec3(0.0, 0.0, 1.0),
+        );
+        assert_eq!(
+            perspective_defs(projection, &[]),
+            [
+                "PERSPECTIVE_UNDER_ZN",
+                "PERSPECTIVE_UNDER_NN",
+                "PERSPECTIVE_UNDER_PN",
+            ]
+        );
+    }
+
+    #[test]
+    fn edge_fade_is_uniform_only() {
+        let plain = builder().build();
+        let faded = builder()
+            .with_edge_fade(vec2(2.0, -1.0), Color::BLACK)
+            .build();
+        // Negative widths disable the fade
+        assert_eq!(faded.edge_fade().0, vec2(2.0, 0.0));
+        assert_eq!(faded.edge_fade().1, Color::BLACK);
+        // No pipeline specialization
+        assert!(key(&plain) == key(&faded));
+    }
+
+    #[test]
+    fn staggered_map_positions() {
+        // Points in the hexagon of each tile (relative to its rectangle)
+        let inside = [
+            vec2(0.5, 0.5),
+            vec2(0.5, 0.05),
+            vec2(0.5, 0.95),
+            vec2(0.05, 0.5),
+            vec2(0.95, 0.5),
+        ];
+        for projection in [
+            HEX_POINTY_TOP,
+            HEX_FLAT_TOP,
+            HEX_POINTY_TOP_EVEN,
+            HEX_FLAT_TOP_EVEN,
+        ] {
+            let map = builder().with_projection(projection).build();
+            for tile in map.indexer().positions() {
+                for p in inside.map(|p| tile.as_vec2() + p) {
+                    let round_trip = map.local_to_map(map.map_to_local(p));
+                    assert!(round_trip.distance(p) < 1e-4, "{:?} != {:?}", round_trip, p);
+                }
+            }
+        }
+
+        let map = builder().with_projection(HEX_POINTY_TOP).build();
+        // Odd rows are shifted right by half a tile
+        let center = |tile| map.tile_center_local(tile);
+        assert_eq!(center(uvec2(0, 1)).x - center(uvec2(0, 0)).x, 8.0);
+        assert_eq!(center(uvec2(0, 2)).x, center(uvec2(0, 0)).x);
+        // and overlap by a quarter tile
+        assert_eq!(center(uvec2(0, 0)).y - center(uvec2(0, 1)).y, 12.0);
+        // Below the bottom vertex of tile (0, 0), tiles (-1, 1) and (0, 1) of the shifted row meet
+        let bottom = map.map_to_local(vec2(0.5, 0.999));
+        assert_eq!(
+            map.local_to_map(bottom + vec2(0.0, 1.0)).floor(),
+            vec2(0.0, 0.0)
+        );
+        assert_eq!(
+            map.local_to_map(bottom + vec2(-1.0, -1.0)).floor(),
+            vec2(-1.0, 1.0)
+        );
+        assert_eq!(
+            map.local_to_map(bottom + vec2(1.0, -1.0)).floor(),
+            vec2(0.0, 1.0)
+        );
+
+        // The even variants shift the other rows and columns
+        let map = builder().with_projection(HEX_POINTY_TOP_EVEN).build();
+        let center = |tile| map.tile_center_local(tile);
+        assert_eq!(center(uvec2(0, 0)).x - center(uvec2(0, 1)).x, 8.0);
+        assert_eq!(center(uvec2(0, 2)).x, center(uvec2(0, 0)).x);
+        let map = builder().with_projection(HEX_FLAT_TOP_EVEN).build();
+        let center = |tile| map.tile_center_local(tile);
+        assert_eq!(center(uvec2(1, 0)).y - center(uvec2(0, 0)).y, 8.0);
+        assert_eq!(center(uvec2(0, 0)).x - center(uvec2(1, 0)).x, -12.0);
+    }
+
+    #[test]
+    fn attributes_match_the_vertex_count() {
+        for format in [VertexFormat::Float32x3, VertexFormat::Float32x2] {
+            let mut mesh = indexed_quad(format);
+            assert_eq!(vertex_count(&mesh), Some(4));
+
+            // More colors than vertices
+            let attributes = MapAttributes {
+                mix_color: vec![Vec4::ZERO; 6],
+            };
+            MapAttributes::set_mix_color(Some(&attributes), &mut mesh);
+            MapAttributes::set_animation_state(Some(&attributes), &mut mesh, &Time::default());
+            let Some(VertexAttributeValues::Float32x4(mix_color)) =
+                mesh.attribute(ATTRIBUTE_MIX_COLOR)
+            else {
+                panic!("{format:?}: no mix color");
+            };
+            assert_eq!(mix_color, &vec![[0.0; 4]; 4], "{format:?}");
+            assert_eq!(
+                mesh.attribute(ATTRIBUTE_ANIMATION_STATE).map(|v| v.len()),
+                Some(4),
+                "{format:?}"
+            );
+            assert_eq!(mesh.indices().map(|i| i.len()), Some(6));
+
+            // Fewer colors than vertices, the others are white
+            let attributes = MapAttributes {
+                mix_color: vec![Vec4::ZERO; 2],
+            };
+            MapAttributes::set_mix_color(Some(&attributes), &mut mesh);
+            let Some(VertexAttributeValues::Float32x4(mix_color)) =
+                mesh.attribute(ATTRIBUTE_MIX_COLOR)
+            else {
+                panic!("{format:?}: no mix color");
+            };
+            assert_eq!(mix_color[1..3], [[0.0; 4], [1.0; 4]], "{format:?}");
+        }
+    }
+
+    #[test]
+    fn overhang_extent_grows_world_size() {
+        let axonometric = || {
+            builder()
+                .with_projection(AXONOMETRIC)
+                .with_perspective_overhang()
+        };
+        let base = axonometric().build().world_size();
+        for levels in [1, 2, 5] {
+            let map = axonometric().with_overhang_extent_tiles(levels).build();
+            let extra = (levels - 1) as f32 * map.world_tile_size().y;
+            assert_eq!(map.world_size(), base + vec2(0.0, extra), "{levels}");
+        }
+
+        // Forced underhangs reserve space for every level above the tile as well
+        let forced = |levels| {
+            builder()
+                .with_forced_underhangs(vec![vec2(0.0, 1.0)])
+                .with_overhang_extent_tiles(levels)
+                .build()
+                .world_size()
+        };
+        assert!(forced(1).y < forced(2).y && forced(2).y < forced(5).y);
+        assert_eq!(forced(1).x, forced(5).x);
+    }
+
+    #[test]
+    fn expired_decals_are_removed_from_the_grid() {
+        use std::time::Duration;
+
+        use bevy::time::TimeUpdateStrategy;
+
+        use crate::decal::{update_map_decals, Decal};
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Map>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                250,
+            )))
+            .add_systems(Update, update_map_decals::<NoCustomization>);
+        let mut map = builder().build();
+        let center = map.tile_center_local(uvec2(4, 4));
+        map.add_decal(Decal::new(center, 3));
+        map.add_decal(Decal::new(center, 5).with_ttl(0.6));
+        let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+        let elapsed = |app: &App| app.world().resource::<Time>().elapsed_seconds();
+        fn map<'a>(app: &'a App, handle: &Handle<Map>) -> &'a Map {
+            app.world().resource::<Assets<Map>>().get(handle).unwrap()
+        }
+
+        app.update();
+        let added = elapsed(&app);
+        // Both decals are listed in the cell of the center tile
+        assert_eq!(map(&app, &handle).decal_buffer.len(), 2);
+        assert_eq!(&map(&app, &handle).decal_grid[..4], &[8, 1, 1, 0]);
+        assert_eq!(&map(&app, &handle).decal_grid[4..], &[2, 0, 1]);
+
+        for _ in 0..10 {
+            if map(&app, &handle).decals().count() == 1 {
+                break;
+            }
+            app.update();
+        }
+        let removed = elapsed(&app) - added;
+        assert!(removed >= 0.6 && removed < 0.6 + 0.25 + 1e-3, "{removed}");
+        assert_eq!(map(&app, &handle).decals().next().unwrap().atlas_index, 3);
+        assert_eq!(map(&app, &handle).decal_buffer.len(), 1);
+        assert_eq!(&map(&app, &handle).decal_grid[4..], &[1, 0]);
+
+        // Without decals, the grid is reset
+        app.world_mut()
+            .resource_mut::<Assets<Map>>()
+            .get_mut(&handle)
+            .unwrap()
+            .clear_decals();
+        app.update();
+        assert_eq!(map(&app, &handle).decal_grid, vec![0]);
+    }
+
+    #[test]
+    fn u16_tiles_round_trip() {
+        let tiles: Vec<u16> = (0..64).map(|i| i * 1000 + 7).collect();
+        let mut map = builder().build();
+        map.enable_change_ticks();
+        let tick = map.current_tick();
+        assert_eq!(map.indexer_mut().copy_from_u16_slice(&tiles), 64);
+
+        let indexer = map.indexer();
+        let read: Vec<u16> = indexer
+            .positions()
+            .map(|p| indexer.at_uvec(p).try_into().unwrap())
+            .collect();
+        assert_eq!(read, tiles);
+        assert_eq!(map.indexer().at(7, 7), 63_007);
+        assert_eq!(
+            map.tiles_changed_since(tick, URect::new(0, 0, 8, 8))
+                .count(),
+            64
+        );
+    }
+
+    #[test]
+    fn u16_slices_of_the_wrong_length_are_rejected() {
+        let mut map = builder().build_and_set(|p| p.x + p.y);
+        for len in [0, 63, 65] {
+            assert_eq!(map.indexer_mut().copy_from_u16_slice(&vec![9; len]), 0);
+        }
+        assert_eq!(map.indexer().at(3, 4), 7);
+
+        // Uniform maps stay uniform
+        let mut map = builder().build();
+        assert_eq!(map.indexer_mut().copy_from_u16_slice(&[1; 10]), 0);
+        assert_eq!(map.uniform_tile(), Some(0));
+    }
+
+    #[test]
+    fn only_changed_rows_are_synced() {
+        let grid: Vec<u8> = (0..64).map(|i| i as u8).collect();
+        let mut map = builder().build_and_set(|_| 1000);
+        map.enable_change_ticks();
+        let tick = map.current_tick();
+        // Rows 1 and 6, and bits beyond the map
+        map.indexer_mut().sync_changed_rows_from(
+            &grid,
+            &[(1 << 1) | (1 << 6) | (1 << 40)],
+            |&cell| cell as u32 + 1,
+        );
+
+        let indexer = map.indexer();
+        for p in indexer.positions() {
+            let expected = match p.y {
+                1 | 6 => p.y * 8 + p.x + 1,
+                _ => 1000,
+            };
+            assert_eq!(indexer.at_uvec(p), expected, "{p}");
+        }
+        let mut rows: Vec<u32> = map
+            .tiles_changed_since(tick, URect::new(0, 0, 8, 8))
+            .map(|p| p.y)
+            .collect();
+        rows.sort();
+        rows.dedup();
+        assert_eq!(rows, [1, 6]);
+
+        // No changed rows leave everything as it is
+        map.indexer_mut().sync_changed_rows_from(&grid, &[], |_| 0);
+        assert_eq!(map.indexer().at(0, 0), 1000);
+        assert_eq!(map.indexer().at(0, 1), 9);
+    }
+
+    #[test]
+    fn uniform_maps_are_synced() {
+        let grid: Vec<u8> = (0..64).map(|i| (i % 3) as u8).collect();
+        let mut map = builder().build_and_set(|_| 4);
+        assert_eq!(map.uniform_tile(), Some(4));
+
+        map.indexer_mut()
+            .sync_changed_rows_from(&grid, &[1 << 2], |&cell| cell.into());
+        assert_eq!(map.uniform_tile(), None);
+        assert_eq!(map.indexer().at(5, 2), (21 % 3) as u32);
+        assert_eq!(map.indexer().at(5, 3), 4);
+
+        let mut map = builder().build_and_set(|_| 4);
+        map.indexer_mut().sync_from(&grid, |&cell| cell.into());
+        let indexer = map.indexer();
+        assert!(indexer
+            .positions()
+            .all(|p| indexer.at_uvec(p) == (p.y * 8 + p.x) % 3));
     }
 }
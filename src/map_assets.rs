@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+
+use super::{map::Map, plugin::Customization, state_snapshot::MapStateSnapshot};
+
+/// Change-aware writes to map assets.
+///
+/// Every [`Assets::get_mut`] marks the map as modified, so it is re-extracted and uploaded in
+/// full, even if nothing actually changed. These methods look at the map first and only borrow
+/// it mutably if the write changes it, so idempotent writes (eg. of a cursor position every
+/// frame) cost nothing.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_fast_tilemap::prelude::*;
+/// fn place_wall(mut maps: ResMut<Assets<Map>>, map: Query<&Handle<Map>>) {
+///     // No upload unless the tile was not a wall before
+///     maps.set_tile_if_neq(map.single(), UVec2::new(3, 4), 7);
+/// }
+/// ```
+pub trait MapAssetsExt<C: Customization> {
+    /// Call `modify` on the map if `needs_change` returns true for it.
+    /// Returns whether the map was modified.
+    fn modify_if(
+        &mut self,
+        id: impl Into<AssetId<Map<C>>>,
+        needs_change: impl FnOnce(&Map<C>) -> bool,
+        modify: impl FnOnce(&mut Map<C>),
+    ) -> bool;
+
+    /// Set the tile at `tile` to `value` unless it already has that value or is out of bounds.
+    /// Returns whether the map was modified.
+    fn set_tile_if_neq(&mut self, id: impl Into<AssetId<Map<C>>>, tile: UVec2, value: u32) -> bool {
+        self.modify_if(
+            id,
+            |map| tile.cmplt(map.map_size()).all() && map.indexer().at_uvec(tile) != value,
+            |map| map.indexer_mut().set_uvec(tile, value),
+        )
+    }
+
+    /// Replace the user data of the map unless it is equal to `user_data`.
+    /// Returns whether the map was modified.
+    fn set_user_data_if_neq(
+        &mut self,
+        id: impl Into<AssetId<Map<C>>>,
+        user_data: C::UserData,
+    ) -> bool
+    where
+        C::UserData: PartialEq,
+    {
+        self.modify_if(
+            id,
+            |map| map.user_data != user_data,
+            |map| map.user_data = user_data.clone(),
+        )
+    }
+
+    /// Restore the state of the map (see [`Map::restore_state`]) unless it is in that state
+    /// already.
+    /// Returns whether the map was modified.
+    fn restore_state_if_neq(
+        &mut self,
+        id: impl Into<AssetId<Map<C>>>,
+        snapshot: &MapStateSnapshot<C>,
+    ) -> bool
+    where
+        C::UserData: PartialEq,
+    {
+        self.modify_if(
+            id,
+            |map| map.snapshot_state() != *snapshot,
+            |map| map.restore_state(snapshot),
+        )
+    }
+}
+
+impl<C: Customization> MapAssetsExt<C> for Assets<Map<C>> {
+    fn modify_if(
+        &mut self,
+        id: impl Into<AssetId<Map<C>>>,
+        needs_change: impl FnOnce(&Map<C>) -> bool,
+        modify: impl FnOnce(&mut Map<C>),
+    ) -> bool {
+        let id = id.into();
+        if !self.get(id).is_some_and(needs_change) {
+            return false;
+        }
+        modify(self.get_mut(id).unwrap());
+        true
+    }
+}
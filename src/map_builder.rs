@@ -1,11 +1,18 @@
+use std::{fmt, ops::Range};
+
 use super::prelude::*;
-use bevy::{math::uvec2, prelude::*};
+use bevy::{math::Vec3Swizzles, prelude::*};
 
-use super::tile_projection::TileProjection;
+use super::{
+    atlas_metadata::AtlasMetadataState, overlay_canvas::OverlayCanvas, tile_colors::WHITE,
+    tile_projection::TileProjection,
+};
 
 /// Builder for constructing a map component. This is usually the preferred way of constructing.
 pub struct MapBuilder<C: Customization = NoCustomization> {
-    map: Map<C>,
+    pub(crate) map: Map<C>,
+    secondary_projection: Option<TileProjection>,
+    viewport_fit: Option<(Vec2, UVec2)>,
 }
 
 impl<C: Customization> MapBuilder<C> {
@@ -18,6 +25,7 @@ impl<C: Customization> MapBuilder<C> {
                 map_uniform: MapUniform {
                     map_size,
                     tile_size,
+                    world_tile_size: tile_size,
                     ..default()
                 },
                 perspective_overhangs: true,
@@ -25,6 +33,8 @@ impl<C: Customization> MapBuilder<C> {
                 dominance_overhangs: false,
                 ..default()
             },
+            secondary_projection: None,
+            viewport_fit: None,
         }
     } // fn new
 
@@ -42,6 +52,7 @@ impl<C: Customization> MapBuilder<C> {
                 map_uniform: MapUniform {
                     map_size,
                     tile_size,
+                    world_tile_size: tile_size,
                     ..default()
                 },
                 perspective_overhangs: true,
@@ -50,6 +61,8 @@ impl<C: Customization> MapBuilder<C> {
                 user_data,
                 ..default()
             },
+            secondary_projection: None,
+            viewport_fit: None,
         }
     } // fn new
 
@@ -63,11 +76,106 @@ impl<C: Customization> MapBuilder<C> {
         self
     }
 
+    /// Set custom shader parameter `index` (in `0..4`), see [`Map::set_param`].
+    pub fn with_param(mut self, index: usize, value: Vec4) -> Self {
+        self.map.set_param(index, value);
+        self
+    }
+
     /// Us the given map projection for rendering. Default is [`crate::tile_projection::IDENTITY`],
     /// which will render the tiles in rectangular layout.
     pub fn with_projection(mut self, projection: TileProjection) -> Self {
         self.map.map_uniform.projection = projection.projection;
         self.map.map_uniform.tile_anchor_point = projection.tile_anchor_point;
+        self.map.map_uniform.stagger = projection.stagger as u32;
+        self
+    }
+
+    /// Additionally allow rendering with `projection`, see [`Map::set_projection_blend`].
+    ///
+    /// Both projections should have the same orientation (sign of the determinant),
+    /// otherwise the blended projection degenerates somewhere in between.
+    pub fn with_secondary_projection(mut self, projection: TileProjection) -> Self {
+        self.secondary_projection = Some(projection);
+        self
+    }
+
+    /// Additionally sample `atlas` (which should have the same tile layout as the atlas) and
+    /// blend between the two, see [`Map::set_atlas_blend`].
+    /// The map waits for both atlases to load. Maps without a secondary atlas don't sample one.
+    pub fn with_secondary_atlas(mut self, atlas: Handle<Image>) -> Self {
+        self.map.secondary_atlas = Some(atlas);
+        self
+    }
+
+    /// Add a paintable RGBA layer with `texels_per_tile` texels per tile in each direction,
+    /// eg. for tracks, scorch marks or blood that accumulate over time without touching the
+    /// tiles. Paint it with [`Map::paint_circle`] and [`Map::paint_stamp`], it is drawn over
+    /// the tiles (before decals) with linear filtering.
+    /// Its image is created by [`update_overlay_canvases`], see [`Map::overlay_canvas`].
+    pub fn with_overlay_canvas(mut self, texels_per_tile: u32) -> Self {
+        self.map.overlay_canvas = Some(OverlayCanvas::new(self.map.map_size(), texels_per_tile));
+        self
+    }
+
+    /// Add a color per tile (initially white) that the tile color is multiplied with, set with
+    /// [`MapIndexerMut::set_color`], eg. for fog of war, territory or tinting the hovered tile
+    /// (a single tile write instead of changing tile values).
+    /// This is an extra storage buffer of 4 bytes per tile, maps without it only bind a single
+    /// placeholder entry and don't apply tile colors.
+    pub fn with_tile_color_channel(mut self) -> Self {
+        let size = self.map.map_size();
+        self.map.tile_colors = vec![WHITE; (size.x * size.y).max(1) as usize];
+        self.map.tile_color_channel = true;
+        self
+    }
+
+    /// Same as [`Self::with_tile_color_channel`]: per tile tints are the tile colors,
+    /// see [`MapIndexerMut::set_tint`].
+    pub fn with_per_tile_tint(self) -> Self {
+        self.with_tile_color_channel()
+    }
+
+    /// Stack `n` layers of tiles (including layer 0, the tiles set with [`MapIndexerMut::set`])
+    /// in this map, which are rendered in order with alpha blending, still as a single quad.
+    /// Upper layers start out as [`EMPTY_TILE`], set them with [`MapIndexerMut::set_layer`]
+    /// and show or hide them with [`Map::set_layer_visible`].
+    /// Upper layers are drawn after the overhangs of layer 0 and have none of their own, their
+    /// tiles take an extra storage buffer of 4 bytes per tile and layer.
+    /// `n` is at most [`MAX_TILE_LAYERS`].
+    pub fn with_layers(mut self, n: u32) -> Self {
+        if n > MAX_TILE_LAYERS {
+            warn!(
+                "with_layers(): {} layers exceed the maximum of {}",
+                n, MAX_TILE_LAYERS
+            );
+        }
+        let n = n.clamp(1, MAX_TILE_LAYERS);
+        let size = self.map.map_size();
+        let len = ((n - 1) * size.x * size.y).max(1) as usize;
+        self.map.layer_tiles = vec![EMPTY_TILE; len];
+        self.map.map_uniform.n_layers = n;
+        self
+    }
+
+    /// Animate tiles without custom shader code: tiles with the base index of an animation
+    /// show its frames in turn, driven by the animation state of the map (so they follow
+    /// [`Map::set_animation_speed`]). The map keeps the base index, so animated tiles are not
+    /// re-uploaded and keep their dominance. Their overhangs animate as well.
+    /// Custom `sample_tile` code gets the index of the current frame in `ExtractIn::tile_index`.
+    /// Animations without frames are ignored, of several animations for the same base index,
+    /// the last one is used. See [`Map::current_frame`] for the frame on the CPU.
+    pub fn with_tile_animations(mut self, animations: Vec<TileAnimation>) -> Self {
+        self.map.set_tile_animations(animations);
+        self
+    }
+
+    /// Add a looping animation through the atlas indices `frames` for tiles with atlas index
+    /// `base`, see [`Self::with_tile_animations`].
+    pub fn with_tile_animation(mut self, base: u32, frames: &[u32], fps: f32) -> Self {
+        let mut animations = self.map.tile_animations().to_vec();
+        animations.push(TileAnimation::with_frames(base, frames, fps));
+        self.map.set_tile_animations(animations);
         self
     }
 
@@ -78,8 +186,8 @@ impl<C: Customization> MapBuilder<C> {
     ///
     /// Note that it is crucial that these values are precisely correct,
     /// we use them internally to determine how many tiles there are in the atlas in each
-    /// direction, if that does not produce a number close to an integer,
-    /// you will get a `panic` when the tile atlas is loaded.
+    /// direction, if that does not produce a number close to an integer, the map is not
+    /// loaded ([`MapError::NonIntegralTileCount`], see [`Self::try_build`]).
     pub fn with_padding(mut self, inner: Vec2, topleft: Vec2, bottomright: Vec2) -> Self {
         self.map.map_uniform.inner_padding = inner;
         self.map.map_uniform.outer_padding_topleft = topleft;
@@ -87,6 +195,34 @@ impl<C: Customization> MapBuilder<C> {
         self
     }
 
+    /// Read tile size and padding from the metadata file next to the atlas when the map is
+    /// loaded, overriding the values given to the builder, see [`AtlasMetadata`].
+    /// For `tiles/atlas.png` this is `tiles/atlas.tiles.ron`, if it does not exist the builder
+    /// values are used.
+    pub fn with_atlas_metadata(mut self) -> Self {
+        self.map.atlas_metadata = AtlasMetadataState::Requested;
+        self
+    }
+
+    /// Size of each tile in world units (ie. in local coordinates of the map entity),
+    /// default is the tile size in the atlas.
+    /// Eg. `vec2(1.0, 1.0)` renders tiles of 64x64 pixel art as one world unit each.
+    /// Only the world scale changes, the atlas is still addressed in pixels.
+    pub fn with_world_tile_size(mut self, world_tile_size: Vec2) -> Self {
+        self.map.map_uniform.world_tile_size = world_tile_size;
+        self
+    }
+
+    /// Derive the world tile size such that `tiles` tiles exactly span `viewport_size`
+    /// (eg. the window size), see [`Map::fit_to_viewport`].
+    /// This overrides [`Self::with_world_tile_size`] and takes the projection into account
+    /// regardless of the order of the builder calls.
+    /// Add [`FitMapToWindow`] to the map entity to follow window resizes.
+    pub fn fit_to_viewport(mut self, viewport_size: Vec2, tiles: UVec2) -> Self {
+        self.viewport_fit = Some((viewport_size, tiles));
+        self
+    }
+
     /// Render this map in "dominance" overhang mode.
     /// "Dominance" overhang draws the overlap of tiles depending on their index in the tile atlas.
     /// Tiles with higher index will be drawn on top of tiles with lower index.
@@ -98,6 +234,41 @@ impl<C: Customization> MapBuilder<C> {
         self
     }
 
+    /// In dominance overhang mode, tiles with atlas indices in `ranges` neither overhang into
+    /// their neighbors nor are overhung by them, eg. for fences that need a high index for other
+    /// reasons.
+    pub fn with_overhang_exclusions(mut self, ranges: Vec<Range<u32>>) -> Self {
+        self.map.set_overhang_exclusions(&ranges);
+        self
+    }
+
+    /// Tiles with atlas indices in `ranges` are emissive: their color (not their alpha) is
+    /// multiplied by `strength` (see [`Map::set_emissive_strength`]), so with an HDR camera
+    /// and bloom they glow, eg. for lava or lamps.
+    pub fn with_emissive_tiles(mut self, ranges: Vec<Range<u32>>, strength: f32) -> Self {
+        self.map.set_emissive_tiles(&ranges);
+        self.map.set_emissive_strength(strength);
+        self
+    }
+
+    /// Tiles with atlas indices in `ranges` get a per-tile brightness jitter of up to `amount`
+    /// (relative, eg. `0.03` for ±3%), to break up large areas of identical tiles, eg. grass.
+    /// The jitter is a hash of the tile position (see [`Map::color_jitter_at`]), so it does not
+    /// change between frames or when zooming, and is applied right after sampling the tile,
+    /// before the mix color. Without tiles in `ranges`, the jitter is compiled out.
+    pub fn with_color_jitter(mut self, amount: f32, ranges: Vec<Range<u32>>) -> Self {
+        self.map.set_color_jitter_tiles(&ranges);
+        self.map.set_color_jitter(amount);
+        self
+    }
+
+    /// Additionally to [`Self::with_color_jitter`], rotate the hue of these tiles by up to
+    /// `angle` radians.
+    pub fn with_color_jitter_hue(mut self, angle: f32) -> Self {
+        self.map.set_color_jitter_hue(angle);
+        self
+    }
+
     /// Render this map in "perspective" overhang mode.
     /// "Perspective" overhang draws the overlap of tiles depending on their "depth" that is the
     /// y-axis of their world position (tiles higher up are considered further away).
@@ -122,6 +293,24 @@ impl<C: Customization> MapBuilder<C> {
         self
     }
 
+    /// In perspective overhang mode, allow tile graphics to extend up to `levels_up` tiles
+    /// upwards beyond their own cell (default is 1), eg. for tall buildings.
+    ///
+    /// The tile graphics still need to fit into the padding of the atlas
+    /// (see [`Self::with_padding`]).
+    pub fn with_overhang_extent_tiles(mut self, levels_up: u32) -> Self {
+        self.map.map_uniform.overhang_levels = levels_up.max(1);
+        self
+    }
+
+    /// Enable the overhang modes independently.
+    ///
+    /// With dominance and perspective overhangs combined, perspective resolves depth first:
+    /// neighbors in the perspective directions are drawn below (underhangs) or above
+    /// (overhangs) the tile regardless of their index. Dominance then only applies among the
+    /// remaining neighbors at the same depth, eg. decor of higher index blending into the
+    /// ground next to it. Each fragment samples both the dominance and the perspective
+    /// neighbors, so this costs about as much as both modes together.
     pub fn with_overhangs(
         mut self,
         dominance: bool,
@@ -134,27 +323,193 @@ impl<C: Customization> MapBuilder<C> {
         self
     }
 
+    /// Blend the map with what is rendered below it using `blend_mode`.
+    /// Default is [`MapBlendMode::Alpha`].
+    pub fn with_blend_mode(mut self, blend_mode: MapBlendMode) -> Self {
+        self.map.blend_mode = blend_mode;
+        self
+    }
+
+    /// Render the parts of the map quad outside of the map (eg. the corners around an
+    /// axonometric map) with `color` instead of leaving them transparent.
+    /// See [`Map::set_edge_fade`].
+    pub fn with_edge_fade(mut self, width: Vec2, color: Color) -> Self {
+        self.map.set_edge_fade(width, color);
+        self
+    }
+
+    /// See [`Map::set_outside_color`].
+    pub fn with_outside_color(mut self, color: Color) -> Self {
+        self.map.set_outside_color(color);
+        self
+    }
+
+    /// Repeat the whole map content infinitely in both axes, eg. for a decorative background
+    /// pattern. Overhangs wrap around at the map edges, so the pattern tiles seamlessly.
+    /// Use [`MeshManagedByMap::ViewportFit`](crate::map::MeshManagedByMap::ViewportFit) (or a
+    /// custom mesh) to cover the whole view, the default mesh only covers the map itself.
+    /// Edge fade does not apply to repeated maps, decals are not repeated.
+    pub fn with_repeat_content(mut self) -> Self {
+        self.map.map_uniform.repeat_content = 1;
+        self
+    }
+
+    /// Make the mesh cover exactly the map (its tiles projected, without margins for overhangs)
+    /// and render the edge tiles for fragments at the map edges that end up just outside of it
+    /// (instead of the outside color), so maps placed edge to edge (eg. chunks) tile seamlessly
+    /// at any zoom level.
+    /// Overhangs beyond the map edges are cut off. Staggered maps are not clamped.
+    pub fn with_exact_bounds(mut self, exact: bool) -> Self {
+        self.map.map_uniform.exact_bounds = exact as u32;
+        self
+    }
+
+    /// Snap the rendered map to the screen pixel grid, see [`Map::set_pixel_snap`].
+    pub fn with_pixel_snap(mut self) -> Self {
+        self.map.set_pixel_snap(true);
+        self
+    }
+
+    /// Number of tile columns the atlas is expected to have, so tiles can be given by
+    /// (column, row) before the atlas is loaded, see [`Map::index_from_colrow`].
+    /// A warning is logged if the loaded atlas has a different number of columns.
+    pub fn with_expected_atlas_columns(mut self, columns: u32) -> Self {
+        self.map.expected_atlas_columns = Some(columns);
+        self
+    }
+
+    /// Bound the queue of [`Map::update_queue`], by default it holds any number of writes.
+    pub fn with_update_queue(mut self, overflow: QueueOverflow) -> Self {
+        self.map.set_update_queue_overflow(overflow);
+        self
+    }
+
+    /// Pre-allocate the buffers reused by large edits for edits of up to `tiles` tiles, so not
+    /// even the first such edit allocates: The stack of [`MapIndexerMut::flood_fill`], the copy
+    /// of the old values of [`MapIndexerMut::step_region`] and the [`Map::update_queue`].
+    /// Without this hint the buffers grow on first use and are kept for later edits.
+    pub fn with_expected_edit_size(mut self, tiles: usize) -> Self {
+        self.map.edit_scratch.reserve(tiles);
+        self.map.update_queue().reserve(tiles);
+        self
+    }
+
+    /// Write motion vectors when rendering this map, see [`Map::set_motion_vectors`].
+    #[cfg(feature = "motion-vectors")]
+    pub fn with_motion_vectors(mut self) -> Self {
+        self.map.set_motion_vectors(true);
+        self
+    }
+
+    /// Replace atlas texels within `tolerance` (distance in linear RGB) of `key_colors[i]`
+    /// by color `i` of the map's palette (see [`Map::set_palette`]), keeping their luminance.
+    /// Use eg. for team colors: paint units in the atlas in a few key colors and give each
+    /// map (layer) its own palette.
+    /// At most [`MAX_PALETTE_COLORS`] key colors are supported.
+    ///
+    /// Maps without key colors don't pay for this feature.
+    pub fn with_color_key_palette(mut self, key_colors: Vec<Color>, tolerance: f32) -> Self {
+        self.map.set_color_keys(&key_colors, tolerance);
+        self
+    }
+
+    /// Store an owner id in the `bits` bits above bit `shift` of each tile value
+    /// (the bits below are the atlas index).
+    /// Texels matching any key color of a tile (see [`Self::with_color_key_palette`])
+    /// are then replaced by the palette color of the tile's owner,
+    /// so a single map can show units of several teams.
+    /// The owner can not use the tile flag bits (see [`crate::tile_flags`]), so at most
+    /// `29 - shift` bits are used (`27 - shift` with [`Self::with_additional_atlas`]).
+    pub fn with_palette_owner_bits(mut self, shift: u32, bits: u32) -> Self {
+        self.map.set_palette_owner_bits(shift, bits);
+        self
+    }
+
     /// Build the map component.
+    /// Errors (see [`Self::try_build`]) are logged, maps with an atlas that does not fit the
+    /// tile size and padding are not loaded.
     pub fn build(self) -> Map<C> {
         self.build_and_initialize(|_| {})
     }
 
+    /// Build the map component, checking its settings and (if it is in `images` already) its
+    /// atlas first, instead of logging errors once the map is loaded.
+    ///
+    /// ```
+    /// # use bevy::{math::{uvec2, vec2}, prelude::*};
+    /// # use bevy_fast_tilemap::prelude::*;
+    /// let images = Assets::<Image>::default();
+    /// let result = Map::<NoCustomization>::builder(uvec2(0, 32), default(), vec2(16.0, 16.0))
+    ///     .try_build(&images);
+    /// assert_eq!(result.unwrap_err(), MapError::ZeroMapSize { map_size: uvec2(0, 32) });
+    /// ```
+    pub fn try_build(self, images: &Assets<Image>) -> Result<Map<C>, MapError> {
+        self.map.validate(images)?;
+        Ok(self.build())
+    }
+
     /// Build the map component and immediately initialize the map
     /// data with the given initializer callback.
     /// The callback will receive a mutable reference to a `MapIndexer`.
+    ///
+    /// ```
+    /// # use bevy::{math::{uvec2, vec2}, prelude::*};
+    /// # use bevy_fast_tilemap::prelude::*;
+    /// let map: Map = Map::builder(uvec2(64, 32), Handle::default(), vec2(16.0, 16.0))
+    ///     .build_and_initialize(|m| {
+    ///         for p in m.positions() {
+    ///             m.set_uvec(p, (p.x + p.y) % 4);
+    ///         }
+    ///     });
+    /// ```
     pub fn build_and_initialize<F>(mut self, initializer: F) -> Map<C>
     where
         F: FnOnce(&mut MapIndexerMut<C>),
     {
-        self.map.map_texture.resize(
-            (self.map.map_size().x * self.map.map_size().y) as usize,
-            0u32,
-        );
+        if let Err(e) = self.map.validate_settings() {
+            error!("{e}");
+        }
+        // Uniform maps (eg. decoded by `Self::from_bytes`) only hold a single tile
+        if self.map.uniform_tile().is_none() {
+            self.map.map_texture.resize(
+                (self.map.map_size().x * self.map.map_size().y) as usize,
+                0u32,
+            );
+        }
 
         initializer(&mut MapIndexerMut::<C> { map: &mut self.map });
 
-        self.map.update_inverse_projection();
-        self.map.map_uniform.update_world_size();
+        // Uniformly filled maps don't need their data on the GPU
+        if let Some(&first) = self.map.map_texture.first() {
+            if self.map.map_texture.iter().all(|&tile| tile == first) {
+                self.map.set_uniform_tile(Some(first));
+            }
+        }
+
+        if let Some(secondary) = self.secondary_projection {
+            let primary = TileProjection {
+                projection: self.map.map_uniform.projection,
+                tile_anchor_point: self.map.map_uniform.tile_anchor_point,
+                stagger: self.map.map_uniform.stagger(),
+            };
+            let orientation = |p: Mat3| {
+                Mat2::from_cols(p.x_axis.xy(), p.y_axis.xy())
+                    .determinant()
+                    .signum()
+            };
+            if orientation(primary.projection) != orientation(secondary.projection) {
+                warn!("Primary and secondary map projection have different orientation");
+            }
+            self.map.projections = Some([primary, secondary]);
+        }
+
+        if let Some((viewport_size, tiles)) = self.viewport_fit {
+            if let Some(size) = self.map.viewport_fit_tile_size(viewport_size, tiles) {
+                self.map.map_uniform.world_tile_size = size;
+            }
+        }
+
+        self.map.update_projection();
 
         self.map
     } // fn build_and_initialize
@@ -166,15 +521,91 @@ impl<C: Customization> MapBuilder<C> {
     where
         F: FnMut(UVec2) -> u32,
     {
-        let sx = self.map.map_size().x;
-        let sy = self.map.map_size().y;
-
         self.build_and_initialize(|m: &mut MapIndexerMut<C>| {
-            for y in 0..sy {
-                for x in 0..sx {
-                    m.set(x, y, initializer(uvec2(x, y)));
-                }
+            for p in m.positions() {
+                m.set_uvec(p, initializer(p));
             }
         })
     } // build_and_set()
 }
+
+/// Why a map can not be rendered, see [`MapBuilder::try_build`] and [`Map::replace_texture`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapError {
+    /// The map has no tiles along at least one axis.
+    ZeroMapSize { map_size: UVec2 },
+    /// The size of the tiles in the atlas (see [`MapBuilder::with_atlas_tile_size_factor`])
+    /// is zero along at least one axis.
+    ZeroTileSize { tile_size: Vec2 },
+    /// The tiles are larger than the atlas minus its outer padding (`available`).
+    TileSizeExceedsAtlas { tile_size: Vec2, available: Vec2 },
+    /// Atlas size, tile size and padding don't add up to a whole number of tiles (`n_tiles`).
+    /// With `outer_padding_bottomright` as bottom right padding (see
+    /// [`MapBuilder::with_padding`]) they would, for the whole tiles of `n_tiles` only.
+    NonIntegralTileCount {
+        n_tiles: Vec2,
+        outer_padding_bottomright: Vec2,
+    },
+    /// The number of tiles does not match the map size.
+    WrongTileCount { expected: usize, actual: usize },
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroMapSize { map_size } => write!(f, "map size {map_size} has no tiles"),
+            Self::ZeroTileSize { tile_size } => write!(f, "tile size {tile_size} is zero"),
+            Self::TileSizeExceedsAtlas {
+                tile_size,
+                available,
+            } => write!(
+                f,
+                "tile size {tile_size} exceeds atlas size {available} (minus padding)"
+            ),
+            Self::NonIntegralTileCount {
+                n_tiles,
+                outer_padding_bottomright,
+            } => write!(
+                f,
+                "expected an integral number of tiles in the atlas, but computes to be {n_tiles} \
+                 (a bottom right padding of {outer_padding_bottomright} would fit the whole tiles)"
+            ),
+            Self::WrongTileCount { expected, actual } => {
+                write!(f, "map needs {expected} tiles, got {actual}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
+impl<C: Customization> Map<C> {
+    /// Check the size, tile size and padding of this map against its atlases, see [`MapError`].
+    /// For atlases not in `images` (yet), only the map and tile size are checked.
+    pub fn validate(&self, images: &Assets<Image>) -> Result<(), MapError> {
+        self.validate_settings()?;
+        self.validate_additional_atlases(images)?;
+        match images.get(&self.atlas_texture) {
+            Some(atlas) => self
+                .map_uniform
+                .atlas_tile_count(atlas.size().as_vec2())
+                .map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    /// The checks of [`Self::validate`] that don't need the atlas.
+    fn validate_settings(&self) -> Result<(), MapError> {
+        let uniform = &self.map_uniform;
+        if uniform.map_size.cmpeq(UVec2::ZERO).any() {
+            return Err(MapError::ZeroMapSize {
+                map_size: uniform.map_size,
+            });
+        }
+        let tile_size = uniform.tile_size * uniform.atlas_tile_size_factor as f32;
+        if tile_size.cmple(Vec2::ZERO).any() {
+            return Err(MapError::ZeroTileSize { tile_size });
+        }
+        Ok(())
+    }
+}
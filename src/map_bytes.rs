@@ -0,0 +1,289 @@
+//! Compact binary format of maps, eg. for save games, see [`Map::to_bytes`].
+//!
+//! ```
+//! # use bevy::{math::{uvec2, vec2}, prelude::*};
+//! # use bevy_fast_tilemap::prelude::*;
+//! let map: Map = Map::builder(uvec2(1024, 1024), default(), vec2(16.0, 16.0))
+//!     .build_and_set(|p| if p.y < 512 { 1 } else { 2 });
+//! let bytes = map.to_bytes();
+//! // Runs of identical tiles are stored once
+//! assert!(bytes.len() < 10_000);
+//!
+//! let loaded: Map = MapBuilder::from_bytes(&bytes, default()).unwrap().build();
+//! assert_eq!(loaded.indexer().at(3, 600), 2);
+//! ```
+
+use std::fmt;
+
+use bevy::prelude::*;
+
+use super::{map::Map, map_builder::MapBuilder, plugin::Customization};
+
+/// First bytes of every encoded map.
+const MAGIC: &[u8; 4] = b"FTMP";
+
+/// Version of the format written by [`Map::to_bytes`], increased with every change of the
+/// format. Older versions stay readable.
+pub const MAP_FORMAT_VERSION: u8 = 1;
+
+/// Largest number of tiles (eg. 16384x16384) [`MapBuilder::from_bytes`] accepts, so corrupt data
+/// claiming a huge map size can't make it allocate unbounded memory.
+pub const MAX_DECODED_TILES: u64 = 1 << 28;
+
+/// Why [`MapBuilder::from_bytes`] could not decode a map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapDecodeError {
+    /// The data does not start like an encoded map, ie. it is something else entirely.
+    NotAMap,
+    /// The data was written by a newer format version than [`MAP_FORMAT_VERSION`].
+    UnsupportedVersion { version: u8 },
+    /// The data is truncated or holds invalid values.
+    Corrupt { reason: &'static str },
+}
+
+impl fmt::Display for MapDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAMap => write!(f, "data is not an encoded map"),
+            Self::UnsupportedVersion { version } => write!(
+                f,
+                "map format version {version} is not supported (up to {MAP_FORMAT_VERSION})"
+            ),
+            Self::Corrupt { reason } => write!(f, "encoded map is corrupt: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for MapDecodeError {}
+
+fn corrupt(reason: &'static str) -> MapDecodeError {
+    MapDecodeError::Corrupt { reason }
+}
+
+/// Little endian writer of the format, integers of varying size are LEB128 encoded.
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.0.extend(value.to_le_bytes());
+    }
+
+    fn vec2(&mut self, value: Vec2) {
+        self.f32(value.x);
+        self.f32(value.y);
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn bytes(&mut self, n: usize) -> Result<&[u8], MapDecodeError> {
+        if self.0.len() < n {
+            return Err(corrupt("unexpected end of data"));
+        }
+        let (bytes, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, MapDecodeError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, MapDecodeError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(corrupt("integer too long"))
+    }
+
+    fn u32(&mut self) -> Result<u32, MapDecodeError> {
+        u32::try_from(self.varint()?).map_err(|_| corrupt("integer out of range"))
+    }
+
+    fn f32(&mut self) -> Result<f32, MapDecodeError> {
+        let bytes = self.bytes(4)?;
+        let value = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        match value.is_finite() {
+            true => Ok(value),
+            false => Err(corrupt("non-finite number")),
+        }
+    }
+
+    fn vec2(&mut self) -> Result<Vec2, MapDecodeError> {
+        Ok(Vec2::new(self.f32()?, self.f32()?))
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Encode the tiles of this map along with its size, tile size, padding, projection and
+    /// overhang settings (including [`MapBuilder::with_tile_overhang_flags`]) for
+    /// [`MapBuilder::from_bytes`].
+    ///
+    /// Runs of identical tiles are stored once, so mostly uniform maps only take a few bytes per
+    /// run. Not covered are the atlas, the user data, upper layers (see
+    /// [`MapBuilder::with_layers`]) and anything else set when building the map.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let uniform = &self.map_uniform;
+        let mut w = Writer(MAGIC.to_vec());
+        w.u8(MAP_FORMAT_VERSION);
+
+        w.varint(uniform.map_size.x.into());
+        w.varint(uniform.map_size.y.into());
+        w.vec2(uniform.tile_size);
+        w.varint(uniform.atlas_tile_size_factor.max(1) as u64);
+        w.vec2(uniform.inner_padding);
+        w.vec2(uniform.outer_padding_topleft);
+        w.vec2(uniform.outer_padding_bottomright);
+
+        for value in uniform.projection.to_cols_array() {
+            w.f32(value);
+        }
+        w.vec2(uniform.tile_anchor_point);
+        w.u8(uniform.stagger as u8);
+
+        let overhangs = [
+            self.dominance_overhangs,
+            self.perspective_underhangs,
+            self.perspective_overhangs,
+        ];
+        let mut modes = 0;
+        for (i, enabled) in overhangs.into_iter().enumerate() {
+            modes |= (enabled as u8) << i;
+        }
+        w.u8(modes);
+        w.varint(uniform.overhang_levels.into());
+        w.varint(self.tile_overhang_flags.len() as u64);
+        for &entry in &self.tile_overhang_flags {
+            w.varint(entry.into());
+        }
+
+        // Runs of (length, tile value)
+        let n = uniform.map_size.x as u64 * uniform.map_size.y as u64;
+        match self.uniform_tile() {
+            Some(tile) => {
+                w.varint(n);
+                w.varint(tile.into());
+            }
+            None => {
+                let mut tiles = self.map_texture.iter().peekable();
+                while let Some(&tile) = tiles.next() {
+                    let mut length = 1;
+                    while tiles.next_if_eq(&&tile).is_some() {
+                        length += 1;
+                    }
+                    w.varint(length);
+                    w.varint(tile.into());
+                }
+            }
+        }
+        w.0
+    }
+}
+
+impl<C: Customization> MapBuilder<C> {
+    /// Builder of a map encoded by [`Map::to_bytes`], using `atlas` as its atlas.
+    /// The builder holds the decoded tiles, so build it with [`Self::build`] (or
+    /// [`Self::build_and_initialize`] to change them).
+    /// Maps of more than [`MAX_DECODED_TILES`] tiles are rejected as corrupt.
+    pub fn from_bytes(bytes: &[u8], atlas: Handle<Image>) -> Result<Self, MapDecodeError> {
+        let mut r = Reader(bytes);
+        if r.bytes(MAGIC.len()).ok() != Some(MAGIC.as_slice()) {
+            return Err(MapDecodeError::NotAMap);
+        }
+        let version = r.u8()?;
+        if version == 0 || version > MAP_FORMAT_VERSION {
+            return Err(MapDecodeError::UnsupportedVersion { version });
+        }
+
+        let map_size = UVec2::new(r.u32()?, r.u32()?);
+        let n = map_size.x as u64 * map_size.y as u64;
+        if n == 0 || n > MAX_DECODED_TILES {
+            return Err(corrupt("map size"));
+        }
+        let tile_size = r.vec2()?;
+        let factor = i32::try_from(r.u32()?).map_err(|_| corrupt("atlas tile size factor"))?;
+        let inner = r.vec2()?;
+        let topleft = r.vec2()?;
+        let bottomright = r.vec2()?;
+
+        let mut projection = [0.0; 9];
+        for value in projection.iter_mut() {
+            *value = r.f32()?;
+        }
+        let tile_anchor_point = r.vec2()?;
+        let stagger = r.u8()?;
+        if stagger > 4 {
+            return Err(corrupt("unknown stagger"));
+        }
+
+        let modes = r.u8()?;
+        let overhang_levels = r.u32()?;
+        let n_flags = r.u32()? as usize;
+        // Every entry takes at least one byte
+        if n_flags > r.0.len() {
+            return Err(corrupt("unexpected end of data"));
+        }
+        let mut tile_overhang_flags = Vec::with_capacity(n_flags.max(1));
+        for _ in 0..n_flags {
+            tile_overhang_flags.push(r.u32()?);
+        }
+        if tile_overhang_flags.is_empty() {
+            tile_overhang_flags.push(0);
+        }
+
+        // Decode all runs before expanding them, so invalid data fails without allocating
+        let mut runs = Vec::new();
+        let mut decoded = 0;
+        while decoded < n {
+            let length = r.varint()?;
+            let tile = r.u32()?;
+            if length == 0 || decoded + length > n {
+                return Err(corrupt("tile runs don't match the map size"));
+            }
+            decoded += length;
+            runs.push((length as usize, tile));
+        }
+        if !r.0.is_empty() {
+            return Err(corrupt("trailing data"));
+        }
+
+        let mut builder = Self::new(map_size, atlas, tile_size)
+            .with_atlas_tile_size_factor(factor)
+            .with_padding(inner, topleft, bottomright)
+            .with_overhangs(modes & 1 != 0, modes & 2 != 0, modes & 4 != 0)
+            .with_overhang_extent_tiles(overhang_levels);
+        let uniform = &mut builder.map.map_uniform;
+        uniform.projection = Mat3::from_cols_array(&projection);
+        uniform.tile_anchor_point = tile_anchor_point;
+        uniform.stagger = stagger.into();
+        builder.map.tile_overhang_flags = tile_overhang_flags;
+        if let [(_, tile)] = runs[..] {
+            builder.map.set_uniform_tile(Some(tile));
+        } else {
+            let mut tiles = Vec::with_capacity(n as usize);
+            for (length, tile) in runs {
+                tiles.resize(tiles.len() + length, tile);
+            }
+            builder.map.map_texture = tiles;
+        }
+        Ok(builder)
+    }
+}
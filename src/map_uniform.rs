@@ -1,10 +1,10 @@
 use bevy::{
-    math::{vec2, vec3, Vec3Swizzles},
+    math::{vec2, DMat3, DVec3, Vec3Swizzles},
     prelude::*,
     render::render_resource::{AsBindGroup, ShaderType},
 };
 
-use super::prelude::*;
+use super::{additional_atlases::AtlasLayout, prelude::*};
 
 #[derive(ShaderType, Clone, Debug, Reflect, AsBindGroup)]
 pub struct MapUniform {
@@ -16,7 +16,7 @@ pub struct MapUniform {
     /// Will be derived from the tile atlas texture.
     pub(crate) atlas_size: Vec2,
 
-    /// Size of each tile, in pixels.
+    /// Size of each tile in the atlas, in pixels.
     pub(crate) tile_size: Vec2,
 
     pub(crate) atlas_tile_size_factor: i32,
@@ -34,6 +34,8 @@ pub struct MapUniform {
     /// fractional 2d map index -> projected 2d "map index"
     pub(crate) projection: Mat3,
 
+    /// Transform last given to `Map::apply_transform`, for the transform-less conversion methods.
+    /// Not used for rendering, so maps can be shared by entities with different transforms.
     pub(crate) global_transform_matrix: Mat3,
     pub(crate) global_transform_translation: Vec3,
 
@@ -60,6 +62,111 @@ pub struct MapUniform {
     /// (derived) global world pos -> fractional 2d map index
     pub(crate) global_inverse_transform_matrix: Mat3,
     pub(crate) global_inverse_transform_translation: Vec3,
+
+    /// Only map positions in `[clip_min, clip_max]` are rendered.
+    pub(crate) clip_min: Vec2,
+    pub(crate) clip_max: Vec2,
+
+    /// Width (in map coordinates) of the fade-out at the edges of the clip rectangle.
+    pub(crate) clip_feather: f32,
+
+    /// If `has_uniform_tile` is non-zero, every tile of the map is `uniform_tile`
+    /// and the map texture is not used.
+    pub(crate) has_uniform_tile: u32,
+    pub(crate) uniform_tile: u32,
+
+    /// Number of tiles a tile graphic may extend upwards beyond its own cell
+    /// in perspective overhang mode.
+    pub(crate) overhang_levels: u32,
+
+    /// (derived) Map offset of the neighbor tile directly below (in world space).
+    pub(crate) overhang_step: IVec2,
+
+    /// Free parameters for custom shader code, available as `map.custom_params[n]`.
+    pub(crate) custom_params: [Vec4; 4],
+
+    /// Number of used entries in `palette_keys` and `palette_colors`, see [`Map::set_palette`].
+    pub(crate) n_palette_keys: u32,
+    pub(crate) n_palette_colors: u32,
+    /// Maximum (linear RGB) distance of a texel to a key color for it to be replaced.
+    pub(crate) palette_tolerance: f32,
+    /// In owner mode, tile values hold the owner id in `palette_owner_mask << palette_owner_shift`
+    /// and the atlas index in the bits below.
+    pub(crate) palette_owner_shift: u32,
+    pub(crate) palette_owner_mask: u32,
+    /// Key and replacement colors (linear RGBA).
+    pub(crate) palette_keys: [Vec4; MAX_PALETTE_COLORS],
+    pub(crate) palette_colors: [Vec4; MAX_PALETTE_COLORS],
+
+    /// Color (linear RGBA) of fragments outside of the map within its bounding box.
+    pub(crate) outside_color: Vec4,
+
+    /// Size of each tile in world units (local coordinates of the map entity),
+    /// usually the same as `tile_size`.
+    pub(crate) world_tile_size: Vec2,
+
+    /// If non-zero, the rendered map is shifted so its tile grid starts on a whole screen pixel,
+    /// see [`Map::set_pixel_snap`] and [`FastTileMapGlobals::pixel_snap`].
+    pub(crate) pixel_snap: u32,
+
+    /// Position and size (in tiles) of the tile preview, see [`Map::set_preview`].
+    /// No preview is rendered while the size is zero.
+    pub(crate) preview_origin: IVec2,
+    pub(crate) preview_size: UVec2,
+    pub(crate) preview_opacity: f32,
+
+    /// Width (in tiles) of the fade towards `edge_fade_color` at the map edges, per axis,
+    /// see [`Map::set_edge_fade`].
+    pub(crate) edge_fade: Vec2,
+    /// Linear RGBA
+    pub(crate) edge_fade_color: Vec4,
+
+    /// Factor for the color of emissive tiles, see [`Map::set_emissive_strength`].
+    pub(crate) emissive_strength: f32,
+
+    /// [`TileStagger`] of the projection, as `u32`.
+    pub(crate) stagger: u32,
+
+    /// Tiles (`max` exclusive) written before the last upload, tinted with `dirty_region_color`
+    /// weighted by `dirty_region_highlight`, see [`FastTileMapDebug`].
+    pub(crate) dirty_region_min: UVec2,
+    pub(crate) dirty_region_max: UVec2,
+    pub(crate) dirty_region_highlight: f32,
+    /// Linear RGBA
+    pub(crate) dirty_region_color: Vec4,
+
+    /// Non-zero if the map content repeats, see [`MapBuilder::with_repeat_content`].
+    pub(crate) repeat_content: u32,
+
+    /// Blend factor towards the secondary atlas, see [`Map::set_atlas_blend`].
+    pub(crate) atlas_blend: f32,
+
+    /// Color jitter of the tiles in the jitter bitset, see [`Map::set_color_jitter`]
+    /// and [`Map::set_color_jitter_hue`].
+    pub(crate) color_jitter: f32,
+    pub(crate) color_jitter_hue: f32,
+
+    /// Factor (linear RGBA) for the final color of the map, see [`Map::set_color_filter`].
+    pub(crate) color_filter: Vec4,
+
+    /// Animation state of the shader is `animation_speed * state + animation_offset` for the
+    /// animation state of the mesh, see [`Map::set_animation_speed`].
+    pub(crate) animation_speed: f32,
+    pub(crate) animation_offset: f32,
+
+    /// Number of tile layers (at least 1) and bitset of the hidden ones,
+    /// see [`Map::set_layer_visible`].
+    pub(crate) n_layers: u32,
+    pub(crate) hidden_layers: u32,
+
+    /// Number of atlases (at least 1) and the layouts of the additional ones,
+    /// see [`MapBuilder::with_additional_atlas`].
+    pub(crate) n_atlases: u32,
+    pub(crate) atlases: [AtlasLayout; MAX_ATLASES as usize - 1],
+
+    /// If non-zero, the mesh covers exactly the map and fragments at its edges render the edge
+    /// tiles, see [`MapBuilder::with_exact_bounds`].
+    pub(crate) exact_bounds: u32,
 }
 
 impl Default for MapUniform {
@@ -82,6 +189,47 @@ impl Default for MapUniform {
             inverse_projection: default(),
             global_inverse_transform_matrix: default(),
             global_inverse_transform_translation: default(),
+            clip_min: Vec2::splat(f32::MIN),
+            clip_max: Vec2::splat(f32::MAX),
+            clip_feather: 0.0,
+            has_uniform_tile: 0,
+            uniform_tile: 0,
+            overhang_levels: 1,
+            overhang_step: IVec2::ZERO,
+            custom_params: [Vec4::ZERO; 4],
+            n_palette_keys: 0,
+            n_palette_colors: 0,
+            palette_tolerance: 0.0,
+            palette_owner_shift: 0,
+            palette_owner_mask: 0,
+            palette_keys: [Vec4::ZERO; MAX_PALETTE_COLORS],
+            palette_colors: [Vec4::ZERO; MAX_PALETTE_COLORS],
+            outside_color: Vec4::ZERO,
+            world_tile_size: default(),
+            pixel_snap: 0,
+            preview_origin: IVec2::ZERO,
+            preview_size: UVec2::ZERO,
+            preview_opacity: 0.0,
+            edge_fade: Vec2::ZERO,
+            edge_fade_color: Vec4::ZERO,
+            emissive_strength: 1.0,
+            stagger: TileStagger::None as u32,
+            dirty_region_min: UVec2::ZERO,
+            dirty_region_max: UVec2::ZERO,
+            dirty_region_highlight: 0.0,
+            dirty_region_color: Vec4::ZERO,
+            repeat_content: 0,
+            atlas_blend: 0.0,
+            color_jitter: 0.0,
+            color_jitter_hue: 0.0,
+            color_filter: Vec4::ONE,
+            animation_speed: 1.0,
+            animation_offset: 0.0,
+            n_layers: 1,
+            hidden_layers: 0,
+            n_atlases: 1,
+            atlases: default(),
+            exact_bounds: 0,
         }
     }
 }
@@ -91,31 +239,170 @@ impl MapUniform {
         self.map_size
     }
 
+    /// Atlas index of the given tile value, ie. without the flags, without the owner bits in
+    /// palette owner mode and without the atlas selector of maps with additional atlases.
+    pub(crate) fn atlas_index(&self, tile: u32) -> u32 {
+        let tile = match self.n_atlases {
+            0 | 1 => tile & TILE_INDEX_MASK,
+            _ => tile & TILE_INDEX_MASK & !TILE_ATLAS_MASK,
+        };
+        if self.palette_owner_mask == 0 {
+            tile
+        } else {
+            tile & ((1 << self.palette_owner_shift) - 1)
+        }
+    }
+
     pub(crate) fn world_size(&self) -> Vec2 {
         self.world_size
     }
 
+    pub(crate) fn stagger(&self) -> TileStagger {
+        match self.stagger {
+            1 => TileStagger::HexRows,
+            2 => TileStagger::HexColumns,
+            3 => TileStagger::HexEvenRows,
+            4 => TileStagger::HexEvenColumns,
+            _ => TileStagger::None,
+        }
+    }
+
     pub(crate) fn map_to_local(&self, map_position: Vec3) -> Vec3 {
-        (self.projection * map_position) * self.tile_size.extend(1.0)
-            + self.world_offset.extend(0.0)
+        let grid = self.map_to_grid(map_position.xy()).extend(map_position.z);
+        (self.projection * grid) * self.world_tile_size.extend(1.0) + self.world_offset.extend(0.0)
+    }
+
+    /// Position in the unstaggered grid of tile rectangles of `map_position`,
+    /// the same for maps without [`TileStagger`].
+    pub(crate) fn map_to_grid(&self, map_position: Vec2) -> Vec2 {
+        let stagger = self.stagger();
+        if stagger == TileStagger::None {
+            return map_position;
+        }
+        let tile = map_position.floor();
+        let offset = map_position - tile - Vec2::splat(0.5);
+        stagger_center(stagger, tile) + self.inverse_projection * vec2(offset.x, -offset.y)
+    }
+
+    /// Map position of the tile containing the unstaggered grid position `grid`,
+    /// see [`Self::map_to_grid`].
+    fn grid_to_map(&self, grid: Vec2) -> Vec2 {
+        let stagger = self.stagger();
+        if stagger == TileStagger::None {
+            return grid;
+        }
+        let (tile, center) = stagger_tile(stagger, grid);
+        let offset = (self.projection * (grid - center).extend(0.0)).xy();
+        tile + Vec2::splat(0.5) + vec2(offset.x, -offset.y)
     }
 
     pub(crate) fn map_to_world(&self, map_position: Vec3) -> Vec3 {
-        let local = self.map_to_local(map_position);
-        self.global_transform_matrix * local + self.global_inverse_transform_translation
+        self.map_to_world_by(
+            self.global_transform_matrix.as_dmat3(),
+            self.global_transform_translation.as_dvec3(),
+            map_position,
+        )
+    }
+
+    /// Like [`Self::map_to_world`], but for a map entity with the given transform
+    /// instead of the one last applied with [`Self::apply_transform`].
+    pub(crate) fn map_to_world_with(
+        &self,
+        transform: &GlobalTransform,
+        map_position: Vec3,
+    ) -> Vec3 {
+        let affine = transform.affine();
+        self.map_to_world_by(
+            Mat3::from(affine.matrix3).as_dmat3(),
+            Vec3::from(affine.translation).as_dvec3(),
+            map_position,
+        )
+    }
+
+    fn map_to_world_by(&self, matrix: DMat3, translation: DVec3, map_position: Vec3) -> Vec3 {
+        // Computed relative to the map in f64, so maps far away from the origin
+        // still get precise results.
+        let local = self.map_to_local(map_position).as_dvec3();
+        (matrix * local + translation).as_vec3()
     }
 
     /// As of now, this will ignore `world`s z coordinate
     /// and always project to z=0 on the map.
     /// This behavior might change in the future
     pub(crate) fn local_to_map(&self, local: Vec3) -> Vec3 {
-        (self.inverse_projection * ((local.xy() - self.world_offset) / self.tile_size)).extend(0.0)
+        self.grid_to_map(self.local_to_grid(local).xy()).extend(0.0)
+    }
+
+    /// Like [`Self::local_to_map`], but without [`TileStagger`], ie. affine.
+    /// This is what the mesh interpolates, the shader resolves the staggering per fragment.
+    pub(crate) fn local_to_grid(&self, local: Vec3) -> Vec3 {
+        (self.inverse_projection * ((local.xy() - self.world_offset) / self.world_tile_size))
+            .extend(0.0)
+    }
+
+    /// Like [`Self::world_to_map`] for a position in the xy plane.
+    /// NaN (with a warning) if the map is seen edge-on from +z, as there is no single map
+    /// position then.
+    pub(crate) fn world_to_map_2d(&self, world: Vec2) -> Vec2 {
+        if !faces_xy_plane(self.global_transform_matrix.as_dmat3()) {
+            warn_once!("Map is edge-on in the xy plane, world_to_map has no result");
+            return Vec2::NAN;
+        }
+        self.world_to_map(world.extend(0.0)).xy()
+    }
+
+    /// Like [`Self::world_to_map_2d`], but for a map entity with the given transform.
+    pub(crate) fn world_to_map_2d_with(&self, transform: &GlobalTransform, world: Vec2) -> Vec2 {
+        if !faces_xy_plane(Mat3::from(transform.affine().matrix3).as_dmat3()) {
+            warn_once!("Map is edge-on in the xy plane, world_to_map_with has no result");
+            return Vec2::NAN;
+        }
+        self.world_to_map_with(transform, world.extend(0.0)).xy()
     }
 
     pub(crate) fn world_to_map(&self, world: Vec3) -> Vec3 {
-        let local = self.global_inverse_transform_matrix * world
-            + self.global_inverse_transform_translation;
-        self.local_to_map(local)
+        self.world_to_map_by(
+            self.global_inverse_transform_matrix.as_dmat3(),
+            self.global_transform_translation.as_dvec3(),
+            world,
+        )
+    }
+
+    /// Like [`Self::world_to_map`], but for a map entity with the given transform
+    /// instead of the one last applied with [`Self::apply_transform`].
+    pub(crate) fn world_to_map_with(&self, transform: &GlobalTransform, world: Vec3) -> Vec3 {
+        let affine = transform.affine();
+        self.world_to_map_by(
+            plane_inverse(Mat3::from(affine.matrix3).as_dmat3()),
+            Vec3::from(affine.translation).as_dvec3(),
+            world,
+        )
+    }
+
+    fn world_to_map_by(&self, inverse_matrix: DMat3, translation: DVec3, world: Vec3) -> Vec3 {
+        // Subtract the translation before applying the inverse matrix (in f64),
+        // instead of using `global_inverse_transform_translation`, which for maps far away
+        // from the origin would be a big number with comparatively low precision.
+        let relative = world.as_dvec3() - translation;
+        let local = inverse_matrix * relative;
+        self.local_to_map(local.as_vec3())
+    }
+
+    /// Bounding rectangle (low, high) of the map in local coordinates
+    /// when projected with `projection`, not taking `world_offset` into account.
+    fn projected_bounds(&self, projection: Mat3) -> (Vec2, Vec2) {
+        let mut low = Vec2::ZERO;
+        let mut high = low;
+        for corner in [
+            vec2(self.map_size().x as f32, 0.0),
+            vec2(0.0, self.map_size().y as f32),
+            vec2(self.map_size().x as f32, self.map_size().y as f32),
+        ] {
+            let pos = (projection * corner.extend(0.0)).xy() * self.world_tile_size;
+            low = low.min(pos);
+            high = high.max(pos);
+        }
+        (low, high)
     }
 
     pub(crate) fn update_world_size(&mut self) {
@@ -128,25 +415,18 @@ impl MapUniform {
         // works and is simple enough:
         // 1. save coordinates for all 4 corners
         // 2. take maximum x- and y distances
-        let mut low = self.map_to_local(vec3(0.0, 0.0, 0.0)).xy();
-        let mut high = low;
-        for corner in [
-            vec2(self.map_size().x as f32, 0.0),
-            vec2(0.0, self.map_size().y as f32),
-            vec2(self.map_size().x as f32, self.map_size().y as f32),
-        ] {
-            let pos = self.map_to_local(corner.extend(0.0)).xy();
-            low = low.min(pos);
-            high = high.max(pos);
-        }
+        let (low, high) = self.projected_bounds(self.projection);
         self.world_size = high - low;
 
         // Leave a full tile space on each side so overhangs are not visually cut off
-        let padding = 2.0 * self.tile_size;
+        let padding = 2.0 * self.world_tile_size;
 
         // Increase world size by one tile total such that overhangs are fully visible
         self.world_size += padding;
 
+        // Additional space at the top for tiles extending further upwards
+        self.world_size.y += self.overhang_levels.saturating_sub(1) as f32 * self.world_tile_size.y;
+
         // World offset
         //
         // `map.projection` keeps the map coordinate (0, 0) at the world coordinate (0, 0).
@@ -156,6 +436,31 @@ impl MapUniform {
         self.world_offset = vec2(-0.5, -0.5) * self.world_size - low + padding / 2.0;
     }
 
+    /// Set `world_size` and `world_offset` such that the mesh exactly covers the map plus
+    /// `low` tiles before its first and `high` tiles after its last column and row (in map
+    /// coordinates).
+    pub(crate) fn update_world_size_with_margins(&mut self, low: Vec2, high: Vec2) {
+        let mut min = Vec2::INFINITY;
+        let mut max = Vec2::NEG_INFINITY;
+        let end = self.map_size().as_vec2() + high;
+        for corner in [-low, vec2(end.x, -low.y), vec2(-low.x, end.y), end] {
+            let pos = (self.projection * corner.extend(0.0)).xy() * self.world_tile_size;
+            min = min.min(pos);
+            max = max.max(pos);
+        }
+        self.world_size = max - min;
+        self.world_offset = -0.5 * self.world_size - min;
+    }
+
+    /// Grow `world_size` such that the map would also fit when projected with `projection`.
+    /// `world_offset` stays unchanged, ie. the map stays centered.
+    pub(crate) fn expand_world_size(&mut self, projection: Mat3) {
+        let (low, high) = self.projected_bounds(projection);
+        let mut padding = 2.0 * self.world_tile_size;
+        padding.y += self.overhang_levels.saturating_sub(1) as f32 * self.world_tile_size.y;
+        self.world_size = self.world_size.max(high - low + padding);
+    }
+
     /// Return true iff this update made the uniform ready
     /// (ie. it was not ready before and is ready now).
     pub(crate) fn update_atlas_size(&mut self, atlas_size: Vec2) -> bool {
@@ -168,30 +473,153 @@ impl MapUniform {
         true
     }
 
-    pub(crate) fn _apply_transform(&mut self, transform: GlobalTransform) {
-        let affine = transform.compute_transform().compute_affine();
+    /// Update the transform of the entity holding this map (used for converting from and to
+    /// world coordinates).
+    /// Inverses are computed in f64, see [`plane_inverse`] for transforms without z extent.
+    pub(crate) fn apply_transform(&mut self, transform: &GlobalTransform) {
+        let affine = transform.affine();
         self.global_transform_matrix = affine.matrix3.into();
         self.global_transform_translation = affine.translation.into();
 
-        let inverse = affine.inverse();
-        self.global_inverse_transform_matrix = inverse.matrix3.into();
-        self.global_inverse_transform_translation = inverse.translation.into();
+        let inverse = plane_inverse(Mat3::from(affine.matrix3).as_dmat3());
+        self.global_inverse_transform_matrix = inverse.as_mat3();
+        self.global_inverse_transform_translation =
+            (-(inverse * Vec3::from(affine.translation).as_dvec3())).as_vec3();
+    }
+
+    /// Whether the applied transform turns the map upside down in the world, ie. map tiles
+    /// further up in local space are further down in the world.
+    pub(crate) fn flips_depth(&self) -> bool {
+        self.global_transform_matrix.y_axis.y < 0.0
+    }
+
+    /// Whether `transform` is the one last given to [`Self::apply_transform`].
+    pub(crate) fn has_transform(&self, transform: &GlobalTransform) -> bool {
+        let affine = transform.affine();
+        self.global_transform_matrix == Mat3::from(affine.matrix3)
+            && self.global_transform_translation == Vec3::from(affine.translation)
     }
 
     fn update_n_tiles(&mut self) {
-        let inner = self.atlas_size - self.outer_padding_topleft - self.outer_padding_bottomright;
-        let n_tiles = (inner + self.inner_padding)
-            / (self.inner_padding + self.tile_size * self.atlas_tile_size_factor as f32);
+        self.n_tiles = match self.atlas_tile_count(self.atlas_size) {
+            Ok(n_tiles) => n_tiles,
+            Err(e) => {
+                error!("{e}");
+                // `round()` as a value of eg. 0.999 should still count as one tile
+                let n_tiles = self.fractional_tile_count(self.atlas_size).round();
+                n_tiles.max(Vec2::ONE).as_uvec2()
+            }
+        };
+    }
 
+    /// Number of tiles along each axis of an atlas of size `atlas_size` with the tile size and
+    /// padding of this uniform, not necessarily integral.
+    fn fractional_tile_count(&self, atlas_size: Vec2) -> Vec2 {
+        let inner = atlas_size - self.outer_padding_topleft - self.outer_padding_bottomright;
+        let atlas_tile_size = self.tile_size * self.atlas_tile_size_factor as f32;
+
+        // Single tile atlas (in one or both dimensions): Avoid float rounding issues
+        // and accept any inner padding.
+        let single = inner.cmpeq(atlas_tile_size);
+        Vec2::select(
+            single,
+            Vec2::ONE,
+            (inner + self.inner_padding) / (self.inner_padding + atlas_tile_size),
+        )
+    }
+
+    /// Number of tiles along each axis of an atlas of size `atlas_size`, see [`MapError`].
+    pub(crate) fn atlas_tile_count(&self, atlas_size: Vec2) -> Result<UVec2, MapError> {
         let eps = 0.01;
-        if (n_tiles.x - n_tiles.x.round()).abs() > eps
-            || (n_tiles.y - n_tiles.y.round()).abs() > eps
-        {
-            panic!(
-                "Expected an integral number of tiles in your atlas, but computes to be {:?}",
-                n_tiles
-            );
+        let atlas_tile_size = self.tile_size * self.atlas_tile_size_factor as f32;
+        if atlas_tile_size.cmple(Vec2::ZERO).any() {
+            return Err(MapError::ZeroTileSize {
+                tile_size: atlas_tile_size,
+            });
+        }
+        let available = atlas_size - self.outer_padding_topleft - self.outer_padding_bottomright;
+        if (atlas_tile_size - available).cmpgt(Vec2::splat(eps)).any() {
+            return Err(MapError::TileSizeExceedsAtlas {
+                tile_size: atlas_tile_size,
+                available,
+            });
+        }
+
+        let n_tiles = self.fractional_tile_count(atlas_size);
+        let fractional = (n_tiles - n_tiles.round()).abs().cmpgt(Vec2::splat(eps));
+        if fractional.any() {
+            // Padding that leaves room for the whole tiles only
+            let whole = Vec2::select(fractional, n_tiles.floor(), n_tiles.round()).max(Vec2::ONE);
+            let used = whole * atlas_tile_size + (whole - 1.0) * self.inner_padding;
+            return Err(MapError::NonIntegralTileCount {
+                n_tiles,
+                outer_padding_bottomright: atlas_size - self.outer_padding_topleft - used,
+            });
+        }
+
+        // `round()` as a value of eg. 0.999 should still count as one tile
+        Ok(n_tiles.round().as_uvec2().max(UVec2::ONE))
+    }
+}
+
+/// Center of `tile` of a staggered map in the unstaggered grid.
+/// Inverse of the transform `matrix` of a map entity for converting world to local positions.
+/// Local z is dropped by the conversion anyway, so if `matrix` has no z extent (eg. from a zero
+/// z scale somewhere in the hierarchy), its z axis is replaced with the normal of the map plane.
+fn plane_inverse(matrix: DMat3) -> DMat3 {
+    let scale = matrix.x_axis.length() * matrix.y_axis.length() * matrix.z_axis.length();
+    if matrix.determinant().abs() > scale * 1e-9 {
+        return matrix.inverse();
+    }
+    let normal = matrix.x_axis.cross(matrix.y_axis).normalize_or_zero();
+    DMat3::from_cols(matrix.x_axis, matrix.y_axis, normal).inverse()
+}
+
+/// Whether the map plane of an entity with transform `matrix` is not edge-on when seen along z,
+/// ie. positions in the xy plane correspond to single map positions.
+fn faces_xy_plane(matrix: DMat3) -> bool {
+    let area = matrix.x_axis.xy().perp_dot(matrix.y_axis.xy());
+    area.abs() > matrix.x_axis.length() * matrix.y_axis.length() * 1e-6
+}
+
+fn stagger_center(stagger: TileStagger, tile: Vec2) -> Vec2 {
+    let shift = |i: f32| {
+        if stagger.is_shifted(i as i32) {
+            0.5
+        } else {
+            0.0
+        }
+    };
+    match stagger {
+        TileStagger::None => tile + Vec2::splat(0.5),
+        _ if stagger.staggers_columns() => tile + vec2(0.5, 0.5 + shift(tile.x)),
+        _ => tile + vec2(0.5 + shift(tile.y), 0.5),
+    }
+}
+
+/// Tile (and its center) of the hexagon containing `grid`,
+/// ie. the tile with the closest center, measured such that all neighbors are equally far.
+fn stagger_tile(stagger: TileStagger, grid: Vec2) -> (Vec2, Vec2) {
+    // Compute staggered columns as staggered rows with swapped axes
+    let swap = |v: Vec2| match stagger.staggers_columns() {
+        true => v.yx(),
+        false => v,
+    };
+    let g = swap(grid);
+    let row_distance = 0.75f32.sqrt();
+    let mut closest = (f32::INFINITY, Vec2::ZERO, Vec2::ZERO);
+    for row in [-1.0, 0.0, 1.0].map(|d| g.y.floor() + d) {
+        let shift = if stagger.is_shifted(row as i32) {
+            0.5
+        } else {
+            0.0
+        };
+        let tile = vec2((g.x - shift).floor(), row);
+        let center = tile + vec2(0.5 + shift, 0.5);
+        let distance = ((g - center) * vec2(1.0, row_distance)).length_squared();
+        if distance < closest.0 {
+            closest = (distance, tile, center);
         }
-        self.n_tiles = n_tiles.as_uvec2();
     }
+    (swap(closest.1), swap(closest.2))
 }
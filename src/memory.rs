@@ -0,0 +1,142 @@
+use std::{iter::Sum, mem::size_of, ops::Add};
+
+use bevy::{prelude::*, utils::HashSet};
+
+use super::{decal::GpuDecal, map::Map, plugin::Customization};
+
+/// Memory used by a map, see [`Map::memory_info`].
+/// All sizes are in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MapMemoryInfo {
+    /// Tile values kept on the CPU (and uploaded whenever the map changes).
+    pub cpu_tile_bytes: u64,
+    /// Other CPU side buffers uploaded with the map, ie. decals, overlay patches, the preview,
+    /// the tile index bitsets, tile colors and animations and the overlay canvas.
+    pub cpu_buffer_bytes: u64,
+    /// Uniform and storage buffers of the map on the GPU, see [`Map::upload_size`].
+    /// plus the overlay canvas image.
+    /// This does not include [`Customization::ExtraBindings`] and the atlases.
+    pub estimated_gpu_bytes: u64,
+    /// Atlas textures on the GPU (primary and secondary), computed from their texture
+    /// descriptors. Atlases that are not loaded count as 0.
+    pub atlas_bytes_estimate: u64,
+}
+
+impl MapMemoryInfo {
+    /// Everything on the CPU.
+    pub fn cpu_bytes(&self) -> u64 {
+        self.cpu_tile_bytes + self.cpu_buffer_bytes
+    }
+
+    /// Everything on the GPU, including the atlases.
+    pub fn gpu_bytes(&self) -> u64 {
+        self.estimated_gpu_bytes + self.atlas_bytes_estimate
+    }
+}
+
+impl Add for MapMemoryInfo {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            cpu_tile_bytes: self.cpu_tile_bytes + rhs.cpu_tile_bytes,
+            cpu_buffer_bytes: self.cpu_buffer_bytes + rhs.cpu_buffer_bytes,
+            estimated_gpu_bytes: self.estimated_gpu_bytes + rhs.estimated_gpu_bytes,
+            atlas_bytes_estimate: self.atlas_bytes_estimate + rhs.atlas_bytes_estimate,
+        }
+    }
+}
+
+impl Sum for MapMemoryInfo {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Memory used by this map, computed from its current configuration.
+    ///
+    /// Atlases are often shared between maps, use [`total_map_memory`] to count them once.
+    pub fn memory_info(&self, images: &Assets<Image>) -> MapMemoryInfo {
+        MapMemoryInfo {
+            atlas_bytes_estimate: self
+                .atlases()
+                .filter_map(|atlas| images.get(atlas))
+                .map(texture_bytes)
+                .sum(),
+            ..self.buffer_memory_info()
+        }
+    }
+
+    /// [`Self::memory_info`] without the atlases.
+    pub(crate) fn buffer_memory_info(&self) -> MapMemoryInfo {
+        let bytes = |len: usize, size: usize| (len * size) as u64;
+        let canvas_bytes = self.overlay_canvas.as_ref().map_or(0, |c| c.bytes() as u64);
+        MapMemoryInfo {
+            cpu_tile_bytes: bytes(self.map_texture.len(), size_of::<u32>()),
+            cpu_buffer_bytes: bytes(self.decal_buffer.len(), size_of::<GpuDecal>())
+                + [
+                    &self.decal_grid,
+                    &self.overhang_exclusions,
+                    &self.preview_buffer,
+                    &self.emissive_tiles,
+                    &self.color_jitter_tiles,
+                    &self.patch_buffer,
+                    &self.tile_colors,
+                    &self.tile_animation_buffer,
+                    &self.layer_tiles,
+                    &self.tile_overhang_flags,
+                    &self.minimap_colors,
+                    &self.autotile_rules,
+                ]
+                .iter()
+                .map(|buffer| bytes(buffer.len(), size_of::<u32>()))
+                .sum::<u64>()
+                + canvas_bytes,
+            estimated_gpu_bytes: self.upload_size() + canvas_bytes,
+            atlas_bytes_estimate: 0,
+        }
+    }
+
+    fn atlases(&self) -> impl Iterator<Item = &Handle<Image>> {
+        std::iter::once(&self.atlas_texture)
+            .chain(self.secondary_atlas.as_ref())
+            .chain(&self.additional_atlases)
+    }
+}
+
+/// Memory used by all `maps`, with each atlas counted once no matter how many maps use it.
+pub fn total_map_memory<C: Customization>(
+    maps: &Assets<Map<C>>,
+    images: &Assets<Image>,
+) -> MapMemoryInfo {
+    let mut atlases = HashSet::new();
+    let mut total: MapMemoryInfo = maps.iter().map(|(_, map)| map.buffer_memory_info()).sum();
+    for (_, map) in maps.iter() {
+        for atlas in map.atlases() {
+            if atlases.insert(atlas.id()) {
+                total.atlas_bytes_estimate += images.get(atlas).map_or(0, texture_bytes);
+            }
+        }
+    }
+    total
+}
+
+/// Size of all mip levels and layers of `image` on the GPU.
+fn texture_bytes(image: &Image) -> u64 {
+    let descriptor = &image.texture_descriptor;
+    let format = descriptor.format;
+    let (block_width, block_height) = format.block_dimensions();
+    let Some(block_size) = format.block_copy_size(None) else {
+        // Eg. combined depth stencil formats, which are no atlases anyway
+        return image.data.len() as u64;
+    };
+    let size = descriptor.size;
+    (0..descriptor.mip_level_count)
+        .map(|level| {
+            let width = (size.width >> level).max(1).div_ceil(block_width) as u64;
+            let height = (size.height >> level).max(1).div_ceil(block_height) as u64;
+            width * height * size.depth_or_array_layers as u64 * block_size as u64
+        })
+        .sum()
+}
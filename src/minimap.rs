@@ -0,0 +1,222 @@
+//! Minimaps: Maps rendering the tiles of another map with one color per tile, eg. for an
+//! overview of the whole map in a corner of the screen.
+//!
+//! A minimap shares the GPU buffer of the tiles of its source map, so edits of the source show
+//! up on the minimap with the next frame without copying any tiles. It has its own uniform,
+//! mesh and transform (and so its own projection and size, see [`Map::minimap_builder`]).
+
+use bevy::{prelude::*, render::render_resource::Buffer};
+
+use super::{
+    map::Map, map_builder::MapBuilder, map_uniform::MapUniform, picking::image_texel,
+    plugin::Customization, tile_colors::pack_tile_color, tile_upload::TileUpload,
+};
+
+/// Link of a minimap to the map whose tiles it renders.
+#[derive(Debug, Clone)]
+pub(crate) struct MinimapLink {
+    pub(crate) source: UntypedAssetId,
+    /// Shares the GPU buffer of the tiles with the source
+    tiles: TileUpload,
+    /// `has_uniform_tile` and `uniform_tile` of the source, the minimap itself is uniform
+    has_uniform_tile: u32,
+    uniform_tile: u32,
+    /// Whether the colors are averaged from the atlas (once it is loaded)
+    average_colors: bool,
+}
+
+impl MinimapLink {
+    fn new<C: Customization>(source: &Map<C>, id: UntypedAssetId) -> Self {
+        Self {
+            source: id,
+            tiles: source.tile_upload.clone(),
+            has_uniform_tile: source.map_uniform.has_uniform_tile,
+            uniform_tile: source.map_uniform.uniform_tile,
+            average_colors: true,
+        }
+    }
+
+    /// Whether the link still matches `source`.
+    fn matches<C: Customization>(&self, source: &Map<C>) -> bool {
+        self.tiles.same_slot(&source.tile_upload)
+            && self.has_uniform_tile == source.map_uniform.has_uniform_tile
+            && self.uniform_tile == source.map_uniform.uniform_tile
+    }
+
+    /// `uniform` with the uniform tile of the source, as rendered.
+    pub(crate) fn uniform(&self, uniform: &MapUniform) -> MapUniform {
+        MapUniform {
+            has_uniform_tile: self.has_uniform_tile,
+            uniform_tile: self.uniform_tile,
+            ..uniform.clone()
+        }
+    }
+
+    /// GPU buffer with the tiles of the source (a map of `map_size` tiles), `None` until the
+    /// source uploaded them.
+    pub(crate) fn tiles(&self, map_size: UVec2) -> Option<Buffer> {
+        let len = match self.has_uniform_tile {
+            0 => (map_size.x * map_size.y) as usize,
+            _ => 1,
+        };
+        self.tiles.shared_buffer(len)
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Builder of a minimap of this map (whose handle is `handle`): A map rendering the tiles
+    /// of this map with one color per tile index (without atlas lookups, overhangs, layers,
+    /// patches, decals or the preview).
+    ///
+    /// The colors are averaged from the atlas once it is loaded, unless they are given with
+    /// [`MapBuilder::with_minimap_colors`]. Tiles of additional atlases use the colors of the
+    /// same index in the atlas given to the builder.
+    /// The minimap has one world unit per tile (see [`MapBuilder::with_world_tile_size`]), exact
+    /// bounds and no projection (see [`MapBuilder::with_projection`]). Its tiles follow this
+    /// map: Edits and resizes show up with the next frame, while the minimap itself has no tiles
+    /// to edit (its indexer, picking and [`Map::render_to_image`] see a map filled with tile 0).
+    ///
+    /// ```
+    /// # use bevy::{math::{uvec2, vec2}, prelude::*};
+    /// # use bevy_fast_tilemap::prelude::*;
+    /// fn startup(mut commands: Commands, mut materials: ResMut<Assets<Map>>) {
+    ///     let map = Map::builder(uvec2(2048, 2048), default(), vec2(16.0, 16.0)).build();
+    ///     let handle = materials.add(map);
+    ///     // 0.1 world units per tile
+    ///     let minimap = materials
+    ///         .get(&handle)
+    ///         .unwrap()
+    ///         .minimap_builder(&handle)
+    ///         .with_world_tile_size(vec2(0.1, 0.1))
+    ///         .build();
+    ///     commands.spawn(MapBundleManaged::new(minimap, materials.as_mut()));
+    /// }
+    /// ```
+    pub fn minimap_builder(&self, handle: &Handle<Map<C>>) -> MapBuilder<C> {
+        let uniform = &self.map_uniform;
+        let atlas = self.atlas_texture.clone();
+        let mut builder = Self::builder(self.map_size(), atlas, uniform.tile_size)
+            .with_padding(
+                uniform.inner_padding,
+                uniform.outer_padding_topleft,
+                uniform.outer_padding_bottomright,
+            )
+            .with_atlas_tile_size_factor(uniform.atlas_tile_size_factor)
+            .with_world_tile_size(Vec2::ONE)
+            .with_exact_bounds(true);
+        // Metadata that is not applied yet sets the padding of the minimap as well
+        builder.map.atlas_metadata = self.atlas_metadata.clone();
+        // Palette owners are not part of the tile index
+        builder.map.map_uniform.palette_owner_shift = uniform.palette_owner_shift;
+        builder.map.map_uniform.palette_owner_mask = uniform.palette_owner_mask;
+        builder.map.minimap = Some(MinimapLink::new(self, handle.id().untyped()));
+        builder
+    }
+
+    /// Whether this map is a minimap, see [`Self::minimap_builder`].
+    pub fn is_minimap(&self) -> bool {
+        self.minimap.is_some()
+    }
+
+    /// The map whose tiles this minimap renders, `None` for maps that are not minimaps.
+    pub fn minimap_source(&self) -> Option<AssetId<Map<C>>> {
+        self.minimap.as_ref().map(|link| link.source.typed())
+    }
+
+    /// Average color of the texels of tile `index` in the atlas (weighted by their alpha),
+    /// `None` if the atlas is not loaded or has no CPU side data in an 8-bit RGBA or BGRA format.
+    pub fn average_tile_color(&self, index: u32, images: &Assets<Image>) -> Option<Color> {
+        let atlas = images.get(&self.atlas_texture)?;
+        let rect = self.atlas_rect(index)?;
+        let mut sum = Vec4::ZERO;
+        let mut n = 0.0;
+        for y in rect.min.y as u32..rect.max.y as u32 {
+            for x in rect.min.x as u32..rect.max.x as u32 {
+                let c = image_texel(atlas, UVec2::new(x, y).as_vec2())?;
+                sum += (c.xyz() * c.w).extend(c.w);
+                n += 1.0;
+            }
+        }
+        if sum.w <= 0.0 {
+            return Some(Color::NONE);
+        }
+        let rgb = sum.xyz() / sum.w;
+        Some(LinearRgba::from_vec4(rgb.extend(sum.w / n)).into())
+    }
+
+    /// Average the minimap colors from the atlas, see [`Self::minimap_builder`].
+    /// Returns false if the atlas is not ready yet.
+    fn average_minimap_colors(&mut self, images: &Assets<Image>) -> bool {
+        let Some(count) = self.atlas_tile_count() else {
+            return false;
+        };
+        let mut colors = Vec::with_capacity(count as usize);
+        for index in 0..count {
+            let Some(color) = self.average_tile_color(index, images) else {
+                return false;
+            };
+            colors.push(pack_tile_color(color));
+        }
+        self.minimap_colors = colors;
+        true
+    }
+}
+
+impl<C: Customization> MapBuilder<C> {
+    /// Colors of the tiles of a minimap by atlas index (see [`Map::minimap_builder`]),
+    /// instead of the average colors of the tiles in the atlas. Indices beyond `colors` are
+    /// transparent. Ignored for maps that are not minimaps.
+    pub fn with_minimap_colors(mut self, colors: Vec<Color>) -> Self {
+        self.map.minimap_colors = colors.into_iter().map(pack_tile_color).collect();
+        // Storage buffers can't be empty
+        if self.map.minimap_colors.is_empty() {
+            self.map.minimap_colors.push(0);
+        }
+        if let Some(link) = self.map.minimap.as_mut() {
+            link.average_colors = false;
+        }
+        self
+    }
+}
+
+/// Keep minimaps in sync with their source maps: Their size and the buffer with the tiles
+/// (which is replaced when the number of tiles changes), and average their colors from the
+/// atlas once it is loaded.
+pub fn sync_minimaps<C: Customization>(
+    mut maps: ResMut<Assets<Map<C>>>,
+    images: Res<Assets<Image>>,
+) {
+    let minimaps: Vec<AssetId<Map<C>>> = maps
+        .iter()
+        .filter(|(_, map)| map.minimap.is_some())
+        .map(|(id, _)| id)
+        .collect();
+    for id in minimaps {
+        let Some(link) = maps.get(id).and_then(|map| map.minimap.clone()) else {
+            continue;
+        };
+        let Some(source) = maps.get(link.source.typed::<Map<C>>()) else {
+            continue;
+        };
+        let size = source.map_size();
+        if !link.matches(source) || maps.get(id).unwrap().map_size() != size {
+            let mut updated = MinimapLink::new(source, link.source);
+            updated.average_colors = link.average_colors;
+            let minimap = maps.get_mut(id).unwrap();
+            minimap.minimap = Some(updated);
+            if minimap.map_size() != size {
+                minimap.map_uniform.map_size = size;
+                minimap.update_projection();
+            }
+        }
+
+        if link.average_colors && maps.get(id).unwrap().atlas_tile_count().is_some() {
+            let minimap = maps.get_mut(id).unwrap();
+            if minimap.average_minimap_colors(&images) {
+                if let Some(link) = minimap.minimap.as_mut() {
+                    link.average_colors = false;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use bevy::{
+    core_pipeline::prepass::MOTION_VECTOR_PREPASS_FORMAT,
+    prelude::*,
+    render::{
+        mesh::{MeshVertexAttribute, VertexAttributeValues},
+        render_resource::{
+            ColorTargetState, ColorWrites, RenderPipelineDescriptor, ShaderDefVal, VertexFormat,
+        },
+    },
+    sprite::Mesh2dHandle,
+};
+
+use super::{map::Map, plugin::Customization};
+
+/// Clip position of each vertex in the previous frame, zero if unknown.
+pub(crate) const ATTRIBUTE_PREVIOUS_CLIP_POSITION: MeshVertexAttribute =
+    MeshVertexAttribute::new("PreviousClipPosition", 988779057, VertexFormat::Float32x4);
+
+/// Camera for which the motion vectors of maps are computed,
+/// see [`Map::set_motion_vectors`].
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub struct MapMotionVectorCamera;
+
+/// Clip-from-local matrix of a map entity in the previous frame, maintained for entities of
+/// maps with motion vectors.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PreviousMapClipTransform(pub Mat4);
+
+impl<C: Customization> Map<C> {
+    /// Additionally write motion vectors (in the format of bevy's motion vector prepass,
+    /// to the second color target) when rendering this map, computed from the movement of the
+    /// map entity and of the [`MapMotionVectorCamera`] since the previous frame.
+    ///
+    /// Bevy's 2d passes only have a single color target, so this is only useful for maps
+    /// rendered by a custom render pass with a motion vector attachment.
+    pub fn set_motion_vectors(&mut self, motion_vectors: bool) {
+        self.motion_vectors = motion_vectors;
+    }
+
+    pub fn motion_vectors(&self) -> bool {
+        self.motion_vectors
+    }
+}
+
+/// Add the motion vector target to the pipeline of a map with motion vectors.
+pub(crate) fn specialize(descriptor: &mut RenderPipelineDescriptor) {
+    descriptor
+        .vertex
+        .shader_defs
+        .push(ShaderDefVal::Bool("MOTION_VECTORS".to_string(), true));
+    descriptor
+        .fragment
+        .as_mut()
+        .unwrap()
+        .targets
+        .push(Some(ColorTargetState {
+            format: MOTION_VECTOR_PREPASS_FORMAT,
+            blend: None,
+            write_mask: ColorWrites::ALL,
+        }));
+}
+
+/// Set the previous clip positions of the mesh vertices of maps with motion vectors
+/// and remember the current transforms for the next frame.
+#[allow(clippy::type_complexity)]
+pub fn update_map_motion_vectors<C: Customization>(
+    mut commands: Commands,
+    cameras: Query<(&Camera, &GlobalTransform), With<MapMotionVectorCamera>>,
+    maps: Query<(
+        Entity,
+        &Handle<Map<C>>,
+        &GlobalTransform,
+        &Mesh2dHandle,
+        Option<&PreviousMapClipTransform>,
+    )>,
+    map_materials: Res<Assets<Map<C>>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let clip_from_world = cameras
+        .iter()
+        .find(|(camera, _)| camera.is_active)
+        .map(|(camera, transform)| camera.clip_from_view() * transform.compute_matrix().inverse());
+
+    for (entity, map_handle, transform, mesh_handle, previous) in maps.iter() {
+        if !map_materials
+            .get(map_handle)
+            .is_some_and(|map| map.motion_vectors)
+        {
+            continue;
+        }
+        let Some(mesh) = meshes.get_mut(&mesh_handle.0) else {
+            continue;
+        };
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            continue;
+        };
+
+        let current =
+            clip_from_world.map(|clip_from_world| clip_from_world * transform.compute_matrix());
+        // Without a previous frame, the map did not move
+        let previous_clip_from_local = previous.map(|previous| previous.0).or(current);
+        let previous_clip: Vec<[f32; 4]> = positions
+            .iter()
+            .map(|p| match previous_clip_from_local {
+                Some(m) => (m * Vec3::from(*p).extend(1.0)).to_array(),
+                None => [0.0; 4],
+            })
+            .collect();
+        mesh.insert_attribute(ATTRIBUTE_PREVIOUS_CLIP_POSITION, previous_clip);
+
+        if let Some(current) = current {
+            commands
+                .entity(entity)
+                .insert(PreviousMapClipTransform(current));
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use bevy::{
+    math::{ivec2, uvec2},
+    prelude::*,
+};
+
+use super::{map::MapIndexerMut, plugin::Customization};
+
+/// Tile values of a 3x3 neighborhood around a tile.
+/// Tiles outside of the map have value `0` (same as [`MapIndexerMut::at`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Neighborhood {
+    values: [u32; 9],
+}
+
+impl Neighborhood {
+    /// Offsets of the 8 neighbors, in the order of [`Self::neighbors`].
+    pub const OFFSETS: [IVec2; 8] = [
+        ivec2(-1, -1),
+        ivec2(0, -1),
+        ivec2(1, -1),
+        ivec2(-1, 0),
+        ivec2(1, 0),
+        ivec2(-1, 1),
+        ivec2(0, 1),
+        ivec2(1, 1),
+    ];
+
+    /// Value at `offset` (each component in `-1..=1`) from the center tile.
+    pub fn get(&self, offset: IVec2) -> u32 {
+        let offset = offset.clamp(IVec2::NEG_ONE, IVec2::ONE) + IVec2::ONE;
+        self.values[(offset.y * 3 + offset.x) as usize]
+    }
+
+    /// Value of the center tile.
+    pub fn center(&self) -> u32 {
+        self.values[4]
+    }
+
+    /// Values of the 8 neighbors (without the center).
+    pub fn neighbors(&self) -> impl Iterator<Item = u32> + '_ {
+        Self::OFFSETS.iter().map(|offset| self.get(*offset))
+    }
+
+    /// Number of neighbors (without the center) for which `f` returns true.
+    pub fn count(&self, f: impl Fn(u32) -> bool) -> usize {
+        self.neighbors().filter(|v| f(*v)).count()
+    }
+
+    /// Neighborhood with `f` applied to all values.
+    pub fn map(&self, f: impl Fn(u32) -> u32) -> Self {
+        Self {
+            values: self.values.map(f),
+        }
+    }
+}
+
+impl<'a, C: Customization> MapIndexerMut<'a, C> {
+    /// Compute new values for all tiles in `region` (`max` exclusive) from their old values and
+    /// the old values of their neighbors, eg. for cellular automata.
+    ///
+    /// All tiles are updated "at once", ie. `f` only ever sees values from before this call.
+    /// The old values of `region` (plus a one-tile border) are copied into a buffer kept by the
+    /// map, which only grows for larger regions, see
+    /// [`MapBuilder::with_expected_edit_size`](crate::map_builder::MapBuilder::with_expected_edit_size).
+    pub fn step_region<F>(&mut self, region: URect, f: F)
+    where
+        F: Fn(UVec2, u32, &Neighborhood) -> u32,
+    {
+        let max = region.max.min(self.size());
+        let min = region.min;
+        if min.cmpge(max).any() {
+            return;
+        }
+
+        // Old values of the region plus border
+        let scratch_min = min.as_ivec2() - IVec2::ONE;
+        let scratch_size = (max - min + UVec2::splat(2)).as_ivec2();
+        let mut scratch = std::mem::take(&mut self.map.edit_scratch.values);
+        scratch.clear();
+        scratch.reserve((scratch_size.x * scratch_size.y) as usize);
+        for y in 0..scratch_size.y {
+            for x in 0..scratch_size.x {
+                let p = scratch_min + ivec2(x, y);
+                scratch.push(match p.cmpge(IVec2::ZERO).all() {
+                    true => self.at(p.x as u32, p.y as u32),
+                    false => 0,
+                });
+            }
+        }
+
+        for y in min.y..max.y {
+            for x in min.x..max.x {
+                // Position of (x, y) in scratch
+                let s = ivec2(x as i32, y as i32) - scratch_min;
+                let mut values = [0; 9];
+                for dy in 0..3 {
+                    for dx in 0..3 {
+                        let i = (s.y - 1 + dy) * scratch_size.x + (s.x - 1 + dx);
+                        values[(dy * 3 + dx) as usize] = scratch[i as usize];
+                    }
+                }
+                let neighborhood = Neighborhood { values };
+                let v = f(uvec2(x, y), neighborhood.center(), &neighborhood);
+                self.set(x, y, v);
+            }
+        }
+        self.map.edit_scratch.values = scratch;
+    }
+}
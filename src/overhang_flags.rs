@@ -0,0 +1,137 @@
+//! Per-tile overhang directions, for maps whose tiles don't all overhang the same way
+//! (eg. flat ground mixed with tall walls and trees in one layer).
+//!
+//! ```
+//! # use bevy::{math::{ivec2, uvec2, vec2}, prelude::*};
+//! # use bevy_fast_tilemap::prelude::*;
+//! let map: Map = Map::builder(uvec2(4, 4), default(), vec2(16., 16.))
+//!     .with_dominance_overhang()
+//!     .with_tile_overhang_flags(0..8, OverhangFlags::NONE)
+//!     .with_tile_overhang_flags(2..4, OverhangFlags::SOUTH | OverhangFlags::EAST)
+//!     .build();
+//! assert!(map.tile_overhangs(3, ivec2(1, 0)));
+//! assert!(!map.tile_overhangs(3, ivec2(0, -1)));
+//! assert!(!map.tile_overhangs(5, ivec2(1, 0)));
+//! // Tiles without flags overhang as the map does
+//! assert_eq!(map.tile_overhang_flags(8), None);
+//! ```
+
+use std::ops::{BitOr, BitOrAssign, Range};
+
+use bevy::prelude::*;
+
+use super::{map::Map, map_builder::MapBuilder, plugin::Customization};
+
+/// Marks the entries of tile indices with [`OverhangFlags`] in the buffer of a map, so that
+/// [`OverhangFlags::NONE`] can be told apart from tiles without flags.
+pub(crate) const OVERHANG_FLAGS_SET: u32 = 1 << 8;
+
+/// Directions a tile overhangs into its neighbors, in map coordinates (east is `+x`, south is
+/// `+y`), see [`MapBuilder::with_tile_overhang_flags`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct OverhangFlags(u32);
+
+impl OverhangFlags {
+    pub const NONE: Self = Self(0);
+    pub const NORTH_WEST: Self = Self(1 << 0);
+    pub const NORTH: Self = Self(1 << 1);
+    pub const NORTH_EAST: Self = Self(1 << 2);
+    pub const WEST: Self = Self(1 << 3);
+    pub const EAST: Self = Self(1 << 4);
+    pub const SOUTH_WEST: Self = Self(1 << 5);
+    pub const SOUTH: Self = Self(1 << 6);
+    pub const SOUTH_EAST: Self = Self(1 << 7);
+    pub const ALL: Self = Self(0xff);
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Flags of the given bits, bits not belonging to a direction are dropped.
+    pub const fn from_bits_truncate(bits: u32) -> Self {
+        Self(bits & Self::ALL.0)
+    }
+
+    /// The direction of `offset` (eg. `(0, 3)` is [`Self::SOUTH`]), [`Self::NONE`] for zero.
+    pub fn from_direction(offset: IVec2) -> Self {
+        let d = offset.signum() + IVec2::ONE;
+        match d.y * 3 + d.x {
+            4 => Self::NONE,
+            i if i > 4 => Self(1 << (i - 1)),
+            i => Self(1 << i),
+        }
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for OverhangFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for OverhangFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl<C: Customization> MapBuilder<C> {
+    /// Tiles with atlas indices in `indices` overhang into their neighbors in the directions
+    /// `flags` only, instead of following the overhang rules of the map. Later calls override
+    /// the flags of earlier calls for the same indices.
+    ///
+    /// This refines the overhangs of the map, so it needs dominance or perspective overhangs
+    /// (or forced underhangs):
+    /// With dominance overhangs, tiles with flags overhang in their directions regardless of
+    /// their index (they are still drawn in the order of their indices), with perspective
+    /// overhangs and underhangs, tiles with flags only reach into the neighbors in their
+    /// directions. Tiles without flags keep overhanging as before.
+    /// Overhangs beyond the neighbors (see [`MapBuilder::with_overhang_extent_tiles`]) use the
+    /// direction towards the overhung tile.
+    ///
+    /// The flags are stored per index up to the highest index given, so keep indices small.
+    pub fn with_tile_overhang_flags(mut self, indices: Range<u32>, flags: OverhangFlags) -> Self {
+        let buffer = &mut self.map.tile_overhang_flags;
+        if buffer.len() < indices.end as usize {
+            buffer.resize(indices.end as usize, 0);
+        }
+        for index in indices {
+            buffer[index as usize] = flags.bits() | OVERHANG_FLAGS_SET;
+        }
+        self
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Overhang directions of tiles with the given atlas index, `None` if the tiles follow the
+    /// overhang rules of the map, see [`MapBuilder::with_tile_overhang_flags`].
+    pub fn tile_overhang_flags(&self, index: u32) -> Option<OverhangFlags> {
+        let entry = self.tile_overhang_flags.get(index as usize).copied()?;
+        (entry & OVERHANG_FLAGS_SET != 0).then_some(OverhangFlags::from_bits_truncate(entry))
+    }
+
+    /// Whether tiles with the given atlas index may overhang into the tile at `offset` from
+    /// them, true for tiles without [`OverhangFlags`] (whether they actually overhang is up
+    /// to the overhang rules of the map).
+    pub fn tile_overhangs(&self, index: u32, offset: IVec2) -> bool {
+        match self.tile_overhang_flags(index) {
+            Some(flags) => flags.contains(OverhangFlags::from_direction(offset)),
+            None => true,
+        }
+    }
+
+    /// Whether any tile has [`OverhangFlags`].
+    pub(crate) fn has_tile_overhang_flags(&self) -> bool {
+        self.tile_overhang_flags.iter().any(|entry| *entry != 0)
+    }
+}
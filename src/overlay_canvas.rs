@@ -0,0 +1,339 @@
+use std::ops::Range;
+
+use bevy::{
+    math::{ivec2, uvec2, vec2},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+
+use super::{map::Map, picking::image_texel, plugin::Customization};
+
+/// How painted colors are combined with the overlay canvas, see [`Map::paint_circle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CanvasBlend {
+    /// Paint on top of the canvas, eg. for scorch marks.
+    #[default]
+    Over,
+    /// Add the color (times its alpha) to the canvas, eg. for dirt accumulating in tracks.
+    Add,
+    /// Replace the canvas with the color.
+    Replace,
+    /// Reduce the canvas alpha by the alpha of the color, eg. for rain washing tracks away.
+    Erase,
+}
+
+/// Paintable RGBA overlay of a map with a fixed number of texels per tile,
+/// see [`crate::map_builder::MapBuilder::with_overlay_canvas`].
+/// The pixels are kept here and copied to the image by [`update_overlay_canvases`].
+#[derive(Debug)]
+pub(crate) struct OverlayCanvas {
+    texels_per_tile: u32,
+    /// Size in texels
+    size: UVec2,
+    /// sRGB RGBA8 texels, row by row
+    data: Vec<u8>,
+    image: Option<Handle<Image>>,
+    /// Rows painted since they were copied to the image
+    dirty_rows: Option<Range<u32>>,
+}
+
+impl Clone for OverlayCanvas {
+    /// Clones of a map get a canvas image of their own
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+            image: None,
+            dirty_rows: None,
+            ..*self
+        }
+    }
+}
+
+impl OverlayCanvas {
+    pub(crate) fn new(map_size: UVec2, texels_per_tile: u32) -> Self {
+        let texels_per_tile = texels_per_tile.max(1);
+        let size = map_size * texels_per_tile;
+        Self {
+            texels_per_tile,
+            size,
+            data: vec![0; (size.x * size.y * 4) as usize],
+            image: None,
+            dirty_rows: None,
+        }
+    }
+
+    pub(crate) fn bytes(&self) -> usize {
+        self.data.len()
+    }
+
+    pub(crate) fn image(&self) -> Option<&Handle<Image>> {
+        self.image.as_ref()
+    }
+
+    fn mark(&mut self, rows: Range<u32>) {
+        self.dirty_rows = Some(match self.dirty_rows.take() {
+            Some(dirty) => dirty.start.min(rows.start)..dirty.end.max(rows.end),
+            None => rows,
+        });
+    }
+
+    /// Combine the texel at `texel` with `color` (sRGB RGBA) by `blend`, `coverage` of it.
+    fn paint(&mut self, texel: UVec2, color: Vec4, coverage: f32, blend: CanvasBlend) {
+        let i = ((texel.y * self.size.x + texel.x) * 4) as usize;
+        let dst = Vec4::from_array([0, 1, 2, 3].map(|c| self.data[i + c] as f32 / 255.0));
+        let a = color.w * coverage;
+        let out = match blend {
+            CanvasBlend::Over => {
+                let alpha = a + dst.w * (1.0 - a);
+                match alpha > 0.0 {
+                    true => {
+                        let rgb =
+                            (color.truncate() * a + dst.truncate() * dst.w * (1.0 - a)) / alpha;
+                        rgb.extend(alpha)
+                    }
+                    false => Vec4::ZERO,
+                }
+            }
+            CanvasBlend::Add => dst + (color.truncate() * a).extend(a),
+            CanvasBlend::Replace => dst.lerp(color, coverage),
+            CanvasBlend::Erase => dst.truncate().extend(dst.w * (1.0 - a)),
+        };
+        let out = (out.clamp(Vec4::ZERO, Vec4::ONE) * 255.0).round();
+        for (c, value) in out.to_array().into_iter().enumerate() {
+            self.data[i + c] = value as u8;
+        }
+    }
+
+    /// Resize for a map of `map_size` tiles, moving the texels by `shift` tiles.
+    /// The image has the wrong size now, so a new one is created.
+    pub(crate) fn resize(&mut self, map_size: UVec2, shift: IVec2) {
+        let mut resized = Self::new(map_size, self.texels_per_tile);
+        let shift = shift * self.texels_per_tile as i32;
+        let row_bytes = |x: Range<i32>, y: i32, size: UVec2| {
+            (y * size.x as i32 + x.start) as usize * 4..(y * size.x as i32 + x.end) as usize * 4
+        };
+        let low = shift.max(IVec2::ZERO);
+        let high = (self.size.as_ivec2() + shift).min(resized.size.as_ivec2());
+        if low.cmplt(high).all() {
+            for y in low.y..high.y {
+                let to = row_bytes(low.x..high.x, y, resized.size);
+                let from = row_bytes(low.x - shift.x..high.x - shift.x, y - shift.y, self.size);
+                resized.data[to].copy_from_slice(&self.data[from]);
+            }
+        }
+        *self = resized;
+    }
+
+    /// Bilinear sample (linear RGBA) at `map_position`, like the shader.
+    pub(crate) fn sample(&self, map_position: Vec2) -> Vec4 {
+        let p = map_position * self.texels_per_tile as f32 - 0.5;
+        let base = p.floor();
+        let f = p - base;
+        let texel = |offset: IVec2| {
+            let t = (base.as_ivec2() + offset)
+                .clamp(IVec2::ZERO, self.size.as_ivec2() - 1)
+                .as_uvec2();
+            let i = ((t.y * self.size.x + t.x) * 4) as usize;
+            let [r, g, b, a] = [0, 1, 2, 3].map(|c| self.data[i + c]);
+            LinearRgba::from(Srgba::rgba_u8(r, g, b, a)).to_vec4()
+        };
+        let top = texel(IVec2::ZERO).lerp(texel(IVec2::X), f.x);
+        let bottom = texel(IVec2::Y).lerp(texel(IVec2::ONE), f.x);
+        top.lerp(bottom, f.y)
+    }
+
+    fn new_image(&self) -> Image {
+        let mut image = Image::new(
+            Extent3d {
+                width: self.size.x.max(1),
+                height: self.size.y.max(1),
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            self.data.clone(),
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        image.sampler = ImageSampler::linear();
+        image
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Image of the overlay canvas (see [`crate::map_builder::MapBuilder::with_overlay_canvas`]),
+    /// once created by [`update_overlay_canvases`].
+    pub fn overlay_canvas(&self) -> Option<&Handle<Image>> {
+        self.overlay_canvas.as_ref().and_then(OverlayCanvas::image)
+    }
+
+    /// Texels of the overlay canvas per tile (in each direction), `None` without a canvas.
+    pub fn overlay_texels_per_tile(&self) -> Option<u32> {
+        self.overlay_canvas.as_ref().map(|c| c.texels_per_tile)
+    }
+
+    /// Color of the overlay canvas texel `texel` (row `y` covers map positions
+    /// `y / texels_per_tile` to `(y + 1) / texels_per_tile`), if there is one.
+    pub fn overlay_texel(&self, texel: UVec2) -> Option<Color> {
+        let canvas = self.overlay_canvas.as_ref()?;
+        if texel.cmpge(canvas.size).any() {
+            return None;
+        }
+        let i = ((texel.y * canvas.size.x + texel.x) * 4) as usize;
+        let [r, g, b, a] = [0, 1, 2, 3].map(|c| canvas.data[i + c]);
+        Some(Srgba::rgba_u8(r, g, b, a).into())
+    }
+
+    /// Position in the overlay canvas (in texels) of the world position `world`,
+    /// see [`Self::world_to_map`].
+    pub fn world_to_overlay(&self, world: Vec2) -> Option<Vec2> {
+        let texels_per_tile = self.overlay_texels_per_tile()?;
+        Some(self.world_to_map(world) * texels_per_tile as f32)
+    }
+
+    /// Paint a circle of `radius` (in world units, ie. an ellipse in map space for eg. iso
+    /// projections) around `world` into the overlay canvas, with an antialiased edge.
+    /// Only the touched rows are copied to the canvas image, with the next
+    /// [`update_overlay_canvases`]. Does nothing without an overlay canvas.
+    pub fn paint_circle(&mut self, world: Vec2, radius: f32, color: Color, blend: CanvasBlend) {
+        let Some(texels_per_tile) = self.overlay_texels_per_tile() else {
+            return;
+        };
+        let scale = texels_per_tile as f32;
+        let color = Vec4::from_array(color.to_srgba().to_f32_array());
+
+        // Texels of the map positions of the corners of the world square around the circle
+        let corners = [
+            vec2(-1.0, -1.0),
+            vec2(-1.0, 1.0),
+            vec2(1.0, -1.0),
+            vec2(1.0, 1.0),
+        ]
+        .map(|corner| self.world_to_map(world + corner * radius) * scale);
+        let low = corners.iter().fold(Vec2::INFINITY, |low, c| low.min(*c));
+        let high = corners
+            .iter()
+            .fold(Vec2::NEG_INFINITY, |high, c| high.max(*c));
+
+        // World size of a texel, for the width of the antialiased edge
+        let center = self.world_to_map(world);
+        let texel_world = (self
+            .map_to_world_3d((center + Vec2::X / scale).extend(0.0))
+            .xy()
+            - self.map_to_world_3d(center.extend(0.0)).xy())
+        .length()
+        .max(f32::EPSILON);
+
+        let Some(canvas) = self.overlay_canvas.as_mut() else {
+            return;
+        };
+        let low = low.floor().max(Vec2::ZERO).as_uvec2();
+        let high = high.ceil().max(Vec2::ZERO).as_uvec2().min(canvas.size);
+        if low.cmpge(high).any() {
+            return;
+        }
+        let to_world = |texel: UVec2| {
+            let map_position = (texel.as_vec2() + 0.5) / scale;
+            self.map_uniform.map_to_world(map_position.extend(0.0)).xy()
+        };
+        for y in low.y..high.y {
+            for x in low.x..high.x {
+                let distance = to_world(uvec2(x, y)).distance(world);
+                let coverage = ((radius - distance) / texel_world + 0.5).clamp(0.0, 1.0);
+                if coverage > 0.0 {
+                    canvas.paint(uvec2(x, y), color, coverage, blend);
+                }
+            }
+        }
+        canvas.mark(low.y..high.y);
+    }
+
+    /// Paint `stamp` centered at `world` on top of the overlay canvas, one stamp pixel per
+    /// canvas texel (so the stamp follows the map grid, eg. it is a rhombus on iso maps).
+    /// The stamp needs CPU side data in an 8-bit RGBA or BGRA format.
+    /// Does nothing without an overlay canvas.
+    pub fn paint_stamp(&mut self, world: Vec2, stamp: &Image) {
+        let Some(center) = self.world_to_overlay(world) else {
+            return;
+        };
+        let stamp_size = stamp.size();
+        if image_texel(stamp, Vec2::ZERO).is_none() {
+            warn!("paint_stamp() needs a stamp with RGBA8 or BGRA8 data");
+            return;
+        }
+        let Some(canvas) = self.overlay_canvas.as_mut() else {
+            return;
+        };
+        let origin = (center - stamp_size.as_vec2() / 2.0).round().as_ivec2();
+        let low = origin.max(IVec2::ZERO);
+        let high = (origin + stamp_size.as_ivec2()).min(canvas.size.as_ivec2());
+        if low.cmpge(high).any() {
+            return;
+        }
+        for y in low.y..high.y {
+            for x in low.x..high.x {
+                let p = ivec2(x, y) - origin;
+                let Some(linear) = image_texel(stamp, p.as_vec2()) else {
+                    continue;
+                };
+                let color = LinearRgba::from_vec4(linear);
+                let color = Vec4::from_array(Srgba::from(color).to_f32_array());
+                canvas.paint(uvec2(x as u32, y as u32), color, 1.0, CanvasBlend::Over);
+            }
+        }
+        canvas.mark(low.y as u32..high.y as u32);
+    }
+
+    /// Make the overlay canvas transparent again.
+    pub fn clear_overlay_canvas(&mut self) {
+        if let Some(canvas) = self.overlay_canvas.as_mut() {
+            canvas.data.fill(0);
+            canvas.mark(0..canvas.size.y);
+        }
+    }
+}
+
+/// Create the images of overlay canvases and copy the rows painted since to them.
+/// Only maps with new paint are accessed mutably, which re-extracts them so they render
+/// with the updated image.
+pub fn update_overlay_canvases<C: Customization>(
+    mut maps: ResMut<Assets<Map<C>>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let pending: Vec<_> = maps
+        .iter()
+        .filter(|(_, map)| {
+            map.overlay_canvas.as_ref().is_some_and(|canvas| {
+                canvas.dirty_rows.is_some()
+                    || canvas
+                        .image
+                        .as_ref()
+                        .map_or(true, |image| !images.contains(image))
+            })
+        })
+        .map(|(id, _)| id)
+        .collect();
+
+    for id in pending {
+        let Some(canvas) = maps.get_mut(id).and_then(|map| map.overlay_canvas.as_mut()) else {
+            continue;
+        };
+        let rows = canvas.dirty_rows.take();
+        let image = canvas
+            .image
+            .as_ref()
+            .and_then(|image| images.get_mut(image));
+        match (image, rows) {
+            (Some(image), Some(rows)) => {
+                let row_bytes = (canvas.size.x * 4) as usize;
+                let bytes = rows.start as usize * row_bytes..rows.end as usize * row_bytes;
+                image.data[bytes.clone()].copy_from_slice(&canvas.data[bytes]);
+            }
+            (Some(_), None) => {}
+            (None, _) => canvas.image = Some(images.add(canvas.new_image())),
+        }
+    }
+}
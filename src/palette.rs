@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+
+use super::{
+    additional_atlases::TILE_ATLAS_SHIFT,
+    map::Map,
+    plugin::Customization,
+    tile_flags::{TILE_FLAGS_MASK, TILE_INDEX_MASK},
+};
+
+/// Maximum number of key colors and of palette colors of a map.
+pub const MAX_PALETTE_COLORS: usize = 8;
+
+fn to_vec4(color: &Color) -> Vec4 {
+    Vec4::from_array(color.to_linear().to_f32_array())
+}
+
+fn from_vec4(v: &Vec4) -> Color {
+    LinearRgba::from_f32_array(v.to_array()).into()
+}
+
+impl<C: Customization> Map<C> {
+    /// See [`MapBuilder::with_color_key_palette`](crate::map_builder::MapBuilder::with_color_key_palette).
+    pub(crate) fn set_color_keys(&mut self, key_colors: &[Color], tolerance: f32) {
+        if key_colors.len() > MAX_PALETTE_COLORS {
+            warn!(
+                "{} key colors given, only the first {} are used",
+                key_colors.len(),
+                MAX_PALETTE_COLORS
+            );
+        }
+        let n = key_colors.len().min(MAX_PALETTE_COLORS);
+        let uniform = &mut self.map_uniform;
+        for (key, color) in uniform.palette_keys.iter_mut().zip(key_colors) {
+            *key = to_vec4(color);
+        }
+        uniform.n_palette_keys = n as u32;
+        uniform.palette_tolerance = tolerance;
+        if uniform.palette_owner_mask == 0 {
+            // Key colors map to themselves until a palette is set
+            uniform.palette_colors = uniform.palette_keys;
+            uniform.n_palette_colors = n as u32;
+        }
+    }
+
+    /// See [`MapBuilder::with_palette_owner_bits`](crate::map_builder::MapBuilder::with_palette_owner_bits).
+    pub(crate) fn set_palette_owner_bits(&mut self, shift: u32, bits: u32) {
+        // The owner must not overlap the tile flags (or the atlas selector)
+        let flags_shift = match self.map_uniform.n_atlases {
+            0 | 1 => TILE_FLAGS_MASK.trailing_zeros(),
+            _ => TILE_ATLAS_SHIFT,
+        };
+        let shift = shift.min(flags_shift);
+        let max_bits = flags_shift - shift;
+        if bits > max_bits {
+            warn!(
+                "Palette owner bits {shift}..{} overlap the tile flags or the atlas selector, \
+                 using {max_bits} bits",
+                shift + bits
+            );
+        }
+        let bits = bits.min(max_bits);
+        let uniform = &mut self.map_uniform;
+        uniform.palette_owner_shift = shift;
+        uniform.palette_owner_mask = (1 << bits) - 1;
+        // Owners have no colors until a palette is set
+        uniform.n_palette_colors = 0;
+    }
+
+    /// Set the colors that replace the key colors of this map.
+    ///
+    /// Normally entry `i` replaces key color `i`, so `palette` should have the same length as the
+    /// key colors.
+    /// In owner mode (see
+    /// [`MapBuilder::with_palette_owner_bits`](crate::map_builder::MapBuilder::with_palette_owner_bits))
+    /// entry `i` replaces all key colors in tiles owned by owner `i`.
+    /// Tiles whose owner has no entry are rendered unchanged.
+    pub fn set_palette(&mut self, palette: Vec<Color>) {
+        let uniform = &mut self.map_uniform;
+        if uniform.palette_owner_mask == 0 && palette.len() != uniform.n_palette_keys as usize {
+            warn!(
+                "Palette has {} colors, but the map has {} key colors",
+                palette.len(),
+                uniform.n_palette_keys
+            );
+        }
+        if palette.len() > MAX_PALETTE_COLORS {
+            warn!(
+                "Palette has {} colors, only the first {} are used",
+                palette.len(),
+                MAX_PALETTE_COLORS
+            );
+        }
+        for (entry, color) in uniform.palette_colors.iter_mut().zip(palette.iter()) {
+            *entry = to_vec4(color);
+        }
+        uniform.n_palette_colors = palette.len().min(MAX_PALETTE_COLORS) as u32;
+    }
+
+    /// Colors replacing the key colors, see [`Self::set_palette`].
+    pub fn palette(&self) -> Vec<Color> {
+        let uniform = &self.map_uniform;
+        uniform.palette_colors[..uniform.n_palette_colors as usize]
+            .iter()
+            .map(from_vec4)
+            .collect()
+    }
+
+    /// Atlas colors that are replaced by the palette.
+    pub fn color_keys(&self) -> Vec<Color> {
+        let uniform = &self.map_uniform;
+        uniform.palette_keys[..uniform.n_palette_keys as usize]
+            .iter()
+            .map(from_vec4)
+            .collect()
+    }
+
+    /// Owner id stored in the given tile value in palette owner mode, 0 otherwise.
+    pub fn palette_owner(&self, tile: u32) -> u32 {
+        let uniform = &self.map_uniform;
+        ((tile & TILE_INDEX_MASK) >> uniform.palette_owner_shift) & uniform.palette_owner_mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::{uvec2, vec2, vec4};
+
+    use super::*;
+    use crate::{map_builder::MapBuilder, plugin::NoCustomization};
+
+    fn builder() -> MapBuilder<NoCustomization> {
+        Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+    }
+
+    fn gray(i: usize) -> Color {
+        LinearRgba::rgb(i as f32 / 16.0, 0.5, 1.0).into()
+    }
+
+    #[test]
+    fn keys_and_colors_are_packed() {
+        let keys = vec![gray(1), gray(2), gray(3)];
+        let mut map = builder().with_color_key_palette(keys.clone(), 0.25).build();
+        let uniform = &map.map_uniform;
+        assert_eq!(uniform.n_palette_keys, 3);
+        assert_eq!(uniform.palette_tolerance, 0.25);
+        assert_eq!(uniform.palette_keys[1], vec4(2.0 / 16.0, 0.5, 1.0, 1.0));
+        assert_eq!(uniform.palette_keys[3], Vec4::ZERO);
+        // Key colors map to themselves until a palette is set
+        assert_eq!(uniform.n_palette_colors, 3);
+        assert_eq!(uniform.palette_colors, uniform.palette_keys);
+
+        map.set_palette(vec![gray(7), gray(8), gray(9)]);
+        let uniform = &map.map_uniform;
+        assert_eq!(uniform.n_palette_colors, 3);
+        assert_eq!(uniform.palette_colors[2], vec4(9.0 / 16.0, 0.5, 1.0, 1.0));
+        assert_eq!(uniform.palette_keys[2], vec4(3.0 / 16.0, 0.5, 1.0, 1.0));
+        assert_eq!(map.color_keys(), keys);
+        assert_eq!(map.palette(), vec![gray(7), gray(8), gray(9)]);
+    }
+
+    #[test]
+    fn owner_bits_are_packed() {
+        let mut map = builder()
+            .with_palette_owner_bits(16, 4)
+            .with_color_key_palette(vec![gray(1)], 0.1)
+            .build();
+        let uniform = &map.map_uniform;
+        assert_eq!(uniform.palette_owner_shift, 16);
+        assert_eq!(uniform.palette_owner_mask, 0xf);
+        // Owners have no colors until a palette is set
+        assert_eq!(uniform.n_palette_colors, 0);
+        assert_eq!(map.palette_owner((5 << 16) | 123), 5);
+        assert_eq!(map.palette_owner((0x1f << 16) | 123), 0xf);
+
+        // One color per owner, independent of the number of key colors
+        map.set_palette(vec![gray(4), gray(5), gray(6)]);
+        assert_eq!(map.map_uniform.n_palette_colors, 3);
+        assert_eq!(map.map_uniform.palette_keys[0], to_vec4(&gray(1)));
+
+        // Owner bits are cut off below the tile flags
+        let map = builder().with_palette_owner_bits(24, 8).build();
+        assert_eq!(map.map_uniform.palette_owner_mask, 0x1f);
+    }
+
+    #[test]
+    fn at_most_eight_keys_are_used() {
+        let keys: Vec<Color> = (0..10).map(gray).collect();
+        let mut map = builder().with_color_key_palette(keys.clone(), 0.1).build();
+        assert_eq!(map.map_uniform.n_palette_keys, MAX_PALETTE_COLORS as u32);
+        assert_eq!(map.color_keys(), keys[..MAX_PALETTE_COLORS]);
+
+        map.set_palette(keys.iter().rev().copied().collect());
+        assert_eq!(map.map_uniform.n_palette_colors, MAX_PALETTE_COLORS as u32);
+        assert_eq!(map.palette()[0], gray(9));
+        assert_eq!(map.palette().len(), MAX_PALETTE_COLORS);
+    }
+}
@@ -0,0 +1,173 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use super::{map::Map, plugin::Customization};
+
+/// Maximum number of patches per map, as the shader checks every patch for every fragment.
+pub const MAX_PATCHES: usize = 64;
+
+/// Entries per patch in the header of the patch buffer, see `patches` in the shader.
+const PATCH_HEADER_SIZE: usize = 6;
+
+/// Rectangle of tiles rendered on top of the map without changing its tile data,
+/// eg. a construction blueprint or the area of a spell, see [`Map::add_patch`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Patch {
+    /// Map position of the top left tile of the patch.
+    pub origin: UVec2,
+    pub size: UVec2,
+    /// Tile values, row by row.
+    pub tiles: Vec<u32>,
+    /// 0 shows only the map, 1 shows the patch tiles fully opaque.
+    pub opacity: f32,
+}
+
+impl Patch {
+    /// Patch of the given size filled with `tile`.
+    pub fn filled(origin: UVec2, size: UVec2, tile: u32, opacity: f32) -> Self {
+        Self {
+            origin,
+            size,
+            tiles: vec![tile; (size.x * size.y) as usize],
+            opacity,
+        }
+    }
+}
+
+/// Identifies a patch of a map, see [`Map::add_patch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PatchId(u32);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// The map already has [`MAX_PATCHES`] patches.
+    TooManyPatches,
+    /// The number of tiles does not match the size of the patch.
+    WrongTileCount { expected: usize, actual: usize },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManyPatches => write!(f, "map already has {MAX_PATCHES} patches"),
+            Self::WrongTileCount { expected, actual } => {
+                write!(f, "patch needs {expected} tiles, got {actual}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// Patches of a map, in the order they were added.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MapPatches {
+    patches: Vec<(PatchId, Patch)>,
+    next_id: u32,
+}
+
+impl<C: Customization> Map<C> {
+    /// Render `patch` on top of the map.
+    ///
+    /// Patches are composited over the tiles (including overhangs and the preview) in the order
+    /// they were added, each blended with its opacity, decals are rendered on top of them.
+    /// Patch tiles are drawn within their own tile only, they neither overhang nor are overhung.
+    /// Patches don't change the tile data, but like other map changes adding, moving or removing
+    /// them re-uploads the map. Every fragment checks all patches, so keep them few and small.
+    pub fn add_patch(&mut self, patch: Patch) -> Result<PatchId, PatchError> {
+        let expected = (patch.size.x * patch.size.y) as usize;
+        if patch.tiles.len() != expected {
+            return Err(PatchError::WrongTileCount {
+                expected,
+                actual: patch.tiles.len(),
+            });
+        }
+        if self.patches.patches.len() >= MAX_PATCHES {
+            return Err(PatchError::TooManyPatches);
+        }
+        let id = PatchId(self.patches.next_id);
+        self.patches.next_id += 1;
+        self.patches.patches.push((id, patch));
+        self.update_patch_buffer();
+        Ok(id)
+    }
+
+    /// Remove a patch, returns `None` if it was already removed.
+    pub fn remove_patch(&mut self, id: PatchId) -> Option<Patch> {
+        let i = self.patches.patches.iter().position(|(i, _)| *i == id)?;
+        let (_, patch) = self.patches.patches.remove(i);
+        self.update_patch_buffer();
+        Some(patch)
+    }
+
+    /// Move a patch so its top left tile is at `origin`, returns false if it does not exist.
+    pub fn move_patch(&mut self, id: PatchId, origin: UVec2) -> bool {
+        let Some((_, patch)) = self.patches.patches.iter_mut().find(|(i, _)| *i == id) else {
+            return false;
+        };
+        if patch.origin != origin {
+            patch.origin = origin;
+            self.update_patch_buffer();
+        }
+        true
+    }
+
+    pub fn patch(&self, id: PatchId) -> Option<&Patch> {
+        self.patches
+            .patches
+            .iter()
+            .find(|(i, _)| *i == id)
+            .map(|(_, patch)| patch)
+    }
+
+    /// All patches, in the order they are rendered.
+    pub fn patches(&self) -> impl Iterator<Item = (PatchId, &Patch)> {
+        self.patches.patches.iter().map(|(id, patch)| (*id, patch))
+    }
+
+    pub fn clear_patches(&mut self) {
+        self.patches.patches.clear();
+        self.update_patch_buffer();
+    }
+
+    /// Move all patches by `shift` tiles (see [`Map::resize_with`]), patches moved beyond the
+    /// top or left edge of the map are removed.
+    pub(crate) fn shift_patches(&mut self, shift: IVec2) {
+        if shift == IVec2::ZERO || self.patches.patches.is_empty() {
+            return;
+        }
+        self.patches.patches.retain_mut(|(_, patch)| {
+            let origin = patch.origin.as_ivec2() + shift;
+            patch.origin = origin.max(IVec2::ZERO).as_uvec2();
+            origin.cmpge(IVec2::ZERO).all()
+        });
+        self.update_patch_buffer();
+    }
+
+    /// Number of patches, the headers of all patches, then the tiles of all patches.
+    fn update_patch_buffer(&mut self) {
+        let patches = &self.patches.patches;
+        let mut buffer = Vec::with_capacity(
+            1 + patches.len() * PATCH_HEADER_SIZE
+                + patches.iter().map(|(_, p)| p.tiles.len()).sum::<usize>(),
+        );
+        buffer.push(patches.len() as u32);
+        let mut start = (1 + patches.len() * PATCH_HEADER_SIZE) as u32;
+        for (_, patch) in patches {
+            buffer.extend([
+                patch.origin.x,
+                patch.origin.y,
+                patch.size.x,
+                patch.size.y,
+                start,
+                patch.opacity.clamp(0.0, 1.0).to_bits(),
+            ]);
+            start += patch.tiles.len() as u32;
+        }
+        for (_, patch) in patches {
+            buffer.extend(&patch.tiles);
+        }
+        self.patch_buffer = buffer;
+    }
+}
@@ -0,0 +1,156 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use bevy::{math::ivec2, prelude::*};
+
+use super::{map::Map, plugin::Customization};
+
+/// Which neighbors of a tile can be reached in one step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Connectivity {
+    /// Only horizontal and vertical steps.
+    #[default]
+    Four,
+    /// Also diagonal steps, but only if both tiles next to the diagonal are passable
+    /// (no cutting of corners).
+    Eight,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PathOptions {
+    pub connectivity: Connectivity,
+    /// Give up (ie. return `None`) after expanding this many tiles.
+    pub max_nodes: usize,
+}
+
+impl Default for PathOptions {
+    fn default() -> Self {
+        Self {
+            connectivity: Connectivity::Four,
+            max_nodes: 1 << 20,
+        }
+    }
+}
+
+/// Cost of a horizontal/vertical and a diagonal step (relative to tile cost).
+const STRAIGHT: u64 = 10;
+const DIAGONAL: u64 = 14;
+
+/// Find a cheapest path from `start` to `goal` (both included) using A* with 4-connectivity,
+/// see [`find_path_with`].
+pub fn find_path<C: Customization>(
+    map: &Map<C>,
+    start: UVec2,
+    goal: UVec2,
+    cost: impl Fn(u32) -> Option<u32>,
+) -> Option<Vec<UVec2>> {
+    find_path_with(map, start, goal, PathOptions::default(), cost)
+}
+
+/// Find a cheapest path from `start` to `goal` (both included) using A*.
+///
+/// `cost` gives the cost of entering a tile with the given tile index, `None` means impassable.
+/// Costs below 1 are treated as 1.
+/// Returns `None` if there is no path, or no path was found within `options.max_nodes`.
+pub fn find_path_with<C: Customization>(
+    map: &Map<C>,
+    start: UVec2,
+    goal: UVec2,
+    options: PathOptions,
+    cost: impl Fn(u32) -> Option<u32>,
+) -> Option<Vec<UVec2>> {
+    let size = map.map_size();
+    if start.cmpge(size).any() || goal.cmpge(size).any() {
+        return None;
+    }
+    if start == goal {
+        return Some(vec![start]);
+    }
+
+    let indexer = map.indexer();
+    let tile_cost = |p: IVec2| -> Option<u64> {
+        if p.cmplt(IVec2::ZERO).any() || p.cmpge(size.as_ivec2()).any() {
+            return None;
+        }
+        cost(indexer.at(p.x as u32, p.y as u32)).map(|c| c.max(1) as u64)
+    };
+    tile_cost(goal.as_ivec2())?;
+
+    let index = |p: IVec2| (p.y as u32 * size.x + p.x as u32) as usize;
+    let heuristic = |p: IVec2| -> u64 {
+        let d = (p - goal.as_ivec2()).abs();
+        let (min, max) = (d.x.min(d.y) as u64, d.x.max(d.y) as u64);
+        match options.connectivity {
+            Connectivity::Four => (min + max) * STRAIGHT,
+            Connectivity::Eight => min * DIAGONAL + (max - min) * STRAIGHT,
+        }
+    };
+
+    let steps: &[IVec2] = match options.connectivity {
+        Connectivity::Four => &[ivec2(1, 0), ivec2(-1, 0), ivec2(0, 1), ivec2(0, -1)],
+        Connectivity::Eight => &[
+            ivec2(1, 0),
+            ivec2(-1, 0),
+            ivec2(0, 1),
+            ivec2(0, -1),
+            ivec2(1, 1),
+            ivec2(1, -1),
+            ivec2(-1, 1),
+            ivec2(-1, -1),
+        ],
+    };
+
+    let n = (size.x * size.y) as usize;
+    let mut best = vec![u64::MAX; n];
+    let mut parent = vec![u32::MAX; n];
+    let mut open = BinaryHeap::new();
+
+    let start = start.as_ivec2();
+    let goal = goal.as_ivec2();
+    best[index(start)] = 0;
+    open.push(Reverse((heuristic(start), 0u64, start.x, start.y)));
+
+    let mut expanded = 0;
+    while let Some(Reverse((_, g, x, y))) = open.pop() {
+        let p = ivec2(x, y);
+        if g > best[index(p)] {
+            // Outdated entry
+            continue;
+        }
+        if p == goal {
+            let mut path = vec![goal.as_uvec2()];
+            let mut i = index(goal);
+            while parent[i] != u32::MAX {
+                i = parent[i] as usize;
+                path.push(UVec2::new(i as u32 % size.x, i as u32 / size.x));
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        expanded += 1;
+        if expanded > options.max_nodes {
+            return None;
+        }
+
+        for step in steps {
+            let q = p + *step;
+            let Some(c) = tile_cost(q) else {
+                continue;
+            };
+            let diagonal = step.x != 0 && step.y != 0;
+            if diagonal
+                && (tile_cost(ivec2(q.x, p.y)).is_none() || tile_cost(ivec2(p.x, q.y)).is_none())
+            {
+                continue;
+            }
+            let g = g + c * if diagonal { DIAGONAL } else { STRAIGHT };
+            if g < best[index(q)] {
+                best[index(q)] = g;
+                parent[index(q)] = index(p) as u32;
+                open.push(Reverse((g + heuristic(q), g, q.x, q.y)));
+            }
+        }
+    }
+
+    None
+}
@@ -0,0 +1,267 @@
+use bevy::{
+    math::{vec2, Vec3Swizzles},
+    prelude::*,
+    render::{camera::NormalizedRenderTarget, render_resource::TextureFormat},
+};
+
+use super::{
+    map::Map,
+    map_uniform::MapUniform,
+    plugin::Customization,
+    render_to_image::CpuRenderer,
+    tile_flags::{TILE_FLAGS_MASK, TILE_FLIP_X, TILE_FLIP_Y, TILE_ROTATE},
+    tile_projection::TileStagger,
+};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PickOptions {
+    /// Tile index that counts as empty, ie. is never picked.
+    pub empty_tile: u32,
+    /// If set, additionally require the alpha of the atlas texel rendered at the picked position
+    /// to be greater than this, for pixel-perfect picking of partially transparent tiles.
+    /// Only the tile itself is considered, not overhangs of neighboring tiles.
+    pub alpha_threshold: Option<f32>,
+}
+
+/// Position in the atlas (in pixels) that is sampled for tile `index` at `map_position`,
+/// `None` if that position is not covered by the tile.
+/// `value` is the tile value, including flags, `layout` the uniform with the layout of its atlas
+/// (see [`MapUniform::for_atlas`]).
+fn atlas_texel(
+    uniform: &MapUniform,
+    layout: &MapUniform,
+    value: u32,
+    map_position: Vec2,
+) -> Option<Vec2> {
+    let tile = map_position.floor();
+    let map_space_offset = map_position - tile;
+    let tile_offset = if uniform.stagger() == TileStagger::None {
+        let world_space_offset =
+            (uniform.projection * map_space_offset.extend(0.0)) * uniform.tile_size.extend(1.0);
+        vec2(1.0, -1.0) * world_space_offset.xy()
+    } else {
+        // Staggered map positions are relative positions in the tile rectangle already
+        (map_space_offset - uniform.tile_anchor_point) * uniform.tile_size
+    };
+    atlas_texel_at(layout, value, tile.as_ivec2(), tile_offset)
+}
+
+/// Position in the atlas (in pixels) that is sampled for tile `value` at `tile_offset`
+/// (in pixels from the tile anchor point) of the tile at `tile_position`,
+/// `None` if that position is not covered by the tile.
+/// Mirrors `_sample_tile` and `sample_tile_at` in the shader.
+pub(crate) fn atlas_texel_at(
+    uniform: &MapUniform,
+    value: u32,
+    tile_position: IVec2,
+    tile_offset: Vec2,
+) -> Option<Vec2> {
+    let index = uniform.atlas_index(value);
+    let n_tiles = uniform.n_tiles.max(UVec2::ONE);
+    let index2d = vec2((index % n_tiles.x) as f32, (index / n_tiles.x) as f32);
+    let factor = uniform.atlas_tile_size_factor;
+    let tile_start = if factor > 1 {
+        index2d * (uniform.tile_size * factor as f32 + uniform.inner_padding)
+            + uniform.outer_padding_topleft
+            + uniform.tile_size
+                * vec2(
+                    tile_position.x.rem_euclid(factor) as f32,
+                    tile_position.y.rem_euclid(factor) as f32,
+                )
+    } else {
+        index2d * (uniform.tile_size + uniform.inner_padding) + uniform.outer_padding_topleft
+    };
+
+    let uv = flip_tile_uv(
+        tile_offset / uniform.tile_size + uniform.tile_anchor_point,
+        value & TILE_FLAGS_MASK,
+    );
+    let rect_offset = uv * uniform.tile_size;
+    let max_overhang = uniform.inner_padding / 2.0;
+    if rect_offset.cmplt(-max_overhang).any()
+        || rect_offset.cmpge(uniform.tile_size + max_overhang).any()
+    {
+        return None;
+    }
+    Some(tile_start + rect_offset)
+}
+
+/// Position in the atlas tile that is shown at `uv` of a tile with the given flags.
+/// Mirrors `flip_tile_uv` in the shader.
+fn flip_tile_uv(uv: Vec2, flags: u32) -> Vec2 {
+    let mut r = uv;
+    if flags & TILE_FLIP_Y != 0 {
+        r.y = 1.0 - r.y;
+    }
+    if flags & TILE_FLIP_X != 0 {
+        r.x = 1.0 - r.x;
+    }
+    if flags & TILE_ROTATE != 0 {
+        r = r.yx();
+    }
+    r
+}
+
+/// Color (linear RGBA, as sampled by the shader) of `image` at pixel `texel`, `None` if it can
+/// not be determined (unsupported format, out of bounds or no CPU side data).
+pub(crate) fn image_texel(image: &Image, texel: Vec2) -> Option<Vec4> {
+    let texel = texel.floor();
+    let size = image.size();
+    if texel.cmplt(Vec2::ZERO).any() || texel.cmpge(size.as_vec2()).any() {
+        return None;
+    }
+    let (bgra, srgb) = match image.texture_descriptor.format {
+        TextureFormat::Rgba8Unorm => (false, false),
+        TextureFormat::Rgba8UnormSrgb => (false, true),
+        TextureFormat::Bgra8Unorm => (true, false),
+        TextureFormat::Bgra8UnormSrgb => (true, true),
+        _ => return None,
+    };
+    let i = (texel.y as usize * size.x as usize + texel.x as usize) * 4;
+    let &[c0, c1, c2, a] = image.data.get(i..i + 4)? else {
+        return None;
+    };
+    let [r, g, b] = if bgra { [c2, c1, c0] } else { [c0, c1, c2] };
+    let color = if srgb {
+        LinearRgba::from(Srgba::rgba_u8(r, g, b, a))
+    } else {
+        LinearRgba::from_f32_array([r, g, b, a].map(|c| c as f32 / 255.0))
+    };
+    Some(color.to_vec4())
+}
+
+impl<C: Customization> Map<C> {
+    /// Alpha of the atlas texel this map renders at `map_position` for its own tile
+    /// (not taking overhangs of neighboring tiles into account).
+    /// Returns `None` if the alpha can not be determined, eg. because the atlas is not loaded
+    /// or uses a texture format other than 8-bit RGBA/BGRA.
+    pub fn texel_alpha_at(&self, map_position: Vec2, images: &Assets<Image>) -> Option<f32> {
+        let Some(tile) = self.content_tile(map_position) else {
+            return Some(0.0);
+        };
+        let index = self.indexer().at_uvec(tile);
+        let uniform = &self.map_uniform;
+        let additional;
+        let (atlas, layout) = match uniform.atlas_selector(index) {
+            0 => (&self.atlas_texture, uniform),
+            i => {
+                // Tiles of atlases the map doesn't have render nothing
+                let Some(atlas) = self.additional_atlases.get(i as usize - 1) else {
+                    return Some(0.0);
+                };
+                additional = uniform.for_atlas(i);
+                (atlas, &additional)
+            }
+        };
+        match atlas_texel(uniform, layout, index, map_position) {
+            Some(texel) => image_texel(images.get(atlas)?, texel).map(|c| c.w),
+            None => Some(0.0),
+        }
+    }
+
+    /// Tile the player sees at `world` (in world coordinates, see [`Self::world_to_map`]):
+    /// The topmost tile whose atlas texel rendered there has an alpha above 0.5, including
+    /// perspective and dominance overhangs of neighboring tiles in the order the shader draws
+    /// them. `None` if only transparent texels (or nothing) are rendered there.
+    /// For maps with repeated content, this is the wrapped position (see [`Self::content_tile`]).
+    ///
+    /// If the atlas alpha can not be determined (see [`Self::texel_alpha_at`]), this falls back
+    /// to the tile at `world` without overhangs.
+    pub fn pick(&self, world: Vec2, images: &Assets<Image>) -> Option<UVec2> {
+        self.pick_at(self.world_to_map(world), images)
+    }
+
+    /// Like [`Self::pick`], for the map entity with the given `transform`.
+    pub fn pick_with(
+        &self,
+        transform: &GlobalTransform,
+        world: Vec2,
+        images: &Assets<Image>,
+    ) -> Option<UVec2> {
+        self.pick_at(self.world_to_map_with(transform, world), images)
+    }
+
+    fn pick_at(&self, map_position: Vec2, images: &Assets<Image>) -> Option<UVec2> {
+        match images.get(&self.atlas_texture) {
+            Some(atlas) if image_texel(atlas, Vec2::ZERO).is_some() => {
+                let renderer = CpuRenderer::new(self, atlas).with_additional_atlases(images);
+                renderer.pick(map_position, 0.5)
+            }
+            _ => self.content_tile(map_position),
+        }
+    }
+}
+
+/// Pick the topmost visible tile at `world` (in world coordinates) in a stack of map layers.
+///
+/// `layers` must be ordered top to bottom and yield the entity, map handle and global transform
+/// of each layer.
+/// Returns the entity and tile position of the first layer whose tile at `world` is not
+/// `options.empty_tile` (and passes the alpha check, if enabled).
+/// For maps with repeated content, this is the wrapped position (see [`Map::content_tile`]).
+/// Layers whose map is not loaded are skipped.
+/// If the alpha of a tile can not be determined, the tile counts as visible.
+pub fn pick_tile_in_layers<'a, C: Customization>(
+    layers: impl IntoIterator<Item = (Entity, &'a Handle<Map<C>>, &'a GlobalTransform)>,
+    world: Vec2,
+    maps: &Assets<Map<C>>,
+    images: &Assets<Image>,
+    options: PickOptions,
+) -> Option<(Entity, UVec2)> {
+    for (entity, handle, transform) in layers {
+        let Some(map) = maps.get(handle) else {
+            continue;
+        };
+        let map_position = map.world_to_map_with(transform, world);
+        let Some(tile) = map.content_tile(map_position) else {
+            continue;
+        };
+        if map.indexer().at_uvec(tile) == options.empty_tile {
+            continue;
+        }
+        if let Some(threshold) = options.alpha_threshold {
+            if map
+                .texel_alpha_at(map_position, images)
+                .is_some_and(|alpha| alpha <= threshold)
+            {
+                continue;
+            }
+        }
+        return Some((entity, tile));
+    }
+    None
+}
+
+/// World position at `cursor` (in logical pixels of `window`, eg. from a [`CursorMoved`] event)
+/// as seen by the active camera with the highest order that renders to `window` and whose
+/// viewport contains the cursor.
+///
+/// `primary_window` is needed for cameras rendering to the primary window
+/// ([`WindowRef::Primary`](bevy::window::WindowRef::Primary)).
+/// With several windows, use this instead of assuming a single camera or window.
+pub fn cursor_to_world<'a>(
+    cameras: impl IntoIterator<Item = (&'a Camera, &'a GlobalTransform)>,
+    window: Entity,
+    primary_window: Option<Entity>,
+    cursor: Vec2,
+) -> Option<Vec2> {
+    cameras
+        .into_iter()
+        .filter(|(camera, _)| camera.is_active)
+        .filter(|(camera, _)| {
+            matches!(
+                camera.target.normalize(primary_window),
+                Some(NormalizedRenderTarget::Window(w)) if w.entity() == window
+            )
+        })
+        .filter_map(|(camera, transform)| {
+            let viewport = camera.logical_viewport_rect()?;
+            if !viewport.contains(cursor) {
+                return None;
+            }
+            let world = camera.viewport_to_world_2d(transform, cursor - viewport.min)?;
+            Some((camera.order, world))
+        })
+        .max_by_key(|(order, _)| *order)
+        .map(|(_, world)| world)
+}
@@ -1,16 +1,65 @@
-use super::map::{log_map_events, update_loading_maps, update_map_vertex_attributes};
+use super::map::{
+    apply_map_animation_speeds, detect_stalled_map_loads, log_map_events, send_map_atlas_ready,
+    sync_map_transforms, update_loading_maps, update_map_vertex_attributes,
+    update_reloaded_atlases, MapAtlasReady, MapLoadStallTimeout, MapLoadStalled,
+};
 use bevy::{
+    asset::AssetEvents,
     prelude::*,
-    render::render_resource::{encase::internal::WriteInto, AsBindGroup, ShaderSize, ShaderType},
-    sprite::Material2dPlugin,
+    render::{
+        camera::CameraUpdateSystem,
+        render_asset::prepare_assets,
+        render_resource::{encase::internal::WriteInto, AsBindGroup, ShaderSize, ShaderType},
+        Render, RenderApp, RenderSet,
+    },
+    sprite::{Material2dPlugin, PreparedMaterial2d},
 };
 
 use super::{
-    map::{DefaultUserData, Map},
-    shader::SHADER_CODE,
+    anchor::update_tile_anchors,
+    any_map::insert_any_map_handles,
+    async_fill::apply_async_map_fills,
+    atlas_metadata::{load_atlas_metadata, AtlasMetadata, AtlasMetadataLoader},
+    autotile::apply_autotiling,
+    chunked::update_chunked_maps,
+    debug::{update_map_debug, FastTileMapDebug},
+    decal::update_map_decals,
+    globals::{apply_map_globals, FastTileMapGlobals},
+    hooks::run_map_sync_hooks,
+    map::{DefaultUserData, Map, NoExtraBindings},
+    minimap::sync_minimaps,
+    overlay_canvas::update_overlay_canvases,
+    shader::{check_user_data_size, insert_map_shader, ComposedShaders},
+    shared_mesh::SharedMapMeshes,
+    tile_changed::{send_map_tile_changed, MapTileChanged},
+    tile_upload::{claim_tile_uploads, write_tile_uploads, MapTileUploads},
+    uniform_batch::{claim_uniform_slots, write_map_uniforms, MapUniformBatch},
+    update_queue::apply_map_update_queues,
+    viewport_fit::update_viewport_fit_meshes,
+    visibility::MapVisibilityPlugin,
+    warmup::{
+        prepare_map_warmup, update_map_warmup, MapWarmup, MapWarmupComplete, MapWarmupShared,
+    },
+    window_fit::{fit_maps_to_window, FitMapToWindow},
+};
+
+#[cfg(feature = "validation")]
+use super::validation::{
+    validate_managed_meshes, validate_mix_colors, warn_plugin_added_twice, MapValidation,
+    MapValidationPlugin,
 };
 
 /// Implement this trait to customize the shader code and user data.
+///
+/// `custom_shader_code()` has to define a `UserData` struct matching `UserData` (checked when
+/// adding the plugin, see [`crate::shader::check_user_data_size`]) and a
+/// `fn sample_tile(in: ExtractIn) -> vec4<f32>`, see `ExtractIn` in `tilemap_shader.wgsl`
+/// for the available fields.
+/// `ExtractIn` is considered stable API: Fields are only added at the end and any changes
+/// are listed in CHANGELOG.md.
+/// Some of its fields are only populated with the according shader def enabled
+/// (`EXTRACT_MAP_POSITION`, `EXTRACT_MAP_POSITION_DERIVATIVES`, `EXTRACT_MIX_COLOR`),
+/// see [`Customization::shader_defs`].
 pub trait Customization: Sync + Send + 'static + TypePath + Clone
 {
     const SHADER_HANDLE: Handle<Shader>;
@@ -22,7 +71,21 @@ pub trait Customization: Sync + Send + 'static + TypePath + Clone
         + WriteInto
         + ShaderSize
         + Default;
+
+    /// Additional textures, samplers or buffers for the custom shader code, set per map with
+    /// [`Map::extra_bindings_mut`]. Use [`NoExtraBindings`] if there are none.
+    ///
+    /// Derive `AsBindGroup` for it like for a material, with binding indices of 200 and above
+    /// (lower ones are used by the map) and declare the bindings in group 2 of the
+    /// `custom_shader_code()`, eg. `@group(2) @binding(200) var noise: texture_2d<f32>;`.
+    type ExtraBindings: AsBindGroup<Data = ()> + Clone + Default + Send + Sync + 'static;
+
     fn custom_shader_code() -> String;
+
+    /// Additional shader defs to enable for the fragment shader.
+    fn shader_defs() -> Vec<String> {
+        Vec::new()
+    }
 }
 
 /// Default custumization that will use the default user data and shader code.
@@ -36,12 +99,28 @@ impl Customization for NoCustomization {
         };
 
         fn sample_tile(in: ExtractIn) -> vec4<f32> {
-            return sample_tile_at(in.tile_index, in.tile_position, in.tile_offset);
+            return sample_tile_at(in.tile_index, in.tile_position, in.tile_offset_texels);
         }
     "#.to_string()
 }
     const SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(15375856360518374895);
     type UserData = DefaultUserData;
+    type ExtraBindings = NoExtraBindings;
+}
+
+/// System sets of the map systems, for ordering your own systems relative to them.
+///
+/// Map edits are rendered atomically per frame: Changed maps are extracted for rendering once,
+/// after the whole main schedule ran, so all edits made before the asset events are sent
+/// (in `Last`) are rendered together in the same frame, no rendered frame shows only some of them.
+/// Edits made in `Update` or in `PostUpdate` before [`MapSystems::Prepare`] additionally get
+/// the derived render data (eg. decals) of the same frame.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MapSystems {
+    /// Loading maps and maintaining their meshes, in `Update`.
+    Update,
+    /// Preparing changed maps for rendering, in `PostUpdate` after transform propagation.
+    Prepare,
 }
 
 /// Plugin for fast tilemap.
@@ -56,22 +135,168 @@ pub struct CustomFastTileMapPlugin<C: Customization = NoCustomization> {
 }
 
 impl<C: Customization> Plugin for CustomFastTileMapPlugin<C> {
+    /// With the `validation` feature, adding the plugin twice is reported as a warning
+    /// (see `MapMisuse::PluginAddedTwice`) instead of failing.
+    fn is_unique(&self) -> bool {
+        !cfg!(feature = "validation")
+    }
+
     fn build(&self, app: &mut App) {
+        #[cfg(feature = "validation")]
+        {
+            if !app.is_plugin_added::<MapValidationPlugin>() {
+                app.add_plugins(MapValidationPlugin);
+            }
+            let mut validation = app.world_mut().resource_mut::<MapValidation>();
+            if !validation.register::<C>() {
+                warn_plugin_added_twice::<C>(app);
+                return;
+            }
+        }
+
+        // Fail here rather than with a bind group layout mismatch in the render thread
+        if let Err(mismatch) = check_user_data_size::<C>() {
+            panic!("{mismatch}");
+        }
+
         app.add_plugins(Material2dPlugin::<Map<C>>::default());
-        let mut shaders = app.world_mut().resource_mut::<Assets<Shader>>();
+        if !app.is_plugin_added::<MapVisibilityPlugin>() {
+            app.add_plugins(MapVisibilityPlugin);
+        }
 
-        let mut code = SHADER_CODE.to_string();
+        // The shader is only composed once a map using it exists
+        app.init_resource::<ComposedShaders>()
+            .add_systems(PostUpdate, insert_map_shader::<C>);
 
-        code = code.replace("#[user_code]", C::custom_shader_code().as_str());
+        app.configure_sets(
+            PostUpdate,
+            MapSystems::Prepare.after(TransformSystem::TransformPropagate),
+        );
 
-        shaders.insert(&C::SHADER_HANDLE, Shader::from_wgsl(code, file!()));
+        // Shared by all customizations
+        if !app.world().contains_resource::<Assets<AtlasMetadata>>() {
+            app.init_asset::<AtlasMetadata>()
+                .init_asset_loader::<AtlasMetadataLoader>();
+        }
 
         app.add_systems(
             Update,
             (
-                (update_loading_maps::<C>, log_map_events::<C>).chain(),
+                (
+                    load_atlas_metadata::<C>,
+                    update_loading_maps::<C>,
+                    update_reloaded_atlases::<C>,
+                    log_map_events::<C>,
+                    send_map_atlas_ready::<C>,
+                )
+                    .chain(),
                 update_map_vertex_attributes::<C>,
-            ),
+                fit_maps_to_window::<C>.before(update_map_vertex_attributes::<C>),
+                update_map_warmup::<C>
+                    .run_if(resource_exists::<MapWarmup<C>>)
+                    .after(update_loading_maps::<C>),
+                detect_stalled_map_loads::<C>.after(update_loading_maps::<C>),
+                update_chunked_maps::<C>,
+                insert_any_map_handles::<C>,
+                apply_async_map_fills::<C>.before(update_loading_maps::<C>),
+            )
+                .in_set(MapSystems::Update),
         );
+
+        app.init_resource::<FastTileMapGlobals>()
+            .register_type::<FastTileMapGlobals>();
+
+        app.init_resource::<FastTileMapDebug>()
+            .register_type::<FastTileMapDebug>();
+
+        app.init_resource::<SharedMapMeshes>();
+
+        app.register_type::<FitMapToWindow>();
+
+        app.init_resource::<MapLoadStallTimeout>()
+            .add_event::<MapLoadStalled<C>>()
+            .add_event::<MapAtlasReady<C>>()
+            .add_event::<MapTileChanged<C>>();
+
+        app.add_systems(
+            PostUpdate,
+            (
+                sync_map_transforms::<C>,
+                update_tile_anchors::<C>,
+                update_map_decals::<C>,
+                update_viewport_fit_meshes::<C>.after(CameraUpdateSystem),
+                apply_map_globals::<C>,
+                apply_map_animation_speeds::<C>,
+                update_overlay_canvases::<C>,
+                apply_map_update_queues::<C>.before(apply_autotiling::<C>),
+                apply_autotiling::<C>.before(update_map_debug::<C>),
+                update_map_debug::<C>,
+                send_map_tile_changed::<C>
+                    .after(apply_map_update_queues::<C>)
+                    .after(apply_autotiling::<C>),
+                sync_minimaps::<C>
+                    .after(apply_map_update_queues::<C>)
+                    .after(apply_autotiling::<C>),
+            )
+                .in_set(MapSystems::Prepare),
+        );
+
+        app.add_systems(Last, run_map_sync_hooks::<C>.after(AssetEvents));
+
+        #[cfg(feature = "validation")]
+        app.add_systems(
+            Update,
+            validate_managed_meshes::<C>
+                .in_set(MapSystems::Update)
+                .before(update_loading_maps::<C>),
+        )
+        .add_systems(
+            PostUpdate,
+            validate_mix_colors::<C>.in_set(MapSystems::Prepare),
+        );
+
+        #[cfg(feature = "motion-vectors")]
+        app.register_type::<crate::motion_vectors::MapMotionVectorCamera>()
+            .add_systems(
+                PostUpdate,
+                crate::motion_vectors::update_map_motion_vectors::<C>
+                    .in_set(MapSystems::Prepare)
+                    .after(CameraUpdateSystem)
+                    .after(update_viewport_fit_meshes::<C>),
+            );
+
+        let warmup_shared = MapWarmupShared::<C>::default();
+        app.add_event::<MapWarmupComplete<C>>()
+            .insert_resource(warmup_shared.clone());
+
+        let tile_uploads = MapTileUploads::<C>::default();
+        let uniform_batch = MapUniformBatch::<C>::default();
+        app.insert_resource(tile_uploads.clone())
+            .insert_resource(uniform_batch.clone())
+            .add_systems(
+                Last,
+                (claim_tile_uploads::<C>, claim_uniform_slots::<C>).after(AssetEvents),
+            );
+
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .insert_resource(warmup_shared)
+                .insert_resource(tile_uploads)
+                .insert_resource(uniform_batch)
+                .add_systems(
+                    Render,
+                    (
+                        prepare_map_warmup::<C>
+                            .in_set(RenderSet::Queue)
+                            .after(prepare_assets::<PreparedMaterial2d<Map<C>>>),
+                        write_tile_uploads::<C>
+                            .in_set(RenderSet::PrepareAssets)
+                            .after(prepare_assets::<PreparedMaterial2d<Map<C>>>),
+                        write_map_uniforms::<C>
+                            .in_set(RenderSet::PrepareAssets)
+                            .after(prepare_assets::<PreparedMaterial2d<Map<C>>>),
+                    ),
+                );
+        }
     }
 }
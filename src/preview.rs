@@ -0,0 +1,107 @@
+use bevy::{math::uvec2, prelude::*};
+
+use super::{
+    map::{Map, MapIndexerMut},
+    plugin::Customization,
+};
+
+/// Rectangular block of tile values, eg. the brush of a level editor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileStamp {
+    size: UVec2,
+    /// Row by row
+    tiles: Vec<u32>,
+}
+
+impl TileStamp {
+    /// Stamp of the given size, `tiles` are given row by row.
+    pub fn new(size: UVec2, tiles: Vec<u32>) -> Self {
+        assert_eq!(
+            tiles.len(),
+            (size.x * size.y) as usize,
+            "Stamp of size {size} needs {} tiles",
+            size.x * size.y
+        );
+        Self { size, tiles }
+    }
+
+    /// Stamp of a single tile.
+    pub fn single(tile: u32) -> Self {
+        Self::new(UVec2::ONE, vec![tile])
+    }
+
+    pub fn from_fn(size: UVec2, f: impl Fn(UVec2) -> u32) -> Self {
+        let tiles = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| uvec2(x, y)))
+            .map(f)
+            .collect();
+        Self { size, tiles }
+    }
+
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    pub fn at(&self, p: UVec2) -> u32 {
+        self.tiles[(p.y * self.size.x + p.x) as usize]
+    }
+}
+
+/// Ghost of a [`TileStamp`] rendered on top of the map, see [`Map::set_preview`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TilePreview {
+    /// Map position of the top left tile of the stamp.
+    pub origin: UVec2,
+    pub tiles: TileStamp,
+    /// 0 shows only the map, 1 shows the map as if the stamp was placed.
+    pub opacity: f32,
+}
+
+impl<C: Customization> Map<C> {
+    /// Show `preview` on top of the map or remove the current preview.
+    ///
+    /// The map is rendered as if the stamp was placed (including overhangs from and onto
+    /// neighboring tiles) and faded with the map as it is by `opacity`, so the preview looks
+    /// exactly like [`MapIndexerMut::place_stamp`] will.
+    /// Like other map changes, this re-uploads the map, so on very large maps it is better to
+    /// only change the preview when the cursor moves to another tile.
+    pub fn set_preview(&mut self, preview: Option<TilePreview>) {
+        if self.preview == preview {
+            return;
+        }
+        match &preview {
+            Some(preview) => {
+                self.map_uniform.preview_origin = preview.origin.as_ivec2();
+                self.map_uniform.preview_size = preview.tiles.size;
+                self.map_uniform.preview_opacity = preview.opacity;
+                self.preview_buffer = preview.tiles.tiles.clone();
+            }
+            None => {
+                self.map_uniform.preview_size = UVec2::ZERO;
+                self.preview_buffer = vec![0];
+            }
+        }
+        // Storage buffers must not be empty
+        if self.preview_buffer.is_empty() {
+            self.preview_buffer.push(0);
+        }
+        self.preview = preview;
+    }
+
+    pub fn preview(&self) -> Option<&TilePreview> {
+        self.preview.as_ref()
+    }
+}
+
+impl<'a, C: Customization> MapIndexerMut<'a, C> {
+    /// Set the tiles of `stamp` with its top left tile at `origin`,
+    /// parts outside of the map are ignored.
+    pub fn place_stamp(&mut self, origin: UVec2, stamp: &TileStamp) {
+        let end = (origin + stamp.size).min(self.size());
+        for y in origin.y..end.y {
+            for x in origin.x..end.x {
+                self.set(x, y, stamp.at(uvec2(x, y) - origin));
+            }
+        }
+    }
+}
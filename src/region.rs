@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Named rectangular region of a map (eg. a room or a biome).
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+pub struct MapRegion {
+    pub name: String,
+    /// Tiles covered by this region, `min` is inclusive, `max` is exclusive.
+    pub rect: URect,
+    pub id: u32,
+}
+
+impl MapRegion {
+    pub fn new(name: impl Into<String>, rect: URect, id: u32) -> Self {
+        Self {
+            name: name.into(),
+            rect,
+            id,
+        }
+    }
+
+    /// True iff the given tile lies inside of this region.
+    pub fn contains(&self, tile: UVec2) -> bool {
+        tile.cmpge(self.rect.min).all() && tile.cmplt(self.rect.max).all()
+    }
+}
+
+/// Width and height (in tiles) of the buckets used for looking up regions.
+const BUCKET_SIZE: u32 = 16;
+
+/// Regions of a map along with a lookup structure.
+///
+/// The map is divided into buckets of `BUCKET_SIZE`² tiles, each of which holds the indices of
+/// all regions intersecting it, in priority order.
+/// Thus a lookup only needs to consider the (usually very few) regions of a single bucket.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MapRegions {
+    regions: Vec<MapRegion>,
+    map_size: UVec2,
+    n_buckets: UVec2,
+    buckets: Vec<Vec<u32>>,
+}
+
+impl MapRegions {
+    pub(crate) fn new(regions: Vec<MapRegion>, map_size: UVec2) -> Self {
+        let n_buckets = (map_size + UVec2::splat(BUCKET_SIZE - 1)) / BUCKET_SIZE;
+        let mut buckets = vec![Vec::new(); (n_buckets.x * n_buckets.y) as usize];
+
+        for (i, region) in regions.iter().enumerate() {
+            if region.rect.min.cmpge(region.rect.max).any() {
+                warn!("Region {:?} ({}) is empty", region.name, region.id);
+                continue;
+            }
+            if region.rect.max.cmpgt(map_size).any() {
+                warn!(
+                    "Region {:?} ({}) exceeds map size {:?}: {:?}",
+                    region.name, region.id, map_size, region.rect
+                );
+            }
+
+            let max = region.rect.max.min(map_size);
+            if region.rect.min.cmpge(max).any() {
+                continue;
+            }
+            let bucket_min = region.rect.min / BUCKET_SIZE;
+            let bucket_max = (max - UVec2::ONE) / BUCKET_SIZE;
+            for y in bucket_min.y..=bucket_max.y {
+                for x in bucket_min.x..=bucket_max.x {
+                    buckets[(y * n_buckets.x + x) as usize].push(i as u32);
+                }
+            }
+        }
+
+        Self {
+            regions,
+            map_size,
+            n_buckets,
+            buckets,
+        }
+    }
+
+    pub(crate) fn regions(&self) -> &[MapRegion] {
+        &self.regions
+    }
+
+    /// First region (in the order they were given) containing `tile`.
+    /// `None` for tiles outside of the map, even if a region exceeding the map contains them.
+    pub(crate) fn at(&self, tile: UVec2) -> Option<&MapRegion> {
+        if tile.cmpge(self.map_size).any() {
+            return None;
+        }
+        let bucket = tile / BUCKET_SIZE;
+        self.buckets[(bucket.y * self.n_buckets.x + bucket.x) as usize]
+            .iter()
+            .map(|&i| &self.regions[i as usize])
+            .find(|region| region.contains(tile))
+    }
+}
@@ -0,0 +1,299 @@
+use bevy::prelude::*;
+
+use super::{map::MapIndexerMut, plugin::Customization};
+
+/// Mutable view of a rectangular window of a map, see [`MapIndexerMut::region_mut`].
+///
+/// Coordinates are relative to the window, accesses outside of it are ignored.
+/// Several non-overlapping views can exist at once (see [`MapIndexerMut::split_regions`]) and be
+/// sent to different threads.
+#[derive(Debug)]
+pub struct MapRegionMut<'a> {
+    /// Window in map coordinates, `max` exclusive.
+    rect: URect,
+    rows: Vec<&'a mut [u32]>,
+}
+
+impl<'a> MapRegionMut<'a> {
+    /// Window covered by this view, in map coordinates (`max` exclusive).
+    pub fn rect(&self) -> URect {
+        self.rect
+    }
+
+    /// Size of the window in tiles.
+    pub fn size(&self) -> UVec2 {
+        self.rect.size()
+    }
+
+    /// Map coordinates of the tile with region-local coordinates `local`.
+    pub fn to_global(&self, local: UVec2) -> UVec2 {
+        self.rect.min + local
+    }
+
+    /// Get tile at given region-local position, 0 if outside of the window.
+    pub fn at(&self, x: u32, y: u32) -> u32 {
+        self.rows
+            .get(y as usize)
+            .and_then(|row| row.get(x as usize))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Get tile at given region-local position, 0 if outside of the window.
+    pub fn at_uvec(&self, i: UVec2) -> u32 {
+        self.at(i.x, i.y)
+    }
+
+    /// Set tile at given region-local position, does nothing if outside of the window.
+    pub fn set(&mut self, x: u32, y: u32, v: u32) {
+        if let Some(tile) = self
+            .rows
+            .get_mut(y as usize)
+            .and_then(|row| row.get_mut(x as usize))
+        {
+            *tile = v;
+        }
+    }
+
+    /// Set tile at given region-local position, does nothing if outside of the window.
+    pub fn set_uvec(&mut self, i: UVec2, v: u32) {
+        self.set(i.x, i.y, v)
+    }
+
+    /// Set all tiles of the window.
+    pub fn fill(&mut self, v: u32) {
+        for row in self.rows.iter_mut() {
+            row.fill(v);
+        }
+    }
+
+    /// Rows of the window, top to bottom, eg. for copying whole rows from other storage.
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [u32]> {
+        self.rows.iter_mut().map(|row| &mut **row)
+    }
+}
+
+impl<'a, C: Customization> MapIndexerMut<'a, C> {
+    /// Mutable view of the tiles in `rect` (`max` exclusive, clamped to the map).
+    pub fn region_mut(&mut self, rect: URect) -> MapRegionMut<'_> {
+        self.split_regions(&[rect]).unwrap().pop().unwrap()
+    }
+
+    /// Independent mutable views of the tiles in each of `rects` (`max` exclusive, clamped
+    /// to the map), in the same order.
+    /// Returns `None` if any two of the (clamped) rects overlap.
+    ///
+    /// All tiles in `rects` are considered changed
+    /// (see [`Map::tiles_changed_since`](crate::map::Map::tiles_changed_since)),
+    /// whether they are actually written or not.
+    pub fn split_regions(&mut self, rects: &[URect]) -> Option<Vec<MapRegionMut<'_>>> {
+        let size = self.size();
+        let rects: Vec<URect> = rects.iter().map(|rect| self.clamp_rect(*rect)).collect();
+
+        for (i, a) in rects.iter().enumerate() {
+            for b in rects[i + 1..].iter() {
+                if !a.intersect(*b).is_empty() {
+                    return None;
+                }
+            }
+        }
+
+        self.map.set_uniform_tile(None);
+        for rect in rects.iter() {
+            self.record_rect_write(*rect);
+        }
+
+        let mut regions: Vec<_> = rects
+            .iter()
+            .map(|rect| MapRegionMut {
+                rect: *rect,
+                rows: Vec::with_capacity(rect.height() as usize),
+            })
+            .collect();
+        if size.x == 0 {
+            return Some(regions);
+        }
+
+        for (y, row) in self
+            .map
+            .map_texture
+            .chunks_exact_mut(size.x as usize)
+            .enumerate()
+        {
+            let y = y as u32;
+            let mut in_row: Vec<usize> = (0..rects.len())
+                .filter(|i| {
+                    !rects[*i].is_empty() && (rects[*i].min.y..rects[*i].max.y).contains(&y)
+                })
+                .collect();
+            in_row.sort_by_key(|i| rects[*i].min.x);
+
+            // Cut the row into the parts belonging to each region
+            let mut rest = row;
+            let mut x = 0;
+            for i in in_row {
+                let (_, tail) = rest.split_at_mut((rects[i].min.x - x) as usize);
+                let (part, tail) = tail.split_at_mut(rects[i].width() as usize);
+                regions[i].rows.push(part);
+                rest = tail;
+                x = rects[i].max.x;
+            }
+        }
+
+        Some(regions)
+    }
+
+    /// Set all tiles from `min` to `max` (exclusive, clamped to the map) to `v`.
+    /// The rectangle is clamped once and filled a row at a time, which is much faster than
+    /// setting each tile with [`Self::set`].
+    /// Returns the number of tiles inside of the map, which is less than the size of the
+    /// rectangle if it was clamped.
+    pub fn fill_rect(&mut self, min: UVec2, max: UVec2, v: u32) -> usize {
+        let rect = self.clamp_rect(URect { min, max });
+        let n_tiles = rect.width() as usize * rect.height() as usize;
+        if rect.is_empty() || self.map.uniform_tile() == Some(v) {
+            return n_tiles;
+        }
+        self.map.set_uniform_tile(None);
+        self.record_rect_write(rect);
+        let width = self.size().x as usize;
+        for y in rect.min.y..rect.max.y {
+            let start = y as usize * width;
+            self.map.map_texture[start + rect.min.x as usize..start + rect.max.x as usize].fill(v);
+        }
+        n_tiles
+    }
+
+    /// Set each tile from `min` to `max` (exclusive, clamped to the map) to the value `f` returns
+    /// for its map position, row by row, like [`Self::fill_rect`].
+    /// Returns the number of tiles written, `f` is only called for these.
+    pub fn fill_rect_with(
+        &mut self,
+        min: UVec2,
+        max: UVec2,
+        mut f: impl FnMut(UVec2) -> u32,
+    ) -> usize {
+        let rect = self.clamp_rect(URect { min, max });
+        if rect.is_empty() {
+            return 0;
+        }
+        self.map.set_uniform_tile(None);
+        self.record_rect_write(rect);
+        let width = self.size().x as usize;
+        for y in rect.min.y..rect.max.y {
+            let start = y as usize * width;
+            let row =
+                &mut self.map.map_texture[start + rect.min.x as usize..start + rect.max.x as usize];
+            for (tile, x) in row.iter_mut().zip(rect.min.x..) {
+                *tile = f(UVec2::new(x, y));
+            }
+        }
+        rect.width() as usize * rect.height() as usize
+    }
+
+    /// Copy `data` (row-major, `region.width()` tiles per row) into the tiles of `region`
+    /// (`max` exclusive), eg. a chunk of generated terrain.
+    /// Parts of `region` outside of the map are skipped, a row at a time is copied.
+    /// `data` must hold exactly one value per tile of `region`, otherwise nothing is copied
+    /// (with a warning).
+    /// Returns the number of tiles copied, so less than `data.len()` if parts were skipped.
+    pub fn copy_from_slice(&mut self, region: URect, data: &[u32]) -> usize {
+        if data.len() != region.width() as usize * region.height() as usize {
+            warn!(
+                "copy_from_slice: got {} tiles for a region of {} x {}",
+                data.len(),
+                region.width(),
+                region.height()
+            );
+            return 0;
+        }
+        let rect = self.clamp_rect(region);
+        if rect.is_empty() {
+            return 0;
+        }
+        self.map.set_uniform_tile(None);
+        self.record_rect_write(rect);
+        let width = self.size().x as usize;
+        let src_width = region.width() as usize;
+        let skip = (rect.min.x - region.min.x) as usize;
+        for y in rect.min.y..rect.max.y {
+            let src = (y - region.min.y) as usize * src_width + skip;
+            let dst = y as usize * width + rect.min.x as usize;
+            let n = rect.width() as usize;
+            self.map.map_texture[dst..dst + n].copy_from_slice(&data[src..src + n]);
+        }
+        rect.width() as usize * rect.height() as usize
+    }
+
+    /// Rows of the map (`size().x` tiles each), top to bottom, for writing tiles directly, eg.
+    /// with [`slice::copy_from_slice`] from other chunk storage.
+    /// Like [`Self::split_regions`], all tiles of the map are considered changed.
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [u32]> {
+        let size = self.size();
+        self.map.set_uniform_tile(None);
+        self.record_rect_write(URect::from_corners(UVec2::ZERO, size));
+        self.map
+            .map_texture
+            .chunks_exact_mut(size.x.max(1) as usize)
+    }
+
+    /// Set all tiles of the map to `v`.
+    /// This makes the map uniform (see [`crate::map::Map::set_uniform_tile`]), so only a single
+    /// value is uploaded until another value is written.
+    /// Returns the number of tiles of the map.
+    pub fn fill(&mut self, v: u32) -> usize {
+        self.map.set_uniform_tile(Some(v));
+        let size = self.size();
+        size.x as usize * size.y as usize
+    }
+
+    /// `rect` (`max` exclusive) clamped to the map
+    fn clamp_rect(&self, rect: URect) -> URect {
+        let max = rect.max.min(self.size());
+        URect::from_corners(rect.min.min(max), max)
+    }
+
+    /// Record the tiles of `rect` as changed (see [`crate::map::Map::tiles_changed_since`])
+    /// and written.
+    fn record_rect_write(&mut self, rect: URect) {
+        if let Some(ticks) = self.map.change_ticks.as_mut() {
+            ticks.record_rect(rect);
+        }
+        if !rect.is_empty() {
+            self.map.mark_written(rect);
+        }
+    }
+
+    /// Set the tile at `start` and all tiles connected to it (by edges) with the same value
+    /// to `v`, like the fill tool of a paint program.
+    /// Returns the number of tiles changed.
+    pub fn flood_fill(&mut self, start: UVec2, v: u32) -> usize {
+        let size = self.size();
+        if start.cmpge(size).any() {
+            return 0;
+        }
+        let old = self.at_uvec(start);
+        if old == v {
+            return 0;
+        }
+        let mut stack = std::mem::take(&mut self.map.edit_scratch.positions);
+        stack.clear();
+        stack.push(start);
+        let mut n = 0;
+        while let Some(p) = stack.pop() {
+            if self.at_uvec(p) != old {
+                continue;
+            }
+            self.set_uvec(p, v);
+            n += 1;
+            let p = p.as_ivec2();
+            for q in [p + IVec2::X, p - IVec2::X, p + IVec2::Y, p - IVec2::Y] {
+                if q.cmpge(IVec2::ZERO).all() && q.cmplt(size.as_ivec2()).all() {
+                    stack.push(q.as_uvec2());
+                }
+            }
+        }
+        self.map.edit_scratch.positions = stack;
+        n
+    }
+}
@@ -0,0 +1,447 @@
+use bevy::{
+    math::{ivec2, vec2},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+
+use super::{
+    map::{apply_color_jitter, Map},
+    map_uniform::MapUniform,
+    picking::{atlas_texel_at, image_texel},
+    plugin::Customization,
+    tile_colors::unpack_tile_color,
+    tile_flags::TILE_INDEX_MASK,
+    tile_layers::EMPTY_TILE,
+    tile_projection::TileStagger,
+};
+
+/// Neighbors rendered for each `PERSPECTIVE_UNDER_*` shader def, as offsets for underhangs and
+/// overhangs, in the order of the shader.
+const UNDERHANGS: [(&str, IVec2); 8] = [
+    ("NN", ivec2(-1, -1)),
+    ("NP", ivec2(-1, 1)),
+    ("PN", ivec2(1, -1)),
+    ("PP", ivec2(1, 1)),
+    ("ZN", ivec2(0, -1)),
+    ("NZ", ivec2(-1, 0)),
+    ("ZP", ivec2(0, 1)),
+    ("PZ", ivec2(1, 0)),
+];
+const OVERHANGS: [(&str, IVec2); 8] = [
+    ("ZN", ivec2(0, 1)),
+    ("NZ", ivec2(1, 0)),
+    ("ZP", ivec2(0, -1)),
+    ("PZ", ivec2(-1, 0)),
+    ("NN", ivec2(1, 1)),
+    ("NP", ivec2(1, -1)),
+    ("PN", ivec2(-1, 1)),
+    ("PP", ivec2(-1, -1)),
+];
+
+/// Neighbors in the order of `render_dominance_overhangs` in the shader.
+const DOMINANCE_NEIGHBORS: [IVec2; 8] = [
+    ivec2(-1, -1),
+    ivec2(-1, 0),
+    ivec2(-1, 1),
+    ivec2(0, 1),
+    ivec2(1, 1),
+    ivec2(1, 0),
+    ivec2(1, -1),
+    ivec2(0, -1),
+];
+
+/// CPU version of the tile rendering in `tilemap_shader.wgsl`, see [`Map::render_to_image`].
+pub(crate) struct CpuRenderer<'a, C: Customization> {
+    map: &'a Map<C>,
+    atlas: &'a Image,
+    /// Layout and image of each additional atlas
+    additional: Vec<(MapUniform, Option<&'a Image>)>,
+    underhangs: Vec<IVec2>,
+    overhangs: Vec<IVec2>,
+}
+
+impl<'a, C: Customization> CpuRenderer<'a, C> {
+    pub(crate) fn new(map: &'a Map<C>, atlas: &'a Image) -> Self {
+        let enabled = |table: [(&str, IVec2); 8]| {
+            table
+                .iter()
+                .filter(|(def, _)| {
+                    map.perspective_defs
+                        .iter()
+                        .any(|d| d.strip_prefix("PERSPECTIVE_UNDER_") == Some(def))
+                })
+                .map(|(_, offset)| *offset)
+                .collect()
+        };
+        Self {
+            map,
+            atlas,
+            additional: Vec::new(),
+            underhangs: enabled(UNDERHANGS),
+            overhangs: enabled(OVERHANGS),
+        }
+    }
+
+    /// Also render the tiles of the additional atlases in `images`
+    /// (see [`crate::map_builder::MapBuilder::with_additional_atlas`]).
+    pub(crate) fn with_additional_atlases(mut self, images: &'a Assets<Image>) -> Self {
+        let map = self.map;
+        for (i, atlas) in map.additional_atlases.iter().enumerate() {
+            let layout = map.map_uniform.for_atlas(i as u32 + 1);
+            self.additional.push((layout, images.get(atlas)));
+        }
+        self
+    }
+
+    fn is_valid_tile(&self, tile: IVec2) -> bool {
+        self.map.repeats_content()
+            || (tile.cmpge(IVec2::ZERO).all() && tile.cmplt(self.map.map_size().as_ivec2()).all())
+    }
+
+    /// `get_tile_index`: Tile shown at `tile`, which must be valid
+    fn tile_value(&self, tile: IVec2) -> u32 {
+        let tile = tile.rem_euclid(self.map.map_size().as_ivec2().max(IVec2::ONE));
+        self.map.autotiled(tile, self.map.indexer().at_ivec(tile))
+    }
+
+    fn tile_value_checked(&self, tile: IVec2) -> u32 {
+        if self.is_valid_tile(tile) {
+            self.tile_value(tile)
+        } else {
+            0
+        }
+    }
+
+    /// `_sample_tile`, `tile_offset` in pixels from the tile anchor point
+    fn sample_tile(&self, value: u32, tile: IVec2, tile_offset: Vec2) -> Vec4 {
+        if value == EMPTY_TILE {
+            return Vec4::ZERO;
+        }
+        let uniform = &self.map.map_uniform;
+        // Frame of tile animations for meshes at animation state 0
+        let (index, state) = (uniform.atlas_index(value), uniform.animation_offset);
+        let value = value + self.map.animated_tile_index(index, state) - index;
+        let (layout, atlas) = match uniform.atlas_selector(value) {
+            0 => (uniform, Some(self.atlas)),
+            i => match self.additional.get(i as usize - 1) {
+                Some((layout, atlas)) => (layout, *atlas),
+                None => return Vec4::ZERO,
+            },
+        };
+        let Some(texel) = atlas_texel_at(layout, value, tile, tile_offset) else {
+            return Vec4::ZERO;
+        };
+        let texel = atlas.and_then(|atlas| image_texel(atlas, texel));
+        let mut color = texel.unwrap_or(Vec4::ZERO);
+        if self.map.has_color_jitter(uniform.atlas_index(value)) {
+            color = apply_color_jitter(uniform, color, tile);
+        }
+        if self.map.is_emissive(uniform.atlas_index(value)) {
+            color = (color.xyz() * uniform.emissive_strength).extend(color.w);
+        }
+        if self.map.has_tile_color_channel() {
+            color *= self.tile_color(tile);
+        }
+        color
+    }
+
+    /// `get_tile_color`
+    fn tile_color(&self, tile: IVec2) -> Vec4 {
+        let tile = match self.map.repeats_content() {
+            true => tile.rem_euclid(self.map.map_size().as_ivec2().max(IVec2::ONE)),
+            false => tile,
+        };
+        if tile.cmplt(IVec2::ZERO).any() {
+            return Vec4::ONE;
+        }
+        unpack_tile_color(self.map.tile_color_at(tile.x as u32, tile.y as u32))
+    }
+
+    /// `sample_neighbor_tile_index`
+    fn sample_neighbor_value(
+        &self,
+        value: u32,
+        tile: IVec2,
+        offset: Vec2,
+        neighbor: IVec2,
+    ) -> Vec4 {
+        let uniform = &self.map.map_uniform;
+        let overhang =
+            (uniform.projection * (-neighbor.as_vec2()).extend(0.0)).xy() * uniform.tile_size;
+        self.sample_tile(value, tile + neighbor, offset + vec2(1.0, -1.0) * overhang)
+    }
+
+    /// `sample_neighbor`
+    fn sample_neighbor(&self, tile: IVec2, offset: Vec2, neighbor: IVec2) -> Vec4 {
+        if !self.is_valid_tile(tile + neighbor) {
+            return Vec4::ZERO;
+        }
+        let value = self.tile_value(tile + neighbor);
+        if !self.overhangs(value, -neighbor, true) {
+            return Vec4::ZERO;
+        }
+        self.sample_neighbor_value(value, tile, offset, neighbor)
+    }
+
+    /// `tile_overhangs`: Whether the tile `value` overhangs into the tile at `offset` from it
+    fn overhangs(&self, value: u32, offset: IVec2, fallback: bool) -> bool {
+        let index = self.map.map_uniform.atlas_index(value);
+        match self.map.tile_overhang_flags(index) {
+            Some(_) => self.map.tile_overhangs(index, offset),
+            None => fallback,
+        }
+    }
+
+    /// `render_dominance_overhangs`
+    fn render_dominance_overhangs(
+        &self,
+        stack: &mut Vec<(IVec2, Vec4)>,
+        value: u32,
+        tile: IVec2,
+        offset: Vec2,
+    ) {
+        let exclusions = self.map.overhang_exclusions.iter().any(|bits| *bits != 0);
+        let excluded = |value: u32| {
+            exclusions
+                && self
+                    .map
+                    .is_overhang_excluded(self.map.map_uniform.atlas_index(value))
+        };
+        if excluded(value) {
+            return;
+        }
+        // `DOMINANCE_SAME_DEPTH`: neighbors at other depths are left to the perspective overhangs
+        let same_depth_only = self.map.perspective_underhangs || self.map.perspective_overhangs;
+        let other_depth = |n: &IVec2| {
+            same_depth_only && (self.underhangs.contains(n) || self.overhangs.contains(n))
+        };
+        let mut neighbors = DOMINANCE_NEIGHBORS.map(|n| (self.tile_value_checked(tile + n), n));
+        // Same (unstable) sort as the shader, so neighbors of equal index blend in the same order
+        for i in 0..8 {
+            for j in i + 1..8 {
+                if (neighbors[i].0 & TILE_INDEX_MASK) > (neighbors[j].0 & TILE_INDEX_MASK) {
+                    neighbors.swap(i, j);
+                }
+            }
+        }
+        for (v, n) in neighbors {
+            let dominant = (v & TILE_INDEX_MASK) > (value & TILE_INDEX_MASK) && !excluded(v);
+            // Tiles with overhang flags overhang regardless of their index
+            let overhangs = self.overhangs(v, -n, dominant) && self.is_valid_tile(tile + n);
+            if overhangs && !other_depth(&n) {
+                stack.push((tile + n, self.sample_neighbor_value(v, tile, offset, n)));
+            }
+        }
+    }
+
+    /// Tiles blended by `render_tiles` at `offset` of `tile` and their colors, bottom to top.
+    fn tile_stack(&self, tile: IVec2, offset: Vec2) -> Vec<(IVec2, Vec4)> {
+        let map = self.map;
+        let (value, sample_color) = if self.is_valid_tile(tile) {
+            let value = self.tile_value(tile);
+            (value, self.sample_tile(value, tile, offset))
+        } else {
+            (0, map.map_uniform.outside_color)
+        };
+
+        let mut stack = Vec::new();
+        if map.perspective_underhangs && sample_color.w < 1.0 {
+            for neighbor in &self.underhangs {
+                let color = self.sample_neighbor(tile, offset, *neighbor);
+                stack.push((tile + *neighbor, color));
+            }
+        }
+        stack.push((tile, sample_color));
+        if map.dominance_overhangs {
+            self.render_dominance_overhangs(&mut stack, value, tile, offset);
+        }
+        if map.perspective_overhangs {
+            for neighbor in &self.overhangs {
+                let color = self.sample_neighbor(tile, offset, *neighbor);
+                stack.push((tile + *neighbor, color));
+            }
+            for k in 2..=map.map_uniform.overhang_levels {
+                let neighbor = map.map_uniform.overhang_step * k as i32;
+                let color = self.sample_neighbor(tile, offset, neighbor);
+                stack.push((tile + neighbor, color));
+            }
+        }
+        stack
+    }
+
+    /// `render_tiles`
+    fn render_tiles(&self, tile: IVec2, offset: Vec2) -> Vec4 {
+        let mut color = Vec4::ZERO;
+        for (_, sample_color) in self.tile_stack(tile, offset) {
+            color = blend(color, sample_color);
+        }
+        color
+    }
+
+    /// `render_layers`
+    fn render_layers(&self, color: Vec4, tile: IVec2, offset: Vec2) -> Vec4 {
+        let map = self.map;
+        let mut color = match map.is_layer_visible(0) {
+            true => color,
+            false => Vec4::ZERO,
+        };
+        if !self.is_valid_tile(tile) {
+            return color;
+        }
+        let p = tile.rem_euclid(map.map_size().as_ivec2().max(IVec2::ONE));
+        for layer in (1..map.n_layers()).filter(|layer| map.is_layer_visible(*layer)) {
+            let value = map.layer_tile_at(layer, p.x as u32, p.y as u32);
+            if value != EMPTY_TILE {
+                color = blend(color, self.sample_tile(value, tile, offset));
+            }
+        }
+        color
+    }
+
+    /// Tile containing `map_position` and the offset (in pixels from the tile anchor point) of
+    /// `map_position` in it
+    fn tile_and_offset(&self, map_position: Vec2) -> (IVec2, Vec2) {
+        let uniform = &self.map.map_uniform;
+        let mut tile = map_position.floor();
+        let mut map_space_offset = map_position - tile;
+        if self.map.has_exact_bounds() && uniform.stagger() == TileStagger::None {
+            let clamped = tile.clamp(Vec2::ZERO, uniform.map_size.as_vec2() - 1.0);
+            map_space_offset = (map_position - clamped).clamp(Vec2::ZERO, Vec2::ONE);
+            tile = clamped;
+        }
+        let tile_offset = if uniform.stagger() == TileStagger::None {
+            let world_space_offset =
+                (uniform.projection * map_space_offset.extend(0.0)).xy() * uniform.tile_size;
+            vec2(1.0, -1.0) * world_space_offset
+        } else {
+            // `staggered_map_position`: Relative positions in the tile rectangle
+            (map_position - tile - uniform.tile_anchor_point) * uniform.tile_size
+        };
+        (tile.as_ivec2(), tile_offset)
+    }
+
+    /// Topmost tile of the map rendered at `map_position` with an alpha above `threshold`,
+    /// see [`Map::pick`]
+    pub(crate) fn pick(&self, map_position: Vec2, threshold: f32) -> Option<UVec2> {
+        let (tile, offset) = self.tile_and_offset(map_position);
+        self.tile_stack(tile, offset)
+            .into_iter()
+            .rev()
+            .filter(|(_, color)| color.w > threshold)
+            .find_map(|(tile, _)| self.map.content_tile(tile.as_vec2()))
+    }
+
+    /// Color at `local` (local coordinates of the map entity),
+    /// the parts of `fragment` that don't depend on the mesh
+    fn render(&self, local: Vec2) -> Vec4 {
+        let uniform = &self.map.map_uniform;
+        let map_position = self.map.local_to_map(local);
+        let (tile, tile_offset) = self.tile_and_offset(map_position);
+        let mut color = self.render_tiles(tile, tile_offset);
+        color = self.render_layers(color, tile, tile_offset);
+        color = self.render_overlay_canvas(color, map_position);
+        color = self.render_edge_fade(color, map_position);
+
+        let clip_distance = (map_position - uniform.clip_min).min(uniform.clip_max - map_position);
+        let d = clip_distance.min_element();
+        if d < 0.0 {
+            color = Vec4::ZERO;
+        } else if uniform.clip_feather > 0.0 {
+            color.w *= (d / uniform.clip_feather).clamp(0.0, 1.0);
+        }
+        color * uniform.color_filter
+    }
+
+    /// `render_overlay_canvas`
+    fn render_overlay_canvas(&self, color: Vec4, map_position: Vec2) -> Vec4 {
+        let Some(canvas) = self.map.overlay_canvas.as_ref() else {
+            return color;
+        };
+        let size = self.map.map_size().as_vec2();
+        if map_position.cmplt(Vec2::ZERO).any() || map_position.cmpge(size).any() {
+            return color;
+        }
+        let overlay = canvas.sample(map_position);
+        let rgb = color.truncate().lerp(overlay.truncate(), overlay.w);
+        rgb.extend(color.w)
+    }
+
+    /// `render_edge_fade`
+    fn render_edge_fade(&self, color: Vec4, map_position: Vec2) -> Vec4 {
+        let uniform = &self.map.map_uniform;
+        if uniform.edge_fade.cmple(Vec2::ZERO).all() || self.map.repeats_content() {
+            return color;
+        }
+        let distance = map_position.min(self.map.map_size().as_vec2() - map_position);
+        if distance.cmplt(Vec2::ZERO).any() {
+            return color;
+        }
+        let fade = Vec2::select(
+            uniform.edge_fade.cmpgt(Vec2::ZERO),
+            (distance / uniform.edge_fade).clamp(Vec2::ZERO, Vec2::ONE),
+            Vec2::ONE,
+        );
+        uniform.edge_fade_color.lerp(color, fade.min_element())
+    }
+}
+
+/// `blend` in the shader: `c1` on top of `c0`
+fn blend(c0: Vec4, c1: Vec4) -> Vec4 {
+    if c0.w == 0.0 && c1.w == 0.0 {
+        return Vec4::ZERO;
+    }
+    let a_mix = c1.w + (1.0 - c1.w) * c0.w;
+    let r = (c1 * c1.w + c0 * c0.w * (1.0 - c1.w)) / a_mix;
+    r.truncate().extend(a_mix)
+}
+
+impl<C: Customization> Map<C> {
+    /// Render the whole map (its [`Self::world_size`] bounding box, including overhangs) into a
+    /// new image on the CPU, eg. for sharing a map or baking a static background.
+    /// `scale` is the number of image pixels per world unit, so `1.0` renders atlas pixels 1:1
+    /// for maps whose world tile size is the tile size.
+    ///
+    /// This mirrors the shader of [`crate::plugin::NoCustomization`] including perspective and
+    /// dominance overhangs (with tile overhang flags), flags, emissive tiles, color jitter, tile
+    /// colors, the overlay canvas, edge fade, clipping, tile layers, additional atlases,
+    /// autotile rules and staggered (hexagonal) projections. Tile animations show the frame at animation state 0.
+    /// Custom shader code, palettes, decals, the preview, patches and the secondary atlas are not
+    /// rendered.
+    /// Every pixel is computed on the CPU, so this is slow for huge maps.
+    ///
+    /// The atlas needs CPU side data in an 8-bit RGBA or BGRA format, otherwise (or if it is not
+    /// loaded) the image is transparent.
+    pub fn render_to_image(&self, images: &mut Assets<Image>, scale: f32) -> Handle<Image> {
+        let size = (self.world_size() * scale).ceil().max(Vec2::ONE).as_uvec2();
+        let mut data = vec![0u8; (size.x * size.y * 4) as usize];
+
+        match images.get(&self.atlas_texture) {
+            Some(atlas) if image_texel(atlas, Vec2::ZERO).is_some() => {
+                let renderer = CpuRenderer::new(self, atlas).with_additional_atlases(images);
+                let top_left = self.world_size() * vec2(-0.5, 0.5);
+                for (i, pixel) in data.chunks_exact_mut(4).enumerate() {
+                    let p = vec2((i as u32 % size.x) as f32, (i as u32 / size.x) as f32) + 0.5;
+                    let local = top_left + vec2(p.x, -p.y) / scale;
+                    let color = renderer.render(local);
+                    pixel.copy_from_slice(&Srgba::from(LinearRgba::from_vec4(color)).to_u8_array());
+                }
+            }
+            _ => warn!("render_to_image() needs a loaded atlas with RGBA8 or BGRA8 data"),
+        }
+
+        images.add(Image::new(
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::all(),
+        ))
+    }
+}
@@ -0,0 +1,154 @@
+use bevy::{math::vec2, prelude::*, sprite::Anchor};
+
+use super::{
+    map::{positions, Map},
+    plugin::Customization,
+    region::MapRegion,
+    tile_colors::WHITE,
+    tile_layers::EMPTY_TILE,
+};
+
+impl<C: Customization> Map<C> {
+    /// Change the size of the map, keeping the tiles at their map positions.
+    /// Tiles beyond `new_size` are dropped, new tiles are set to `fill`.
+    /// See [`Self::resize_with`] for details.
+    pub fn resize(&mut self, new_size: UVec2, fill: u32) -> Vec2 {
+        self.resize_anchored(new_size, fill, Anchor::TopLeft)
+    }
+
+    /// Change the size of the map, keeping the tiles at the side or corner given by `anchor`
+    /// (eg. [`Anchor::Center`] grows or shrinks the map evenly on all sides).
+    /// New tiles are set to `fill`.
+    /// See [`Self::resize_with`] for details.
+    pub fn resize_anchored(&mut self, new_size: UVec2, fill: u32, anchor: Anchor) -> Vec2 {
+        self.resize_with(new_size, anchor, |_, old| old.unwrap_or(fill))
+    }
+
+    /// Change the size of the map, each tile at position `p` of the resized map is set to
+    /// `tile(p, old)`, where `old` is the value that tile had before, if any.
+    ///
+    /// `anchor` refers to the map as seen in map coordinates, ie. `TopLeft` is tile (0, 0) and
+    /// `BottomRight` the last tile. Tiles move by the difference in size times the anchor, so
+    /// shrinking and then growing by the same amount puts them back into place.
+    /// Regions, the preview, patches, decals, tile colors, upper layers and the overlay canvas
+    /// move along, regions are cut off at the new size and the preview and patches are removed
+    /// if they would start beyond the top or left edge. New tiles are white (see
+    /// [`MapIndexerMut::set_color`]) and empty in upper layers (see
+    /// [`MapIndexerMut::set_layer`]).
+    ///
+    /// Resizing works on maps that are already rendered: The map is not reloaded (it keeps its
+    /// material, `user_data` and bindings), its tiles are uploaded in full with the next frame
+    /// and [`MapIndexer`](crate::map::MapIndexer) bounds follow the new size immediately.
+    /// The mesh of the map follows in [`crate::plugin::MapSystems::Update`], as the map is
+    /// centered on its entity, the kept tiles usually move in world space.
+    /// Returns how far they moved (in local coordinates of the map entity), move the entity by
+    /// the negative of that to keep them in place.
+    /// For staggered projections, moving tiles by an odd number of rows (or columns) changes
+    /// which of them are staggered.
+    pub fn resize_with<F>(&mut self, new_size: UVec2, anchor: Anchor, mut tile: F) -> Vec2
+    where
+        F: FnMut(UVec2, Option<u32>) -> u32,
+    {
+        let old_size = self.map_size();
+        let a = anchor.as_vec();
+        let growth = (new_size.as_ivec2() - old_size.as_ivec2()).as_vec2();
+        // Truncated towards zero, so shrinking undoes growing
+        let shift = (growth * vec2(a.x + 0.5, 0.5 - a.y)).as_ivec2();
+
+        let indexer = self.indexer();
+        let tiles: Vec<u32> = positions(new_size)
+            .map(|p| {
+                let old = p.as_ivec2() - shift;
+                let inside = old.cmpge(IVec2::ZERO).all() && old.cmplt(old_size.as_ivec2()).all();
+                tile(p, inside.then(|| indexer.at_ivec(old)))
+            })
+            .collect();
+
+        if self.tile_color_channel {
+            self.tile_colors = positions(new_size)
+                .map(|p| {
+                    let old = p.as_ivec2() - shift;
+                    match old.cmpge(IVec2::ZERO).all() {
+                        true => self.tile_color_at(old.x as u32, old.y as u32),
+                        false => WHITE,
+                    }
+                })
+                .collect();
+        }
+
+        if self.n_layers() > 1 {
+            self.layer_tiles = (1..self.n_layers())
+                .flat_map(|layer| positions(new_size).map(move |p| (layer, p)))
+                .map(|(layer, p)| {
+                    let old = p.as_ivec2() - shift;
+                    match old.cmpge(IVec2::ZERO).all() {
+                        true => self.layer_tile_at(layer, old.x as u32, old.y as u32),
+                        false => EMPTY_TILE,
+                    }
+                })
+                .collect();
+            // Storage buffers can't be empty
+            if self.layer_tiles.is_empty() {
+                self.layer_tiles.push(EMPTY_TILE);
+            }
+        }
+
+        let before = self.map_to_local(Vec2::splat(0.5));
+        self.map_uniform.map_size = new_size;
+        let first = tiles.first().copied().unwrap_or_default();
+        if tiles.iter().all(|&t| t == first) {
+            self.map_uniform.has_uniform_tile = 1;
+            self.map_uniform.uniform_tile = first;
+            self.map_texture = vec![first];
+        } else {
+            self.map_uniform.has_uniform_tile = 0;
+            self.map_texture = tiles;
+        }
+        self.update_projection();
+        let moved = self.map_to_local(shift.as_vec2() + 0.5) - before;
+
+        if let Some(ticks) = self.change_ticks.as_mut() {
+            ticks.resize(new_size);
+        }
+        self.written = None;
+        self.tiles_changed = None;
+        self.mark_written(URect::from_corners(UVec2::ZERO, new_size));
+
+        let move_rect = |rect: URect| {
+            let clamp = |p: UVec2| {
+                (p.as_ivec2() + shift)
+                    .max(IVec2::ZERO)
+                    .as_uvec2()
+                    .min(new_size)
+            };
+            URect::from_corners(clamp(rect.min), clamp(rect.max))
+        };
+        let regions = self
+            .regions()
+            .iter()
+            .filter_map(|region| {
+                let rect = move_rect(region.rect);
+                (!rect.is_empty()).then(|| MapRegion {
+                    rect,
+                    ..region.clone()
+                })
+            })
+            .collect();
+        self.set_regions(regions);
+
+        if let Some(mut preview) = self.preview().cloned() {
+            let origin = preview.origin.as_ivec2() + shift;
+            preview.origin = origin.max(IVec2::ZERO).as_uvec2();
+            self.set_preview(origin.cmpge(IVec2::ZERO).all().then_some(preview));
+        }
+
+        self.shift_patches(shift);
+
+        if let Some(canvas) = self.overlay_canvas.as_mut() {
+            canvas.resize(new_size, shift);
+        }
+
+        self.move_decals(moved);
+        moved
+    }
+}
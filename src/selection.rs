@@ -0,0 +1,100 @@
+use bevy::{
+    math::{vec2, Vec3Swizzles},
+    prelude::*,
+};
+
+use super::{map::Map, plugin::Customization};
+
+/// True iff the convex polygons `a` and `b` intersect (separating axis theorem).
+fn convex_intersect(a: &[Vec2; 4], b: &[Vec2; 4]) -> bool {
+    for polygon in [a, b] {
+        for i in 0..4 {
+            let edge = polygon[(i + 1) % 4] - polygon[i];
+            let axis = edge.perp();
+            let project = |p: &[Vec2; 4]| {
+                p.iter().fold((f32::MAX, f32::MIN), |(min, max), v| {
+                    let d = axis.dot(*v);
+                    (min.min(d), max.max(d))
+                })
+            };
+            let (a_min, a_max) = project(a);
+            let (b_min, b_max) = project(b);
+            if a_max < b_min || b_max < a_min {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+impl<C: Customization> Map<C> {
+    /// Range of tiles (`max` exclusive) covering the map area of `rect` (in world coordinates),
+    /// for a map entity with the given `transform`.
+    fn world_rect_tile_range(&self, rect: Rect, transform: &GlobalTransform) -> URect {
+        let inverse = transform.affine().inverse();
+        let mut low = Vec2::MAX;
+        let mut high = Vec2::MIN;
+        for corner in [
+            rect.min,
+            vec2(rect.max.x, rect.min.y),
+            rect.max,
+            vec2(rect.min.x, rect.max.y),
+        ] {
+            let local = inverse.transform_point3(corner.extend(0.0));
+            let map_position = self.map_uniform.local_to_map(local).xy();
+            low = low.min(map_position);
+            high = high.max(map_position);
+        }
+
+        let size = self.map_size().as_vec2();
+        let low = low.floor().clamp(Vec2::ZERO, size);
+        let high = (high.floor() + Vec2::ONE).clamp(Vec2::ZERO, size);
+        URect::from_corners(low.as_uvec2(), high.as_uvec2())
+    }
+
+    /// Tiles whose (projected) shape intersects `rect` (in world coordinates),
+    /// for a map entity with the given `transform`.
+    /// Eg. for rectangle selection with the mouse on axonometric maps.
+    pub fn tiles_in_world_rect<'a>(
+        &'a self,
+        rect: Rect,
+        transform: &GlobalTransform,
+    ) -> impl Iterator<Item = UVec2> + 'a {
+        let rect_polygon = [
+            rect.min,
+            vec2(rect.max.x, rect.min.y),
+            rect.max,
+            vec2(rect.min.x, rect.max.y),
+        ];
+        let transform = *transform;
+        self.tiles_in_world_rect_approx(rect, &transform)
+            .filter(move |tile| {
+                let tile = tile.as_vec2();
+                let corners = [
+                    tile,
+                    tile + vec2(1.0, 0.0),
+                    tile + vec2(1.0, 1.0),
+                    tile + vec2(0.0, 1.0),
+                ]
+                .map(|p| {
+                    transform
+                        .transform_point(self.map_to_local(p).extend(0.0))
+                        .xy()
+                });
+                convex_intersect(&rect_polygon, &corners)
+            })
+    }
+
+    /// Like [`Self::tiles_in_world_rect`], but may also return tiles close to `rect`
+    /// (all tiles in the bounding range in map coordinates).
+    /// Cheaper for very large selections.
+    pub fn tiles_in_world_rect_approx(
+        &self,
+        rect: Rect,
+        transform: &GlobalTransform,
+    ) -> impl Iterator<Item = UVec2> {
+        let range = self.world_rect_tile_range(rect, transform);
+        (range.min.y..range.max.y)
+            .flat_map(move |y| (range.min.x..range.max.x).map(move |x| UVec2::new(x, y)))
+    }
+}
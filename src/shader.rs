@@ -1 +1,201 @@
+use std::fmt;
+
+use bevy::{prelude::*, render::render_resource::ShaderSize, utils::HashMap};
+
+use super::{map::Map, plugin::Customization};
+
 pub const SHADER_CODE: &str = include_str!("../assets/tilemap_shader.wgsl");
+
+/// Shaders composed from [`SHADER_CODE`] and custom shader code,
+/// shared by all [`crate::plugin::CustomFastTileMapPlugin`] instances.
+///
+/// Customizations producing identical code reuse the composed shader.
+/// Note that each customization still needs its own shader asset
+/// (as [`Customization::SHADER_HANDLE`] is fixed per type).
+#[derive(Resource, Default)]
+pub(crate) struct ComposedShaders {
+    /// Composed shader by custom code ([`SHADER_CODE`] is the same for all of them)
+    shaders: HashMap<String, Shader>,
+}
+
+impl ComposedShaders {
+    /// Shader for the given custom code, composing it if necessary.
+    pub(crate) fn get_or_compose(&mut self, custom_code: &str) -> Shader {
+        if let Some(shader) = self.shaders.get(custom_code) {
+            return shader.clone();
+        }
+        let shader = Shader::from_wgsl(SHADER_CODE.replace("#[user_code]", custom_code), file!());
+        self.shaders.insert(custom_code.to_string(), shader.clone());
+        shader
+    }
+}
+
+/// Compose and insert the shader for `C` once the first `Map<C>` exists,
+/// so registered but unused customizations don't cost anything.
+pub(crate) fn insert_map_shader<C: Customization>(
+    mut inserted: Local<bool>,
+    maps: Res<Assets<Map<C>>>,
+    mut composed: ResMut<ComposedShaders>,
+    mut shaders: ResMut<Assets<Shader>>,
+) {
+    if *inserted || maps.is_empty() {
+        return;
+    }
+    *inserted = true;
+
+    let shader = composed.get_or_compose(&C::custom_shader_code());
+    shaders.insert(&C::SHADER_HANDLE, shader);
+}
+
+/// `UserData` of a [`Customization`] has a different size in Rust than in its
+/// `custom_shader_code()`, see [`check_user_data_size`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserDataSizeMismatch {
+    /// Type path of the customization.
+    pub customization: String,
+    /// `ShaderSize` of [`Customization::UserData`].
+    pub rust_size: u64,
+    /// Size of `struct UserData` in the WGSL code.
+    pub wgsl_size: u64,
+}
+
+impl fmt::Display for UserDataSizeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "UserData of {} is {} bytes in Rust but `struct UserData` in its custom_shader_code() \
+            is {} bytes, their fields have to match",
+            self.customization, self.rust_size, self.wgsl_size
+        )
+    }
+}
+
+impl std::error::Error for UserDataSizeMismatch {}
+
+/// Compare the size of [`Customization::UserData`] with the `struct UserData` declared in
+/// [`Customization::custom_shader_code`], so a mismatch is reported when the plugin is added
+/// rather than as a bind group layout error while rendering.
+///
+/// The struct declarations of the custom code are parsed on their own, so a `UserData` using
+/// types of the map shader can not be checked and is accepted.
+pub fn check_user_data_size<C: Customization>() -> Result<(), UserDataSizeMismatch> {
+    let Some(wgsl_size) = wgsl_struct_size(&C::custom_shader_code(), "UserData") else {
+        debug!(
+            "Could not determine the size of UserData in the shader code of {}",
+            C::type_path()
+        );
+        return Ok(());
+    };
+    let rust_size = C::UserData::SHADER_SIZE.get();
+    match rust_size == wgsl_size {
+        true => Ok(()),
+        false => Err(UserDataSizeMismatch {
+            customization: C::type_path().to_string(),
+            rust_size,
+            wgsl_size,
+        }),
+    }
+}
+
+/// Size of the struct `name` declared in `code`, from parsing all struct declarations of `code`.
+fn wgsl_struct_size(code: &str, name: &str) -> Option<u64> {
+    let module = naga::front::wgsl::parse_str(&struct_declarations(code)).ok()?;
+    module.types.iter().find_map(|(_, ty)| match &ty.inner {
+        naga::TypeInner::Struct { span, .. } if ty.name.as_deref() == Some(name) => {
+            Some(*span as u64)
+        }
+        _ => None,
+    })
+}
+
+/// All `struct Name { .. }` declarations of `code`, without line comments.
+fn struct_declarations(code: &str) -> String {
+    let code: String = code
+        .lines()
+        .map(|line| line.split("//").next().unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut declarations = String::new();
+    let mut rest = code.as_str();
+    while let Some(start) = rest.find("struct") {
+        let declaration = &rest[start..];
+        let is_keyword = !rest[..start].ends_with(|c: char| c.is_alphanumeric() || c == '_')
+            && declaration["struct".len()..].starts_with(char::is_whitespace);
+        let Some(open) = declaration.find('{').filter(|_| is_keyword) else {
+            rest = &declaration["struct".len()..];
+            continue;
+        };
+        let mut depth = 0;
+        let close = declaration[open..].find(|c| {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            depth == 0
+        });
+        let Some(close) = close else {
+            break;
+        };
+        let end = open + close + 1;
+        declarations.push_str(&declaration[..end]);
+        declarations.push('\n');
+        rest = &declaration[end..];
+    }
+    declarations
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        math::{uvec2, vec2},
+        render::render_resource::Source,
+    };
+
+    use super::*;
+    use crate::plugin::NoCustomization;
+
+    fn source(shader: &Shader) -> &str {
+        match &shader.source {
+            Source::Wgsl(code) => code.as_ref(),
+            _ => panic!("not WGSL"),
+        }
+    }
+
+    #[test]
+    fn composed_shaders_are_reused_by_code() {
+        let mut composed = ComposedShaders::default();
+        let a = composed.get_or_compose("fn a() {}");
+        let b = composed.get_or_compose("fn b() {}");
+        assert!(source(&a).contains("fn a() {}"));
+        assert!(source(&b).contains("fn b() {}"));
+        assert_eq!(source(&composed.get_or_compose("fn a() {}")), source(&a));
+        assert_eq!(composed.shaders.len(), 2);
+    }
+
+    #[test]
+    fn shaders_are_composed_once_a_map_exists() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Shader>()
+            .init_asset::<Map>()
+            .init_resource::<ComposedShaders>()
+            .add_systems(Update, insert_map_shader::<NoCustomization>);
+        let inserted = |app: &App| {
+            app.world()
+                .resource::<Assets<Shader>>()
+                .contains(&NoCustomization::SHADER_HANDLE)
+        };
+
+        app.update();
+        assert!(!inserted(&app));
+        assert!(app.world().resource::<ComposedShaders>().shaders.is_empty());
+
+        let map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0)).build();
+        app.world_mut().resource_mut::<Assets<Map>>().add(map);
+        app.update();
+        assert!(inserted(&app));
+        assert_eq!(app.world().resource::<ComposedShaders>().shaders.len(), 1);
+    }
+}
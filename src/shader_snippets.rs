@@ -0,0 +1,122 @@
+//! WGSL building blocks for [`Customization::custom_shader_code`](crate::plugin::Customization),
+//! combined with your `sample_tile` by [`compose_custom_shader`]:
+//!
+//! ```
+//! # use bevy_fast_tilemap::prelude::*;
+//! let code = compose_custom_shader(
+//!     &[HUE_SHIFT, TILE_OUTLINE],
+//!     r#"
+//!     struct UserData { dummy: u32 };
+//!
+//!     fn sample_tile(in: ExtractIn) -> vec4<f32> {
+//!         var color = sample_tile_at(in.tile_index, in.tile_position, in.tile_offset_texels);
+//!         color = hue_shift(color, in.animation_state);
+//!         return tile_outline(color, in.tile_uv, 1.0, vec4<f32>(0.0, 0.0, 0.0, 1.0));
+//!     }
+//!     "#,
+//! );
+//! ```
+//!
+//! Desaturating needs no snippet, the map shader provides `desaturate(color, amount)`.
+//!
+//! Offsets are in atlas pixels relative to the tile anchor point, like
+//! `ExtractIn::tile_offset_texels`, positions within tiles are given like `ExtractIn::tile_uv`.
+
+/// A named piece of WGSL code defining helper functions, see [`compose_custom_shader`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snippet {
+    pub name: &'static str,
+    pub code: &'static str,
+}
+
+/// `fn hue_shift(color: vec4<f32>, angle: f32) -> vec4<f32>`:
+/// Rotate the hue of `color` by `angle` (in radians), keeping its luminance roughly the same.
+pub const HUE_SHIFT: Snippet = Snippet {
+    name: "hue_shift",
+    code: r#"
+fn hue_shift(color: vec4<f32>, angle: f32) -> vec4<f32> {
+    // Rotation around the gray axis
+    let k = vec3<f32>(0.57735, 0.57735, 0.57735);
+    let c = cos(angle);
+    let rgb = color.rgb * c + cross(k, color.rgb) * sin(angle) + k * dot(k, color.rgb) * (1.0 - c);
+    return vec4<f32>(rgb, color.a);
+}
+"#,
+};
+
+/// `fn tile_outline(color: vec4<f32>, tile_uv: vec2<f32>, width: f32, outline_color: vec4<f32>) -> vec4<f32>`:
+/// Blend `outline_color` over `color` within `width` atlas pixels of the border of the tile.
+pub const TILE_OUTLINE: Snippet = Snippet {
+    name: "tile_outline",
+    code: r#"
+fn tile_outline(color: vec4<f32>, tile_uv: vec2<f32>, width: f32, outline_color: vec4<f32>) -> vec4<f32> {
+    let p = tile_uv * map.tile_size;
+    let d = min(p, map.tile_size - p);
+    if min(d.x, d.y) >= width {
+        return color;
+    }
+    return vec4<f32>(mix(color.rgb, outline_color.rgb, outline_color.a), max(color.a, outline_color.a));
+}
+"#,
+};
+
+/// `fn dissolve(color: vec4<f32>, in: ExtractIn, progress: f32, edge_width: f32, edge_color: vec4<f32>) -> vec4<f32>`:
+/// Let the tile appear pixel by pixel in random order as `progress` goes from 0 (invisible)
+/// to 1 (fully visible), pixels appearing next are tinted with `edge_color`.
+/// The order is fixed per map pixel, so neighboring tiles dissolve consistently.
+pub const DISSOLVE: Snippet = Snippet {
+    name: "dissolve",
+    code: r#"
+fn dissolve_noise(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+}
+
+fn dissolve(color: vec4<f32>, in: ExtractIn, progress: f32, edge_width: f32, edge_color: vec4<f32>) -> vec4<f32> {
+    let pixel = floor((vec2<f32>(in.tile_position) + in.tile_uv) * map.tile_size);
+    let n = dissolve_noise(pixel) * (1.0 - edge_width) + edge_width;
+    if n > progress + edge_width {
+        return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    }
+    if n > progress {
+        return vec4<f32>(edge_color.rgb, color.a * edge_color.a);
+    }
+    return color;
+}
+"#,
+};
+
+/// `fn wave_offset(in: ExtractIn, amplitude: vec2<f32>, wavelength: f32, speed: f32) -> vec2<f32>`:
+/// `in.tile_offset_texels` displaced by a wave travelling over the map, eg. for water.
+/// Pass the result to `sample_tile_at`. `amplitude` and `wavelength` are in atlas pixels,
+/// `speed` in radians per unit of `animation_state`.
+pub const WAVE: Snippet = Snippet {
+    name: "wave",
+    code: r#"
+fn wave_offset(in: ExtractIn, amplitude: vec2<f32>, wavelength: f32, speed: f32) -> vec2<f32> {
+    let p = (vec2<f32>(in.tile_position) + in.tile_uv) * map.tile_size;
+    let phase = p / wavelength * 6.2831853 + in.animation_state * speed;
+    return in.tile_offset_texels + amplitude * vec2<f32>(sin(phase.y), sin(phase.x));
+}
+"#,
+};
+
+/// All snippets, eg. for testing.
+pub const ALL_SNIPPETS: [Snippet; 4] = [HUE_SHIFT, TILE_OUTLINE, DISSOLVE, WAVE];
+
+/// Custom shader code consisting of the given `snippets` (each included once)
+/// followed by `body`, which defines `UserData` and `sample_tile`.
+pub fn compose_custom_shader(snippets: &[Snippet], body: &str) -> String {
+    let mut code = String::new();
+    let mut included = Vec::new();
+    for snippet in snippets {
+        if included.contains(&snippet.name) {
+            continue;
+        }
+        included.push(snippet.name);
+        code.push_str(&format!("// Snippet: {}\n", snippet.name));
+        code.push_str(snippet.code);
+        code.push('\n');
+    }
+    code.push_str(body);
+    code
+}
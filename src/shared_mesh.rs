@@ -0,0 +1,112 @@
+use bevy::{
+    math::uvec2,
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+    utils::HashMap,
+};
+
+use super::map::MapAttributes;
+
+/// Quad meshes shared by all maps with [`crate::map::MeshManagedByMap::Full`] of the same
+/// world size, so many chunks or layers of the same size use a single mesh asset.
+///
+/// Maps with per-vertex mix colors (see [`MapAttributes`]), [`crate::instances::MapInstances`]
+/// or motion vectors get a mesh of their own.
+///
+/// Quads of very long maps (eg. 1x4096 strips) are split into segments of at most
+/// [`MESH_SEGMENT_TILES`] tiles, so map positions are only interpolated across a segment and
+/// stay precise at the far end of the map.
+#[derive(Resource, Debug, Default)]
+pub struct SharedMapMeshes {
+    /// By the bits of the world size and the number of segments
+    meshes: HashMap<([u32; 2], UVec2), Handle<Mesh>>,
+}
+
+impl SharedMapMeshes {
+    /// Number of distinct shared meshes.
+    pub fn len(&self) -> usize {
+        self.meshes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.meshes.is_empty()
+    }
+
+    /// Shared quad of `world_size` split into `segments`, created if there is none yet.
+    pub(crate) fn get_or_add(
+        &mut self,
+        world_size: Vec2,
+        segments: UVec2,
+        meshes: &mut Assets<Mesh>,
+        time: &Time,
+    ) -> Handle<Mesh> {
+        self.meshes
+            .entry((world_size.to_array().map(f32::to_bits), segments))
+            .or_insert_with(|| {
+                let mut mesh = segmented_quad(world_size, segments);
+                MapAttributes::set_mix_color(None, &mut mesh);
+                MapAttributes::set_animation_state(None, &mut mesh, time);
+                meshes.add(mesh)
+            })
+            .clone()
+    }
+
+    /// Advance the animation state of all shared meshes still in use (as given by
+    /// `in_use`) and forget the others.
+    pub(crate) fn update(
+        &mut self,
+        in_use: impl Fn(&Handle<Mesh>) -> bool,
+        meshes: &mut Assets<Mesh>,
+        time: &Time,
+    ) {
+        self.meshes.retain(|_, handle| in_use(handle));
+        for handle in self.meshes.values() {
+            if let Some(mesh) = meshes.get_mut(handle) {
+                MapAttributes::set_animation_state(None, mesh, time);
+            }
+        }
+    }
+}
+
+/// Maximum number of tiles a segment of a shared map mesh spans in each direction.
+pub const MESH_SEGMENT_TILES: u32 = 256;
+
+/// Quad of `world_size` centered at the origin, split into a grid of `segments`.
+/// Neighboring segments share their vertices, so there are no gaps between them.
+pub(crate) fn segmented_quad(world_size: Vec2, segments: UVec2) -> Mesh {
+    if segments == UVec2::ONE {
+        return Mesh::from(Rectangle {
+            half_size: world_size / 2.0,
+        });
+    }
+
+    let columns = segments.x + 1;
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    for y in 0..=segments.y {
+        for x in 0..=segments.x {
+            let uv = uvec2(x, y).as_vec2() / segments.as_vec2();
+            let position = (uv - 0.5) * world_size;
+            positions.push([position.x, position.y, 0.0]);
+            uvs.push([uv.x, 1.0 - uv.y]);
+        }
+    }
+    let indices = (0..segments.y)
+        .flat_map(|y| (0..segments.x).map(move |x| y * columns + x))
+        .flat_map(|i| [i, i + 1, i + columns + 1, i, i + columns + 1, i + columns])
+        .collect();
+
+    let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_indices(Indices::U32(indices))
+}
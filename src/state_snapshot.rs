@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+
+use super::{
+    map::Map,
+    palette::MAX_PALETTE_COLORS,
+    plugin::{Customization, NoCustomization},
+};
+
+/// Runtime state of a map besides its tiles, eg. for rolling it back in lockstep netcode:
+/// The user data and everything changed by setters such as [`Map::set_clip_rect`],
+/// [`Map::set_projection_blend`], [`Map::set_atlas_blend`], [`Map::set_color_filter`],
+/// [`Map::set_animation_speed`] or [`Map::set_palette`].
+///
+/// Not included are the tiles, decals, the preview and anything only set when building the map.
+/// See [`Map::snapshot_state`].
+pub struct MapStateSnapshot<C: Customization = NoCustomization> {
+    pub user_data: C::UserData,
+    projection_blend: f32,
+    clip_min: Vec2,
+    clip_max: Vec2,
+    clip_feather: f32,
+    outside_color: Vec4,
+    edge_fade: Vec2,
+    edge_fade_color: Vec4,
+    pixel_snap: bool,
+    emissive_strength: f32,
+    color_jitter: f32,
+    color_jitter_hue: f32,
+    color_filter: Vec4,
+    animation_speed: f32,
+    animation_offset: f32,
+    atlas_blend: f32,
+    palette_colors: [Vec4; MAX_PALETTE_COLORS],
+    n_palette_colors: u32,
+}
+
+impl<C: Customization> Clone for MapStateSnapshot<C> {
+    fn clone(&self) -> Self {
+        Self {
+            user_data: self.user_data.clone(),
+            ..*self
+        }
+    }
+}
+
+impl<C: Customization> PartialEq for MapStateSnapshot<C>
+where
+    C::UserData: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.user_data == other.user_data
+            && self.projection_blend == other.projection_blend
+            && self.clip_min == other.clip_min
+            && self.clip_max == other.clip_max
+            && self.clip_feather == other.clip_feather
+            && self.outside_color == other.outside_color
+            && self.edge_fade == other.edge_fade
+            && self.edge_fade_color == other.edge_fade_color
+            && self.pixel_snap == other.pixel_snap
+            && self.emissive_strength == other.emissive_strength
+            && self.color_jitter == other.color_jitter
+            && self.color_jitter_hue == other.color_jitter_hue
+            && self.color_filter == other.color_filter
+            && self.animation_speed == other.animation_speed
+            && self.animation_offset == other.animation_offset
+            && self.atlas_blend == other.atlas_blend
+            && self.palette_colors == other.palette_colors
+            && self.n_palette_colors == other.n_palette_colors
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Capture the runtime state of this map besides its tiles, see [`MapStateSnapshot`].
+    pub fn snapshot_state(&self) -> MapStateSnapshot<C> {
+        let uniform = &self.map_uniform;
+        MapStateSnapshot {
+            user_data: self.user_data.clone(),
+            projection_blend: self.projection_blend,
+            clip_min: uniform.clip_min,
+            clip_max: uniform.clip_max,
+            clip_feather: uniform.clip_feather,
+            outside_color: uniform.outside_color,
+            edge_fade: uniform.edge_fade,
+            edge_fade_color: uniform.edge_fade_color,
+            pixel_snap: self.pixel_snap,
+            emissive_strength: uniform.emissive_strength,
+            color_jitter: uniform.color_jitter,
+            color_jitter_hue: uniform.color_jitter_hue,
+            color_filter: uniform.color_filter,
+            animation_speed: uniform.animation_speed,
+            animation_offset: uniform.animation_offset,
+            atlas_blend: uniform.atlas_blend,
+            palette_colors: uniform.palette_colors,
+            n_palette_colors: uniform.n_palette_colors,
+        }
+    }
+
+    /// Apply a state captured with [`Self::snapshot_state`] (of this or a similar map).
+    /// Like other map changes this re-uploads the map, use
+    /// [`crate::map_assets::MapAssetsExt::restore_state_if_neq`] to skip that if the state is unchanged.
+    pub fn restore_state(&mut self, snapshot: &MapStateSnapshot<C>) {
+        self.user_data = snapshot.user_data.clone();
+        self.pending_animation_speed = None;
+        if self.projections.is_some() && self.projection_blend != snapshot.projection_blend {
+            self.projection_blend = snapshot.projection_blend;
+            self.update_projection();
+        }
+        let uniform = &mut self.map_uniform;
+        uniform.clip_min = snapshot.clip_min;
+        uniform.clip_max = snapshot.clip_max;
+        uniform.clip_feather = snapshot.clip_feather;
+        uniform.outside_color = snapshot.outside_color;
+        uniform.edge_fade = snapshot.edge_fade;
+        uniform.edge_fade_color = snapshot.edge_fade_color;
+        uniform.emissive_strength = snapshot.emissive_strength;
+        uniform.color_jitter = snapshot.color_jitter;
+        uniform.color_jitter_hue = snapshot.color_jitter_hue;
+        uniform.color_filter = snapshot.color_filter;
+        uniform.animation_speed = snapshot.animation_speed;
+        uniform.animation_offset = snapshot.animation_offset;
+        uniform.atlas_blend = snapshot.atlas_blend;
+        uniform.palette_colors = snapshot.palette_colors;
+        uniform.n_palette_colors = snapshot.n_palette_colors;
+        self.set_pixel_snap(snapshot.pixel_snap);
+    }
+}
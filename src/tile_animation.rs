@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+
+use super::{map::Map, plugin::Customization};
+
+/// Words per animation in the header of the tile animation buffer:
+/// base index, start of its frames in the buffer, number of frames, fps (f32 bits) and mode.
+const HEADER_WORDS: usize = 5;
+
+/// How a [`TileAnimation`] continues after its last frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TileAnimationMode {
+    /// Start over with the first frame
+    #[default]
+    Loop,
+    /// Play the frames backwards down to the first one, then forwards again
+    PingPong,
+}
+
+/// Tiles with atlas index `base` show the atlas indices in `frames` in turn,
+/// `fps` frames per second of animation state (see [`Map::set_animation_speed`]).
+/// See [`crate::map_builder::MapBuilder::with_tile_animations`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileAnimation {
+    pub base: u32,
+    pub frames: Vec<u32>,
+    pub fps: f32,
+    pub mode: TileAnimationMode,
+}
+
+impl TileAnimation {
+    /// Animation through the `frames` consecutive atlas indices starting at `base`.
+    pub fn new(base: u32, frames: u32, fps: f32) -> Self {
+        Self::with_frames(base, &(base..base + frames).collect::<Vec<_>>(), fps)
+    }
+
+    /// Animation through the atlas indices `frames`, which need not include `base`.
+    pub fn with_frames(base: u32, frames: &[u32], fps: f32) -> Self {
+        Self {
+            base,
+            frames: frames.to_vec(),
+            fps,
+            mode: default(),
+        }
+    }
+
+    /// Play the frames back and forth, see [`TileAnimationMode::PingPong`].
+    pub fn ping_pong(mut self) -> Self {
+        self.mode = TileAnimationMode::PingPong;
+        self
+    }
+
+    /// Number (in `frames`) of the frame shown at the given animation state.
+    pub fn frame(&self, animation_state: f32) -> usize {
+        // Same as `animate_tile` in the shader
+        let n = self.frames.len().max(1) as i32;
+        let period = match self.mode {
+            TileAnimationMode::PingPong if n > 1 => 2 * n - 2,
+            _ => n,
+        };
+        let k = ((animation_state * self.fps).floor() as i32).rem_euclid(period);
+        (if k >= n { period - k } else { k }) as usize
+    }
+
+    /// Atlas index shown at the given animation state.
+    pub fn frame_index(&self, animation_state: f32) -> u32 {
+        self.frames
+            .get(self.frame(animation_state))
+            .copied()
+            .unwrap_or(self.base)
+    }
+}
+
+/// Buffer of `animations` (ordered by base index) for the shader: the number of animations,
+/// a header of [`HEADER_WORDS`] words per animation and then the frames of all animations.
+fn animation_buffer(animations: &[TileAnimation]) -> Vec<u32> {
+    let mut buffer = vec![animations.len() as u32];
+    let mut start = 1 + animations.len() * HEADER_WORDS;
+    for animation in animations {
+        buffer.extend([
+            animation.base,
+            start as u32,
+            animation.frames.len() as u32,
+            animation.fps.to_bits(),
+            match animation.mode {
+                TileAnimationMode::Loop => 0,
+                TileAnimationMode::PingPong => 1,
+            },
+        ]);
+        start += animation.frames.len();
+    }
+    for animation in animations {
+        buffer.extend(&animation.frames);
+    }
+    buffer
+}
+
+impl<C: Customization> Map<C> {
+    /// Animations set with [`crate::map_builder::MapBuilder::with_tile_animations`] or
+    /// [`Self::set_tile_animations`], ordered by their base index.
+    pub fn tile_animations(&self) -> &[TileAnimation] {
+        &self.tile_animations
+    }
+
+    /// Replace the tile animations of this map,
+    /// see [`crate::map_builder::MapBuilder::with_tile_animations`].
+    /// Adding the first or removing the last animation recompiles the pipeline of the map.
+    pub fn set_tile_animations(&mut self, animations: Vec<TileAnimation>) {
+        let mut animations: Vec<_> = animations
+            .into_iter()
+            .filter(|animation| {
+                if animation.frames.is_empty() {
+                    warn!(
+                        "Ignoring tile animation without frames for tile {}",
+                        animation.base
+                    );
+                }
+                !animation.frames.is_empty()
+            })
+            .collect();
+        // Stable, so the last one of several animations for a base index is kept
+        animations.sort_by_key(|animation| animation.base);
+        animations.reverse();
+        animations.dedup_by_key(|animation| animation.base);
+        animations.reverse();
+
+        self.tile_animation_buffer = animation_buffer(&animations);
+        self.tile_animations = animations;
+    }
+
+    /// Atlas index shown for tiles with atlas index `index` at the given animation state
+    /// (see [`Self::scaled_animation_state`]), `index` itself for tiles without an animation.
+    /// Frames are only selected by the shader, the map keeps holding the base index.
+    pub fn animated_tile_index(&self, index: u32, animation_state: f32) -> u32 {
+        let animations = self.tile_animations();
+        match animations.binary_search_by_key(&index, |animation| animation.base) {
+            Ok(i) => animations[i].frame_index(animation_state),
+            Err(_) => index,
+        }
+    }
+
+    /// Atlas index displayed right now for tiles with atlas index `index`, for meshes animated
+    /// with `time` (as by the map systems), eg. to keep gameplay in sync with the animation.
+    pub fn current_frame(&self, index: u32, time: &Time) -> u32 {
+        let state = self.scaled_animation_state(time.elapsed_seconds_wrapped());
+        self.animated_tile_index(index, state)
+    }
+}
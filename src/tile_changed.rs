@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+
+use super::{
+    map::Map,
+    plugin::{Customization, NoCustomization},
+};
+
+/// Sent (at most once per map and frame) when tile values of a map were written through
+/// indexers, bulk operations, [`Map::resize`] and the like, with the bounding rectangle of the
+/// written tiles (`max` exclusive).
+/// Unlike [`AssetEvent::Modified`], other changes of the map (eg. its `user_data`, tile
+/// colors or upper tile layers) don't send this.
+///
+/// Writes before [`crate::plugin::MapSystems::Prepare`] are sent in the same frame, later
+/// ones in the next frame. The rectangle may include tiles that bulk operations wrote with
+/// their current value.
+#[derive(Event, Debug, Clone)]
+pub struct MapTileChanged<C: Customization = NoCustomization> {
+    pub map: AssetId<Map<C>>,
+    pub rect: URect,
+}
+
+/// Send [`MapTileChanged`] for the maps whose tiles were written since the last run.
+/// Only those maps are accessed mutably.
+pub fn send_map_tile_changed<C: Customization>(
+    mut maps: ResMut<Assets<Map<C>>>,
+    mut ev_changed: EventWriter<MapTileChanged<C>>,
+) {
+    let changed: Vec<_> = maps
+        .iter()
+        .filter(|(_, map)| map.tiles_changed.is_some())
+        .map(|(id, _)| id)
+        .collect();
+    for id in changed {
+        let Some(rect) = maps.get_mut(id).and_then(|map| map.tiles_changed.take()) else {
+            continue;
+        };
+        ev_changed.send(MapTileChanged { map: id, rect });
+    }
+}
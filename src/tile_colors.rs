@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+
+use super::{
+    map::{Map, MapIndexer, MapIndexerMut},
+    plugin::Customization,
+};
+
+/// Packed tile color of white, ie. no tint
+pub(crate) const WHITE: u32 = u32::MAX;
+
+/// Pack `color` into 8 bits per linear channel, as unpacked by `unpack4x8unorm` in the shader.
+pub(crate) fn pack_tile_color(color: Color) -> u32 {
+    u32::from_le_bytes(color.to_linear().to_u8_array())
+}
+
+pub(crate) fn unpack_tile_color(packed: u32) -> Vec4 {
+    Vec4::from_array(packed.to_le_bytes().map(|c| c as f32 / 255.0))
+}
+
+impl<C: Customization> Map<C> {
+    /// Whether this map has a color per tile, see
+    /// [`crate::map_builder::MapBuilder::with_tile_color_channel`].
+    pub fn has_tile_color_channel(&self) -> bool {
+        self.tile_color_channel
+    }
+
+    /// Set the color of all tiles, eg. `Color::WHITE` to remove all tints.
+    /// Does nothing without a tile color channel.
+    pub fn fill_tile_colors(&mut self, color: Color) {
+        if self.tile_color_channel {
+            self.tile_colors.fill(pack_tile_color(color));
+            self.mark_uploaded(URect::from_corners(UVec2::ZERO, self.map_size()));
+        }
+    }
+
+    /// Packed color of the tile at `x`, `y`, white out of bounds or without a tile color channel.
+    pub(crate) fn tile_color_at(&self, x: u32, y: u32) -> u32 {
+        let size = self.map_size();
+        if !self.tile_color_channel || x >= size.x || y >= size.y {
+            return WHITE;
+        }
+        self.tile_colors[(y * size.x + x) as usize]
+    }
+}
+
+impl<C: Customization> MapIndexer<'_, C> {
+    /// Color the tile at the given position is multiplied with, white without a tile color
+    /// channel. Colors are stored with 8 bits per linear channel, so they are only
+    /// approximately what was set.
+    pub fn color_at(&self, x: u32, y: u32) -> Color {
+        LinearRgba::from_vec4(unpack_tile_color(self.map.tile_color_at(x, y))).into()
+    }
+
+    pub fn color_at_uvec(&self, i: UVec2) -> Color {
+        self.color_at(i.x, i.y)
+    }
+
+    /// Same as [`Self::color_at`].
+    pub fn tint_at(&self, x: u32, y: u32) -> Color {
+        self.color_at(x, y)
+    }
+}
+
+impl<C: Customization> MapIndexerMut<'_, C> {
+    /// See [`MapIndexer::color_at`].
+    pub fn color_at(&self, x: u32, y: u32) -> Color {
+        LinearRgba::from_vec4(unpack_tile_color(self.map.tile_color_at(x, y))).into()
+    }
+
+    pub fn color_at_uvec(&self, i: UVec2) -> Color {
+        self.color_at(i.x, i.y)
+    }
+
+    /// Set the color the tile at the given position is multiplied with (including alpha),
+    /// eg. a dark gray for fog of war or a team color for territory.
+    /// Like tile values, tile colors are uploaded with the next change of the map and show up
+    /// in [`crate::debug::FastTileMapDebug::dirty_regions`]. Custom shader code finds them in
+    /// `ExtractIn::tile_color`.
+    /// Positions out of bounds are ignored like for [`Self::set`], as are all writes to maps
+    /// without a tile color channel (with a warning), see
+    /// [`crate::map_builder::MapBuilder::with_tile_color_channel`].
+    pub fn set_color(&mut self, x: u32, y: u32, color: Color) {
+        if !self.map.tile_color_channel {
+            warn_once!("set_color() on a map without a tile color channel is ignored");
+            return;
+        }
+        let size = self.size();
+        if x >= size.x || y >= size.y {
+            if cfg!(debug_assertions) {
+                self.check_transposed(x, y);
+            }
+            return;
+        }
+        let idx = (y * size.x + x) as usize;
+        let packed = pack_tile_color(color);
+        if self.map.tile_colors[idx] == packed {
+            return;
+        }
+        self.map.tile_colors[idx] = packed;
+        self.map.mark_uploaded(URect::new(x, y, x + 1, y + 1));
+    }
+
+    pub fn set_color_uvec(&mut self, i: UVec2, color: Color) {
+        self.set_color(i.x, i.y, color)
+    }
+
+    /// Same as [`Self::color_at`].
+    pub fn tint_at(&self, x: u32, y: u32) -> Color {
+        self.color_at(x, y)
+    }
+
+    /// Same as [`Self::set_color`], tints need a map built with
+    /// [`crate::map_builder::MapBuilder::with_per_tile_tint`].
+    pub fn set_tint(&mut self, x: u32, y: u32, color: Color) {
+        self.set_color(x, y, color)
+    }
+}
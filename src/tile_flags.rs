@@ -0,0 +1,87 @@
+//! Flip and rotation flags in the high bits of tile values, with the same bits as Tiled uses.
+//!
+//! The bits and their meaning are stable, so custom shader code can rely on them (the shader has
+//! consts of the same names). The default shader applies them before `sample_tile`, ie. it passes
+//! the transformed `tile_offset_texels` and `tile_uv` on.
+//!
+//! ```
+//! # use bevy::{math::{uvec2, vec2}, prelude::*};
+//! # use bevy_fast_tilemap::prelude::*;
+//! # let map: Map = Map::builder(uvec2(4, 4), default(), vec2(16., 16.)).build_and_set(|_| 0);
+//! # let mut map = map;
+//! let mut m = map.indexer_mut();
+//! m.set(1, 2, 7 | TILE_FLIP_X);
+//! assert_eq!(m.index_at(1, 2), 7);
+//! assert_eq!(m.flags_at(1, 2), TILE_FLIP_X);
+//!
+//! m.set_flags(1, 2, TILE_FLIP_Y | TILE_ROTATE);
+//! m.set_index(1, 2, 9);
+//! assert_eq!(m.at(1, 2), 9 | TILE_FLIP_Y | TILE_ROTATE);
+//! ```
+
+use super::{
+    map::{MapIndexer, MapIndexerMut},
+    plugin::Customization,
+};
+
+/// Mirror the tile horizontally.
+pub const TILE_FLIP_X: u32 = 1 << 31;
+/// Mirror the tile vertically.
+pub const TILE_FLIP_Y: u32 = 1 << 30;
+/// Transpose the tile (mirror it along its top left to bottom right diagonal), applied before the
+/// flips. `TILE_ROTATE | TILE_FLIP_X` rotates the tile clockwise by 90 degrees.
+/// Only meaningful for square tiles.
+pub const TILE_ROTATE: u32 = 1 << 29;
+/// All flag bits.
+pub const TILE_FLAGS_MASK: u32 = TILE_FLIP_X | TILE_FLIP_Y | TILE_ROTATE;
+/// The bits of a tile value below the flags, ie. the atlas index (and the owner in palette owner
+/// mode, the atlas selector with additional atlases, see [`crate::additional_atlases`]).
+pub const TILE_INDEX_MASK: u32 = !TILE_FLAGS_MASK;
+
+impl<C: Customization> MapIndexer<'_, C> {
+    /// Tile value at the given position without the flags.
+    pub fn index_at(&self, x: u32, y: u32) -> u32 {
+        self.at(x, y) & TILE_INDEX_MASK
+    }
+
+    /// Flags of the tile at the given position (a combination of [`TILE_FLIP_X`],
+    /// [`TILE_FLIP_Y`] and [`TILE_ROTATE`]).
+    pub fn flags_at(&self, x: u32, y: u32) -> u32 {
+        self.at(x, y) & TILE_FLAGS_MASK
+    }
+}
+
+impl<C: Customization> MapIndexerMut<'_, C> {
+    /// Tile value at the given position without the flags.
+    pub fn index_at(&self, x: u32, y: u32) -> u32 {
+        self.at(x, y) & TILE_INDEX_MASK
+    }
+
+    /// Flags of the tile at the given position (a combination of [`TILE_FLIP_X`],
+    /// [`TILE_FLIP_Y`] and [`TILE_ROTATE`]).
+    pub fn flags_at(&self, x: u32, y: u32) -> u32 {
+        self.at(x, y) & TILE_FLAGS_MASK
+    }
+
+    /// Replace the flags of the tile at the given position with `flags`, keeping its index.
+    /// Bits of `flags` outside of [`TILE_FLAGS_MASK`] are ignored, positions out of bounds are
+    /// ignored like for [`Self::set`].
+    pub fn set_flags(&mut self, x: u32, y: u32, flags: u32) {
+        if x >= self.size().x || y >= self.size().y {
+            return;
+        }
+        let index = self.index_at(x, y);
+        self.set(x, y, index | (flags & TILE_FLAGS_MASK));
+    }
+
+    /// Replace the index of the tile at the given position with `index`, keeping its flags.
+    /// Flag bits of `index` are ignored, positions out of bounds are ignored like for
+    /// [`Self::set`].
+    pub fn set_index(&mut self, x: u32, y: u32, index: u32) {
+        if x >= self.size().x || y >= self.size().y {
+            return;
+        }
+        let flags = self.flags_at(x, y);
+        self.set(x, y, (index & TILE_INDEX_MASK) | flags);
+    }
+}
@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+
+use super::{
+    map::{Map, MapIndexer, MapIndexerMut},
+    plugin::Customization,
+};
+
+/// Tile value that renders nothing, in the upper layers (see
+/// [`crate::map_builder::MapBuilder::with_layers`]) as well as in layer 0.
+pub const EMPTY_TILE: u32 = u32::MAX;
+
+/// Maximum number of layers of a map (including layer 0).
+pub const MAX_TILE_LAYERS: u32 = 32;
+
+impl<C: Customization> Map<C> {
+    /// Number of tile layers of this map including layer 0 (the tiles set with
+    /// [`MapIndexerMut::set`]), see [`crate::map_builder::MapBuilder::with_layers`].
+    pub fn n_layers(&self) -> u32 {
+        self.map_uniform.n_layers
+    }
+
+    /// Whether `layer` is rendered, see [`Self::set_layer_visible`].
+    pub fn is_layer_visible(&self, layer: u32) -> bool {
+        layer < self.n_layers() && self.map_uniform.hidden_layers & (1 << layer) == 0
+    }
+
+    /// Show or hide `layer` (including layer 0). This only changes the uniform, the tiles of
+    /// hidden layers are kept (and can still be written).
+    pub fn set_layer_visible(&mut self, layer: u32, visible: bool) {
+        if layer >= self.n_layers() {
+            warn!(
+                "set_layer_visible(): layer {} of a map with {} layers",
+                layer,
+                self.n_layers()
+            );
+            return;
+        }
+        match visible {
+            true => self.map_uniform.hidden_layers &= !(1 << layer),
+            false => self.map_uniform.hidden_layers |= 1 << layer,
+        }
+    }
+
+    /// Tile value of upper `layer` (1 or above) at `x`, `y`, [`EMPTY_TILE`] out of bounds.
+    pub(crate) fn layer_tile_at(&self, layer: u32, x: u32, y: u32) -> u32 {
+        let size = self.map_size();
+        if layer == 0 || layer >= self.n_layers() || x >= size.x || y >= size.y {
+            return EMPTY_TILE;
+        }
+        self.layer_tiles[(((layer - 1) * size.y + y) * size.x + x) as usize]
+    }
+}
+
+impl<C: Customization> MapIndexer<'_, C> {
+    /// Tile value of `layer` at the given position, the same as [`Self::at`] for layer 0.
+    /// Upper layers are [`EMPTY_TILE`] out of bounds.
+    pub fn layer_at(&self, layer: u32, x: u32, y: u32) -> u32 {
+        match layer {
+            0 => self.at(x, y),
+            _ => self.map.layer_tile_at(layer, x, y),
+        }
+    }
+
+    pub fn layer_at_uvec(&self, layer: u32, i: UVec2) -> u32 {
+        self.layer_at(layer, i.x, i.y)
+    }
+}
+
+impl<C: Customization> MapIndexerMut<'_, C> {
+    /// See [`MapIndexer::layer_at`].
+    pub fn layer_at(&self, layer: u32, x: u32, y: u32) -> u32 {
+        match layer {
+            0 => self.at(x, y),
+            _ => self.map.layer_tile_at(layer, x, y),
+        }
+    }
+
+    pub fn layer_at_uvec(&self, layer: u32, i: UVec2) -> u32 {
+        self.layer_at(layer, i.x, i.y)
+    }
+
+    /// Set the tile of `layer` at the given position, the same as [`Self::set`] for layer 0.
+    /// Upper layers are drawn on top of layer 0 (including its overhangs) without overhangs of
+    /// their own, [`EMPTY_TILE`] draws nothing.
+    /// Like tile values, they are uploaded with the next change of the map and show up in
+    /// [`crate::debug::FastTileMapDebug::dirty_regions`].
+    /// Positions out of bounds and layers the map doesn't have are ignored.
+    pub fn set_layer(&mut self, layer: u32, x: u32, y: u32, v: u32) {
+        if layer == 0 {
+            self.set(x, y, v);
+            return;
+        }
+        if layer >= self.map.n_layers() {
+            warn_once!("set_layer() on a layer the map doesn't have is ignored");
+            return;
+        }
+        let size = self.size();
+        if x >= size.x || y >= size.y {
+            if cfg!(debug_assertions) {
+                self.check_transposed(x, y);
+            }
+            return;
+        }
+        let idx = (((layer - 1) * size.y + y) * size.x + x) as usize;
+        if self.map.layer_tiles[idx] == v {
+            return;
+        }
+        self.map.layer_tiles[idx] = v;
+        self.map.mark_uploaded(URect::new(x, y, x + 1, y + 1));
+    }
+
+    pub fn set_layer_uvec(&mut self, layer: u32, i: UVec2, v: u32) {
+        self.set_layer(layer, i.x, i.y, v)
+    }
+
+    /// Set all tiles of `layer` to `v`, eg. [`EMPTY_TILE`] to clear an upper layer.
+    pub fn fill_layer(&mut self, layer: u32, v: u32) {
+        if layer == 0 {
+            self.fill(v);
+            return;
+        }
+        if layer >= self.map.n_layers() {
+            warn_once!("fill_layer() on a layer the map doesn't have is ignored");
+            return;
+        }
+        let all = URect::from_corners(UVec2::ZERO, self.size());
+        let n = all.size().element_product() as usize;
+        let start = (layer - 1) as usize * n;
+        self.map.layer_tiles[start..start + n].fill(v);
+        self.map.mark_uploaded(all);
+    }
+}
@@ -4,7 +4,7 @@ use bevy::{
 };
 
 /// Determines how map coordinates are related to world coordinates.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
 pub struct TileProjection {
     /// Projection matrix for converting map coordinates to world coordinates.
     /// This is normalized to the tile dimensions, ie. 1.0 means full tile width/height.
@@ -13,6 +13,46 @@ pub struct TileProjection {
     /// Relative anchor point into a tile.
     /// `(0.0, 0.0)` is top left, `(1.0, 1.0)` is bottom-right
     pub tile_anchor_point: Vec2,
+
+    /// Shift of every other row or column for hexagonal maps.
+    pub stagger: TileStagger,
+}
+
+/// Staggered (hexagonal) map layouts.
+///
+/// Staggered projections map coordinates to the (unstaggered) grid of tile rectangles,
+/// tiles are the hexagons inscribed in these rectangles shifted by half a tile on every odd (or
+/// even) row or column.
+/// Map positions of staggered maps are tile coordinates plus the relative position in the
+/// rectangle of the tile, so `floor()` still gives the tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum TileStagger {
+    #[default]
+    None,
+    /// Pointy-top hexagons, odd rows are shifted right by half a tile.
+    HexRows,
+    /// Flat-top hexagons, odd columns are shifted down by half a tile.
+    HexColumns,
+    /// Pointy-top hexagons, even rows are shifted right by half a tile.
+    HexEvenRows,
+    /// Flat-top hexagons, even columns are shifted down by half a tile.
+    HexEvenColumns,
+}
+
+impl TileStagger {
+    /// Whether columns (instead of rows) are shifted, ie. the hexagons are flat-top.
+    pub fn staggers_columns(&self) -> bool {
+        matches!(self, Self::HexColumns | Self::HexEvenColumns)
+    }
+
+    /// Whether row (or column) `i` is shifted by half a tile.
+    pub fn is_shifted(&self, i: i32) -> bool {
+        match self {
+            Self::None => false,
+            Self::HexRows | Self::HexColumns => i.rem_euclid(2) == 1,
+            Self::HexEvenRows | Self::HexEvenColumns => i.rem_euclid(2) == 0,
+        }
+    }
 }
 
 /// Default projection that renders every tile as-is in a rectangular grid.
@@ -24,6 +64,7 @@ pub const IDENTITY: TileProjection = TileProjection {
         vec3(0.0, 0.0, 1.0),
     ),
     tile_anchor_point: vec2(0.0, 0.0),
+    stagger: TileStagger::None,
 };
 
 /// Assumes the tiles reperesent projections of square tiles
@@ -56,4 +97,47 @@ pub const AXONOMETRIC: TileProjection = TileProjection {
     ),
 
     tile_anchor_point: vec2(0.0, 0.5),
+    stagger: TileStagger::None,
+};
+
+/// Pointy-top hexagons: Each tile graphic is a hexagon touching the top and bottom center of the
+/// tile rectangle, rows overlap by a quarter of the tile height and odd rows are shifted right by
+/// half a tile.
+/// For regular hexagons, use a tile size of about `(sqrt(3) * r, 2 * r)`.
+pub const HEX_POINTY_TOP: TileProjection = TileProjection {
+    projection: mat3(
+        vec3(1.0, 0.0, 0.0),
+        vec3(0.0, -0.75, 0.0),
+        vec3(0.0, 0.0, 1.0),
+    ),
+    tile_anchor_point: vec2(0.0, 0.0),
+    stagger: TileStagger::HexRows,
+};
+
+/// Flat-top hexagons: Each tile graphic is a hexagon touching the left and right center of the
+/// tile rectangle, columns overlap by a quarter of the tile width and odd columns are shifted down
+/// by half a tile.
+/// For regular hexagons, use a tile size of about `(2 * r, sqrt(3) * r)`.
+pub const HEX_FLAT_TOP: TileProjection = TileProjection {
+    projection: mat3(
+        vec3(0.75, 0.0, 0.0),
+        vec3(0.0, -1.0, 0.0),
+        vec3(0.0, 0.0, 1.0),
+    ),
+    tile_anchor_point: vec2(0.0, 0.0),
+    stagger: TileStagger::HexColumns,
+};
+
+/// Like [`HEX_POINTY_TOP`], but even rows (including the first one) are shifted right by half a
+/// tile.
+pub const HEX_POINTY_TOP_EVEN: TileProjection = TileProjection {
+    stagger: TileStagger::HexEvenRows,
+    ..HEX_POINTY_TOP
+};
+
+/// Like [`HEX_FLAT_TOP`], but even columns (including the first one) are shifted down by half a
+/// tile.
+pub const HEX_FLAT_TOP_EVEN: TileProjection = TileProjection {
+    stagger: TileStagger::HexEvenColumns,
+    ..HEX_FLAT_TOP
 };
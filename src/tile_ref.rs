@@ -0,0 +1,137 @@
+use bevy::{math::uvec2, prelude::*};
+
+use super::{map::Map, plugin::Customization, preview::TileStamp};
+
+/// A tile of the atlas, either by its index or by its (column, row) position in the atlas.
+///
+/// Converting positions to indices depends on the number of atlas columns, which is only known
+/// once the atlas is loaded (or given by [`crate::map_builder::MapBuilder::with_expected_atlas_columns`]),
+/// see [`Map::tile_index`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum TileRef {
+    Index(u32),
+    ColRow(UVec2),
+}
+
+impl From<u32> for TileRef {
+    fn from(index: u32) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl From<UVec2> for TileRef {
+    fn from(colrow: UVec2) -> Self {
+        Self::ColRow(colrow)
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Number of tile columns of the atlas, or the expected number before the atlas is loaded,
+    /// if that was given to [`crate::map_builder::MapBuilder::with_expected_atlas_columns`].
+    pub fn atlas_columns(&self) -> Option<u32> {
+        match self.map_uniform.n_tiles.x {
+            0 => self.expected_atlas_columns,
+            columns => Some(columns),
+        }
+    }
+
+    /// Number of tiles in the atlas, `None` until the atlas is loaded (see [`crate::map::MapAtlasReady`]).
+    pub fn atlas_tile_count(&self) -> Option<u32> {
+        let n_tiles = self.map_uniform.n_tiles;
+        (n_tiles != UVec2::ZERO).then_some(n_tiles.x * n_tiles.y)
+    }
+
+    /// Check that all tiles of the map refer to tiles of the atlas (ignoring flags and palette
+    /// owners), otherwise return the positions and values of those that don't.
+    /// Before the atlas is loaded, there is nothing to check against and this returns `Ok`.
+    pub fn validate_indices(&self) -> Result<(), Vec<(UVec2, u32)>> {
+        let Some(count) = self.atlas_tile_count() else {
+            return Ok(());
+        };
+        let m = self.indexer();
+        let invalid: Vec<_> = m
+            .positions()
+            .map(|p| (p, m.at_uvec(p)))
+            .filter(|(_, tile)| self.map_uniform.atlas_index(*tile) >= count)
+            .collect();
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid)
+        }
+    }
+
+    /// Index of the tile at `col`, `row` of the atlas.
+    /// `None` if the number of atlas columns is not known yet or `col` is outside of the atlas.
+    pub fn index_from_colrow(&self, col: u32, row: u32) -> Option<u32> {
+        let columns = self.atlas_columns()?;
+        let rows = self.map_uniform.n_tiles.y;
+        if col >= columns || (rows > 0 && row >= rows) {
+            return None;
+        }
+        Some(row * columns + col)
+    }
+
+    /// (column, row) position of the tile `index` in the atlas, the inverse of
+    /// [`Self::index_from_colrow`].
+    pub fn colrow_from_index(&self, index: u32) -> Option<UVec2> {
+        let columns = self.atlas_columns()?;
+        let colrow = uvec2(index % columns, index / columns);
+        let rows = self.map_uniform.n_tiles.y;
+        (rows == 0 || colrow.y < rows).then_some(colrow)
+    }
+
+    /// Rectangle (in pixels) covered by the tile `index` in the atlas, eg. for showing it in a UI.
+    /// For maps with [`crate::map_builder::MapBuilder::with_atlas_tile_size_factor`], this is the
+    /// whole (bigger) atlas tile.
+    /// `None` if the atlas is not loaded yet or has no tile `index`.
+    pub fn atlas_rect(&self, index: u32) -> Option<Rect> {
+        let uniform = &self.map_uniform;
+        if index >= uniform.n_tiles.x * uniform.n_tiles.y {
+            return None;
+        }
+        let colrow = self.colrow_from_index(index)?.as_vec2();
+        let size = uniform.tile_size * uniform.atlas_tile_size_factor as f32;
+        let min = uniform.outer_padding_topleft + colrow * (size + uniform.inner_padding);
+        Some(Rect::from_corners(min, min + size))
+    }
+
+    /// Index of `tile`, see [`Self::index_from_colrow`].
+    pub fn tile_index(&self, tile: impl Into<TileRef>) -> Option<u32> {
+        match tile.into() {
+            TileRef::Index(index) => Some(index),
+            TileRef::ColRow(colrow) => self.index_from_colrow(colrow.x, colrow.y),
+        }
+    }
+
+    /// Warn if the atlas does not have the columns given to
+    /// [`crate::map_builder::MapBuilder::with_expected_atlas_columns`].
+    pub(crate) fn validate_expected_atlas_columns(&self) {
+        let Some(expected) = self.expected_atlas_columns else {
+            return;
+        };
+        let columns = self.map_uniform.n_tiles.x;
+        if columns != expected {
+            warn!(
+                "Atlas has {columns} tile columns, but {expected} were expected, \
+                 tiles given by (column, row) before loading refer to different tiles now"
+            );
+        }
+    }
+}
+
+impl TileStamp {
+    /// Stamp of the given size with tiles given by index or atlas position, row by row.
+    /// `None` if any of the tiles can not be resolved by `map`, see [`Map::tile_index`].
+    pub fn from_tile_refs<C: Customization>(
+        size: UVec2,
+        tiles: impl IntoIterator<Item = impl Into<TileRef>>,
+        map: &Map<C>,
+    ) -> Option<Self> {
+        let tiles = tiles
+            .into_iter()
+            .map(|tile| map.tile_index(tile))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self::new(size, tiles))
+    }
+}
@@ -0,0 +1,253 @@
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, Weak,
+    },
+};
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{Buffer, BufferInitDescriptor, BufferUsages},
+        renderer::{RenderDevice, RenderQueue},
+    },
+};
+
+use super::{map::Map, plugin::Customization};
+
+/// Rows of the tile data written since they were last uploaded, so a changed map only writes
+/// these rows to its GPU buffer instead of uploading all tiles again.
+///
+/// The GPU side (`slot`) is shared with the copies of the map extracted for rendering.
+/// The rows are written in the frame the bind group of a copy is prepared, together with the
+/// uniforms and user data of the same copy, so tiles and user data changed in the same frame are
+/// always rendered together.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TileUpload {
+    slot: Arc<TileUploadSlot>,
+    /// Rows written since the tiles of `slot.uploaded` (or more)
+    rows: Option<Range<u32>>,
+    /// Number of writes so far
+    serial: u64,
+}
+
+#[derive(Debug, Default)]
+struct TileUploadSlot {
+    /// `serial` of the tiles on the GPU
+    uploaded: AtomicU64,
+    gpu: Mutex<Option<GpuTiles>>,
+    /// Map asset using this slot, see [`claim_tile_uploads`]
+    owner: Mutex<Option<UntypedAssetId>>,
+}
+
+#[derive(Debug)]
+struct GpuTiles {
+    buffer: Buffer,
+    len: usize,
+    /// Changed rows (byte offset and data) for [`write_tile_uploads`]
+    pending: Vec<(u64, Vec<u8>)>,
+}
+
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // A panic while holding the lock at most causes a redundant upload
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn bytes(tiles: &[u32]) -> Vec<u8> {
+    tiles.iter().flat_map(|tile| tile.to_le_bytes()).collect()
+}
+
+impl TileUpload {
+    /// Record a write to the rows `rows`.
+    pub(crate) fn mark(&mut self, rows: Range<u32>) {
+        // Everything written before has been uploaded
+        if self.slot.uploaded.load(Ordering::Acquire) == self.serial {
+            self.rows = None;
+        }
+        self.serial += 1;
+        self.rows = Some(match self.rows.take() {
+            Some(written) => written.start.min(rows.start)..written.end.max(rows.end),
+            None => rows,
+        });
+    }
+
+    /// Rows the next upload writes, all rows if the map was never uploaded.
+    pub(crate) fn pending_rows(&self, height: u32) -> Range<u32> {
+        if lock(&self.slot.gpu).is_none() {
+            return 0..height;
+        }
+        match self.slot.uploaded.load(Ordering::Acquire) == self.serial {
+            true => 0..0,
+            false => self.rows.clone().unwrap_or(0..height),
+        }
+    }
+
+    /// Buffer holding `tiles` for binding 100. It is created with all tiles the first time and
+    /// whenever their number changes, otherwise only the rows written since the last upload
+    /// are queued for [`write_tile_uploads`].
+    ///
+    /// `None` for a copy of the map older than the tiles on the GPU: Its uniforms and user data
+    /// don't belong to these tiles, and uploading its tiles would undo newer writes.
+    pub(crate) fn buffer(
+        &self,
+        render_device: &RenderDevice,
+        tiles: &[u32],
+        width: u32,
+    ) -> Option<Buffer> {
+        let mut gpu = lock(&self.slot.gpu);
+        let uploaded = self.slot.uploaded.load(Ordering::Acquire);
+        if gpu.is_some() && uploaded > self.serial {
+            return None;
+        }
+        // Queued rows are only written for maps registered by `claim_tile_uploads`
+        let registered = lock(&self.slot.owner).is_some();
+        match gpu.as_mut() {
+            Some(gpu) if registered && gpu.len == tiles.len() => {
+                if uploaded < self.serial {
+                    let rows = self.rows.clone().unwrap_or(0..u32::MAX);
+                    let end = (rows.end as usize * width as usize).min(tiles.len());
+                    let start = (rows.start as usize * width as usize).min(end);
+                    if start < end {
+                        let data = bytes(&tiles[start..end]);
+                        gpu.pending.push(((start * 4) as u64, data));
+                    }
+                }
+            }
+            _ => {
+                let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+                    label: Some("map_texture"),
+                    contents: &bytes(tiles),
+                    usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+                });
+                *gpu = Some(GpuTiles {
+                    buffer,
+                    len: tiles.len(),
+                    pending: Vec::new(),
+                });
+            }
+        }
+        self.slot.uploaded.store(self.serial, Ordering::Release);
+        Some(gpu.as_ref().unwrap().buffer.clone())
+    }
+
+    /// The buffer created by [`Self::buffer`] if it holds `len` tiles, for maps rendering the
+    /// tiles of this one (see [`Map::minimap_builder`]).
+    pub(crate) fn shared_buffer(&self, len: usize) -> Option<Buffer> {
+        let gpu = lock(&self.slot.gpu);
+        let gpu = gpu.as_ref().filter(|gpu| gpu.len == len)?;
+        Some(gpu.buffer.clone())
+    }
+
+    /// Whether `other` uploads to the same buffer.
+    pub(crate) fn same_slot(&self, other: &TileUpload) -> bool {
+        Arc::ptr_eq(&self.slot, &other.slot)
+    }
+
+    /// Make `id` the owner of the slot if it has none yet (`Some(true)`, `Some(false)` if it
+    /// already is). `None` if the slot belongs to another map, ie. the map was cloned.
+    fn claim(&self, id: UntypedAssetId) -> Option<bool> {
+        let mut owner = lock(&self.slot.owner);
+        match *owner {
+            None => {
+                *owner = Some(id);
+                Some(true)
+            }
+            Some(owner) if owner == id => Some(false),
+            Some(_) => None,
+        }
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Upload all tiles with the next change of the map, instead of only the rows written
+    /// since the last upload.
+    ///
+    /// Only needed if the tiles on the GPU were changed in other ways than through this map,
+    /// eg. by a compute shader writing to the same buffer.
+    pub fn force_full_sync(&mut self) {
+        let height = self.map_size().y;
+        self.tile_upload.mark(0..height);
+    }
+
+    /// Rows of the tiles that are written to the GPU with the next change of the map, empty
+    /// if the tiles on the GPU are up to date.
+    /// Other buffers (eg. decals or tile colors) are always uploaded in full.
+    pub fn pending_tile_rows(&self) -> Range<u32> {
+        self.tile_upload.pending_rows(self.map_size().y)
+    }
+}
+
+/// Slots of all maps, shared between the main world and the render world.
+#[derive(Resource)]
+pub(crate) struct MapTileUploads<C: Customization> {
+    slots: Arc<Mutex<Vec<Weak<TileUploadSlot>>>>,
+    _customization: std::marker::PhantomData<C>,
+}
+
+impl<C: Customization> Default for MapTileUploads<C> {
+    fn default() -> Self {
+        Self {
+            slots: default(),
+            _customization: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<C: Customization> Clone for MapTileUploads<C> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            _customization: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Register the slots of new maps with [`MapTileUploads`], giving cloned maps their own slot
+/// (their tiles are uploaded in full once).
+pub(crate) fn claim_tile_uploads<C: Customization>(
+    mut events: EventReader<AssetEvent<Map<C>>>,
+    mut maps: ResMut<Assets<Map<C>>>,
+    uploads: Res<MapTileUploads<C>>,
+) {
+    for event in events.read() {
+        let (AssetEvent::Added { id } | AssetEvent::Modified { id }) = *event else {
+            continue;
+        };
+        let Some(map) = maps.get(id) else {
+            continue;
+        };
+        let claimed = match map.tile_upload.claim(id.untyped()) {
+            Some(claimed) => claimed,
+            None => {
+                let Some(map) = maps.get_mut(id) else {
+                    continue;
+                };
+                map.tile_upload = TileUpload::default();
+                map.tile_upload.claim(id.untyped()).unwrap_or(false)
+            }
+        };
+        if claimed {
+            let slot = Arc::downgrade(&maps.get(id).unwrap().tile_upload.slot);
+            lock(&uploads.slots).push(slot);
+        }
+    }
+}
+
+/// Write the rows queued by [`TileUpload::buffer`] while preparing the map bind groups.
+pub(crate) fn write_tile_uploads<C: Customization>(
+    uploads: Res<MapTileUploads<C>>,
+    render_queue: Res<RenderQueue>,
+) {
+    lock(&uploads.slots).retain(|slot| {
+        let Some(slot) = slot.upgrade() else {
+            return false;
+        };
+        if let Some(gpu) = lock(&slot.gpu).as_mut() {
+            for (offset, data) in gpu.pending.drain(..) {
+                render_queue.write_buffer(&gpu.buffer, offset, &data);
+            }
+        }
+        true
+    });
+}
@@ -0,0 +1,405 @@
+//! Import of maps made with [Tiled](https://www.mapeditor.org/) (`tiled` feature).
+//!
+//! [`TiledMapLoader`] loads `.tmx` files (with their external `.tsx` tilesets) as [`TiledMap`]s,
+//! holding one [`Map`] per tile layer. Spawn a [`TiledMapBundle`] to render them, the layers are
+//! spawned as children once the map is loaded (see [`TiledMapLayers`]).
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_fast_tilemap::prelude::*;
+//! fn startup(mut commands: Commands, asset_server: Res<AssetServer>) {
+//!     commands.spawn(TiledMapBundle {
+//!         tiled_map: asset_server.load("tiled/sample.tmx"),
+//!         ..default()
+//!     });
+//! }
+//! ```
+//!
+//! Tiled's global tile IDs become atlas indices of the tileset of the tile, the flip flags use
+//! the same bits as [`TILE_FLIP_X`], [`TILE_FLIP_Y`] and [`TILE_ROTATE`]. A layer can use up to
+//! [`MAX_ATLASES`] tilesets (each an atlas of the layer map, see [`tile_in_atlas`]), tilesets
+//! other than the first one of a layer can not have a margin. Empty tiles are [`EMPTY_TILE`].
+//!
+//! Orthogonal maps use [`IDENTITY`], isometric maps [`AXONOMETRIC`]. Staggered and hexagonal
+//! maps, infinite maps and image collection tilesets are not supported.
+
+use std::{
+    collections::HashMap,
+    fmt, io,
+    path::{Component, Path, PathBuf},
+};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, ReadAssetBytesError},
+    prelude::*,
+};
+use tiled::{LayerType, ObjectShape, Orientation, TileLayer};
+
+use super::{
+    additional_atlases::{tile_in_atlas, MAX_ATLASES},
+    bundle::MapBundleManaged,
+    map::Map,
+    plugin::MapSystems,
+    tile_flags::{TILE_FLIP_X, TILE_FLIP_Y, TILE_ROTATE},
+    tile_layers::EMPTY_TILE,
+    tile_projection::{AXONOMETRIC, IDENTITY},
+};
+
+/// A map loaded from a Tiled `.tmx` file, see [`TiledMapLoader`].
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct TiledMap {
+    /// Size of the map in tiles
+    pub map_size: UVec2,
+    /// Tile layers, bottom to top
+    pub layers: Vec<TiledLayer>,
+    pub object_layers: Vec<TiledObjectLayer>,
+}
+
+/// A tile layer of a [`TiledMap`].
+#[derive(Debug, Clone)]
+pub struct TiledLayer {
+    pub name: String,
+    /// Map of the layer, a labeled asset (`layer0`, `layer1`, ...) of the `.tmx` file
+    pub map: Handle<Map>,
+    /// Translation of the layer relative to the [`TiledMapBundle`]: The layer offset of Tiled
+    /// and the index of the layer as z.
+    pub translation: Vec3,
+    pub visible: bool,
+}
+
+/// An object layer of a [`TiledMap`].
+#[derive(Debug, Clone)]
+pub struct TiledObjectLayer {
+    pub name: String,
+    pub objects: Vec<TiledObject>,
+}
+
+/// Rectangle, ellipse or point object of an object layer (other shapes are skipped).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TiledObject {
+    pub name: String,
+    /// Bounds of the object in map coordinates (tiles, see [`Map::map_to_world`]), empty for
+    /// points
+    pub rect: Rect,
+}
+
+/// Tiled map, its layers are spawned as children with [`MapBundleManaged`] once it is loaded.
+#[derive(Bundle, Clone, Default)]
+pub struct TiledMapBundle {
+    pub tiled_map: Handle<TiledMap>,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+    pub visibility: Visibility,
+    pub inherited_visibility: InheritedVisibility,
+    pub view_visibility: ViewVisibility,
+}
+
+/// Entities of the tile layers of a [`TiledMapBundle`] (bottom to top), inserted when they are
+/// spawned.
+#[derive(Component, Debug, Clone)]
+pub struct TiledMapLayers(pub Vec<Entity>);
+
+/// Object layers of a [`TiledMapBundle`], inserted along with [`TiledMapLayers`].
+#[derive(Component, Debug, Clone)]
+pub struct TiledObjects(pub Vec<TiledObjectLayer>);
+
+#[derive(Debug)]
+pub enum TiledMapLoaderError {
+    Io(io::Error),
+    ReadAsset(ReadAssetBytesError),
+    Tiled(tiled::Error),
+    /// The map uses features that can't be imported.
+    Unsupported(String),
+}
+
+impl fmt::Display for TiledMapLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read Tiled map: {e}"),
+            Self::ReadAsset(e) => write!(f, "could not read Tiled tileset: {e}"),
+            Self::Tiled(e) => write!(f, "could not parse Tiled map: {e}"),
+            Self::Unsupported(what) => write!(f, "Tiled map is not supported: {what}"),
+        }
+    }
+}
+
+impl std::error::Error for TiledMapLoaderError {}
+
+fn unsupported(what: impl Into<String>) -> TiledMapLoaderError {
+    TiledMapLoaderError::Unsupported(what.into())
+}
+
+/// Files read so far, for the `tiled` crate (which reads synchronously).
+struct FileBytes<'a>(&'a HashMap<PathBuf, Vec<u8>>);
+
+impl<'a> tiled::ResourceReader for FileBytes<'a> {
+    type Resource = &'a [u8];
+    type Error = io::Error;
+
+    fn read_from(&mut self, path: &Path) -> Result<&'a [u8], io::Error> {
+        match self.0.get(path) {
+            Some(bytes) => Ok(bytes),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "not read yet")),
+        }
+    }
+}
+
+/// `path` without `.` and `..` components, as asset paths are not normalized.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            c => normalized.push(c),
+        }
+    }
+    normalized
+}
+
+/// Loader for Tiled `.tmx` files, see [`TiledMap`].
+#[derive(Default)]
+pub struct TiledMapLoader;
+
+impl AssetLoader for TiledMapLoader {
+    type Asset = TiledMap;
+    type Settings = ();
+    type Error = TiledMapLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<TiledMap, TiledMapLoaderError> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(TiledMapLoaderError::Io)?;
+        let path = load_context.path().to_path_buf();
+        let mut files = HashMap::from([(path.clone(), bytes)]);
+
+        // Asset reads are async, so read the tilesets the map asks for one by one and parse again
+        let tmx = loop {
+            let result = tiled::Loader::with_reader(FileBytes(&files)).load_tmx_map(&path);
+            match result {
+                Ok(tmx) => break tmx,
+                Err(tiled::Error::ResourceLoadingError { path, .. })
+                    if !files.contains_key(&path) =>
+                {
+                    let bytes = load_context
+                        .read_asset_bytes(normalize(&path))
+                        .await
+                        .map_err(TiledMapLoaderError::ReadAsset)?;
+                    files.insert(path, bytes);
+                }
+                Err(e) => return Err(TiledMapLoaderError::Tiled(e)),
+            }
+        };
+        import(&tmx, load_context)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmx"]
+    }
+}
+
+fn import(
+    tmx: &tiled::Map,
+    load_context: &mut LoadContext,
+) -> Result<TiledMap, TiledMapLoaderError> {
+    let map_size = UVec2::new(tmx.width, tmx.height);
+    let grid_tile_size = UVec2::new(tmx.tile_width, tmx.tile_height).as_vec2();
+    let (projection, object_unit) = match tmx.orientation {
+        Orientation::Orthogonal => (IDENTITY, grid_tile_size),
+        // Object coordinates of isometric maps count tile heights along both axes
+        Orientation::Isometric => (AXONOMETRIC, Vec2::splat(grid_tile_size.y)),
+        orientation => return Err(unsupported(format!("{orientation:?} orientation"))),
+    };
+
+    let mut tiled_map = TiledMap {
+        map_size,
+        layers: Vec::new(),
+        object_layers: Vec::new(),
+    };
+    for layer in tmx.layers() {
+        match layer.layer_type() {
+            LayerType::Tiles(TileLayer::Finite(tiles)) => {
+                let z = tiled_map.layers.len() as f32;
+                let translation = Vec3::new(layer.offset_x, -layer.offset_y, z);
+
+                // Tilesets used by the layer, in order of their index
+                let mut tilesets: Vec<usize> = Vec::new();
+                let mut values = Vec::with_capacity((map_size.x * map_size.y) as usize);
+                for y in 0..map_size.y as i32 {
+                    for x in 0..map_size.x as i32 {
+                        let Some(tile) = tiles.get_tile(x, y) else {
+                            values.push(None);
+                            continue;
+                        };
+                        if !tilesets.contains(&tile.tileset_index()) {
+                            tilesets.push(tile.tileset_index());
+                        }
+                        let mut flags = 0;
+                        for (flipped, flag) in [
+                            (tile.flip_h, TILE_FLIP_X),
+                            (tile.flip_v, TILE_FLIP_Y),
+                            (tile.flip_d, TILE_ROTATE),
+                        ] {
+                            if flipped {
+                                flags |= flag;
+                            }
+                        }
+                        values.push(Some((tile.tileset_index(), tile.id(), flags)));
+                    }
+                }
+                tilesets.sort_unstable();
+                if tilesets.len() > MAX_ATLASES as usize {
+                    return Err(unsupported(format!(
+                        "layer {} uses more than {MAX_ATLASES} tilesets",
+                        layer.name
+                    )));
+                }
+
+                let mut builder = None;
+                for &index in &tilesets {
+                    let tileset = &tmx.tilesets()[index];
+                    let Some(image) = tileset.image.as_ref() else {
+                        return Err(unsupported(format!(
+                            "tileset {} is an image collection",
+                            tileset.name
+                        )));
+                    };
+                    let handle: Handle<Image> = load_context.load(normalize(&image.source));
+                    let tile_size = UVec2::new(tileset.tile_width, tileset.tile_height).as_vec2();
+                    let spacing = Vec2::splat(tileset.spacing as f32);
+                    let margin = Vec2::splat(tileset.margin as f32);
+                    builder = Some(match builder {
+                        None => {
+                            // Whatever is left right of and below the tiles
+                            let columns = tileset.columns.max(1);
+                            let rows = tileset.tilecount.div_ceil(columns).max(1);
+                            let n_tiles = UVec2::new(columns, rows).as_vec2();
+                            let image_size = Vec2::new(image.width as f32, image.height as f32);
+                            let used = margin + n_tiles * (tile_size + spacing) - spacing;
+                            let bottomright = (image_size - used).max(Vec2::ZERO);
+                            Map::builder(map_size, handle, tile_size)
+                                .with_padding(spacing, margin, bottomright)
+                                .with_projection(projection)
+                        }
+                        Some(builder) => {
+                            if tileset.margin != 0 {
+                                return Err(unsupported(format!(
+                                    "tileset {} has a margin and is not the first tileset of \
+                                     layer {}",
+                                    tileset.name, layer.name
+                                )));
+                            }
+                            builder.with_additional_atlas(handle, tile_size, spacing)
+                        }
+                    });
+                }
+                let builder = match builder {
+                    Some(builder) => builder,
+                    // Layer without tiles
+                    None => Map::builder(map_size, default(), grid_tile_size)
+                        .with_projection(projection),
+                };
+
+                let mut map = builder.build_and_set(|p| {
+                    let value = values[(p.y * map_size.x + p.x) as usize];
+                    match value {
+                        Some((tileset, id, flags)) => {
+                            let atlas = tilesets.iter().position(|t| *t == tileset).unwrap_or(0);
+                            tile_in_atlas(atlas as u32, id) | flags
+                        }
+                        None => EMPTY_TILE,
+                    }
+                });
+                if layer.opacity < 1.0 {
+                    map.set_color_filter(Color::WHITE.with_alpha(layer.opacity));
+                }
+
+                let label = format!("layer{}", tiled_map.layers.len());
+                tiled_map.layers.push(TiledLayer {
+                    name: layer.name.clone(),
+                    map: load_context.add_labeled_asset(label, map),
+                    translation,
+                    visible: layer.visible,
+                });
+            }
+            LayerType::Tiles(_) => return Err(unsupported("infinite maps")),
+            LayerType::Objects(objects) => {
+                let mut object_layer = TiledObjectLayer {
+                    name: layer.name.clone(),
+                    objects: Vec::new(),
+                };
+                for object in objects.objects() {
+                    let size = match object.shape {
+                        ObjectShape::Rect { width, height }
+                        | ObjectShape::Ellipse { width, height } => Vec2::new(width, height),
+                        ObjectShape::Point(..) => Vec2::ZERO,
+                        _ => continue,
+                    };
+                    let min = Vec2::new(object.x, object.y) / object_unit;
+                    object_layer.objects.push(TiledObject {
+                        name: object.name.clone(),
+                        rect: Rect::from_corners(min, min + size / object_unit),
+                    });
+                }
+                tiled_map.object_layers.push(object_layer);
+            }
+            // Image and group layers
+            _ => {}
+        }
+    }
+    Ok(tiled_map)
+}
+
+/// Spawn the layers of loaded [`TiledMapBundle`]s as children.
+pub fn spawn_tiled_maps(
+    mut commands: Commands,
+    tiled_maps: Res<Assets<TiledMap>>,
+    new: Query<(Entity, &Handle<TiledMap>), Without<TiledMapLayers>>,
+) {
+    for (entity, handle) in new.iter() {
+        let Some(tiled_map) = tiled_maps.get(handle) else {
+            continue;
+        };
+        let mut layers = Vec::new();
+        for layer in &tiled_map.layers {
+            let visibility = match layer.visible {
+                true => Visibility::Inherited,
+                false => Visibility::Hidden,
+            };
+            let child = commands
+                .spawn(MapBundleManaged {
+                    material: layer.map.clone(),
+                    transform: Transform::from_translation(layer.translation),
+                    visibility,
+                    ..default()
+                })
+                .id();
+            layers.push(child);
+        }
+        commands.entity(entity).push_children(&layers).insert((
+            TiledMapLayers(layers),
+            TiledObjects(tiled_map.object_layers.clone()),
+        ));
+    }
+}
+
+/// Registers [`TiledMapLoader`] and spawns [`TiledMapBundle`]s, in addition to
+/// [`crate::plugin::FastTileMapPlugin`].
+pub struct TiledMapPlugin;
+
+impl Plugin for TiledMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<TiledMap>()
+            .init_asset_loader::<TiledMapLoader>()
+            .add_systems(Update, spawn_tiled_maps.in_set(MapSystems::Update));
+    }
+}
@@ -0,0 +1,337 @@
+use std::{
+    marker::PhantomData,
+    num::NonZeroU64,
+    ops::Range,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{
+            encase::UniformBuffer, Buffer, BufferDescriptor, BufferInitDescriptor, BufferUsages,
+            ShaderType,
+        },
+        renderer::{RenderDevice, RenderQueue},
+    },
+};
+
+use super::{map::Map, map_uniform::MapUniform, plugin::Customization, tile_upload::lock};
+
+/// Smallest number of slots the GPU buffer is created with.
+const MIN_CAPACITY: u32 = 64;
+
+/// The [`MapUniform`]s of all maps of a customization, packed into a single uniform buffer.
+///
+/// Each map binds its slot of the buffer at an offset instead of creating a buffer of its own
+/// whenever it changes. The slots written while preparing the map bind groups are written to
+/// the GPU with a single `write_buffer` per frame ([`write_map_uniforms`]).
+///
+/// Shared between the main world (which assigns the slots) and the render world.
+#[derive(Resource)]
+pub struct MapUniformBatch<C: Customization> {
+    batch: Arc<UniformBatch>,
+    _customization: PhantomData<C>,
+}
+
+impl<C: Customization> Default for MapUniformBatch<C> {
+    fn default() -> Self {
+        Self {
+            batch: default(),
+            _customization: PhantomData,
+        }
+    }
+}
+
+impl<C: Customization> Clone for MapUniformBatch<C> {
+    fn clone(&self) -> Self {
+        Self {
+            batch: self.batch.clone(),
+            _customization: PhantomData,
+        }
+    }
+}
+
+impl<C: Customization> MapUniformBatch<C> {
+    /// Number of writes to the GPU buffer so far.
+    pub fn writes(&self) -> u64 {
+        self.batch.writes.load(Ordering::Acquire)
+    }
+}
+
+#[derive(Debug, Default)]
+struct UniformBatch {
+    packed: Mutex<PackedUniforms>,
+    /// Number of `write_buffer` calls so far
+    writes: AtomicU64,
+}
+
+/// CPU side of a [`UniformBatch`].
+#[derive(Debug, Default)]
+struct PackedUniforms {
+    /// Slots of removed maps
+    free: Vec<u32>,
+    /// Number of slots ever assigned
+    len: u32,
+    /// Bytes per slot, a multiple of the uniform offset alignment
+    stride: u64,
+    /// Uniforms of all slots, `stride` bytes each
+    data: Vec<u8>,
+    /// Slots packed since the last write
+    dirty: Option<Range<u32>>,
+    gpu: Option<GpuUniforms>,
+}
+
+#[derive(Debug)]
+struct GpuUniforms {
+    buffer: Buffer,
+    capacity: u32,
+}
+
+impl PackedUniforms {
+    fn assign(&mut self) -> u32 {
+        self.free.pop().unwrap_or_else(|| {
+            self.len += 1;
+            self.len - 1
+        })
+    }
+
+    /// Copy `bytes` to `slot`, to be written to the GPU with the next [`Self::take_write`].
+    fn pack(&mut self, slot: u32, bytes: &[u8]) {
+        let start = (slot as u64 * self.stride) as usize;
+        let end = start + self.stride as usize;
+        if self.data.len() < end {
+            self.data.resize(end, 0);
+        }
+        self.data[start..start + bytes.len()].copy_from_slice(bytes);
+        self.dirty = Some(match self.dirty.take() {
+            Some(dirty) => dirty.start.min(slot)..dirty.end.max(slot + 1),
+            None => slot..slot + 1,
+        });
+    }
+
+    /// Byte offset and data of all slots packed since the last call, one range for all of them.
+    fn take_write(&mut self) -> Option<(u64, &[u8])> {
+        let dirty = self.dirty.take()?;
+        let start = (dirty.start as u64 * self.stride) as usize;
+        // Slots assigned but never packed are not in `data` yet
+        let end = ((dirty.end as u64 * self.stride) as usize).min(self.data.len());
+        let start = start.min(end);
+        Some((start as u64, &self.data[start..end]))
+    }
+}
+
+/// Slot of a map in [`MapUniformBatch`], shared with the copies of the map extracted for
+/// rendering. The slot is freed when the map and all of its copies are dropped.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MapUniformSlot(Arc<UniformSlot>);
+
+#[derive(Debug, Default)]
+struct UniformSlot {
+    /// Batch and slot, see [`claim_uniform_slots`]
+    assigned: Mutex<Option<(Arc<UniformBatch>, u32)>>,
+    /// Map asset using this slot
+    owner: Mutex<Option<UntypedAssetId>>,
+}
+
+impl Drop for UniformSlot {
+    fn drop(&mut self) {
+        let assigned = self.assigned.get_mut().unwrap_or_else(|e| e.into_inner());
+        if let Some((batch, slot)) = assigned.take() {
+            lock(&batch.packed).free.push(slot);
+        }
+    }
+}
+
+/// Buffer holding the uniform of a map at `offset`, `size` bytes long.
+pub(crate) struct UniformBinding {
+    pub(crate) buffer: Buffer,
+    pub(crate) offset: u64,
+    pub(crate) size: NonZeroU64,
+}
+
+impl UniformBinding {
+    /// A buffer of its own holding `uniform`.
+    pub(crate) fn standalone(render_device: &RenderDevice, uniform: &MapUniform) -> Self {
+        let mut bytes = UniformBuffer::new(Vec::<u8>::new());
+        bytes.write(uniform).unwrap();
+        let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("map_uniform"),
+            contents: bytes.as_ref(),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+        Self {
+            buffer,
+            offset: 0,
+            size: MapUniform::min_size(),
+        }
+    }
+}
+
+impl MapUniformSlot {
+    /// Make `id` the owner of the slot and assign it a slot of `batch` if it has none yet.
+    /// `None` if the slot belongs to another map, ie. the map was cloned.
+    fn claim(&self, id: UntypedAssetId, batch: &Arc<UniformBatch>) -> Option<()> {
+        let mut owner = lock(&self.0.owner);
+        match *owner {
+            Some(owner) if owner != id => return None,
+            Some(_) => {}
+            None => *owner = Some(id),
+        }
+        let mut assigned = lock(&self.0.assigned);
+        if assigned.is_none() {
+            let slot = lock(&batch.packed).assign();
+            *assigned = Some((batch.clone(), slot));
+        }
+        Some(())
+    }
+
+    /// Pack `uniform` into the batch buffer (written by [`write_map_uniforms`] later this frame)
+    /// and return its binding.
+    ///
+    /// `None` if the map has no slot, ie. it was not added to `Assets<Map<C>>` of an app with the
+    /// plugin of its customization.
+    pub(crate) fn bind(
+        &self,
+        render_device: &RenderDevice,
+        uniform: &MapUniform,
+    ) -> Option<UniformBinding> {
+        let assigned = lock(&self.0.assigned);
+        let (batch, slot) = assigned.as_ref()?;
+        let mut packed = lock(&batch.packed);
+
+        let size = MapUniform::min_size();
+        if packed.stride == 0 {
+            let alignment = render_device.limits().min_uniform_buffer_offset_alignment as u64;
+            packed.stride = size.get().div_ceil(alignment) * alignment;
+        }
+        let mut bytes = UniformBuffer::new(Vec::<u8>::new());
+        bytes.write(uniform).ok()?;
+        packed.pack(*slot, bytes.as_ref());
+
+        // A new buffer for slots beyond the capacity. Maps bound to the old buffer keep it
+        // until they change, which binds them to the new one.
+        if packed
+            .gpu
+            .as_ref()
+            .map_or(true, |gpu| gpu.capacity <= *slot)
+        {
+            let capacity = packed.len.next_power_of_two().max(MIN_CAPACITY);
+            let buffer = render_device.create_buffer(&BufferDescriptor {
+                label: Some("map_uniforms"),
+                size: capacity as u64 * packed.stride,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            packed.gpu = Some(GpuUniforms { buffer, capacity });
+            // Upload all slots to the new buffer
+            let len = packed.len;
+            packed.dirty = Some(0..len);
+        }
+
+        Some(UniformBinding {
+            buffer: packed.gpu.as_ref()?.buffer.clone(),
+            offset: *slot as u64 * packed.stride,
+            size,
+        })
+    }
+}
+
+/// Assign a slot of [`MapUniformBatch`] to new maps, giving cloned maps their own slot.
+pub(crate) fn claim_uniform_slots<C: Customization>(
+    mut events: EventReader<AssetEvent<Map<C>>>,
+    mut maps: ResMut<Assets<Map<C>>>,
+    batch: Res<MapUniformBatch<C>>,
+) {
+    for event in events.read() {
+        let (AssetEvent::Added { id } | AssetEvent::Modified { id }) = *event else {
+            continue;
+        };
+        let Some(map) = maps.get(id) else {
+            continue;
+        };
+        if map.uniform_slot.claim(id.untyped(), &batch.batch).is_some() {
+            continue;
+        }
+        let Some(map) = maps.get_mut(id) else {
+            continue;
+        };
+        map.uniform_slot = MapUniformSlot::default();
+        map.uniform_slot.claim(id.untyped(), &batch.batch);
+    }
+}
+
+/// Write the uniforms packed by [`MapUniformSlot::bind`] while preparing the map bind groups,
+/// with a single write for all maps.
+pub(crate) fn write_map_uniforms<C: Customization>(
+    batch: Res<MapUniformBatch<C>>,
+    render_queue: Res<RenderQueue>,
+) {
+    let mut packed = lock(&batch.batch.packed);
+    let Some(buffer) = packed.gpu.as_ref().map(|gpu| gpu.buffer.clone()) else {
+        return;
+    };
+    if let Some((offset, data)) = packed.take_write() {
+        render_queue.write_buffer(&buffer, offset, data);
+        batch.batch.writes.fetch_add(1, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packed() -> PackedUniforms {
+        PackedUniforms {
+            stride: 256,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn all_packed_slots_are_written_at_once() {
+        let mut packed = packed();
+        let slots: Vec<u32> = (0..300).map(|_| packed.assign()).collect();
+        for &slot in slots.iter().rev().step_by(7) {
+            packed.pack(slot, &[slot as u8; 16]);
+        }
+        // Slots 5, 12, .., 299 in one range
+        let (offset, data) = packed.take_write().unwrap();
+        assert_eq!(offset, 5 * 256);
+        assert_eq!(data.len(), 295 * 256);
+        assert_eq!(&data[..16], &[5; 16]);
+        assert_eq!(&data[(294 * 256)..(294 * 256 + 16)], &[299u32 as u8; 16]);
+        assert!(packed.take_write().is_none());
+
+        packed.pack(7, &[1; 4]);
+        let (offset, data) = packed.take_write().unwrap();
+        assert_eq!((offset, data.len()), (7 * 256, 256));
+    }
+
+    #[test]
+    fn slots_are_reused_after_drop() {
+        let batch = Arc::new(UniformBatch::default());
+        let id = AssetId::<Map>::from(bevy::asset::AssetIndex::from_bits(1)).untyped();
+        let other = AssetId::<Map>::from(bevy::asset::AssetIndex::from_bits(2)).untyped();
+
+        let a = MapUniformSlot::default();
+        assert_eq!(a.claim(id, &batch), Some(()));
+        // Claiming again keeps the slot, a clone used by another map is rejected
+        assert_eq!(a.clone().claim(id, &batch), Some(()));
+        assert_eq!(a.clone().claim(other, &batch), None);
+        assert_eq!(lock(&batch.packed).len, 1);
+
+        let b = MapUniformSlot::default();
+        b.claim(other, &batch);
+        assert_eq!(lock(&batch.packed).len, 2);
+        drop(a);
+        assert_eq!(lock(&batch.packed).free, vec![0]);
+        let c = MapUniformSlot::default();
+        c.claim(id, &batch);
+        assert_eq!(lock(&batch.packed).len, 2);
+        assert!(lock(&batch.packed).free.is_empty());
+    }
+}
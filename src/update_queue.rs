@@ -0,0 +1,190 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use bevy::prelude::*;
+
+use super::{map::Map, plugin::Customization};
+
+/// Write of a single tile, see [`MapUpdateQueue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileWrite {
+    pub pos: UVec2,
+    pub value: u32,
+}
+
+impl TileWrite {
+    pub fn new(pos: UVec2, value: u32) -> Self {
+        Self { pos, value }
+    }
+}
+
+/// What [`MapUpdateQueue::push`] does when the queue is full,
+/// see [`crate::map_builder::MapBuilder::with_update_queue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueueOverflow {
+    /// Never full.
+    #[default]
+    Unbounded,
+    /// Hold at most this many writes, further writes are rejected.
+    Reject(usize),
+    /// Hold at most this many writes, the oldest ones are dropped for further writes.
+    DropOldest(usize),
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    writes: VecDeque<TileWrite>,
+    overflow: QueueOverflow,
+    dropped: u64,
+}
+
+impl QueueState {
+    fn push(&mut self, write: TileWrite) -> bool {
+        let accepted = match self.overflow {
+            QueueOverflow::Unbounded => true,
+            QueueOverflow::Reject(capacity) => self.writes.len() < capacity,
+            QueueOverflow::DropOldest(capacity) => {
+                while self.writes.len() >= capacity.max(1) {
+                    self.writes.pop_front();
+                    self.dropped += 1;
+                }
+                true
+            }
+        };
+        match accepted {
+            true => self.writes.push_back(write),
+            false => self.dropped += 1,
+        }
+        accepted
+    }
+}
+
+/// Handle for writing tiles of a map from any thread, see [`Map::update_queue`].
+///
+/// Writes are applied to the map by [`apply_map_update_queues`] in the order they were pushed
+/// (per map), so they show up in the same frame if pushed before
+/// [`crate::plugin::MapSystems::Prepare`] and in the next frame otherwise.
+/// Out of bounds writes are ignored like [`crate::map::MapIndexerMut::set`] does.
+///
+/// Handles are cheap to clone, all clones push into the same queue.
+#[derive(Clone, Default)]
+pub struct MapUpdateQueue(Arc<Mutex<QueueState>>);
+
+impl MapUpdateQueue {
+    fn state(&self) -> MutexGuard<'_, QueueState> {
+        // A panic while holding the lock can not leave the queue inconsistent
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Queue a write. Returns false if the write was rejected by [`QueueOverflow::Reject`].
+    pub fn push(&self, write: TileWrite) -> bool {
+        self.state().push(write)
+    }
+
+    /// Queue several writes at once (taking the lock once).
+    /// Returns the number of writes accepted, see [`Self::push`].
+    pub fn push_all(&self, writes: impl IntoIterator<Item = TileWrite>) -> usize {
+        let mut state = self.state();
+        writes
+            .into_iter()
+            .filter(|write| state.push(*write))
+            .count()
+    }
+
+    /// Queue writing `value` to all tiles of `rect` (`max` exclusive).
+    pub fn push_rect(&self, rect: URect, value: u32) -> usize {
+        self.push_all((rect.min.y..rect.max.y).flat_map(|y| {
+            (rect.min.x..rect.max.x).map(move |x| TileWrite::new(UVec2::new(x, y), value))
+        }))
+    }
+
+    /// Number of writes waiting to be applied.
+    pub fn len(&self) -> usize {
+        self.state().writes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of writes rejected or dropped because the queue was full.
+    pub fn dropped(&self) -> u64 {
+        self.state().dropped
+    }
+
+    pub(crate) fn reserve(&self, writes: usize) {
+        self.state().writes.reserve(writes);
+    }
+
+    fn set_overflow(&self, overflow: QueueOverflow) {
+        self.state().overflow = overflow;
+    }
+}
+
+impl fmt::Debug for MapUpdateQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state();
+        f.debug_struct("MapUpdateQueue")
+            .field("len", &state.writes.len())
+            .field("overflow", &state.overflow)
+            .finish()
+    }
+}
+
+/// Update queue of a map. Clones of a map get a queue of their own,
+/// so writes are only applied to the map the handle was taken from.
+#[derive(Debug, Default)]
+pub(crate) struct MapUpdates {
+    queue: MapUpdateQueue,
+}
+
+impl Clone for MapUpdates {
+    fn clone(&self) -> Self {
+        let updates = Self::default();
+        updates.queue.set_overflow(self.queue.state().overflow);
+        updates
+    }
+}
+
+impl<C: Customization> Map<C> {
+    /// Handle for writing tiles of this map from other threads, eg. a simulation or network
+    /// thread, without routing the writes through a system.
+    pub fn update_queue(&self) -> MapUpdateQueue {
+        self.updates.queue.clone()
+    }
+
+    /// See [`crate::map_builder::MapBuilder::with_update_queue`].
+    pub fn set_update_queue_overflow(&mut self, overflow: QueueOverflow) {
+        self.updates.queue.set_overflow(overflow);
+    }
+
+    /// Apply the writes queued with [`Self::update_queue`] now.
+    /// This is done by [`apply_map_update_queues`] with the plugin.
+    /// The queue stays locked while applying, so writes are not copied (or allocated) again.
+    pub fn apply_update_queue(&mut self) {
+        let queue = self.updates.queue.clone();
+        let mut state = queue.state();
+        let mut indexer = self.indexer_mut();
+        for write in state.writes.drain(..) {
+            indexer.set(write.pos.x, write.pos.y, write.value);
+        }
+    }
+}
+
+/// Apply the queued writes of all maps, see [`Map::update_queue`].
+/// Only maps with queued writes are accessed mutably.
+pub fn apply_map_update_queues<C: Customization>(mut maps: ResMut<Assets<Map<C>>>) {
+    let pending: Vec<_> = maps
+        .iter()
+        .filter(|(_, map)| !map.updates.queue.is_empty())
+        .map(|(id, _)| id)
+        .collect();
+    for id in pending {
+        if let Some(map) = maps.get_mut(id) {
+            map.apply_update_queue();
+        }
+    }
+}
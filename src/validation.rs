@@ -0,0 +1,223 @@
+use std::any::type_name;
+
+use bevy::{
+    prelude::*,
+    sprite::Mesh2dHandle,
+    utils::{HashMap, HashSet},
+};
+
+use super::{
+    map::{Map, MapAttributes, MapLoading, MeshManagedByMap},
+    plugin::{Customization, NoCustomization},
+};
+
+/// Common mistakes when setting up maps, detected with the `validation` feature
+/// (on by default). Each is logged as a warning once and sent as [`MapMisuseDetected`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapMisuse {
+    /// A map entity with [`MeshManagedByMap`] (eg. spawned with
+    /// [`crate::bundle::MapBundleManaged`]) got a mesh of its own, which is replaced by the map
+    /// mesh once the map is loaded. Use [`crate::bundle::MapBundleUnmanaged`] for custom meshes.
+    ManagedMeshReplaced,
+    /// The plugin for this customization was added more than once, the extra ones are ignored.
+    PluginAddedTwice { customization: &'static str },
+    /// Maps of this customization exist (as assets or handles on entities), but its plugin
+    /// ([`crate::plugin::CustomFastTileMapPlugin`]) was never added, so they don't render.
+    PluginMissing { customization: String },
+    /// [`MapAttributes::mix_color`] has a different number of entries than the mesh has
+    /// vertices. Missing entries are white, extra ones are ignored.
+    MixColorLength { entries: usize, vertices: usize },
+}
+
+/// Sent for each [`MapMisuse`] detected, `entity` is the map entity if there is one.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct MapMisuseDetected {
+    pub entity: Option<Entity>,
+    pub misuse: MapMisuse,
+}
+
+impl MapMisuseDetected {
+    fn warn(&self) {
+        let entity = self
+            .entity
+            .map(|e| format!("Map entity {e}: "))
+            .unwrap_or_default();
+        match &self.misuse {
+            MapMisuse::ManagedMeshReplaced => warn!(
+                "{entity}The mesh is managed by the map (MapBundleManaged), but the entity got a \
+                 Mesh2dHandle of its own, which will be replaced by the map mesh. \
+                 Use MapBundleUnmanaged for custom meshes."
+            ),
+            MapMisuse::PluginAddedTwice { customization } => warn!(
+                "{entity}The map plugin for {customization} was added more than once, \
+                 only the first one is used. Remove the extra add_plugins() call."
+            ),
+            MapMisuse::PluginMissing { customization } => warn!(
+                "{entity}Maps of {customization} exist, but CustomFastTileMapPlugin::<{}> was \
+                 never added, so they won't render. Add it to the app.",
+                customization.rsplit("::").next().unwrap_or(customization)
+            ),
+            MapMisuse::MixColorLength { entries, vertices } => warn!(
+                "{entity}MapAttributes::mix_color has {entries} entries, but the mesh has \
+                 {vertices} vertices. Give one color per vertex."
+            ),
+        }
+    }
+}
+
+/// Registers the checks shared by all customizations, added by the map plugin.
+pub struct MapValidationPlugin;
+
+impl Plugin for MapValidationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MapMisuseDetected>()
+            .init_resource::<MapValidation>()
+            .add_systems(Last, validate_map_plugins);
+    }
+}
+
+/// Customizations with a plugin, by the type names of their map assets and handles.
+#[derive(Resource, Debug, Default)]
+pub struct MapValidation {
+    registered: HashSet<&'static str>,
+}
+
+impl MapValidation {
+    /// Record the plugin of `C`, returns `false` if it was recorded before.
+    pub(crate) fn register<C: Customization>(&mut self) -> bool {
+        let new = self.registered.insert(type_name::<Assets<Map<C>>>());
+        self.registered.insert(type_name::<Handle<Map<C>>>());
+        new
+    }
+}
+
+/// Warn about the plugin of `C` being added a second time.
+pub(crate) fn warn_plugin_added_twice<C: Customization>(app: &mut App) {
+    let detected = MapMisuseDetected {
+        entity: None,
+        misuse: MapMisuse::PluginAddedTwice {
+            customization: type_name::<C>(),
+        },
+    };
+    detected.warn();
+    app.world_mut().send_event(detected);
+}
+
+/// Prefix of the type name of `T<Map<C>>` for any `C`
+fn map_type_prefix<T: 'static>() -> &'static str {
+    let name = type_name::<T>();
+    let end = name
+        .find(type_name::<NoCustomization>())
+        .unwrap_or(name.len());
+    &name[..end]
+}
+
+/// Warn about map assets or handles of customizations without a plugin.
+/// Only looks again when new component types or archetypes appeared since the last check.
+pub fn validate_map_plugins(
+    world: &mut World,
+    mut seen: Local<(usize, usize)>,
+    mut warned: Local<HashSet<String>>,
+) {
+    let counts = (world.components().len(), world.archetypes().len());
+    if *seen == counts {
+        return;
+    }
+    *seen = counts;
+
+    let registered = &world.resource::<MapValidation>().registered;
+    let prefixes = [
+        map_type_prefix::<Assets<Map<NoCustomization>>>(),
+        map_type_prefix::<Handle<Map<NoCustomization>>>(),
+    ];
+    let mut missing = Vec::new();
+    for info in world.components().iter() {
+        let name = info.name();
+        let Some(prefix) = prefixes.iter().find(|prefix| name.starts_with(**prefix)) else {
+            continue;
+        };
+        let in_use = world.contains_resource_by_id(info.id())
+            || world
+                .archetypes()
+                .iter()
+                .any(|archetype| !archetype.is_empty() && archetype.contains(info.id()));
+        if !in_use || registered.contains(name) {
+            continue;
+        }
+        // `Assets<Map<C>>` and `Handle<Map<C>>` both end with `>>`
+        let customization = name[prefix.len()..name.len().saturating_sub(2)].to_string();
+        if warned.insert(customization.clone()) {
+            missing.push(customization);
+        }
+    }
+
+    for customization in missing {
+        let detected = MapMisuseDetected {
+            entity: None,
+            misuse: MapMisuse::PluginMissing { customization },
+        };
+        detected.warn();
+        world.send_event(detected);
+    }
+}
+
+/// Warn about managed map entities that got a mesh of their own before the map loaded.
+pub fn validate_managed_meshes<C: Customization>(
+    maps: Query<
+        (Entity, &Mesh2dHandle),
+        (
+            With<Handle<Map<C>>>,
+            With<MeshManagedByMap>,
+            With<MapLoading>,
+            Changed<Mesh2dHandle>,
+        ),
+    >,
+    mut misuse: EventWriter<MapMisuseDetected>,
+) {
+    for (entity, mesh) in maps.iter() {
+        if mesh.0 == Handle::default() {
+            continue;
+        }
+        let detected = MapMisuseDetected {
+            entity: Some(entity),
+            misuse: MapMisuse::ManagedMeshReplaced,
+        };
+        detected.warn();
+        misuse.send(detected);
+    }
+}
+
+/// Warn about mix colors that don't match the vertices of the mesh, once per entity
+/// (and again if the counts change).
+pub fn validate_mix_colors<C: Customization>(
+    maps: Query<
+        (Entity, &MapAttributes, &Mesh2dHandle),
+        (
+            With<Handle<Map<C>>>,
+            Or<(Changed<MapAttributes>, Changed<Mesh2dHandle>)>,
+        ),
+    >,
+    meshes: Res<Assets<Mesh>>,
+    mut warned: Local<HashMap<Entity, (usize, usize)>>,
+    mut misuse: EventWriter<MapMisuseDetected>,
+) {
+    for (entity, attributes, mesh) in maps.iter() {
+        let entries = attributes.mix_color.len();
+        let Some(vertices) = meshes.get(&mesh.0).map(Mesh::count_vertices) else {
+            continue;
+        };
+        if entries == 0 || entries == vertices {
+            warned.remove(&entity);
+            continue;
+        }
+        if warned.insert(entity, (entries, vertices)) == Some((entries, vertices)) {
+            continue;
+        }
+        let detected = MapMisuseDetected {
+            entity: Some(entity),
+            misuse: MapMisuse::MixColorLength { entries, vertices },
+        };
+        detected.warn();
+        misuse.send(detected);
+    }
+}
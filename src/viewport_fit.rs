@@ -0,0 +1,131 @@
+use bevy::{
+    math::{vec2, Vec3Swizzles},
+    prelude::*,
+    sprite::Mesh2dHandle,
+};
+
+use super::{
+    hooks::{run_hook, MapHooks},
+    instances::MapInstances,
+    map::{Map, MapAttributes, MapLoading, MeshManagedByMap},
+    plugin::Customization,
+};
+
+/// Margin around the visible part of the map covered by a [`MeshManagedByMap::ViewportFit`]
+/// mesh, relative to the size of the visible part.
+const VIEWPORT_FIT_MARGIN: f32 = 0.25;
+
+/// Local rectangle currently covered by a [`MeshManagedByMap::ViewportFit`] mesh,
+/// added automatically.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ViewportFitRect(pub(crate) Rect);
+
+impl ViewportFitRect {
+    pub fn rect(&self) -> Rect {
+        self.0
+    }
+}
+
+/// Rectangular mesh covering `rect` (in local coordinates of the map entity).
+pub(crate) fn quad_mesh(rect: Rect) -> Mesh {
+    Mesh::from(Rectangle {
+        half_size: rect.half_size(),
+    })
+    .translated_by(rect.center().extend(0.0))
+}
+
+/// Whether `outer` fully contains `inner`.
+fn contains_rect(outer: Rect, inner: Rect) -> bool {
+    outer.min.cmple(inner.min).all() && outer.max.cmpge(inner.max).all()
+}
+
+/// Rebuild the meshes of [`MeshManagedByMap::ViewportFit`] maps whose visible part is no longer
+/// covered by their mesh, or covers much less than it.
+#[allow(clippy::type_complexity)]
+pub fn update_viewport_fit_meshes<C: Customization>(
+    mut commands: Commands,
+    cameras: Query<(&Camera, &GlobalTransform, &OrthographicProjection)>,
+    maps: Query<
+        (
+            Entity,
+            &MeshManagedByMap,
+            &Handle<Map<C>>,
+            &GlobalTransform,
+            Option<&MapAttributes>,
+            Option<&ViewportFitRect>,
+            Option<&MapHooks>,
+        ),
+        (Without<MapLoading>, Without<MapInstances>),
+    >,
+    map_materials: Res<Assets<Map<C>>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    time: Res<Time>,
+) {
+    // Corners of the areas seen by all active cameras, in world coordinates
+    let view_corners: Vec<Vec3> = cameras
+        .iter()
+        .filter(|(camera, _, _)| camera.is_active)
+        .flat_map(|(_, transform, projection)| {
+            let area = projection.area;
+            [
+                area.min,
+                vec2(area.max.x, area.min.y),
+                area.max,
+                vec2(area.min.x, area.max.y),
+            ]
+            .map(|corner| transform.transform_point(corner.extend(0.0)))
+        })
+        .collect();
+    if view_corners.is_empty() {
+        return;
+    }
+
+    for (entity, managed, map_handle, transform, attributes, fit, hooks) in maps.iter() {
+        if *managed != MeshManagedByMap::ViewportFit {
+            continue;
+        }
+        let Some(map) = map_materials.get(map_handle) else {
+            continue;
+        };
+
+        // Repeated content has no bounds
+        let full = if map.repeats_content() {
+            Rect {
+                min: Vec2::splat(f32::MIN),
+                max: Vec2::splat(f32::MAX),
+            }
+        } else {
+            Rect::from_center_half_size(Vec2::ZERO, map.world_size() / 2.0)
+        };
+        let to_local = transform.affine().inverse();
+        let visible = view_corners
+            .iter()
+            .fold(Rect::EMPTY, |rect, corner| {
+                rect.union_point(to_local.transform_point3(*corner).xy())
+            })
+            .intersect(full);
+        if visible.is_empty() {
+            continue;
+        }
+
+        let target_size = visible.size() * (1.0 + 2.0 * VIEWPORT_FIT_MARGIN);
+        if let Some(ViewportFitRect(current)) = fit {
+            let covers = contains_rect(*current, visible) && contains_rect(full, *current);
+            let too_large = current.size().cmpgt(target_size * 2.0).any();
+            if covers && !too_large {
+                continue;
+            }
+        }
+
+        let rect = Rect::from_center_size(visible.center(), target_size).intersect(full);
+        let mut mesh = quad_mesh(rect);
+        MapAttributes::set_mix_color(attributes, &mut mesh);
+        MapAttributes::set_animation_state(attributes, &mut mesh, &time);
+
+        commands
+            .entity(entity)
+            .insert((Mesh2dHandle(meshes.add(mesh)), ViewportFitRect(rect)));
+        let rebuilt = hooks.and_then(|h| h.after_mesh_rebuild);
+        run_hook(&mut commands, rebuilt, entity);
+    }
+}
@@ -0,0 +1,261 @@
+use std::ops::Range;
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+
+/// Visibility state of a single tile, eg. for fog of war.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum TileVisibility {
+    /// Never seen by the player.
+    #[default]
+    Unseen,
+    /// Seen before, but not currently visible.
+    Seen,
+    /// Currently visible.
+    Visible,
+}
+
+/// Per-tile visibility of a map.
+/// Add this next to the [`crate::map::Map`] handle of a map entity.
+///
+/// This is a component rather than part of the `Map` asset, so revealing tiles doesn't upload the
+/// map to the GPU again. The explored tiles are available as bitmask from [`Self::bitmask`]
+/// (instead of a `Map::visibility_bitmask`).
+///
+/// Changes are tracked per row, see [`MapVisibilityChanged`] and [`VisibilityMinimap`].
+#[derive(Component, Debug, Clone, Default)]
+pub struct MapVisibility {
+    size: UVec2,
+    tiles: Vec<TileVisibility>,
+    /// One bit per tile (in row-major order), set iff the tile is not [`TileVisibility::Unseen`].
+    explored: Vec<u64>,
+    dirty_rows: Option<Range<u32>>,
+}
+
+impl MapVisibility {
+    /// All tiles unseen.
+    pub fn new(size: UVec2) -> Self {
+        let n = size.x as usize * size.y as usize;
+        Self {
+            size,
+            tiles: vec![TileVisibility::Unseen; n],
+            explored: vec![0; n.div_ceil(64)],
+            dirty_rows: None,
+        }
+    }
+
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    /// Visibility of the given tile, [`TileVisibility::Unseen`] if out of bounds.
+    pub fn get(&self, tile: UVec2) -> TileVisibility {
+        if tile.x >= self.size.x || tile.y >= self.size.y {
+            return TileVisibility::Unseen;
+        }
+        self.tiles[self.index(tile)]
+    }
+
+    /// Set visibility of the given tile, out of bounds tiles are ignored.
+    pub fn set(&mut self, tile: UVec2, visibility: TileVisibility) {
+        if tile.x >= self.size.x || tile.y >= self.size.y {
+            return;
+        }
+        let idx = self.index(tile);
+        if self.tiles[idx] == visibility {
+            return;
+        }
+        self.tiles[idx] = visibility;
+
+        let bit = 1u64 << (idx % 64);
+        if visibility == TileVisibility::Unseen {
+            self.explored[idx / 64] &= !bit;
+        } else {
+            self.explored[idx / 64] |= bit;
+        }
+        self.mark_dirty(tile.y..tile.y + 1);
+    }
+
+    /// Set visibility of all tiles in `rect` (`max` exclusive).
+    pub fn set_rect(&mut self, rect: URect, visibility: TileVisibility) {
+        let max = rect.max.min(self.size);
+        for y in rect.min.y..max.y {
+            for x in rect.min.x..max.x {
+                self.set(UVec2::new(x, y), visibility);
+            }
+        }
+    }
+
+    /// Turn all currently visible tiles into seen ones,
+    /// eg. before revealing the new field of view of the player.
+    pub fn hide_visible(&mut self) {
+        for y in 0..self.size.y {
+            for x in 0..self.size.x {
+                let tile = UVec2::new(x, y);
+                if self.get(tile) == TileVisibility::Visible {
+                    self.set(tile, TileVisibility::Seen);
+                }
+            }
+        }
+    }
+
+    /// True iff the tile has been seen or is visible.
+    pub fn is_explored(&self, tile: UVec2) -> bool {
+        self.get(tile) != TileVisibility::Unseen
+    }
+
+    /// Explored tiles as bitmask, bit `i % 64` of `bitmask()[i / 64]` corresponds to the tile
+    /// with index `i = y * size.x + x`.
+    pub fn bitmask(&self) -> &[u64] {
+        &self.explored
+    }
+
+    /// Rows that changed since the last [`MapVisibilityChanged`] event.
+    pub fn dirty_rows(&self) -> Option<Range<u32>> {
+        self.dirty_rows.clone()
+    }
+
+    fn index(&self, tile: UVec2) -> usize {
+        tile.y as usize * self.size.x as usize + tile.x as usize
+    }
+
+    fn mark_dirty(&mut self, rows: Range<u32>) {
+        self.dirty_rows = Some(match self.dirty_rows.take() {
+            Some(dirty) => dirty.start.min(rows.start)..dirty.end.max(rows.end),
+            None => rows,
+        });
+    }
+}
+
+/// Sent whenever the [`MapVisibility`] of `map` changed.
+#[derive(Event, Debug, Clone)]
+pub struct MapVisibilityChanged {
+    pub map: Entity,
+    /// Range of rows that (possibly) changed.
+    pub rows: Range<u32>,
+}
+
+/// Image rendering of a [`MapVisibility`] with one pixel per tile, eg. for a minimap.
+/// Add this next to the [`MapVisibility`] component, the image will be kept up to date
+/// automatically, only changed rows are redrawn.
+/// (The image is still uploaded to the GPU as a whole though.)
+#[derive(Component, Debug, Clone)]
+pub struct VisibilityMinimap {
+    pub image: Handle<Image>,
+    pub unseen: Color,
+    pub seen: Color,
+    pub visible: Color,
+}
+
+impl VisibilityMinimap {
+    /// Create a minimap image for a map of the given size.
+    pub fn new(images: &mut Assets<Image>, size: UVec2) -> Self {
+        let unseen = Color::BLACK;
+        let image = Image::new_fill(
+            Extent3d {
+                width: size.x.max(1),
+                height: size.y.max(1),
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &unseen.to_srgba().to_u8_array(),
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        Self {
+            image: images.add(image),
+            unseen,
+            seen: Color::srgb(0.4, 0.4, 0.4),
+            visible: Color::WHITE,
+        }
+    }
+
+    pub fn with_colors(mut self, unseen: Color, seen: Color, visible: Color) -> Self {
+        self.unseen = unseen;
+        self.seen = seen;
+        self.visible = visible;
+        self
+    }
+
+    fn color(&self, visibility: TileVisibility) -> [u8; 4] {
+        match visibility {
+            TileVisibility::Unseen => self.unseen,
+            TileVisibility::Seen => self.seen,
+            TileVisibility::Visible => self.visible,
+        }
+        .to_srgba()
+        .to_u8_array()
+    }
+
+    fn draw_rows(&self, visibility: &MapVisibility, rows: Range<u32>, image: &mut Image) {
+        let size = visibility.size();
+        if image.width() != size.x || image.height() != size.y {
+            warn!(
+                "Minimap image size {:?} does not match map size {:?}",
+                image.size(),
+                size
+            );
+            return;
+        }
+        let colors = [
+            self.color(TileVisibility::Unseen),
+            self.color(TileVisibility::Seen),
+            self.color(TileVisibility::Visible),
+        ];
+        for y in rows {
+            let row_start = visibility.index(UVec2::new(0, y));
+            let row = &visibility.tiles[row_start..row_start + size.x as usize];
+            let start = row_start * 4;
+            let pixels = &mut image.data[start..start + size.x as usize * 4];
+            for (pixel, tile) in pixels.chunks_exact_mut(4).zip(row) {
+                pixel.copy_from_slice(&colors[*tile as usize]);
+            }
+        }
+    }
+}
+
+/// Send change events for and redraw the minimaps of changed map visibilities.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_map_visibility(
+    mut visibilities: Query<
+        (Entity, &mut MapVisibility, Option<Ref<VisibilityMinimap>>),
+        Or<(Changed<MapVisibility>, Added<VisibilityMinimap>)>,
+    >,
+    mut images: ResMut<Assets<Image>>,
+    mut ev_changed: EventWriter<MapVisibilityChanged>,
+) {
+    for (entity, mut visibility, minimap) in visibilities.iter_mut() {
+        let rows = visibility.bypass_change_detection().dirty_rows.take();
+
+        if let Some(minimap) = minimap {
+            // New minimaps need to be drawn fully
+            let draw = match minimap.is_added() {
+                true => Some(0..visibility.size().y),
+                false => rows.clone(),
+            };
+            if let (Some(draw), Some(image)) = (draw, images.get_mut(&minimap.image)) {
+                minimap.draw_rows(&visibility, draw, image);
+            }
+        }
+
+        if let Some(rows) = rows {
+            ev_changed.send(MapVisibilityChanged { map: entity, rows });
+        }
+    }
+}
+
+/// Keeps [`MapVisibility`] events and minimaps up to date.
+/// Added automatically by [`crate::plugin::CustomFastTileMapPlugin`].
+pub struct MapVisibilityPlugin;
+
+impl Plugin for MapVisibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MapVisibilityChanged>()
+            .add_systems(PostUpdate, update_map_visibility);
+    }
+}
@@ -0,0 +1,259 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::{
+    core_pipeline::tonemapping::{DebandDither, Tonemapping},
+    prelude::*,
+    render::{
+        mesh::GpuMesh,
+        render_asset::RenderAssets,
+        render_resource::{PipelineCache, SpecializedMeshPipelines},
+        view::{ExtractedView, Msaa},
+    },
+    sprite::{
+        tonemapping_pipeline_key, Material2dKey, Material2dPipeline, Mesh2dHandle,
+        Mesh2dPipelineKey, PreparedMaterial2d,
+    },
+    utils::HashSet,
+};
+
+use super::{
+    map::{Map, MapLoading, MeshManagedByMap},
+    plugin::{Customization, NoCustomization},
+};
+
+/// Drives a set of maps to a fully prepared state so they can be revealed in a single frame.
+///
+/// Insert this as a resource right after spawning your maps (typically with
+/// [`Visibility::Hidden`]). Every frame the plugin checks whether, for each map,
+/// - the [`Map`] asset and its atlas texture are available,
+/// - loading finished (sampler set up, uniform updated, managed mesh built),
+/// - the render world prepared the bind group and the mesh buffers and
+/// - the render pipelines for all current views have been compiled.
+///
+/// Bevy would normally only specialize pipelines for visible entities, so during warm-up
+/// this is done explicitly for the maps listed here.
+/// Once everything is ready, a [`MapWarmupComplete`] event is sent and the warm-up stops
+/// (you may then remove the resource).
+#[derive(Resource)]
+pub struct MapWarmup<C: Customization = NoCustomization> {
+    maps: Vec<Handle<Map<C>>>,
+    complete: bool,
+}
+
+impl<C: Customization> MapWarmup<C> {
+    pub fn new(maps: Vec<Handle<Map<C>>>) -> Self {
+        Self {
+            maps,
+            complete: false,
+        }
+    }
+
+    /// Maps handled by this warm-up.
+    pub fn maps(&self) -> &[Handle<Map<C>>] {
+        &self.maps
+    }
+
+    /// True iff all maps are fully prepared.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+}
+
+/// Sent once all maps of a [`MapWarmup`] are ready to be shown.
+#[derive(Event)]
+pub struct MapWarmupComplete<C: Customization = NoCustomization> {
+    pub maps: Vec<AssetId<Map<C>>>,
+}
+
+/// A map whose main world preparation is finished, along with the meshes it is rendered with.
+struct WarmupRequest<C: Customization> {
+    map: AssetId<Map<C>>,
+    meshes: Vec<AssetId<Mesh>>,
+}
+
+/// State shared between the main world and the render world during warm-up.
+struct WarmupState<C: Customization> {
+    requested: Vec<WarmupRequest<C>>,
+    /// Maps fully prepared by the render world.
+    ready: HashSet<AssetId<Map<C>>>,
+}
+
+#[derive(Resource)]
+pub(crate) struct MapWarmupShared<C: Customization>(Arc<Mutex<WarmupState<C>>>);
+
+impl<C: Customization> Default for MapWarmupShared<C> {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(WarmupState {
+            requested: Vec::new(),
+            ready: HashSet::new(),
+        })))
+    }
+}
+
+impl<C: Customization> Clone for MapWarmupShared<C> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// Check main world preparation of the maps in [`MapWarmup`] and forward them to the
+/// render world once that is done.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_map_warmup<C: Customization>(
+    mut warmup: ResMut<MapWarmup<C>>,
+    shared: Res<MapWarmupShared<C>>,
+    map_materials: Res<Assets<Map<C>>>,
+    images: Res<Assets<Image>>,
+    meshes: Res<Assets<Mesh>>,
+    maps: Query<(
+        &Handle<Map<C>>,
+        Option<&Mesh2dHandle>,
+        Has<MapLoading>,
+        Has<MeshManagedByMap>,
+    )>,
+    mut ev_complete: EventWriter<MapWarmupComplete<C>>,
+) {
+    if warmup.complete {
+        return;
+    }
+
+    let mut requested = Vec::new();
+    let mut all_requested = true;
+
+    'maps: for map_handle in warmup.maps.iter() {
+        let Some(map) = map_materials.get(map_handle) else {
+            all_requested = false;
+            continue;
+        };
+        if !map.is_loaded(images.as_ref()) {
+            all_requested = false;
+            continue;
+        }
+
+        let mut mesh_ids = Vec::new();
+        for (handle, mesh, loading, managed) in maps.iter() {
+            if handle.id() != map_handle.id() {
+                continue;
+            }
+            // For managed meshes, MapLoading is only removed together with inserting the mesh,
+            // but the mesh insertion is deferred by one command flush.
+            if loading || (managed && mesh.is_none()) {
+                all_requested = false;
+                continue 'maps;
+            }
+            if let Some(mesh) = mesh {
+                if meshes.get(&mesh.0).is_none() {
+                    all_requested = false;
+                    continue 'maps;
+                }
+                mesh_ids.push(mesh.0.id());
+            }
+        }
+        requested.push(WarmupRequest {
+            map: map_handle.id(),
+            meshes: mesh_ids,
+        });
+    }
+
+    let mut state = shared.0.lock().unwrap();
+    let all_ready = all_requested
+        && requested
+            .iter()
+            .all(|request| state.ready.contains(&request.map));
+    state.requested = requested;
+
+    if all_ready {
+        state.requested.clear();
+        state.ready.clear();
+        warmup.complete = true;
+        ev_complete.send(MapWarmupComplete {
+            maps: warmup.maps.iter().map(|h| h.id()).collect(),
+        });
+        debug!("Map warm-up complete ({} maps)", warmup.maps.len());
+    }
+}
+
+/// Render world side of the warm-up: wait for the material bind group and the meshes to be
+/// prepared and specialize (and thereby compile) the pipelines for all views,
+/// independent of map visibility.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn prepare_map_warmup<C: Customization>(
+    shared: Res<MapWarmupShared<C>>,
+    material2d_pipeline: Res<Material2dPipeline<Map<C>>>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<Material2dPipeline<Map<C>>>>,
+    pipeline_cache: Res<PipelineCache>,
+    msaa: Res<Msaa>,
+    render_meshes: Res<RenderAssets<GpuMesh>>,
+    render_materials: Res<RenderAssets<PreparedMaterial2d<Map<C>>>>,
+    views: Query<(&ExtractedView, Option<&Tonemapping>, Option<&DebandDither>)>,
+) {
+    let mut state = shared.0.lock().unwrap();
+    if state.requested.is_empty() {
+        return;
+    }
+
+    // Same key computation as in `bevy::sprite::queue_material2d_meshes`,
+    // so the pipelines we compile here are the ones that will be used once visible.
+    let view_keys: Vec<_> = views
+        .iter()
+        .map(|(view, tonemapping, dither)| {
+            let mut view_key = Mesh2dPipelineKey::from_msaa_samples(msaa.samples())
+                | Mesh2dPipelineKey::from_hdr(view.hdr);
+
+            if !view.hdr {
+                if let Some(tonemapping) = tonemapping {
+                    view_key |= Mesh2dPipelineKey::TONEMAP_IN_SHADER;
+                    view_key |= tonemapping_pipeline_key(*tonemapping);
+                }
+                if let Some(DebandDither::Enabled) = dither {
+                    view_key |= Mesh2dPipelineKey::DEBAND_DITHER;
+                }
+            }
+            view_key
+        })
+        .collect();
+
+    let mut ready = Vec::new();
+    'maps: for request in state.requested.iter() {
+        if state.ready.contains(&request.map) {
+            continue;
+        }
+        let Some(material) = render_materials.get(request.map) else {
+            continue;
+        };
+
+        for mesh_id in request.meshes.iter() {
+            let Some(mesh) = render_meshes.get(*mesh_id) else {
+                continue 'maps;
+            };
+
+            for view_key in view_keys.iter() {
+                let mesh_key = *view_key
+                    | Mesh2dPipelineKey::from_primitive_topology(mesh.primitive_topology());
+
+                let pipeline_id = match pipelines.specialize(
+                    &pipeline_cache,
+                    &material2d_pipeline,
+                    Material2dKey {
+                        mesh_key,
+                        bind_group_data: material.key.clone(),
+                    },
+                    &mesh.layout,
+                ) {
+                    Ok(id) => id,
+                    Err(err) => {
+                        error!("{}", err);
+                        continue 'maps;
+                    }
+                };
+
+                if pipeline_cache.get_render_pipeline(pipeline_id).is_none() {
+                    continue 'maps;
+                }
+            }
+        }
+        ready.push(request.map);
+    }
+
+    state.ready.extend(ready);
+}
@@ -0,0 +1,336 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{
+        camera::CameraUpdateSystem,
+        render_resource::{AsBindGroup, ShaderType},
+    },
+};
+
+use super::{
+    bundle::MapBundleManaged,
+    map::{Map, NoExtraBindings},
+    plugin::{CustomFastTileMapPlugin, Customization},
+};
+
+/// Placeholder atlas for weather overlays, which don't sample any atlas.
+const WEATHER_ATLAS: Handle<Image> = Handle::weak_from_u128(0x5c1e7a2b9d4f4e10b3a6c8d7e2f1a094);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum WeatherKind {
+    #[default]
+    Rain,
+    Snow,
+}
+
+/// Animated full screen weather effect, rendered as a single tile map that follows the camera.
+/// Add [`WeatherOverlayPlugin`] and spawn eg. `WeatherOverlay::rain(0.5)`, the map for it
+/// is created automatically.
+///
+/// Change the fields at runtime to change the effect.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct WeatherOverlay {
+    pub kind: WeatherKind,
+    /// Density of drops/flakes in [0, 1].
+    pub intensity: f32,
+    /// Horizontal wind, ie. slant of rain and drift of snow.
+    /// Around 0.3 is a moderate wind, negative values blow to the left.
+    pub wind: f32,
+    /// Z coordinate of the overlay, should be in front of everything it covers.
+    pub z: f32,
+}
+
+impl WeatherOverlay {
+    pub fn rain(intensity: f32) -> Self {
+        Self {
+            kind: WeatherKind::Rain,
+            intensity,
+            wind: 0.15,
+            z: 100.0,
+        }
+    }
+
+    pub fn snow(intensity: f32) -> Self {
+        Self {
+            kind: WeatherKind::Snow,
+            intensity,
+            wind: 0.3,
+            z: 100.0,
+        }
+    }
+
+    pub fn with_wind(mut self, wind: f32) -> Self {
+        self.wind = wind;
+        self
+    }
+
+    pub fn with_z(mut self, z: f32) -> Self {
+        self.z = z;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Reflect, AsBindGroup, ShaderType)]
+pub struct WeatherUserData {
+    kind: u32,
+    intensity: f32,
+    wind: f32,
+    /// Size of the viewport in (logical) pixels
+    viewport: Vec2,
+}
+
+/// Customization rendering [`WeatherOverlay`]s.
+#[derive(Clone, TypePath, Default)]
+pub struct WeatherCustomization;
+
+impl Customization for WeatherCustomization {
+    const SHADER_HANDLE: Handle<Shader> =
+        Handle::weak_from_u128(0x8f3d2c6a1b7e49d5a0c4e9f2b6d8a173);
+    type UserData = WeatherUserData;
+    type ExtraBindings = NoExtraBindings;
+
+    fn custom_shader_code() -> String {
+        r#"
+        struct UserData {
+            kind: u32,
+            intensity: f32,
+            wind: f32,
+            viewport: vec2<f32>,
+        };
+
+        fn weather_hash(p: vec2<f32>) -> f32 {
+            return fract(sin(dot(p, vec2<f32>(127.1, 311.7))) * 43758.5453);
+        }
+
+        /// Falling streaks in columns, three layers of different size and speed
+        fn rain(pixel: vec2<f32>, t: f32) -> vec4<f32> {
+            var alpha = 0.0;
+            for (var layer = 0; layer < 3; layer++) {
+                var scale = 1.0 + f32(layer) * 0.6;
+                var p = pixel * scale + vec2<f32>(f32(layer) * 37.0, 0.0);
+                // Slant by the wind
+                p.x -= p.y * user_data.wind;
+
+                var column_width = 7.0;
+                var column = floor(p.x / column_width);
+                var h = weather_hash(vec2<f32>(column, f32(layer)));
+                if h > user_data.intensity {
+                    continue;
+                }
+
+                var y = p.y - t * (700.0 + 500.0 * h) + h * 1000.0;
+                var period = 120.0 + 200.0 * h;
+                var len = 25.0;
+                var phase = y - floor(y / period) * period;
+                var dx = abs(p.x - (column + 0.5) * column_width);
+                if phase < len && dx < 0.6 * scale {
+                    alpha += 0.45 * (phase / len) / scale;
+                }
+            }
+            return vec4<f32>(0.75, 0.8, 0.9, clamp(alpha, 0.0, 1.0));
+        }
+
+        /// Drifting round flakes on a jittered grid, three layers of different size and speed
+        fn snow(pixel: vec2<f32>, t: f32) -> vec4<f32> {
+            var alpha = 0.0;
+            for (var layer = 0; layer < 3; layer++) {
+                var cell_size = 28.0 + f32(layer) * 14.0;
+                var speed = 30.0 + f32(layer) * 25.0;
+                var p = pixel - vec2<f32>(user_data.wind * speed * t, speed * t);
+                p.x += sin(p.y / 40.0 + f32(layer)) * 6.0;
+
+                var cell = floor(p / cell_size);
+                var h = weather_hash(cell + vec2<f32>(f32(layer) * 17.0, 0.0));
+                if h > user_data.intensity {
+                    continue;
+                }
+
+                var jitter = vec2<f32>(h, weather_hash(cell + vec2<f32>(5.3, 1.7))) - 0.5;
+                var center = (cell + 0.5 + 0.6 * jitter) * cell_size;
+                var radius = 1.0 + f32(layer) * 1.0 + h;
+                alpha += clamp(radius - length(p - center) + 0.5, 0.0, 1.0);
+            }
+            return vec4<f32>(1.0, 1.0, 1.0, clamp(alpha, 0.0, 1.0) * 0.9);
+        }
+
+        fn sample_tile(in: ExtractIn) -> vec4<f32> {
+            // The single tile of the map covers the viewport
+            var pixel = in.map_position * user_data.viewport;
+            if user_data.kind == 1u {
+                return snow(pixel, in.animation_state);
+            }
+            return rain(pixel, in.animation_state);
+        }
+    "#
+        .to_string()
+    }
+
+    fn shader_defs() -> Vec<String> {
+        vec!["EXTRACT_MAP_POSITION".to_string()]
+    }
+}
+
+/// Renders [`WeatherOverlay`]s.
+pub struct WeatherOverlayPlugin;
+
+impl Plugin for WeatherOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(CustomFastTileMapPlugin::<WeatherCustomization>::default())
+            .register_type::<WeatherOverlay>()
+            .add_systems(Update, spawn_weather_overlays)
+            .add_systems(
+                PostUpdate,
+                update_weather_overlays
+                    .after(CameraUpdateSystem)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+fn weather_user_data(overlay: &WeatherOverlay, viewport: Vec2) -> WeatherUserData {
+    WeatherUserData {
+        kind: match overlay.kind {
+            WeatherKind::Rain => 0,
+            WeatherKind::Snow => 1,
+        },
+        intensity: overlay.intensity.clamp(0.0, 1.0),
+        wind: overlay.wind,
+        viewport,
+    }
+}
+
+fn spawn_weather_overlays(
+    mut commands: Commands,
+    overlays: Query<(Entity, &WeatherOverlay), Without<Handle<Map<WeatherCustomization>>>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<Map<WeatherCustomization>>>,
+) {
+    for (entity, overlay) in overlays.iter() {
+        if !images.contains(&WEATHER_ATLAS) {
+            images.insert(&WEATHER_ATLAS, Image::default());
+        }
+
+        let map = Map::<WeatherCustomization>::builder(uvec2(1, 1), WEATHER_ATLAS, vec2(1.0, 1.0))
+            .with_user_data(weather_user_data(overlay, Vec2::ONE))
+            .with_overhangs(false, false, false)
+            .build();
+
+        commands.entity(entity).insert(MapBundleManaged {
+            material: materials.add(map),
+            ..default()
+        });
+    }
+}
+
+/// Make overlays cover the view of the first active 2d camera and apply changed settings.
+fn update_weather_overlays(
+    cameras: Query<(&Camera, &GlobalTransform, &OrthographicProjection)>,
+    mut overlays: Query<(
+        &WeatherOverlay,
+        &Handle<Map<WeatherCustomization>>,
+        &mut Transform,
+    )>,
+    mut materials: ResMut<Assets<Map<WeatherCustomization>>>,
+) {
+    let Some((camera, camera_transform, projection)) =
+        cameras.iter().find(|(camera, _, _)| camera.is_active)
+    else {
+        return;
+    };
+    let viewport = camera.logical_viewport_size().unwrap_or(Vec2::ONE);
+    let center = camera_transform.transform_point(projection.area.center().extend(0.0));
+    let size = projection.area.size() * camera_transform.compute_transform().scale.truncate();
+
+    for (overlay, map_handle, mut transform) in overlays.iter_mut() {
+        *transform = Transform::from_translation(center.truncate().extend(overlay.z))
+            .with_scale(size.extend(1.0));
+
+        let user_data = weather_user_data(overlay, viewport);
+        if materials
+            .get(map_handle)
+            .is_some_and(|map| map.user_data != user_data)
+        {
+            if let Some(map) = materials.get_mut(map_handle) {
+                map.user_data = user_data;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlays_cover_the_camera_and_follow_their_settings() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_asset::<Map<WeatherCustomization>>()
+            .add_systems(
+                Update,
+                (spawn_weather_overlays, update_weather_overlays).chain(),
+            );
+        app.world_mut().spawn((
+            Camera::default(),
+            GlobalTransform::from_xyz(100.0, 50.0, 0.0),
+            OrthographicProjection {
+                area: Rect::new(-200.0, -100.0, 200.0, 100.0),
+                ..default()
+            },
+        ));
+        let overlay = app
+            .world_mut()
+            .spawn((WeatherOverlay::snow(1.5).with_z(50.0), Transform::default()))
+            .id();
+        app.update();
+
+        let user_data = |app: &App| {
+            let handle = app
+                .world()
+                .get::<Handle<Map<WeatherCustomization>>>(overlay)
+                .unwrap();
+            let maps = app.world().resource::<Assets<Map<WeatherCustomization>>>();
+            maps.get(handle).unwrap().user_data.clone()
+        };
+        assert!(app
+            .world()
+            .resource::<Assets<Image>>()
+            .contains(&WEATHER_ATLAS));
+        assert_eq!(
+            user_data(&app),
+            WeatherUserData {
+                kind: 1,
+                intensity: 1.0,
+                wind: 0.3,
+                viewport: Vec2::ONE,
+            }
+        );
+        assert_eq!(
+            *app.world().get::<Transform>(overlay).unwrap(),
+            Transform::from_xyz(100.0, 50.0, 50.0).with_scale(Vec3::new(400.0, 200.0, 1.0))
+        );
+
+        // Changing the settings changes the map
+        *app.world_mut().get_mut::<WeatherOverlay>(overlay).unwrap() =
+            WeatherOverlay::rain(0.25).with_wind(-0.5);
+        app.update();
+        assert_eq!(
+            user_data(&app),
+            WeatherUserData {
+                kind: 0,
+                intensity: 0.25,
+                wind: -0.5,
+                viewport: Vec2::ONE,
+            }
+        );
+        // The overlay keeps its map
+        assert_eq!(
+            app.world()
+                .resource::<Assets<Map<WeatherCustomization>>>()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use bevy::{math::Vec3Swizzles, prelude::*, window::PrimaryWindow};
+
+use super::{map::Map, plugin::Customization};
+
+/// Keep the map of this entity fitted to the primary window, so `tiles` tiles exactly span it
+/// at the default camera scale, see [`Map::fit_to_viewport`].
+///
+/// The world tile size is updated (along with the managed mesh) whenever the window is resized.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct FitMapToWindow {
+    pub tiles: UVec2,
+}
+
+impl<C: Customization> Map<C> {
+    /// Set the world tile size such that `tiles` tiles (in each dimension, usually the map size)
+    /// exactly span `viewport_size` world units, eg. the window size for a camera with the
+    /// default scale. For projections other than [`crate::tile_projection::IDENTITY`], the
+    /// bounding box of the projected tiles spans the viewport.
+    ///
+    /// Tiles are stretched to the aspect ratio of the viewport and usually don't end up with an
+    /// integer size, ie. pixel art is not rendered crisply unless the viewport is a multiple of
+    /// the atlas tile size times `tiles`.
+    /// Overhangs beyond the map (eg. the half tiles on the sides of staggered maps)
+    /// are not considered.
+    pub fn fit_to_viewport(&mut self, viewport_size: Vec2, tiles: UVec2) {
+        let Some(world_tile_size) = self.viewport_fit_tile_size(viewport_size, tiles) else {
+            return;
+        };
+        if world_tile_size != self.map_uniform.world_tile_size {
+            self.map_uniform.world_tile_size = world_tile_size;
+            self.update_projection();
+        }
+    }
+
+    /// World tile size for [`Self::fit_to_viewport`], `None` for empty viewports or tiles.
+    pub(crate) fn viewport_fit_tile_size(&self, viewport_size: Vec2, tiles: UVec2) -> Option<Vec2> {
+        let tiles = tiles.as_vec2();
+        let mut low = Vec2::ZERO;
+        let mut high = Vec2::ZERO;
+        for corner in [Vec2::new(tiles.x, 0.0), Vec2::new(0.0, tiles.y), tiles] {
+            let pos = (self.map_uniform.projection * corner.extend(0.0)).xy();
+            low = low.min(pos);
+            high = high.max(pos);
+        }
+        let extent = high - low;
+        if extent.cmple(Vec2::ZERO).any() || viewport_size.cmple(Vec2::ZERO).any() {
+            return None;
+        }
+        Some(viewport_size / extent)
+    }
+}
+
+/// Fit the maps of all entities with [`FitMapToWindow`] to the primary window.
+/// Maps are only modified if the window size (or [`FitMapToWindow`]) changed.
+pub fn fit_maps_to_window<C: Customization>(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    fits: Query<(&FitMapToWindow, &Handle<Map<C>>)>,
+    mut maps: ResMut<Assets<Map<C>>>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    for (fit, handle) in fits.iter() {
+        let outdated = maps.get(handle).is_some_and(|map| {
+            map.viewport_fit_tile_size(window.size(), fit.tiles)
+                .is_some_and(|size| size != map.world_tile_size())
+        });
+        if outdated {
+            maps.get_mut(handle)
+                .unwrap()
+                .fit_to_viewport(window.size(), fit.tiles);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+fn atlas_image(width: u32, height: u32, color: [u8; 4]) -> Image {
+    Image::new_fill(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &color,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    )
+}
+
+fn has_map_sampler(app: &App, atlas: &Handle<Image>) -> bool {
+    let image = app.world().resource::<Assets<Image>>().get(atlas).unwrap();
+    matches!(image.sampler, ImageSampler::Descriptor(_))
+}
+
+#[test]
+fn tile_values_select_the_atlas() {
+    let map: Map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+        .with_additional_atlas(default(), vec2(32.0, 32.0), Vec2::ZERO)
+        .build();
+    assert_eq!(map.additional_atlases().len(), 1);
+    assert_eq!(map.atlas_of(7), 0);
+    assert_eq!(map.atlas_of(tile_in_atlas(1, 7)), 1);
+    assert_eq!(map.atlas_of(tile_in_atlas(1, 7) | TILE_FLIP_Y), 1);
+    assert_eq!(tile_in_atlas(3, 7) & !TILE_ATLAS_MASK, 7);
+    // The selector bits are not part of the index, flags are dropped
+    assert_eq!(
+        tile_in_atlas(1, TILE_ATLAS_MASK | TILE_FLIP_X | 7),
+        tile_in_atlas(1, 7)
+    );
+
+    // Maps without additional atlases don't interpret the selector
+    let plain: Map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0)).build();
+    assert_eq!(plain.atlas_of(tile_in_atlas(1, 7)), 0);
+}
+
+#[test]
+fn at_most_four_atlases() {
+    let mut builder = Map::<NoCustomization>::builder(uvec2(4, 4), default(), vec2(16.0, 16.0));
+    for _ in 0..MAX_ATLASES {
+        builder = builder.with_additional_atlas(default(), vec2(16.0, 16.0), Vec2::ZERO);
+    }
+    let map = builder.build();
+    assert_eq!(map.additional_atlases().len() as u32, MAX_ATLASES - 1);
+}
+
+#[test]
+fn palette_owner_bits_stay_below_the_atlas_selector() {
+    let tile = tile_in_atlas(1, 3 << 24);
+    let map: Map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+        .with_palette_owner_bits(24, 5)
+        .with_additional_atlas(default(), vec2(16.0, 16.0), Vec2::ZERO)
+        .build();
+    assert_eq!(map.palette_owner(tile), 3);
+
+    let map: Map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+        .with_additional_atlas(default(), vec2(16.0, 16.0), Vec2::ZERO)
+        .with_palette_owner_bits(24, 5)
+        .build();
+    assert_eq!(map.palette_owner(tile), 3);
+}
+
+#[test]
+fn tiles_are_counted_per_atlas() {
+    let mut images = Assets::<Image>::default();
+    let atlas = images.add(atlas_image(64, 64, [255; 4]));
+    let additional = images.add(atlas_image(98, 32, [255; 4]));
+    let mut map: Map = Map::builder(uvec2(4, 4), atlas, vec2(16.0, 16.0))
+        .with_additional_atlas(additional.clone(), vec2(32.0, 32.0), vec2(1.0, 0.0))
+        .build();
+    assert!(map.update(&images));
+    assert_eq!(map.atlas_n_tiles(0), uvec2(4, 4));
+    assert_eq!(map.atlas_n_tiles(1), uvec2(3, 1));
+    assert_eq!(map.atlas_n_tiles(2), UVec2::ZERO);
+    assert_eq!(map.validate(&images), Ok(()));
+
+    images.insert(&additional, atlas_image(100, 32, [255; 4]));
+    assert!(matches!(
+        map.validate(&images),
+        Err(MapError::NonIntegralTileCount { .. })
+    ));
+}
+
+#[test]
+fn loading_waits_for_all_atlases() {
+    let mut app = common::map_app();
+    app.add_systems(Update, update_loading_maps::<NoCustomization>);
+
+    let mut images = app.world_mut().resource_mut::<Assets<Image>>();
+    let atlas = images.add(atlas_image(64, 64, [255; 4]));
+    let additional = images.reserve_handle();
+    let map = Map::builder(uvec2(8, 8), atlas.clone(), Vec2::splat(16.0))
+        .with_additional_atlas(additional.clone(), Vec2::splat(32.0), Vec2::ZERO)
+        .build();
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    let entity = app
+        .world_mut()
+        .spawn(MapBundleManaged {
+            material: handle.clone(),
+            ..default()
+        })
+        .id();
+
+    app.update();
+    assert!(app.world().get::<MapLoading>(entity).is_some());
+
+    app.world_mut()
+        .resource_mut::<Assets<Image>>()
+        .insert(&additional, atlas_image(64, 32, [255; 4]));
+    app.update();
+    assert!(app.world().get::<MapLoading>(entity).is_none());
+    assert!(has_map_sampler(&app, &additional));
+    let map = app.world().resource::<Assets<Map>>().get(&handle).unwrap();
+    assert_eq!(map.atlas_n_tiles(1), uvec2(2, 1));
+}
+
+#[test]
+fn render_to_image_samples_the_selected_atlas() {
+    let (red, green) = ([255, 0, 0, 255], [0, 255, 0, 255]);
+    let mut images = Assets::<Image>::default();
+    let atlas = images.add(atlas_image(4, 4, red));
+    let additional = images.add(atlas_image(4, 4, green));
+    let mut map: Map = Map::builder(uvec2(2, 1), atlas, vec2(4.0, 4.0))
+        .with_additional_atlas(additional, vec2(4.0, 4.0), Vec2::ZERO)
+        .build_and_set(|p| tile_in_atlas(p.x, 0));
+    map.update(&images);
+
+    let rendered = map.render_to_image(&mut images, 1.0);
+    let data = &images.get(&rendered).unwrap().data;
+    assert_eq!(data[0..4], red);
+    assert_eq!(data[16..20], green);
+    assert_eq!(map.texel_alpha_at(vec2(1.5, 0.5), &images), Some(1.0));
+}
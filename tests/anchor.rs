@@ -0,0 +1,115 @@
+use bevy::{
+    math::{uvec2, vec2, Vec3Swizzles},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+struct Setup {
+    app: App,
+    map: Entity,
+    anchored: Entity,
+    material: Handle<Map>,
+}
+
+fn setup(policy: AnchorOrphanPolicy) -> Setup {
+    let mut app = common::map_app();
+    app.add_systems(Update, update_tile_anchors::<NoCustomization>);
+    let map = Map::builder(uvec2(8, 8), default(), vec2(16.0, 16.0)).build();
+    let material = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    let map = app
+        .world_mut()
+        .spawn((
+            material.clone(),
+            GlobalTransform::from_xyz(100.0, 50.0, 0.0),
+        ))
+        .id();
+    let anchored = app
+        .world_mut()
+        .spawn((
+            TileAnchor::new(map, uvec2(2, 1)).with_orphan_policy(policy),
+            Transform::from_xyz(0.0, 0.0, 5.0),
+            GlobalTransform::default(),
+        ))
+        .id();
+    app.update();
+    Setup {
+        app,
+        map,
+        anchored,
+        material,
+    }
+}
+
+impl Setup {
+    /// Where the anchored entity should be for `tile`
+    fn expected(&self, tile: UVec2) -> Vec3 {
+        let map = self.app.world().resource::<Assets<Map>>();
+        let local = map.get(&self.material).unwrap().tile_center_local(tile);
+        let map_transform = self.app.world().get::<GlobalTransform>(self.map).unwrap();
+        map_transform
+            .transform_point(local.extend(0.0))
+            .xy()
+            .extend(5.0)
+    }
+
+    fn translation(&self) -> Vec3 {
+        let transform = self.app.world().get::<Transform>(self.anchored).unwrap();
+        let global = self
+            .app
+            .world()
+            .get::<GlobalTransform>(self.anchored)
+            .unwrap();
+        assert_eq!(global.translation(), transform.translation);
+        transform.translation
+    }
+}
+
+#[test]
+fn anchored_entities_follow_their_tile() {
+    let mut setup = setup(default());
+    assert_eq!(setup.translation(), setup.expected(uvec2(2, 1)));
+
+    // Moving the anchor to another tile
+    setup
+        .app
+        .world_mut()
+        .get_mut::<TileAnchor>(setup.anchored)
+        .unwrap()
+        .tile = uvec2(5, 7);
+    setup.app.update();
+    assert_eq!(setup.translation(), setup.expected(uvec2(5, 7)));
+    assert_ne!(setup.expected(uvec2(5, 7)), setup.expected(uvec2(2, 1)));
+
+    // Moving the map
+    *setup
+        .app
+        .world_mut()
+        .get_mut::<GlobalTransform>(setup.map)
+        .unwrap() = GlobalTransform::from_xyz(-20.0, 0.0, 0.0);
+    setup.app.update();
+    assert_eq!(setup.translation(), setup.expected(uvec2(5, 7)));
+}
+
+#[test]
+fn anchored_entities_are_despawned_with_their_map() {
+    let mut setup = setup(AnchorOrphanPolicy::Despawn);
+    setup.app.world_mut().despawn(setup.map);
+    setup.app.update();
+    assert!(setup.app.world().get_entity(setup.anchored).is_none());
+}
+
+#[test]
+fn orphaned_entities_stay_in_place() {
+    let mut setup = setup(AnchorOrphanPolicy::Orphan);
+    let before = setup.translation();
+    setup.app.world_mut().despawn(setup.map);
+    setup.app.update();
+    assert!(setup
+        .app
+        .world()
+        .get::<TileAnchor>(setup.anchored)
+        .is_none());
+    assert_eq!(setup.translation(), before);
+}
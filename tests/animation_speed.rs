@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    time::TimeUpdateStrategy,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+const STEP: Duration = Duration::from_millis(250);
+
+fn app() -> App {
+    let mut app = common::map_app();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(STEP))
+        .add_systems(PostUpdate, apply_map_animation_speeds::<NoCustomization>);
+    app
+}
+
+fn add_map(app: &mut App) -> Handle<Map> {
+    let map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0)).build();
+    app.world_mut().resource_mut::<Assets<Map>>().add(map)
+}
+
+/// Current animation state of the meshes, as set by the map systems
+fn clock(app: &App) -> f32 {
+    app.world().resource::<Time>().elapsed_seconds_wrapped()
+}
+
+/// Animation state the shader of the map sees
+fn state(app: &App, handle: &Handle<Map>) -> f32 {
+    let maps = app.world().resource::<Assets<Map>>();
+    maps.get(handle).unwrap().scaled_animation_state(clock(app))
+}
+
+fn set_speed(app: &mut App, handle: &Handle<Map>, speed: f32) {
+    let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+    maps.get_mut(handle).unwrap().set_animation_speed(speed);
+}
+
+fn assert_near(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-4, "{a} != {b}");
+}
+
+#[test]
+fn maps_animate_at_their_own_speed_from_the_same_clock() {
+    let mut app = app();
+    let world = add_map(&mut app);
+    let ui = add_map(&mut app);
+    for _ in 0..4 {
+        app.update();
+    }
+    assert_near(state(&app, &world), clock(&app));
+
+    // Bullet time for the world, the UI keeps running at full speed
+    set_speed(&mut app, &world, 0.3);
+    app.update();
+    let (start_clock, start) = (clock(&app), state(&app, &world));
+    // No jump when changing speed
+    assert_near(start, start_clock);
+    for _ in 0..8 {
+        app.update();
+    }
+    let elapsed = clock(&app) - start_clock;
+    assert_near(elapsed, 8.0 * STEP.as_secs_f32());
+    assert_near(state(&app, &world), start + 0.3 * elapsed);
+    assert_near(state(&app, &ui), clock(&app));
+}
+
+#[test]
+fn paused_maps_freeze_and_resume_where_they_were() {
+    let mut app = app();
+    let map = add_map(&mut app);
+    for _ in 0..4 {
+        app.update();
+    }
+    set_speed(&mut app, &map, 0.0);
+    app.update();
+    let frozen = state(&app, &map);
+    for _ in 0..8 {
+        app.update();
+    }
+    assert_near(state(&app, &map), frozen);
+
+    set_speed(&mut app, &map, 1.0);
+    app.update();
+    let resumed_at = clock(&app);
+    app.update();
+    assert_near(state(&app, &map), frozen + clock(&app) - resumed_at);
+    assert_eq!(
+        app.world()
+            .resource::<Assets<Map>>()
+            .get(&map)
+            .unwrap()
+            .animation_speed(),
+        1.0
+    );
+}
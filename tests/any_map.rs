@@ -0,0 +1,100 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+#[derive(Clone, TypePath, Default)]
+struct Other;
+
+impl Customization for Other {
+    const SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x3f0c6a5e91d24b7e);
+    type UserData = DefaultUserData;
+    type ExtraBindings = NoExtraBindings;
+
+    fn custom_shader_code() -> String {
+        r#"
+        struct UserData {
+            dummy: u32,
+        };
+
+        fn sample_tile(in: ExtractIn) -> vec4<f32> {
+            return sample_tile_at(in.tile_index, in.tile_position, in.tile_offset_texels);
+        }
+        "#
+        .to_string()
+    }
+}
+
+const DIMMED: Color = Color::srgb(0.5, 0.5, 0.5);
+
+/// Written once for all customizations
+fn dim_all_maps(mut commands: Commands, maps: Query<&AnyMapHandle>) {
+    for map in maps.iter() {
+        map.modify(&mut commands, |map| map.set_color_filter(DIMMED));
+    }
+}
+
+#[test]
+fn dims_maps_of_all_customizations() {
+    let mut app = common::map_app();
+    app.init_asset::<Map<Other>>().add_systems(
+        Update,
+        (
+            insert_any_map_handles::<NoCustomization>,
+            insert_any_map_handles::<Other>,
+        ),
+    );
+
+    let map: Map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0)).build();
+    let other: Map<Other> = Map::builder(uvec2(8, 2), default(), vec2(16.0, 16.0)).build();
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    let other_handle = app
+        .world_mut()
+        .resource_mut::<Assets<Map<Other>>>()
+        .add(other);
+    app.world_mut().spawn(handle.clone());
+    app.world_mut().spawn(other_handle.clone());
+    app.update();
+
+    // Reading without knowing the customization
+    let mut sizes: Vec<UVec2> = app
+        .world_mut()
+        .query::<&AnyMapHandle>()
+        .iter(app.world())
+        .filter_map(|handle| handle.get(app.world()))
+        .map(|map| map.map_size())
+        .collect();
+    sizes.sort_by_key(|size| size.x);
+    assert_eq!(sizes, [uvec2(4, 4), uvec2(8, 2)]);
+
+    app.add_systems(PostUpdate, dim_all_maps);
+    app.update();
+    let world = app.world();
+    let filter = world
+        .resource::<Assets<Map>>()
+        .get(&handle)
+        .unwrap()
+        .color_filter();
+    assert_eq!(filter, DIMMED.to_linear().into());
+    let other = world
+        .resource::<Assets<Map<Other>>>()
+        .get(&other_handle)
+        .unwrap();
+    assert_eq!(other.color_filter(), DIMMED.to_linear().into());
+}
+
+#[test]
+fn removed_maps_are_skipped() {
+    let mut app = common::map_app();
+    let map: Map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0)).build();
+    let id = app.world_mut().resource_mut::<Assets<Map>>().add(map).id();
+    let handle = AnyMapHandle::new(id);
+    assert!(handle.get(app.world()).is_some());
+
+    app.world_mut().resource_mut::<Assets<Map>>().remove(id);
+    assert!(handle.get(app.world()).is_none());
+    assert!(handle.get_mut(app.world_mut()).is_none());
+}
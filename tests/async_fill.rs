@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+fn app() -> App {
+    let mut app = common::loading_app();
+    app.add_systems(
+        Update,
+        apply_async_map_fills::<NoCustomization>.before(update_loading_maps::<NoCustomization>),
+    );
+    app
+}
+
+#[test]
+fn map_loads_once_filled() {
+    let mut app = app();
+    let atlas = app
+        .world_mut()
+        .resource_mut::<Assets<Image>>()
+        .add(Image::new_fill(
+            Extent3d {
+                width: 64,
+                height: 16,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[255; 4],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::all(),
+        ));
+    let (map, fill) = Map::builder(uvec2(64, 32), atlas, vec2(16.0, 16.0))
+        .build_and_set_async(|p| (p.x + p.y) % 4);
+    assert_eq!(map.uniform_tile(), Some(0));
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    let entity = app
+        .world_mut()
+        .spawn((
+            MapBundleManaged {
+                material: handle.clone(),
+                ..default()
+            },
+            fill,
+        ))
+        .id();
+
+    for _ in 0..500 {
+        if app.world().get::<MapAsyncFill>(entity).is_none() {
+            break;
+        }
+        // Loading waits for the tiles
+        assert!(app.world().get::<MapLoading>(entity).is_some());
+        app.update();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    app.update();
+    assert!(app.world().get::<MapAsyncFill>(entity).is_none());
+    assert!(app.world().get::<MapLoading>(entity).is_none());
+
+    let maps = app.world().resource::<Assets<Map>>();
+    let indexer = maps.get(&handle).unwrap().indexer();
+    assert!(indexer
+        .positions()
+        .all(|p| indexer.at_uvec(p) == (p.x + p.y) % 4));
+}
+
+#[test]
+fn replace_texture_checks_the_size() {
+    let mut map: Map = Map::builder(uvec2(4, 2), default(), vec2(16.0, 16.0)).build();
+    assert_eq!(
+        map.replace_texture(vec![1; 6]),
+        Err(MapError::WrongTileCount {
+            expected: 8,
+            actual: 6
+        })
+    );
+    assert_eq!(map.replace_texture((0..8).collect()), Ok(()));
+    assert_eq!(map.indexer().at(3, 1), 7);
+    assert_eq!(map.replace_texture(vec![2; 8]), Ok(()));
+    assert_eq!(map.uniform_tile(), Some(2));
+}
@@ -0,0 +1,122 @@
+use std::{fs, path::PathBuf, thread, time::Duration};
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+/// Asset directory with the given files, unique per test
+fn asset_dir(test: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bevy_fast_tilemap_{test}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for (name, content) in files {
+        fs::write(dir.join(name), content).unwrap();
+    }
+    dir
+}
+
+fn app(dir: PathBuf) -> App {
+    let mut app = common::map_app_in(&dir);
+    app.init_asset::<AtlasMetadata>()
+        .init_asset_loader::<AtlasMetadataLoader>()
+        .add_systems(
+            Update,
+            (
+                load_atlas_metadata::<NoCustomization>,
+                update_loading_maps::<NoCustomization>,
+            )
+                .chain(),
+        );
+    app
+}
+
+/// Spawn a map with a 128x64 atlas at `atlas.png`, built with 16x16 tiles
+fn spawn_map(app: &mut App) -> (Entity, Handle<Map>) {
+    // No image loader is registered, so the atlas is only known by its path until it is inserted
+    let atlas: Handle<Image> = app.world().resource::<AssetServer>().load("atlas.png");
+    let map = Map::builder(uvec2(4, 4), atlas.clone(), vec2(16.0, 16.0))
+        .with_atlas_metadata()
+        .build();
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    let entity = app
+        .world_mut()
+        .spawn(MapBundleManaged {
+            material: handle.clone(),
+            ..default()
+        })
+        .id();
+
+    let image = Image::new_fill(
+        Extent3d {
+            width: 128,
+            height: 64,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[255; 4],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    );
+    app.world_mut()
+        .resource_mut::<Assets<Image>>()
+        .insert(&atlas, image);
+    (entity, handle)
+}
+
+/// Update until the map finished loading
+fn load(app: &mut App, entity: Entity) {
+    for _ in 0..200 {
+        app.update();
+        if app.world().get::<MapLoading>(entity).is_none() {
+            return;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    panic!("map did not finish loading");
+}
+
+#[test]
+fn sidecar_overrides_builder_values() {
+    let dir = asset_dir(
+        "sidecar",
+        &[(
+            "atlas.tiles.ron",
+            "(tile_size: (32.0, 32.0), inner_padding: (0.0, 0.0), n_tiles: Some((4, 2)))",
+        )],
+    );
+    let mut app = app(dir);
+    let (entity, handle) = spawn_map(&mut app);
+    load(&mut app, entity);
+
+    let map = app.world().resource::<Assets<Map>>().get(&handle).unwrap();
+    assert_eq!(map.tile_size(), vec2(32.0, 32.0));
+    // The world tile size follows unless set explicitly
+    assert_eq!(map.world_tile_size(), vec2(32.0, 32.0));
+    assert_eq!(map.world_size(), vec2(4.0 * 32.0, 4.0 * 32.0));
+}
+
+#[test]
+fn missing_sidecar_keeps_builder_values() {
+    let dir = asset_dir("missing_sidecar", &[]);
+    let mut app = app(dir);
+    let (entity, handle) = spawn_map(&mut app);
+    load(&mut app, entity);
+
+    let map = app.world().resource::<Assets<Map>>().get(&handle).unwrap();
+    assert_eq!(map.tile_size(), vec2(16.0, 16.0));
+    assert_eq!(map.world_size(), vec2(4.0 * 16.0, 4.0 * 16.0));
+}
+
+#[test]
+fn metadata_path_replaces_the_extension() {
+    let path = atlas_metadata_path(&"tiles/atlas.png".into());
+    assert_eq!(path, "tiles/atlas.tiles.ron".into());
+}
@@ -0,0 +1,117 @@
+use bevy::{
+    math::uvec2,
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+/// Atlas of `columns` x 2 tiles of 16x16 pixels
+fn atlas_image(columns: u32) -> Image {
+    Image::new_fill(
+        Extent3d {
+            width: 16 * columns,
+            height: 32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[255; 4],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    )
+}
+
+fn app() -> App {
+    let mut app = common::map_app();
+    app.add_event::<MapAtlasReady>().add_systems(
+        Update,
+        (
+            update_loading_maps::<NoCustomization>,
+            update_reloaded_atlases::<NoCustomization>,
+            send_map_atlas_ready::<NoCustomization>,
+        )
+            .chain(),
+    );
+    app
+}
+
+fn ready_events(app: &mut App) -> Vec<(AssetId<Map>, UVec2)> {
+    app.world_mut()
+        .resource_mut::<Events<MapAtlasReady>>()
+        .drain()
+        .map(|ev| (ev.map, ev.n_tiles))
+        .collect()
+}
+
+#[test]
+fn atlas_ready_is_sent_once_per_layout() {
+    let mut app = app();
+    let atlas = app
+        .world_mut()
+        .resource_mut::<Assets<Image>>()
+        .add(atlas_image(4));
+    let map = Map::builder(uvec2(8, 8), atlas.clone(), Vec2::splat(16.0)).build_and_set(|_| 0);
+    assert_eq!(map.atlas_tile_count(), None);
+    assert_eq!(map.validate_indices(), Ok(()));
+
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    app.world_mut().spawn(MapBundleManaged {
+        material: handle.clone(),
+        ..default()
+    });
+    app.update();
+    assert_eq!(ready_events(&mut app), vec![(handle.id(), uvec2(4, 2))]);
+    let maps = app.world().resource::<Assets<Map>>();
+    assert_eq!(maps.get(&handle).unwrap().atlas_tile_count(), Some(8));
+
+    // Further changes to the map don't derive the layout again
+    app.world_mut()
+        .resource_mut::<Assets<Map>>()
+        .get_mut(&handle)
+        .unwrap()
+        .indexer_mut()
+        .set(0, 0, 1);
+    app.update();
+    app.update();
+    assert_eq!(ready_events(&mut app), vec![]);
+
+    // Runtime swap to an atlas of a different size
+    app.world_mut()
+        .resource_mut::<Assets<Image>>()
+        .insert(&atlas, atlas_image(8));
+    app.update();
+    app.update();
+    assert_eq!(ready_events(&mut app), vec![(handle.id(), uvec2(8, 2))]);
+}
+
+#[test]
+fn validate_indices_reports_tiles_beyond_the_atlas() {
+    let mut app = app();
+    let atlas = app
+        .world_mut()
+        .resource_mut::<Assets<Image>>()
+        .add(atlas_image(4));
+    let map = Map::builder(uvec2(4, 4), atlas, Vec2::splat(16.0)).build_and_set(|p| {
+        if p == uvec2(2, 3) {
+            8
+        } else {
+            7 | TILE_FLIP_X
+        }
+    });
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    app.world_mut().spawn(MapBundleManaged {
+        material: handle.clone(),
+        ..default()
+    });
+    app.update();
+
+    let maps = app.world().resource::<Assets<Map>>();
+    assert_eq!(
+        maps.get(&handle).unwrap().validate_indices(),
+        Err(vec![(uvec2(2, 3), 8)])
+    );
+}
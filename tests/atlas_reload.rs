@@ -0,0 +1,142 @@
+use bevy::{
+    math::uvec2,
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+    sprite::Mesh2dHandle,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+/// Atlas of `columns` x 1 tiles of 16x16 pixels
+fn atlas_image(columns: u32) -> Image {
+    Image::new_fill(
+        Extent3d {
+            width: 16 * columns,
+            height: 16,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[255; 4],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    )
+}
+
+fn app() -> App {
+    let mut app = common::map_app();
+    app.add_systems(
+        Update,
+        (
+            update_loading_maps::<NoCustomization>,
+            update_reloaded_atlases::<NoCustomization>,
+        )
+            .chain(),
+    );
+    app
+}
+
+/// Loaded map with an atlas of 4 columns
+fn spawn_map(app: &mut App) -> (Entity, Handle<Map>, Handle<Image>) {
+    let atlas = app
+        .world_mut()
+        .resource_mut::<Assets<Image>>()
+        .add(atlas_image(4));
+    let map = Map::builder(uvec2(8, 8), atlas.clone(), Vec2::splat(16.0)).build();
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    let entity = app
+        .world_mut()
+        .spawn(MapBundleManaged {
+            material: handle.clone(),
+            ..default()
+        })
+        .id();
+    app.update();
+    (entity, handle, atlas)
+}
+
+fn columns(app: &App, map: &Handle<Map>) -> Option<u32> {
+    app.world()
+        .resource::<Assets<Map>>()
+        .get(map)?
+        .atlas_columns()
+}
+
+fn has_map_sampler(app: &App, atlas: &Handle<Image>) -> bool {
+    let image = app.world().resource::<Assets<Image>>().get(atlas).unwrap();
+    matches!(image.sampler, ImageSampler::Descriptor(_))
+}
+
+#[test]
+fn map_recovers_from_atlas_reinsert() {
+    let mut app = app();
+    let (_, map, atlas) = spawn_map(&mut app);
+    assert_eq!(columns(&app, &map), Some(4));
+    assert!(has_map_sampler(&app, &atlas));
+
+    app.world_mut()
+        .resource_mut::<Assets<Image>>()
+        .remove(&atlas);
+    app.update();
+
+    // Back with a new size
+    app.world_mut()
+        .resource_mut::<Assets<Image>>()
+        .insert(&atlas, atlas_image(8));
+    app.world_mut()
+        .resource_mut::<Events<AssetEvent<Map>>>()
+        .clear();
+    app.update();
+    app.update();
+
+    assert_eq!(columns(&app, &map), Some(8));
+    assert!(has_map_sampler(&app, &atlas));
+    // The map was modified, so it is extracted again with the new atlas
+    let events = app.world().resource::<Events<AssetEvent<Map>>>();
+    assert!(events
+        .get_reader()
+        .read(events)
+        .any(|ev| ev.is_modified(&map)));
+}
+
+#[test]
+fn explicit_reload() {
+    let mut app = app();
+    let (entity, map, atlas) = spawn_map(&mut app);
+    let old_mesh = app.world().get::<Mesh2dHandle>(entity).unwrap().0.clone();
+
+    let world = app.world_mut();
+    world.resource_scope(|world, mut maps: Mut<Assets<Map>>| {
+        let mut commands = world.commands();
+        maps.get_mut(&map).unwrap().reload(&mut commands, entity);
+    });
+    world.flush();
+    assert!(app.world().get::<MapLoading>(entity).is_some());
+
+    app.update();
+    assert!(app.world().get::<MapLoading>(entity).is_none());
+    assert_eq!(columns(&app, &map), Some(4));
+    assert!(has_map_sampler(&app, &atlas));
+    // The mesh is rebuilt for the reloaded map, the map size did not change so the shared mesh
+    // of that size is re-used
+    let mesh = &app.world().get::<Mesh2dHandle>(entity).unwrap().0;
+    assert_eq!(*mesh, old_mesh);
+    let world_size = app
+        .world()
+        .resource::<Assets<Map>>()
+        .get(&map)
+        .unwrap()
+        .world_size();
+    let aabb = app
+        .world()
+        .resource::<Assets<Mesh>>()
+        .get(mesh)
+        .unwrap()
+        .compute_aabb()
+        .unwrap();
+    assert!((aabb.half_extents.truncate() * 2.0 - world_size).length() < 1e-4);
+}
@@ -0,0 +1,78 @@
+use bevy::{math::uvec2, prelude::*};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+const WATER: u32 = 16;
+
+/// 5x5 map autotiled with [`AutoTiler::cardinal`], 16 variants for each terrain
+fn field() -> Map {
+    let mut map: Map = Map::builder(uvec2(5, 5), Handle::default(), Vec2::splat(16.0)).build();
+    map.set_autotile(
+        AutoTiler::cardinal(|terrain| terrain * 16),
+        TerrainSource::tiles(|v| v / 16),
+    );
+    map
+}
+
+fn tiles(map: &Map) -> Vec<Vec<u32>> {
+    let indexer = map.indexer();
+    (0..5)
+        .map(|y| (0..5).map(|x| indexer.at(x, y)).collect())
+        .collect()
+}
+
+#[test]
+fn whole_map_is_autotiled_initially() {
+    let mut map = field();
+    assert!(map.needs_autotile());
+    map.apply_autotile();
+    assert!(!map.needs_autotile());
+    // Tiles outside of the map count as the terrain of value 0
+    assert!(tiles(&map).iter().flatten().all(|v| *v == 15));
+}
+
+#[test]
+fn terrain_edit_is_autotiled_in_the_same_frame() {
+    let mut app = common::map_app();
+    app.add_systems(PostUpdate, apply_autotiling::<NoCustomization>);
+
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(field());
+    app.update();
+
+    let edit = handle.clone();
+    app.add_systems(Update, move |mut maps: ResMut<Assets<Map>>| {
+        maps.get_mut(&edit).unwrap().indexer_mut().set(2, 2, WATER);
+    });
+    app.update();
+
+    let maps = app.world().resource::<Assets<Map>>();
+    let map = maps.get(&handle).unwrap();
+    assert!(!map.needs_autotile());
+    assert_eq!(
+        tiles(map),
+        [
+            [15, 15, 15, 15, 15],
+            [15, 15, 11, 15, 15],
+            [15, 13, WATER, 7, 15],
+            [15, 15, 14, 15, 15],
+            [15, 15, 15, 15, 15],
+        ]
+    );
+}
+
+#[test]
+fn autotiler_writes_do_not_retrigger() {
+    let mut map = field();
+    map.apply_autotile();
+    map.indexer_mut().set(0, 0, WATER);
+    map.apply_autotile();
+    assert_eq!(map.indexer().at(0, 0), WATER);
+    assert_eq!(map.indexer().at(1, 0), 7);
+    assert!(!map.needs_autotile());
+
+    map.clear_autotile();
+    map.indexer_mut().set(4, 4, WATER);
+    assert!(!map.needs_autotile());
+    assert_eq!(map.indexer().at(4, 4), WATER);
+}
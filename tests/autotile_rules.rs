@@ -0,0 +1,114 @@
+use std::collections::HashSet;
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+const GRASS: u32 = 0;
+const WATER: u32 = 1;
+const SAND: u32 = 2;
+
+fn rules() -> AutotileRules {
+    AutotileRules::new()
+        .with_terrain(WATER, 16, AutotileLayout::Blob47)
+        .with_terrain(SAND, 64, AutotileLayout::Wang16)
+}
+
+fn shown(map: &Map) -> Vec<Vec<u32>> {
+    let size = map.map_size();
+    (0..size.y)
+        .map(|y| (0..size.x).map(|x| map.shown_tile(uvec2(x, y))).collect())
+        .collect()
+}
+
+#[test]
+fn blob_indices() {
+    let indices: HashSet<u32> = (0..256).map(autotile_index).collect();
+    assert_eq!(indices.len(), 47);
+    assert!(indices.iter().all(|i| *i < 47));
+    assert_eq!(autotile_index(0), 0);
+    assert_eq!(autotile_index(0xff), 46);
+    // Diagonals only count between two cardinal neighbors
+    assert_eq!(autotile_index(0b0000_0010), 0);
+    assert_eq!(autotile_index(0b0000_0011), autotile_index(0b0000_0001));
+    assert_ne!(autotile_index(0b0000_0111), autotile_index(0b0000_0101));
+    // Higher bits are ignored
+    assert_eq!(autotile_index(0x1ff), 46);
+
+    assert_eq!(AutotileLayout::Blob47.tile_offset(0xff), 46);
+    assert_eq!(AutotileLayout::Wang16.tile_offset(0xff), 15);
+    assert_eq!(AutotileLayout::Wang16.tile_offset(0b0101_0000), 4 | 8);
+}
+
+#[test]
+fn terrains_show_transition_tiles() {
+    let map: Map = Map::builder(uvec2(5, 5), default(), vec2(16.0, 16.0))
+        .with_autotile(rules())
+        .build_and_set(|p| if p == uvec2(2, 2) { SAND } else { WATER });
+
+    assert_eq!(map.autotile_rule(WATER), Some((16, AutotileLayout::Blob47)));
+    assert_eq!(map.autotile_rule(GRASS), None);
+    assert_eq!(map.autotile_rule(7), None);
+    // Stored values stay terrain IDs
+    assert_eq!(map.indexer().at(2, 2), SAND);
+
+    let full = 16 + 46;
+    let shown = shown(&map);
+    // Neighbors outside of the map count as the same terrain
+    assert_eq!(shown[0], [full; 5]);
+    // Isolated sand
+    assert_eq!(shown[2][2], 64);
+    // Water next to sand in the south-east: The corner is missing
+    assert_eq!(shown[1][1], 16 + autotile_index(0xff & !0b0000_1000));
+    // Water next to sand in the east, so both diagonals to the east are missing too
+    assert_eq!(shown[2][1], 16 + autotile_index(0b1100_0001 | 0b0011_0000));
+}
+
+#[test]
+fn edits_change_neighbors() {
+    let mut map: Map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+        .with_autotile(rules())
+        .build_and_set(|_| SAND);
+    assert!(shown(&map).iter().flatten().all(|tile| *tile == 64 + 15));
+
+    map.indexer_mut().set(1, 1, GRASS | TILE_FLIP_X);
+    // Flags of the stored value apply to the shown tile, terrains without rules show as stored
+    assert_eq!(map.shown_tile(uvec2(1, 1)), GRASS | TILE_FLIP_X);
+    assert_eq!(map.shown_tile(uvec2(1, 0)), 64 + (15 & !4));
+    assert_eq!(map.shown_tile(uvec2(2, 1)), 64 + (15 & !8));
+    assert_eq!(map.shown_tile(uvec2(0, 1)), 64 + (15 & !2));
+    assert_eq!(map.shown_tile(uvec2(1, 2)), 64 + (15 & !1));
+    // Diagonals don't matter for Wang tiles
+    assert_eq!(map.shown_tile(uvec2(2, 2)), 64 + 15);
+
+    map.indexer_mut().set(3, 3, SAND | TILE_FLIP_Y);
+    assert_eq!(map.shown_tile(uvec2(3, 3)), (64 + 15) | TILE_FLIP_Y);
+}
+
+#[test]
+fn repeated_maps_wrap_neighbors() {
+    let map: Map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+        .with_repeat_content()
+        .with_autotile(rules())
+        .build_and_set(|p| if p.x == 0 { GRASS } else { SAND });
+    // The west neighbor of x = 1 and the east neighbor of x = 3 are grass
+    assert_eq!(map.shown_tile(uvec2(1, 0)), 64 + (15 & !8));
+    assert_eq!(map.shown_tile(uvec2(3, 0)), 64 + (15 & !2));
+    assert_eq!(map.shown_tile(uvec2(2, 0)), 64 + 15);
+}
+
+#[test]
+fn content_hash_covers_rules() {
+    let build = |rules: AutotileRules| -> Map {
+        Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+            .with_autotile(rules)
+            .build_and_set(|p| p.x % 3)
+    };
+    let plain: Map =
+        Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0)).build_and_set(|p| p.x % 3);
+    assert!(build(AutotileRules::new()).content_eq(&plain));
+    assert!(build(rules()).content_eq(&build(rules())));
+    assert!(!build(rules()).content_eq(&plain));
+}
@@ -0,0 +1,154 @@
+use bevy::{
+    math::{ivec2, uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+const CHUNK_SIZE: UVec2 = uvec2(4, 4);
+const TILE_SIZE: Vec2 = vec2(16.0, 16.0);
+
+/// Provided value of world tile `p`
+fn terrain_at(p: IVec2) -> u32 {
+    (p.x + 100) as u32 + 1000 * (p.y + 100) as u32
+}
+
+fn terrain(chunk: IVec2, m: &mut MapIndexerMut) {
+    for local in m.positions() {
+        m.set_uvec(
+            local,
+            terrain_at(chunk * CHUNK_SIZE.as_ivec2() + local.as_ivec2()),
+        );
+    }
+}
+
+fn app(load_radius: u32, unload_radius: u32) -> (App, Entity, Entity) {
+    let mut app = common::map_app();
+    app.add_systems(Update, update_chunked_maps::<NoCustomization>);
+    let chunked = app
+        .world_mut()
+        .spawn(
+            ChunkedMap::new(CHUNK_SIZE, TILE_SIZE, default(), terrain)
+                .with_radius(load_radius, unload_radius),
+        )
+        .id();
+    let camera = app
+        .world_mut()
+        .spawn((ChunkedMapCamera, GlobalTransform::from_xyz(8.0, -8.0, 0.0)))
+        .id();
+    // Loading is spread over several frames
+    for _ in 0..3 {
+        app.update();
+    }
+    (app, chunked, camera)
+}
+
+fn loaded(app: &App, chunked: Entity) -> Vec<(IVec2, Entity)> {
+    let chunked = app.world().get::<ChunkedMap>(chunked).unwrap();
+    let mut chunks: Vec<_> = chunked.loaded_chunks().collect();
+    chunks.sort_by_key(|(coord, _)| (coord.y, coord.x));
+    chunks
+}
+
+fn tile(app: &App, chunked: Entity, world_tile: IVec2) -> Option<u32> {
+    let maps = app.world().resource::<Assets<Map>>();
+    let chunked = app.world().get::<ChunkedMap>(chunked).unwrap();
+    chunked.tile(world_tile, maps)
+}
+
+fn chunk_map(app: &App, chunked: Entity, coord: IVec2) -> &Map {
+    let chunked = app.world().get::<ChunkedMap>(chunked).unwrap();
+    let entity = chunked.chunk_entity(coord).unwrap();
+    let handle = app.world().get::<Handle<Map>>(entity).unwrap();
+    app.world().resource::<Assets<Map>>().get(handle).unwrap()
+}
+
+fn set_tile(app: &mut App, chunked: Entity, world_tile: IVec2, v: u32) -> bool {
+    app.world_mut()
+        .resource_scope(|world, mut maps: Mut<Assets<Map>>| {
+            let chunked = world.get::<ChunkedMap>(chunked).unwrap();
+            chunked.set_tile(world_tile, v, &mut maps)
+        })
+}
+
+#[test]
+fn writes_go_to_the_chunk_of_the_tile() {
+    let (mut app, chunked, _) = app(1, 2);
+    let coords: Vec<IVec2> = loaded(&app, chunked).iter().map(|(c, _)| *c).collect();
+    let expected: Vec<IVec2> = (-1..=1)
+        .flat_map(|y| (-1..=1).map(move |x| ivec2(x, y)))
+        .collect();
+    assert_eq!(coords, expected);
+    assert_eq!(
+        tile(&app, chunked, ivec2(-3, 5)),
+        Some(terrain_at(ivec2(-3, 5)))
+    );
+
+    // Tiles on both sides of chunk boundaries, including negative coordinates
+    let writes = [
+        (ivec2(3, 3), 1),
+        (ivec2(4, 3), 2),
+        (ivec2(3, 4), 3),
+        (ivec2(-1, 0), 4),
+        (ivec2(0, -1), 5),
+    ];
+    for (p, v) in writes {
+        assert!(set_tile(&mut app, chunked, p, v));
+    }
+    for (p, v) in writes {
+        assert_eq!(tile(&app, chunked, p), Some(v), "{p}");
+    }
+
+    // Each write ended up at the local position in its own chunk
+    let map_of = |coord| chunk_map(&app, chunked, coord);
+    assert_eq!(map_of(ivec2(0, 0)).indexer().at(3, 3), 1);
+    assert_eq!(map_of(ivec2(1, 0)).indexer().at(0, 3), 2);
+    assert_eq!(map_of(ivec2(0, 1)).indexer().at(3, 0), 3);
+    assert_eq!(map_of(ivec2(-1, 0)).indexer().at(3, 0), 4);
+    assert_eq!(map_of(ivec2(0, -1)).indexer().at(0, 3), 5);
+
+    // Tiles of chunks that are not loaded are not written
+    assert!(!set_tile(&mut app, chunked, ivec2(100, 0), 6));
+    assert_eq!(tile(&app, chunked, ivec2(100, 0)), None);
+}
+
+#[test]
+fn scrolling_reuses_chunks() {
+    let (mut app, chunked, camera) = app(0, 0);
+    let chunks = loaded(&app, chunked);
+    assert_eq!(chunks.len(), 1);
+    let (coord, entity) = chunks[0];
+    assert_eq!(coord, IVec2::ZERO);
+    let translation = app.world().get::<Transform>(entity).unwrap().translation;
+    assert!(set_tile(&mut app, chunked, ivec2(1, 1), 7));
+
+    // Five chunks to the right
+    let scrolled = GlobalTransform::from_xyz(8.0 + 5.0 * 64.0, -8.0, 0.0);
+    *app.world_mut().get_mut::<GlobalTransform>(camera).unwrap() = scrolled;
+    app.update();
+    assert_eq!(loaded(&app, chunked), vec![(ivec2(5, 0), entity)]);
+    assert_eq!(app.world().resource::<Assets<Map>>().len(), 1);
+    let chunk = app.world().get::<MapChunk>(entity).unwrap();
+    assert_eq!((chunk.chunked_map, chunk.coord), (chunked, ivec2(5, 0)));
+    let moved = app.world().get::<Transform>(entity).unwrap().translation;
+    assert_eq!(moved - translation, Vec3::new(5.0 * 64.0, 0.0, 0.0));
+    assert_eq!(
+        tile(&app, chunked, ivec2(21, 1)),
+        Some(terrain_at(ivec2(21, 1)))
+    );
+
+    // Scrolling back refills the chunk, changes are lost
+    let back = GlobalTransform::from_xyz(8.0, -8.0, 0.0);
+    *app.world_mut().get_mut::<GlobalTransform>(camera).unwrap() = back;
+    app.update();
+    assert_eq!(loaded(&app, chunked), vec![(IVec2::ZERO, entity)]);
+    assert_eq!(
+        tile(&app, chunked, ivec2(1, 1)),
+        Some(terrain_at(ivec2(1, 1)))
+    );
+    assert_eq!(
+        app.world().get::<Visibility>(entity),
+        Some(&Visibility::Inherited)
+    );
+}
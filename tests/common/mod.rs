@@ -0,0 +1,112 @@
+//! App setup shared by the integration tests.
+
+// Each test uses only some of these
+#![allow(dead_code)]
+
+use std::path::Path;
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+        RenderPlugin,
+    },
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
+use bevy_fast_tilemap::prelude::*;
+
+/// Headless app with the assets maps need (maps, images and meshes), but no map systems.
+pub fn map_app() -> App {
+    with_map_assets(AssetPlugin::default())
+}
+
+/// Like [`map_app`], loading assets from `dir`.
+pub fn map_app_in(dir: &Path) -> App {
+    with_map_assets(AssetPlugin {
+        file_path: dir.to_string_lossy().into_owned(),
+        ..default()
+    })
+}
+
+/// [`map_app`] in which maps load and get their managed meshes in `Update`.
+pub fn loading_app() -> App {
+    let mut app = map_app();
+    app.add_systems(
+        Update,
+        (
+            update_loading_maps::<NoCustomization>,
+            update_map_vertex_attributes::<NoCustomization>,
+        )
+            .chain(),
+    );
+    app
+}
+
+/// App rendering with the GPU (without a window) into an image, with [`FastTileMapPlugin`].
+/// Tests using it are ignored by default, run them with `cargo test -- --ignored`.
+pub fn render_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        DefaultPlugins
+            .build()
+            .disable::<WinitPlugin>()
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            })
+            .set(RenderPlugin {
+                synchronous_pipeline_compilation: true,
+                ..default()
+            }),
+        FastTileMapPlugin::default(),
+    ));
+    // Done by `App::run` otherwise
+    app.finish();
+    app.cleanup();
+
+    let mut target = solid_image(64, TextureFormat::Bgra8UnormSrgb);
+    target.texture_descriptor.usage |= TextureUsages::RENDER_ATTACHMENT;
+    let target = app.world_mut().resource_mut::<Assets<Image>>().add(target);
+    app.world_mut().spawn(Camera2dBundle {
+        camera: Camera {
+            target: RenderTarget::Image(target),
+            ..default()
+        },
+        ..default()
+    });
+    app
+}
+
+/// Atlas of white 16x16 tiles for [`render_app`].
+pub fn solid_atlas(app: &mut App) -> Handle<Image> {
+    let atlas = solid_image(16, TextureFormat::Rgba8UnormSrgb);
+    app.world_mut().resource_mut::<Assets<Image>>().add(atlas)
+}
+
+fn solid_image(size: u32, format: TextureFormat) -> Image {
+    Image::new_fill(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[255; 4],
+        format,
+        RenderAssetUsages::all(),
+    )
+}
+
+fn with_map_assets(asset_plugin: AssetPlugin) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, asset_plugin))
+        .init_asset::<Image>()
+        .init_asset::<Mesh>()
+        .init_resource::<SharedMapMeshes>()
+        .init_asset::<Map>();
+    app
+}
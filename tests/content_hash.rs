@@ -0,0 +1,84 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+fn chunk(f: impl FnMut(UVec2) -> u32) -> Map {
+    Map::builder(uvec2(16, 8), default(), vec2(16., 16.)).build_and_set(f)
+}
+
+fn terrain(p: UVec2) -> u32 {
+    (p.x * 7 + p.y * 3) % 5
+}
+
+#[test]
+fn identical_builds_hash_the_same() {
+    let a = chunk(terrain);
+    let b = chunk(terrain);
+    assert_eq!(a.content_hash(), b.content_hash());
+    assert_eq!(a.content_hash(), a.content_hash());
+    assert!(a.content_eq(&b));
+}
+
+#[test]
+fn one_tile_edit_changes_the_hash() {
+    let a = chunk(terrain);
+    let mut b = chunk(terrain);
+    b.indexer_mut().set(15, 7, 9);
+    assert_ne!(a.content_hash(), b.content_hash());
+    assert!(!a.content_eq(&b));
+}
+
+#[test]
+fn uniform_maps_hash_like_their_tiles() {
+    // Ocean chunks, built uniform and written tile by tile
+    let a = chunk(|_| 3);
+    assert_eq!(a.uniform_tile(), Some(3));
+    let mut b = chunk(terrain);
+    let mut m = b.indexer_mut();
+    for p in m.positions().collect::<Vec<_>>() {
+        m.set_uvec(p, 3);
+    }
+    assert_eq!(b.uniform_tile(), None);
+    assert_eq!(a.content_hash(), b.content_hash());
+    assert!(a.content_eq(&b));
+}
+
+#[test]
+fn incremental_hash_matches_recompute() {
+    let mut map = chunk(terrain);
+    map.content_hash();
+
+    let mut edits = Vec::new();
+    for (p, value) in [(uvec2(0, 0), 7), (uvec2(5, 3), 8), (uvec2(15, 7), 9)] {
+        map.indexer_mut().set_uvec(p, value);
+        edits.push((p, value));
+        // Only the written row is hashed again here
+        let recomputed = chunk(|q| {
+            let edit = edits.iter().find(|(e, _)| *e == q);
+            edit.map_or(terrain(q), |(_, value)| *value)
+        });
+        assert_eq!(map.content_hash(), recomputed.content_hash());
+    }
+
+    map.resize(uvec2(8, 4), 0);
+    let recomputed = Map::builder(uvec2(8, 4), default(), vec2(16., 16.))
+        .build_and_set(|p| map.indexer().at_uvec(p));
+    assert_eq!(map.content_hash(), recomputed.content_hash());
+}
+
+#[test]
+fn rendering_channels_participate_but_transforms_do_not() {
+    let a = chunk(terrain);
+    let emissive = Map::builder(uvec2(16, 8), default(), vec2(16., 16.))
+        .with_emissive_tiles(vec![2..3], 1.0)
+        .build_and_set(terrain);
+    assert_ne!(a.content_hash(), emissive.content_hash());
+
+    let mut moved = chunk(terrain);
+    moved.apply_transform(&GlobalTransform::from_xyz(100.0, 0.0, 0.0));
+    moved.set_color_filter(Color::srgb(0.5, 0.5, 0.5));
+    assert_eq!(a.content_hash(), moved.content_hash());
+    assert!(a.content_eq(&moved));
+}
@@ -0,0 +1,84 @@
+use bevy::{
+    asset::AssetEvents,
+    math::{uvec2, URect},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+#[derive(Resource, Default)]
+struct Modified(usize);
+
+fn count_modified(mut events: EventReader<AssetEvent<Map>>, mut modified: ResMut<Modified>) {
+    modified.0 += events
+        .read()
+        .filter(|event| matches!(event, AssetEvent::Modified { .. }))
+        .count();
+}
+
+fn app(debug: FastTileMapDebug) -> (App, Handle<Map>) {
+    let mut app = common::map_app();
+    app.insert_resource(debug)
+        .init_resource::<Modified>()
+        .add_systems(PostUpdate, update_map_debug::<NoCustomization>)
+        .add_systems(Last, count_modified.after(AssetEvents));
+    let map = Map::builder(uvec2(16, 16), default(), Vec2::splat(16.0)).build_and_set(|p| p.x);
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    app.update();
+    app.update();
+    app.world_mut().resource_mut::<Modified>().0 = 0;
+    (app, handle)
+}
+
+fn map(app: &App, handle: &Handle<Map>) -> Map {
+    app.world()
+        .resource::<Assets<Map>>()
+        .get(handle)
+        .unwrap()
+        .clone()
+}
+
+#[test]
+fn written_tiles_are_tinted_and_fade_out() {
+    let (mut app, handle) = app(FastTileMapDebug {
+        dirty_region_frames: 4,
+        ..FastTileMapDebug::show_dirty_regions(true)
+    });
+    // Initial upload fades out
+    for _ in 0..4 {
+        app.update();
+    }
+    assert_eq!(map(&app, &handle).debug_dirty_region(), None);
+
+    let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+    let mut m = maps.get_mut(&handle).unwrap().indexer_mut();
+    m.set(2, 3, 7);
+    m.set(5, 1, 7);
+    app.update();
+    assert_eq!(
+        map(&app, &handle).debug_dirty_region(),
+        Some((URect::new(2, 1, 6, 4), 1.0))
+    );
+
+    app.update();
+    assert_eq!(map(&app, &handle).debug_dirty_region().unwrap().1, 0.75);
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(map(&app, &handle).debug_dirty_region(), None);
+
+    // Nothing to show, so the map is left alone
+    app.world_mut().resource_mut::<Modified>().0 = 0;
+    app.update();
+    assert_eq!(app.world().resource::<Modified>().0, 0);
+}
+
+#[test]
+fn disabled_by_default() {
+    let (mut app, handle) = app(default());
+    let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+    maps.get_mut(&handle).unwrap().indexer_mut().set(2, 3, 7);
+    app.update();
+    assert_eq!(map(&app, &handle).debug_dirty_region(), None);
+}
@@ -0,0 +1,71 @@
+//! Pins the formula of `Map::depth_at`, changes to these values are breaking changes.
+
+use bevy::{
+    math::{mat3, uvec2, vec2, vec3},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+fn map(size: UVec2, projection: TileProjection) -> Map {
+    Map::builder(size, default(), vec2(16., 16.))
+        .with_projection(projection)
+        .build()
+}
+
+fn assert_depths(map: &Map, expected: &[(Vec2, f32)]) {
+    for &(position, depth) in expected {
+        let actual = map.depth_at(position);
+        assert!(
+            (actual - depth).abs() < 1e-5,
+            "{position}: {actual} != {depth}"
+        );
+    }
+}
+
+#[test]
+fn axonometric_depth() {
+    let map = map(uvec2(10, 10), AXONOMETRIC);
+    assert_eq!(map.max_depth(), 10.0);
+    assert_depths(
+        &map,
+        &[
+            // Top corner, furthest away
+            (vec2(0.0, 10.0), 0.0),
+            // Bottom corner, closest
+            (vec2(10.0, 0.0), 10.0),
+            (vec2(0.0, 0.0), 5.0),
+            (vec2(10.0, 10.0), 5.0),
+            (vec2(2.5, 4.5), 4.0),
+        ],
+    );
+}
+
+#[test]
+fn custom_projection_depth() {
+    let projection = TileProjection {
+        projection: mat3(
+            vec3(1.0, 0.0, 0.25),
+            vec3(0.0, 1.0, 0.5),
+            vec3(0.0, 0.0, 1.0),
+        ),
+        ..IDENTITY
+    };
+    let map = map(uvec2(8, 4), projection);
+    assert_eq!(map.max_depth(), 4.0);
+    assert_depths(
+        &map,
+        &[
+            (vec2(0.0, 0.0), 0.0),
+            (vec2(8.0, 4.0), 4.0),
+            (vec2(6.0, 1.0), 2.0),
+            (vec2(1.0, 3.0), 1.75),
+        ],
+    );
+}
+
+#[test]
+fn flat_projection_has_no_depth() {
+    let map = map(uvec2(8, 4), IDENTITY);
+    assert_eq!(map.max_depth(), 0.0);
+    assert_depths(&map, &[(vec2(0.0, 0.0), 0.0), (vec2(5.5, 2.0), 0.0)]);
+}
@@ -0,0 +1,97 @@
+use bevy::{diagnostic::DiagnosticsStore, math::uvec2, prelude::*};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+type Counters = MapDiagnosticsPlugin<NoCustomization>;
+
+fn app() -> App {
+    let mut app = common::map_app();
+    app.add_plugins(Counters::default());
+    app
+}
+
+fn latest(app: &App, path: bevy::diagnostic::DiagnosticPath) -> Option<f64> {
+    let store = app.world().resource::<DiagnosticsStore>();
+    store.get(&path)?.value()
+}
+
+#[test]
+fn counts_maps_and_uploads() {
+    let mut app = app();
+    let handles: Vec<Handle<Map>> = (0..3)
+        .map(|_| {
+            let map = Map::builder(uvec2(4, 4), default(), Vec2::splat(16.0)).build();
+            app.world_mut().resource_mut::<Assets<Map>>().add(map)
+        })
+        .collect();
+    app.update();
+    app.update();
+    assert_eq!(latest(&app, Counters::maps_path()), Some(3.0));
+    assert_eq!(latest(&app, Counters::map_uploads_path()), Some(3.0));
+
+    app.update();
+    assert_eq!(latest(&app, Counters::map_uploads_path()), Some(0.0));
+    // Uniforms are only written by the render app
+    assert_eq!(latest(&app, Counters::map_uniform_writes_path()), Some(0.0));
+
+    // Several changes of the same map are uploaded once
+    let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+    for _ in 0..2 {
+        maps.get_mut(&handles[1])
+            .unwrap()
+            .indexer_mut()
+            .set(0, 0, 1);
+    }
+    app.update();
+    app.update();
+    assert_eq!(latest(&app, Counters::map_uploads_path()), Some(1.0));
+}
+
+#[test]
+fn counts_upload_bytes() {
+    let mut app = app();
+    let map = Map::builder(uvec2(64, 32), default(), Vec2::splat(16.0)).build_and_set(|p| p.x);
+    let size = map.upload_size();
+    // At least the tiles
+    assert!(size >= 64 * 32 * 4);
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    app.update();
+    app.update();
+    assert_eq!(
+        latest(&app, Counters::map_upload_bytes_path()),
+        Some(size as f64)
+    );
+
+    app.update();
+    assert_eq!(latest(&app, Counters::map_upload_bytes_path()), Some(0.0));
+
+    let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+    maps.get_mut(&handle).unwrap().indexer_mut().set(0, 0, 1);
+    app.update();
+    app.update();
+    assert_eq!(
+        latest(&app, Counters::map_upload_bytes_path()),
+        Some(size as f64)
+    );
+}
+
+#[test]
+fn reports_memory() {
+    let mut app = app();
+    let map = Map::builder(uvec2(64, 32), default(), Vec2::splat(16.0)).build();
+    let cpu = 64 * 32 * 4 + map.memory_info(&default()).cpu_buffer_bytes;
+    let gpu = map.upload_size();
+    app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    app.update();
+    app.update();
+    assert_eq!(
+        latest(&app, Counters::map_cpu_bytes_path()),
+        Some(cpu as f64)
+    );
+    // No atlas loaded
+    assert_eq!(
+        latest(&app, Counters::map_gpu_bytes_path()),
+        Some(gpu as f64)
+    );
+}
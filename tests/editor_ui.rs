@@ -0,0 +1,100 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_egui::egui;
+use bevy_fast_tilemap::prelude::*;
+
+/// Loaded 8x8 map of zeros using the debug atlas
+fn setup() -> (Assets<Map>, Handle<Map>, Assets<Image>) {
+    let mut images = Assets::<Image>::default();
+    let (atlas, tile_size) = debug_atlas(&mut images);
+    let mut map = Map::builder(uvec2(8, 8), atlas, tile_size).build_and_set(|_| 0);
+    map.update(&images);
+    let mut maps = Assets::<Map>::default();
+    let handle = maps.add(map);
+    (maps, handle, images)
+}
+
+fn tiles(maps: &Assets<Map>, handle: &Handle<Map>) -> Vec<u32> {
+    let m = maps.get(handle).unwrap().indexer();
+    m.positions().map(|p| m.at_uvec(p)).collect()
+}
+
+#[test]
+fn atlas_rects_follow_the_atlas_layout() {
+    let (maps, handle, _) = setup();
+    let map = maps.get(&handle).unwrap();
+    let tile_size = map.tile_size();
+    assert_eq!(
+        map.atlas_rect(17),
+        Some(Rect::from_corners(
+            tile_size * vec2(1.0, 2.0),
+            tile_size * vec2(2.0, 3.0)
+        ))
+    );
+    assert_eq!(map.atlas_rect(64), None);
+}
+
+#[test]
+fn brush_fill_and_pick() {
+    let (mut maps, handle, _) = setup();
+    let mut state = EditorUiState::new(egui::TextureId::User(0));
+    state.selected = 5;
+    state.brush_size = 3;
+
+    // Nothing hovered
+    assert!(!state.apply_tool(&mut maps, &handle));
+
+    state.hovered = Some(uvec2(0, 0));
+    assert!(state.apply_tool(&mut maps, &handle));
+    let painted = tiles(&maps, &handle).iter().filter(|t| **t == 5).count();
+    // Clipped at the map corner
+    assert_eq!(painted, 4);
+    assert!(!state.apply_tool(&mut maps, &handle));
+
+    state.tool = EditorTool::Fill;
+    state.selected = 7;
+    state.hovered = Some(uvec2(4, 4));
+    assert!(state.apply_tool(&mut maps, &handle));
+    let t = tiles(&maps, &handle);
+    assert_eq!(t.iter().filter(|t| **t == 7).count(), 60);
+    assert_eq!(t.iter().filter(|t| **t == 5).count(), 4);
+
+    state.tool = EditorTool::Pick;
+    state.hovered = Some(uvec2(1, 1));
+    assert!(!state.apply_tool(&mut maps, &handle));
+    assert_eq!(state.selected, 5);
+}
+
+#[test]
+fn hover_is_limited_to_the_map() {
+    let (maps, handle, _) = setup();
+    let map = maps.get(&handle).unwrap();
+    let mut state = EditorUiState::new(egui::TextureId::User(0));
+    let transform = GlobalTransform::IDENTITY;
+
+    state.hover(map, &transform, Some(map.map_to_local(vec2(2.5, 3.5))));
+    assert_eq!(state.hovered, Some(uvec2(2, 3)));
+    state.hover(map, &transform, Some(map.map_to_local(vec2(-0.5, 3.5))));
+    assert_eq!(state.hovered, None);
+    state.hover(map, &transform, None);
+    assert_eq!(state.hovered, None);
+}
+
+#[test]
+fn panel_runs_headless() {
+    let (mut maps, handle, images) = setup();
+    let mut state = EditorUiState::new(egui::TextureId::User(0));
+    state.hovered = Some(uvec2(1, 1));
+
+    let ctx = egui::Context::default();
+    let mut changed = true;
+    let _ = ctx.run(egui::RawInput::default(), |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            changed =
+                fast_tilemap_editor_ui(ui, maps.get_mut(&handle).unwrap(), &images, &mut state);
+        });
+    });
+    assert!(!changed);
+}
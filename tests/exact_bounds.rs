@@ -0,0 +1,93 @@
+use bevy::{
+    math::{uvec2, vec2, vec3},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+
+fn red_atlas(images: &mut Assets<Image>) -> Handle<Image> {
+    images.add(Image::new_fill(
+        Extent3d {
+            width: 16,
+            height: 16,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &RED,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    ))
+}
+
+fn chunk(atlas: Handle<Image>, exact: bool) -> Map {
+    Map::builder(uvec2(64, 64), atlas, vec2(16.0, 16.0))
+        .with_dominance_overhang()
+        .with_exact_bounds(exact)
+        .build()
+}
+
+#[test]
+fn exact_bounds_have_no_margin() {
+    let map = chunk(default(), true);
+    assert!(map.has_exact_bounds());
+    assert_eq!(map.world_size(), vec2(1024.0, 1024.0));
+    assert!(chunk(default(), false)
+        .world_size()
+        .cmpgt(map.world_size())
+        .all());
+
+    // Projected maps are exactly as large as their projected tiles
+    let iso: Map = Map::builder(uvec2(8, 8), default(), vec2(32.0, 16.0))
+        .with_projection(AXONOMETRIC)
+        .with_dominance_overhang()
+        .with_exact_bounds(true)
+        .build();
+    assert_eq!(iso.world_size(), iso.world_bounds().size());
+}
+
+#[test]
+fn adjacent_chunks_share_their_edges() {
+    let map = chunk(default(), true);
+    let size = map.world_size();
+    for y in 0..3 {
+        for x in 0..3 {
+            let transform =
+                GlobalTransform::from_translation((vec2(x as f32, y as f32) * size).extend(0.0));
+            let right = GlobalTransform::from_translation(
+                (vec2(x as f32 + 1.0, y as f32) * size).extend(0.0),
+            );
+            // The right edge of a chunk is the left edge of its right neighbor
+            let edge = transform.transform_point(vec3(0.5 * size.x, 0.0, 0.0)).xy();
+            assert_eq!(map.world_to_map_with(&transform, edge).x, 64.0);
+            assert_eq!(map.world_to_map_with(&right, edge).x, 0.0);
+        }
+    }
+}
+
+#[test]
+fn edge_pixels_show_the_edge_tiles() {
+    let mut images = Assets::<Image>::default();
+    let atlas = red_atlas(&mut images);
+    for scale in [0.5, 0.75, 1.0, 1.5] {
+        let mut map = chunk(atlas.clone(), true);
+        map.update(&images);
+        let rendered = map.render_to_image(&mut images, scale);
+        let data = &images.get(&rendered).unwrap().data;
+        assert!(
+            data.chunks_exact(4).all(|pixel| pixel == RED),
+            "scale {scale}"
+        );
+    }
+
+    // Without exact bounds the margin shows the outside color
+    let mut map = chunk(atlas, false);
+    map.update(&images);
+    let rendered = map.render_to_image(&mut images, 0.75);
+    let data = &images.get(&rendered).unwrap().data;
+    assert_ne!(data[0..4], RED);
+}
@@ -0,0 +1,184 @@
+// `#[derive(ShaderType)]` emits never-called `check` functions.
+#![allow(dead_code)]
+
+use bevy::{
+    asset::AssetEvents,
+    math::uvec2,
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderType},
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+const GAMEPLAY: u32 = 5;
+const AUTOTILED: u32 = 6;
+
+#[derive(Resource)]
+struct TestMap(Handle<Map>);
+
+/// Tile values of the map as seen by each extraction, ie. in frames in which it changed
+#[derive(Resource, Default)]
+struct Extracted(Vec<u32>);
+
+fn gameplay(maps: Res<TestMap>, mut materials: ResMut<Assets<Map>>) {
+    materials
+        .get_mut(&maps.0)
+        .unwrap()
+        .indexer_mut()
+        .set(1, 1, GAMEPLAY);
+}
+
+fn autotile(maps: Res<TestMap>, mut materials: ResMut<Assets<Map>>) {
+    let map = materials.get_mut(&maps.0).unwrap();
+    if map.indexer().at(1, 1) == GAMEPLAY {
+        map.indexer_mut().set(1, 1, AUTOTILED);
+    }
+}
+
+/// Mirrors render asset extraction, which copies changed maps after the main schedule.
+fn inspect_extract(
+    mut events: EventReader<AssetEvent<Map>>,
+    maps: Res<TestMap>,
+    materials: Res<Assets<Map>>,
+    mut extracted: ResMut<Extracted>,
+) {
+    if events.read().any(|ev| ev.is_modified(&maps.0)) {
+        let map = materials.get(&maps.0).unwrap();
+        extracted.0.push(map.indexer().at(1, 1));
+    }
+}
+
+fn app() -> App {
+    let mut app = common::map_app();
+    app.init_resource::<Extracted>()
+        .add_systems(Last, inspect_extract.after(AssetEvents));
+
+    let map = Map::builder(uvec2(4, 4), default(), Vec2::splat(16.0)).build();
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    app.insert_resource(TestMap(handle));
+    app
+}
+
+fn run(app: &mut App) -> Vec<u32> {
+    for _ in 0..5 {
+        app.update();
+    }
+    std::mem::take(&mut app.world_mut().resource_mut::<Extracted>().0)
+}
+
+#[test]
+fn chained_edits_in_update_are_extracted_together() {
+    let mut app = app();
+    app.add_systems(Update, (gameplay, autotile).chain());
+    assert_eq!(run(&mut app), vec![AUTOTILED; 5]);
+}
+
+#[test]
+fn edits_before_prepare_are_extracted_with_update_edits() {
+    let mut app = app();
+    app.add_systems(Update, gameplay)
+        .add_systems(PostUpdate, autotile.before(MapSystems::Prepare));
+    assert_eq!(run(&mut app), vec![AUTOTILED; 5]);
+}
+
+const MARKER: u32 = 7;
+
+#[derive(Debug, Clone, Default, Reflect, AsBindGroup, ShaderType)]
+struct Cursor {
+    position: UVec2,
+}
+
+#[derive(Clone, TypePath, Default)]
+struct CursorMap;
+
+impl Customization for CursorMap {
+    const SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x5c07_41a2_9d3e_18b6);
+    type UserData = Cursor;
+    type ExtraBindings = NoExtraBindings;
+
+    fn custom_shader_code() -> String {
+        r#"
+        struct UserData {
+            position: vec2<u32>,
+        };
+
+        fn sample_tile(in: ExtractIn) -> vec4<f32> {
+            return sample_tile_at(in.tile_index, in.tile_position, in.tile_offset_texels);
+        }
+        "#
+        .to_string()
+    }
+}
+
+#[derive(Resource)]
+struct CursorTestMap(Handle<Map<CursorMap>>);
+
+/// Whether each extraction saw the marker tile at the cursor of the user data, and nowhere else
+#[derive(Resource, Default)]
+struct CursorExtracted(Vec<bool>);
+
+fn cursor_position(frame: u32) -> UVec2 {
+    uvec2(frame % 8, frame / 8 % 8)
+}
+
+fn move_marker(
+    maps: Res<CursorTestMap>,
+    mut materials: ResMut<Assets<Map<CursorMap>>>,
+    mut frame: Local<u32>,
+) {
+    *frame += 1;
+    let mut m = materials.get_mut(&maps.0).unwrap().indexer_mut();
+    let old = cursor_position(*frame - 1);
+    m.set(old.x, old.y, 0);
+    let new = cursor_position(*frame);
+    m.set(new.x, new.y, MARKER);
+}
+
+fn move_cursor(
+    maps: Res<CursorTestMap>,
+    mut materials: ResMut<Assets<Map<CursorMap>>>,
+    mut frame: Local<u32>,
+) {
+    *frame += 1;
+    materials.get_mut(&maps.0).unwrap().user_data.position = cursor_position(*frame);
+}
+
+fn inspect_cursor_extract(
+    mut events: EventReader<AssetEvent<Map<CursorMap>>>,
+    maps: Res<CursorTestMap>,
+    materials: Res<Assets<Map<CursorMap>>>,
+    mut extracted: ResMut<CursorExtracted>,
+) {
+    if events.read().any(|ev| ev.is_modified(&maps.0)) {
+        let map = materials.get(&maps.0).unwrap();
+        let m = map.indexer();
+        let cursor = map.user_data.position;
+        let markers: Vec<_> = m.positions().filter(|p| m.at_uvec(*p) == MARKER).collect();
+        extracted.0.push(markers == [cursor]);
+    }
+}
+
+#[test]
+fn user_data_and_tiles_changed_in_one_frame_are_extracted_together() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Map<CursorMap>>()
+        .init_resource::<CursorExtracted>()
+        .add_systems(Update, move_marker)
+        .add_systems(PostUpdate, move_cursor.in_set(MapSystems::Prepare))
+        .add_systems(Last, inspect_cursor_extract.after(AssetEvents));
+
+    let map = Map::<CursorMap>::builder(uvec2(8, 8), default(), Vec2::splat(16.0))
+        .build_and_set(|p| if p == UVec2::ZERO { MARKER } else { 0 });
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<Map<CursorMap>>>()
+        .add(map);
+    app.insert_resource(CursorTestMap(handle));
+
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<CursorExtracted>().0, vec![true; 20]);
+}
@@ -0,0 +1,132 @@
+use bevy::{asset::AssetEvents, math::uvec2, prelude::*};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+/// Frame number, counted in `First`
+#[derive(Resource, Default)]
+struct Frame(u32);
+
+/// Hook invocations and extractions with their frame
+#[derive(Resource, Default)]
+struct Log(Vec<(u32, &'static str)>);
+
+#[derive(Resource)]
+struct TestMap(Handle<Map>);
+
+fn count_frames(mut frame: ResMut<Frame>) {
+    frame.0 += 1;
+}
+
+fn on_loaded(
+    In(entity): In<Entity>,
+    frame: Res<Frame>,
+    loading: Query<(), With<MapLoading>>,
+    mut log: ResMut<Log>,
+) {
+    assert!(!loading.contains(entity));
+    log.0.push((frame.0, "on_loaded"));
+}
+
+fn before_sync(In(_): In<Entity>, frame: Res<Frame>, mut log: ResMut<Log>) {
+    log.0.push((frame.0, "before_sync"));
+}
+
+fn after_mesh_rebuild(In(_): In<Entity>, frame: Res<Frame>, mut log: ResMut<Log>) {
+    log.0.push((frame.0, "after_mesh_rebuild"));
+}
+
+/// Mirrors render asset extraction, which copies changed maps after the main schedule
+fn inspect_extract(
+    mut events: EventReader<AssetEvent<Map>>,
+    maps: Res<TestMap>,
+    frame: Res<Frame>,
+    mut log: ResMut<Log>,
+) {
+    if events.read().any(|ev| ev.is_modified(&maps.0)) {
+        log.0.push((frame.0, "extract"));
+    }
+}
+
+fn app() -> (App, Handle<Map>) {
+    let mut app = common::map_app();
+    app.init_resource::<Frame>()
+        .init_resource::<Log>()
+        .add_systems(First, count_frames)
+        .add_systems(
+            Update,
+            (
+                update_loading_maps::<NoCustomization>,
+                update_map_vertex_attributes::<NoCustomization>,
+            )
+                .chain(),
+        )
+        .add_systems(
+            Last,
+            (run_map_sync_hooks::<NoCustomization>, inspect_extract)
+                .chain()
+                .after(AssetEvents),
+        );
+
+    let (atlas, tile_size) = debug_atlas(&mut app.world_mut().resource_mut::<Assets<Image>>());
+    let map = Map::builder(uvec2(8, 8), atlas, tile_size).build();
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    app.insert_resource(TestMap(handle.clone()));
+
+    let world = app.world_mut();
+    let hooks = MapHooks {
+        on_loaded: Some(world.register_system(on_loaded)),
+        before_sync: Some(world.register_system(before_sync)),
+        after_mesh_rebuild: Some(world.register_system(after_mesh_rebuild)),
+    };
+    world.spawn((
+        MapBundleManaged {
+            material: handle.clone(),
+            ..default()
+        },
+        hooks,
+    ));
+    (app, handle)
+}
+
+#[test]
+fn hooks_run_in_order_before_the_map_is_extracted() {
+    let (mut app, handle) = app();
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(
+        app.world().resource::<Log>().0,
+        vec![(1, "on_loaded"), (1, "before_sync"), (1, "extract")]
+    );
+
+    // A new size needs a new mesh
+    app.world_mut()
+        .resource_mut::<Assets<Map>>()
+        .get_mut(&handle)
+        .unwrap()
+        .resize(uvec2(4, 4), 0);
+    app.world_mut().resource_mut::<Log>().0.clear();
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(
+        app.world().resource::<Log>().0,
+        vec![
+            (4, "after_mesh_rebuild"),
+            (4, "before_sync"),
+            (4, "extract")
+        ]
+    );
+}
+
+#[test]
+fn maps_without_changes_run_no_hooks() {
+    let (mut app, _) = app();
+    app.update();
+    app.world_mut().resource_mut::<Log>().0.clear();
+    for _ in 0..5 {
+        app.update();
+    }
+    assert!(app.world().resource::<Log>().0.is_empty());
+}
@@ -0,0 +1,116 @@
+use bevy::{asset::AssetEvents, math::uvec2, prelude::*};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+#[derive(Resource, Default)]
+struct Modified(usize);
+
+fn count_modified(mut events: EventReader<AssetEvent<Map>>, mut modified: ResMut<Modified>) {
+    modified.0 += events
+        .read()
+        .filter(|event| matches!(event, AssetEvent::Modified { .. }))
+        .count();
+}
+
+fn app() -> (App, Handle<Map>) {
+    let mut app = common::map_app();
+    app.init_resource::<Modified>()
+        .add_systems(Last, count_modified.after(AssetEvents));
+    let map = Map::builder(uvec2(8, 8), default(), Vec2::splat(16.0)).build_and_set(|_| 1);
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    app.update();
+    app.world_mut().resource_mut::<Modified>().0 = 0;
+    (app, handle)
+}
+
+fn modified(app: &mut App) -> usize {
+    app.update();
+    std::mem::take(&mut app.world_mut().resource_mut::<Modified>().0)
+}
+
+#[test]
+fn idempotent_tile_writes_are_not_uploaded() {
+    let (mut app, handle) = app();
+
+    for _ in 0..1000 {
+        let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+        assert!(!maps.set_tile_if_neq(&handle, uvec2(3, 4), 1));
+        // Out of bounds
+        assert!(!maps.set_tile_if_neq(&handle, uvec2(8, 0), 2));
+    }
+    assert_eq!(modified(&mut app), 0);
+
+    let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+    assert!(maps.set_tile_if_neq(&handle, uvec2(3, 4), 2));
+    assert!(!maps.set_tile_if_neq(&handle, uvec2(3, 4), 2));
+    assert_eq!(maps.get(&handle).unwrap().indexer().at(3, 4), 2);
+    assert_eq!(modified(&mut app), 1);
+}
+
+#[test]
+fn idempotent_user_data_writes_are_not_uploaded() {
+    let (mut app, handle) = app();
+
+    for _ in 0..1000 {
+        let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+        assert!(!maps.set_user_data_if_neq(&handle, DefaultUserData::default()));
+    }
+    assert_eq!(modified(&mut app), 0);
+
+    // A map that is not there is never modified
+    let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+    assert!(!maps.modify_if(AssetId::<Map>::invalid(), |_| true, |_| {}));
+    assert!(maps.modify_if(&handle, |_| true, |map| map.set_emissive_strength(2.0)));
+    assert_eq!(modified(&mut app), 1);
+}
+
+#[test]
+fn idempotent_indexer_writes_are_not_changes() {
+    let mut map: Map = Map::builder(uvec2(8, 8), default(), Vec2::splat(16.0)).build_and_set(|_| 1);
+    map.enable_change_ticks();
+    let tick = map.current_tick();
+    let all = URect::new(0, 0, 8, 8);
+
+    map.indexer_mut().set(3, 4, 1);
+    assert_eq!(map.tiles_changed_since(tick, all).count(), 0);
+
+    map.indexer_mut().set(3, 4, 2);
+    assert_eq!(
+        map.tiles_changed_since(tick, all).collect::<Vec<_>>(),
+        [uvec2(3, 4)]
+    );
+}
+
+#[test]
+fn restoring_a_state_uploads_once() {
+    let (mut app, handle) = app();
+    let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+    let snapshot = maps.get(&handle).unwrap().snapshot_state();
+
+    let map = maps.get_mut(&handle).unwrap();
+    map.set_clip_rect(Some(Rect::new(1.0, 1.0, 4.0, 4.0)));
+    map.set_edge_fade(Vec2::ONE, Color::BLACK);
+    map.set_outside_color(Color::WHITE);
+    map.set_emissive_strength(3.0);
+    map.set_pixel_snap(true);
+    assert!(map.snapshot_state() != snapshot);
+    let changed = map.snapshot_state();
+    modified(&mut app);
+
+    let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+    assert!(maps.restore_state_if_neq(&handle, &snapshot));
+    assert!(!maps.restore_state_if_neq(&handle, &snapshot));
+    let map = maps.get(&handle).unwrap();
+    assert!(map.snapshot_state() == snapshot);
+    assert_eq!(map.clip_rect(), None);
+    assert_eq!(map.emissive_strength(), 1.0);
+    assert!(!map.pixel_snap());
+    assert_eq!(modified(&mut app), 1);
+
+    // And forward again
+    let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+    assert!(maps.restore_state_if_neq(&handle, &changed));
+    assert_eq!(maps.get(&handle).unwrap().emissive_strength(), 3.0);
+    assert_eq!(modified(&mut app), 1);
+}
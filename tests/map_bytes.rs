@@ -0,0 +1,140 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn tiles(map: &Map) -> Vec<u32> {
+    let m = map.indexer();
+    m.positions().map(|p| m.at_uvec(p)).collect()
+}
+
+fn round_trip(map: &Map) -> Map {
+    let bytes = map.to_bytes();
+    MapBuilder::from_bytes(&bytes, default()).unwrap().build()
+}
+
+#[test]
+fn maps_round_trip() {
+    let empty: Map = Map::builder(uvec2(64, 32), default(), vec2(16.0, 16.0)).build();
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let random: Map = Map::builder(uvec2(50, 70), default(), vec2(16.0, 16.0))
+        .build_and_set(|_| rng.gen_range(0..1000));
+
+    // Constant chunks of 16x16 tiles
+    let chunks: Map = Map::builder(uvec2(128, 128), default(), vec2(16.0, 16.0))
+        .build_and_set(|p| (p.x / 16 + p.y / 16 * 8) % 5);
+
+    for map in [empty, random, chunks] {
+        let loaded = round_trip(&map);
+        assert_eq!(loaded.map_size(), map.map_size());
+        assert_eq!(tiles(&loaded), tiles(&map));
+        assert_eq!(loaded.to_bytes(), map.to_bytes());
+    }
+}
+
+#[test]
+fn settings_round_trip() {
+    let map: Map = Map::builder(uvec2(8, 8), default(), vec2(32.0, 16.0))
+        .with_padding(vec2(2.0, 2.0), vec2(1.0, 1.0), vec2(3.0, 3.0))
+        .with_projection(AXONOMETRIC)
+        .with_dominance_overhang()
+        .with_tile_overhang_flags(2..4, OverhangFlags::SOUTH)
+        .build_and_set(|p| p.x);
+
+    let loaded = round_trip(&map);
+    assert_eq!(loaded.world_size(), map.world_size());
+    assert_eq!(loaded.world_bounds(), map.world_bounds());
+    assert_eq!(loaded.tile_overhang_flags(3), Some(OverhangFlags::SOUTH));
+    assert_eq!(loaded.tile_overhang_flags(4), None);
+    assert_eq!(tiles(&loaded), tiles(&map));
+}
+
+#[test]
+fn large_maps_with_runs_are_small() {
+    let map: Map = Map::builder(uvec2(1024, 1024), default(), vec2(16.0, 16.0))
+        .build_and_set(|p| if (p.x / 8 + p.y) % 3 == 0 { 2 } else { 1 });
+    let bytes = map.to_bytes();
+    assert!(bytes.len() < 400_000, "{} bytes", bytes.len());
+    assert_eq!(tiles(&round_trip(&map)), tiles(&map));
+}
+
+#[test]
+fn decode_errors() {
+    let map: Map = Map::builder(uvec2(8, 8), default(), vec2(16.0, 16.0)).build_and_set(|p| p.y);
+    let bytes = map.to_bytes();
+
+    let decode = |bytes: &[u8]| MapBuilder::<NoCustomization>::from_bytes(bytes, default()).err();
+    assert_eq!(decode(b"PNG image"), Some(MapDecodeError::NotAMap));
+    assert_eq!(decode(&[]), Some(MapDecodeError::NotAMap));
+
+    let mut newer = bytes.clone();
+    newer[4] = MAP_FORMAT_VERSION + 1;
+    assert_eq!(
+        decode(&newer),
+        Some(MapDecodeError::UnsupportedVersion {
+            version: MAP_FORMAT_VERSION + 1
+        })
+    );
+
+    let truncated = &bytes[..bytes.len() - 1];
+    assert!(matches!(
+        decode(truncated),
+        Some(MapDecodeError::Corrupt { .. })
+    ));
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert!(matches!(
+        decode(&trailing),
+        Some(MapDecodeError::Corrupt { .. })
+    ));
+}
+
+fn varint(mut value: u64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+    bytes
+}
+
+#[test]
+fn huge_map_sizes_are_rejected() {
+    // A few bytes claiming u32::MAX x u32::MAX tiles
+    let mut bytes = b"FTMP".to_vec();
+    bytes.push(MAP_FORMAT_VERSION);
+    bytes.extend(varint(u32::MAX.into()));
+    bytes.extend(varint(u32::MAX.into()));
+    bytes.extend([0; 6]);
+    assert!(bytes.len() <= 20);
+    assert_eq!(
+        MapBuilder::<NoCustomization>::from_bytes(&bytes, default()).err(),
+        Some(MapDecodeError::Corrupt { reason: "map size" })
+    );
+}
+
+#[test]
+fn single_runs_decode_to_uniform_maps() {
+    let small: Map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0)).build_and_set(|_| 3);
+    let bytes = small.to_bytes();
+    // Same settings, but 8192x8192 tiles, still in a single run
+    let side = 8192;
+    let mut huge = bytes[..5].to_vec();
+    huge.extend(varint(side));
+    huge.extend(varint(side));
+    huge.extend(&bytes[7..bytes.len() - 2]);
+    huge.extend(varint(side * side));
+    huge.push(3);
+
+    let map = MapBuilder::<NoCustomization>::from_bytes(&huge, default())
+        .unwrap()
+        .build();
+    assert_eq!(map.map_size(), uvec2(8192, 8192));
+    assert_eq!(map.uniform_tile(), Some(3));
+    assert_eq!(map.indexer().at(8000, 100), 3);
+    assert_eq!(map.to_bytes(), huge);
+}
@@ -0,0 +1,111 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    sprite::Mesh2dHandle,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+fn atlas_image(width: u32, height: u32) -> Image {
+    Image::new_fill(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[255; 4],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    )
+}
+
+#[test]
+fn try_build_reports_errors() {
+    let mut images = Assets::<Image>::default();
+    // 4 tiles of 16x16 pixels with 2 pixels between them and 1 pixel around them
+    let atlas = images.add(atlas_image(72, 18));
+    let builder = |map_size: UVec2, tile_size: Vec2, bottomright: f32| {
+        Map::<NoCustomization>::builder(map_size, atlas.clone(), tile_size).with_padding(
+            vec2(2.0, 2.0),
+            vec2(1.0, 1.0),
+            vec2(bottomright, 1.0),
+        )
+    };
+
+    let map = builder(uvec2(8, 8), vec2(16.0, 16.0), 1.0).try_build(&images);
+    assert!(map.is_ok());
+
+    let error = builder(uvec2(8, 8), vec2(16.0, 16.0), 4.0).try_build(&images);
+    let Err(MapError::NonIntegralTileCount {
+        n_tiles,
+        outer_padding_bottomright,
+    }) = error
+    else {
+        panic!("{:?}", error.map(|_| ()));
+    };
+    assert!((n_tiles.x - 3.833).abs() < 0.01, "{n_tiles}");
+    assert_eq!(n_tiles.y, 1.0);
+    // Three whole tiles fit
+    assert_eq!(outer_padding_bottomright, vec2(19.0, 1.0));
+
+    let error = builder(uvec2(8, 8), vec2(16.0, 32.0), 1.0).try_build(&images);
+    assert_eq!(
+        error.map(|_| ()),
+        Err(MapError::TileSizeExceedsAtlas {
+            tile_size: vec2(16.0, 32.0),
+            available: vec2(70.0, 16.0),
+        })
+    );
+
+    let error = builder(uvec2(8, 0), vec2(16.0, 16.0), 1.0).try_build(&images);
+    assert_eq!(
+        error.map(|_| ()),
+        Err(MapError::ZeroMapSize {
+            map_size: uvec2(8, 0)
+        })
+    );
+
+    let error = builder(uvec2(8, 8), vec2(0.0, 16.0), 1.0).try_build(&images);
+    assert_eq!(
+        error.map(|_| ()),
+        Err(MapError::ZeroTileSize {
+            tile_size: vec2(0.0, 16.0)
+        })
+    );
+
+    // Without the atlas, only the map and tile size are checked
+    let images = Assets::<Image>::default();
+    let map = builder(uvec2(8, 8), vec2(16.0, 16.0), 4.0).try_build(&images);
+    assert!(map.is_ok());
+}
+
+#[test]
+fn maps_not_fitting_their_atlas_are_marked_broken() {
+    let mut app = common::map_app();
+    app.add_systems(Update, update_loading_maps::<NoCustomization>);
+    let atlas = app
+        .world_mut()
+        .resource_mut::<Assets<Image>>()
+        .add(atlas_image(40, 16));
+    let map = Map::builder(uvec2(8, 8), atlas, vec2(16.0, 16.0)).build();
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    let entity = app
+        .world_mut()
+        .spawn(MapBundleManaged {
+            material: handle,
+            ..default()
+        })
+        .id();
+
+    app.update();
+    app.update();
+    assert!(app.world().get::<MapBroken>(entity).is_some());
+    assert!(app.world().get::<MapLoading>(entity).is_none());
+    assert!(app.world().get::<Mesh2dHandle>(entity).is_none());
+}
@@ -0,0 +1,149 @@
+use bevy::{
+    math::{uvec2, vec2, vec3},
+    prelude::*,
+    render::camera::ScalingMode,
+    transform::TransformSystem,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+#[derive(Component)]
+struct Ship;
+
+fn move_ship(mut ships: Query<&mut Transform, With<Ship>>) {
+    for mut transform in ships.iter_mut() {
+        transform.translation.x += 100.0;
+    }
+}
+
+fn app() -> App {
+    let mut app = common::map_app();
+    app.add_plugins((TransformPlugin, HierarchyPlugin))
+        .add_systems(Update, (update_loading_maps::<NoCustomization>, move_ship))
+        .add_systems(
+            PostUpdate,
+            (
+                sync_map_transforms::<NoCustomization>,
+                update_viewport_fit_meshes::<NoCustomization>,
+            )
+                .after(TransformSystem::TransformPropagate),
+        );
+    app
+}
+
+/// Map with 16x16 tiles as child of a ship at the origin
+fn spawn_ship(app: &mut App, managed: MeshManagedByMap) -> (Entity, Entity, Handle<Map>) {
+    let (atlas, tile_size) = debug_atlas(&mut app.world_mut().resource_mut::<Assets<Image>>());
+    let map = Map::builder(uvec2(100, 100), atlas, tile_size).build();
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    let ship = app.world_mut().spawn((Ship, SpatialBundle::default())).id();
+    let map = app
+        .world_mut()
+        .spawn(MapBundleManaged {
+            material: handle.clone(),
+            mesh_managed_by_map: managed,
+            ..default()
+        })
+        .set_parent(ship)
+        .id();
+    (ship, map, handle)
+}
+
+#[test]
+fn picking_follows_moving_parent_in_the_same_frame() {
+    let mut app = app();
+    let (_, _, handle) = spawn_ship(&mut app, MeshManagedByMap::Full);
+
+    for frame in 1..4 {
+        app.update();
+        let map = app.world().resource::<Assets<Map>>().get(&handle).unwrap();
+        let center = vec2(100.0 * frame as f32, 0.0);
+        assert_eq!(map.world_to_map(center), vec2(50.0, 50.0));
+        assert_eq!(
+            map.world_to_map(center + vec2(16.0, 16.0)),
+            vec2(51.0, 49.0)
+        );
+    }
+}
+
+#[test]
+fn shared_maps_keep_their_transform() {
+    let mut app = app();
+    let (_, _, handle) = spawn_ship(&mut app, MeshManagedByMap::Full);
+    app.world_mut().spawn(MapBundleManaged {
+        material: handle.clone(),
+        ..default()
+    });
+
+    app.update();
+    let map = app.world().resource::<Assets<Map>>().get(&handle).unwrap();
+    // Still the default (identity) transform
+    assert_eq!(map.world_to_map(Vec2::ZERO), vec2(50.0, 50.0));
+}
+
+#[test]
+fn viewport_fit_mesh_follows_parent_scale() {
+    let mut app = app();
+    app.world_mut().spawn((Camera2dBundle {
+        projection: OrthographicProjection {
+            scaling_mode: ScalingMode::Fixed {
+                width: 160.0,
+                height: 160.0,
+            },
+            area: Rect::from_center_size(Vec2::ZERO, vec2(160.0, 160.0)),
+            ..default()
+        },
+        ..default()
+    },));
+    let (ship, map, _) = spawn_ship(&mut app, MeshManagedByMap::ViewportFit);
+    app.world_mut().entity_mut(ship).remove::<Ship>();
+    app.update();
+    app.update();
+    let before = app.world().get::<ViewportFitRect>(map).unwrap().rect();
+
+    // Scaling up the parent shows less of the map, so the mesh shrinks, covering the same
+    // area of the screen
+    app.world_mut().get_mut::<Transform>(ship).unwrap().scale = vec3(4.0, 4.0, 1.0);
+    app.update();
+    let scaled = app.world().get::<ViewportFitRect>(map).unwrap().rect();
+    assert!(scaled.size().cmplt(before.size() / 2.0).all());
+}
+
+#[test]
+fn picking_follows_rotated_and_scaled_parent() {
+    let mut app = app();
+    let (ship, _, handle) = spawn_ship(&mut app, MeshManagedByMap::Full);
+    app.world_mut().entity_mut(ship).remove::<Ship>();
+    app.update();
+
+    *app.world_mut().get_mut::<Transform>(ship).unwrap() =
+        Transform::from_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2))
+            .with_scale(vec3(2.0, 2.0, 1.0));
+    app.update();
+    let map = app.world().resource::<Assets<Map>>().get(&handle).unwrap();
+    // Two tiles up in the world are one tile to the right in the map
+    let position = map.world_to_map(vec2(0.0, 32.0));
+    assert!(position.abs_diff_eq(vec2(51.0, 50.0), 1e-4), "{position}");
+    let world = map.map_to_world_3d(vec3(51.0, 50.0, 0.0)).truncate();
+    assert!(world.abs_diff_eq(vec2(0.0, 32.0), 1e-3), "{world}");
+}
+
+#[test]
+fn replaced_maps_get_the_transform() {
+    let mut app = app();
+    let (ship, _, handle) = spawn_ship(&mut app, MeshManagedByMap::Full);
+    app.world_mut().entity_mut(ship).remove::<Ship>();
+    app.world_mut().get_mut::<Transform>(ship).unwrap().scale = vec3(2.0, 2.0, 1.0);
+    app.update();
+
+    // The entity does not change, but its map does
+    let (atlas, tile_size) = debug_atlas(&mut app.world_mut().resource_mut::<Assets<Image>>());
+    let map = Map::builder(uvec2(10, 10), atlas, tile_size).build();
+    app.world_mut()
+        .resource_mut::<Assets<Map>>()
+        .insert(&handle, map);
+    app.update();
+    let map = app.world().resource::<Assets<Map>>().get(&handle).unwrap();
+    assert_eq!(map.world_to_map(vec2(32.0, 0.0)), vec2(6.0, 5.0));
+}
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+fn app() -> App {
+    let mut app = common::map_app();
+    app.insert_resource(MapLoadStallTimeout(Duration::from_millis(500)))
+        // Virtual time advances at most 250ms per update
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )))
+        .add_event::<MapLoadStalled>()
+        .add_systems(Update, detect_stalled_map_loads::<NoCustomization>);
+    app
+}
+
+fn stalled(app: &mut App) -> Vec<Entity> {
+    let events = app.world_mut().resource_mut::<Events<MapLoadStalled>>();
+    events.get_reader().read(&events).map(|ev| ev.map).collect()
+}
+
+#[test]
+fn bogus_handle_is_reported_once() {
+    let mut app = app();
+    let valid = app
+        .world_mut()
+        .resource_mut::<Assets<Map>>()
+        .add(Map::default());
+    let bogus = app
+        .world_mut()
+        .spawn(MapBundleManaged::<NoCustomization>::default())
+        .id();
+    app.world_mut().spawn(MapBundleManaged {
+        material: valid,
+        ..default()
+    });
+
+    for _ in 0..4 {
+        app.update();
+    }
+    assert!(stalled(&mut app).is_empty());
+
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(stalled(&mut app), vec![bogus]);
+    assert!(app.world().get::<MapBroken>(bogus).is_some());
+
+    // Not reported again
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(stalled(&mut app).is_empty());
+}
+
+#[test]
+fn removed_map_is_marked_broken() {
+    let mut app = app();
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<Map>>()
+        .add(Map::default());
+    let map = app
+        .world_mut()
+        .spawn(MapBundleManaged {
+            material: handle.clone(),
+            ..default()
+        })
+        .id();
+    app.world_mut()
+        .resource_mut::<Assets<Map>>()
+        .remove(&handle);
+
+    // Well before the stall timeout
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(app.world().get::<MapLoading>(map).is_none());
+    assert!(app.world().get::<MapBroken>(map).is_some());
+    assert_eq!(stalled(&mut app), vec![map]);
+
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(stalled(&mut app).is_empty());
+}
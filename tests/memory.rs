@@ -0,0 +1,94 @@
+use bevy::{
+    math::uvec2,
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+
+fn atlas(width: u32, height: u32) -> Image {
+    Image::new_fill(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    )
+}
+
+#[test]
+fn tile_bytes_follow_map_size() {
+    let images = Assets::<Image>::default();
+    let map: Map = Map::builder(uvec2(10, 20), default(), Vec2::splat(16.0)).build();
+    let info = map.memory_info(&images);
+    assert_eq!(info.cpu_tile_bytes, 10 * 20 * 4);
+    assert_eq!(info.estimated_gpu_bytes, map.upload_size());
+    // Atlas not loaded
+    assert_eq!(info.atlas_bytes_estimate, 0);
+    assert_eq!(info.gpu_bytes(), map.upload_size());
+}
+
+#[test]
+fn optional_buffers_are_counted() {
+    let images = Assets::<Image>::default();
+    let mut map: Map = Map::builder(uvec2(8, 8), default(), Vec2::splat(16.0)).build();
+    let before = map.memory_info(&images);
+
+    map.add_patch(Patch::filled(uvec2(1, 1), uvec2(3, 2), 5, 0.5))
+        .unwrap();
+    let after = map.memory_info(&images);
+    // Header of the patch and its tiles
+    assert_eq!(
+        after.cpu_buffer_bytes - before.cpu_buffer_bytes,
+        (6 + 3 * 2) * 4
+    );
+    assert_eq!(after.cpu_tile_bytes, before.cpu_tile_bytes);
+    assert_eq!(
+        after.estimated_gpu_bytes - before.estimated_gpu_bytes,
+        (6 + 3 * 2) * 4
+    );
+}
+
+#[test]
+fn atlas_bytes_from_descriptor() {
+    let mut images = Assets::<Image>::default();
+    let atlas = images.add(atlas(64, 32));
+    let mut secondary = atlas(64, 32);
+    secondary.texture_descriptor.mip_level_count = 2;
+    let secondary = images.add(secondary);
+
+    let map: Map = Map::builder(uvec2(4, 4), atlas.clone(), Vec2::splat(16.0)).build();
+    assert_eq!(map.memory_info(&images).atlas_bytes_estimate, 64 * 32 * 4);
+
+    let map: Map = Map::builder(uvec2(4, 4), atlas, Vec2::splat(16.0))
+        .with_secondary_atlas(secondary)
+        .build();
+    assert_eq!(
+        map.memory_info(&images).atlas_bytes_estimate,
+        64 * 32 * 4 + (64 * 32 + 32 * 16) * 4
+    );
+}
+
+#[test]
+fn total_counts_shared_atlases_once() {
+    let mut images = Assets::<Image>::default();
+    let atlas = images.add(atlas(64, 32));
+    let mut maps = Assets::<Map>::default();
+    let a = Map::builder(uvec2(4, 4), atlas.clone(), Vec2::splat(16.0)).build();
+    let b = Map::builder(uvec2(8, 2), atlas, Vec2::splat(16.0)).build();
+    let sum = a.memory_info(&images) + b.memory_info(&images);
+    maps.add(a);
+    maps.add(b);
+
+    let total = total_map_memory(&maps, &images);
+    assert_eq!(total.cpu_tile_bytes, (16 + 16) * 4);
+    assert_eq!(total.estimated_gpu_bytes, sum.estimated_gpu_bytes);
+    assert_eq!(total.atlas_bytes_estimate, 64 * 32 * 4);
+    assert_eq!(sum.atlas_bytes_estimate, 2 * 64 * 32 * 4);
+}
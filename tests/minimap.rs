@@ -0,0 +1,89 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+/// Atlas of two 16x16 tiles: Red and green with a transparent bottom half.
+fn atlas() -> Image {
+    let mut data = Vec::new();
+    for y in 0..16 {
+        for x in 0..32 {
+            let pixel = match (x < 16, y < 8) {
+                (true, _) => [255, 0, 0, 255],
+                (false, true) => [0, 255, 0, 255],
+                (false, false) => [0, 0, 0, 0],
+            };
+            data.extend(pixel);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: 32,
+            height: 16,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8Unorm,
+        RenderAssetUsages::all(),
+    )
+}
+
+#[test]
+fn average_colors_are_weighted_by_alpha() {
+    let mut images = Assets::<Image>::default();
+    let atlas = images.add(atlas());
+    let mut map: Map = Map::builder(uvec2(4, 4), atlas, vec2(16.0, 16.0)).build();
+    assert_eq!(map.average_tile_color(0, &images), None);
+
+    map.update(&images);
+    assert_eq!(
+        map.average_tile_color(0, &images),
+        Some(LinearRgba::RED.into())
+    );
+    assert_eq!(
+        map.average_tile_color(1, &images),
+        Some(LinearRgba::new(0.0, 1.0, 0.0, 0.5).into())
+    );
+    assert_eq!(map.average_tile_color(2, &images), None);
+}
+
+#[test]
+fn minimaps_follow_their_source() {
+    let mut app = common::map_app();
+    app.add_systems(Update, sync_minimaps::<NoCustomization>);
+
+    let atlas = app.world_mut().resource_mut::<Assets<Image>>().add(atlas());
+    let map = Map::builder(uvec2(64, 32), atlas, vec2(16.0, 16.0)).build_and_set(|p| p.x % 2);
+    let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+    let source = maps.add(map);
+    let minimap = maps.get(&source).unwrap().minimap_builder(&source).build();
+    let minimap = maps.add(minimap);
+
+    app.update();
+    let maps = app.world().resource::<Assets<Map>>();
+    let map = maps.get(&minimap).unwrap();
+    assert!(map.is_minimap());
+    assert_eq!(map.minimap_source(), Some(source.id()));
+    assert!(maps.get(&source).unwrap().minimap_source().is_none());
+    // One world unit per tile, without tiles of its own
+    assert_eq!(map.world_size(), vec2(64.0, 32.0));
+    assert_eq!(map.uniform_tile(), Some(0));
+
+    app.world_mut()
+        .resource_mut::<Assets<Map>>()
+        .get_mut(&source)
+        .unwrap()
+        .resize(uvec2(16, 8), 1);
+    app.update();
+    let map = app.world().resource::<Assets<Map>>().get(&minimap).unwrap();
+    assert_eq!(map.map_size(), uvec2(16, 8));
+    assert_eq!(map.world_size(), vec2(16.0, 8.0));
+}
@@ -0,0 +1,114 @@
+use bevy::{
+    math::{uvec2, vec3},
+    prelude::*,
+    render::{
+        mesh::{MeshVertexAttribute, VertexAttributeValues},
+        render_resource::VertexFormat,
+    },
+    sprite::Mesh2dHandle,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+/// Previous clip position vertex attribute of map meshes
+const ATTRIBUTE_PREVIOUS_CLIP_POSITION: MeshVertexAttribute =
+    MeshVertexAttribute::new("PreviousClipPosition", 988779057, VertexFormat::Float32x4);
+
+fn app() -> App {
+    let mut app = common::loading_app();
+    app.add_plugins(TransformPlugin).add_systems(
+        PostUpdate,
+        update_map_motion_vectors::<NoCustomization>
+            .after(bevy::transform::TransformSystem::TransformPropagate),
+    );
+    // Without a projection, clip space is view space
+    app.world_mut().spawn((
+        Camera::default(),
+        GlobalTransform::IDENTITY,
+        MapMotionVectorCamera,
+    ));
+    app
+}
+
+fn spawn_map(app: &mut App, motion_vectors: bool) -> Entity {
+    let (atlas, tile_size) = debug_atlas(&mut app.world_mut().resource_mut::<Assets<Image>>());
+    let mut map = Map::builder(uvec2(4, 4), atlas, tile_size);
+    if motion_vectors {
+        map = map.with_motion_vectors();
+    }
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<Map>>()
+        .add(map.build());
+    app.world_mut()
+        .spawn(MapBundleManaged {
+            material: handle,
+            ..default()
+        })
+        .id()
+}
+
+/// Vertex positions and previous clip positions of the mesh of `entity`
+fn vertices(app: &App, entity: Entity) -> Option<Vec<(Vec3, Vec4)>> {
+    let mesh = app.world().get::<Mesh2dHandle>(entity).unwrap();
+    let mesh = app.world().resource::<Assets<Mesh>>().get(&mesh.0).unwrap();
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        panic!("unexpected vertex positions");
+    };
+    let Some(VertexAttributeValues::Float32x4(previous)) =
+        mesh.attribute(ATTRIBUTE_PREVIOUS_CLIP_POSITION)
+    else {
+        return None;
+    };
+    Some(
+        positions
+            .iter()
+            .zip(previous)
+            .map(|(p, q)| (Vec3::from(*p), Vec4::from(*q)))
+            .collect(),
+    )
+}
+
+#[test]
+fn previous_clip_positions_follow_the_map() {
+    let mut app = app();
+    let map = spawn_map(&mut app, true);
+    app.update();
+    app.update();
+
+    // Not moved, previous positions are the current ones
+    let unmoved = vertices(&app, map).unwrap();
+    for (p, previous) in &unmoved {
+        assert_eq!(*previous, p.extend(1.0));
+    }
+
+    app.world_mut()
+        .get_mut::<Transform>(map)
+        .unwrap()
+        .translation = vec3(10.0, -5.0, 0.0);
+    app.update();
+
+    // Previous positions are still where the map was in the last frame
+    for (p, previous) in vertices(&app, map).unwrap() {
+        assert_eq!(previous, p.extend(1.0));
+    }
+
+    app.update();
+    for (p, previous) in vertices(&app, map).unwrap() {
+        assert_eq!(previous, (p + vec3(10.0, -5.0, 0.0)).extend(1.0));
+    }
+}
+
+#[test]
+fn maps_without_motion_vectors_are_untouched() {
+    let mut app = app();
+    let map = spawn_map(&mut app, false);
+    app.update();
+    app.update();
+
+    assert!(vertices(&app, map).is_none());
+    assert!(app.world().get::<PreviousMapClipTransform>(map).is_none());
+}
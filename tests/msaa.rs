@@ -0,0 +1,59 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::{
+    core_pipeline::core_2d::Transparent2d,
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{
+        render_phase::ViewSortedRenderPhases, render_resource::PipelineCache, Render, RenderApp,
+        RenderSet,
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+/// Sample count and readiness of the pipelines of the items queued in the last frame
+#[derive(Resource, Clone, Default)]
+struct Queued(Arc<Mutex<Vec<(u32, bool)>>>);
+
+fn record_queued(
+    phases: Res<ViewSortedRenderPhases<Transparent2d>>,
+    pipeline_cache: Res<PipelineCache>,
+    queued: Res<Queued>,
+) {
+    let mut queued = queued.0.lock().unwrap();
+    queued.clear();
+    for item in phases.values().flat_map(|phase| phase.items.iter()) {
+        let descriptor = pipeline_cache.get_render_pipeline_descriptor(item.pipeline);
+        let ready = pipeline_cache.get_render_pipeline(item.pipeline).is_some();
+        queued.push((descriptor.multisample.count, ready));
+    }
+}
+
+#[test]
+#[ignore = "needs a GPU"]
+fn msaa_changes_respecialize_maps() {
+    let mut app = common::render_app();
+    let queued = Queued::default();
+    app.sub_app_mut(RenderApp)
+        .insert_resource(queued.clone())
+        .add_systems(Render, record_queued.in_set(RenderSet::PhaseSort));
+
+    let atlas = common::solid_atlas(&mut app);
+    let map = Map::builder(uvec2(4, 4), atlas, vec2(16.0, 16.0)).build_and_set(|_| 1);
+    let bundle = {
+        let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+        MapBundleManaged::new(map, maps.as_mut())
+    };
+    app.world_mut().spawn(bundle);
+
+    for msaa in [Msaa::Off, Msaa::Sample4, Msaa::Off, Msaa::Sample4] {
+        *app.world_mut().resource_mut::<Msaa>() = msaa;
+        for _ in 0..3 {
+            app.update();
+        }
+        // The map is queued with a pipeline for the new sample count, which is compiled
+        assert_eq!(*queued.0.lock().unwrap(), vec![(msaa.samples(), true)]);
+    }
+}
@@ -0,0 +1,133 @@
+use bevy::{
+    math::{uvec2, vec2, vec3},
+    prelude::*,
+    render::camera::{camera_system, ManualTextureViews, RenderTarget},
+    window::{
+        PrimaryWindow, WindowCreated, WindowRef, WindowResized, WindowResolution,
+        WindowScaleFactorChanged,
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+
+struct Windows {
+    primary: Entity,
+    secondary: Entity,
+}
+
+/// Primary window with a camera at the origin, secondary window with a zoomed in camera
+/// over the same map
+fn app() -> (App, Windows) {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Image>()
+        .init_resource::<ManualTextureViews>()
+        .add_event::<WindowCreated>()
+        .add_event::<WindowResized>()
+        .add_event::<WindowScaleFactorChanged>()
+        .add_systems(PostUpdate, camera_system::<OrthographicProjection>);
+
+    let world = app.world_mut();
+    let primary = world
+        .spawn((
+            Window {
+                resolution: WindowResolution::new(800.0, 600.0),
+                ..default()
+            },
+            PrimaryWindow,
+        ))
+        .id();
+    let secondary = world
+        .spawn(Window {
+            resolution: WindowResolution::new(400.0, 300.0),
+            ..default()
+        })
+        .id();
+
+    world.spawn(Camera2dBundle::default());
+    world.spawn(Camera2dBundle {
+        camera: Camera {
+            target: RenderTarget::Window(WindowRef::Entity(secondary)),
+            ..default()
+        },
+        projection: OrthographicProjection {
+            scale: 0.25,
+            ..default()
+        },
+        transform: Transform::from_translation(vec3(64.0, -32.0, 0.0)),
+        global_transform: GlobalTransform::from_translation(vec3(64.0, -32.0, 0.0)),
+        ..default()
+    });
+
+    app.update();
+    (app, Windows { primary, secondary })
+}
+
+fn cursor_world(app: &mut App, window: Entity, primary: Entity, cursor: Vec2) -> Option<Vec2> {
+    let mut cameras = app.world_mut().query::<(&Camera, &GlobalTransform)>();
+    cursor_to_world(cameras.iter(app.world()), window, Some(primary), cursor)
+}
+
+#[test]
+fn cursor_uses_the_camera_of_its_window() {
+    let (mut app, windows) = app();
+    let Windows { primary, secondary } = windows;
+
+    // Window centers are at the camera positions
+    let world = cursor_world(&mut app, primary, primary, vec2(400.0, 300.0)).unwrap();
+    assert!(world.length() < 1e-3);
+    let world = cursor_world(&mut app, secondary, primary, vec2(200.0, 150.0)).unwrap();
+    assert!((world - vec2(64.0, -32.0)).length() < 1e-3);
+
+    // The secondary camera is zoomed in
+    let world = cursor_world(&mut app, primary, primary, vec2(500.0, 300.0)).unwrap();
+    assert!((world - vec2(100.0, 0.0)).length() < 1e-3);
+    let world = cursor_world(&mut app, secondary, primary, vec2(300.0, 150.0)).unwrap();
+    assert!((world - vec2(64.0 + 25.0, -32.0)).length() < 1e-3);
+}
+
+#[test]
+fn cursor_outside_of_all_viewports() {
+    let (mut app, windows) = app();
+    let unknown = app.world_mut().spawn_empty().id();
+    assert_eq!(
+        cursor_world(&mut app, unknown, windows.primary, vec2(10.0, 10.0)),
+        None
+    );
+    assert_eq!(
+        cursor_world(
+            &mut app,
+            windows.secondary,
+            windows.primary,
+            vec2(500.0, 10.0)
+        ),
+        None
+    );
+}
+
+#[test]
+fn both_windows_see_the_same_map_tiles() {
+    let (mut app, windows) = app();
+    let map: Map = Map::builder(uvec2(16, 16), default(), vec2(16.0, 16.0)).build();
+    let transform = GlobalTransform::IDENTITY;
+
+    // Both cursors are over the same world position, ie. the same tile
+    let a = cursor_world(
+        &mut app,
+        windows.primary,
+        windows.primary,
+        vec2(472.0, 340.0),
+    )
+    .unwrap();
+    let b = cursor_world(
+        &mut app,
+        windows.secondary,
+        windows.primary,
+        vec2(232.0, 182.0),
+    )
+    .unwrap();
+    assert!((a - b).length() < 1e-3);
+    assert_eq!(
+        map.world_to_map_with(&transform, a).floor(),
+        map.world_to_map_with(&transform, b).floor()
+    );
+}
@@ -0,0 +1,119 @@
+use bevy::{
+    math::{ivec2, uvec2, vec2},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+
+const COLORS: [(char, [u8; 4]); 4] = [
+    ('.', [0, 0, 0, 0]),
+    ('g', [0, 255, 0, 255]),
+    ('b', [0, 0, 255, 255]),
+    ('r', [255, 0, 0, 255]),
+];
+
+/// Two 4x4 tiles (green and blue), both padded with red, ie. they overhang by one pixel.
+fn atlas() -> Image {
+    let rows = [
+        "rrrrrrrrrrrr",
+        "rggggrrbbbbr",
+        "rggggrrbbbbr",
+        "rggggrrbbbbr",
+        "rggggrrbbbbr",
+        "rrrrrrrrrrrr",
+    ];
+    let data = rows
+        .iter()
+        .flat_map(|row| row.chars())
+        .flat_map(|c| COLORS.iter().find(|(k, _)| *k == c).unwrap().1)
+        .collect();
+    Image::new(
+        Extent3d {
+            width: 12,
+            height: 6,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    )
+}
+
+/// Middle row of the rendered map with a blue tile left of a green tile.
+fn middle_row(builder: MapBuilder<NoCustomization>, images: &mut Assets<Image>) -> String {
+    let mut map = builder
+        .with_padding(Vec2::splat(2.0), Vec2::ONE, Vec2::ONE)
+        .with_dominance_overhang()
+        .build_and_set(|p| 1 - p.x);
+    map.update(images);
+    let rendered = map.render_to_image(images, 1.0);
+    let image = images.get(&rendered).unwrap();
+    let row = &image.data[5 * 4 * image.width() as usize..6 * 4 * image.width() as usize];
+    row.chunks_exact(4)
+        .map(|c| COLORS.iter().find(|(_, v)| v == c).map_or('?', |(k, _)| *k))
+        .collect()
+}
+
+#[test]
+fn flags_select_the_overhang_directions() {
+    let mut images = Assets::<Image>::default();
+    let atlas = images.add(atlas());
+    let builder = || Map::builder(uvec2(2, 1), atlas.clone(), vec2(4.0, 4.0));
+
+    // By dominance, blue overhangs green (and the outside of the map)
+    assert_eq!(middle_row(builder(), &mut images), "...rbbbbrggg....");
+
+    let blue_west = builder().with_tile_overhang_flags(1..2, OverhangFlags::WEST);
+    assert_eq!(middle_row(blue_west, &mut images), "...rbbbbgggg....");
+
+    // Flags overhang regardless of the tile index
+    let green_west = builder()
+        .with_tile_overhang_flags(0..1, OverhangFlags::WEST)
+        .with_tile_overhang_flags(1..2, OverhangFlags::NONE);
+    assert_eq!(middle_row(green_west, &mut images), "....bbbrgggg....");
+}
+
+#[test]
+fn tiles_without_flags_follow_the_map() {
+    let map: Map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+        .with_dominance_overhang()
+        .with_tile_overhang_flags(4..6, OverhangFlags::SOUTH | OverhangFlags::EAST)
+        .with_tile_overhang_flags(5..6, OverhangFlags::ALL)
+        .build();
+    assert_eq!(map.tile_overhang_flags(3), None);
+    assert_eq!(map.tile_overhang_flags(6), None);
+    assert!(map.tile_overhangs(3, ivec2(0, -1)));
+    assert!(map.tile_overhangs(4, ivec2(1, 1)));
+    assert!(map.tile_overhangs(4, ivec2(0, 2)));
+    assert!(!map.tile_overhangs(4, ivec2(-1, 0)));
+    // Later flags override earlier ones
+    assert_eq!(map.tile_overhang_flags(5), Some(OverhangFlags::ALL));
+}
+
+#[test]
+fn directions_of_offsets() {
+    assert_eq!(
+        OverhangFlags::from_direction(ivec2(0, 0)),
+        OverhangFlags::NONE
+    );
+    assert_eq!(
+        OverhangFlags::from_direction(ivec2(-1, -1)),
+        OverhangFlags::NORTH_WEST
+    );
+    assert_eq!(
+        OverhangFlags::from_direction(ivec2(1, 0)),
+        OverhangFlags::EAST
+    );
+    assert_eq!(
+        OverhangFlags::from_direction(ivec2(-3, 2)),
+        OverhangFlags::SOUTH_WEST
+    );
+    assert_eq!(
+        OverhangFlags::from_direction(ivec2(1, 1)),
+        OverhangFlags::SOUTH_EAST
+    );
+}
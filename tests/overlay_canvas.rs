@@ -0,0 +1,133 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    sprite::Anchor,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+const RED: Color = Color::srgb(1.0, 0.0, 0.0);
+
+fn alpha(map: &Map, texel: UVec2) -> f32 {
+    map.overlay_texel(texel).unwrap().alpha()
+}
+
+#[test]
+fn circles_follow_the_projection() {
+    let mut map = Map::builder(uvec2(8, 8), default(), vec2(32.0, 16.0))
+        .with_projection(AXONOMETRIC)
+        .with_overlay_canvas(4)
+        .build();
+    let center = map.tile_center_world(uvec2(3, 5));
+    map.paint_circle(center, 8.0, RED, CanvasBlend::Over);
+
+    // Texels around the tile center are painted, the rest of the canvas is untouched
+    for texel in [uvec2(13, 21), uvec2(14, 22)] {
+        assert_eq!(map.overlay_texel(texel), Some(RED), "{texel}");
+    }
+    assert_eq!(alpha(&map, uvec2(0, 0)), 0.0);
+    assert_eq!(alpha(&map, uvec2(14, 26)), 0.0);
+    assert_eq!(map.overlay_texel(uvec2(32, 0)), None);
+
+    // A world circle is an ellipse in map space: the next tile along the x axis of the map is
+    // about 18 world units away and covered by a radius of 20, while the next tile along the
+    // diagonal (one step in x and y) is 32 world units away and not reached
+    map.clear_overlay_canvas();
+    map.paint_circle(center, 20.0, RED, CanvasBlend::Over);
+    assert!(alpha(&map, uvec2(17, 22)) > 0.99);
+    assert_eq!(alpha(&map, uvec2(17, 25)), 0.0);
+}
+
+#[test]
+fn blend_modes() {
+    let mut map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+        .with_overlay_canvas(2)
+        .build();
+    let center = map.tile_center_world(uvec2(1, 1));
+    let texel = uvec2(2, 2);
+
+    let blue = Color::srgba(0.0, 0.0, 1.0, 0.5);
+    map.paint_circle(center, 12.0, blue, CanvasBlend::Add);
+    map.paint_circle(center, 12.0, blue, CanvasBlend::Add);
+    assert!(alpha(&map, texel) > 0.99);
+
+    let half = Color::srgba(0.0, 0.0, 0.0, 0.5);
+    map.paint_circle(center, 12.0, half, CanvasBlend::Erase);
+    assert!((alpha(&map, texel) - 0.5).abs() < 0.01);
+
+    map.paint_circle(center, 12.0, RED, CanvasBlend::Replace);
+    assert_eq!(map.overlay_texel(texel), Some(RED));
+}
+
+#[test]
+fn only_painted_rows_are_copied_to_the_image() {
+    let mut app = common::map_app();
+    app.add_systems(Update, update_overlay_canvases::<NoCustomization>);
+
+    let map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+        .with_overlay_canvas(4)
+        .build();
+    assert!(map.overlay_canvas().is_none());
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    app.update();
+
+    let maps = app.world().resource::<Assets<Map>>();
+    let image = maps.get(&handle).unwrap().overlay_canvas().unwrap().clone();
+    let row_bytes = 16 * 4;
+    let images = app.world().resource::<Assets<Image>>();
+    assert_eq!(images.get(&image).unwrap().data.len(), 16 * row_bytes);
+
+    // Mark the first row of the image, which paint further down must leave alone
+    app.world_mut()
+        .resource_mut::<Assets<Image>>()
+        .get_mut(&image)
+        .unwrap()
+        .data[..4]
+        .copy_from_slice(&[1, 2, 3, 4]);
+    let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+    let map = maps.get_mut(&handle).unwrap();
+    let center = map.tile_center_world(uvec2(2, 2));
+    map.paint_circle(center, 6.0, RED, CanvasBlend::Over);
+    app.update();
+
+    let images = app.world().resource::<Assets<Image>>();
+    let data = &images.get(&image).unwrap().data;
+    assert_eq!(data[..4], [1, 2, 3, 4]);
+    let i = 10 * row_bytes + 10 * 4;
+    assert_eq!(data[i..i + 4], [255, 0, 0, 255]);
+}
+
+#[test]
+fn resizing_moves_the_paint_along() {
+    let mut map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+        .with_overlay_canvas(2)
+        .build();
+    let center = map.tile_center_world(uvec2(1, 1));
+    map.paint_circle(center, 12.0, RED, CanvasBlend::Over);
+    map.resize_anchored(uvec2(6, 6), 0, Anchor::BottomRight);
+    assert_eq!(alpha(&map, uvec2(2, 2)), 0.0);
+    assert_eq!(map.overlay_texel(uvec2(6, 6)), Some(RED));
+    assert_eq!(
+        map.overlay_texel(uvec2(11, 11)).map(|c| c.alpha()),
+        Some(0.0)
+    );
+}
+
+#[test]
+fn render_to_image_includes_the_canvas() {
+    let mut images = Assets::<Image>::default();
+    let (atlas, tile_size) = debug_atlas(&mut images);
+    let mut map = Map::builder(uvec2(4, 4), atlas, tile_size)
+        .with_overlay_canvas(4)
+        .build();
+    let center = map.tile_center_world(uvec2(1, 2));
+    map.paint_circle(center, tile_size.x / 2.0, RED, CanvasBlend::Over);
+
+    let rendered = map.render_to_image(&mut images, 1.0);
+    let image = images.get(&rendered).unwrap();
+    let top_left = map.world_size() * vec2(-0.5, 0.5);
+    let pixel = ((center - top_left) * vec2(1.0, -1.0)).as_uvec2();
+    let i = ((pixel.y * image.width() + pixel.x) * 4) as usize;
+    assert_eq!(image.data[i..i + 4], [255, 0, 0, 255]);
+}
@@ -0,0 +1,83 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+fn map() -> Map {
+    Map::builder(uvec2(8, 8), Handle::default(), vec2(16.0, 16.0)).build_and_set(|_| 1)
+}
+
+#[test]
+fn patches_do_not_change_map_data() {
+    let mut map = map();
+    let id = map
+        .add_patch(Patch::filled(uvec2(2, 3), uvec2(2, 2), 5, 0.5))
+        .unwrap();
+    assert!(map.move_patch(id, uvec2(4, 4)));
+    assert_eq!(map.patch(id).unwrap().origin, uvec2(4, 4));
+    assert!(map
+        .indexer()
+        .positions()
+        .all(|p| map.indexer().at_uvec(p) == 1));
+
+    assert_eq!(map.remove_patch(id).map(|p| p.tiles), Some(vec![5; 4]));
+    assert_eq!(map.remove_patch(id), None);
+    assert!(!map.move_patch(id, UVec2::ZERO));
+    assert_eq!(map.patches().count(), 0);
+}
+
+#[test]
+fn patches_keep_insertion_order() {
+    let mut map = map();
+    let a = map
+        .add_patch(Patch::filled(UVec2::ZERO, UVec2::ONE, 2, 1.0))
+        .unwrap();
+    let b = map
+        .add_patch(Patch::filled(UVec2::ZERO, UVec2::ONE, 3, 1.0))
+        .unwrap();
+    let c = map
+        .add_patch(Patch::filled(UVec2::ZERO, UVec2::ONE, 4, 1.0))
+        .unwrap();
+    map.remove_patch(b);
+    // Ids are not reused
+    let d = map
+        .add_patch(Patch::filled(UVec2::ZERO, UVec2::ONE, 5, 1.0))
+        .unwrap();
+    assert_ne!(d, b);
+    assert_eq!(
+        map.patches().map(|(id, _)| id).collect::<Vec<_>>(),
+        [a, c, d]
+    );
+}
+
+#[test]
+fn invalid_patches_are_rejected() {
+    let mut map = map();
+    let patch = Patch {
+        origin: UVec2::ZERO,
+        size: uvec2(2, 2),
+        tiles: vec![1, 2, 3],
+        opacity: 1.0,
+    };
+    assert_eq!(
+        map.add_patch(patch),
+        Err(PatchError::WrongTileCount {
+            expected: 4,
+            actual: 3
+        })
+    );
+
+    for _ in 0..MAX_PATCHES {
+        map.add_patch(Patch::filled(UVec2::ZERO, UVec2::ONE, 2, 1.0))
+            .unwrap();
+    }
+    assert_eq!(
+        map.add_patch(Patch::filled(UVec2::ZERO, UVec2::ONE, 2, 1.0)),
+        Err(PatchError::TooManyPatches)
+    );
+    map.clear_patches();
+    assert!(map
+        .add_patch(Patch::filled(UVec2::ZERO, UVec2::ONE, 2, 1.0))
+        .is_ok());
+}
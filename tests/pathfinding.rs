@@ -0,0 +1,70 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+const FLOOR: u32 = 0;
+const WALL: u32 = 1;
+
+fn walkable(tile: u32) -> Option<u32> {
+    (tile != WALL).then_some(1)
+}
+
+/// 8x8 map with walls where `wall` returns true
+fn map(wall: impl Fn(UVec2) -> bool) -> Map {
+    Map::builder(uvec2(8, 8), default(), vec2(16., 16.)).build_and_set(|p| {
+        if wall(p) {
+            WALL
+        } else {
+            FLOOR
+        }
+    })
+}
+
+#[test]
+fn finds_shortest_path() {
+    // Wall along x = 4 with a gap at y = 7
+    let map = map(|p| p.x == 4 && p.y < 7);
+    let path = find_path(&map, uvec2(0, 0), uvec2(7, 0), walkable).unwrap();
+    assert_eq!(path.first(), Some(&uvec2(0, 0)));
+    assert_eq!(path.last(), Some(&uvec2(7, 0)));
+    // 7 steps up, 7 to the right and 7 down
+    assert_eq!(path.len(), 22);
+    for step in path.windows(2) {
+        let d = (step[0].as_ivec2() - step[1].as_ivec2()).abs();
+        assert_eq!(d.x + d.y, 1);
+        assert_eq!(walkable(map.indexer().at(step[1].x, step[1].y)), Some(1));
+    }
+}
+
+#[test]
+fn no_path() {
+    let map = map(|p| p.x == 4);
+    assert_eq!(find_path(&map, uvec2(0, 0), uvec2(7, 0), walkable), None);
+    let options = PathOptions {
+        connectivity: Connectivity::Eight,
+        ..default()
+    };
+    assert_eq!(
+        find_path_with(&map, uvec2(0, 0), uvec2(7, 7), options, walkable),
+        None
+    );
+}
+
+#[test]
+fn start_is_goal() {
+    let map = map(|_| false);
+    assert_eq!(
+        find_path(&map, uvec2(3, 5), uvec2(3, 5), walkable),
+        Some(vec![uvec2(3, 5)])
+    );
+}
+
+#[test]
+fn blocked_goal() {
+    let map = map(|p| p == uvec2(7, 7));
+    assert_eq!(find_path(&map, uvec2(0, 0), uvec2(7, 7), walkable), None);
+    // Out of bounds goals are never reached either
+    assert_eq!(find_path(&map, uvec2(0, 0), uvec2(8, 7), walkable), None);
+}
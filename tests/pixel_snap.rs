@@ -0,0 +1,67 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+fn app() -> App {
+    let mut app = common::map_app();
+    app.init_resource::<FastTileMapGlobals>()
+        .add_systems(PostUpdate, apply_map_globals::<NoCustomization>);
+    app
+}
+
+fn add_map(app: &mut App, builder: impl FnOnce(MapBuilder) -> MapBuilder) -> Handle<Map> {
+    let map = builder(Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))).build();
+    app.world_mut().resource_mut::<Assets<Map>>().add(map)
+}
+
+fn snapped(app: &App, handle: &Handle<Map>) -> bool {
+    let maps = app.world().resource::<Assets<Map>>();
+    maps.get(handle).unwrap().is_pixel_snapped()
+}
+
+fn set_global(app: &mut App, pixel_snap: bool) {
+    app.world_mut()
+        .resource_mut::<FastTileMapGlobals>()
+        .pixel_snap = pixel_snap;
+    app.update();
+}
+
+#[test]
+fn global_pixel_snap_combines_with_per_map_setting() {
+    let mut app = app();
+    let plain = add_map(&mut app, |b| b);
+    let snapping = add_map(&mut app, |b| b.with_pixel_snap());
+    app.update();
+    assert!(!snapped(&app, &plain));
+    assert!(snapped(&app, &snapping));
+
+    set_global(&mut app, true);
+    assert!(snapped(&app, &plain));
+    assert!(snapped(&app, &snapping));
+
+    set_global(&mut app, false);
+    assert!(!snapped(&app, &plain));
+    assert!(snapped(&app, &snapping));
+
+    // Per-map settings are kept while the global one is set
+    let maps = app.world().resource::<Assets<Map>>();
+    assert!(!maps.get(&plain).unwrap().pixel_snap());
+}
+
+#[test]
+fn maps_added_later_follow_the_global_setting() {
+    let mut app = app();
+    set_global(&mut app, true);
+    let map = add_map(&mut app, |b| b);
+    app.update();
+    assert!(snapped(&app, &map));
+
+    let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+    maps.get_mut(&map).unwrap().set_pixel_snap(false);
+    app.update();
+    assert!(snapped(&app, &map));
+}
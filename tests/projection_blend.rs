@@ -0,0 +1,50 @@
+use bevy::{
+    math::{mat3, uvec2, vec2, vec3},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+/// `AXONOMETRIC` seen from straight above, as in the `projection_blend` example
+const TACTICAL: TileProjection = TileProjection {
+    projection: mat3(
+        vec3(0.5, -1.0, 0.5),
+        vec3(0.5, 1.0, -0.5),
+        vec3(0.0, -1.0, 0.0),
+    ),
+    tile_anchor_point: vec2(0.0, 0.5),
+    stagger: TileStagger::None,
+};
+
+fn builder() -> MapBuilder {
+    Map::builder(uvec2(20, 10), default(), vec2(16., 16.))
+}
+
+fn assert_near(a: Vec2, b: Vec2) {
+    assert!((a - b).length() < 1e-3, "{a} != {b}");
+}
+
+#[test]
+fn blend_ends_match_single_projections() {
+    let mut map = builder()
+        .with_projection(AXONOMETRIC)
+        .with_secondary_projection(TACTICAL)
+        .build();
+    let primary = builder().with_projection(AXONOMETRIC).build();
+    let secondary = builder().with_projection(TACTICAL).build();
+    // The mesh fits both projections during the whole transition
+    let world_size = primary.world_size().max(secondary.world_size());
+
+    for (blend, single) in [(0.0, &primary), (1.0, &secondary), (0.0, &primary)] {
+        map.set_projection_blend(blend);
+        assert_eq!(map.projection_blend(), blend);
+        assert_near(map.world_size(), world_size);
+        for map_position in [vec2(0.0, 0.0), vec2(3.25, 7.5), vec2(19.5, 9.5)] {
+            let world = single.map_to_world_3d(map_position.extend(0.0)).xy();
+            assert_near(map.map_to_world_3d(map_position.extend(0.0)).xy(), world);
+            assert_near(map.world_to_map(world), map_position);
+        }
+    }
+
+    map.set_projection_blend(0.5);
+    assert_near(map.world_size(), world_size);
+}
@@ -0,0 +1,381 @@
+//! Property based tests of the indexing and coordinate math against naive reference
+//! implementations.
+
+use bevy::{
+    math::{ivec2, uvec2, vec2, vec3, Mat3},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+use proptest::prelude::*;
+
+/// Row by row reference for the tiles of a map
+#[derive(Debug, Clone, PartialEq)]
+struct Reference {
+    size: UVec2,
+    tiles: Vec<u32>,
+}
+
+impl Reference {
+    fn of(map: &Map) -> Self {
+        let indexer = map.indexer();
+        Self {
+            size: indexer.size(),
+            tiles: indexer.positions().map(|p| indexer.at_uvec(p)).collect(),
+        }
+    }
+
+    fn contains(&self, p: IVec2) -> bool {
+        p.cmpge(IVec2::ZERO).all() && p.cmplt(self.size.as_ivec2()).all()
+    }
+
+    fn at(&self, p: IVec2) -> u32 {
+        match self.contains(p) {
+            true => self.tiles[(p.y * self.size.x as i32 + p.x) as usize],
+            false => 0,
+        }
+    }
+
+    fn set(&mut self, p: IVec2, v: u32) {
+        if self.contains(p) {
+            self.tiles[(p.y * self.size.x as i32 + p.x) as usize] = v;
+        }
+    }
+}
+
+fn map_with_tiles(size: UVec2, seed: &[u32]) -> Map {
+    Map::builder(size, Handle::default(), vec2(16.0, 16.0))
+        .build_and_set(|p| seed[((p.y * size.x + p.x) as usize) % seed.len()])
+}
+
+fn map_size() -> impl Strategy<Value = UVec2> {
+    (0u32..24, 0u32..24).prop_map(|(x, y)| uvec2(x, y))
+}
+
+/// Positions including some out of bounds and far out of bounds ones
+fn position() -> impl Strategy<Value = IVec2> {
+    prop_oneof![
+        4 => (-4i32..32, -4i32..32).prop_map(|(x, y)| ivec2(x, y)),
+        1 => any::<(i32, i32)>().prop_map(|(x, y)| ivec2(x, y)),
+    ]
+}
+
+/// Values of few distinct tiles, so flood fills have something to connect
+fn seed() -> impl Strategy<Value = Vec<u32>> {
+    prop::collection::vec(0u32..3, 1..64)
+}
+
+/// Well conditioned projections (including flips and skews)
+fn projection() -> impl Strategy<Value = Mat3> {
+    (
+        -2.0f32..2.0,
+        -2.0f32..2.0,
+        -2.0f32..2.0,
+        -2.0f32..2.0,
+        -1.0f32..1.0,
+        -1.0f32..1.0,
+    )
+        .prop_filter("projection must be invertible", |(a, b, c, d, _, _)| {
+            (a * d - b * c).abs() > 0.1
+        })
+        .prop_map(|(a, b, c, d, e, f)| Mat3::from_cols(vec3(a, b, e), vec3(c, d, f), Vec3::Z))
+}
+
+fn transform() -> impl Strategy<Value = GlobalTransform> {
+    (
+        (-1000.0f32..1000.0, -1000.0f32..1000.0, -10.0f32..10.0),
+        -std::f32::consts::PI..std::f32::consts::PI,
+        (0.1f32..4.0, 0.1f32..4.0),
+        (any::<bool>(), any::<bool>()),
+    )
+        .prop_map(|((x, y, z), angle, (sx, sy), (flip_x, flip_y))| {
+            let scale = vec3(
+                if flip_x { -sx } else { sx },
+                if flip_y { -sy } else { sy },
+                1.0,
+            );
+            GlobalTransform::from(
+                Transform::from_xyz(x, y, z)
+                    .with_rotation(Quat::from_rotation_z(angle))
+                    .with_scale(scale),
+            )
+        })
+}
+
+fn assert_close(a: Vec2, b: Vec2, what: &str) -> Result<(), TestCaseError> {
+    let epsilon = 1e-3 * (1.0 + b.abs().max_element());
+    prop_assert!((a - b).abs().max_element() <= epsilon, "{what}: {a} != {b}");
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn set_and_at_match_reference(
+        size in map_size(),
+        writes in prop::collection::vec((position(), any::<u32>()), 0..64),
+        reads in prop::collection::vec(position(), 0..64),
+    ) {
+        let mut map = map_with_tiles(size, &[0]);
+        let mut reference = Reference::of(&map);
+        for (p, v) in writes {
+            map.indexer_mut().set(p.x as u32, p.y as u32, v);
+            reference.set(p, v);
+        }
+        prop_assert_eq!(&Reference::of(&map), &reference);
+        for p in reads {
+            prop_assert_eq!(map.indexer().at_ivec(p), reference.at(p));
+            prop_assert_eq!(map.indexer_mut().at_ivec(p), reference.at(p));
+        }
+    }
+
+    #[test]
+    fn region_fill_matches_reference(
+        size in map_size(),
+        seed in seed(),
+        min in (0u32..32, 0u32..32),
+        extent in (0u32..32, 0u32..32),
+        v in any::<u32>(),
+    ) {
+        let mut map = map_with_tiles(size, &seed);
+        let mut reference = Reference::of(&map);
+        let rect = URect::new(min.0, min.1, min.0 + extent.0, min.1 + extent.1);
+        map.indexer_mut().region_mut(rect).fill(v);
+        for y in rect.min.y..rect.max.y {
+            for x in rect.min.x..rect.max.x {
+                reference.set(ivec2(x as i32, y as i32), v);
+            }
+        }
+        prop_assert_eq!(Reference::of(&map), reference);
+    }
+
+    #[test]
+    fn fill_matches_reference(size in map_size(), seed in seed(), v in any::<u32>()) {
+        let mut map = map_with_tiles(size, &seed);
+        let written = map.indexer_mut().fill(v);
+        prop_assert_eq!(written, size.x as usize * size.y as usize);
+        let reference = Reference {
+            size,
+            tiles: vec![v; written],
+        };
+        prop_assert_eq!(Reference::of(&map), reference);
+    }
+
+    #[test]
+    fn fill_rect_matches_reference(
+        size in map_size(),
+        seed in seed(),
+        min in (0u32..32, 0u32..32),
+        max in (0u32..32, 0u32..32),
+        v in any::<u32>(),
+    ) {
+        let mut map = map_with_tiles(size, &seed);
+        let mut reference = Reference::of(&map);
+        let written = map.indexer_mut().fill_rect(uvec2(min.0, min.1), uvec2(max.0, max.1), v);
+        let mut expected = 0;
+        for y in min.1..max.1 {
+            for x in min.0..max.0 {
+                expected += usize::from(x < size.x && y < size.y);
+                reference.set(ivec2(x as i32, y as i32), v);
+            }
+        }
+        prop_assert_eq!(written, expected);
+        prop_assert_eq!(Reference::of(&map), reference);
+    }
+
+    #[test]
+    fn fill_rect_with_matches_reference(
+        size in map_size(),
+        seed in seed(),
+        min in (0u32..32, 0u32..32),
+        max in (0u32..32, 0u32..32),
+    ) {
+        let mut map = map_with_tiles(size, &seed);
+        let mut reference = Reference::of(&map);
+        let value = |p: UVec2| p.x * 1000 + p.y;
+        let written = map
+            .indexer_mut()
+            .fill_rect_with(uvec2(min.0, min.1), uvec2(max.0, max.1), value);
+        let mut expected = 0;
+        for y in min.1..max.1 {
+            for x in min.0..max.0 {
+                expected += usize::from(x < size.x && y < size.y);
+                reference.set(ivec2(x as i32, y as i32), value(uvec2(x, y)));
+            }
+        }
+        prop_assert_eq!(written, expected);
+        prop_assert_eq!(Reference::of(&map), reference);
+    }
+
+    #[test]
+    fn copy_from_slice_matches_reference(
+        size in map_size(),
+        seed in seed(),
+        min in (0u32..32, 0u32..32),
+        extent in (0u32..16, 0u32..16),
+    ) {
+        let mut map = map_with_tiles(size, &seed);
+        let mut reference = Reference::of(&map);
+        let rect = URect::new(min.0, min.1, min.0 + extent.0, min.1 + extent.1);
+        let data: Vec<u32> = (0..extent.0 * extent.1).map(|i| i + 100).collect();
+        let written = map.indexer_mut().copy_from_slice(rect, &data);
+        let mut expected = 0;
+        for y in 0..extent.1 {
+            for x in 0..extent.0 {
+                let p = uvec2(rect.min.x + x, rect.min.y + y);
+                expected += usize::from(p.x < size.x && p.y < size.y);
+                reference.set(p.as_ivec2(), data[(y * extent.0 + x) as usize]);
+            }
+        }
+        prop_assert_eq!(written, expected);
+        prop_assert_eq!(Reference::of(&map), reference);
+    }
+
+    #[test]
+    fn rows_mut_matches_reference(
+        size in map_size(),
+        seed in seed(),
+        v in any::<u32>(),
+    ) {
+        let mut map = map_with_tiles(size, &seed);
+        let mut reference = Reference::of(&map);
+        let mut indexer = map.indexer_mut();
+        let mut rows = 0;
+        for (y, row) in indexer.rows_mut().enumerate() {
+            prop_assert_eq!(row.len(), size.x as usize);
+            for (x, tile) in row.iter_mut().enumerate().step_by(2) {
+                *tile = v;
+                reference.set(ivec2(x as i32, y as i32), v);
+            }
+            rows += 1;
+        }
+        prop_assert_eq!(rows, if size.x == 0 { 0 } else { size.y });
+        prop_assert_eq!(Reference::of(&map), reference);
+    }
+
+    #[test]
+    fn stamp_matches_reference(
+        size in map_size(),
+        origin in (0u32..32, 0u32..32),
+        stamp_size in (0u32..8, 0u32..8),
+    ) {
+        let mut map = map_with_tiles(size, &[0]);
+        let mut reference = Reference::of(&map);
+        let stamp_size = uvec2(stamp_size.0, stamp_size.1);
+        let stamp = TileStamp::from_fn(stamp_size, |p| p.y * stamp_size.x + p.x + 1);
+        let origin = uvec2(origin.0, origin.1);
+        map.indexer_mut().place_stamp(origin, &stamp);
+        for y in 0..stamp_size.y {
+            for x in 0..stamp_size.x {
+                reference.set((origin + uvec2(x, y)).as_ivec2(), stamp.at(uvec2(x, y)));
+            }
+        }
+        prop_assert_eq!(Reference::of(&map), reference);
+    }
+
+    #[test]
+    fn flood_fill_matches_reference(
+        size in map_size(),
+        seed in seed(),
+        start in position(),
+        v in 0u32..4,
+    ) {
+        let mut map = map_with_tiles(size, &seed);
+        let mut reference = Reference::of(&map);
+
+        // Breadth first search instead of the depth first search of `flood_fill`
+        let mut changed = 0;
+        if reference.contains(start) && reference.at(start) != v {
+            let old = reference.at(start);
+            let mut queue = std::collections::VecDeque::from([start]);
+            reference.set(start, v);
+            while let Some(p) = queue.pop_front() {
+                changed += 1;
+                for q in [p + IVec2::X, p - IVec2::X, p + IVec2::Y, p - IVec2::Y] {
+                    if reference.contains(q) && reference.at(q) == old {
+                        reference.set(q, v);
+                        queue.push_back(q);
+                    }
+                }
+            }
+        }
+
+        let n = map.indexer_mut().flood_fill(start.as_uvec2(), v);
+        prop_assert_eq!(n, changed);
+        prop_assert_eq!(Reference::of(&map), reference);
+    }
+
+    #[test]
+    fn step_region_matches_reference(
+        size in map_size(),
+        seed in seed(),
+        min in (0u32..32, 0u32..32),
+        extent in (0u32..32, 0u32..32),
+    ) {
+        let mut map = map_with_tiles(size, &seed);
+        let old = Reference::of(&map);
+        let mut reference = old.clone();
+        let rect = URect::new(min.0, min.1, min.0 + extent.0, min.1 + extent.1);
+        // Sum of the 3x3 neighborhood
+        map.indexer_mut().step_region(rect, |_, _, n| n.center() + n.neighbors().sum::<u32>());
+        for y in rect.min.y..rect.max.y {
+            for x in rect.min.x..rect.max.x {
+                let p = ivec2(x as i32, y as i32);
+                let sum = (-1..=1)
+                    .flat_map(|dy| (-1..=1).map(move |dx| ivec2(dx, dy)))
+                    .map(|d| old.at(p + d))
+                    .sum();
+                reference.set(p, sum);
+            }
+        }
+        prop_assert_eq!(Reference::of(&map), reference);
+    }
+
+    #[test]
+    fn map_and_local_positions_round_trip(
+        size in (1u32..200, 1u32..200),
+        tile_size in (1.0f32..64.0, 1.0f32..64.0),
+        world_tile_scale in 0.25f32..4.0,
+        projection in projection(),
+        anchor in (0.0f32..1.0, 0.0f32..1.0),
+        map_position in (-50.0f32..250.0, -50.0f32..250.0),
+    ) {
+        let tile_size = vec2(tile_size.0, tile_size.1);
+        let map: Map = Map::builder(uvec2(size.0, size.1), Handle::default(), tile_size)
+            .with_projection(TileProjection {
+                projection,
+                tile_anchor_point: vec2(anchor.0, anchor.1),
+                stagger: TileStagger::None,
+            })
+            .with_world_tile_size(tile_size * world_tile_scale)
+            .build();
+        let map_position = vec2(map_position.0, map_position.1);
+        let local = map.map_to_local(map_position);
+        assert_close(map.local_to_map(local), map_position, "local_to_map(map_to_local)")?;
+    }
+
+    #[test]
+    fn map_and_world_positions_round_trip(
+        size in (1u32..200, 1u32..200),
+        projection in projection(),
+        transform in transform(),
+        map_position in (-50.0f32..250.0, -50.0f32..250.0),
+    ) {
+        let mut map: Map = Map::builder(uvec2(size.0, size.1), Handle::default(), vec2(16.0, 8.0))
+            .with_projection(TileProjection {
+                projection,
+                ..IDENTITY
+            })
+            .build();
+        let map_position = vec2(map_position.0, map_position.1);
+        let world = map.map_to_world_3d_with(&transform, map_position.extend(0.0));
+        assert_close(
+            map.world_to_map_with(&transform, world.truncate()),
+            map_position,
+            "world_to_map_with(map_to_world_3d_with)",
+        )?;
+
+        // Same for the applied transform
+        map.apply_transform(&transform);
+        let world = map.map_to_world_3d(map_position.extend(0.0));
+        assert_close(map.world_to_map(world.truncate()), map_position, "world_to_map")?;
+    }
+}
@@ -0,0 +1,94 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+fn map() -> Map {
+    Map::builder(uvec2(8, 4), default(), vec2(16.0, 16.0)).build_and_set(|p| p.x + 10 * p.y)
+}
+
+fn tiles_in(rects: &[URect]) -> Vec<UVec2> {
+    let mut tiles: Vec<UVec2> = rects
+        .iter()
+        .flat_map(|r| {
+            (r.min.y..r.max.y).flat_map(move |y| (r.min.x..r.max.x).map(move |x| uvec2(x, y)))
+        })
+        .collect();
+    tiles.sort_by_key(|p| (p.y, p.x));
+    tiles
+}
+
+#[test]
+fn overlapping_regions_are_rejected() {
+    let mut map: Map = Map::builder(uvec2(8, 4), default(), vec2(16.0, 16.0)).build();
+    let mut indexer = map.indexer_mut();
+    let overlapping = [URect::new(0, 0, 4, 4), URect::new(3, 3, 6, 6)];
+    assert!(indexer.split_regions(&overlapping).is_none());
+    // Rejecting leaves the map as it is
+    assert_eq!(map.uniform_tile(), Some(0));
+
+    // Rects only overlapping outside of the map are clamped apart
+    let mut indexer = map.indexer_mut();
+    let outside = [URect::new(6, 2, 12, 12), URect::new(0, 5, 12, 12)];
+    let regions = indexer.split_regions(&outside).unwrap();
+    assert_eq!(regions[0].rect(), URect::new(6, 2, 8, 4));
+    assert!(regions[1].rect().is_empty());
+
+    // Adjacent rects don't overlap
+    let adjacent = [URect::new(0, 0, 4, 4), URect::new(4, 0, 8, 4)];
+    let mut regions = indexer.split_regions(&adjacent).unwrap();
+    regions[0].fill(1);
+    regions[1].fill(2);
+    drop(regions);
+    let indexer = map.indexer();
+    assert_eq!((indexer.at(3, 3), indexer.at(4, 0)), (1, 2));
+}
+
+#[test]
+fn regions_are_clamped_to_the_map() {
+    let mut map = map();
+    let mut indexer = map.indexer_mut();
+    let mut region = indexer.region_mut(URect::new(6, 2, 20, 20));
+    assert_eq!(region.rect(), URect::new(6, 2, 8, 4));
+    assert_eq!(region.size(), uvec2(2, 2));
+    assert_eq!(region.to_global(uvec2(1, 1)), uvec2(7, 3));
+    assert_eq!(region.at(1, 0), 27);
+
+    // Accesses outside of the (clamped) window are ignored
+    region.set(2, 0, 99);
+    region.set(0, 2, 99);
+    assert_eq!(region.at(2, 0), 0);
+    region.set(1, 1, 5);
+    drop(region);
+    assert_eq!(map.indexer().at(7, 3), 5);
+    assert!(!map
+        .indexer()
+        .positions()
+        .any(|p| map.indexer().at_uvec(p) == 99));
+}
+
+#[test]
+fn only_region_rows_are_dirty() {
+    let mut map = map();
+    map.enable_change_ticks();
+    let tick = map.current_tick();
+    let rects = [URect::new(1, 1, 3, 2), URect::new(5, 3, 7, 4)];
+    let regions = map.indexer_mut().split_regions(&rects).unwrap().len();
+    assert_eq!(regions, 2);
+
+    // All tiles of the regions count as changed, whether written or not
+    let mut changed: Vec<UVec2> = map
+        .tiles_changed_since(tick, URect::new(0, 0, 8, 4))
+        .collect();
+    changed.sort_by_key(|p| (p.y, p.x));
+    assert_eq!(changed, tiles_in(&rects));
+    assert_eq!(
+        map.tiles_changed_since(tick, URect::new(0, 0, 8, 1))
+            .count()
+            + map
+                .tiles_changed_since(tick, URect::new(0, 2, 8, 3))
+                .count(),
+        0
+    );
+}
@@ -0,0 +1,86 @@
+use bevy::{
+    math::{ivec2, uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+const SIZE: UVec2 = uvec2(40, 20);
+
+fn map(projection: TileProjection) -> Map {
+    let mut map = Map::builder(SIZE, default(), vec2(16., 16.))
+        .with_projection(projection)
+        .build();
+    map.set_regions(vec![
+        MapRegion::new("room", URect::new(2, 2, 6, 6), 1),
+        // Overlaps the room, which takes priority
+        MapRegion::new("biome", URect::new(0, 0, 20, 20), 2),
+        // Reaches beyond the map
+        MapRegion::new("border", URect::new(30, 10, 50, 30), 3),
+    ]);
+    map
+}
+
+fn id(region: Option<&MapRegion>) -> Option<u32> {
+    region.map(|region| region.id)
+}
+
+/// World position of the center of `tile`
+fn center(map: &Map, tile: IVec2) -> Vec2 {
+    map.map_to_world_3d((tile.as_vec2() + 0.5).extend(0.0)).xy()
+}
+
+#[test]
+fn overlapping_regions_by_priority() {
+    for projection in [IDENTITY, AXONOMETRIC] {
+        let map = map(projection);
+        for (tile, expected) in [
+            (uvec2(0, 0), Some(2)),
+            (uvec2(2, 2), Some(1)),
+            (uvec2(5, 5), Some(1)),
+            (uvec2(6, 5), Some(2)),
+            (uvec2(19, 19), Some(2)),
+            (uvec2(20, 5), None),
+            (uvec2(35, 15), Some(3)),
+        ] {
+            assert_eq!(id(map.region_at_tile(tile)), expected, "{tile}");
+            assert_eq!(
+                id(map.region_at_world(center(&map, tile.as_ivec2()))),
+                expected,
+                "{tile}"
+            );
+        }
+    }
+}
+
+#[test]
+fn out_of_bounds_tiles_have_no_region() {
+    for projection in [IDENTITY, AXONOMETRIC] {
+        let map = map(projection);
+        // Inside of "border", but outside of the map
+        for tile in [
+            uvec2(40, 15),
+            uvec2(35, 20),
+            uvec2(45, 25),
+            uvec2(u32::MAX, 0),
+        ] {
+            assert_eq!(id(map.region_at_tile(tile)), None, "{tile}");
+        }
+        for tile in [
+            ivec2(-1, 0),
+            ivec2(0, -1),
+            ivec2(40, 15),
+            ivec2(35, 20),
+            ivec2(45, 25),
+        ] {
+            assert_eq!(id(map.region_at_world(center(&map, tile))), None, "{tile}");
+        }
+    }
+}
+
+#[test]
+fn regions_round_trip_through_serde() {
+    let map = map(IDENTITY);
+    let text = ron::to_string(map.regions()).unwrap();
+    let regions: Vec<MapRegion> = ron::from_str(&text).unwrap();
+    assert_eq!(regions, map.regions());
+}
@@ -0,0 +1,163 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+
+const COLORS: [(char, [u8; 4]); 4] = [
+    ('.', [0, 0, 0, 0]),
+    ('g', [0, 255, 0, 255]),
+    ('b', [0, 0, 255, 255]),
+    ('r', [255, 0, 0, 255]),
+];
+
+fn image(rows: &[&str]) -> Image {
+    let data = rows
+        .iter()
+        .flat_map(|row| row.chars())
+        .flat_map(|c| COLORS.iter().find(|(k, _)| *k == c).unwrap().1)
+        .collect();
+    Image::new(
+        Extent3d {
+            width: rows[0].len() as u32,
+            height: rows.len() as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    )
+}
+
+fn rows(image: &Image) -> Vec<String> {
+    image
+        .data
+        .chunks_exact(4 * image.width() as usize)
+        .map(|row| {
+            row.chunks_exact(4)
+                .map(|c| COLORS.iter().find(|(_, v)| v == c).map_or('?', |(k, _)| *k))
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn dominance_overhangs_match_the_shader() {
+    let mut images = Assets::<Image>::default();
+    // Two 4x4 tiles, inner padding of 2 and outer padding of 1.
+    // The padding of the blue tile (1) is red, ie. it overhangs its neighbors by one pixel.
+    let atlas = images.add(image(&[
+        "......rrrrrr",
+        ".gggg.rbbbbr",
+        ".gggg.rbbbbr",
+        ".gggg.rbbbbr",
+        ".gggg.rbbbbr",
+        "......rrrrrr",
+    ]));
+    let mut map: Map = Map::builder(uvec2(2, 1), atlas, vec2(4.0, 4.0))
+        .with_padding(Vec2::splat(2.0), Vec2::ONE, Vec2::ONE)
+        .with_dominance_overhang()
+        .build_and_set(|p| p.x);
+    map.update(&images);
+    assert_eq!(map.atlas_tile_count(), Some(2));
+
+    let rendered = map.render_to_image(&mut images, 1.0);
+    assert_eq!(
+        rows(images.get(&rendered).unwrap()),
+        [
+            "................",
+            "................",
+            "................",
+            ".......rrrrrr...",
+            "....gggrbbbbr...",
+            "....gggrbbbbr...",
+            "....gggrbbbbr...",
+            "....gggrbbbbr...",
+            ".......rrrrrr...",
+            "................",
+            "................",
+            "................",
+        ]
+    );
+
+    // Scaled down by half
+    let rendered = map.render_to_image(&mut images, 0.5);
+    let rendered = images.get(&rendered).unwrap();
+    assert_eq!(rendered.size(), uvec2(8, 6));
+}
+
+#[test]
+fn pick_returns_the_overhanging_tile() {
+    let mut images = Assets::<Image>::default();
+    let atlas = images.add(image(&[
+        "......rrrrrr",
+        ".gggg.rbbbbr",
+        ".gggg.rbbbbr",
+        ".gggg.rbbbbr",
+        ".gggg.rbbbbr",
+        "......rrrrrr",
+    ]));
+    let mut map: Map = Map::builder(uvec2(2, 1), atlas, vec2(4.0, 4.0))
+        .with_padding(Vec2::splat(2.0), Vec2::ONE, Vec2::ONE)
+        .with_dominance_overhang()
+        .build_and_set(|p| p.x);
+    map.update(&images);
+
+    // The rightmost column of tile 0 shows the overhang of tile 1 (see above)
+    assert_eq!(map.world_to_map(vec2(-0.5, 0.5)).floor(), vec2(0.0, 0.0));
+    assert_eq!(map.pick(vec2(-0.5, 0.5), &images), Some(uvec2(1, 0)));
+    assert_eq!(map.pick(vec2(-2.5, 0.5), &images), Some(uvec2(0, 0)));
+    // Overhangs of tile 1 outside of the map
+    assert_eq!(map.pick(vec2(4.5, 0.5), &images), Some(uvec2(1, 0)));
+    assert_eq!(map.pick(vec2(4.5, 2.5), &images), Some(uvec2(1, 0)));
+    assert_eq!(map.pick(vec2(-6.5, 0.5), &images), None);
+
+    // Without the atlas, picking falls back to the tile at the position
+    let images = Assets::<Image>::default();
+    assert_eq!(map.pick(vec2(-0.5, 0.5), &images), Some(uvec2(0, 0)));
+}
+
+#[test]
+fn unloaded_atlas_renders_transparent() {
+    let mut images = Assets::<Image>::default();
+    let map: Map = Map::builder(uvec2(2, 2), Handle::default(), vec2(4.0, 4.0)).build();
+    let rendered = map.render_to_image(&mut images, 2.0);
+    let rendered = images.get(&rendered).unwrap();
+    assert_eq!(rendered.size(), (map.world_size() * 2.0).as_uvec2());
+    assert!(rendered.data.iter().all(|c| *c == 0));
+}
+
+#[test]
+fn staggered_rows_match_the_shader() {
+    let mut images = Assets::<Image>::default();
+    let atlas = images.add(image(&["ggggggggbbbbbbbb"; 8]));
+    for (projection, shifted_row) in [(HEX_POINTY_TOP, 1), (HEX_POINTY_TOP_EVEN, 0)] {
+        let mut map: Map = Map::builder(uvec2(2, 2), atlas.clone(), vec2(8.0, 8.0))
+            .with_projection(projection)
+            .build_and_set(|p| p.y);
+        map.update(&images);
+        let rendered = map.render_to_image(&mut images, 1.0);
+        let rendered = rows(images.get(&rendered).unwrap());
+        let top_left = map.world_size() * vec2(-0.5, 0.5);
+        let pixel = |world: Vec2| {
+            let p = ((world - top_left) * vec2(1.0, -1.0)).as_uvec2();
+            rendered[p.y as usize].chars().nth(p.x as usize).unwrap()
+        };
+
+        for (row, color) in [(0, 'g'), (1, 'b')] {
+            let center = map.tile_center_world(uvec2(0, row));
+            assert_eq!(pixel(center), color);
+            // A quarter tile right of the grid origin is left of the first hexagon of the
+            // shifted row
+            let unshifted = map.tile_center_world(uvec2(0, 1 - shifted_row));
+            let left = vec2(unshifted.x - 2.0, center.y);
+            let expected = if row == shifted_row { '.' } else { color };
+            assert_eq!(pixel(left), expected, "{:?}", projection.stagger);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    render::mesh::VertexAttributeValues,
+    sprite::{Anchor, Mesh2dHandle},
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+fn map() -> Map {
+    // Tile values encode their position
+    Map::builder(uvec2(4, 3), default(), vec2(16., 16.)).build_and_set(|p| p.y * 10 + p.x)
+}
+
+fn tiles(map: &Map) -> Vec<Vec<u32>> {
+    let m = map.indexer();
+    (0..m.size().y)
+        .map(|y| (0..m.size().x).map(|x| m.at(x, y)).collect())
+        .collect()
+}
+
+#[test]
+fn resize_keeps_the_overlap() {
+    let mut map = map();
+    map.resize(uvec2(5, 2), 99);
+    assert_eq!(map.map_size(), uvec2(5, 2));
+    assert_eq!(
+        tiles(&map),
+        vec![vec![0, 1, 2, 3, 99], vec![10, 11, 12, 13, 99]]
+    );
+    assert_eq!(map.world_size(), vec2(5.0 * 16.0, 2.0 * 16.0));
+
+    // Kept tiles move left in local coordinates as the map grows to the right
+    let moved = map.resize(uvec2(7, 2), 99);
+    assert_eq!(moved, vec2(-16.0, 0.0));
+}
+
+#[test]
+fn anchored_resize_round_trips() {
+    let mut map = map();
+    let moved = map.resize_anchored(uvec2(6, 5), 7, Anchor::Center);
+    assert_eq!(moved, Vec2::ZERO);
+    assert_eq!(map.indexer().at(1, 1), 0);
+    assert_eq!(map.indexer().at(0, 0), 7);
+    assert_eq!(map.indexer().at(4, 3), 23);
+
+    map.resize_anchored(uvec2(4, 3), 7, Anchor::Center);
+    assert_eq!(tiles(&map), tiles(&self::map()));
+
+    map.resize_anchored(uvec2(2, 1), 7, Anchor::BottomRight);
+    assert_eq!(tiles(&map), vec![vec![22, 23]]);
+}
+
+#[test]
+fn resize_with_sees_old_tiles() {
+    let mut map = map();
+    map.resize_with(uvec2(5, 3), Anchor::TopLeft, |p, old| {
+        // Extend the last column
+        old.unwrap_or(p.y * 10 + 3)
+    });
+    assert_eq!(map.indexer().at(4, 2), 23);
+    assert_eq!(map.indexer().at(2, 2), 22);
+}
+
+#[test]
+fn resize_moves_regions_and_records_changes() {
+    let mut map = map();
+    map.set_regions(vec![
+        MapRegion::new("left", URect::new(0, 0, 2, 3), 1),
+        MapRegion::new("right", URect::new(3, 0, 4, 3), 2),
+    ]);
+    map.enable_change_ticks();
+    let tick = map.current_tick();
+
+    map.resize_anchored(uvec2(3, 3), 0, Anchor::CenterRight);
+    assert_eq!(map.regions().len(), 2);
+    assert_eq!(map.regions()[0].rect, URect::new(0, 0, 1, 3));
+    assert_eq!(map.regions()[1].rect, URect::new(2, 0, 3, 3));
+    assert_eq!(
+        map.tiles_changed_since(tick, URect::new(0, 0, 3, 3))
+            .count(),
+        9
+    );
+
+    map.resize_anchored(uvec2(1, 3), 0, Anchor::CenterRight);
+    assert_eq!(map.regions().len(), 1);
+    assert_eq!(map.regions()[0].name, "right");
+}
+
+#[test]
+fn resize_moves_patches() {
+    let mut map = map();
+    let kept = map
+        .add_patch(Patch::filled(uvec2(2, 2), uvec2(2, 1), 7, 1.0))
+        .unwrap();
+    let dropped = map
+        .add_patch(Patch::filled(uvec2(0, 0), uvec2(1, 1), 7, 1.0))
+        .unwrap();
+
+    map.resize_anchored(uvec2(2, 3), 0, Anchor::CenterRight);
+    assert_eq!(map.patch(kept).unwrap().origin, uvec2(0, 2));
+    assert!(map.patch(dropped).is_none());
+}
+
+#[test]
+fn uniform_maps_stay_uniform() {
+    let mut map: Map = Map::builder(uvec2(4, 4), default(), vec2(16., 16.)).build();
+    map.resize(uvec2(8, 8), 0);
+    assert_eq!(map.uniform_tile(), Some(0));
+    map.resize(uvec2(9, 8), 1);
+    assert_eq!(map.uniform_tile(), None);
+    assert_eq!(map.indexer().at(8, 0), 1);
+}
+
+#[test]
+fn managed_mesh_follows_the_size() {
+    let mut app = common::loading_app();
+    let (atlas, tile_size) = debug_atlas(&mut app.world_mut().resource_mut::<Assets<Image>>());
+    let map = Map::builder(uvec2(4, 4), atlas, tile_size).build();
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    let entity = app
+        .world_mut()
+        .spawn(MapBundleManaged {
+            material: handle.clone(),
+            ..default()
+        })
+        .id();
+    app.update();
+    app.update();
+
+    let mesh_size = |app: &App| {
+        let mesh = app.world().get::<Mesh2dHandle>(entity).unwrap();
+        let mesh = app.world().resource::<Assets<Mesh>>().get(&mesh.0).unwrap();
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("unexpected vertex positions");
+        };
+        positions
+            .iter()
+            .fold(Rect::EMPTY, |rect, p| rect.union_point(vec2(p[0], p[1])))
+            .size()
+    };
+    let before = mesh_size(&app);
+
+    let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+    let map = maps.get_mut(&handle).unwrap();
+    map.resize(uvec2(8, 4), 0);
+    let world_size = map.world_size();
+    app.update();
+
+    assert_ne!(mesh_size(&app), before);
+    assert_eq!(mesh_size(&app), world_size);
+    // The map is resized in place, not reloaded
+    assert!(app.world().get::<MapLoading>(entity).is_none());
+}
@@ -0,0 +1,65 @@
+use bevy::{
+    math::uvec2,
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+/// Atlas of 4 x 4 tiles of 16x16 pixels
+fn atlas_image() -> Image {
+    Image::new_fill(
+        Extent3d {
+            width: 64,
+            height: 64,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[255; 4],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    )
+}
+
+fn has_map_sampler(app: &App, atlas: &Handle<Image>) -> bool {
+    let image = app.world().resource::<Assets<Image>>().get(atlas).unwrap();
+    matches!(image.sampler, ImageSampler::Descriptor(_))
+}
+
+#[test]
+fn loading_waits_for_the_secondary_atlas() {
+    let mut app = common::map_app();
+    app.add_systems(Update, update_loading_maps::<NoCustomization>);
+
+    let mut images = app.world_mut().resource_mut::<Assets<Image>>();
+    let atlas = images.add(atlas_image());
+    let secondary = images.reserve_handle();
+    let map = Map::builder(uvec2(8, 8), atlas.clone(), Vec2::splat(16.0))
+        .with_secondary_atlas(secondary.clone())
+        .build();
+    assert_eq!(map.secondary_atlas(), Some(&secondary));
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    let entity = app
+        .world_mut()
+        .spawn(MapBundleManaged {
+            material: handle,
+            ..default()
+        })
+        .id();
+
+    app.update();
+    assert!(app.world().get::<MapLoading>(entity).is_some());
+
+    app.world_mut()
+        .resource_mut::<Assets<Image>>()
+        .insert(&secondary, atlas_image());
+    app.update();
+    assert!(app.world().get::<MapLoading>(entity).is_none());
+    assert!(has_map_sampler(&app, &atlas));
+    assert!(has_map_sampler(&app, &secondary));
+}
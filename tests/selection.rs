@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+
+use bevy::{
+    math::{uvec2, vec2, vec3},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+const SIZE: UVec2 = uvec2(20, 10);
+
+fn transforms() -> [GlobalTransform; 2] {
+    [
+        GlobalTransform::IDENTITY,
+        GlobalTransform::from(
+            Transform::from_translation(vec3(40.0, -8.0, 0.0))
+                .with_rotation(Quat::from_rotation_z(0.4))
+                .with_scale(vec3(2.0, 1.5, 1.0)),
+        ),
+    ]
+}
+
+/// Rectangles around and across the map, spanned by pairs of map positions
+fn rects(map: &Map, transform: &GlobalTransform) -> Vec<Rect> {
+    let positions = [
+        vec2(-1.3, 2.7),
+        vec2(3.4, 5.1),
+        vec2(3.6, 5.4),
+        vec2(10.2, 0.6),
+        vec2(7.7, 7.9),
+        vec2(22.1, -3.2),
+        vec2(19.4, 11.3),
+    ];
+    let world = |p: Vec2| map.map_to_world_3d_with(transform, p.extend(0.0)).xy();
+    let mut rects = Vec::new();
+    for (i, a) in positions.iter().enumerate() {
+        for b in &positions[i + 1..] {
+            rects.push(Rect::from_corners(world(*a), world(*b)));
+        }
+    }
+    rects
+}
+
+fn segments_intersect(a: [Vec2; 2], b: [Vec2; 2]) -> bool {
+    let side = |p: Vec2, q: Vec2, r: Vec2| (q - p).perp_dot(r - p);
+    let (d1, d2) = (side(b[0], b[1], a[0]), side(b[0], b[1], a[1]));
+    let (d3, d4) = (side(a[0], a[1], b[0]), side(a[0], a[1], b[1]));
+    d1 * d2 <= 0.0 && d3 * d4 <= 0.0
+}
+
+/// Whether the tile quad `corners` and `rect` intersect, by checking every edge and corner
+fn brute_force_intersects(corners: [Vec2; 4], rect: Rect) -> bool {
+    let rect_corners = [
+        rect.min,
+        vec2(rect.max.x, rect.min.y),
+        rect.max,
+        vec2(rect.min.x, rect.max.y),
+    ];
+    let inside_quad = |p: Vec2| {
+        let sides: [f32; 4] =
+            std::array::from_fn(|i| (corners[(i + 1) % 4] - corners[i]).perp_dot(p - corners[i]));
+        sides.iter().all(|s| *s >= 0.0) || sides.iter().all(|s| *s <= 0.0)
+    };
+    corners.iter().any(|corner| rect.contains(*corner))
+        || rect_corners.iter().any(|corner| inside_quad(*corner))
+        || (0..4).any(|i| {
+            (0..4).any(|j| {
+                segments_intersect(
+                    [corners[i], corners[(i + 1) % 4]],
+                    [rect_corners[j], rect_corners[(j + 1) % 4]],
+                )
+            })
+        })
+}
+
+fn brute_force(map: &Map, rect: Rect, transform: &GlobalTransform) -> HashSet<UVec2> {
+    let mut tiles = HashSet::new();
+    for y in 0..SIZE.y {
+        for x in 0..SIZE.x {
+            let tile = uvec2(x, y).as_vec2();
+            let corners = [
+                tile,
+                tile + vec2(1.0, 0.0),
+                tile + vec2(1.0, 1.0),
+                tile + vec2(0.0, 1.0),
+            ]
+            .map(|p| map.map_to_world_3d_with(transform, p.extend(0.0)).xy());
+            if brute_force_intersects(corners, rect) {
+                tiles.insert(uvec2(x, y));
+            }
+        }
+    }
+    tiles
+}
+
+#[test]
+fn tiles_in_world_rect_match_brute_force() {
+    for (projection, name) in [(IDENTITY, "identity"), (AXONOMETRIC, "axonometric")] {
+        let map = Map::builder(SIZE, default(), vec2(16., 16.))
+            .with_projection(projection)
+            .build();
+        for transform in transforms() {
+            for rect in rects(&map, &transform) {
+                let expected = brute_force(&map, rect, &transform);
+                let tiles: HashSet<_> = map.tiles_in_world_rect(rect, &transform).collect();
+                assert_eq!(tiles, expected, "{name} {rect:?}");
+
+                let approx: HashSet<_> = map.tiles_in_world_rect_approx(rect, &transform).collect();
+                assert!(approx.is_superset(&tiles), "{name} {rect:?}");
+                assert!(approx.iter().all(|tile| tile.cmplt(SIZE).all()));
+            }
+        }
+    }
+}
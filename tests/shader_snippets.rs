@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use bevy_fast_tilemap::{prelude::*, shader::SHADER_CODE};
+use naga_oil::compose::{
+    ComposableModuleDescriptor, Composer, NagaModuleDescriptor, ShaderDefValue, ShaderLanguage,
+    ShaderType,
+};
+
+/// Minimal stand-ins for the bevy shader modules imported by the map shader
+const BEVY_MODULES: [(&str, &str); 3] = [
+    (
+        "mesh2d_bindings.wgsl",
+        r#"
+        #define_import_path bevy_sprite::mesh2d_bindings
+        struct Mesh2d { world_from_local: mat4x4<f32> };
+        @group(1) @binding(0) var<storage> mesh: array<Mesh2d>;
+        "#,
+    ),
+    (
+        "mesh2d_functions.wgsl",
+        r#"
+        #define_import_path bevy_sprite::mesh2d_functions
+        #import bevy_sprite::mesh2d_bindings::mesh
+        fn get_world_from_local(i: u32) -> mat4x4<f32> { return mesh[i].world_from_local; }
+        fn mesh2d_position_local_to_clip(m: mat4x4<f32>, p: vec4<f32>) -> vec4<f32> { return m * p; }
+        fn mesh2d_position_local_to_world(m: mat4x4<f32>, p: vec4<f32>) -> vec4<f32> { return m * p; }
+        "#,
+    ),
+    (
+        "mesh_view_bindings.wgsl",
+        r#"
+        #define_import_path mesh_view_bindings
+        struct Globals { time: f32, delta_time: f32, frame_count: u32 };
+        @group(0) @binding(1) var<uniform> globals: Globals;
+        struct View { viewport: vec4<f32> };
+        @group(0) @binding(0) var<uniform> view: View;
+        "#,
+    ),
+];
+
+/// Compose the map shader with `custom_code` and validate it
+fn validate(custom_code: &str) -> Result<(), String> {
+    let mut composer = Composer::default();
+    for (file_path, source) in BEVY_MODULES {
+        composer
+            .add_composable_module(ComposableModuleDescriptor {
+                source,
+                file_path,
+                language: ShaderLanguage::Wgsl,
+                ..Default::default()
+            })
+            .unwrap();
+    }
+    let code = SHADER_CODE.replace("#[user_code]", custom_code);
+    let module = composer
+        .make_naga_module(NagaModuleDescriptor {
+            source: &code,
+            file_path: "tilemap_shader.wgsl",
+            shader_type: ShaderType::Wgsl,
+            shader_defs: HashMap::<String, ShaderDefValue>::new(),
+            ..Default::default()
+        })
+        .map_err(|e| e.emit_to_string(&composer))?;
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .map(|_| ())
+    .map_err(|e| format!("{e:?}"))
+}
+
+const USER_DATA: &str = "struct UserData { dummy: u32 };";
+
+#[test]
+fn default_customization_is_valid() {
+    validate(&NoCustomization::custom_shader_code()).unwrap();
+}
+
+#[test]
+fn snippets_are_valid() {
+    let uses = [
+        (HUE_SHIFT, "hue_shift(color, in.animation_state)"),
+        (
+            TILE_OUTLINE,
+            "tile_outline(color, in.tile_uv, 1.0, vec4<f32>(1.0))",
+        ),
+        (DISSOLVE, "dissolve(color, in, 0.5, 0.1, vec4<f32>(1.0))"),
+        (
+            WAVE,
+            "sample_tile_at(in.tile_index, in.tile_position, \
+             wave_offset(in, vec2<f32>(1.0), 16.0, 2.0))",
+        ),
+    ];
+    assert_eq!(uses.len(), ALL_SNIPPETS.len());
+
+    for (snippet, expression) in uses {
+        let body = format!(
+            "{USER_DATA}
+            fn sample_tile(in: ExtractIn) -> vec4<f32> {{
+                var color = sample_tile_at(in.tile_index, in.tile_position, in.tile_offset_texels);
+                return {expression};
+            }}"
+        );
+        let code = compose_custom_shader(&[snippet], &body);
+        if let Err(e) = validate(&code) {
+            panic!("Snippet {} is invalid:\n{e}", snippet.name);
+        }
+    }
+}
+
+#[test]
+fn all_snippets_compose() {
+    // Snippets given twice are only included once
+    let snippets: Vec<_> = ALL_SNIPPETS.iter().chain(&ALL_SNIPPETS).copied().collect();
+    let body = format!(
+        "{USER_DATA}
+        fn sample_tile(in: ExtractIn) -> vec4<f32> {{
+            return sample_tile_at(in.tile_index, in.tile_position, in.tile_offset_texels);
+        }}"
+    );
+    validate(&compose_custom_shader(&snippets, &body)).unwrap();
+}
@@ -0,0 +1,90 @@
+use bevy::{
+    math::{uvec2, vec3},
+    prelude::*,
+    sprite::Mesh2dHandle,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+/// Two entities with the same map at different transforms
+fn spawn_shared(app: &mut App) -> (Handle<Map>, [(Entity, GlobalTransform); 2]) {
+    let (atlas, tile_size) = debug_atlas(&mut app.world_mut().resource_mut::<Assets<Image>>());
+    let map = Map::builder(uvec2(8, 4), atlas, tile_size).build_and_set(|p| p.x + 1);
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+
+    let transforms = [
+        GlobalTransform::from_translation(vec3(-1000.0, 200.0, 0.0)),
+        GlobalTransform::from(
+            Transform::from_translation(vec3(500.0, -300.0, 1.0))
+                .with_rotation(Quat::from_rotation_z(0.5))
+                .with_scale(vec3(2.0, 2.0, 1.0)),
+        ),
+    ];
+    let entities = transforms.map(|transform| {
+        let entity = app
+            .world_mut()
+            .spawn(MapBundleManaged {
+                material: handle.clone(),
+                global_transform: transform,
+                ..default()
+            })
+            .id();
+        (entity, transform)
+    });
+    (handle, entities)
+}
+
+#[test]
+fn every_entity_gets_a_mesh_of_the_map_size() {
+    let mut app = common::loading_app();
+    let (handle, entities) = spawn_shared(&mut app);
+    app.update();
+    app.update();
+
+    let world_size = app
+        .world()
+        .resource::<Assets<Map>>()
+        .get(&handle)
+        .unwrap()
+        .world_size();
+    for (entity, _) in entities {
+        let mesh_handle = app.world().get::<Mesh2dHandle>(entity).unwrap().0.clone();
+        let meshes = app.world().resource::<Assets<Mesh>>();
+        let aabb = meshes.get(&mesh_handle).unwrap().compute_aabb().unwrap();
+        assert!((aabb.half_extents.truncate() * 2.0 - world_size).length() < 1e-4);
+    }
+}
+
+#[test]
+fn conversions_use_the_entity_transform() {
+    let mut app = common::loading_app();
+    let (handle, entities) = spawn_shared(&mut app);
+    app.update();
+
+    let maps = app.world().resource::<Assets<Map>>();
+    let map = maps.get(&handle).unwrap();
+    let tile_center = vec3(2.5, 1.5, 0.0);
+    for (_, transform) in entities.iter() {
+        let world = map.map_to_world_3d_with(transform, tile_center);
+        let expected =
+            transform.transform_point(map.map_to_local_3d(tile_center).truncate().extend(0.0));
+        assert!((world.truncate() - expected.truncate()).length() < 1e-3);
+
+        let map_position = map.world_to_map_with(transform, world.truncate());
+        assert!((map_position - tile_center.truncate()).length() < 1e-4);
+    }
+
+    // Picking finds the tile in the entity that is actually there
+    let images = app.world().resource::<Assets<Image>>();
+    for (entity, transform) in entities.iter() {
+        let world = map.map_to_world_3d_with(transform, tile_center).truncate();
+        let layers = entities
+            .iter()
+            .map(|(entity, transform)| (*entity, &handle, transform));
+        assert_eq!(
+            pick_tile_in_layers(layers, world, maps, images, PickOptions::default()),
+            Some((*entity, uvec2(2, 1)))
+        );
+    }
+}
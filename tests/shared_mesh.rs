@@ -0,0 +1,66 @@
+use bevy::{math::uvec2, prelude::*, sprite::Mesh2dHandle};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+fn spawn_map(app: &mut App, size: UVec2, attributes: MapAttributes) -> Entity {
+    let (atlas, tile_size) = debug_atlas(&mut app.world_mut().resource_mut::<Assets<Image>>());
+    let map = Map::builder(size, atlas, tile_size).build();
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    app.world_mut()
+        .spawn(MapBundleManaged {
+            material: handle,
+            attributes,
+            ..default()
+        })
+        .id()
+}
+
+fn mesh(app: &App, entity: Entity) -> Handle<Mesh> {
+    app.world().get::<Mesh2dHandle>(entity).unwrap().0.clone()
+}
+
+#[test]
+fn maps_of_the_same_size_share_a_mesh() {
+    let mut app = common::loading_app();
+    let chunks: Vec<_> = (0..100)
+        .map(|_| spawn_map(&mut app, uvec2(16, 16), default()))
+        .collect();
+    let other = spawn_map(&mut app, uvec2(8, 16), default());
+    app.update();
+    app.update();
+
+    let shared = mesh(&app, chunks[0]);
+    assert!(chunks.iter().all(|chunk| mesh(&app, *chunk) == shared));
+    assert_ne!(mesh(&app, other), shared);
+    assert_eq!(app.world().resource::<SharedMapMeshes>().len(), 2);
+    assert_eq!(app.world().resource::<Assets<Mesh>>().len(), 2);
+}
+
+#[test]
+fn mix_colors_get_a_mesh_of_their_own() {
+    let mut app = common::loading_app();
+    let plain = spawn_map(&mut app, uvec2(16, 16), default());
+    let tinted = spawn_map(
+        &mut app,
+        uvec2(16, 16),
+        MapAttributes {
+            mix_color: vec![Vec4::new(1.0, 0.0, 0.0, 1.0); 4],
+        },
+    );
+    app.update();
+    app.update();
+
+    assert_ne!(mesh(&app, plain), mesh(&app, tinted));
+    assert_eq!(app.world().resource::<SharedMapMeshes>().len(), 1);
+
+    // Back to white, the map uses the shared mesh again
+    app.world_mut()
+        .get_mut::<MapAttributes>(tinted)
+        .unwrap()
+        .mix_color
+        .clear();
+    app.update();
+    app.update();
+    assert_eq!(mesh(&app, plain), mesh(&app, tinted));
+}
@@ -0,0 +1,92 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+fn atlas_image(size: UVec2) -> Image {
+    Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[255; 4],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    )
+}
+
+/// Atlas size, padding (inner, top left, bottom right) and expected tile count
+/// of atlases of 64x64 pixel tiles with a single column
+const ATLASES: [(UVec2, [Vec2; 3], u32); 5] = [
+    (uvec2(64, 64), [Vec2::ZERO; 3], 1),
+    // Inner padding does not matter for a single tile
+    (uvec2(64, 64), [vec2(3.0, 3.0), Vec2::ZERO, Vec2::ZERO], 1),
+    (
+        uvec2(70, 69),
+        [Vec2::ZERO, vec2(3.0, 2.0), vec2(3.0, 3.0)],
+        1,
+    ),
+    (uvec2(64, 256), [Vec2::ZERO; 3], 4),
+    (uvec2(64, 262), [vec2(2.0, 2.0), Vec2::ZERO, Vec2::ZERO], 4),
+];
+
+#[test]
+fn single_tile_atlases_build() {
+    for projection in [IDENTITY, AXONOMETRIC] {
+        let mut images = Assets::<Image>::default();
+        for (size, [inner, topleft, bottomright], _) in ATLASES {
+            let atlas = images.add(atlas_image(size));
+            let map = Map::<NoCustomization>::builder(uvec2(8, 8), atlas, vec2(64.0, 64.0))
+                .with_padding(inner, topleft, bottomright)
+                .with_projection(projection)
+                .try_build(&images);
+            assert!(map.is_ok(), "{size}: {:?}", map.map(|_| ()));
+        }
+    }
+}
+
+#[test]
+fn single_tile_atlases_load() {
+    for projection in [IDENTITY, AXONOMETRIC] {
+        let mut app = common::loading_app();
+        let mut maps = Vec::new();
+        for (size, [inner, topleft, bottomright], n_tiles) in ATLASES {
+            let atlas = app
+                .world_mut()
+                .resource_mut::<Assets<Image>>()
+                .add(atlas_image(size));
+            let map = Map::builder(uvec2(8, 8), atlas, vec2(64.0, 64.0))
+                .with_padding(inner, topleft, bottomright)
+                .with_projection(projection)
+                .build_and_set(|p| (p.x + p.y) % n_tiles);
+            let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+            let entity = app
+                .world_mut()
+                .spawn(MapBundleManaged {
+                    material: handle.clone(),
+                    ..default()
+                })
+                .id();
+            maps.push((size, handle, entity, n_tiles));
+        }
+        app.update();
+        app.update();
+
+        for (size, handle, entity, n_tiles) in maps {
+            let map = app.world().resource::<Assets<Map>>().get(&handle).unwrap();
+            assert_eq!(map.atlas_tile_count(), Some(n_tiles), "{size}");
+            assert_eq!(map.validate_indices(), Ok(()), "{size}");
+            assert!(app.world().get::<MapBroken>(entity).is_none(), "{size}");
+            assert!(app.world().get::<MapLoading>(entity).is_none(), "{size}");
+        }
+    }
+}
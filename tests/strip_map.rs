@@ -0,0 +1,105 @@
+use bevy::{
+    math::{uvec2, vec2, vec3},
+    prelude::*,
+    render::mesh::VertexAttributeValues,
+    sprite::Mesh2dHandle,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+/// Length of the strips, eg. for a scrolling ticker
+const LENGTH: u32 = 4096;
+
+fn strip() -> Map {
+    Map::builder(uvec2(LENGTH, 1), default(), vec2(16., 16.)).build()
+}
+
+#[test]
+fn strip_is_exactly_as_large_as_its_tiles() {
+    let map = strip();
+    assert_eq!(map.world_size(), vec2(LENGTH as f32 * 16.0, 16.0));
+    assert_eq!(
+        map.world_bounds(),
+        Rect::from_center_size(Vec2::ZERO, map.world_size())
+    );
+}
+
+#[test]
+fn strip_ends_are_aligned() {
+    let map = strip();
+    let half = LENGTH as f32 * 8.0;
+    assert_eq!(map.tile_center_local(uvec2(0, 0)), vec2(8.0 - half, 0.0));
+    assert_eq!(
+        map.tile_center_local(uvec2(LENGTH - 1, 0)),
+        vec2(half - 8.0, 0.0)
+    );
+    assert_eq!(map.map_to_local(vec2(0.0, 0.0)), vec2(-half, 8.0));
+    assert_eq!(map.map_to_local(vec2(LENGTH as f32, 1.0)), vec2(half, -8.0));
+}
+
+#[test]
+fn strip_scrolls_end_to_end_without_stepping() {
+    let mut map = strip();
+    let half = LENGTH as f32 * 8.0;
+    for x in 0..LENGTH {
+        // Scroll tile `x` under the origin, like a ticker does
+        let scroll = half - 16.0 * x as f32 - 8.0;
+        map.apply_transform(&GlobalTransform::from_translation(vec3(scroll, 0.0, 0.0)));
+        assert_eq!(map.world_to_tile(Vec2::ZERO), Some(uvec2(x, 0)));
+        assert_eq!(map.tile_center_world(uvec2(x, 0)), Vec2::ZERO);
+        // Sub-tile positions stay exact at the far end as well
+        assert_eq!(
+            map.world_to_map(vec2(-4.0, 0.0)),
+            vec2(x as f32 + 0.25, 0.5)
+        );
+    }
+}
+
+fn mesh_positions(app: &App, entity: Entity) -> Vec<[f32; 3]> {
+    let mesh = app.world().get::<Mesh2dHandle>(entity).unwrap();
+    let mesh = app.world().resource::<Assets<Mesh>>().get(&mesh.0).unwrap();
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        panic!("unexpected vertex positions");
+    };
+    positions.clone()
+}
+
+#[test]
+fn long_strips_get_a_segmented_mesh() {
+    let mut app = common::map_app();
+    app.add_systems(Update, update_loading_maps::<NoCustomization>);
+
+    let (atlas, tile_size) = debug_atlas(&mut app.world_mut().resource_mut::<Assets<Image>>());
+    let mut spawn = |size: UVec2| {
+        let map = Map::builder(size, atlas.clone(), tile_size).build();
+        let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+        app.world_mut()
+            .spawn(MapBundleManaged {
+                material: handle,
+                ..default()
+            })
+            .id()
+    };
+    let strip = spawn(uvec2(LENGTH, 1));
+    let square = spawn(uvec2(16, 16));
+    app.update();
+    app.update();
+
+    let segments = LENGTH / MESH_SEGMENT_TILES;
+    let positions = mesh_positions(&app, strip);
+    assert_eq!(positions.len() as u32, 2 * (segments + 1));
+    let bounds = positions
+        .iter()
+        .fold(Rect::EMPTY, |rect, p| rect.union_point(vec2(p[0], p[1])));
+    assert_eq!(bounds.size(), tile_size * vec2(LENGTH as f32, 1.0));
+    // Segment borders are at exact tile borders
+    for p in positions.iter() {
+        let tiles = (p[0] - bounds.min.x) / tile_size.x;
+        assert_eq!(tiles % MESH_SEGMENT_TILES as f32, 0.0);
+    }
+
+    assert_eq!(mesh_positions(&app, square).len(), 4);
+}
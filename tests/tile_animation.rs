@@ -0,0 +1,147 @@
+use std::time::Duration;
+
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    time::TimeUpdateStrategy,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+const STEP: Duration = Duration::from_millis(250);
+
+fn map(animations: Vec<TileAnimation>) -> Map {
+    Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+        .with_tile_animations(animations)
+        .build()
+}
+
+#[test]
+fn frames_follow_the_animation_state() {
+    let map = map(vec![TileAnimation::new(6, 3, 5.0)]);
+    let frames: Vec<u32> = [0.0, 0.19, 0.2, 0.45, 0.6, 1.0]
+        .into_iter()
+        .map(|state| map.animated_tile_index(6, state))
+        .collect();
+    assert_eq!(frames, [6, 6, 7, 8, 6, 8]);
+
+    // Backwards as well
+    assert_eq!(map.animated_tile_index(6, -0.1), 8);
+
+    // Other tiles, including the other frames, are not animated
+    assert_eq!(map.animated_tile_index(7, 0.2), 7);
+    assert_eq!(map.animated_tile_index(0, 0.2), 0);
+}
+
+#[test]
+fn frame_tables_and_ping_pong() {
+    let map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+        .with_tile_animation(2, &[9, 4, 2], 1.0)
+        .with_tile_animations(vec![
+            TileAnimation::with_frames(6, &[6, 7, 8, 9], 1.0).ping_pong()
+        ])
+        .build_and_set(|_| 6);
+    // Replaced by the second call
+    assert_eq!(map.animated_tile_index(2, 0.0), 2);
+
+    let frames: Vec<u32> = (0..8)
+        .map(|t| map.animated_tile_index(6, t as f32 + 0.5))
+        .collect();
+    assert_eq!(frames, [6, 7, 8, 9, 8, 7, 6, 7]);
+    // The map keeps the base index
+    assert_eq!(map.indexer().at(1, 1), 6);
+
+    let map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+        .with_tile_animation(2, &[9, 4, 2], 1.0)
+        .with_tile_animation(3, &[5], 1.0)
+        .build();
+    let frames: Vec<u32> = (0..4)
+        .map(|t| map.animated_tile_index(2, t as f32))
+        .collect();
+    assert_eq!(frames, [9, 4, 2, 9]);
+    assert_eq!(map.animated_tile_index(3, 7.0), 5);
+}
+
+#[test]
+fn current_frame_follows_time() {
+    let map = map(vec![TileAnimation::new(6, 3, 5.0)]);
+    let mut time = Time::<()>::default();
+    time.advance_by(Duration::from_millis(250));
+    assert_eq!(map.current_frame(6, &time), 7);
+    assert_eq!(map.current_frame(1, &time), 1);
+    time.advance_by(Duration::from_millis(200));
+    assert_eq!(map.current_frame(6, &time), 8);
+}
+
+#[test]
+fn animations_are_sorted_and_deduplicated() {
+    let map = map(vec![
+        TileAnimation::new(10, 2, 1.0),
+        TileAnimation::new(3, 4, 1.0),
+        TileAnimation::new(7, 0, 1.0),
+        TileAnimation::new(10, 5, 2.0),
+    ]);
+    assert_eq!(
+        map.tile_animations(),
+        [
+            TileAnimation::new(3, 4, 1.0),
+            TileAnimation::new(10, 5, 2.0)
+        ]
+    );
+    assert_eq!(map.animated_tile_index(10, 2.0), 14);
+    assert_eq!(map.animated_tile_index(7, 0.5), 7);
+}
+
+#[test]
+fn without_animations() {
+    let mut map = map(vec![]);
+    assert!(map.tile_animations().is_empty());
+    assert_eq!(map.animated_tile_index(6, 0.5), 6);
+
+    map.set_tile_animations(vec![TileAnimation::new(6, 3, 5.0)]);
+    assert_eq!(map.tile_animations().len(), 1);
+    map.set_tile_animations(vec![TileAnimation::new(6, 0, 5.0)]);
+    assert!(map.tile_animations().is_empty());
+}
+
+#[test]
+fn render_to_image_shows_the_current_frame() {
+    let mut app = common::map_app();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(STEP))
+        .add_systems(PostUpdate, apply_map_animation_speeds::<NoCustomization>);
+    let mut images = Assets::<Image>::default();
+    let (atlas, tile_size) = debug_atlas(&mut images);
+    let render = |map: &Map, images: &mut Assets<Image>| {
+        let rendered = map.render_to_image(images, 1.0);
+        images.get(&rendered).unwrap().data.clone()
+    };
+    let first_frame = Map::builder(uvec2(4, 4), atlas.clone(), tile_size).build_and_set(|_| 1);
+    let second_frame = Map::builder(uvec2(4, 4), atlas.clone(), tile_size).build_and_set(|_| 2);
+    let animated = Map::builder(uvec2(4, 4), atlas, tile_size)
+        .with_tile_animations(vec![TileAnimation::new(1, 2, 1.0)])
+        .build_and_set(|_| 1);
+    assert_eq!(
+        render(&animated, &mut images),
+        render(&first_frame, &mut images)
+    );
+
+    // Stop the animation after more than a second, which keeps it at the second frame
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(animated);
+    for _ in 0..6 {
+        app.update();
+    }
+    let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+    maps.get_mut(&handle).unwrap().set_animation_speed(0.0);
+    app.update();
+    let maps = app.world().resource::<Assets<Map>>();
+    let stopped = maps.get(&handle).unwrap();
+    assert_eq!(
+        stopped.animated_tile_index(1, stopped.scaled_animation_state(0.0)),
+        2
+    );
+    assert_eq!(
+        render(stopped, &mut images),
+        render(&second_frame, &mut images)
+    );
+}
@@ -0,0 +1,89 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+fn app() -> App {
+    let mut app = common::map_app();
+    app.add_event::<MapTileChanged>()
+        .add_systems(PostUpdate, send_map_tile_changed::<NoCustomization>);
+    app
+}
+
+fn changed(app: &mut App) -> Vec<(AssetId<Map>, URect)> {
+    app.world_mut()
+        .resource_mut::<Events<MapTileChanged>>()
+        .drain()
+        .map(|ev| (ev.map, ev.rect))
+        .collect()
+}
+
+fn edit(app: &mut App, handle: &Handle<Map>, f: impl FnOnce(&mut Map)) {
+    let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+    f(maps.get_mut(handle).unwrap());
+}
+
+#[test]
+fn writes_are_batched_per_frame() {
+    let mut app = app();
+    let map = Map::builder(uvec2(64, 64), default(), vec2(16.0, 16.0)).build();
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    app.update();
+    changed(&mut app);
+
+    edit(&mut app, &handle, |map| {
+        let mut indexer = map.indexer_mut();
+        for x in 10..60 {
+            for y in 5..55 {
+                indexer.set(x, y, 3);
+            }
+        }
+    });
+    app.update();
+    assert_eq!(
+        changed(&mut app),
+        vec![(handle.id(), URect::new(10, 5, 60, 55))]
+    );
+
+    edit(&mut app, &handle, |map| {
+        map.indexer_mut().set(1, 2, 4);
+        map.indexer_mut().set(7, 3, 4);
+    });
+    app.update();
+    assert_eq!(
+        changed(&mut app),
+        vec![(handle.id(), URect::new(1, 2, 8, 4))]
+    );
+
+    // Nothing written
+    app.update();
+    assert_eq!(changed(&mut app), vec![]);
+}
+
+#[test]
+fn other_changes_are_not_reported() {
+    let mut app = app();
+    let map = Map::builder(uvec2(8, 8), default(), vec2(16.0, 16.0)).build_and_set(|p| p.x);
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    app.update();
+    changed(&mut app);
+
+    edit(&mut app, &handle, |map| {
+        map.set_param(0, Vec4::ONE);
+        let _ = map.indexer_mut().at(1, 1);
+    });
+    app.update();
+    assert_eq!(changed(&mut app), vec![]);
+
+    edit(&mut app, &handle, |map| {
+        map.resize(uvec2(10, 8), 0);
+    });
+    app.update();
+    assert_eq!(
+        changed(&mut app),
+        vec![(handle.id(), URect::new(0, 0, 10, 8))]
+    );
+}
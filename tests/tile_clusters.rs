@@ -0,0 +1,140 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+const WATER: u32 = 1;
+
+/// Map from whitespace separated rows of text, `#` is water
+fn map(rows: &str) -> Map {
+    let rows: Vec<&str> = rows.split_whitespace().collect();
+    let size = uvec2(rows[0].len() as u32, rows.len() as u32);
+    Map::builder(size, Handle::default(), vec2(16.0, 16.0)).build_and_set(|p| {
+        match rows[p.y as usize].as_bytes()[p.x as usize] {
+            b'#' => WATER,
+            _ => 0,
+        }
+    })
+}
+
+fn is_water(tile: u32) -> bool {
+    tile == WATER
+}
+
+fn sizes(clusters: &[TileCluster]) -> Vec<usize> {
+    clusters.iter().map(|cluster| cluster.tiles).collect()
+}
+
+#[test]
+fn orthogonal_neighbors_are_connected() {
+    let map = map("
+            ##..
+            .#..
+            .###
+            ....
+        ");
+    assert_eq!(sizes(&map.cluster_tiles(is_water, 1)), vec![6]);
+}
+
+#[test]
+fn diagonal_neighbors_are_not_connected() {
+    let map = map("
+            #...
+            .#..
+            ..#.
+            ...#
+        ");
+    assert_eq!(sizes(&map.cluster_tiles(is_water, 1)), vec![1, 1, 1, 1]);
+}
+
+#[test]
+fn small_clusters_are_skipped() {
+    let map = map("
+            ##.#
+            ##..
+            ....
+            #..#
+        ");
+    assert_eq!(sizes(&map.cluster_tiles(is_water, 1)), vec![4, 1, 1, 1]);
+    assert_eq!(sizes(&map.cluster_tiles(is_water, 2)), vec![4]);
+    assert!(map.cluster_tiles(is_water, 5).is_empty());
+}
+
+#[test]
+fn center_and_bounds_are_in_world_coordinates() {
+    let map = map("
+            ##..
+            ##..
+            ....
+            ....
+        ");
+    let clusters = map.cluster_tiles(is_water, 1);
+    assert_eq!(clusters.len(), 1);
+
+    // The map is centered on the origin, map y grows downwards
+    let expected = Rect::new(-32.0, 0.0, 0.0, 32.0);
+    assert!((clusters[0].center_world - expected.center()).length() < 1e-4);
+    assert!((clusters[0].bounds.min - expected.min).length() < 1e-4);
+    assert!((clusters[0].bounds.max - expected.max).length() < 1e-4);
+}
+
+fn assert_matches_full(clusters: &TileClusters, map: &Map) {
+    assert_eq!(clusters.clusters(map), map.cluster_tiles(is_water, 1));
+}
+
+#[test]
+fn incremental_updates_match_full_recomputation() {
+    let mut map = map("
+            ##.##
+            ##.##
+            .....
+            #...#
+            #...#
+        ");
+    map.enable_change_ticks();
+    let mut clusters = TileClusters::new(&map, is_water, 1);
+    assert_eq!(sizes(&clusters.clusters(&map)), vec![4, 4, 2, 2]);
+    let untouched = clusters.label_at(uvec2(0, 3));
+
+    assert!(!clusters.update(&map, is_water));
+
+    // Bridge the two top blobs
+    map.indexer_mut().set(2, 0, WATER);
+    assert!(clusters.update(&map, is_water));
+    assert_matches_full(&clusters, &map);
+    assert_eq!(sizes(&clusters.clusters(&map)), vec![9, 2, 2]);
+    assert_eq!(clusters.label_at(uvec2(0, 3)), untouched);
+
+    // Split them again
+    map.indexer_mut().set(2, 0, 0);
+    clusters.update(&map, is_water);
+    assert_matches_full(&clusters, &map);
+    assert_eq!(sizes(&clusters.clusters(&map)), vec![4, 4, 2, 2]);
+
+    // Remove a blob, grow another one
+    let mut m = map.indexer_mut();
+    m.set(3, 0, 0);
+    m.set(4, 0, 0);
+    m.set(3, 1, 0);
+    m.set(4, 1, 0);
+    m.set(4, 2, WATER);
+    clusters.update(&map, is_water);
+    assert_matches_full(&clusters, &map);
+    assert_eq!(sizes(&clusters.clusters(&map)), vec![4, 3, 2]);
+    assert_eq!(clusters.label_at(uvec2(3, 0)), None);
+}
+
+#[test]
+fn updates_without_change_ticks_recompute_everything() {
+    let mut map = map("
+            #.#
+            ...
+            #.#
+        ");
+    let mut clusters = TileClusters::new(&map, is_water, 1);
+    map.indexer_mut().set(1, 0, WATER);
+    clusters.update(&map, is_water);
+    assert_matches_full(&clusters, &map);
+    assert_eq!(sizes(&clusters.clusters(&map)), vec![3, 1, 1]);
+}
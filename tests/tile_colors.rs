@@ -0,0 +1,140 @@
+use bevy::{
+    math::{uvec2, vec2, URect},
+    prelude::*,
+    sprite::Anchor,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+const RED: Color = Color::srgb(1.0, 0.0, 0.0);
+
+/// Tile colors are linear, compare them as such
+fn color_at(map: &Map, x: u32, y: u32) -> LinearRgba {
+    map.indexer().color_at(x, y).to_linear()
+}
+
+fn map() -> Map {
+    Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+        .with_tile_color_channel()
+        .build()
+}
+
+#[test]
+fn set_and_get() {
+    let mut map = map();
+    assert!(map.has_tile_color_channel());
+    assert_eq!(color_at(&map, 1, 2), LinearRgba::WHITE);
+
+    let mut indexer = map.indexer_mut();
+    indexer.set_color(1, 2, RED);
+    indexer.set_color_uvec(uvec2(3, 3), Color::BLACK);
+    // Ignored
+    indexer.set_color(4, 0, RED);
+
+    assert_eq!(color_at(&map, 1, 2), LinearRgba::RED);
+    assert_eq!(
+        map.indexer().color_at_uvec(uvec2(3, 3)).to_linear(),
+        LinearRgba::BLACK
+    );
+    assert_eq!(color_at(&map, 2, 1), LinearRgba::WHITE);
+    assert_eq!(color_at(&map, 4, 0), LinearRgba::WHITE);
+
+    map.fill_tile_colors(Color::WHITE);
+    assert_eq!(color_at(&map, 1, 2), LinearRgba::WHITE);
+}
+
+#[test]
+fn tints_are_tile_colors() {
+    let mut map: Map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+        .with_per_tile_tint()
+        .build();
+    assert!(map.has_tile_color_channel());
+    assert_eq!(map.indexer().tint_at(2, 2).to_linear(), LinearRgba::WHITE);
+
+    let mut indexer = map.indexer_mut();
+    indexer.set_tint(2, 2, RED);
+    assert_eq!(indexer.tint_at(2, 2).to_linear(), LinearRgba::RED);
+    indexer.set_color(1, 0, Color::BLACK);
+    assert_eq!(map.indexer().tint_at(1, 0).to_linear(), LinearRgba::BLACK);
+    assert_eq!(color_at(&map, 2, 2), LinearRgba::RED);
+}
+
+#[test]
+fn colors_are_approximate() {
+    let mut map = map();
+    let gray = Color::linear_rgba(0.3, 0.3, 0.3, 0.5);
+    map.indexer_mut().set_color(0, 0, gray);
+    let stored = color_at(&map, 0, 0);
+    assert!((stored.red - 0.3).abs() < 0.01);
+    assert!((stored.alpha - 0.5).abs() < 0.01);
+}
+
+#[test]
+fn ignored_without_channel() {
+    let mut map: Map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0)).build();
+    assert!(!map.has_tile_color_channel());
+    map.indexer_mut().set_color(1, 1, RED);
+    map.fill_tile_colors(RED);
+    assert_eq!(color_at(&map, 1, 1), LinearRgba::WHITE);
+}
+
+#[test]
+fn resizing_moves_the_colors_along() {
+    let mut map = map();
+    map.indexer_mut().set_color(1, 1, RED);
+    map.resize_anchored(uvec2(6, 6), 0, Anchor::BottomRight);
+    assert_eq!(color_at(&map, 3, 3), LinearRgba::RED);
+    assert_eq!(color_at(&map, 1, 1), LinearRgba::WHITE);
+    assert_eq!(color_at(&map, 0, 0), LinearRgba::WHITE);
+}
+
+#[test]
+fn color_writes_are_dirty_regions() {
+    let mut app = common::map_app();
+    app.insert_resource(FastTileMapDebug::show_dirty_regions(true))
+        .add_systems(PostUpdate, update_map_debug::<NoCustomization>);
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map());
+    app.update();
+
+    let set_colors = |app: &mut App| {
+        let mut maps = app.world_mut().resource_mut::<Assets<Map>>();
+        let mut indexer = maps.get_mut(&handle).unwrap().indexer_mut();
+        indexer.set_color(1, 2, RED);
+        indexer.set_color(3, 0, RED);
+    };
+    let dirty_region = |app: &App| {
+        let maps = app.world().resource::<Assets<Map>>();
+        maps.get(&handle).unwrap().debug_dirty_region()
+    };
+    set_colors(&mut app);
+    app.update();
+    assert_eq!(dirty_region(&app), Some((URect::new(1, 0, 4, 3), 1.0)));
+
+    // Unchanged colors are not written again, so the region fades
+    set_colors(&mut app);
+    app.update();
+    assert!(dirty_region(&app).unwrap().1 < 1.0);
+}
+
+#[test]
+fn render_to_image_applies_the_colors() {
+    let mut images = Assets::<Image>::default();
+    let (atlas, tile_size) = debug_atlas(&mut images);
+    let mut map = Map::builder(uvec2(4, 4), atlas, tile_size)
+        .with_tile_color_channel()
+        .build();
+    map.indexer_mut().set_color(1, 2, RED);
+
+    let rendered = map.render_to_image(&mut images, 1.0);
+    let image = images.get(&rendered).unwrap();
+    let top_left = map.world_size() * vec2(-0.5, 0.5);
+    let pixel = |tile: UVec2| {
+        let p = ((map.tile_center_world(tile) - top_left) * vec2(1.0, -1.0)).as_uvec2();
+        let i = ((p.y * image.width() + p.x) * 4) as usize;
+        image.data[i..i + 4].to_vec()
+    };
+    // Tile centers of the debug atlas are white
+    assert_eq!(pixel(uvec2(2, 2)), [255, 255, 255, 255]);
+    assert_eq!(pixel(uvec2(1, 2)), [255, 0, 0, 255]);
+}
@@ -0,0 +1,102 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_fast_tilemap::prelude::*;
+
+/// Single 4x4 tile, only the top left texel is opaque
+fn corner_atlas(images: &mut Assets<Image>) -> Handle<Image> {
+    let mut data = vec![0u8; 4 * 4 * 4];
+    data[3] = 255;
+    images.add(Image::new(
+        Extent3d {
+            width: 4,
+            height: 4,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8Unorm,
+        RenderAssetUsages::all(),
+    ))
+}
+
+/// Alpha at the four corners of tile (0, 0): top left, top right, bottom left, bottom right
+/// (in atlas orientation)
+fn corner_alphas(map: &Map, images: &Assets<Image>) -> [f32; 4] {
+    [
+        vec2(0.125, 0.125),
+        vec2(0.875, 0.125),
+        vec2(0.125, 0.875),
+        vec2(0.875, 0.875),
+    ]
+    .map(|p| map.texel_alpha_at(p, images).unwrap())
+}
+
+#[test]
+fn indexer_splits_flags_from_index() {
+    let mut map: Map = Map::builder(uvec2(4, 4), default(), vec2(16., 16.)).build_and_set(|_| 3);
+    let mut m = map.indexer_mut();
+    m.set(1, 2, 5 | TILE_FLIP_Y | TILE_ROTATE);
+
+    assert_eq!(m.at(1, 2), 5 | TILE_FLIP_Y | TILE_ROTATE);
+    assert_eq!(m.index_at(1, 2), 5);
+    assert_eq!(m.flags_at(1, 2), TILE_FLIP_Y | TILE_ROTATE);
+    assert_eq!(m.flags_at(0, 0), 0);
+    assert_eq!(map.indexer().index_at(1, 2), 5);
+}
+
+#[test]
+fn flags_and_index_are_set_separately() {
+    let mut map: Map = Map::builder(uvec2(4, 4), default(), vec2(16., 16.)).build_and_set(|_| 3);
+    let mut m = map.indexer_mut();
+    m.set_flags(1, 2, TILE_FLIP_X | 42);
+    assert_eq!(m.at(1, 2), 3 | TILE_FLIP_X);
+    m.set_index(1, 2, 6 | TILE_ROTATE);
+    assert_eq!(m.at(1, 2), 6 | TILE_FLIP_X);
+    m.set_flags(1, 2, 0);
+    assert_eq!(m.at(1, 2), 6);
+    // Out of bounds is ignored
+    m.set_flags(4, 0, TILE_FLIP_Y);
+    m.set_index(0, 4, 1);
+}
+
+#[test]
+fn flags_transform_the_sampled_texel() {
+    let mut images = Assets::<Image>::default();
+    let atlas = corner_atlas(&mut images);
+    let alphas = |flags: u32| {
+        let map: Map =
+            Map::builder(uvec2(1, 1), atlas.clone(), vec2(4., 4.)).build_and_set(|_| flags);
+        corner_alphas(&map, &images)
+    };
+
+    assert_eq!(alphas(0), [1.0, 0.0, 0.0, 0.0]);
+    assert_eq!(alphas(TILE_FLIP_X), [0.0, 1.0, 0.0, 0.0]);
+    assert_eq!(alphas(TILE_FLIP_Y), [0.0, 0.0, 1.0, 0.0]);
+    assert_eq!(alphas(TILE_FLIP_X | TILE_FLIP_Y), [0.0, 0.0, 0.0, 1.0]);
+    // The opaque texel is on the diagonal, so transposing keeps it in place
+    assert_eq!(alphas(TILE_ROTATE), [1.0, 0.0, 0.0, 0.0]);
+    // Rotating clockwise moves the top left corner to the top right,
+    // counterclockwise to the bottom left
+    assert_eq!(alphas(TILE_ROTATE | TILE_FLIP_X), [0.0, 1.0, 0.0, 0.0]);
+    assert_eq!(alphas(TILE_ROTATE | TILE_FLIP_Y), [0.0, 0.0, 1.0, 0.0]);
+}
+
+#[test]
+fn palette_owner_ignores_flags() {
+    let map: Map = Map::builder(uvec2(1, 1), default(), vec2(16., 16.))
+        .with_palette_owner_bits(16, 8)
+        .build_and_set(|_| 0);
+    assert_eq!(map.palette_owner((3 << 16) | 7 | TILE_FLIP_X), 3);
+
+    // Owner bits are clamped below the flags
+    let map: Map = Map::builder(uvec2(1, 1), default(), vec2(16., 16.))
+        .with_palette_owner_bits(24, 8)
+        .build_and_set(|_| 0);
+    assert_eq!(map.palette_owner((0x1f << 24) | TILE_FLAGS_MASK), 0x1f);
+}
@@ -0,0 +1,105 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    sprite::Anchor,
+};
+use bevy_fast_tilemap::prelude::*;
+
+fn map() -> Map {
+    Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0))
+        .with_layers(3)
+        .build_and_set(|_| 1)
+}
+
+#[test]
+fn set_and_get() {
+    let mut map = map();
+    assert_eq!(map.n_layers(), 3);
+    assert_eq!(map.indexer().layer_at(1, 2, 2), EMPTY_TILE);
+
+    let mut indexer = map.indexer_mut();
+    indexer.set_layer(1, 2, 2, 5);
+    indexer.set_layer_uvec(2, uvec2(0, 3), 6);
+    indexer.set_layer(0, 1, 1, 7);
+    // Ignored
+    indexer.set_layer(3, 0, 0, 8);
+    indexer.set_layer(1, 4, 0, 8);
+
+    let indexer = map.indexer();
+    assert_eq!(indexer.layer_at(1, 2, 2), 5);
+    assert_eq!(indexer.layer_at_uvec(2, uvec2(0, 3)), 6);
+    assert_eq!(indexer.layer_at(2, 2, 2), EMPTY_TILE);
+    // Layer 0 are the tiles of the map
+    assert_eq!(indexer.at(1, 1), 7);
+    assert_eq!(indexer.layer_at(0, 2, 2), 1);
+    assert_eq!(indexer.layer_at(3, 0, 0), EMPTY_TILE);
+    assert_eq!(indexer.layer_at(1, 4, 0), EMPTY_TILE);
+
+    map.indexer_mut().fill_layer(1, 9);
+    assert_eq!(map.indexer().layer_at(1, 3, 3), 9);
+    assert_eq!(map.indexer().layer_at(2, 0, 3), 6);
+}
+
+#[test]
+fn visibility() {
+    let mut map = map();
+    assert!((0..3).all(|layer| map.is_layer_visible(layer)));
+    map.set_layer_visible(1, false);
+    map.set_layer_visible(0, false);
+    assert!(!map.is_layer_visible(0));
+    assert!(!map.is_layer_visible(1));
+    assert!(map.is_layer_visible(2));
+    map.set_layer_visible(0, true);
+    assert!(map.is_layer_visible(0));
+    // Layers the map doesn't have are never visible
+    map.set_layer_visible(3, true);
+    assert!(!map.is_layer_visible(3));
+}
+
+#[test]
+fn single_layer_by_default() {
+    let mut map: Map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0)).build();
+    assert_eq!(map.n_layers(), 1);
+    map.indexer_mut().set_layer(1, 0, 0, 2);
+    assert_eq!(map.indexer().layer_at(1, 0, 0), EMPTY_TILE);
+}
+
+#[test]
+fn resizing_moves_the_layers_along() {
+    let mut map = map();
+    map.indexer_mut().set_layer(2, 1, 1, 5);
+    map.resize_anchored(uvec2(6, 6), 0, Anchor::BottomRight);
+    assert_eq!(map.indexer().layer_at(2, 3, 3), 5);
+    assert_eq!(map.indexer().layer_at(2, 1, 1), EMPTY_TILE);
+    assert_eq!(map.indexer().layer_at(1, 3, 3), EMPTY_TILE);
+    assert_eq!(map.indexer().layer_at(1, 5, 5), EMPTY_TILE);
+}
+
+#[test]
+fn render_to_image_stacks_the_layers() {
+    let mut images = Assets::<Image>::default();
+    let (atlas, tile_size) = debug_atlas(&mut images);
+    let mut render = |map: &Map| {
+        let rendered = map.render_to_image(&mut images, 1.0);
+        images.get(&rendered).unwrap().data.clone()
+    };
+    let plain = |tile: u32| {
+        Map::builder(uvec2(4, 4), atlas.clone(), tile_size).build_and_set(move |_| tile)
+    };
+    let (first, second) = (render(&plain(1)), render(&plain(2)));
+
+    let mut map = Map::builder(uvec2(4, 4), atlas.clone(), tile_size)
+        .with_layers(2)
+        .build_and_set(|_| 1);
+    assert_eq!(render(&map), first);
+
+    // The tiles of the debug atlas are opaque, so the upper layer covers layer 0
+    map.indexer_mut().fill_layer(1, 2);
+    assert_eq!(render(&map), second);
+
+    map.set_layer_visible(1, false);
+    assert_eq!(render(&map), first);
+
+    map.set_layer_visible(0, false);
+    assert!(render(&map).iter().all(|c| *c == 0));
+}
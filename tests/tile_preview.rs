@@ -0,0 +1,58 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+fn map() -> Map {
+    Map::builder(uvec2(4, 3), Handle::default(), vec2(16.0, 16.0)).build()
+}
+
+#[test]
+fn stamps_are_row_by_row() {
+    let stamp = TileStamp::new(uvec2(3, 2), vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(stamp.at(uvec2(2, 0)), 3);
+    assert_eq!(stamp.at(uvec2(0, 1)), 4);
+    assert_eq!(
+        TileStamp::from_fn(uvec2(3, 2), |p| p.y * 3 + p.x + 1),
+        stamp
+    );
+}
+
+#[test]
+#[should_panic]
+fn stamp_size_must_match_tiles() {
+    TileStamp::new(uvec2(2, 2), vec![1, 2, 3]);
+}
+
+#[test]
+fn placing_a_stamp_is_clipped_to_the_map() {
+    let mut map = map();
+    let stamp = TileStamp::new(uvec2(2, 2), vec![1, 2, 3, 4]);
+    map.indexer_mut().place_stamp(uvec2(3, 2), &stamp);
+    map.indexer_mut().place_stamp(uvec2(0, 0), &stamp);
+
+    let m = map.indexer();
+    assert_eq!(m.at(3, 2), 1);
+    assert_eq!(
+        [m.at(0, 0), m.at(1, 0), m.at(0, 1), m.at(1, 1)],
+        [1, 2, 3, 4]
+    );
+    assert_eq!(m.at(2, 0), 0);
+}
+
+#[test]
+fn preview_does_not_change_map_data() {
+    let mut map = map();
+    let preview = TilePreview {
+        origin: uvec2(1, 1),
+        tiles: TileStamp::single(7),
+        opacity: 0.5,
+    };
+    map.set_preview(Some(preview.clone()));
+    assert_eq!(map.preview(), Some(&preview));
+    assert_eq!(map.indexer().at(1, 1), 0);
+
+    map.set_preview(None);
+    assert_eq!(map.preview(), None);
+}
@@ -0,0 +1,62 @@
+use bevy::{math::uvec2, prelude::*};
+use bevy_fast_tilemap::prelude::*;
+
+/// Map using the 8x8 tiles debug atlas, `update`d iff `loaded`
+fn map(loaded: bool, expected_columns: Option<u32>) -> Map {
+    let mut images = Assets::<Image>::default();
+    let (atlas, tile_size) = debug_atlas(&mut images);
+    let mut builder = Map::builder(uvec2(4, 4), atlas, tile_size);
+    if let Some(columns) = expected_columns {
+        builder = builder.with_expected_atlas_columns(columns);
+    }
+    let mut map = builder.build();
+    if loaded {
+        map.update(&images);
+    }
+    map
+}
+
+#[test]
+fn conversion_needs_the_atlas() {
+    let map = map(false, None);
+    assert_eq!(map.atlas_columns(), None);
+    assert_eq!(map.index_from_colrow(1, 2), None);
+    assert_eq!(map.colrow_from_index(17), None);
+    assert_eq!(map.tile_index(uvec2(1, 2)), None);
+    assert_eq!(map.tile_index(17), Some(17));
+}
+
+#[test]
+fn conversion_roundtrips_after_loading() {
+    let map = map(true, None);
+    assert_eq!(map.atlas_columns(), Some(8));
+    assert_eq!(map.index_from_colrow(1, 2), Some(17));
+    assert_eq!(map.colrow_from_index(17), Some(uvec2(1, 2)));
+    assert_eq!(map.tile_index(TileRef::ColRow(uvec2(7, 7))), Some(63));
+
+    // Outside of the atlas
+    assert_eq!(map.index_from_colrow(8, 0), None);
+    assert_eq!(map.index_from_colrow(0, 8), None);
+    assert_eq!(map.colrow_from_index(64), None);
+}
+
+#[test]
+fn expected_columns_allow_conversion_before_loading() {
+    let pending = map(false, Some(4));
+    assert_eq!(pending.index_from_colrow(1, 2), Some(9));
+    assert_eq!(pending.colrow_from_index(9), Some(uvec2(1, 2)));
+
+    // The real atlas wins once loaded
+    let loaded = map(true, Some(4));
+    assert_eq!(loaded.index_from_colrow(1, 2), Some(17));
+}
+
+#[test]
+fn stamps_from_tile_refs() {
+    let loaded = map(true, None);
+    let tiles = [TileRef::Index(3), TileRef::ColRow(uvec2(0, 1))];
+    let stamp = TileStamp::from_tile_refs(uvec2(2, 1), tiles, &loaded).unwrap();
+    assert_eq!(stamp, TileStamp::new(uvec2(2, 1), vec![3, 8]));
+
+    assert!(TileStamp::from_tile_refs(uvec2(2, 1), tiles, &map(false, None)).is_none());
+}
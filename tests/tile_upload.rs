@@ -0,0 +1,18 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+#[test]
+fn all_rows_are_pending_before_the_first_upload() {
+    let mut map: Map = Map::builder(uvec2(8, 6), default(), vec2(16.0, 16.0)).build_and_set(|_| 1);
+    assert_eq!(map.pending_tile_rows(), 0..6);
+
+    map.indexer_mut().set(2, 3, 4);
+    map.force_full_sync();
+    assert_eq!(map.pending_tile_rows(), 0..6);
+
+    map.resize(uvec2(8, 10), 0);
+    assert_eq!(map.pending_tile_rows(), 0..10);
+}
@@ -0,0 +1,170 @@
+use std::{fs, path::PathBuf, thread, time::Duration};
+
+use bevy::{math::vec2, prelude::*};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+const TILESET: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" name="tiles" tilewidth="16" tileheight="16" tilecount="6" columns="3">
+ <image source="../images/tiles.png" width="48" height="40"/>
+</tileset>
+"#;
+
+/// Two layers, the second one using both tilesets, and one object layer
+const MAP: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" renderorder="right-down" width="4" height="3" tilewidth="16" tileheight="16" infinite="0" nextlayerid="4" nextobjectid="3">
+ <tileset firstgid="1" source="../tilesets/tiles.tsx"/>
+ <tileset firstgid="7" name="more" tilewidth="16" tileheight="16" spacing="2" tilecount="2" columns="2">
+  <image source="../images/more.png" width="34" height="16"/>
+ </tileset>
+ <layer id="1" name="ground" width="4" height="3">
+  <data encoding="csv">
+1,2,3,4,
+4,5,6,1,
+1,1,1,1
+</data>
+ </layer>
+ <layer id="2" name="decor" width="4" height="3" offsetx="8" offsety="-4" opacity="0.5">
+  <data encoding="csv">
+0,8,0,0,
+0,0,2147483650,0,
+1073741831,0,0,0
+</data>
+ </layer>
+ <objectgroup id="3" name="spawns">
+  <object id="1" name="player" x="32" y="16" width="32" height="16"/>
+  <object id="2" name="exit" x="8" y="40">
+   <point/>
+  </object>
+ </objectgroup>
+</map>
+"#;
+
+/// Asset directory with the given files, unique per test
+fn asset_dir(test: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bevy_fast_tilemap_{test}"));
+    let _ = fs::remove_dir_all(&dir);
+    for (name, content) in files {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+fn app(dir: PathBuf) -> App {
+    let mut app = common::map_app_in(&dir);
+    app.add_plugins(TiledMapPlugin);
+    app
+}
+
+/// Update `app` until `handle` is loaded (or failed to load)
+fn load(app: &mut App, handle: &Handle<TiledMap>) {
+    for _ in 0..200 {
+        app.update();
+        let asset_server = app.world().resource::<AssetServer>();
+        match asset_server.load_state(handle) {
+            bevy::asset::LoadState::Loaded | bevy::asset::LoadState::Failed(_) => return,
+            _ => thread::sleep(Duration::from_millis(5)),
+        }
+    }
+    panic!("Tiled map did not load");
+}
+
+#[test]
+fn layers_become_maps() {
+    let mut app = app(asset_dir(
+        "tiled_layers",
+        &[("maps/level.tmx", MAP), ("tilesets/tiles.tsx", TILESET)],
+    ));
+    let handle: Handle<TiledMap> = app.world().resource::<AssetServer>().load("maps/level.tmx");
+    load(&mut app, &handle);
+
+    let tiled_map = app.world().resource::<Assets<TiledMap>>().get(&handle);
+    let tiled_map = tiled_map.expect("Tiled map is loaded").clone();
+    assert_eq!(tiled_map.map_size, UVec2::new(4, 3));
+    let names: Vec<_> = tiled_map.layers.iter().map(|l| l.name.as_str()).collect();
+    assert_eq!(names, ["ground", "decor"]);
+    assert_eq!(tiled_map.layers[0].translation, Vec3::ZERO);
+    // Later layers are drawn above, Tiled offsets point down
+    assert_eq!(tiled_map.layers[1].translation, Vec3::new(8.0, 4.0, 1.0));
+
+    let maps = app.world().resource::<Assets<Map>>();
+    let ground = maps.get(&tiled_map.layers[0].map).unwrap();
+    assert_eq!(ground.map_size(), UVec2::new(4, 3));
+    assert_eq!(ground.world_size(), vec2(64.0, 48.0));
+    let m = ground.indexer();
+    assert_eq!([m.at(0, 0), m.at(3, 0), m.at(1, 1)], [0, 3, 4]);
+    let asset_server = app.world().resource::<AssetServer>();
+    let atlas_path = asset_server.get_path(ground.atlas_texture().id());
+    assert_eq!(atlas_path.unwrap().to_string(), "images/tiles.png");
+
+    let decor = maps.get(&tiled_map.layers[1].map).unwrap();
+    let m = decor.indexer();
+    assert_eq!(m.at(0, 0), EMPTY_TILE);
+    assert_eq!(m.at(1, 0), tile_in_atlas(1, 1));
+    assert_eq!(m.at(2, 1), 1 | TILE_FLIP_X);
+    assert_eq!(m.at(0, 2), tile_in_atlas(1, 0) | TILE_FLIP_Y);
+
+    let [spawns] = tiled_map.object_layers.as_slice() else {
+        panic!("expected one object layer");
+    };
+    assert_eq!(spawns.name, "spawns");
+    assert_eq!(spawns.objects[0].name, "player");
+    assert_eq!(
+        spawns.objects[0].rect,
+        Rect::from_corners(vec2(2.0, 1.0), vec2(4.0, 2.0))
+    );
+    assert_eq!(spawns.objects[1].rect, Rect::new(0.5, 2.5, 0.5, 2.5));
+}
+
+#[test]
+fn layers_are_spawned_as_children() {
+    let mut app = app(asset_dir(
+        "tiled_spawn",
+        &[("maps/level.tmx", MAP), ("tilesets/tiles.tsx", TILESET)],
+    ));
+    let handle: Handle<TiledMap> = app.world().resource::<AssetServer>().load("maps/level.tmx");
+    let entity = app
+        .world_mut()
+        .spawn(TiledMapBundle {
+            tiled_map: handle.clone(),
+            ..default()
+        })
+        .id();
+    load(&mut app, &handle);
+    app.update();
+
+    let layers = app.world().get::<TiledMapLayers>(entity).unwrap().0.clone();
+    assert_eq!(layers.len(), 2);
+    let children = app.world().get::<Children>(entity).unwrap();
+    assert_eq!(children.to_vec(), layers);
+    let transform = app.world().get::<Transform>(layers[1]).unwrap();
+    assert_eq!(transform.translation, Vec3::new(8.0, 4.0, 1.0));
+    assert!(app.world().get::<Handle<Map>>(layers[0]).is_some());
+
+    let objects = app.world().get::<TiledObjects>(entity).unwrap();
+    assert_eq!(objects.0[0].objects.len(), 2);
+}
+
+#[test]
+fn unsupported_maps_fail_to_load() {
+    let hexagonal = MAP.replace("orthogonal", "hexagonal");
+    let mut app = app(asset_dir(
+        "tiled_unsupported",
+        &[
+            ("maps/level.tmx", &hexagonal),
+            ("tilesets/tiles.tsx", TILESET),
+        ],
+    ));
+    let handle: Handle<TiledMap> = app.world().resource::<AssetServer>().load("maps/level.tmx");
+    load(&mut app, &handle);
+
+    let asset_server = app.world().resource::<AssetServer>();
+    assert!(matches!(
+        asset_server.load_state(&handle),
+        bevy::asset::LoadState::Failed(_)
+    ));
+    assert!(app.world().resource::<Assets<TiledMap>>().is_empty());
+}
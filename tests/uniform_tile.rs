@@ -0,0 +1,88 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+};
+use bevy_fast_tilemap::prelude::*;
+
+fn uniform_map(tile: u32) -> Map {
+    Map::builder(uvec2(8, 4), default(), vec2(16.0, 16.0)).build_and_set(|_| tile)
+}
+
+fn tiles(map: &Map) -> Vec<u32> {
+    let size = map.map_size();
+    (0..size.y)
+        .flat_map(|y| (0..size.x).map(move |x| uvec2(x, y)))
+        .map(|p| map.indexer().at_uvec(p))
+        .collect()
+}
+
+fn round_trip(map: &Map) -> Map {
+    MapBuilder::<NoCustomization>::from_bytes(&map.to_bytes(), default())
+        .unwrap()
+        .build()
+}
+
+#[test]
+fn uniform_maps_are_detected_at_build() {
+    assert_eq!(uniform_map(3).uniform_tile(), Some(3));
+    let empty: Map = Map::builder(uvec2(8, 4), default(), vec2(16.0, 16.0)).build();
+    assert_eq!(empty.uniform_tile(), Some(0));
+
+    let varied: Map = Map::builder(uvec2(8, 4), default(), vec2(16.0, 16.0)).build_and_set(|p| p.x);
+    assert_eq!(varied.uniform_tile(), None);
+    // Only a single tile is uploaded for the uniform map
+    assert!(uniform_map(3).upload_size() < varied.upload_size());
+}
+
+#[test]
+fn first_write_switches_to_tiles() {
+    let mut map = uniform_map(3);
+    // Writing the uniform value keeps the map uniform
+    map.indexer_mut().set(1, 2, 3);
+    assert_eq!(map.uniform_tile(), Some(3));
+
+    map.indexer_mut().set(1, 2, 5);
+    assert_eq!(map.uniform_tile(), None);
+    let mut expected = vec![3; 8 * 4];
+    expected[2 * 8 + 1] = 5;
+    assert_eq!(tiles(&map), expected);
+}
+
+#[test]
+fn fill_round_trips() {
+    let mut map: Map =
+        Map::builder(uvec2(8, 4), default(), vec2(16.0, 16.0)).build_and_set(|p| p.y);
+    assert_eq!(map.indexer_mut().fill(7), 8 * 4);
+    assert_eq!(map.uniform_tile(), Some(7));
+    assert_eq!(tiles(&map), vec![7; 8 * 4]);
+
+    let decoded = round_trip(&map);
+    assert_eq!(decoded.uniform_tile(), Some(7));
+    assert_eq!(tiles(&decoded), tiles(&map));
+    assert_eq!(decoded.to_bytes(), map.to_bytes());
+}
+
+#[test]
+fn region_writes_round_trip() {
+    let mut map = uniform_map(3);
+    map.indexer_mut().region_mut(URect::new(2, 1, 4, 3)).fill(9);
+    assert_eq!(map.uniform_tile(), None);
+    let expected: Vec<u32> = (0..4)
+        .flat_map(|y| (0..8).map(move |x| (x, y)))
+        .map(|(x, y)| match (2..4).contains(&x) && (1..3).contains(&y) {
+            true => 9,
+            false => 3,
+        })
+        .collect();
+    assert_eq!(tiles(&map), expected);
+
+    let decoded = round_trip(&map);
+    assert_eq!(decoded.uniform_tile(), None);
+    assert_eq!(tiles(&decoded), expected);
+
+    // Regions of uniform maps read the uniform value
+    let mut map = uniform_map(4);
+    let mut indexer = map.indexer_mut();
+    let region = indexer.region_mut(URect::new(0, 0, 2, 2));
+    assert_eq!(region.at(1, 1), 4);
+}
@@ -0,0 +1,77 @@
+use std::thread;
+
+use bevy::{math::uvec2, prelude::*};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+fn map() -> Map {
+    Map::builder(uvec2(8, 8), Handle::default(), Vec2::splat(16.0)).build()
+}
+
+#[test]
+fn writes_from_other_threads_are_applied_in_order() {
+    let mut app = common::map_app();
+    app.add_systems(PostUpdate, apply_map_update_queues::<NoCustomization>);
+
+    let map = map();
+    let queue = map.update_queue();
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+
+    thread::spawn(move || {
+        queue.push(TileWrite::new(uvec2(1, 2), 5));
+        queue.push_rect(URect::new(0, 0, 2, 1), 3);
+        // Later writes win
+        queue.push(TileWrite::new(uvec2(1, 2), 7));
+        // Out of bounds writes are ignored
+        queue.push(TileWrite::new(uvec2(8, 0), 1));
+    })
+    .join()
+    .unwrap();
+
+    app.update();
+    let maps = app.world().resource::<Assets<Map>>();
+    let map = maps.get(&handle).unwrap();
+    assert!(map.update_queue().is_empty());
+    let indexer = map.indexer();
+    assert_eq!(indexer.at(1, 2), 7);
+    assert_eq!(indexer.at(0, 0), 3);
+    assert_eq!(indexer.at(1, 0), 3);
+    assert_eq!(indexer.at(2, 0), 0);
+}
+
+#[test]
+fn bounded_queues() {
+    let map: Map = Map::builder(uvec2(8, 8), Handle::default(), Vec2::splat(16.0))
+        .with_update_queue(QueueOverflow::Reject(2))
+        .build();
+    let queue = map.update_queue();
+    assert_eq!(queue.push_rect(URect::new(0, 0, 3, 1), 1), 2);
+    assert!(!queue.push(TileWrite::new(uvec2(4, 4), 1)));
+    assert_eq!(queue.dropped(), 2);
+
+    let mut map = map;
+    map.set_update_queue_overflow(QueueOverflow::DropOldest(2));
+    map.apply_update_queue();
+    let queue = map.update_queue();
+    queue.push_rect(URect::new(0, 1, 3, 2), 2);
+    map.apply_update_queue();
+    let indexer = map.indexer();
+    assert_eq!(
+        [indexer.at(0, 0), indexer.at(1, 0), indexer.at(2, 0)],
+        [1, 1, 0]
+    );
+    assert_eq!(
+        [indexer.at(0, 1), indexer.at(1, 1), indexer.at(2, 1)],
+        [0, 2, 2]
+    );
+}
+
+#[test]
+fn clones_have_their_own_queue() {
+    let mut map = map();
+    let clone = map.clone();
+    clone.update_queue().push(TileWrite::new(uvec2(0, 0), 1));
+    map.apply_update_queue();
+    assert_eq!(map.indexer().at(0, 0), 0);
+}
@@ -0,0 +1,86 @@
+// `#[derive(ShaderType)]` emits never-called `check` functions.
+#![allow(dead_code)]
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderType},
+};
+use bevy_fast_tilemap::{prelude::*, shader::check_user_data_size};
+
+#[derive(Debug, Clone, Default, Reflect, AsBindGroup, ShaderType)]
+struct UserData {
+    color: Vec4,
+    frequency: f32,
+}
+
+#[derive(Clone, TypePath, Default)]
+struct Matching;
+
+impl Customization for Matching {
+    const SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x7a3c1d9e2b4f6a80);
+    type UserData = UserData;
+    type ExtraBindings = NoExtraBindings;
+
+    fn custom_shader_code() -> String {
+        r#"
+        // struct Commented { a: u32 };
+        struct Wave {
+            frequency: f32,
+        };
+
+        struct UserData {
+            color: vec4<f32>,
+            wave: Wave,
+        };
+
+        fn sample_tile(in: ExtractIn) -> vec4<f32> {
+            return sample_tile_at(in.tile_index, in.tile_position, in.tile_offset_texels);
+        }
+        "#
+        .to_string()
+    }
+}
+
+/// Shader code that was not updated after adding `frequency`
+#[derive(Clone, TypePath, Default)]
+struct Stale;
+
+impl Customization for Stale {
+    const SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x7a3c1d9e2b4f6a81);
+    type UserData = UserData;
+    type ExtraBindings = NoExtraBindings;
+
+    fn custom_shader_code() -> String {
+        r#"
+        struct UserData {
+            color: vec4<f32>,
+        };
+
+        fn sample_tile(in: ExtractIn) -> vec4<f32> {
+            return sample_tile_at(in.tile_index, in.tile_position, in.tile_offset_texels);
+        }
+        "#
+        .to_string()
+    }
+}
+
+#[test]
+fn matching_user_data_passes() {
+    assert_eq!(check_user_data_size::<NoCustomization>(), Ok(()));
+    assert_eq!(check_user_data_size::<Matching>(), Ok(()));
+}
+
+#[test]
+fn stale_user_data_is_reported() {
+    let mismatch = check_user_data_size::<Stale>().unwrap_err();
+    assert!(mismatch.customization.ends_with("Stale"));
+    assert_eq!(mismatch.rust_size, 32);
+    assert_eq!(mismatch.wgsl_size, 16);
+    assert!(mismatch.to_string().contains("32 bytes in Rust"));
+}
+
+#[test]
+#[should_panic(expected = "UserData of")]
+fn plugin_panics_on_stale_user_data() {
+    App::new().add_plugins(CustomFastTileMapPlugin::<Stale>::default());
+}
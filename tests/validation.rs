@@ -0,0 +1,149 @@
+#![cfg(feature = "validation")]
+
+use bevy::{
+    ecs::event::ManualEventReader,
+    math::{uvec2, vec2},
+    prelude::*,
+    sprite::Mesh2dHandle,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+/// Customization without a plugin
+#[derive(Clone, TypePath)]
+struct Forgotten;
+
+impl Customization for Forgotten {
+    const SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(9051782210354921873);
+    type UserData = DefaultUserData;
+    type ExtraBindings = NoExtraBindings;
+
+    fn custom_shader_code() -> String {
+        String::new()
+    }
+}
+
+fn app() -> App {
+    let mut app = common::map_app();
+    app.add_event::<MapMisuseDetected>();
+    app
+}
+
+fn misuses(app: &App) -> Vec<MapMisuseDetected> {
+    let events = app.world().resource::<Events<MapMisuseDetected>>();
+    ManualEventReader::default().read(events).cloned().collect()
+}
+
+fn add_map(app: &mut App) -> Handle<Map> {
+    let map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 16.0)).build();
+    app.world_mut().resource_mut::<Assets<Map>>().add(map)
+}
+
+#[test]
+fn custom_mesh_on_managed_map() {
+    let mut app = app();
+    app.add_systems(Update, validate_managed_meshes::<NoCustomization>);
+    let material = add_map(&mut app);
+    let mesh = app
+        .world_mut()
+        .resource_mut::<Assets<Mesh>>()
+        .add(Rectangle::new(10.0, 10.0));
+
+    let plain = app
+        .world_mut()
+        .spawn(MapBundleManaged {
+            material: material.clone(),
+            ..default()
+        })
+        .id();
+    let custom = app
+        .world_mut()
+        .spawn(MapBundleManaged {
+            material,
+            mesh: Mesh2dHandle(mesh),
+            ..default()
+        })
+        .id();
+    app.update();
+
+    assert_eq!(
+        misuses(&app),
+        vec![MapMisuseDetected {
+            entity: Some(custom),
+            misuse: MapMisuse::ManagedMeshReplaced,
+        }]
+    );
+    assert_ne!(plain, custom);
+}
+
+#[test]
+fn plugin_added_twice() {
+    let mut app = app();
+    app.add_plugins(MapValidationPlugin)
+        .add_plugins(FastTileMapPlugin::default())
+        .add_plugins(FastTileMapPlugin::default());
+    let detected = misuses(&app);
+    assert_eq!(detected.len(), 1);
+    assert!(matches!(
+        detected[0].misuse,
+        MapMisuse::PluginAddedTwice { customization } if customization.ends_with("NoCustomization")
+    ));
+}
+
+#[test]
+fn maps_without_plugin() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), MapValidationPlugin))
+        .init_asset::<Map<Forgotten>>();
+    app.update();
+    let detected = misuses(&app);
+    assert_eq!(detected.len(), 1);
+    match &detected[0].misuse {
+        MapMisuse::PluginMissing { customization } => assert!(customization.ends_with("Forgotten")),
+        other => panic!("unexpected {other:?}"),
+    }
+
+    // Reported once
+    app.update();
+    app.update();
+    assert!(misuses(&app).is_empty());
+}
+
+#[test]
+fn mix_color_per_vertex() {
+    let mut app = app();
+    app.add_systems(Update, validate_mix_colors::<NoCustomization>);
+    let material = add_map(&mut app);
+    let mesh = app
+        .world_mut()
+        .resource_mut::<Assets<Mesh>>()
+        .add(Rectangle::new(10.0, 10.0));
+
+    let spawn = |app: &mut App, n: usize| {
+        app.world_mut()
+            .spawn((
+                material.clone(),
+                Mesh2dHandle(mesh.clone()),
+                MapAttributes {
+                    mix_color: vec![Vec4::ONE; n],
+                },
+            ))
+            .id()
+    };
+    spawn(&mut app, 0);
+    spawn(&mut app, 4);
+    let short = spawn(&mut app, 3);
+    app.update();
+
+    assert_eq!(
+        misuses(&app),
+        vec![MapMisuseDetected {
+            entity: Some(short),
+            misuse: MapMisuse::MixColorLength {
+                entries: 3,
+                vertices: 4
+            },
+        }]
+    );
+}
@@ -0,0 +1,206 @@
+use bevy::{
+    math::{uvec2, vec2, vec3},
+    prelude::*,
+    render::{
+        camera::ScalingMode,
+        mesh::{MeshVertexAttribute, VertexAttributeValues},
+        render_resource::VertexFormat,
+    },
+    sprite::Mesh2dHandle,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+/// Map position vertex attribute, only used by meshes of `MapInstances`
+const ATTRIBUTE_MAP_POSITION: MeshVertexAttribute =
+    MeshVertexAttribute::new("MapPosition", 988779054, VertexFormat::Float32x2);
+
+fn app() -> App {
+    let mut app = common::loading_app();
+    app.add_systems(PostUpdate, update_viewport_fit_meshes::<NoCustomization>);
+    app
+}
+
+/// Camera seeing `size` world units around `center`
+fn spawn_camera(app: &mut App, center: Vec2, size: Vec2) -> Entity {
+    let mut projection = OrthographicProjection {
+        scaling_mode: ScalingMode::Fixed {
+            width: size.x,
+            height: size.y,
+        },
+        ..default()
+    };
+    projection.area = Rect::from_center_size(Vec2::ZERO, size);
+    app.world_mut()
+        .spawn((
+            Camera::default(),
+            projection,
+            GlobalTransform::from_translation(center.extend(0.0)),
+        ))
+        .id()
+}
+
+fn spawn_map(app: &mut App, managed: MeshManagedByMap) -> (Entity, Handle<Map>) {
+    let (atlas, tile_size) = debug_atlas(&mut app.world_mut().resource_mut::<Assets<Image>>());
+    let map = Map::builder(uvec2(1000, 1000), atlas, tile_size).build();
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    let entity = app
+        .world_mut()
+        .spawn(MapBundleManaged {
+            material: handle.clone(),
+            mesh_managed_by_map: managed,
+            ..default()
+        })
+        .id();
+    (entity, handle)
+}
+
+fn mesh(app: &App, entity: Entity) -> &Mesh {
+    let mesh = app.world().get::<Mesh2dHandle>(entity).unwrap();
+    app.world().resource::<Assets<Mesh>>().get(&mesh.0).unwrap()
+}
+
+/// Vertex positions of the mesh of `entity`
+fn vertices(app: &App, entity: Entity) -> Vec<Vec2> {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh(app, entity).attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        panic!("unexpected vertex positions");
+    };
+    positions.iter().map(|p| vec2(p[0], p[1])).collect()
+}
+
+fn bounds(vertices: &[Vec2]) -> Rect {
+    vertices
+        .iter()
+        .fold(Rect::EMPTY, |rect, p| rect.union_point(*p))
+}
+
+/// Map positions are derived from the vertex positions in the shader, not baked into the mesh
+fn assert_no_map_positions(app: &App, entity: Entity) {
+    assert!(mesh(app, entity)
+        .attribute(ATTRIBUTE_MAP_POSITION)
+        .is_none());
+}
+
+#[test]
+fn mesh_covers_only_the_visible_part() {
+    let mut app = app();
+    let camera = spawn_camera(&mut app, vec2(100.0, 50.0), vec2(200.0, 100.0));
+    let (entity, map) = spawn_map(&mut app, MeshManagedByMap::ViewportFit);
+    let (full_entity, _) = spawn_map(&mut app, MeshManagedByMap::Full);
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let visible = Rect::from_center_size(vec2(100.0, 50.0), vec2(200.0, 100.0));
+    let covered = bounds(&vertices(&app, entity));
+    assert_eq!(covered.union(visible), covered);
+    assert!(covered.size().cmplt(vec2(400.0, 200.0)).all());
+    assert_no_map_positions(&app, entity);
+
+    let world_size = app
+        .world()
+        .resource::<Assets<Map>>()
+        .get(&map)
+        .unwrap()
+        .world_size();
+    assert_eq!(bounds(&vertices(&app, full_entity)).size(), world_size);
+
+    // Small camera moves stay within the margin
+    app.world_mut()
+        .entity_mut(camera)
+        .insert(GlobalTransform::from_translation(vec3(110.0, 55.0, 0.0)));
+    app.update();
+    assert_eq!(bounds(&vertices(&app, entity)), covered);
+
+    // Larger ones rebuild the mesh
+    app.world_mut()
+        .entity_mut(camera)
+        .insert(GlobalTransform::from_translation(vec3(
+            -3000.0, 2000.0, 0.0,
+        )));
+    app.update();
+    let moved = bounds(&vertices(&app, entity));
+    assert!(moved.contains(vec2(-3000.0, 2000.0)));
+    assert!(!moved.contains(vec2(100.0, 50.0)));
+    assert_no_map_positions(&app, entity);
+}
+
+#[test]
+fn mesh_is_clamped_to_the_map() {
+    let mut app = app();
+    // Camera at the top left corner of the map, mostly outside of it
+    spawn_camera(&mut app, vec2(-8000.0, 8000.0), vec2(200.0, 100.0));
+    let (entity, map) = spawn_map(&mut app, MeshManagedByMap::ViewportFit);
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let world_size = app
+        .world()
+        .resource::<Assets<Map>>()
+        .get(&map)
+        .unwrap()
+        .world_size();
+    let covered = bounds(&vertices(&app, entity));
+    assert_eq!(covered.min.x, -world_size.x / 2.0);
+    assert_eq!(covered.max.y, world_size.y / 2.0);
+    assert!(covered.max.x > -7900.0 && covered.min.y < 7950.0);
+    assert_no_map_positions(&app, entity);
+}
+
+#[test]
+fn repeated_content_covers_views_beyond_the_map() {
+    let mut app = app();
+    // Far away from the (32 x 32 tiles) map
+    let center = vec2(100_000.0, -40_000.0);
+    spawn_camera(&mut app, center, vec2(800.0, 600.0));
+    let (atlas, tile_size) = debug_atlas(&mut app.world_mut().resource_mut::<Assets<Image>>());
+    let map = Map::builder(uvec2(32, 32), atlas, tile_size)
+        .with_repeat_content()
+        .build_and_set(|p| p.x % 4);
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    let entity = app
+        .world_mut()
+        .spawn(MapBundleManaged {
+            material: handle.clone(),
+            mesh_managed_by_map: MeshManagedByMap::ViewportFit,
+            ..default()
+        })
+        .id();
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let visible = Rect::from_center_size(center, vec2(800.0, 600.0));
+    let covered = bounds(&vertices(&app, entity));
+    assert_eq!(covered.union(visible), covered);
+    // Constant cost: the mesh only grows with the view
+    assert!(covered.size().cmplt(vec2(1600.0, 1200.0)).all());
+
+    // Picking reports the wrapped tile
+    let maps = app.world().resource::<Assets<Map>>();
+    let images = app.world().resource::<Assets<Image>>();
+    let map = maps.get(&handle).unwrap();
+    let transform = GlobalTransform::IDENTITY;
+    let map_position = map.world_to_map_with(&transform, center);
+    let tile = map.content_tile(map_position).unwrap();
+    assert!(tile.cmplt(uvec2(32, 32)).all());
+    assert_eq!(
+        tile.as_ivec2(),
+        map_position.floor().as_ivec2().rem_euclid(IVec2::splat(32))
+    );
+    let picked = pick_tile_in_layers(
+        [(entity, &handle, &transform)],
+        center,
+        maps,
+        images,
+        PickOptions {
+            empty_tile: u32::MAX,
+            ..default()
+        },
+    );
+    assert_eq!(picked, Some((entity, tile)));
+}
@@ -0,0 +1,104 @@
+use bevy::{ecs::event::ManualEventReader, math::uvec2, prelude::*};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+const BLACK: [u8; 4] = [0, 0, 0, 255];
+
+fn app() -> (App, Entity) {
+    let mut app = common::map_app();
+    app.add_plugins(MapVisibilityPlugin);
+    let size = uvec2(4, 4);
+    let minimap = {
+        let mut images = app.world_mut().resource_mut::<Assets<Image>>();
+        VisibilityMinimap::new(&mut images, size)
+    };
+    let entity = app
+        .world_mut()
+        .spawn((MapVisibility::new(size), minimap))
+        .id();
+    app.update();
+    (app, entity)
+}
+
+fn set(app: &mut App, entity: Entity, tile: UVec2, visibility: TileVisibility) {
+    let mut map = app.world_mut().get_mut::<MapVisibility>(entity).unwrap();
+    map.set(tile, visibility);
+}
+
+fn minimap_image(app: &mut App, entity: Entity) -> Mut<'_, Image> {
+    let handle = app
+        .world()
+        .get::<VisibilityMinimap>(entity)
+        .unwrap()
+        .image
+        .clone();
+    let images = app.world_mut().resource_mut::<Assets<Image>>();
+    images.map_unchanged(|images| images.get_mut(&handle).unwrap())
+}
+
+/// Color of the first pixel of each row
+fn row_colors(app: &mut App, entity: Entity) -> Vec<[u8; 4]> {
+    let image = minimap_image(app, entity);
+    let row_bytes = 4 * image.width() as usize;
+    image
+        .data
+        .chunks_exact(row_bytes)
+        .map(|row| row[..4].try_into().unwrap())
+        .collect()
+}
+
+fn changes(
+    app: &App,
+    reader: &mut ManualEventReader<MapVisibilityChanged>,
+) -> Vec<(Entity, u32, u32)> {
+    let events = app.world().resource::<Events<MapVisibilityChanged>>();
+    reader
+        .read(events)
+        .map(|ev| (ev.map, ev.rows.start, ev.rows.end))
+        .collect()
+}
+
+#[test]
+fn only_changed_rows_are_redrawn() {
+    let (mut app, entity) = app();
+    assert_eq!(row_colors(&mut app, entity), vec![BLACK; 4]);
+
+    // Paint over all rows, only the changed one is drawn again
+    minimap_image(&mut app, entity)
+        .data
+        .chunks_exact_mut(4)
+        .for_each(|pixel| pixel.copy_from_slice(&RED));
+    set(&mut app, entity, uvec2(0, 2), TileVisibility::Visible);
+    app.update();
+    assert_eq!(row_colors(&mut app, entity), vec![RED, RED, WHITE, RED]);
+
+    // The rest of the changed row is drawn as well
+    let image = minimap_image(&mut app, entity);
+    assert_eq!(&image.data[(2 * 16 + 4)..(2 * 16 + 8)], &BLACK);
+}
+
+#[test]
+fn changes_are_sent_once() {
+    let (mut app, entity) = app();
+    let mut reader = ManualEventReader::default();
+    changes(&app, &mut reader);
+
+    set(&mut app, entity, uvec2(1, 1), TileVisibility::Seen);
+    set(&mut app, entity, uvec2(2, 3), TileVisibility::Visible);
+    app.update();
+    assert_eq!(changes(&app, &mut reader), vec![(entity, 1, 4)]);
+
+    app.update();
+    assert_eq!(changes(&app, &mut reader), vec![]);
+
+    // Setting the current visibility changes nothing
+    set(&mut app, entity, uvec2(1, 1), TileVisibility::Seen);
+    app.update();
+    assert_eq!(changes(&app, &mut reader), vec![]);
+
+    let visibility = app.world().get::<MapVisibility>(entity).unwrap();
+    assert_eq!(visibility.bitmask(), &[(1 << 5) | (1 << 14)]);
+}
@@ -0,0 +1,76 @@
+use bevy::{
+    ecs::event::ManualEventReader,
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{mesh::GpuMesh, render_asset::RenderAssets, RenderApp},
+    sprite::{Mesh2dHandle, PreparedMaterial2d},
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+#[test]
+#[ignore = "needs a GPU"]
+fn warmup_completes_once_for_hidden_maps() {
+    let mut app = common::render_app();
+
+    // The atlas only becomes available later
+    let atlas = app.world().resource::<Assets<Image>>().reserve_handle();
+    let map = Map::builder(uvec2(4, 4), atlas.clone(), vec2(16.0, 16.0)).build();
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    let entity = app
+        .world_mut()
+        .spawn(MapBundleManaged {
+            material: handle.clone(),
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .id();
+    app.insert_resource(MapWarmup::new(vec![handle.clone()]));
+
+    let mut reader = ManualEventReader::<MapWarmupComplete>::default();
+    let mut completed = |app: &App| {
+        let events = app.world().resource::<Events<MapWarmupComplete>>();
+        reader
+            .read(events)
+            .map(|ev| ev.maps.clone())
+            .collect::<Vec<_>>()
+    };
+
+    for _ in 0..5 {
+        app.update();
+        assert!(completed(&app).is_empty());
+    }
+
+    let loaded = common::solid_atlas(&mut app);
+    {
+        let mut images = app.world_mut().resource_mut::<Assets<Image>>();
+        let image = images.remove(&loaded).unwrap();
+        images.insert(&atlas, image);
+    }
+
+    let mut frames = Vec::new();
+    for frame in 0..20 {
+        app.update();
+        for maps in completed(&app) {
+            assert_eq!(maps, vec![handle.id()]);
+            frames.push(frame);
+
+            // Everything the map is rendered with is prepared
+            let mesh = app.world().get::<Mesh2dHandle>(entity).unwrap().0.id();
+            let render_world = app.sub_app(RenderApp).world();
+            let materials = render_world.resource::<RenderAssets<PreparedMaterial2d<Map>>>();
+            assert!(materials.get(handle.id()).is_some());
+            assert!(render_world
+                .resource::<RenderAssets<GpuMesh>>()
+                .get(mesh)
+                .is_some());
+        }
+    }
+    assert_eq!(frames.len(), 1);
+    assert!(app.world().resource::<MapWarmup>().is_complete());
+    assert_eq!(
+        app.world().get::<Visibility>(entity),
+        Some(&Visibility::Hidden)
+    );
+}
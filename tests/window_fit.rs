@@ -0,0 +1,87 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    window::PrimaryWindow,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+/// Bounding box of the map tiles in local coordinates
+fn local_bounds(map: &Map) -> Rect {
+    let size = map.map_size().as_vec2();
+    let corners = [Vec2::ZERO, vec2(size.x, 0.0), vec2(0.0, size.y), size];
+    corners
+        .iter()
+        .map(|c| Rect::from_center_size(map.map_to_local(*c), Vec2::ZERO))
+        .reduce(|a, b| a.union(b))
+        .unwrap()
+}
+
+#[test]
+fn map_spans_viewport() {
+    let map: Map = Map::builder(uvec2(20, 12), Handle::default(), vec2(16.0, 16.0))
+        .fit_to_viewport(vec2(800.0, 480.0), uvec2(20, 12))
+        .build();
+    assert_eq!(map.world_tile_size(), vec2(40.0, 40.0));
+    assert_eq!(local_bounds(&map), Rect::new(-400.0, -240.0, 400.0, 240.0));
+    // The atlas is still addressed in pixels
+    assert_eq!(map.tile_size(), vec2(16.0, 16.0));
+}
+
+#[test]
+fn projected_map_spans_viewport() {
+    // The projection is applied after fitting, fitting still takes it into account
+    let map: Map = Map::builder(uvec2(20, 12), Handle::default(), vec2(32.0, 16.0))
+        .fit_to_viewport(vec2(800.0, 480.0), uvec2(20, 12))
+        .with_projection(AXONOMETRIC)
+        .build();
+    assert_eq!(map.world_tile_size(), vec2(50.0, 30.0));
+    let bounds = local_bounds(&map);
+    assert!((bounds.size() - vec2(800.0, 480.0)).abs().max_element() < 1e-3);
+}
+
+#[test]
+fn map_follows_window_resizes() {
+    let mut app = common::map_app();
+    app.add_systems(Update, fit_maps_to_window::<NoCustomization>);
+
+    let window = app
+        .world_mut()
+        .spawn((
+            Window {
+                resolution: (800.0, 480.0).into(),
+                ..default()
+            },
+            PrimaryWindow,
+        ))
+        .id();
+    let map = Map::builder(uvec2(20, 12), Handle::default(), vec2(16.0, 16.0)).build();
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    app.world_mut().spawn((
+        handle.clone(),
+        FitMapToWindow {
+            tiles: uvec2(20, 12),
+        },
+    ));
+
+    let tile_size = |app: &App| {
+        let maps = app.world().resource::<Assets<Map>>();
+        maps.get(&handle).unwrap().world_tile_size()
+    };
+    app.update();
+    assert_eq!(tile_size(&app), vec2(40.0, 40.0));
+
+    app.world_mut()
+        .get_mut::<Window>(window)
+        .unwrap()
+        .resolution
+        .set(1000.0, 600.0);
+    app.update();
+    assert_eq!(tile_size(&app), vec2(50.0, 50.0));
+    let maps = app.world().resource::<Assets<Map>>();
+    assert_eq!(
+        local_bounds(maps.get(&handle).unwrap()),
+        Rect::new(-500.0, -300.0, 500.0, 300.0)
+    );
+}
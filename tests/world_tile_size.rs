@@ -0,0 +1,121 @@
+use bevy::{
+    math::{uvec2, vec2},
+    prelude::*,
+    render::{
+        mesh::VertexAttributeValues,
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    sprite::Mesh2dHandle,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+/// Atlas of two 64x64 pixel tiles
+fn atlas(images: &mut Assets<Image>) -> Handle<Image> {
+    images.add(Image::new_fill(
+        Extent3d {
+            width: 128,
+            height: 64,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[255; 4],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    ))
+}
+
+fn app() -> App {
+    let mut app = common::map_app();
+    app.add_systems(Update, update_loading_maps::<NoCustomization>);
+    app
+}
+
+/// 64px art at one world unit per tile
+fn spawn_map(app: &mut App) -> (Entity, Handle<Map>) {
+    let atlas = atlas(&mut app.world_mut().resource_mut::<Assets<Image>>());
+    let map = Map::builder(uvec2(10, 6), atlas, vec2(64.0, 64.0))
+        .with_world_tile_size(vec2(1.0, 1.0))
+        .build_and_set(|p| p.x % 2);
+    let handle = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    let entity = app
+        .world_mut()
+        .spawn(MapBundleManaged {
+            material: handle.clone(),
+            transform: Transform::from_xyz(100.0, 50.0, 0.0),
+            global_transform: GlobalTransform::from_xyz(100.0, 50.0, 0.0),
+            ..default()
+        })
+        .id();
+    app.update();
+    app.update();
+    (entity, handle)
+}
+
+#[test]
+fn world_tile_size_defaults_to_atlas_tile_size() {
+    let map: Map = Map::builder(uvec2(4, 4), default(), vec2(16.0, 8.0)).build();
+    assert_eq!(map.world_tile_size(), vec2(16.0, 8.0));
+    assert_eq!(map.world_size(), vec2(4.0 * 16.0, 4.0 * 8.0));
+}
+
+#[test]
+fn conversions_use_world_tile_size() {
+    let mut app = app();
+    let (entity, handle) = spawn_map(&mut app);
+    let map = app.world().resource::<Assets<Map>>().get(&handle).unwrap();
+    let transform = *app.world().get::<GlobalTransform>(entity).unwrap();
+
+    assert_eq!(map.tile_size(), vec2(64.0, 64.0));
+    // Flat maps without overhangs get no padding
+    assert_eq!(map.world_size(), vec2(10.0, 6.0));
+    assert_eq!(map.map_to_local(Vec2::ZERO), vec2(-5.0, 3.0));
+    assert_eq!(map.map_to_local(vec2(10.0, 6.0)), vec2(5.0, -3.0));
+
+    let world = vec2(100.0 - 5.0 + 2.5, 50.0 + 3.0 - 1.5);
+    assert!((map.world_to_map_with(&transform, world) - vec2(2.5, 1.5)).length() < 1e-4);
+    let back = map.map_to_world_3d_with(&transform, vec2(2.5, 1.5).extend(0.0));
+    assert!((back.truncate() - world).length() < 1e-4);
+}
+
+#[test]
+fn picking_uses_world_tile_size() {
+    let mut app = app();
+    let (entity, handle) = spawn_map(&mut app);
+    let transform = *app.world().get::<GlobalTransform>(entity).unwrap();
+    let maps = app.world().resource::<Assets<Map>>();
+    let images = app.world().resource::<Assets<Image>>();
+
+    // Tile (3, 2) has value 1 and (opaque) pixels
+    let world = vec2(100.0 - 5.0 + 3.5, 50.0 + 3.0 - 2.5);
+    let picked = pick_tile_in_layers(
+        [(entity, &handle, &transform)],
+        world,
+        maps,
+        images,
+        PickOptions {
+            alpha_threshold: Some(0.5),
+            ..default()
+        },
+    );
+    assert_eq!(picked, Some((entity, uvec2(3, 2))));
+}
+
+#[test]
+fn managed_mesh_has_world_scale() {
+    let mut app = app();
+    let (entity, _) = spawn_map(&mut app);
+    let mesh = app.world().get::<Mesh2dHandle>(entity).unwrap();
+    let mesh = app.world().resource::<Assets<Mesh>>().get(&mesh.0).unwrap();
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        panic!("unexpected vertex positions");
+    };
+    let bounds = positions
+        .iter()
+        .fold(Rect::EMPTY, |rect, p| rect.union_point(vec2(p[0], p[1])));
+    assert_eq!(bounds.size(), vec2(10.0, 6.0));
+}
@@ -0,0 +1,392 @@
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+use bevy::{
+    math::{uvec2, vec2, vec3},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    sprite::Mesh2dHandle,
+    transform::TransformSystem,
+};
+use bevy_fast_tilemap::prelude::*;
+
+mod common;
+
+fn map() -> Map {
+    Map::builder(uvec2(20, 10), default(), vec2(16., 16.)).build()
+}
+
+fn assert_near(a: Vec2, b: Vec2) {
+    assert!((a - b).length() < 1e-3, "{a} != {b}");
+}
+
+const ANGLES: [f32; 6] = [FRAC_PI_2, PI, 3.0 * FRAC_PI_2, -FRAC_PI_2, 0.3, 2.0];
+
+#[test]
+fn z_rotations_round_trip() {
+    for angle in ANGLES {
+        let transform = GlobalTransform::from(
+            Transform::from_translation(vec3(100.0, -40.0, 3.0))
+                .with_rotation(Quat::from_rotation_z(angle))
+                .with_scale(vec3(2.0, 0.5, 1.0)),
+        );
+        let mut map = map();
+        map.apply_transform(&transform);
+
+        for map_position in [vec2(0.0, 0.0), vec2(3.25, 7.5), vec2(19.0, 1.0)] {
+            let world = map.map_to_world_3d(map_position.extend(0.0));
+            assert_near(map.world_to_map(world.xy()), map_position);
+            assert_near(map.world_to_map_with(&transform, world.xy()), map_position);
+            assert_near(
+                map.map_to_world_3d_with(&transform, map_position.extend(0.0))
+                    .xy(),
+                world.xy(),
+            );
+        }
+    }
+}
+
+#[test]
+fn far_from_origin_round_trips() {
+    for translation in [
+        vec3(1e6, 1e6, 0.0),
+        vec3(-1e6, -1e6, 0.0),
+        vec3(1e6, -1e6, 0.0),
+        vec3(1_048_576.0, 524_288.0, 0.0),
+    ] {
+        for projection in [IDENTITY, AXONOMETRIC] {
+            let transform = GlobalTransform::from(
+                Transform::from_translation(translation).with_rotation(Quat::from_rotation_z(0.3)),
+            );
+            let mut map = Map::builder(uvec2(20, 10), default(), vec2(16., 16.))
+                .with_projection(projection)
+                .build();
+            map.apply_transform(&transform);
+
+            for map_position in [vec2(0.0, 0.0), vec2(3.25, 7.5), vec2(19.5, 9.5)] {
+                let world = map.map_to_world_3d(map_position.extend(0.0)).xy();
+                for back in [
+                    map.world_to_map(world),
+                    map.world_to_map_with(&transform, world),
+                ] {
+                    let error = (back - map_position).abs().max_element();
+                    assert!(error < 0.01, "{translation} {map_position}: {back}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn clip_rect_follows_transform() {
+    let clip = Rect::new(2.0, 3.0, 6.0, 7.0);
+    for projection in [IDENTITY, AXONOMETRIC] {
+        let mut map = Map::builder(uvec2(20, 10), default(), vec2(16., 16.))
+            .with_projection(projection)
+            .build();
+        map.set_clip_rect(Some(clip));
+
+        for transform in [
+            Transform::from_translation(vec3(100.0, -40.0, 0.0)),
+            Transform::from_translation(vec3(-7.0, 12.0, 0.0))
+                .with_rotation(Quat::from_rotation_z(2.0))
+                .with_scale(vec3(1.5, 0.5, 1.0)),
+        ] {
+            map.apply_transform(&GlobalTransform::from(transform));
+            for y in 0..10 {
+                for x in 0..20 {
+                    let center = vec2(x as f32, y as f32) + 0.5;
+                    let world = map.map_to_world_3d(center.extend(0.0)).xy();
+                    let expected = clip.contains(center).then_some(uvec2(x, y));
+                    assert_eq!(map.world_to_tile_clipped(world), expected, "{center}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn projections_round_trip_in_3d() {
+    let transforms = [
+        Transform::IDENTITY,
+        Transform::from_translation(vec3(100.0, -40.0, 3.0)),
+        Transform::from_rotation(Quat::from_rotation_z(FRAC_PI_2)),
+        Transform::from_rotation(Quat::from_rotation_z(FRAC_PI_4)),
+        Transform::from_scale(vec3(3.0, 3.0, 1.0)),
+        Transform::from_scale(vec3(0.25, 2.0, 1.0)),
+        Transform::from_translation(vec3(-7.0, 12.0, 5.0))
+            .with_rotation(Quat::from_rotation_z(FRAC_PI_4))
+            .with_scale(vec3(1.5, 0.5, 1.0)),
+        // Tilted towards the camera, eg. a map in a 3D scene
+        Transform::from_rotation(Quat::from_rotation_x(-FRAC_PI_4)),
+    ];
+    for projection in [IDENTITY, AXONOMETRIC] {
+        for transform in transforms {
+            let transform = GlobalTransform::from(transform);
+            let mut map = Map::builder(uvec2(20, 10), default(), vec2(16., 16.))
+                .with_projection(projection)
+                .build();
+            map.apply_transform(&transform);
+
+            for map_position in [vec2(0.0, 0.0), vec2(3.25, 7.5), vec2(19.0, 1.0)] {
+                let world = map.map_to_world_3d(map_position.extend(0.0));
+                let expected =
+                    transform.transform_point(map.map_to_local_3d(map_position.extend(0.0)));
+                assert!((world - expected).length() < 1e-3, "{world} != {expected}");
+                assert_near(map.world_to_map_3d(world).xy(), map_position);
+                assert_near(
+                    map.world_to_map_3d_with(&transform, world).xy(),
+                    map_position,
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn quarter_rotation_swaps_axes() {
+    let transform =
+        GlobalTransform::from(Transform::from_rotation(Quat::from_rotation_z(FRAC_PI_2)));
+    let map = map();
+    let center = map.world_to_map_with(&transform, Vec2::ZERO);
+    // Rotated counterclockwise, world +y is the unrotated +x direction (map x)
+    assert_near(
+        map.world_to_map_with(&transform, vec2(0.0, 16.0)),
+        center + vec2(1.0, 0.0),
+    );
+}
+
+#[test]
+fn flat_z_scale_is_ignored() {
+    // Eg. from a parent scaled to zero in z, the map plane itself is fine
+    let transform = GlobalTransform::from(
+        Transform::from_rotation(Quat::from_rotation_z(FRAC_PI_2)).with_scale(vec3(1.0, 1.0, 0.0)),
+    );
+    let mut map = map();
+    map.apply_transform(&transform);
+    let world = map.map_to_world_3d(vec3(2.5, 4.5, 0.0));
+    assert_near(map.world_to_map(world.xy()), vec2(2.5, 4.5));
+    assert_near(
+        map.world_to_map_with(&transform, world.xy()),
+        vec2(2.5, 4.5),
+    );
+}
+
+#[test]
+fn edge_on_maps_have_no_map_position() {
+    // Rotated around x by 90 degrees, the map is a line in the xy plane
+    let transform =
+        GlobalTransform::from(Transform::from_rotation(Quat::from_rotation_x(FRAC_PI_2)));
+    let mut map = map();
+    map.apply_transform(&transform);
+    assert!(map.world_to_map(vec2(1.0, 1.0)).is_nan());
+    assert!(map.world_to_map_with(&transform, vec2(1.0, 1.0)).is_nan());
+}
+
+#[test]
+fn mirrored_maps_round_trip() {
+    for scale in [
+        vec3(-1.0, 1.0, 1.0),
+        vec3(1.0, -2.0, 1.0),
+        vec3(-0.5, -0.5, 1.0),
+    ] {
+        let transform = GlobalTransform::from(
+            Transform::from_translation(vec3(-30.0, 12.0, 0.0)).with_scale(scale),
+        );
+        let mut map = map();
+        map.apply_transform(&transform);
+        for map_position in [vec2(0.0, 0.0), vec2(3.25, 7.5), vec2(19.0, 1.0)] {
+            let world = map.map_to_world_3d(map_position.extend(0.0));
+            assert_near(map.world_to_map(world.xy()), map_position);
+            assert_near(map.world_to_map_with(&transform, world.xy()), map_position);
+        }
+    }
+}
+
+#[test]
+fn horizontal_mirror_flips_map_x() {
+    let transform = GlobalTransform::from(Transform::from_scale(vec3(-1.0, 1.0, 1.0)));
+    let map = map();
+    let center = map.world_to_map_with(&transform, Vec2::ZERO);
+    // World +x is map -x
+    assert_near(
+        map.world_to_map_with(&transform, vec2(16.0, 0.0)),
+        center - vec2(1.0, 0.0),
+    );
+}
+
+#[test]
+fn zero_scale_is_not_applied() {
+    let transform = GlobalTransform::from(Transform::from_translation(vec3(50.0, 0.0, 0.0)));
+    let mut map = map();
+    map.apply_transform(&transform);
+    let flat = GlobalTransform::from(Transform::from_scale(vec3(0.0, 1.0, 1.0)));
+    map.apply_transform(&flat);
+
+    // The previous transform is kept instead of producing NaN
+    let world = map.map_to_world_3d(vec3(2.5, 4.5, 0.0));
+    assert_eq!(
+        world.x,
+        map.map_to_world_3d_with(&transform, vec3(2.5, 4.5, 0.0)).x
+    );
+    assert_near(map.world_to_map(world.xy()), vec2(2.5, 4.5));
+}
+
+#[test]
+fn tile_centers_and_bounds() {
+    let transform = GlobalTransform::from(
+        Transform::from_translation(vec3(100.0, -40.0, 0.0)).with_scale(vec3(2.0, 2.0, 1.0)),
+    );
+    let mut map = map();
+    map.apply_transform(&transform);
+
+    let tile = uvec2(3, 7);
+    assert_eq!(map.world_to_tile(map.tile_center_world(tile)), Some(tile));
+
+    let bounds = map.world_bounds();
+    assert_near(bounds.size(), vec2(20.0 * 32.0, 10.0 * 32.0));
+    assert_eq!(map.world_to_tile(bounds.min - Vec2::ONE), None);
+
+    // Views smaller than the map stay within it, larger ones are centered
+    let view = vec2(100.0, 50.0);
+    assert_near(
+        map.clamp_view_center(bounds.max + vec2(500.0, 0.0), view),
+        bounds.max - view / 2.0,
+    );
+    assert_near(
+        map.clamp_view_center(bounds.min, vec2(2000.0, 50.0)),
+        vec2(bounds.center().x, bounds.min.y + 25.0),
+    );
+}
+
+/// Conversions of the map of `entity` against the rendered transform of the entity, which
+/// maps local (mesh) positions into the world
+fn assert_matches_rendered(app: &App, entity: Entity, projection: &str) {
+    let world = app.world();
+    let handle = world.get::<Handle<Map>>(entity).unwrap();
+    let global = world.get::<GlobalTransform>(entity).unwrap();
+    let map = world.resource::<Assets<Map>>().get(handle).unwrap();
+    for map_position in [vec2(0.0, 0.0), vec2(3.25, 7.5), vec2(19.0, 1.0)] {
+        let rendered = global.transform_point(map.map_to_local_3d(map_position.extend(0.0)));
+        let world = map.map_to_world_3d(map_position.extend(0.0));
+        assert!(
+            (world - rendered).length() < 1e-2,
+            "{world} != {rendered} ({projection})"
+        );
+        assert_near(map.world_to_map(rendered.xy()), map_position);
+    }
+}
+
+#[test]
+fn scaled_managed_mesh_matches_conversions() {
+    let mut app = common::map_app();
+    app.add_plugins((TransformPlugin, HierarchyPlugin))
+        .add_systems(Update, update_loading_maps::<NoCustomization>)
+        .add_systems(
+            PostUpdate,
+            sync_map_transforms::<NoCustomization>.after(TransformSystem::TransformPropagate),
+        );
+
+    // Single 16x16 tile
+    let atlas = Image::new_fill(
+        Extent3d {
+            width: 16,
+            height: 16,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[255; 4],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::all(),
+    );
+    let atlas = app.world_mut().resource_mut::<Assets<Image>>().add(atlas);
+    let map = Map::builder(uvec2(20, 10), atlas, vec2(16., 16.)).build();
+    let material = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+    let entity = app
+        .world_mut()
+        .spawn(MapBundleManaged {
+            material,
+            transform: Transform::from_translation(vec3(40.0, -8.0, 0.0))
+                .with_rotation(Quat::from_rotation_z(0.4))
+                .with_scale(vec3(2.0, 2.0, 1.0)),
+            ..default()
+        })
+        .id();
+    app.update();
+    app.update();
+    assert_matches_rendered(&app, entity, "scaled");
+
+    // The managed mesh is in local units and scaled by the entity, so it covers the bounds
+    let world = app.world();
+    let global = world.get::<GlobalTransform>(entity).unwrap();
+    let mesh = world.get::<Mesh2dHandle>(entity).unwrap();
+    let meshes = world.resource::<Assets<Mesh>>();
+    let aabb = meshes.get(&mesh.0).unwrap().compute_aabb().unwrap();
+    let handle = world.get::<Handle<Map>>(entity).unwrap();
+    let map = world.resource::<Assets<Map>>().get(handle).unwrap();
+    let (min, max) = (aabb.min().xy(), aabb.max().xy());
+    assert_near(max - min, map.world_size());
+    let mesh_bounds = [min, vec2(max.x, min.y), vec2(min.x, max.y), max]
+        .map(|corner| global.transform_point(corner.extend(0.0)).xy())
+        .into_iter()
+        .fold(Rect::EMPTY, |rect, corner| rect.union_point(corner));
+    let bounds = map.world_bounds();
+    assert!(mesh_bounds.contains(bounds.min) && mesh_bounds.contains(bounds.max));
+}
+
+#[test]
+fn runtime_transform_changes_round_trip() {
+    let mut app = common::map_app();
+    app.add_plugins((TransformPlugin, HierarchyPlugin))
+        .add_systems(
+            PostUpdate,
+            sync_map_transforms::<NoCustomization>.after(TransformSystem::TransformPropagate),
+        );
+
+    for (projection, name) in [(IDENTITY, "identity"), (AXONOMETRIC, "axonometric")] {
+        let map = Map::builder(uvec2(20, 10), default(), vec2(16., 16.))
+            .with_projection(projection)
+            .build();
+        let material = app.world_mut().resource_mut::<Assets<Map>>().add(map);
+        let entity = app
+            .world_mut()
+            .spawn(MapBundleManaged {
+                material,
+                ..default()
+            })
+            .id();
+        app.update();
+        assert_matches_rendered(&app, entity, name);
+
+        for transform in [
+            Transform::from_translation(vec3(100.0, -40.0, 3.0)),
+            Transform::from_rotation(Quat::from_rotation_z(0.3)),
+            Transform::from_scale(vec3(2.0, 0.5, 1.0)),
+            Transform::from_translation(vec3(-7.0, 12.0, 0.0))
+                .with_rotation(Quat::from_rotation_z(-1.1))
+                .with_scale(vec3(1.5, 3.0, 1.0)),
+        ] {
+            *app.world_mut().get_mut::<Transform>(entity).unwrap() = transform;
+            app.update();
+            assert_matches_rendered(&app, entity, name);
+        }
+
+        // Moving a (rotated) parent moves the map
+        let parent = app
+            .world_mut()
+            .spawn(SpatialBundle::from_transform(
+                Transform::from_translation(vec3(30.0, 0.0, 0.0))
+                    .with_rotation(Quat::from_rotation_z(0.7)),
+            ))
+            .id();
+        app.world_mut().entity_mut(entity).set_parent(parent);
+        app.update();
+        assert_matches_rendered(&app, entity, name);
+
+        app.world_mut().get_mut::<Transform>(parent).unwrap().scale = vec3(0.5, 2.0, 1.0);
+        app.update();
+        assert_matches_rendered(&app, entity, name);
+    }
+}